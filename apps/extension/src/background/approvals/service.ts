@@ -141,9 +141,11 @@ export class ApprovalsService {
     const { tx } = this.sdkService.getSdk();
 
     try {
-      const signedTxs = pendingTx.txs.map(({ bytes }, i) => {
-        return tx.appendSignature(bytes, responseSign[i]);
-      });
+      const signedTxs = await Promise.all(
+        pendingTx.txs.map(({ bytes }, i) => {
+          return tx.appendSignature(bytes, responseSign[i]);
+        })
+      );
       resolvers.resolve(signedTxs);
     } catch (e) {
       resolvers.reject(e);
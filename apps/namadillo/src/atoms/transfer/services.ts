@@ -41,11 +41,18 @@ export const createShieldedTransferTx = async (
   memo?: string
 ): Promise<TransactionPair<ShieldedTransferProps> | undefined> => {
   const { tx } = await getSdkInstance();
+  // Transfers of every token and target kind are built in a single MASP
+  // transaction, with one set of proofs and one fee, instead of one tx each
+  const transfer = new ShieldedTransferMsgValue({
+    data: props.flatMap(({ data }) => data),
+    gasSpendingKey: props.find(({ gasSpendingKey }) => gasSpendingKey)
+      ?.gasSpendingKey,
+  });
   const transactionPairs = await buildTxPair(
     account,
     gasConfig,
     chain,
-    props,
+    [transfer],
     tx.buildShieldedTransfer,
     props[0]?.data[0]?.source,
     memo
@@ -0,0 +1,137 @@
+// Benchmarks the wasm build end to end: building, MASP proving and signing.
+//
+// Native hot paths are benchmarked with criterion, see packages/shared/lib/benches. Building
+// queries the chain, so this needs a node and a funded implicit account, e.g. on a local
+// devnet. Requires the Node.js build of the SDK, `yarn build:node`:
+//
+//   NAMADA_LEDGER_ADDRESS=http://127.0.0.1:26657 NAMADA_CHAIN_ID=... \
+//   NAMADA_NATIVE_TOKEN=tnam1... NAMADA_SECRET_KEY=... NAMADA_PUBLIC_KEY=tpknam1... \
+//   NAMADA_SOURCE=tnam1... NAMADA_VALIDATOR=tnam1... \
+//   NAMADA_PAYMENT_ADDRESS=znam1... MASP_PARAMS_DIR=~/.masp-params \
+//   node ./scripts/bench.cjs --out bench-results.json
+//
+// MASP proving is skipped without NAMADA_PAYMENT_ADDRESS and MASP_PARAMS_DIR.
+const { parseArgs } = require("node:util");
+const { performance } = require("node:perf_hooks");
+const fs = require("node:fs");
+const os = require("node:os");
+const BigNumber = require("bignumber.js");
+
+const { getSdk } = require("../dist/node/sdk/src/indexNode.js");
+const init = require("../dist/node/sdk/src/initNode.js").default;
+
+const { out, iterations } = parseArgs({
+  args: process.argv.slice(2),
+  options: {
+    out: { type: "string", short: "o" },
+    iterations: { type: "string", short: "i", default: "10" },
+  },
+}).values;
+
+const env = (name) => {
+  const value = process.env[name];
+  if (!value) {
+    throw new Error(`${name} is not set`);
+  }
+  return value;
+};
+
+const url = env("NAMADA_LEDGER_ADDRESS");
+const chainId = env("NAMADA_CHAIN_ID");
+const nativeToken = env("NAMADA_NATIVE_TOKEN");
+const secretKey = env("NAMADA_SECRET_KEY");
+const publicKey = env("NAMADA_PUBLIC_KEY");
+const source = env("NAMADA_SOURCE");
+const validator = env("NAMADA_VALIDATOR");
+const paymentAddress = process.env.NAMADA_PAYMENT_ADDRESS;
+const maspParamsDir = process.env.MASP_PARAMS_DIR;
+
+const wrapperTxProps = {
+  token: nativeToken,
+  feeAmount: BigNumber(0.000001),
+  gasLimit: BigNumber(100_000),
+  chainId,
+  publicKey,
+};
+
+// Runs fn `iterations` times after a warm up run, returns timings in milliseconds
+const bench = async (name, fn) => {
+  await fn();
+  const timings = [];
+  for (let i = 0; i < Number(iterations); i++) {
+    const start = performance.now();
+    await fn();
+    timings.push(performance.now() - start);
+  }
+  timings.sort((a, b) => a - b);
+  const result = {
+    name,
+    iterations: timings.length,
+    medianMs: timings[Math.floor(timings.length / 2)],
+    minMs: timings[0],
+    maxMs: timings[timings.length - 1],
+  };
+  console.log(
+    `${name}: median ${result.medianMs.toFixed(2)} ms ` +
+      `(min ${result.minMs.toFixed(2)}, max ${result.maxMs.toFixed(2)})`
+  );
+  return result;
+};
+
+const main = async () => {
+  const { cryptoMemory } = init();
+  const storagePath = fs.mkdtempSync(`${os.tmpdir()}/namada-bench-`);
+  const sdk = getSdk(cryptoMemory, url, storagePath, nativeToken);
+  const results = [];
+
+  const bondProps = { source, validator, amount: BigNumber(1) };
+  results.push(
+    await bench("build_bond", () =>
+      sdk.tx.buildBond(wrapperTxProps, bondProps)
+    )
+  );
+
+  const transferProps = {
+    data: [
+      { source, target: validator, token: nativeToken, amount: BigNumber(1) },
+    ],
+  };
+  results.push(
+    await bench("build_transparent_transfer", () =>
+      sdk.tx.buildTransparentTransfer(wrapperTxProps, transferProps)
+    )
+  );
+
+  const bond = await sdk.tx.buildBond(wrapperTxProps, bondProps);
+  results.push(
+    await bench("sign_tx", () => sdk.signing.sign(bond, secretKey, chainId))
+  );
+
+  if (paymentAddress && maspParamsDir) {
+    await sdk.masp.loadMaspParams(maspParamsDir);
+    const shieldingProps = {
+      target: paymentAddress,
+      data: [{ source, token: nativeToken, amount: BigNumber(1) }],
+    };
+    results.push(
+      await bench("build_shielding_transfer", () =>
+        sdk.tx.buildShieldingTransfer(wrapperTxProps, shieldingProps)
+      )
+    );
+  }
+
+  const report = {
+    sdkVersion: require("../package.json").version,
+    node: process.version,
+    date: new Date().toISOString(),
+    results,
+  };
+  if (out) {
+    fs.writeFileSync(out, JSON.stringify(report, null, 2) + "\n");
+  }
+};
+
+main().catch((e) => {
+  console.error(e);
+  process.exit(1);
+});
@@ -20,25 +20,74 @@ export type {
 } from "./crypto";
 export type { Address, ShieldedKeys, TransparentKeys } from "./keys";
 export type {
+  AbciProofOp,
+  AbciQueryResponse,
   Balance,
+  BlockInfo,
+  BlockTx,
   Bonds,
+  BridgePoolTransfer,
+  BridgePoolTransferStatus,
   DelegationTotals,
   DelegatorsVotes,
+  EventFilter,
+  LoggedEvent,
+  MaspConversion,
+  MaspRewardRate,
+  PageCallback,
+  ProposalInfo,
   Rpc,
   StakingPositions,
   StakingTotals,
+  StorageValueType,
   Unbonds,
+  VoterRole,
+  WrappedAsset,
 } from "./rpc";
 
-export { TxType, TxTypeLabel } from "./tx";
-export type { SupportedTx } from "./tx";
+export { TxType, TxTypeLabel, parseMaspBuildError } from "./tx";
+export type {
+  AmountCheck,
+  AmountLimits,
+  AssetShortfall,
+  CompoundDecision,
+  CompoundParams,
+  FlaggedAmount,
+  IbcTimeouts,
+  IntentMismatch,
+  MaspBuildError,
+  RebalancePlan,
+  RequiredSignatures,
+  SigningRequest,
+  StakeChange,
+  SupportedTx,
+  TransferIntent,
+} from "./tx";
 
-export { ProgressBarNames, Sdk, SdkEvents } from "./sdk";
+export { ProgressBarNames, ReadOnlySdk, Sdk, SdkEvents } from "./sdk";
+export type { ChainPrefixes, CrashReport, ReadOnlyTx, TxCode } from "./sdk";
 
 export { publicKeyToBech32 } from "./keys";
 
 export type { Masp } from "./masp";
 export { PhraseSize } from "./mnemonic";
 export type { Mnemonic } from "./mnemonic";
-export type { Signing } from "./signing";
+export type {
+  AuditEntry,
+  Counterparty,
+  ReplacementTx,
+  Signing,
+} from "./signing";
 export type { Tx } from "./tx";
+
+export {
+  REQUIRED_API_VERSION,
+  checkApiCompatibility,
+  describeMessages,
+} from "./version";
+export type {
+  ApiCompatibility,
+  Capabilities,
+  MsgSchema,
+  TypeDefinition,
+} from "./version";
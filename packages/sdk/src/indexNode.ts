@@ -1,6 +1,11 @@
-import { Query as QueryWasm, Sdk as SdkWasm } from "@namada/shared";
+import {
+  Query as QueryWasm,
+  ReadOnlySdk as ReadOnlySdkWasm,
+  Sdk as SdkWasm,
+  set_node_fs as setNodeFsWasm,
+} from "@namada/shared";
 import { webcrypto } from "node:crypto";
-import { Sdk } from "./sdk";
+import { ReadOnlySdk, Sdk } from "./sdk";
 export * from "./index";
 export * from "./utils";
 
@@ -31,6 +36,42 @@ export function getSdk(
   return new Sdk(sdk, query, cryptoMemory, url, token);
 }
 
+/**
+ * File system callbacks, with the signatures of their node:fs counterparts
+ */
+export type NodeFileSystem = {
+  readFileSync: (path: string) => Uint8Array;
+  writeFileSync: (path: string, data: Uint8Array) => void;
+  appendFileSync: (path: string, data: Uint8Array) => void;
+  renameSync: (oldPath: string, newPath: string) => void;
+  unlinkSync: (path: string) => void;
+  existsSync: (path: string) => boolean;
+};
+
+/**
+ * Replace the file system storing MASP params, shielded context, wallet,
+ * templates and audit log, e.g. with a sandboxed or remote one. Missing
+ * callbacks fall back to node:fs.
+ * @param [fs] - File system callbacks, node:fs is restored if undefined
+ * @throws {Error} - If fs is not an object
+ * @returns void
+ */
+export function setNodeFs(fs?: Partial<NodeFileSystem>): void {
+  setNodeFsWasm(fs);
+}
+
+/**
+ * Get a read-only SDK instance, for queries and decoding only
+ * @param url - URL of the node
+ * @param token - Native token of the chain
+ * @returns - ReadOnlySdk instance
+ */
+export function getReadOnlySdk(url: string, token: string): ReadOnlySdk {
+  const query = new QueryWasm(url);
+  const sdk = new ReadOnlySdkWasm(url, token);
+  return new ReadOnlySdk(sdk, query, url, token);
+}
+
 /**
  * Query native token from the node
  * @async
@@ -1,5 +1,9 @@
-import { Query as QueryWasm, Sdk as SdkWasm } from "@namada/shared";
-import { Sdk } from "./index";
+import {
+  Query as QueryWasm,
+  ReadOnlySdk as ReadOnlySdkWasm,
+  Sdk as SdkWasm,
+} from "@namada/shared";
+import { ReadOnlySdk, Sdk } from "./index";
 export * from "./index";
 export * from "./utils";
 
@@ -11,6 +15,8 @@ export * from "./utils";
  * @param maspIndexerUrl - optional URL of the MASP indexer
  * @param dbName - Name of the database for the serialized wallet
  * @param [token] - Native token of the chain
+ * @param [indexerUrl] - optional URL of the namada-indexer serving balances,
+ * validators and history, RPC queries are used without it
  * @throws {Error} - Unable to Query native token
  * @returns - Sdk instance
  */
@@ -19,19 +25,43 @@ export function getSdk(
   url: string,
   maspIndexerUrl: string,
   dbName: string,
-  token: string
+  token: string,
+  indexerUrl = ""
 ): Sdk {
   // We change empty string to undefined so it "maps" to the Option<String> in Rust
   const maspIndexerUrlOpt =
     maspIndexerUrl.length === 0 ? undefined : maspIndexerUrl;
+  const indexerUrlOpt = indexerUrl.length === 0 ? undefined : indexerUrl;
   // Instantiate QueryWasm
-  const query = new QueryWasm(url, maspIndexerUrlOpt);
+  const query = new QueryWasm(url, maspIndexerUrlOpt, indexerUrlOpt);
 
   // Instantiate SdkWasm
   const sdk = new SdkWasm(url, token, dbName);
   return new Sdk(sdk, query, cryptoMemory, url, token);
 }
 
+/**
+ * Get a read-only SDK instance, for queries and decoding only
+ * @param url - URL of the node
+ * @param maspIndexerUrl - optional URL of the MASP indexer
+ * @param token - Native token of the chain
+ * @param [indexerUrl] - optional URL of the namada-indexer
+ * @returns - ReadOnlySdk instance
+ */
+export function getReadOnlySdk(
+  url: string,
+  maspIndexerUrl: string,
+  token: string,
+  indexerUrl = ""
+): ReadOnlySdk {
+  const maspIndexerUrlOpt =
+    maspIndexerUrl.length === 0 ? undefined : maspIndexerUrl;
+  const indexerUrlOpt = indexerUrl.length === 0 ? undefined : indexerUrl;
+  const query = new QueryWasm(url, maspIndexerUrlOpt, indexerUrlOpt);
+  const sdk = new ReadOnlySdkWasm(url, token);
+  return new ReadOnlySdk(sdk, query, url, token);
+}
+
 /**
  * Query native token from the node
  * @async
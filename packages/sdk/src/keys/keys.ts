@@ -1,21 +1,31 @@
-import { chains } from "@namada/chains";
 import {
   HDWallet,
   Mnemonic as MnemonicWasm,
   ShieldedHDWallet,
   StringPointer,
   readStringPointer,
+  readVecU8Pointer,
 } from "@namada/crypto";
 import {
   Address as AddressWasm,
   ExtendedSpendingKey,
   ExtendedViewingKey,
   PaymentAddress,
+  derivation_self_test,
   public_key_to_bech32,
 } from "@namada/shared";
 import { Bip44Path, Zip32Path } from "@namada/types";
+import type { ChainPrefixes } from "../sdk";
 import { makeBip44PathArray, makeSaplingPathArray } from "../utils";
-import { Address, ShieldedKeys, TransparentKeys } from "./types";
+import {
+  Address,
+  DerivationMismatch,
+  DerivationSelfTest,
+  DerivationSelfTestPath,
+  DerivationSelfTestResult,
+  ShieldedKeys,
+  TransparentKeys,
+} from "./types";
 
 const DEFAULT_BIP44_PATH: Bip44Path = {
   account: 0,
@@ -27,14 +37,29 @@ const DEFAULT_ZIP32_PATH: Zip32Path = {
   account: 0,
 };
 
+// SLIP-44 coin type of Namada
+const NAMADA_COIN_TYPE = 877;
+
 /**
  * Namespace for key related functions
  */
 export class Keys {
   /**
    * @param cryptoMemory - Memory accessor for crypto lib
+   * @param [prefixes] - prefixes of the chain in use, Namada if not provided
+   */
+  constructor(
+    protected readonly cryptoMemory: WebAssembly.Memory,
+    protected readonly prefixes?: ChainPrefixes
+  ) {}
+
+  /**
+   * SLIP-44 coin type of the chain in use, see Sdk.setChainPrefixes
+   * @returns coin type
    */
-  constructor(protected readonly cryptoMemory: WebAssembly.Memory) {}
+  private coinType(): number {
+    return this.prefixes?.coinType ?? NAMADA_COIN_TYPE;
+  }
 
   /**
    * Get address and public key from private key
@@ -42,7 +67,7 @@ export class Keys {
    * @returns Address and public key
    */
   getAddress(privateKey: string): Address {
-    const addr = new AddressWasm(privateKey);
+    const addr = new AddressWasm(privateKey, this.prefixes);
     const address = addr.implicit();
     const publicKey = addr.public();
 
@@ -83,7 +108,7 @@ export class Keys {
       : undefined;
     const seedPtr = mnemonic.to_seed(passphrasePtr);
     const hdWallet = new HDWallet(seedPtr);
-    const bip44Path = makeBip44PathArray(chains.namada.bip44.coinType, path);
+    const bip44Path = makeBip44PathArray(this.coinType(), path);
     const key = hdWallet.derive(new Uint32Array(bip44Path));
     const privateKeyStringPtr = key.to_hex();
     const privateKey = readStringPointer(
@@ -114,7 +139,7 @@ export class Keys {
     path: Bip44Path = DEFAULT_BIP44_PATH
   ): TransparentKeys {
     const hdWallet = HDWallet.from_seed(seed);
-    const bip44Path = makeBip44PathArray(chains.namada.bip44.coinType, path);
+    const bip44Path = makeBip44PathArray(this.coinType(), path);
     const key = hdWallet.derive(new Uint32Array(bip44Path));
     const privateKeyStringPtr = key.to_hex();
     const privateKey = readStringPointer(
@@ -149,7 +174,7 @@ export class Keys {
   ): ShieldedKeys {
     const shieldedHdWallet = new ShieldedHDWallet(
       seed,
-      makeBip44PathArray(chains.namada.bip44.coinType, bip44Path)
+      makeBip44PathArray(this.coinType(), bip44Path)
     );
     return this.deriveFromShieldedWallet(
       shieldedHdWallet,
@@ -174,13 +199,75 @@ export class Keys {
     return this.deriveFromShieldedWallet(shieldedHdWallet, path, diversifier);
   }
 
+  /**
+   * Derive the paths with both the TS and the wasm implementations and compare
+   * the results, to check at install time that they agree
+   * @param phrase - Mnemonic phrase
+   * @param [paths] - Bip44 and zip32 paths of the accounts to derive
+   * @param [passphrase] - Bip39 passphrase
+   * @returns Values derived by wasm and the values TS derives differently
+   */
+  derivationSelfTest(
+    phrase: string,
+    paths: DerivationSelfTestPath[] = [
+      { bip44: DEFAULT_BIP44_PATH, zip32: DEFAULT_ZIP32_PATH },
+    ],
+    passphrase?: string
+  ): DerivationSelfTestResult {
+    const derived: DerivationSelfTest = derivation_self_test(
+      phrase,
+      paths,
+      passphrase,
+      this.prefixes
+    );
+    const mismatches: DerivationMismatch[] = [];
+    const compare = (
+      field: string,
+      expected: string,
+      actual: string,
+      pathIndex?: number
+    ): void => {
+      if (expected !== actual) {
+        mismatches.push({ pathIndex, field, expected, actual });
+      }
+    };
+
+    const mnemonic = MnemonicWasm.from_phrase(phrase);
+    const passphrasePtr =
+      typeof passphrase === "string" ?
+        new StringPointer(passphrase)
+      : undefined;
+    const seed = new Uint8Array(
+      readVecU8Pointer(mnemonic.to_seed(passphrasePtr), this.cryptoMemory)
+    );
+    mnemonic.free();
+
+    compare("coinType", String(derived.coinType), String(this.coinType()));
+    compare("seed", derived.seed, Buffer.from(seed).toString("hex"));
+
+    paths.forEach(({ bip44, zip32 }, i) => {
+      const account = derived.accounts[i];
+      const transparent = this.deriveFromSeed(seed, bip44);
+      const shielded = this.deriveShieldedFromSeed(seed, bip44, zip32);
+
+      compare("privateKey", account.privateKey, transparent.privateKey, i);
+      compare("publicKey", account.publicKey, transparent.publicKey, i);
+      compare("address", account.address, transparent.address, i);
+      compare("spendingKey", account.spendingKey, shielded.spendingKey, i);
+      compare("viewingKey", account.viewingKey, shielded.viewingKey, i);
+      compare("paymentAddress", account.paymentAddress, shielded.address, i);
+    });
+
+    return { derived, mismatches };
+  }
+
   private deriveFromShieldedWallet(
     shieldedHdWallet: ShieldedHDWallet,
     path: Zip32Path,
     diversifier?: Uint8Array
   ): ShieldedKeys {
     const { account, index } = path;
-    const saplingPath = makeSaplingPathArray(877, account, index);
+    const saplingPath = makeSaplingPathArray(this.coinType(), account, index);
     const derivedAccount = shieldedHdWallet.derive(saplingPath, diversifier);
 
     // Retrieve serialized types from wasm
@@ -191,9 +278,11 @@ export class Keys {
     // Deserialize and encode keys and address
     const extendedSpendingKey = new ExtendedSpendingKey(xsk);
     const extendedViewingKey = new ExtendedViewingKey(xfvk);
-    const address = new PaymentAddress(paymentAddress).encode();
-    const spendingKey = extendedSpendingKey.encode();
-    const viewingKey = extendedViewingKey.encode();
+    const address = new PaymentAddress(paymentAddress).encode(
+      this.prefixes
+    );
+    const spendingKey = extendedSpendingKey.encode(this.prefixes);
+    const viewingKey = extendedViewingKey.encode(this.prefixes);
 
     // Clear wasm resources from memory
     shieldedHdWallet.free();
@@ -211,5 +300,5 @@ export class Keys {
 
 //TODO: think where to put this function
 export const publicKeyToBech32 = (publicKey: Uint8Array): string => {
-  return public_key_to_bech32(publicKey);
+  return public_key_to_bech32(publicKey, undefined);
 };
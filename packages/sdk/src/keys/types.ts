@@ -1,3 +1,5 @@
+import { Bip44Path, Zip32Path } from "@namada/types";
+
 /**
  * Address and public key type
  */
@@ -21,3 +23,55 @@ export type ShieldedKeys = {
   viewingKey: string;
   spendingKey: string;
 };
+
+/**
+ * Paths of the accounts to derive in a derivation self test
+ */
+export type DerivationSelfTestPath = {
+  bip44: Bip44Path;
+  zip32: Zip32Path;
+};
+
+/**
+ * Keys and addresses derived by the wasm implementation, with the values they
+ * are derived from
+ */
+export type DerivedAccount = {
+  bip44Path: string;
+  privateKey: string;
+  publicKey: string;
+  publicKeyHash: string;
+  address: string;
+  zip32Path: string;
+  spendingKey: string;
+  viewingKey: string;
+  paymentAddress: string;
+};
+
+/**
+ * Output of derivation_self_test
+ */
+export type DerivationSelfTest = {
+  coinType: number;
+  seed: string;
+  accounts: DerivedAccount[];
+};
+
+/**
+ * Value derived differently by the TS and wasm implementations
+ */
+export type DerivationMismatch = {
+  // Index of the path, or undefined for the seed
+  pathIndex?: number;
+  field: string;
+  expected: string;
+  actual: string;
+};
+
+/**
+ * Result of a derivation self test
+ */
+export type DerivationSelfTestResult = {
+  derived: DerivationSelfTest;
+  mismatches: DerivationMismatch[];
+};
@@ -38,6 +38,23 @@ export class Masp {
     return await this.sdk.load_masp_params(pathOrDbName);
   }
 
+  /**
+   * Defer loading of MASP params until the first shielded tx is built
+   * @param pathOrDbName - Path to stored MASP params(nodejs) or name of the database(browser)
+   * @returns void
+   */
+  deferMaspParams(pathOrDbName: string): void {
+    this.sdk.defer_masp_params(pathOrDbName);
+  }
+
+  /**
+   * Check if no deferred MASP params are waiting to be loaded
+   * @returns True if MASP params are not deferred
+   */
+  maspParamsLoaded(): boolean {
+    return this.sdk.masp_params_loaded();
+  }
+
   /**
    * Add spending key to SDK wallet
    * @async
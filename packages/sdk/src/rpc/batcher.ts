@@ -0,0 +1,49 @@
+type PendingRead = {
+  key: string;
+  resolve: (value: Uint8Array | undefined) => void;
+  reject: (error: unknown) => void;
+};
+
+/**
+ * Coalesces storage reads issued within a tick into one call of fetchValues,
+ * e.g. the reads of the components of a dashboard rendering at once
+ */
+export class StorageBatcher {
+  private pending: PendingRead[] = [];
+
+  /**
+   * @param fetchValues - Reads the values of keys, in the order of the keys
+   */
+  constructor(
+    private readonly fetchValues: (
+      keys: string[]
+    ) => Promise<(Uint8Array | undefined)[]>
+  ) {}
+
+  /**
+   * Read the value of a storage key, with the other reads of the tick
+   * @param key - Storage key
+   * @returns value bytes, undefined if the key has no value
+   */
+  read(key: string): Promise<Uint8Array | undefined> {
+    return new Promise((resolve, reject) => {
+      if (this.pending.length === 0) {
+        queueMicrotask(() => this.flush());
+      }
+      this.pending.push({ key, resolve, reject });
+    });
+  }
+
+  private async flush(): Promise<void> {
+    const reads = this.pending;
+    this.pending = [];
+
+    try {
+      // Duplicate keys are queried once by the wasm lib
+      const values = await this.fetchValues(reads.map(({ key }) => key));
+      reads.forEach(({ resolve }, i) => resolve(values[i]));
+    } catch (e) {
+      reads.forEach(({ reject }) => reject(e));
+    }
+  }
+}
@@ -1,2 +1,3 @@
+export * from "./batcher";
 export * from "./rpc";
 export * from "./types";
@@ -1,8 +1,12 @@
 import { deserialize } from "@dao-xyz/borsh";
 import {
   Query as QueryWasm,
+  ReadOnlySdk as ReadOnlySdkWasm,
   Sdk as SdkWasm,
   TransferToEthereum,
+  decode_storage_value,
+  erc20_address,
+  wrapped_erc20_address,
 } from "@namada/shared";
 import {
   Message,
@@ -12,29 +16,62 @@ import {
   WrapperTxProps,
 } from "@namada/types";
 
+import { Tx } from "../tx";
+import { StorageBatcher } from "./batcher";
 import {
+  AbciQueryResponse,
+  AuthorDeposits,
   Balance,
+  BlockInfo,
+  BlockTx,
   BondsResponse,
+  BridgePoolTransfer,
+  BridgePoolTransferStatus,
   DelegationTotals,
   DelegatorsVotes,
+  EventFilter,
   GasCosts,
+  LoggedEvent,
+  MaspConversion,
+  MaspRewardRate,
+  MaspTxEstimate,
+  PageCallback,
+  PgfFunding,
+  PgfSteward,
+  ProposalDeposit,
+  ProposalInfo,
+  PendingTx,
   StakingPositions,
   StakingTotals,
   StakingTotalsResponse,
+  StorageValueType,
+  Timeline,
   UnbondsResponse,
+  ValidatorSummary,
+  ValidatorUptime,
+  VoterRole,
   WasmHash,
+  WrappedAsset,
 } from "./types";
 
+type RawBlockTx = Omit<BlockTx, "bytes" | "details" | "wrapperHash"> & {
+  bytes: number[];
+  wrapperHash: string | null;
+};
+
+// Rpc instances are created on each access, batchers are kept per wasm Query
+const storageBatchers = new WeakMap<QueryWasm, StorageBatcher>();
+
 /**
  * API for executing RPC requests with Namada
  */
 export class Rpc {
   /**
-   * @param sdk - Instance of Sdk struct from wasm lib
+   * @param sdk - Instance of Sdk or ReadOnlySdk struct from wasm lib
    * @param query - Instance of Query struct from wasm lib
    */
   constructor(
-    protected readonly sdk: SdkWasm,
+    protected readonly sdk: SdkWasm | ReadOnlySdkWasm,
     protected readonly query: QueryWasm
   ) {}
 
@@ -49,6 +86,42 @@ export class Rpc {
     return await this.query.query_balance(owner, tokens);
   }
 
+  /**
+   * Query all validators, from the indexer if one is configured
+   * @async
+   * @returns Validators with their stake, state and name
+   */
+  async queryValidators(): Promise<ValidatorSummary[]> {
+    return await this.query.query_validators();
+  }
+
+  /**
+   * Query a page of the history of an address and viewing keys as one
+   * timeline, newest entries first. The history of viewing keys is read from
+   * the synced shielded context and merged into the pages of the transparent
+   * history, by height.
+   * @async
+   * @param [address] - Transparent address
+   * @param [viewingKeys] - Extended viewing keys
+   * @param [page] - Page number, starting at 1
+   * @returns Page of the timeline
+   */
+  async queryHistory(
+    address?: string,
+    viewingKeys: string[] = [],
+    page = 1
+  ): Promise<Timeline> {
+    return await this.query.query_history(address, viewingKeys, BigInt(page));
+  }
+
+  /**
+   * Backend serving balance, validator and history queries
+   * @returns "indexer" or "rpc"
+   */
+  indexerBackend(): "indexer" | "rpc" {
+    return this.query.indexer_backend() as "indexer" | "rpc";
+  }
+
   /**
    * Query native token from chain
    * @async
@@ -70,6 +143,272 @@ export class Rpc {
     return pk;
   }
 
+  /**
+   * Query the raw Borsh serialized value of any storage key
+   * @async
+   * @param key - Storage key
+   * @returns value bytes, undefined if the key has no value
+   */
+  async queryStorageValue(key: string): Promise<Uint8Array | undefined> {
+    return await this.query.query_storage_value(key);
+  }
+
+  /**
+   * Query the raw values of many storage keys in batched requests
+   * @async
+   * @param keys - Storage keys, duplicates are queried once
+   * @returns value bytes in the order of the keys, undefined for keys without
+   * a value
+   */
+  async queryStorageValues(
+    keys: string[]
+  ): Promise<(Uint8Array | undefined)[]> {
+    return await this.query.query_storage_values(keys);
+  }
+
+  /**
+   * Query the raw value of a storage key together with the other reads of
+   * the tick, in batched requests. See queryStorageValues.
+   * @async
+   * @param key - Storage key
+   * @returns value bytes, undefined if the key has no value
+   */
+  async queryStorageValueBatched(key: string): Promise<Uint8Array | undefined> {
+    let batcher = storageBatchers.get(this.query);
+    if (!batcher) {
+      const query = this.query;
+      batcher = new StorageBatcher((keys) => query.query_storage_values(keys));
+      storageBatchers.set(this.query, batcher);
+    }
+    return await batcher.read(key);
+  }
+
+  /**
+   * Perform a raw ABCI query
+   * @async
+   * @param path - Query path
+   * @param [data] - Query data
+   * @param [height] - Block height, latest if not provided
+   * @param [prove] - Include proof ops of the value
+   * @returns value, proof ops and height of the response
+   */
+  async abciQuery(
+    path: string,
+    data?: Uint8Array,
+    height?: bigint,
+    prove = false
+  ): Promise<AbciQueryResponse> {
+    const response = (await this.query.abci_query(
+      path,
+      data,
+      height,
+      prove
+    )) as {
+      value: number[];
+      proofOps: { fieldType: string; key: number[]; data: number[] }[] | null;
+      height: number;
+      info: string;
+    };
+
+    return {
+      value: new Uint8Array(response.value),
+      proofOps: response.proofOps?.map(({ fieldType, key, data }) => ({
+        fieldType,
+        key: new Uint8Array(key),
+        data: new Uint8Array(data),
+      })),
+      height: response.height,
+      info: response.info,
+    };
+  }
+
+  /**
+   * Query all validator addresses in pages, avoiding copying the whole set
+   * across the wasm boundary at once
+   * @async
+   * @param onPage - Callback receiving the pages
+   * @param [pageSize] - Number of addresses per page, 100 by default
+   * @returns number of pages sent
+   */
+  async queryAllValidatorsPaged(
+    onPage: PageCallback<string>,
+    pageSize?: number
+  ): Promise<number> {
+    return await this.query.query_all_validator_addresses_paged(
+      pageSize,
+      onPage
+    );
+  }
+
+  /**
+   * Query governance proposals in pages
+   * @async
+   * @param onPage - Callback receiving the pages
+   * @param [fromId] - First proposal id to include, 0 by default
+   * @param [pageSize] - Number of proposals per page, 100 by default
+   * @returns number of pages sent
+   */
+  async queryProposalsPaged(
+    onPage: PageCallback<ProposalInfo>,
+    fromId = BigInt(0),
+    pageSize?: number
+  ): Promise<number> {
+    return await this.query.query_proposals_paged(fromId, pageSize, onPage);
+  }
+
+  /**
+   * Query the deposit of a proposal and whether it was refunded or burned
+   * @async
+   * @param proposalId - ID of the proposal
+   * @returns Deposit of the proposal
+   */
+  async queryProposalDeposit(proposalId: bigint): Promise<ProposalDeposit> {
+    return await this.query.query_proposal_deposit(proposalId);
+  }
+
+  /**
+   * Query the deposits of all proposals of an author
+   * @async
+   * @param author - Address of the author
+   * @returns Deposits with locked, refunded and burned totals
+   */
+  async queryAuthorDeposits(author: string): Promise<AuthorDeposits> {
+    return await this.query.query_author_deposits(author);
+  }
+
+  /**
+   * Query the active continuous PGF fundings
+   * @async
+   * @returns Fundings with their target and amount per epoch
+   */
+  async queryPgfFundings(): Promise<PgfFunding[]> {
+    return await this.query.query_pgf_fundings();
+  }
+
+  /**
+   * Query the PGF stewards
+   * @async
+   * @returns Stewards with their commissions
+   */
+  async queryPgfStewards(): Promise<PgfSteward[]> {
+    return await this.query.query_pgf_stewards();
+  }
+
+  /**
+   * Query the current MASP epoch
+   * @async
+   * @returns MASP epoch
+   */
+  async queryMaspEpoch(): Promise<string> {
+    return await this.query.query_masp_epoch();
+  }
+
+  /**
+   * Query the allowed conversions of assets of past MASP epochs, which are
+   * applied to shielded balances after an epoch rollover
+   * @async
+   * @param [token] - Token address to filter the conversions by
+   * @returns conversions
+   */
+  async queryMaspConversions(token?: string): Promise<MaspConversion[]> {
+    return await this.query.query_masp_conversions(token);
+  }
+
+  /**
+   * Query reward parameters and last inflation of tokens in the MASP
+   * @async
+   * @returns reward rates per token
+   */
+  async queryMaspRewardRates(): Promise<MaspRewardRate[]> {
+    return await this.query.query_masp_reward_rates();
+  }
+
+  /**
+   * Query a block with all of its txs, decoding the supported wrapper txs
+   * @async
+   * @param height - Block height
+   * @param [checksums] - Wasm checksums used for decoding, queried if not provided
+   * @returns block info
+   */
+  async queryBlockByHeight(
+    height: bigint,
+    checksums?: Record<string, string>
+  ): Promise<BlockInfo> {
+    const block = (await this.query.query_block_by_height(height)) as Omit<
+      BlockInfo,
+      "txs"
+    > & { txs: RawBlockTx[] };
+    const wasmChecksums = checksums || (await this.queryChecksums());
+
+    return {
+      ...block,
+      txs: block.txs.map((tx) => this.decodeBlockTx(tx, wasmChecksums)),
+    };
+  }
+
+  /**
+   * Query a tx included in a block by its CometBFT hash, as returned when
+   * broadcasting
+   * @async
+   * @param hash - Hex encoded sha256 hash of the tx bytes
+   * @param [checksums] - Wasm checksums used for decoding, queried if not provided
+   * @returns tx with its height and result
+   */
+  async queryTxByHash(
+    hash: string,
+    checksums?: Record<string, string>
+  ): Promise<BlockTx> {
+    const tx: RawBlockTx = await this.query.query_tx_by_hash(hash);
+    return this.decodeBlockTx(tx, checksums || (await this.queryChecksums()));
+  }
+
+  private decodeBlockTx(
+    tx: RawBlockTx,
+    checksums: Record<string, string>
+  ): BlockTx {
+    const bytes = new Uint8Array(tx.bytes);
+    let details: BlockTx["details"];
+    if (tx.wrapperHash) {
+      try {
+        details = new Tx(this.sdk).deserialize(bytes, checksums);
+      } catch {
+        // Txs without supported commitments are returned undecoded
+      }
+    }
+
+    return {
+      ...tx,
+      wrapperHash: tx.wrapperHash ?? undefined,
+      bytes,
+      details,
+    };
+  }
+
+  /**
+   * Query events emitted by the node, e.g. applied txs, IBC and governance
+   * events, matching a filter
+   * @async
+   * @param filter - Height range and optional event type, tx hash and address
+   * @returns matching events in the order they were emitted
+   */
+  async queryEvents(filter: EventFilter): Promise<LoggedEvent[]> {
+    return await this.query.query_events(filter);
+  }
+
+  /**
+   * Decode a storage value of a common type
+   * @param bytes - Borsh serialized value, see queryStorageValue
+   * @param valueType - Type of the value
+   * @returns amounts and public keys as strings, epochs as numbers and
+   * validator states as their names
+   */
+  decodeStorageValue(
+    bytes: Uint8Array,
+    valueType: StorageValueType
+  ): string | number {
+    return decode_storage_value(bytes, valueType);
+  }
+
   /**
    * Query all validator addresses
    * @async
@@ -103,6 +442,17 @@ export class Rpc {
     return await this.query.delegators_votes(proposalId);
   }
 
+  /**
+   * Query whether an address votes as a validator or delegator, and if it is a
+   * PGF steward, in the current epoch
+   * @async
+   * @param address - address of the voter
+   * @returns Promise resolving to the role and voting power of the voter
+   */
+  async queryVoterRole(address: string): Promise<VoterRole> {
+    return await this.query.query_voter_role(address);
+  }
+
   /**
    * Query staking totals by owner addresses
    * @async
@@ -176,6 +526,23 @@ export class Rpc {
     return await this.query.query_total_bonds(owner);
   }
 
+  /**
+   * Query the uptime of validators over the last blocks, e.g. to rank them by
+   * reliability
+   * @param validators - Validator addresses
+   * @param [window] - Number of blocks, defaults to 100, at most 1000
+   * @returns Uptime of each validator
+   */
+  async queryValidatorUptime(
+    validators: string[],
+    window?: number
+  ): Promise<ValidatorUptime[]> {
+    return await this.query.query_validator_uptime(
+      validators,
+      window === undefined ? undefined : BigInt(window)
+    );
+  }
+
   /**
    * Query pending transactions in the signed bridge pool
    * @async
@@ -186,6 +553,107 @@ export class Rpc {
     return await this.query.query_signed_bridge_pool(owners);
   }
 
+  /**
+   * Query Namada token and decimals of a bridged ERC-20 asset
+   * @async
+   * @param erc20 - ERC-20 address of the asset
+   * @returns Promise resolving to the wrapped asset
+   */
+  async queryWrappedAsset(erc20: string): Promise<WrappedAsset> {
+    return await this.query.query_wrapped_asset(erc20);
+  }
+
+  /**
+   * Query ERC-20 address of a Namada token, including wrapped NAM
+   * @async
+   * @param token - Namada token address
+   * @returns Promise resolving to the ERC-20 address, undefined if the token is not bridged
+   */
+  async queryErc20OfToken(token: string): Promise<string | undefined> {
+    return await this.query.query_erc20_of_token(token);
+  }
+
+  /**
+   * Return Namada address of a bridged ERC-20 asset, without querying. Wrapped NAM
+   * maps to the native token instead, see queryWrappedAsset
+   * @param erc20 - ERC-20 address of the asset
+   * @param [nut] - return the address of non-usable tokens instead
+   * @returns Namada token address
+   */
+  wrappedErc20Address(erc20: string, nut = false): string {
+    return wrapped_erc20_address(erc20, nut);
+  }
+
+  /**
+   * Return ERC-20 address of a wrapped token or NUT, without querying
+   * @param token - Namada token address
+   * @returns ERC-20 address, undefined for other tokens
+   */
+  erc20Address(token: string): string | undefined {
+    return erc20_address(token);
+  }
+
+  /**
+   * Query transfers in the bridge pool sent by any of the owners, with their status
+   * @async
+   * @param owners - Array of owner addresses
+   * @returns Promise resolving to bridge pool transfers
+   */
+  async queryBridgePoolTransfers(
+    owners: string[]
+  ): Promise<BridgePoolTransfer[]> {
+    return await this.query.query_bridge_pool_transfers(owners);
+  }
+
+  /**
+   * Query status of a bridge pool transfer
+   * @async
+   * @param hash - Keccak hash of the transfer
+   * @returns Promise resolving to the transfer status
+   */
+  async queryBridgePoolTransferStatus(
+    hash: string
+  ): Promise<BridgePoolTransferStatus> {
+    return await this.query.query_bridge_pool_transfer_status(hash);
+  }
+
+  /**
+   * Query ABI encoded arguments of transferToErc of the Ethereum bridge contract,
+   * to relay signed bridge pool transfers
+   * @async
+   * @param hashes - Keccak hashes of signed transfers
+   * @param relayer - Address receiving the fees of the transfers
+   * @returns Promise resolving to the relay payload
+   */
+  async queryBridgePoolRelayProof(
+    hashes: string[],
+    relayer: string
+  ): Promise<Uint8Array> {
+    return await this.query.query_bridge_pool_relay_proof(hashes, relayer);
+  }
+
+  /**
+   * Query wrapper transactions paid by the fee payer, which were broadcast but are
+   * not yet included in a block
+   * @async
+   * @param feePayer - Public key or implicit address of the fee payer
+   * @returns Promise resolving to pending transactions
+   */
+  async queryUnconfirmedTxs(feePayer: string): Promise<PendingTx[]> {
+    return await this.query.query_unconfirmed_txs(feePayer);
+  }
+
+  /**
+   * Query expiration date of a Tx valid for the given number of minutes, computed from
+   * the time of the latest block instead of the client clock
+   * @async
+   * @param minutes - Number of minutes the Tx stays valid for
+   * @returns RFC 3339 date to be used as WrapperTxProps expiration
+   */
+  async queryTxExpiration(minutes: number): Promise<string> {
+    return await this.query.query_tx_expiration(BigInt(minutes));
+  }
+
   /**
    * Query gas costs
    * @async
@@ -195,6 +663,31 @@ export class Rpc {
     return await this.query.query_gas_costs();
   }
 
+  /**
+   * Estimate the size and minimum gas limit of a MASP Tx before proving it, to
+   * warn when it won't fit in a block
+   * @async
+   * @param spends - Number of spent notes, e.g. from Tx.maspDescriptions
+   * @param converts - Number of conversions of the spent notes
+   * @param outputs - Number of created notes, including change
+   * @param baseGasLimit - Gas limit of the Tx without its MASP transaction,
+   * e.g. from the gas table of the indexer
+   * @returns MaspTxEstimate
+   */
+  async estimateMaspTx(
+    spends: number,
+    converts: number,
+    outputs: number,
+    baseGasLimit: number
+  ): Promise<MaspTxEstimate> {
+    return await this.query.estimate_masp_tx(
+      BigInt(spends),
+      BigInt(converts),
+      BigInt(outputs),
+      BigInt(baseGasLimit)
+    );
+  }
+
   /**
    * Query code paths and their associated hash on chain
    * @async
@@ -218,22 +711,60 @@ export class Rpc {
    * @async
    * @param signedTxBytes - Transaction with signature
    * @param args - WrapperTxProps
+   * @param [idempotencyKey] - Key identifying the submission, duplicate submissions with
+   * the same key are refused. If the broadcast fails, the key is kept until the tx expires
    * @returns TxResponseProps object
    */
   async broadcastTx(
     signedTxBytes: Uint8Array,
-    args: WrapperTxProps
+    args: WrapperTxProps,
+    idempotencyKey?: string
   ): Promise<TxResponseProps> {
     const wrapperTxMsgValue = new WrapperTxMsgValue(args);
     const msg = new Message<WrapperTxMsgValue>();
     const encodedArgs = msg.encode(wrapperTxMsgValue);
 
-    const response = await this.sdk.process_tx(signedTxBytes, encodedArgs);
+    const response = await this.sdk.process_tx(
+      signedTxBytes,
+      encodedArgs,
+      idempotencyKey
+    );
     return deserialize(Buffer.from(response), TxResponseMsgValue);
   }
 
+  /**
+   * Release an idempotency key, once the tx submitted with it is known not to be
+   * applied, so that it can be submitted again
+   * @param idempotencyKey - Key the tx was broadcast with
+   * @returns void
+   */
+  releaseIdempotencyKey(idempotencyKey: string): void {
+    this.sdk.release_idempotency_key(idempotencyKey);
+  }
+
+  /**
+   * Broadcast a Tx to several RPC endpoints at once, resolving with the first acceptance.
+   * Unlike broadcastTx, it doesn't wait for the Tx to be applied.
+   * @async
+   * @param signedTxBytes - Transaction with signature
+   * @param endpoints - Additional RPC endpoints
+   * @returns url of the accepting endpoint and the tx hash
+   */
+  async broadcastTxToEndpoints(
+    signedTxBytes: Uint8Array,
+    endpoints: string[]
+  ): Promise<{ url: string; hash: string }> {
+    const [url, hash] = (await this.sdk.broadcast_tx_to_endpoints(
+      signedTxBytes,
+      endpoints
+    )) as [string, string];
+    return { url, hash };
+  }
+
   /**
    * Sync the shielded context
+   * Fetched blocks are cached, so syncing additional viewing keys later
+   * only fetches blocks which weren't synced before
    * @async
    * @param vks - Array of viewing keys
    * @returns
@@ -241,4 +772,56 @@ export class Rpc {
   async shieldedSync(vks: string[]): Promise<void> {
     await this.query.shielded_sync(vks);
   }
+
+  /**
+   * Export the scanned notes of a viewing key, encrypted to the viewing key,
+   * to import them in another wallet instance instead of syncing from scratch
+   * @async
+   * @param vk - Synced viewing key
+   * @returns encrypted export
+   */
+  async exportShieldedNotes(vk: string): Promise<Uint8Array> {
+    return await this.query.export_shielded_notes(vk);
+  }
+
+  /**
+   * Import notes exported with exportShieldedNotes. They replace the local
+   * shielded context, other viewing keys are synced again by shieldedSync
+   * @async
+   * @param vk - Viewing key the notes were exported for
+   * @param exportedNotes - Encrypted export
+   * @returns void
+   */
+  async importShieldedNotes(
+    vk: string,
+    exportedNotes: Uint8Array
+  ): Promise<void> {
+    await this.query.import_shielded_notes(vk, exportedNotes);
+  }
+
+  /**
+   * Clear the MASP blocks cached by previous shielded syncs
+   * @returns void
+   */
+  clearBlockCache(): void {
+    this.query.clear_block_cache();
+  }
+
+  /**
+   * Report a new block, e.g. from a block subscription, so that cached
+   * responses of queries of the latest state are queried again
+   * @param height - Height of the block
+   * @returns void
+   */
+  observeBlock(height: number): void {
+    this.query.observe_block(BigInt(height));
+  }
+
+  /**
+   * Clear the cached query responses, e.g. after switching chains
+   * @returns void
+   */
+  clearResponseCache(): void {
+    this.query.clear_response_cache();
+  }
 }
@@ -1,3 +1,5 @@
+import { TxDetails } from "@namada/types";
+
 /**
  * StakingTotalsResponse
  * [owner, validator, bonds, unbonds, withdrawable]
@@ -56,12 +58,74 @@ export type DelegationTotals = Record<string, number>;
  */
 export type DelegatorsVotes = Record<string, boolean>;
 
+/**
+ * Governance role of a voter. PGF stewards vote with their stake as validators
+ * or delegators, votingPower being in raw native token units
+ */
+export type VoterRole = {
+  address: string;
+  isValidator: boolean;
+  isDelegator: boolean;
+  isSteward: boolean;
+  votingPower: string;
+};
+
+/**
+ * Status of a bridge pool transfer. Signed transfers can be relayed, notFound ones
+ * were either completed or expired and refunded
+ */
+export type BridgePoolTransferStatus =
+  | "pending"
+  | "signed"
+  | "relayed"
+  | "notFound";
+
+/**
+ * Transfer in the bridge pool, amounts being in raw token units
+ */
+export type BridgePoolTransfer = {
+  hash: string;
+  asset: string;
+  recipient: string;
+  sender: string;
+  amount: string;
+  feeToken: string;
+  feeAmount: string;
+  feePayer: string;
+  status: BridgePoolTransferStatus;
+};
+
+/**
+ * Namada token of a bridged ERC-20 asset. Amounts of the asset have the given
+ * number of decimals on both chains
+ */
+export type WrappedAsset = {
+  erc20: string;
+  token: string;
+  nut: string;
+  decimals: number;
+  whitelisted: boolean;
+  isNative: boolean;
+};
+
 /**
  * GasCosts
  * [tokenAddress, gasCost][]
  */
 export type GasCosts = [string, string][];
 
+/**
+ * Estimated size and gas of a MASP Tx before proving
+ */
+export type MaspTxEstimate = {
+  bytes: number;
+  gasLimit: number;
+  maxTxBytes: number;
+  maxBlockGas: number;
+  exceedsTxBytes: boolean;
+  exceedsBlockGas: boolean;
+};
+
 /**
  * Balance
  * [tokenAddress, amount][]
@@ -75,3 +139,260 @@ export type WasmHash = {
   path: string;
   hash: string;
 };
+
+/**
+ * Proof op of an ABCI query
+ */
+export type AbciProofOp = {
+  fieldType: string;
+  key: Uint8Array;
+  data: Uint8Array;
+};
+
+/**
+ * Raw ABCI query response, proof ops are present only if requested
+ */
+export type AbciQueryResponse = {
+  value: Uint8Array;
+  proofOps?: AbciProofOp[];
+  height: number;
+  info: string;
+};
+
+/**
+ * Callback receiving a page of a large result, the next page is sent once a
+ * returned promise resolves
+ */
+export type PageCallback<T> = (
+  page: T[],
+  pageIndex: number
+) => void | Promise<void>;
+
+/**
+ * Governance proposal
+ */
+export type ProposalInfo = {
+  id: number;
+  content: string;
+  author: string;
+  startEpoch: number;
+  endEpoch: number;
+  graceEpoch: number;
+  tallyType: string;
+  proposalType: string;
+  data?: string;
+};
+
+/**
+ * Tx included in a block
+ */
+export type BlockTx = {
+  // CometBFT tx hash, sha256 of the tx bytes
+  hash: string;
+  // Namada wrapper hash, for wrapper txs only
+  wrapperHash?: string;
+  height: number;
+  index: number;
+  applied: boolean;
+  bytes: Uint8Array;
+  // Decoded wrapper tx, if it's supported by the decoder
+  details?: TxDetails;
+};
+
+/**
+ * Block with all of its txs
+ */
+export type BlockInfo = {
+  height: number;
+  hash: string;
+  time: string;
+  proposer: string;
+  txs: BlockTx[];
+};
+
+/**
+ * Component of a MASP conversion, amount in raw units of the asset
+ */
+export type ConversionComponent = {
+  token: string;
+  digitPos: number;
+  epoch: string;
+  amount: string;
+};
+
+/**
+ * Allowed conversion of an asset of a past MASP epoch to the current one
+ */
+export type MaspConversion = {
+  token: string;
+  denom: number;
+  digitPos: number;
+  epoch: string;
+  components: ConversionComponent[];
+};
+
+/**
+ * Reward parameters of a token in the MASP, with the inflation and locked
+ * amount of the last MASP epoch
+ */
+export type MaspRewardRate = {
+  name: string;
+  token: string;
+  maxRewardRate: string;
+  kpGain: string;
+  kdGain: string;
+  lockedAmountTarget: string;
+  lastInflation: string | null;
+  lastLockedAmount: string | null;
+};
+
+/**
+ * Matcher of events within an inclusive range of at most 100 blocks
+ */
+export type EventFilter = {
+  fromHeight: number;
+  toHeight: number;
+  // Event type or its prefix, e.g. "tx/applied" or "ibc"
+  eventType?: string;
+  // Hash in any of the hash attributes
+  txHash?: string;
+  // Address in any of the attribute values
+  address?: string;
+};
+
+/**
+ * Event emitted by the node
+ */
+export type LoggedEvent = {
+  height: number;
+  eventType: string;
+  attributes: Record<string, string>;
+};
+
+/**
+ * Storage value types supported by Rpc.decodeStorageValue
+ */
+export type StorageValueType =
+  | "amount"
+  | "epoch"
+  | "publicKey"
+  | "validatorState";
+
+/**
+ * Wrapper transaction broadcast but not yet included in a block
+ */
+export type PendingTx = {
+  hash: string;
+  innerTxHashes: string[];
+  feeToken: string;
+  feeAmountPerGasUnit: string;
+  gasLimit: string;
+};
+
+/**
+ * Uptime of a validator over the last blocks, from CometBFT commits
+ */
+export type ValidatorUptime = {
+  address: string;
+  consensusKey: string | null;
+  // Hex CometBFT address of the consensus key
+  tmAddress: string | null;
+  // In the CometBFT validator set of the latest block
+  active: boolean;
+  signedBlocks: number;
+  missedBlocks: number;
+  window: number;
+  // Share of signed blocks from 0 to 1, null if not active
+  reliability: number | null;
+};
+
+/**
+ * Status of a proposal deposit, refunded or burned after the tally
+ */
+export type DepositStatus = "locked" | "refunded" | "burned" | "unknown";
+
+/**
+ * Deposit locked by the author of a proposal
+ */
+export type ProposalDeposit = {
+  proposalId: number;
+  author: string;
+  amount: string;
+  activationEpoch: number;
+  status: DepositStatus;
+};
+
+/**
+ * Deposits of the proposals of an author, with totals by status
+ */
+export type AuthorDeposits = {
+  deposits: ProposalDeposit[];
+  locked: string;
+  refunded: string;
+  burned: string;
+};
+
+/**
+ * Continuous PGF funding, paid every epoch
+ */
+export type PgfFunding = {
+  id: number;
+  target: string;
+  amountPerEpoch: string;
+  // IBC port and channel, for fundings to other chains
+  ibc: [string, string] | null;
+};
+
+/**
+ * PGF steward with the shares of its rewards sent to other addresses
+ */
+export type PgfSteward = {
+  address: string;
+  commissions: { address: string; share: string }[];
+};
+
+/**
+ * Validator with its raw bonded stake, state and name. Names are only known
+ * when queries are served by the indexer.
+ */
+export type ValidatorSummary = {
+  address: string;
+  votingPower: string | null;
+  state:
+    | "consensus"
+    | "belowCapacity"
+    | "belowThreshold"
+    | "inactive"
+    | "jailed"
+    | null;
+  name: string | null;
+};
+
+/**
+ * Entry of the history timeline
+ */
+export type TimelineEntry = {
+  height: number;
+  wrapper_hash: string;
+  inner_hash: string;
+  kind: string;
+  direction: string;
+  counterparty: string | null;
+  token: string | null;
+  amount: string | null;
+  fee_token: string;
+  fee_amount_per_gas_unit: string;
+  gas_limit: string;
+  applied: boolean;
+  // Whether the entry moves funds in or out of the shielded pool, or within it
+  shielded: boolean;
+};
+
+/**
+ * Page of the history timeline, newest entries first
+ */
+export type Timeline = {
+  entries: TimelineEntry[];
+  page: number;
+  hasMore: boolean;
+};
@@ -1,5 +1,12 @@
 import Transport from "@ledgerhq/hw-transport";
-import { Query as QueryWasm, Sdk as SdkWasm } from "@namada/shared";
+import {
+  Query as QueryWasm,
+  ReadOnlySdk as ReadOnlySdkWasm,
+  Sdk as SdkWasm,
+  get_code_paths,
+  set_code_paths,
+  set_crash_report_callback,
+} from "@namada/shared";
 import packageJson from "../package.json";
 import { Crypto } from "./crypto";
 import { Keys } from "./keys";
@@ -9,9 +16,52 @@ import { Mnemonic } from "./mnemonic";
 import { Rpc } from "./rpc";
 import { Signing } from "./signing";
 import { Tx } from "./tx";
+import { ApiCompatibility, checkApiCompatibility } from "./version";
 
 export { ProgressBarNames, SdkEvents } from "@namada/shared";
 
+/**
+ * Report of a panic of the wasm lib, after which the wasm instance is unusable
+ */
+export type CrashReport = {
+  message: string;
+  location: string | null;
+  // Sdk function in progress, e.g. "build_bond"
+  operation: string | null;
+  memoryBytes: number;
+  timestamp: string;
+};
+
+/**
+ * Tx codes used by the SDK, see Sdk.setCodePaths
+ */
+export type TxCode =
+  | "transfer"
+  | "bond"
+  | "redelegate"
+  | "unbond"
+  | "withdraw"
+  | "claimRewards"
+  | "revealPk"
+  | "voteProposal"
+  | "ibc"
+  | "bridgePool"
+  | "initAccount"
+  | "updateAccount";
+
+/**
+ * Bech32m prefixes and SLIP-44 coin type of a chain, see Sdk.setChainPrefixes
+ */
+export type ChainPrefixes = {
+  // Transparent addresses, e.g. "tnam"
+  address: string;
+  publicKey: string;
+  paymentAddress: string;
+  viewingKey: string;
+  spendingKey: string;
+  coinType: number;
+};
+
 /**
  * API for interacting with Namada SDK
  */
@@ -38,6 +88,8 @@ export class Sdk {
    * @returns this instance of Sdk
    */
   updateNetwork(url: string, nativeToken?: string): Sdk {
+    const prefixes = this.getChainPrefixes();
+
     const query = new QueryWasm(url);
     this.query = query;
 
@@ -45,9 +97,80 @@ export class Sdk {
     const sdk = new SdkWasm(url, nativeToken || this.nativeToken, "");
     this.sdk = sdk;
 
+    // Keep the prefixes of the chain in use
+    this.setChainPrefixes(prefixes);
+
     return this;
   }
 
+  /**
+   * Set a callback receiving crash reports of panics of the wasm lib
+   * @param [callback] - called with the CrashReport, or undefined to remove it
+   * @returns void
+   */
+  onCrash(callback?: (report: CrashReport) => void): void {
+    set_crash_report_callback(callback);
+  }
+
+  /**
+   * Override code paths of txs on a chain, for chains deploying tx wasm under
+   * other names than the defaults
+   * @param chainId - chain the paths apply to
+   * @param paths - code paths by tx code, an empty object resets the chain
+   * @returns void
+   */
+  setCodePaths(chainId: string, paths: Partial<Record<TxCode, string>>): void {
+    set_code_paths(chainId, paths);
+  }
+
+  /**
+   * Return code paths of txs on a chain
+   * @param [chainId] - chain of the paths, defaults are returned if not provided
+   * @returns code paths by tx code
+   */
+  getCodePaths(chainId?: string): Record<TxCode, string> {
+    return get_code_paths(chainId);
+  }
+
+  /**
+   * Set the prefixes of the chain in use, for forks and testnets with other
+   * prefixes than Namada. Addresses and keys with the prefixes are accepted by
+   * the tx builders and returned by the key derivation.
+   * @param [prefixes] - prefixes to override, undefined resets all of them
+   * @returns void
+   */
+  setChainPrefixes(prefixes?: Partial<ChainPrefixes>): void {
+    this.sdk.set_chain_prefixes(prefixes);
+    this.query.set_chain_prefixes(prefixes);
+  }
+
+  /**
+   * Return the prefixes of the chain in use
+   * @returns ChainPrefixes
+   */
+  getChainPrefixes(): ChainPrefixes {
+    return this.sdk.chain_prefixes();
+  }
+
+  /**
+   * Check that the loaded wasm lib matches the API this package expects, e.g.
+   * to disable features instead of failing on a stale cached wasm
+   * @returns ApiCompatibility of the loaded wasm lib
+   */
+  apiCompatibility(): ApiCompatibility {
+    return checkApiCompatibility();
+  }
+
+  /**
+   * Check if the loaded wasm lib accepts a Borsh Msg
+   * @param msgType - name of the Msg, e.g. "BondMsg"
+   * @returns true if the Msg is supported by a compatible wasm lib
+   */
+  supportsMsg(msgType: string): boolean {
+    const { compatible, capabilities } = checkApiCompatibility();
+    return compatible && !!capabilities?.msgTypes.includes(msgType);
+  }
+
   /**
    * Return initialized Rpc class
    * @returns Namada RPC client
@@ -77,7 +200,7 @@ export class Sdk {
    * @returns key-related functionality
    */
   getKeys(): Keys {
-    return new Keys(this.cryptoMemory);
+    return new Keys(this.cryptoMemory, this.getChainPrefixes());
   }
 
   /**
@@ -186,3 +309,86 @@ export class Sdk {
     return this.getVersion();
   }
 }
+
+/**
+ * Decoding functions of Tx available in a ReadOnlySdk
+ */
+export type ReadOnlyTx = Pick<
+  Tx,
+  | "deserialize"
+  | "getInnerTxHashes"
+  | "signingDataToJson"
+  | "signingDataFromJson"
+>;
+
+/**
+ * API for deployments using the SDK purely for queries and decoding, e.g.
+ * explorers and dashboards. Signing and key handling are not exposed, and the
+ * underlying wasm ReadOnlySdk has no such functions either.
+ */
+export class ReadOnlySdk {
+  /**
+   * @param sdk - Instance of ReadOnlySdk struct from wasm lib
+   * @param query - Instance of Query struct from wasm lib
+   * @param url - RPC url
+   * @param nativeToken - Address of chain's native token
+   */
+  constructor(
+    protected sdk: ReadOnlySdkWasm,
+    protected query: QueryWasm,
+    public readonly url: string,
+    public readonly nativeToken: string
+  ) {}
+
+  /**
+   * Return initialized Rpc class
+   * @returns Namada RPC client
+   */
+  getRpc(): Rpc {
+    return new Rpc(this.sdk, this.query);
+  }
+
+  /**
+   * Return Tx decoding functionality
+   * @returns Tx decoding functions
+   */
+  getTx(): ReadOnlyTx {
+    // Decoding doesn't use the wasm Sdk
+    const {
+      deserialize,
+      getInnerTxHashes,
+      signingDataToJson,
+      signingDataFromJson,
+    } = Tx.prototype;
+    return {
+      deserialize,
+      getInnerTxHashes,
+      signingDataToJson,
+      signingDataFromJson,
+    };
+  }
+
+  /**
+   * Return SDK Package version
+   * @returns SDK version
+   */
+  getVersion(): string {
+    return packageJson.version;
+  }
+
+  /**
+   * Define rpc getter to use with destructuring assignment
+   * @returns rpc client
+   */
+  get rpc(): Rpc {
+    return this.getRpc();
+  }
+
+  /**
+   * Define tx getter to use with destructuring assignment
+   * @returns Tx decoding functions
+   */
+  get tx(): ReadOnlyTx {
+    return this.getTx();
+  }
+}
@@ -1,8 +1,53 @@
+import { deserialize } from "@dao-xyz/borsh";
 import { Sdk as SdkWasm } from "@namada/shared";
-import { Message, TxMsgValue, TxProps } from "@namada/types";
+import {
+  Message,
+  TxMsgValue,
+  TxProps,
+  WrapperTxMsgValue,
+  WrapperTxProps,
+} from "@namada/types";
+import BigNumber from "bignumber.js";
 
 type Signature = [string, string];
 
+export type ReplacementTx = {
+  // Signed replacement tx bytes
+  tx: Uint8Array;
+  // Wrapper args to broadcast the replacement with
+  wrapperTxProps: WrapperTxProps;
+  // Wrapper hash of the replaced tx
+  replacedHash: string;
+  // Wrapper hash of the replacement tx
+  hash: string;
+};
+
+export type AuditEntry = {
+  kind: "tx" | "feeReplacement" | "arbitrary" | "maspSpend";
+  // Wrapper hash of signed txs, or hash of signed arbitrary data
+  hash: string;
+  // Code tags of the inner txs
+  txTypes: string[];
+  // Public keys of the signing keys, or default payment addresses of the
+  // spending keys of MASP spends
+  signers: string[];
+  // RFC 3339 timestamp
+  timestamp: string;
+  // Hash of the origin of the requesting dApp
+  origin: string | null;
+};
+
+/**
+ * Counterparty of an inner tx, passed to the screening callback. Receivers are
+ * addresses on other chains, of IBC and Ethereum bridge transfers
+ */
+export type Counterparty = {
+  address: string;
+  role: "source" | "target" | "validator" | "receiver";
+  // Hash of the inner tx
+  commitment: string;
+};
+
 /**
  * Non-Tx signing functions
  */
@@ -18,28 +63,114 @@ export class Signing {
    * @param txProps - TxProps
    * @param signingKey - private key
    * @param [chainId] - optional chain ID, will enforce validation if present
+   * @param [origin] - optional origin of the requesting dApp, recorded hashed in the audit log
    * @returns signed tx bytes - Promise resolving to Uint8Array
    */
   async sign(
     txProps: TxProps,
     signingKey: string,
-    chainId?: string
+    chainId?: string,
+    origin?: string
   ): Promise<Uint8Array> {
     const txMsgValue = new TxMsgValue(txProps);
     const msg = new Message<TxMsgValue>();
     const txBytes = msg.encode(txMsgValue);
 
-    return await this.sdk.sign_tx(txBytes, signingKey, chainId);
+    return await this.sdk.sign_tx(txBytes, signingKey, chainId, origin);
+  }
+
+  /**
+   * Refuse to sign txs with any of the addresses as a counterparty. Replaces a
+   * screening callback
+   * @param blocked - blocked addresses, an empty array removes the screening
+   * @returns void
+   */
+  setScreeningList(blocked: string[]): void {
+    this.sdk.set_screening_list(blocked);
+  }
+
+  /**
+   * Set a callback screening the counterparties of txs before signing, which
+   * has to return true for the tx to be signed. Replaces a screening list
+   * @param [callback] - screening callback, or undefined to remove it
+   * @returns void
+   */
+  setScreeningCallback(
+    callback?: (counterparties: Counterparty[]) => boolean | Promise<boolean>
+  ): void {
+    this.sdk.set_screening_callback(callback);
+  }
+
+  /**
+   * Return the counterparties of a tx, as passed to the screening callback.
+   * Throws if an inner tx has a code unknown to the chain or can't be decoded
+   * @async
+   * @param txBytes - Borsh serialized Namada tx
+   * @returns counterparties of the inner txs
+   */
+  async txCounterparties(txBytes: Uint8Array): Promise<Counterparty[]> {
+    return await this.sdk.tx_counterparties(txBytes);
+  }
+
+  /**
+   * Replace the fee of a broadcast but unconfirmed tx, re-signing the wrapper.
+   * The replaced tx is not cancelled, whichever tx is included first is applied.
+   * @async
+   * @param signedTxBytes - signed tx bytes
+   * @param wrapperTxProps - wrapper args the tx was built with
+   * @param feeAmount - new fee amount per gas unit, higher than the replaced one
+   * @param gasLimit - new gas limit
+   * @param signingKey - private key of the fee payer
+   * @returns replacement tx linked to the replaced one
+   */
+  async replaceTxFee(
+    signedTxBytes: Uint8Array,
+    wrapperTxProps: WrapperTxProps,
+    feeAmount: BigNumber,
+    gasLimit: BigNumber,
+    signingKey: string
+  ): Promise<ReplacementTx> {
+    const msg = new Message<WrapperTxMsgValue>();
+    const encodedArgs = msg.encode(new WrapperTxMsgValue(wrapperTxProps));
+
+    const [tx, wrapperTxMsg, replacedHash, hash] =
+      (await this.sdk.replace_tx_fee(
+        signedTxBytes,
+        encodedArgs,
+        feeAmount.toString(),
+        gasLimit.toString(),
+        signingKey
+      )) as [number[], number[], string, string];
+
+    return {
+      tx: new Uint8Array(tx),
+      wrapperTxProps: deserialize(
+        new Uint8Array(wrapperTxMsg),
+        WrapperTxMsgValue
+      ),
+      replacedHash,
+      hash,
+    };
   }
 
   /**
    * Sign arbitrary data
    * @param signingKey - private key
    * @param data - data to sign
+   * @param [origin] - optional origin of the requesting dApp, recorded hashed in the audit log
    * @returns hash and signature
    */
-  signArbitrary(signingKey: string, data: string): Signature {
-    return this.sdk.sign_arbitrary(signingKey, data);
+  signArbitrary(signingKey: string, data: string, origin?: string): Signature {
+    return this.sdk.sign_arbitrary(signingKey, data, origin);
+  }
+
+  /**
+   * Return the log of all signatures produced by the SDK
+   * @async
+   * @returns audit entries, oldest first
+   */
+  async keyAuditLog(): Promise<AuditEntry[]> {
+    return await this.sdk.key_audit_log();
   }
 
   /**
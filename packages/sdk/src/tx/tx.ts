@@ -1,9 +1,23 @@
 import { deserialize } from "@dao-xyz/borsh";
 import {
+  QrPartsDecoder,
   Sdk as SdkWasm,
   TxType,
+  audit_tx_serialization,
+  build_ibc_memo,
+  check_transfer_intent,
+  compound_decision,
+  decrypt_memo,
   deserialize_tx,
+  encode_qr_parts,
+  encrypt_memo,
+  generate_memo_keypair,
   get_inner_tx_hashes,
+  parse_memo,
+  plan_batch_signing,
+  required_signatures,
+  signing_data_from_json,
+  signing_data_to_json,
 } from "@namada/shared";
 import {
   BondMsgValue,
@@ -14,18 +28,26 @@ import {
   EthBridgeTransferProps,
   IbcTransferMsgValue,
   IbcTransferProps,
+  InitAccountMsgValue,
+  InitAccountProps,
+  MaxUnshieldingTransferMsgValue,
+  MaxUnshieldingTransferProps,
   Message,
   RedelegateMsgValue,
   RedelegateProps,
   RevealPkMsgValue,
   ShieldedTransferMsgValue,
   ShieldedTransferProps,
-  ShieldingTransferMsgValue,
   ShieldingTransferProps,
   SignatureMsgValue,
+  SigningDataMsgValue,
+  SigningDataProps,
   SupportedTxProps,
+  TransferKindMsgValue,
+  TransferKindProps,
   TransferMsgValue,
-  TransparentTransferMsgValue,
+  TransferTemplateMsgValue,
+  TransferTemplateProps,
   TransparentTransferProps,
   TxDetails,
   TxDetailsMsgValue,
@@ -33,18 +55,41 @@ import {
   TxProps,
   UnbondMsgValue,
   UnbondProps,
-  UnshieldingTransferMsgValue,
   UnshieldingTransferProps,
+  UpdateAccountMsgValue,
+  UpdateAccountProps,
+  ValidatorMaintenanceMsgValue,
+  ValidatorMaintenanceProps,
   VoteProposalMsgValue,
   VoteProposalProps,
   WithdrawMsgValue,
   WithdrawProps,
   WrapperTxMsgValue,
   WrapperTxProps,
+  transferKindMsgValue,
 } from "@namada/types";
 import { ResponseSign } from "@zondax/ledger-namada";
 import BigNumber from "bignumber.js";
 import { WasmHash } from "../rpc";
+import {
+  AmountCheck,
+  AmountLimits,
+  CompoundDecision,
+  CompoundParams,
+  EmergencySweep,
+  IbcMemo,
+  IbcMemoFields,
+  IbcTimeouts,
+  IntentMismatch,
+  MaspDescriptions,
+  MaspSigningPlan,
+  QrPayloadKind,
+  RebalancePlan,
+  RequiredSignatures,
+  SigningRequest,
+  TransferIntent,
+  TransparentSweep,
+} from "./types";
 
 /**
  * SDK functionality related to transactions
@@ -55,26 +100,176 @@ export class Tx {
    */
   constructor(protected readonly sdk: SdkWasm) {}
 
+  /**
+   * Build Transfer Tx of any kind
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param transfer - kind and properties of the transfer
+   * @param [retry] - reuse the proven build of a previous call with the same
+   * props, e.g. after a failed broadcast, while the chain still accepts it
+   * @returns promise that resolves to an TxMsgValue
+   */
+  async buildTransfer(
+    wrapperTxProps: WrapperTxProps,
+    transfer: TransferKindProps,
+    retry = false
+  ): Promise<TxMsgValue> {
+    const transferMsg = new Message<TransferKindMsgValue>();
+
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+    const encodedTransfer = transferMsg.encode(transferKindMsgValue(transfer));
+
+    const serializedTx = await this.sdk.build_transfer(
+      encodedTransfer,
+      encodedWrapperArgs,
+      retry
+    );
+    return deserialize(Buffer.from(serializedTx), TxMsgValue);
+  }
+
+  /**
+   * Set limits above which transfers built with buildTransferWithAmountCheck
+   * require an explicit confirmation
+   * @param [limits] - limits, or undefined to remove them
+   * @returns void
+   */
+  setAmountLimits(limits?: AmountLimits): void {
+    this.sdk.set_amount_limits(limits ?? null);
+  }
+
+  /**
+   * Validate amounts of shielded, shielding and unshielding transfers against
+   * the denomination of their tokens before building, so precision errors
+   * fail before generating proofs
+   * @param enabled - true to validate amounts
+   * @returns void
+   */
+  setAmountValidation(enabled: boolean): void {
+    this.sdk.set_amount_validation(enabled);
+  }
+
+  /**
+   * Drop the proven shielded and unshielding transfers kept for retries.
+   * Builds of the same props are reused by buildTransfer with retry set while
+   * the chain still accepts them, so retrying after a failed broadcast doesn't
+   * generate the proofs again.
+   * @returns void
+   */
+  clearBuildCache(): void {
+    this.sdk.clear_build_cache();
+  }
+
+  /**
+   * Validate an amount against the denomination of a token
+   * @async
+   * @param token - address of the token
+   * @param amount - amount to validate
+   * @throws {Error} - if the amount has more decimal places than the token
+   * @returns promise that resolves to the amount with the token decimal places
+   */
+  async validateAmount(token: string, amount: BigNumber): Promise<BigNumber> {
+    return BigNumber(await this.sdk.validate_amount(token, amount.toFixed()));
+  }
+
+  /**
+   * Build Transfer Tx of any kind, checking the amounts sent against the limits
+   * set with setAmountLimits
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param transfer - kind and properties of the transfer
+   * @returns promise that resolves to the TxMsgValue and the result of the check
+   */
+  async buildTransferWithAmountCheck(
+    wrapperTxProps: WrapperTxProps,
+    transfer: TransferKindProps
+  ): Promise<{ tx: TxMsgValue; amountCheck: AmountCheck }> {
+    const transferMsg = new Message<TransferKindMsgValue>();
+
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+    const encodedTransfer = transferMsg.encode(transferKindMsgValue(transfer));
+
+    const [serializedTx, amountCheck] =
+      (await this.sdk.build_transfer_with_amount_check(
+        encodedTransfer,
+        encodedWrapperArgs
+      )) as [number[], AmountCheck];
+
+    return {
+      tx: deserialize(Buffer.from(serializedTx), TxMsgValue),
+      amountCheck,
+    };
+  }
+
   /**
    * Build Transparent Transfer Tx
    * @async
    * @param wrapperTxProps - properties of the transaction
    * @param transferProps -  properties of the transfer
    * @returns promise that resolves to an TxMsgValue
+   * @deprecated Use buildTransfer
    */
   async buildTransparentTransfer(
     wrapperTxProps: WrapperTxProps,
     transferProps: TransparentTransferProps
   ): Promise<TxMsgValue> {
-    const transferMsg = new Message<TransparentTransferMsgValue>();
+    return await this.buildTransfer(wrapperTxProps, {
+      kind: "transparent",
+      props: transferProps,
+    });
+  }
 
-    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
-    const encodedTransfer = transferMsg.encode(
-      new TransparentTransferMsgValue(transferProps)
+  /**
+   * Save a transfer template, replacing a template with the same id
+   * @async
+   * @param templateProps - properties of the template
+   * @returns void
+   */
+  async saveTransferTemplate(
+    templateProps: TransferTemplateProps
+  ): Promise<void> {
+    const templateMsg = new Message<TransferTemplateMsgValue>();
+    const encodedTemplate = templateMsg.encode(
+      new TransferTemplateMsgValue(templateProps)
     );
 
-    const serializedTx = await this.sdk.build_transparent_transfer(
-      encodedTransfer,
+    await this.sdk.save_transfer_template(encodedTemplate);
+  }
+
+  /**
+   * Remove a transfer template
+   * @async
+   * @param id - template id
+   * @returns void
+   */
+  async removeTransferTemplate(id: string): Promise<void> {
+    await this.sdk.remove_transfer_template(id);
+  }
+
+  /**
+   * Return all saved transfer templates
+   * @async
+   * @returns promise that resolves to an array of templates
+   */
+  async transferTemplates(): Promise<TransferTemplateProps[]> {
+    return await this.sdk.transfer_templates();
+  }
+
+  /**
+   * Build Transparent Transfer Tx from a saved template, resolving its amount against
+   * the current balance of the source
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param id - template id
+   * @returns promise that resolves to an TxMsgValue
+   */
+  async buildTransferFromTemplate(
+    wrapperTxProps: WrapperTxProps,
+    id: string
+  ): Promise<TxMsgValue> {
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+
+    const serializedTx = await this.sdk.build_transfer_from_template(
+      id,
       encodedWrapperArgs
     );
     return deserialize(Buffer.from(serializedTx), TxMsgValue);
@@ -86,11 +281,29 @@ export class Tx {
    * @param wrapperTxProps - properties of the transaction
    * @param shieldedTransferProps -  properties of the shielded transfer
    * @returns promise that resolves to an TxMsgValue
+   * @deprecated Use buildTransfer
    */
   async buildShieldedTransfer(
     wrapperTxProps: WrapperTxProps,
     shieldedTransferProps: ShieldedTransferProps
   ): Promise<TxMsgValue> {
+    return await this.buildTransfer(wrapperTxProps, {
+      kind: "shielded",
+      props: shieldedTransferProps,
+    });
+  }
+
+  /**
+   * Build Shielded Transfer Tx sending change to freshly derived payment addresses
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param shieldedTransferProps -  properties of the shielded transfer
+   * @returns promise that resolves to the TxMsgValue and derived change addresses
+   */
+  async buildShieldedTransferWithChangeRotation(
+    wrapperTxProps: WrapperTxProps,
+    shieldedTransferProps: ShieldedTransferProps
+  ): Promise<{ tx: TxMsgValue; changeAddresses: string[] }> {
     const shieldedTransferMsg = new Message<ShieldedTransferMsgValue>();
 
     const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
@@ -98,11 +311,15 @@ export class Tx {
       new ShieldedTransferMsgValue(shieldedTransferProps)
     );
 
-    const serializedTx = await this.sdk.build_shielded_transfer(
-      encodedTransfer,
-      encodedWrapperArgs
-    );
-    return deserialize(Buffer.from(serializedTx), TxMsgValue);
+    const [serializedTx, changeAddresses] =
+      (await this.sdk.build_shielded_transfer_with_change_rotation(
+        encodedTransfer,
+        encodedWrapperArgs
+      )) as [number[], string[]];
+    return {
+      tx: deserialize(Buffer.from(serializedTx), TxMsgValue),
+      changeAddresses,
+    };
   }
 
   /**
@@ -111,51 +328,149 @@ export class Tx {
    * @param wrapperTxProps - properties of the transaction
    * @param shieldingTransferProps -  properties of the shielding transfer
    * @returns promise that resolves to an TxMsgValue
+   * @deprecated Use buildTransfer
    */
   async buildShieldingTransfer(
     wrapperTxProps: WrapperTxProps,
     shieldingTransferProps: ShieldingTransferProps
   ): Promise<TxMsgValue> {
-    const shieldingTransferMsg = new Message<ShieldingTransferMsgValue>();
+    return await this.buildTransfer(wrapperTxProps, {
+      kind: "shielding",
+      props: shieldingTransferProps,
+    });
+  }
+
+  /**
+   * Build Unshielding Transfer Tx
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param unshieldingTransferProps -  properties of the unshielding transfer
+   * @returns promise that resolves to an TxMsgValue
+   * @deprecated Use buildTransfer
+   */
+  async buildUnshieldingTransfer(
+    wrapperTxProps: WrapperTxProps,
+    unshieldingTransferProps: UnshieldingTransferProps
+  ): Promise<TxMsgValue> {
+    return await this.buildTransfer(wrapperTxProps, {
+      kind: "unshielding",
+      props: unshieldingTransferProps,
+    });
+  }
 
-    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+  /**
+   * Compute the maximum amount of a token that can be unshielded, after fees and conversions
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param maxUnshieldingTransferProps -  properties of the max unshielding transfer
+   * @returns promise that resolves to the denominated amount
+   */
+  async computeMaxUnshieldingAmount(
+    wrapperTxProps: WrapperTxProps,
+    maxUnshieldingTransferProps: MaxUnshieldingTransferProps
+  ): Promise<BigNumber> {
+    const maxUnshieldingTransferMsg =
+      new Message<MaxUnshieldingTransferMsgValue>();
 
-    const encodedTransfer = shieldingTransferMsg.encode(
-      new ShieldingTransferMsgValue(shieldingTransferProps)
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+    const encodedTransfer = maxUnshieldingTransferMsg.encode(
+      new MaxUnshieldingTransferMsgValue(maxUnshieldingTransferProps)
     );
 
-    const serializedTx = await this.sdk.build_shielding_transfer(
+    const amount = await this.sdk.compute_max_unshielding_amount(
       encodedTransfer,
       encodedWrapperArgs
     );
-    return deserialize(Buffer.from(serializedTx), TxMsgValue);
+    return new BigNumber(amount);
   }
 
   /**
-   * Build Unshielding Transfer Tx
+   * Build Unshielding Transfer Tx of the maximum spendable amount of a token
    * @async
    * @param wrapperTxProps - properties of the transaction
-   * @param unshieldingTransferProps -  properties of the unshielding transfer
+   * @param maxUnshieldingTransferProps -  properties of the max unshielding transfer
    * @returns promise that resolves to an TxMsgValue
    */
-  async buildUnshieldingTransfer(
+  async buildMaxUnshieldingTransfer(
     wrapperTxProps: WrapperTxProps,
-    unshieldingTransferProps: UnshieldingTransferProps
+    maxUnshieldingTransferProps: MaxUnshieldingTransferProps
   ): Promise<TxMsgValue> {
-    const shieldingTransferMsg = new Message<UnshieldingTransferMsgValue>();
+    const maxUnshieldingTransferMsg =
+      new Message<MaxUnshieldingTransferMsgValue>();
 
     const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
-    const encodedTransfer = shieldingTransferMsg.encode(
-      new UnshieldingTransferMsgValue(unshieldingTransferProps)
+    const encodedTransfer = maxUnshieldingTransferMsg.encode(
+      new MaxUnshieldingTransferMsgValue(maxUnshieldingTransferProps)
     );
 
-    const serializedTx = await this.sdk.build_unshielding_transfer(
+    const serializedTx = await this.sdk.build_max_unshielding_transfer(
       encodedTransfer,
       encodedWrapperArgs
     );
     return deserialize(Buffer.from(serializedTx), TxMsgValue);
   }
 
+  /**
+   * Build the next tx sweeping the whole shielded balance of a compromised
+   * spending key to a safe target, the fees being paid by the swept key. Call
+   * again once the tx is applied and the key synced while tokens remain
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param spendingKey - spending key to sweep
+   * @param target - payment address or transparent address
+   * @returns promise that resolves to the tx and swept tokens
+   */
+  async buildEmergencySweep(
+    wrapperTxProps: WrapperTxProps,
+    spendingKey: string,
+    target: string
+  ): Promise<EmergencySweep> {
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+    const [serializedTx, swept, remaining] =
+      (await this.sdk.build_emergency_sweep(
+        spendingKey,
+        target,
+        encodedWrapperArgs
+      )) as [number[], [string, string][], number];
+
+    return {
+      tx: deserialize(Buffer.from(serializedTx), TxMsgValue),
+      swept,
+      remaining,
+    };
+  }
+
+  /**
+   * Build a single transfer tx draining the balances of tokens held by a
+   * transparent address, e.g. to migrate an account. The fee is left out of
+   * the fee token balance when the address pays it
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param source - swept transparent address
+   * @param target - target address
+   * @param tokens - tokens to sweep, tokens without balance are skipped
+   * @returns promise that resolves to the tx and swept tokens
+   */
+  async buildTransparentSweep(
+    wrapperTxProps: WrapperTxProps,
+    source: string,
+    target: string,
+    tokens: string[]
+  ): Promise<TransparentSweep> {
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+    const [serializedTx, swept] = (await this.sdk.build_transparent_sweep(
+      source,
+      target,
+      tokens,
+      encodedWrapperArgs
+    )) as [number[], [string, string][]];
+
+    return {
+      tx: deserialize(Buffer.from(serializedTx), TxMsgValue),
+      swept,
+    };
+  }
+
   /**
    * Build RevealPK Tx
    * @async
@@ -255,6 +570,67 @@ export class Tx {
     return deserialize(Buffer.from(serializedTx), TxMsgValue);
   }
 
+  /**
+   * Build a single batch Tx of validator maintenance changes, i.e. commission
+   * rate, metadata and consensus key, paying one wrapper fee. Changes left out
+   * are not part of the batch. A new consensus key has to sign the Tx too.
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param validatorMaintenanceProps - changes to the validator
+   * @returns promise that resolves to an TxMsgValue
+   */
+  async buildValidatorMaintenance(
+    wrapperTxProps: WrapperTxProps,
+    validatorMaintenanceProps: ValidatorMaintenanceProps
+  ): Promise<TxMsgValue> {
+    const validatorMaintenanceMsg = new Message<ValidatorMaintenanceMsgValue>();
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+    const encodedValidatorMaintenance = validatorMaintenanceMsg.encode(
+      new ValidatorMaintenanceMsgValue(validatorMaintenanceProps)
+    );
+    const serializedTx = await this.sdk.build_validator_maintenance(
+      encodedValidatorMaintenance,
+      encodedWrapperArgs
+    );
+    return deserialize(Buffer.from(serializedTx), TxMsgValue);
+  }
+
+  /**
+   * Plan the redelegations, bonds and unbonds moving the bonds of an owner to
+   * target weights per validator
+   * @async
+   * @param owner - address of the delegator
+   * @param targets - weights by validator address, e.g. { [validator]: 0.5 }
+   * @param [total] - amount in NAM to have bonded, defaults to the current total
+   * @returns promise that resolves to the plan
+   */
+  async planRebalance(
+    owner: string,
+    targets: Record<string, number>,
+    total?: string
+  ): Promise<RebalancePlan> {
+    return await this.sdk.plan_rebalance(owner, targets, total);
+  }
+
+  /**
+   * Build a single batch Tx of a rebalance plan
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param plan - plan returned by planRebalance
+   * @returns promise that resolves to an TxMsgValue
+   */
+  async buildRebalance(
+    wrapperTxProps: WrapperTxProps,
+    plan: RebalancePlan
+  ): Promise<TxMsgValue> {
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+    const serializedTx = await this.sdk.build_rebalance(
+      plan,
+      encodedWrapperArgs
+    );
+    return deserialize(Buffer.from(serializedTx), TxMsgValue);
+  }
+
   /**
    * Build Ibc Transfer Tx
    * `ibcTransferProps.amountInBaseDenom` is the amount in the **base** denom
@@ -268,16 +644,50 @@ export class Tx {
     wrapperTxProps: WrapperTxProps,
     ibcTransferProps: IbcTransferProps
   ): Promise<TxMsgValue> {
+    const { tx } = await this.buildIbcTransferWithTimeouts(
+      wrapperTxProps,
+      ibcTransferProps
+    );
+    return tx;
+  }
+
+  /**
+   * Build Ibc Transfer Tx, returning the timeouts it was built with
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param ibcTransferProps - properties of the ibc transfer tx
+   * @returns promise that resolves to the TxMsgValue and its timeouts
+   */
+  async buildIbcTransferWithTimeouts(
+    wrapperTxProps: WrapperTxProps,
+    ibcTransferProps: IbcTransferProps
+  ): Promise<{ tx: TxMsgValue; timeouts: IbcTimeouts }> {
     const ibcTransferMsg = new Message<IbcTransferProps>();
     const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
     const encodedIbcTransfer = ibcTransferMsg.encode(
       new IbcTransferMsgValue(ibcTransferProps)
     );
-    const serializedTx = await this.sdk.build_ibc_transfer(
+    const [serializedTx, timeouts] = (await this.sdk.build_ibc_transfer(
       encodedIbcTransfer,
       encodedWrapperArgs
-    );
-    return deserialize(Buffer.from(serializedTx), TxMsgValue);
+    )) as [
+      number[],
+      { timeoutHeight: number | null; timeoutSecOffset: number | null },
+    ];
+
+    return {
+      tx: deserialize(Buffer.from(serializedTx), TxMsgValue),
+      timeouts: {
+        timeoutHeight:
+          timeouts.timeoutHeight === null ?
+            undefined
+          : BigInt(timeouts.timeoutHeight),
+        timeoutSecOffset:
+          timeouts.timeoutSecOffset === null ?
+            undefined
+          : BigInt(timeouts.timeoutSecOffset),
+      },
+    };
   }
 
   /**
@@ -303,6 +713,29 @@ export class Tx {
     return deserialize(Buffer.from(serializedTx), TxMsgValue);
   }
 
+  /**
+   * Check that an Ethereum Bridge Transfer would be accepted, i.e. the asset is
+   * whitelisted and wrapped NAM transfers are within the cap
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param ethBridgeTransferProps - properties of the eth bridge transfer tx
+   * @throws Error describing why the transfer would be rejected
+   */
+  async validateEthBridgeTransfer(
+    wrapperTxProps: WrapperTxProps,
+    ethBridgeTransferProps: EthBridgeTransferProps
+  ): Promise<void> {
+    const ethBridgeTransferMsg = new Message<EthBridgeTransferProps>();
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+    const encodedEthBridgeTransfer = ethBridgeTransferMsg.encode(
+      new EthBridgeTransferMsgValue(ethBridgeTransferProps)
+    );
+    await this.sdk.validate_eth_bridge_transfer(
+      encodedEthBridgeTransfer,
+      encodedWrapperArgs
+    );
+  }
+
   /**
    * Build Vote Proposal Tx
    * @async
@@ -327,6 +760,54 @@ export class Tx {
     return deserialize(Buffer.from(serializedTx), TxMsgValue);
   }
 
+  /**
+   * Build Init Account Tx, creating an established account controlled by a
+   * set of public keys
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param initAccountProps - public keys and threshold of the account
+   * @returns promise that resolves to an TxMsgValue
+   */
+  async buildInitAccount(
+    wrapperTxProps: WrapperTxProps,
+    initAccountProps: InitAccountProps
+  ): Promise<TxMsgValue> {
+    const initAccountMsg = new Message<InitAccountProps>();
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+    const encodedInitAccount = initAccountMsg.encode(
+      new InitAccountMsgValue(initAccountProps)
+    );
+    const serializedTx = await this.sdk.build_init_account(
+      encodedInitAccount,
+      encodedWrapperArgs
+    );
+    return deserialize(Buffer.from(serializedTx), TxMsgValue);
+  }
+
+  /**
+   * Build Update Account Tx, replacing the public keys or the threshold of an
+   * established account
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param updateAccountProps - address, new public keys and new threshold
+   * @returns promise that resolves to an TxMsgValue
+   */
+  async buildUpdateAccount(
+    wrapperTxProps: WrapperTxProps,
+    updateAccountProps: UpdateAccountProps
+  ): Promise<TxMsgValue> {
+    const updateAccountMsg = new Message<UpdateAccountProps>();
+    const encodedWrapperArgs = this.encodeTxArgs(wrapperTxProps);
+    const encodedUpdateAccount = updateAccountMsg.encode(
+      new UpdateAccountMsgValue(updateAccountProps)
+    );
+    const serializedTx = await this.sdk.build_update_account(
+      encodedUpdateAccount,
+      encodedWrapperArgs
+    );
+    return deserialize(Buffer.from(serializedTx), TxMsgValue);
+  }
+
   /**
    * Build Claim Rewards Tx
    * @async
@@ -350,6 +831,53 @@ export class Tx {
     return deserialize(Buffer.from(serializedTx), TxMsgValue);
   }
 
+  /**
+   * Decide whether to compound staking rewards now, i.e. claim and bond them
+   * back in one batch
+   * @param params - claimable rewards, threshold and fee estimate
+   * @returns the decision, with the claims and bonds of the batch
+   */
+  compoundDecision(params: CompoundParams): CompoundDecision {
+    return compound_decision(params);
+  }
+
+  /**
+   * Build the claim + bond batch of a compound decision
+   * @async
+   * @param wrapperTxProps - properties of the transaction
+   * @param owner - address of the delegator
+   * @param decision - decision returned by compoundDecision
+   * @returns promise that resolves to the batch
+   */
+  async buildCompound(
+    wrapperTxProps: WrapperTxProps,
+    owner: string,
+    decision: CompoundDecision
+  ): Promise<TxProps> {
+    if (!decision.compound) {
+      throw new Error(`Nothing to compound: ${decision.reason}`);
+    }
+    const txs: TxProps[] = [];
+    for (const validator of decision.claims) {
+      txs.push(
+        await this.buildClaimRewards(wrapperTxProps, {
+          validator,
+          source: owner,
+        })
+      );
+    }
+    for (const { validator, amount } of decision.bonds) {
+      txs.push(
+        await this.buildBond(wrapperTxProps, {
+          source: owner,
+          validator,
+          amount: new BigNumber(amount),
+        })
+      );
+    }
+    return this.buildBatch(txs);
+  }
+
   /**
    * Build a batched transaction
    * @param txs - array of TxProp
@@ -362,19 +890,248 @@ export class Tx {
       return msg.encode(txMsgValue);
     });
 
-    const batch = SdkWasm.build_batch(encodedTxs.map((tx) => [...tx]));
+    const batch = SdkWasm.build_batch(encodedTxs);
     return deserialize(Buffer.from(batch), TxMsgValue);
   }
 
   /**
-   * Append signature for transactions signed by Ledger Hardware Wallet
+   * Check that a shielded or unshielding transfer is still accepted by the
+   * chain before signing it, rebuilding it from the same props if the MASP
+   * epoch changed, its anchor is unknown or its wrapper expired
+   * @async
+   * @param tx - transfer built by this Tx instance
+   * @param [onRebuild] - called with the reason, "epoch", "anchor" or
+   * "expired", before rebuilding, so the UI can tell about the delay
+   * @throws {Error} - if the notes of the transfer were spent since it was built
+   * @returns promise that resolves to the transfer to sign
+   */
+  async refreshShieldedTx(
+    tx: TxProps,
+    onRebuild?: (reason: "epoch" | "anchor" | "expired") => void
+  ): Promise<TxMsgValue> {
+    const msg = new Message<TxMsgValue>();
+    const encodedTx = msg.encode(new TxMsgValue(tx));
+
+    const refreshed = await this.sdk.refresh_shielded_tx(encodedTx, onRebuild);
+    return deserialize(Buffer.from(refreshed), TxMsgValue);
+  }
+
+  /**
+   * Return the numbers of spends, converts and outputs of the MASP
+   * transactions of a transfer built by this Tx instance, e.g. to estimate a
+   * rebuild with Rpc.estimateMaspTx
+   * @param tx - built transfer
+   * @returns numbers of descriptions
+   */
+  maspDescriptions(tx: TxProps): MaspDescriptions {
+    const msg = new Message<TxMsgValue>();
+    const encodedTx = msg.encode(new TxMsgValue(tx));
+
+    return this.sdk.masp_descriptions(encodedTx);
+  }
+
+  /**
+   * Attach a MASP transaction built elsewhere, e.g. in a worker, to an unsigned transfer
+   * @param tx - built transfer
+   * @param shieldedSectionHash - Borsh serialized id of the MASP transaction
+   * @param maspSection - Borsh serialized MASP transaction
+   * @returns the transfer with the MASP section attached
+   */
+  attachMaspSection(
+    tx: TxProps,
+    shieldedSectionHash: Uint8Array,
+    maspSection: Uint8Array
+  ): TxProps {
+    const msg = new Message<TxMsgValue>();
+    const encodedTx = msg.encode(new TxMsgValue(tx));
+
+    const attached = SdkWasm.attach_masp_section(
+      encodedTx,
+      shieldedSectionHash,
+      maspSection
+    );
+    return deserialize(Buffer.from(attached), TxMsgValue);
+  }
+
+  /**
+   * List the sections of a built tx
+   * @param tx - built tx
+   * @returns kind and hash of every section, in order
+   */
+  txSections(tx: TxProps): [string, string][] {
+    const msg = new Message<TxMsgValue>();
+    return SdkWasm.tx_sections(msg.encode(new TxMsgValue(tx)));
+  }
+
+  /**
+   * Get the signing plans of the MASP transactions of a built tx, for signers
+   * authorizing spends without the MASP builder, e.g. a Ledger
+   * @param tx - built tx
+   * @throws {Error} - if a MASP transaction has no matching builder section
+   * @returns the sighash and spends to authorize of every MASP transaction
+   */
+  maspSigningPlans(tx: TxProps): MaspSigningPlan[] {
+    const msg = new Message<TxMsgValue>();
+    return SdkWasm.masp_signing_plans(msg.encode(new TxMsgValue(tx)));
+  }
+
+  /**
+   * Apply spend authorization signatures made from a signing plan to a built
+   * tx. The signed MASP transaction is verified, recomputing its binding
+   * signature with the binding signing key if needed. Requires MASP params to
+   * be loaded.
+   * @async
+   * @param tx - built tx
+   * @param plan - signing plan of the MASP transaction, see maspSigningPlans
+   * @param signatures - [position of the spend, hex encoded signature][]
+   * @param [bsk] - binding signing key of the bundle
+   * @throws {Error} - if a signature is invalid or the signed transaction
+   * doesn't verify
+   * @returns promise that resolves to the signed tx
+   */
+  async applySpendSignatures(
+    tx: TxProps,
+    plan: MaspSigningPlan,
+    signatures: [number, string][],
+    bsk?: Uint8Array
+  ): Promise<TxMsgValue> {
+    const msg = new Message<TxMsgValue>();
+    const signed = await this.sdk.apply_spend_signatures(
+      msg.encode(new TxMsgValue(tx)),
+      Uint8Array.from(plan.maspTxId),
+      signatures,
+      bsk
+    );
+    return deserialize(Buffer.from(signed), TxMsgValue);
+  }
+
+  /**
+   * Add an extra data section to a built tx. The wrapper signature, if any, is
+   * dropped and has to be added again.
+   * @param tx - built tx
+   * @param data - content of the section
+   * @returns the updated tx and the hash of the section
+   */
+  addTxExtraData(
+    tx: TxProps,
+    data: Uint8Array
+  ): { tx: TxMsgValue; sectionHash: string } {
+    const msg = new Message<TxMsgValue>();
+    const [updated, sectionHash] = SdkWasm.add_tx_extra_data(
+      msg.encode(new TxMsgValue(tx)),
+      data
+    ) as [number[], string];
+    return {
+      tx: deserialize(Buffer.from(updated), TxMsgValue),
+      sectionHash,
+    };
+  }
+
+  /**
+   * Remove a section of a built tx along with the signatures covering it
+   * @param tx - built tx
+   * @param sectionHash - hash of the section, as listed by txSections
+   * @throws {Error} - if the section is committed to by an inner tx
+   * @returns the updated tx
+   */
+  removeTxSection(tx: TxProps, sectionHash: string): TxMsgValue {
+    const msg = new Message<TxMsgValue>();
+    const [updated] = SdkWasm.remove_tx_section(
+      msg.encode(new TxMsgValue(tx)),
+      sectionHash
+    ) as [number[], string];
+    return deserialize(Buffer.from(updated), TxMsgValue);
+  }
+
+  /**
+   * Append signature for transactions signed by Ledger Hardware Wallet. The
+   * counterparties of the tx are screened first, see setScreeningList
+   * @async
    * @param txBytes - Serialized transaction
    * @param ledgerSignatureResponse - Serialized signature as returned from Ledger
    * @returns - Serialized Tx bytes with signature appended
    */
-  appendSignature(
+  async appendSignature(
     txBytes: Uint8Array,
     ledgerSignatureResponse: ResponseSign
+  ): Promise<Uint8Array> {
+    const encodedSignature = this.encodeLedgerSignature(
+      ledgerSignatureResponse
+    );
+    return await this.sdk.append_signature(txBytes, encodedSignature);
+  }
+
+  /**
+   * Split a built tx, or the SignatureMsg of an air-gapped device, into the
+   * parts of an animated QR code
+   * @param kind - Kind of the payload
+   * @param payload - Serialized tx or SignatureMsg
+   * @param [fragmentLength] - Payload bytes per part, 200 by default
+   * @returns Upper case parts, in the QR alphanumeric charset
+   */
+  encodeQrParts(
+    kind: QrPayloadKind,
+    payload: Uint8Array,
+    fragmentLength?: number
+  ): string[] {
+    return encode_qr_parts(kind, payload, fragmentLength);
+  }
+
+  /**
+   * Create a decoder of scanned QR parts. Pass each scan to receive, which
+   * returns a QrDecodeProgress, then read the payload with result once
+   * complete. Scanned SignatureMsgs are appended with sdk.append_signature.
+   * @returns QrPartsDecoder, to be freed after use
+   */
+  qrPartsDecoder(): QrPartsDecoder {
+    return new QrPartsDecoder();
+  }
+
+  /**
+   * Plan signing a batch of independent txs with a hardware wallet, ordering
+   * them so txs of the same key are approved one after the other
+   * @param txs - built txs
+   * @returns signing requests in approval order, pass txBytes to Ledger.sign
+   */
+  planBatchSigning(txs: TxProps[]): SigningRequest[] {
+    const msg = new Message<TxMsgValue>();
+    const requests = plan_batch_signing(
+      txs.map((tx) => msg.encode(new TxMsgValue(tx)))
+    ) as (Omit<SigningRequest, "txBytes"> & { txBytes: number[] })[];
+
+    return requests.map((request) => ({
+      ...request,
+      txBytes: new Uint8Array(request.txBytes),
+    }));
+  }
+
+  /**
+   * Append signatures returned by Ledger to a batch of txs, in any order. The
+   * counterparties of the txs are screened first, see setScreeningList
+   * @async
+   * @param txs - built txs, as passed to planBatchSigning
+   * @param signatures - wrapper hash of each signing request and its response
+   * @returns signed tx bytes in the order of txs
+   */
+  async appendBatchSignatures(
+    txs: TxProps[],
+    signatures: { hash: string; response: ResponseSign }[]
+  ): Promise<Uint8Array[]> {
+    const msg = new Message<TxMsgValue>();
+    return await this.sdk.apply_batch_signatures(
+      txs.map((tx) => msg.encode(new TxMsgValue(tx))),
+      signatures.map(({ hash }) => hash),
+      signatures.map(({ response }) => this.encodeLedgerSignature(response))
+    );
+  }
+
+  /**
+   * Helper to encode a signature returned by Ledger into a SignatureMsgValue
+   * @param ledgerSignatureResponse - signature as returned from Ledger
+   * @returns Serialized SignatureMsgValue
+   */
+  private encodeLedgerSignature(
+    ledgerSignatureResponse: ResponseSign
   ): Uint8Array {
     const { signature } = ledgerSignatureResponse;
     if (!signature) {
@@ -403,9 +1160,7 @@ export class Tx {
     // Serialize signature
     const value = new SignatureMsgValue(props);
     const msg = new Message<SignatureMsgValue>();
-    const encodedSignature = msg.encode(value);
-
-    return this.sdk.append_signature(txBytes, encodedSignature);
+    return msg.encode(value);
   }
 
   /**
@@ -423,11 +1178,17 @@ export class Tx {
    * Method to retrieve JSON strings for all commitments of a Tx
    * @param txBytes - Bytes of a transaction
    * @param checksums - Record of paths mapped to their respective hashes
+   * @param [priceCallback] - Optional callback returning fiat price of a token
+   * @param [nativeToken] - Native token address, used for fiat values of staking txs
+   * @param [origin] - Origin of the dApp requesting the signature, included hashed
    * @returns a TxDetails object
    */
   deserialize(
     txBytes: Uint8Array,
-    checksums: Record<string, string>
+    checksums: Record<string, string>,
+    priceCallback?: (token: string) => string | undefined,
+    nativeToken?: string,
+    origin?: string
   ): TxDetails {
     const wasmHashes: WasmHash[] = [];
     for (const path in checksums) {
@@ -436,8 +1197,17 @@ export class Tx {
         hash: checksums[path],
       });
     }
-    const tx = deserialize_tx(txBytes, wasmHashes);
-    const { wrapperTx, commitments } = deserialize(tx, TxDetailsMsgValue);
+    const tx = deserialize_tx(
+      txBytes,
+      wasmHashes,
+      priceCallback,
+      nativeToken,
+      origin
+    );
+    const { wrapperTx, commitments, originHash } = deserialize(
+      tx,
+      TxDetailsMsgValue
+    );
 
     const getProps = (txType: TxType, data: Uint8Array): SupportedTxProps => {
       switch (txType) {
@@ -466,14 +1236,22 @@ export class Tx {
 
     return {
       ...wrapperTx,
+      originHash,
       commitments: commitments.map(
-        ({ txType, hash, txCodeId, data, memo }) => ({
-          txType: txType as TxType,
-          hash,
-          txCodeId,
-          memo,
-          ...getProps(txType, data),
-        })
+        ({ txType, hash, txCodeId, data, memo, fiatValue }) => {
+          const props = getProps(txType, data);
+          const packetMemo = "memo" in props ? props.memo : undefined;
+          const shownMemo = memo || packetMemo;
+          return {
+            txType: txType as TxType,
+            hash,
+            txCodeId,
+            memo,
+            parsedMemo: shownMemo ? parse_memo(shownMemo) : undefined,
+            fiatValue,
+            ...props,
+          };
+        }
       ),
     };
   }
@@ -510,4 +1288,118 @@ export class Tx {
   getInnerTxHashes(bytes: Uint8Array): string[] {
     return get_inner_tx_hashes(bytes);
   }
+
+  /**
+   * Serialize signing data to JSON, so it can be passed to another context, e.g. the
+   * extension background script, and restored without rebuilding the Tx
+   * @param signingData - SigningDataProps of a built Tx
+   * @returns JSON string
+   */
+  signingDataToJson(signingData: SigningDataProps): string {
+    const msg = new Message<SigningDataMsgValue>();
+    const encoded = msg.encode(new SigningDataMsgValue(signingData));
+    return signing_data_to_json(encoded);
+  }
+
+  /**
+   * Restore signing data from JSON produced by signingDataToJson
+   * @param json - JSON string
+   * @returns SigningDataProps
+   */
+  signingDataFromJson(json: string): SigningDataProps {
+    const signingData = signing_data_from_json(json);
+    return deserialize(Buffer.from(signingData), SigningDataMsgValue);
+  }
+
+  /**
+   * List the signatures required by a built Tx, e.g. when the fee payer differs
+   * from the signers of the inner txs
+   * @param tx - built Tx
+   * @returns signers of the inner txs per owner and the fee payer
+   */
+  requiredSignatures(tx: TxProps): RequiredSignatures {
+    const msg = new Message<TxMsgValue>();
+    return required_signatures(msg.encode(new TxMsgValue(tx)));
+  }
+
+  /**
+   * Check that a built Tx is canonically serialized, i.e. decoding and encoding
+   * it again gives the same bytes
+   * @param tx - built Tx
+   * @returns names of the non-canonical parts, empty if none
+   */
+  auditSerialization(tx: TxProps): string[] {
+    const msg = new Message<TxMsgValue>();
+    return audit_tx_serialization(msg.encode(new TxMsgValue(tx)));
+  }
+
+  /**
+   * Compare the transfers of a Tx built for a dApp against the intent the dApp
+   * declared, so the approval UI can flag swapped payloads
+   * @param txBytes - Serialized Namada Tx
+   * @param checksums - wasm checksums, see deserialize
+   * @param intent - token, denominated amount and receiver declared by the dApp
+   * @returns list of mismatches, empty if the Tx matches the intent
+   */
+  checkTransferIntent(
+    txBytes: Uint8Array,
+    checksums: Record<string, string>,
+    intent: TransferIntent
+  ): IntentMismatch[] {
+    const wasmHashes: WasmHash[] = Object.entries(checksums).map(
+      ([path, hash]) => ({ path, hash })
+    );
+    return check_transfer_intent(
+      txBytes,
+      wasmHashes,
+      intent,
+      this.sdk.chain_prefixes()
+    );
+  }
+
+  /**
+   * Generate an X25519 key pair for receiving encrypted memos
+   * @returns hex encoded secret and public keys
+   */
+  generateMemoKeypair(): { secretKey: string; publicKey: string } {
+    const [secretKey, publicKey] = generate_memo_keypair() as [string, string];
+    return { secretKey, publicKey };
+  }
+
+  /**
+   * Encrypt a memo to a recipient. Set WrapperTxProps.memoRecipient instead to
+   * encrypt the memo of a built Tx
+   * @param memo - memo to encrypt
+   * @param recipientPublicKey - hex encoded X25519 public key
+   * @returns encrypted memo
+   */
+  encryptMemo(memo: string, recipientPublicKey: string): string {
+    return encrypt_memo(memo, recipientPublicKey);
+  }
+
+  /**
+   * Decrypt a memo encrypted to the public key of secretKey
+   * @param memo - encrypted memo
+   * @param secretKey - hex encoded X25519 secret key
+   * @returns decrypted memo
+   */
+  decryptMemo(memo: string, secretKey: string): string {
+    return decrypt_memo(memo, secretKey);
+  }
+
+  /**
+   * Build the memo of an IBC transfer from structured fields instead of a
+   * free-form string, so forwards and wasm hooks are checked to reach the
+   * receiver. Use the returned receiver and memo in IbcTransferProps
+   * @param receiver - final receiver of the transfer
+   * @param fields - forward hops, fallback receiver and wasm hook
+   * @returns receiver of the MsgTransfer and memo
+   */
+  buildIbcMemo(receiver: string, fields: IbcMemoFields): IbcMemo {
+    const { receiver: transferReceiver, memo } = build_ibc_memo(
+      receiver,
+      fields
+    ) as { receiver: string; memo: string };
+    return { receiver: transferReceiver, memo: memo || undefined };
+  }
 }
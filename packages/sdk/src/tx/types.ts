@@ -1,2 +1,247 @@
+import { TxMsgValue } from "@namada/types";
+
 export { TxType, TxTypeLabel } from "@namada/shared";
 export type { SupportedTx } from "@namada/shared";
+
+/**
+ * Shortfall of a token of a shielded source, with raw amounts
+ */
+export type AssetShortfall = {
+  token: string;
+  required: string;
+  available: string;
+  shortfall: string;
+  // [maspEpoch, amount] of notes not yet converted to the current epoch
+  unconverted: [string, string][];
+};
+
+/**
+ * Limits above which transfers require an explicit confirmation, see
+ * Tx.setAmountLimits. maxBalanceFraction is between 0 and 1, thresholds are
+ * denominated amounts by token address
+ */
+export type AmountLimits = {
+  maxBalanceFraction?: number;
+  thresholds?: Record<string, string>;
+};
+
+/**
+ * Amount sent by a transfer exceeding a limit. reference is the balance of the
+ * owner for balanceFraction, the threshold otherwise
+ */
+export type FlaggedAmount = {
+  owner: string;
+  token: string;
+  amount: string;
+  limit: "balanceFraction" | "threshold";
+  reference: string;
+};
+
+export type AmountCheck = {
+  requiresConfirmation: boolean;
+  flagged: FlaggedAmount[];
+};
+
+/**
+ * Timeouts an IBC transfer was built with. timeoutSecOffset is defaulted from the
+ * counterparty client of the channel when no timeouts are given
+ */
+export type IbcTimeouts = {
+  timeoutHeight?: bigint;
+  timeoutSecOffset?: bigint;
+};
+
+/**
+ * Signatures required by a built Tx. threshold of publicKeys have to sign the
+ * inner txs of each owner, the fee payer signs the wrapper
+ */
+export type RequiredSignatures = {
+  inner: {
+    owner: string | null;
+    publicKeys: string[];
+    threshold: number;
+  }[];
+  feePayer: string;
+};
+
+/**
+ * Transfer a dApp declares it requests, amount being denominated
+ */
+export type TransferIntent = {
+  token: string;
+  amount: string;
+  receiver: string;
+  // Denomination of the token, to compare IBC packet amounts in the smallest unit
+  denomination?: number;
+};
+
+/**
+ * Difference between a declared TransferIntent and the Tx. commitment is the
+ * inner tx hash, null when the Tx contains no transfer
+ */
+export type IntentMismatch = {
+  commitment: string | null;
+  field: "receiver" | "token" | "amount" | "txType";
+  declared: string;
+  actual: string;
+};
+
+export type MaspBuildError = {
+  kind: "insufficientFunds";
+  shortfalls: AssetShortfall[];
+};
+
+/**
+ * Parse the detailed error thrown when building a MASP Tx fails
+ * @param error - error thrown by a build function
+ * @returns MaspBuildError, or undefined if the error is not a MaspBuildError
+ */
+export const parseMaspBuildError = (
+  error: unknown
+): MaspBuildError | undefined => {
+  const message = error instanceof Error ? error.message : String(error);
+  try {
+    const parsed = JSON.parse(message);
+    return parsed?.kind === "insufficientFunds" ? parsed : undefined;
+  } catch {
+    return undefined;
+  }
+};
+
+/**
+ * Tx of a batch to be approved on a hardware wallet, see Tx.planBatchSigning.
+ * index is the position of the tx in the batch, hash its wrapper hash
+ */
+export type SigningRequest = {
+  index: number;
+  hash: string;
+  signer: string;
+  txBytes: Uint8Array;
+};
+
+/**
+ * Change of the bonds of a validator, amount in NAM
+ */
+export type StakeChange = {
+  validator: string;
+  amount: string;
+};
+
+/**
+ * Messages moving bonds to target weights, see Tx.planRebalance. blocked are
+ * surpluses which can't be redelegated yet, as they were redelegated recently
+ */
+export type RebalancePlan = {
+  owner: string;
+  unbonds: StakeChange[];
+  redelegations: { source: string; destination: string; amount: string }[];
+  bonds: StakeChange[];
+  blocked: StakeChange[];
+};
+
+/**
+ * Claimable rewards and compounding settings, amounts in NAM. fee is the
+ * estimated fee of the claim + bond batch, rewards below threshold after fees
+ * are left to accumulate
+ */
+export type CompoundParams = {
+  rewards: { validator: string; amount: string }[];
+  threshold: string;
+  fee?: string;
+  epoch?: number;
+  lastCompoundedEpoch?: number;
+  minEpochsBetween?: number;
+};
+
+/**
+ * Whether to compound rewards now, see Tx.compoundDecision
+ */
+export type CompoundDecision = {
+  compound: boolean;
+  reason: "ready" | "noRewards" | "belowThreshold" | "tooSoon";
+  total: string;
+  claims: string[];
+  bonds: StakeChange[];
+};
+
+/**
+ * Payload of an animated QR code for air-gapped signing, a built tx or the
+ * SignatureMsg returned by the signing device
+ */
+export type QrPayloadKind = "tx" | "signature";
+
+/**
+ * Parts of an animated QR code scanned so far, see Tx.qrPartsDecoder
+ */
+export type QrDecodeProgress = {
+  kind: QrPayloadKind;
+  received: number;
+  total: number;
+  complete: boolean;
+};
+
+/**
+ * Next tx of an emergency sweep, see Tx.buildEmergencySweep. swept holds
+ * [token, denominated amount] pairs, remaining the number of tokens left for
+ * further sweeps once the tx is applied
+ */
+export type EmergencySweep = {
+  tx: TxMsgValue;
+  swept: [string, string][];
+  remaining: number;
+};
+
+/**
+ * Transparent sweep tx, see Tx.buildTransparentSweep. swept holds
+ * [token, denominated amount] pairs
+ */
+export type TransparentSweep = {
+  tx: TxMsgValue;
+  swept: [string, string][];
+};
+
+/**
+ * Spend authorizations of a MASP transaction, see Tx.maspSigningPlans.
+ * spends maps the position of each spend description in the transaction to the
+ * builder input and viewing key it belongs to
+ */
+export type MaspSigningPlan = {
+  maspTxId: number[];
+  sighash: string;
+  spends: {
+    txIndex: number;
+    builderIndex: number;
+    viewingKey: string;
+  }[];
+};
+
+/**
+ * Numbers of descriptions of the MASP transactions of a transfer, see
+ * Tx.maspDescriptions
+ */
+export type MaspDescriptions = {
+  spends: number;
+  converts: number;
+  outputs: number;
+};
+
+/**
+ * Structured fields of an IBC transfer memo, see Tx.buildIbcMemo. The receiver
+ * of each forward hop is on the chain it forwards to, the last one being the
+ * final receiver. fallbackReceiver receives the transfer on the first chain
+ * and keeps the funds if forwarding fails. wasm calls a contract through
+ * ibc-hooks on the final chain, the contract being the final receiver
+ */
+export type IbcMemoFields = {
+  forward?: { receiver: string; port: string; channel: string }[];
+  fallbackReceiver?: string;
+  wasm?: { contract: string; msg: Record<string, unknown> };
+};
+
+/**
+ * Receiver and memo of an IBC transfer, see Tx.buildIbcMemo
+ */
+export type IbcMemo = {
+  receiver: string;
+  memo?: string;
+};
@@ -0,0 +1,117 @@
+import * as shared from "@namada/shared";
+
+/**
+ * API version of the wasm lib this package is built against, see
+ * packages/shared/lib/src/version.rs
+ */
+export const REQUIRED_API_VERSION = "2.0.0";
+
+/**
+ * What the loaded wasm lib supports
+ */
+export type Capabilities = {
+  apiVersion: string;
+  crateVersion: string;
+  // Borsh Msgs accepted by the wasm lib, e.g. "BondMsg"
+  msgTypes: string[];
+  txCodes: string[];
+  // Cargo features compiled in, e.g. "multicore"
+  features: string[];
+};
+
+/**
+ * Result of the handshake with the loaded wasm lib
+ */
+export type ApiCompatibility = {
+  compatible: boolean;
+  required: string;
+  // Null if the wasm lib predates the handshake
+  apiVersion: string | null;
+  capabilities: Capabilities | null;
+};
+
+/**
+ * Borsh definition of a type, see packages/shared/lib/src/sdk/msg_schema.rs
+ */
+export type TypeDefinition =
+  | { kind: "primitive"; size: number }
+  | {
+      kind: "sequence";
+      // 0 for sequences of fixed length
+      lengthWidth: number;
+      minLength: number;
+      maxLength: number;
+      elements: string;
+    }
+  | { kind: "tuple"; elements: string[] }
+  | {
+      kind: "enum";
+      tagWidth: number;
+      variants: { discriminant: number; name: string; declaration: string }[];
+    }
+  | {
+      kind: "struct";
+      fields: { name: string | null; declaration: string }[];
+    };
+
+/**
+ * Borsh layout of a Msg accepted by the wasm lib
+ */
+export type MsgSchema = {
+  name: string;
+  // First byte of the encoded Msg, null if it's not tagged
+  tag: number | null;
+  // Changes with the layout of the Msg or of the types nested in it
+  hash: string;
+  // Definitions of the Msg and of the types nested in it, by declaration
+  definitions: Record<string, TypeDefinition>;
+};
+
+// Looked up at runtime, wasm libs older than the handshake don't export them
+type HandshakeExports = {
+  api_version?: () => string;
+  capabilities?: () => Capabilities;
+  is_api_compatible?: (required: string) => boolean;
+  describe_messages?: () => MsgSchema[];
+};
+
+/**
+ * Check that the loaded wasm lib matches the API this package is built
+ * against. Never throws, so callers can degrade gracefully on mismatch.
+ * @returns ApiCompatibility
+ */
+export function checkApiCompatibility(): ApiCompatibility {
+  const wasm = shared as unknown as HandshakeExports;
+  if (
+    typeof wasm.api_version !== "function" ||
+    typeof wasm.capabilities !== "function" ||
+    typeof wasm.is_api_compatible !== "function"
+  ) {
+    return {
+      compatible: false,
+      required: REQUIRED_API_VERSION,
+      apiVersion: null,
+      capabilities: null,
+    };
+  }
+
+  return {
+    compatible: wasm.is_api_compatible(REQUIRED_API_VERSION),
+    required: REQUIRED_API_VERSION,
+    apiVersion: wasm.api_version(),
+    capabilities: wasm.capabilities(),
+  };
+}
+
+/**
+ * Describe the Borsh layouts of the Msgs accepted by the loaded wasm lib, to
+ * generate encoders in other languages or compare layouts at runtime
+ * @returns MsgSchema of each Msg, or null if the wasm lib predates it
+ */
+export function describeMessages(): MsgSchema[] | null {
+  const wasm = shared as unknown as HandshakeExports;
+  if (typeof wasm.describe_messages !== "function") {
+    return null;
+  }
+  return wasm.describe_messages();
+}
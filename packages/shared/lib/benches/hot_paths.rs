@@ -0,0 +1,241 @@
+//! Benchmarks of the native hot paths of building and signing txs
+//!
+//! Covers decoding the Borsh Msgs sent by the TS SDK, their conversion to namada_sdk args,
+//! signing, the spend authorization signatures of MASP transactions, and loading the MASP
+//! prover and proving. The prover is only benchmarked when MASP_PARAMS_DIR points to a
+//! directory with the MASP params. The wasm build is benchmarked end to end by
+//! packages/sdk/scripts/bench.cjs.
+//!
+//! Run with `cargo bench`, results of each release are attached to its GitHub release.
+use std::str::FromStr;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use namada_sdk::account::AccountPublicKeysMap;
+use namada_sdk::address::Address;
+use namada_sdk::borsh;
+use namada_sdk::chain::ChainId;
+use namada_sdk::hash::Hash;
+use namada_sdk::key::{common, ed25519, RefTo};
+use namada_sdk::masp_primitives::asset_type::AssetType;
+use namada_sdk::masp_primitives::consensus::{BlockHeight, TestNetwork};
+use namada_sdk::masp_primitives::jubjub;
+use namada_sdk::masp_primitives::memo::MemoBytes;
+use namada_sdk::masp_primitives::sapling::redjubjub::PrivateKey;
+use namada_sdk::masp_primitives::sapling::spend_sig;
+use namada_sdk::masp_primitives::transaction::builder::Builder;
+use namada_sdk::masp_primitives::transaction::components::sapling::builder::RngBuildParams;
+use namada_sdk::masp_primitives::transaction::components::{TxOut, U64Sum};
+use namada_sdk::masp_primitives::transaction::fees::fixed::FeeRule;
+use namada_sdk::masp_primitives::transaction::TransparentAddress;
+use namada_sdk::masp_primitives::zip32::{ExtendedFullViewingKey, ExtendedSpendingKey};
+use namada_sdk::masp_proofs::prover::LocalTxProver;
+use namada_sdk::tx::Tx;
+use rand::rngs::OsRng;
+use shared::sdk::args::{self, BondMsg, WrapperTxMsg};
+use shared::sdk::decode::{decode_tagged_msg, encode_tagged_msg, MsgTag};
+use shared::sdk::prefixes::ChainPrefixes;
+
+const NATIVE_TOKEN: &str = "tnam1qxvg64psvhwumv3mwrrjfcz0h3t3274hwggyzcee";
+const SOURCE: &str = "tnam1qz4sdx5jlh909j44uz46pl6f3d2ftsvkmcml0yuf";
+const TARGET: &str = "tnam1qpgk927uh2aqjs92yhycsh08n5yggvltn5nk92zp";
+const CHAIN_ID: &str = "namada-test.000000000000000";
+const SECRET_KEY: &str = "1498b5467a63dffa2dc9d9e069caf075d16fc33fdd4c3b01bfadae6433767d93";
+
+fn wrapper_tx_msg() -> Vec<u8> {
+    encode_tagged_msg(&WrapperTxMsg::new(
+        NATIVE_TOKEN.to_string(),
+        "0.000001".to_string(),
+        "50000".to_string(),
+        CHAIN_ID.to_string(),
+        None,
+        Some("benchmark".to_string()),
+        None,
+        None,
+        None,
+        None,
+        None,
+    ))
+}
+
+fn bond_msg() -> Vec<u8> {
+    encode_tagged_msg(&BondMsg::new(
+        SOURCE.to_string(),
+        TARGET.to_string(),
+        "1.5".to_string(),
+    ))
+}
+
+/// Borsh layout of TransparentTransferMsg, as encoded by the TS SDK
+fn transparent_transfer_msg(transfers: usize) -> Vec<u8> {
+    let data: Vec<(String, String, String, String)> = (0..transfers)
+        .map(|_| {
+            (
+                SOURCE.to_string(),
+                TARGET.to_string(),
+                NATIVE_TOKEN.to_string(),
+                "1.5".to_string(),
+            )
+        })
+        .collect();
+    let mut bytes = vec![MsgTag::TransparentTransfer as u8];
+    bytes.extend(borsh::to_vec(&data).unwrap());
+    bytes
+}
+
+fn msg_decoding(c: &mut Criterion) {
+    let tx_msg = wrapper_tx_msg();
+    let bond = bond_msg();
+
+    let mut group = c.benchmark_group("msg_decoding");
+    group.bench_function("wrapper_tx", |b| {
+        b.iter(|| decode_tagged_msg::<WrapperTxMsg>(black_box(&tx_msg)).unwrap())
+    });
+    group.bench_function("bond", |b| {
+        b.iter(|| decode_tagged_msg::<BondMsg>(black_box(&bond)).unwrap())
+    });
+    group.finish();
+}
+
+fn arg_conversion(c: &mut Criterion) {
+    let tx_msg = wrapper_tx_msg();
+    let bond = bond_msg();
+    let transfer = transparent_transfer_msg(1);
+    let batch_transfer = transparent_transfer_msg(32);
+    let prefixes = ChainPrefixes::default();
+
+    let mut group = c.benchmark_group("arg_conversion");
+    group.bench_function("tx", |b| {
+        b.iter(|| args::tx_args_from_slice(black_box(&tx_msg), &prefixes).unwrap())
+    });
+    group.bench_function("bond", |b| {
+        b.iter(|| args::bond_tx_args(black_box(&bond), black_box(&tx_msg), &prefixes).unwrap())
+    });
+    group.bench_function("transparent_transfer", |b| {
+        b.iter(|| {
+            args::transparent_transfer_tx_args(black_box(&transfer), black_box(&tx_msg), &prefixes)
+                .unwrap()
+        })
+    });
+    group.bench_function("transparent_transfer_32", |b| {
+        b.iter(|| {
+            args::transparent_transfer_tx_args(
+                black_box(&batch_transfer),
+                black_box(&tx_msg),
+                &prefixes,
+            )
+            .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn signing(c: &mut Criterion) {
+    let key = common::SecretKey::Ed25519(ed25519::SecretKey::from_str(SECRET_KEY).unwrap());
+    let public_key = key.ref_to();
+    let owner = Address::from(&public_key);
+
+    let mut tx = Tx::new(ChainId(CHAIN_ID.to_string()), None);
+    tx.add_code_from_hash(Hash::zero(), Some("tx_bond.wasm".to_string()))
+        .add_serialized_data(bond_msg());
+
+    let mut group = c.benchmark_group("signing");
+    group.bench_function("sign_raw", |b| {
+        b.iter(|| {
+            let mut tx = tx.clone();
+            tx.sign_raw(
+                vec![key.clone()],
+                AccountPublicKeysMap::from_iter([public_key.clone()]),
+                Some(owner.clone()),
+            );
+            tx
+        })
+    });
+    group.bench_function("sign_wrapper", |b| {
+        b.iter(|| {
+            let mut tx = tx.clone();
+            tx.sign_wrapper(key.clone());
+            tx
+        })
+    });
+    group.bench_function("serialize_signed", |b| {
+        let mut signed = tx.clone();
+        signed.sign_wrapper(key.clone());
+        b.iter(|| borsh::to_vec(black_box(&signed)).unwrap())
+    });
+    group.finish();
+}
+
+fn masp_signing(c: &mut Criterion) {
+    let ask = jubjub::Fr::from(42u64);
+    let ar = jubjub::Fr::from(7u64);
+    let sighash = [7u8; 32];
+
+    let mut group = c.benchmark_group("masp_signing");
+    group.bench_function("spend_auth", |b| {
+        b.iter(|| spend_sig(PrivateKey(ask), ar, black_box(&sighash), &mut OsRng))
+    });
+    group.finish();
+}
+
+/// Builds and proves a MASP transaction shielding a transparent input to `outputs` notes
+fn prove_shielding(prover: &LocalTxProver, outputs: u64) {
+    let xsk = ExtendedSpendingKey::master(b"benchmark");
+    let (_, payment_address) = ExtendedFullViewingKey::from(&xsk).default_address();
+    let asset_type = AssetType::new(b"benchmark").unwrap();
+
+    let mut builder = Builder::<TestNetwork, _>::new(TestNetwork, BlockHeight::from_u32(1));
+    builder
+        .add_transparent_input(TxOut {
+            asset_type,
+            value: outputs,
+            address: TransparentAddress([0; 20]),
+        })
+        .unwrap();
+    for _ in 0..outputs {
+        builder
+            .add_sapling_output(None, payment_address, asset_type, 1, MemoBytes::empty())
+            .unwrap();
+    }
+    builder
+        .build(
+            prover,
+            &FeeRule::non_standard(U64Sum::zero()),
+            &mut OsRng,
+            &mut RngBuildParams::new(OsRng),
+        )
+        .unwrap();
+}
+
+fn masp_prover(c: &mut Criterion) {
+    let Ok(params_dir) = std::env::var("MASP_PARAMS_DIR") else {
+        return;
+    };
+    let read = |name: &str| std::fs::read(std::path::Path::new(&params_dir).join(name)).unwrap();
+    let spend = read("masp-spend.params");
+    let output = read("masp-output.params");
+    let convert = read("masp-convert.params");
+
+    let mut group = c.benchmark_group("masp_prover");
+    // Parsing the params and proving take seconds, a few samples are enough
+    group.sample_size(10);
+    group.bench_function("from_bytes", |b| {
+        b.iter(|| LocalTxProver::from_bytes(&spend, &output, &convert))
+    });
+
+    let prover = LocalTxProver::from_bytes(&spend, &output, &convert);
+    group.bench_function("prove_1_output", |b| b.iter(|| prove_shielding(&prover, 1)));
+    group.bench_function("prove_4_outputs", |b| {
+        b.iter(|| prove_shielding(&prover, 4))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    msg_decoding,
+    arg_conversion,
+    signing,
+    masp_signing,
+    masp_prover
+);
+criterion_main!(benches);
@@ -0,0 +1,131 @@
+//! Structured crash reports of panics
+//!
+//! Panics abort the wasm instance and only surface in JS as an opaque "unreachable" error.
+//! The panic hook logs the panic to the console and passes a CrashReport to the callback set
+//! with set_crash_report_callback, so the frontend can report it.
+use std::cell::{Cell, RefCell};
+use std::panic::{self, PanicHookInfo};
+use std::sync::Once;
+
+use js_sys::Function;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::utils::to_js_result;
+
+/// Size of a wasm memory page in bytes
+const WASM_PAGE_SIZE: usize = 65536;
+
+static INSTALL: Once = Once::new();
+
+thread_local! {
+    static CALLBACK: RefCell<Option<Function>> = const { RefCell::new(None) };
+    static OPERATION: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub message: String,
+    /// file:line:column of the panic
+    pub location: Option<String>,
+    /// Operation in progress, see operation
+    pub operation: Option<String>,
+    /// Size of the wasm memory in bytes
+    pub memory_bytes: usize,
+    /// Time of the panic, in RFC 3339
+    pub timestamp: String,
+}
+
+/// Marks an operation as in progress until the returned guard is dropped
+///
+/// Operations of concurrent futures overwrite each other, so the operation of a report is
+/// the most recently started one.
+pub fn operation(name: &'static str) -> OperationGuard {
+    let previous = OPERATION.with(|operation| operation.replace(Some(name)));
+    OperationGuard { previous }
+}
+
+pub struct OperationGuard {
+    previous: Option<&'static str>,
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        OPERATION.with(|operation| operation.set(self.previous));
+    }
+}
+
+fn memory_bytes() -> usize {
+    #[cfg(target_arch = "wasm32")]
+    {
+        core::arch::wasm32::memory_size(0) * WASM_PAGE_SIZE
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        0
+    }
+}
+
+fn crash_report(info: &PanicHookInfo) -> CrashReport {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("Unknown panic"));
+
+    CrashReport {
+        message,
+        location: info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+        operation: OPERATION.with(|operation| operation.get().map(String::from)),
+        memory_bytes: memory_bytes(),
+        timestamp: String::from(js_sys::Date::new_0().to_iso_string()),
+    }
+}
+
+fn panic_hook(info: &PanicHookInfo) {
+    console_error_panic_hook::hook(info);
+
+    let report = crash_report(info);
+    CALLBACK.with(|callback| {
+        if let (Some(callback), Ok(report)) = (callback.borrow().as_ref(), to_js_result(report)) {
+            // The instance is unusable after a panic anyway, errors of the callback are ignored
+            let _ = callback.call1(&JsValue::NULL, &report);
+        }
+    });
+}
+
+/// Installs the panic hook, once
+pub fn install() {
+    INSTALL.call_once(|| panic::set_hook(Box::new(panic_hook)));
+}
+
+/// Sets a callback `(report: CrashReport) => void` called on panics, or removes it
+#[wasm_bindgen]
+pub fn set_crash_report_callback(callback: Option<Function>) {
+    install();
+    CALLBACK.with(|cb| *cb.borrow_mut() = callback);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn tracks_nested_operations() {
+        let current = || OPERATION.with(|operation| operation.get());
+        {
+            let _outer = operation("build_bond");
+            {
+                let _inner = operation("sign_tx");
+                assert_eq!(current(), Some("sign_tx"));
+            }
+            assert_eq!(current(), Some("build_bond"));
+        }
+        assert_eq!(current(), None);
+    }
+}
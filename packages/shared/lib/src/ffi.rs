@@ -0,0 +1,463 @@
+//! Bindings for mobile wallets, generated with uniffi
+//!
+//! iOS and Android wallets call these instead of re-implementing the Borsh layouts of the
+//! Msgs and the signing of txs. Each Msg is checked by converting it to its namada_sdk args,
+//! like the JS Sdk does before building, and is returned Borsh serialized together with the
+//! wrapper tx msg. Building needs a connection to a node and happens with those bytes in the
+//! Sdk; the built tx comes back here to be signed with the wallet key.
+//!
+//! Building, proving and the spend authorization signatures of MASP transactions are not part
+//! of the bindings, as they need a node and the MASP params. Shielded transfers are encoded
+//! here and built by the JS Sdk, which signs the spends with the spending keys, or with a
+//! device following masp_signing_plans.
+//!
+//! Built with the ffi feature, without web. Swift and Kotlin bindings are generated from the
+//! compiled library with uniffi-bindgen.
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use namada_sdk::account::AccountPublicKeysMap;
+use namada_sdk::address::Address;
+use namada_sdk::borsh;
+use namada_sdk::key::{common, ed25519, RefTo};
+use namada_sdk::tx::Tx;
+
+use crate::sdk::args::{
+    self, BondMsg, ClaimRewardsMsg, IbcTransferMsg, RedelegateMsg, RevealPkMsg,
+    ShieldedTransferDataMsg, ShieldedTransferMsg, TransparentTransferDataMsg,
+    TransparentTransferMsg, UnbondMsg, VoteProposalMsg, WithdrawMsg, WrapperTxMsg,
+};
+use crate::sdk::decode::encode_tagged_msg;
+use crate::sdk::error::JsError;
+use crate::sdk::prefixes::ChainPrefixes;
+use crate::sdk::screening;
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum FfiError {
+    #[error("{message}")]
+    Invalid { message: String },
+}
+
+impl From<JsError> for FfiError {
+    fn from(error: JsError) -> FfiError {
+        FfiError::Invalid {
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<std::io::Error> for FfiError {
+    fn from(error: std::io::Error) -> FfiError {
+        FfiError::Invalid {
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Fields of the wrapper tx, see WrapperTxMsg
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct WrapperTx {
+    pub token: String,
+    pub fee_amount: String,
+    pub gas_limit: String,
+    pub chain_id: String,
+    pub public_key: Option<String>,
+    pub memo: Option<String>,
+    pub expiration: Option<String>,
+    /// Public key paying the fee, defaults to the first signer
+    pub fee_payer: Option<String>,
+    /// Public keys signing the inner txs, defaults to public_key
+    pub signers: Option<Vec<String>>,
+}
+
+impl From<WrapperTx> for WrapperTxMsg {
+    fn from(wrapper: WrapperTx) -> WrapperTxMsg {
+        WrapperTxMsg::new(
+            wrapper.token,
+            wrapper.fee_amount,
+            wrapper.gas_limit,
+            wrapper.chain_id,
+            wrapper.public_key,
+            wrapper.memo,
+            None,
+            wrapper.expiration,
+            wrapper.fee_payer,
+            wrapper.signers,
+            None,
+        )
+    }
+}
+
+/// Tagged Borsh serialized Msg and wrapper tx msg for the matching build function of the Sdk
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct EncodedMsg {
+    pub msg: Vec<u8>,
+    pub tx_msg: Vec<u8>,
+}
+
+/// Serializes the wrapper, and converts it with the serialized Msg to args with `check`.
+/// Addresses and keys are expected with the Namada prefixes.
+fn encode<A>(
+    msg: Vec<u8>,
+    wrapper: WrapperTx,
+    check: impl FnOnce(&[u8], &[u8], &ChainPrefixes) -> Result<A, JsError>,
+) -> Result<EncodedMsg, FfiError> {
+    let tx_msg = encode_tagged_msg(&WrapperTxMsg::from(wrapper));
+    check(&msg, &tx_msg, &ChainPrefixes::default())?;
+    Ok(EncodedMsg { msg, tx_msg })
+}
+
+#[uniffi::export]
+pub fn encode_reveal_pk(public_key: String, wrapper: WrapperTx) -> Result<EncodedMsg, FfiError> {
+    common::PublicKey::from_str(&public_key).map_err(JsError::from)?;
+    let msg = borsh::to_vec(&RevealPkMsg::new(public_key))?;
+    encode(msg, wrapper, |_, tx_msg, prefixes| {
+        args::tx_args_from_slice(tx_msg, prefixes)
+    })
+}
+
+#[uniffi::export]
+pub fn encode_bond(
+    source: String,
+    validator: String,
+    amount: String,
+    wrapper: WrapperTx,
+) -> Result<EncodedMsg, FfiError> {
+    encode(
+        encode_tagged_msg(&BondMsg::new(source, validator, amount)),
+        wrapper,
+        args::bond_tx_args,
+    )
+}
+
+#[uniffi::export]
+pub fn encode_unbond(
+    source: String,
+    validator: String,
+    amount: String,
+    wrapper: WrapperTx,
+) -> Result<EncodedMsg, FfiError> {
+    encode(
+        encode_tagged_msg(&UnbondMsg::new(source, validator, amount)),
+        wrapper,
+        args::unbond_tx_args,
+    )
+}
+
+#[uniffi::export]
+pub fn encode_withdraw(
+    source: String,
+    validator: String,
+    wrapper: WrapperTx,
+) -> Result<EncodedMsg, FfiError> {
+    encode(
+        encode_tagged_msg(&WithdrawMsg::new(source, validator)),
+        wrapper,
+        args::withdraw_tx_args,
+    )
+}
+
+#[uniffi::export]
+pub fn encode_redelegate(
+    owner: String,
+    source_validator: String,
+    destination_validator: String,
+    amount: String,
+    wrapper: WrapperTx,
+) -> Result<EncodedMsg, FfiError> {
+    encode(
+        encode_tagged_msg(&RedelegateMsg::new(
+            owner,
+            source_validator,
+            destination_validator,
+            amount,
+        )),
+        wrapper,
+        args::redelegate_tx_args,
+    )
+}
+
+#[uniffi::export]
+pub fn encode_claim_rewards(
+    validator: String,
+    source: Option<String>,
+    wrapper: WrapperTx,
+) -> Result<EncodedMsg, FfiError> {
+    encode(
+        encode_tagged_msg(&ClaimRewardsMsg::new(validator, source)),
+        wrapper,
+        args::claim_rewards_tx_args,
+    )
+}
+
+#[uniffi::export]
+pub fn encode_vote_proposal(
+    signer: String,
+    proposal_id: u64,
+    vote: String,
+    wrapper: WrapperTx,
+) -> Result<EncodedMsg, FfiError> {
+    encode(
+        encode_tagged_msg(&VoteProposalMsg::new(signer, proposal_id, vote)),
+        wrapper,
+        args::vote_proposal_tx_args,
+    )
+}
+
+/// Part of a transfer, see TransparentTransferDataMsg and ShieldedTransferDataMsg
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct TransferData {
+    /// Address, or spending key of shielded transfers
+    pub source: String,
+    /// Address, or payment address of shielded transfers
+    pub target: String,
+    pub token: String,
+    pub amount: String,
+}
+
+#[uniffi::export]
+pub fn encode_transparent_transfer(
+    data: Vec<TransferData>,
+    wrapper: WrapperTx,
+) -> Result<EncodedMsg, FfiError> {
+    let data = data
+        .into_iter()
+        .map(|data| {
+            TransparentTransferDataMsg::new(data.source, data.target, data.token, data.amount)
+        })
+        .collect();
+    encode(
+        encode_tagged_msg(&TransparentTransferMsg::new(data)),
+        wrapper,
+        args::transparent_transfer_tx_args,
+    )
+}
+
+/// Encodes a transfer from spending keys, to payment addresses or unshielding to transparent
+/// addresses. The gas is paid by the gas spending key if any, or else the wrapper fee payer.
+#[uniffi::export]
+pub fn encode_shielded_transfer(
+    data: Vec<TransferData>,
+    gas_spending_key: Option<String>,
+    wrapper: WrapperTx,
+) -> Result<EncodedMsg, FfiError> {
+    let data = data
+        .into_iter()
+        .map(|data| ShieldedTransferDataMsg::new(data.source, data.target, data.token, data.amount))
+        .collect();
+    encode(
+        encode_tagged_msg(&ShieldedTransferMsg::new(data, gas_spending_key)),
+        wrapper,
+        args::shielded_transfer_tx_args,
+    )
+}
+
+/// Encodes a transparent IBC transfer, the timeouts are defaulted by the Sdk if none are given
+#[uniffi::export]
+#[allow(clippy::too_many_arguments)]
+pub fn encode_ibc_transfer(
+    source: String,
+    receiver: String,
+    token: String,
+    amount_in_base_denom: String,
+    port_id: String,
+    channel_id: String,
+    timeout_height: Option<u64>,
+    timeout_sec_offset: Option<u64>,
+    memo: Option<String>,
+    wrapper: WrapperTx,
+) -> Result<EncodedMsg, FfiError> {
+    encode(
+        encode_tagged_msg(&IbcTransferMsg::new(
+            source,
+            receiver,
+            token,
+            amount_in_base_denom,
+            port_id,
+            channel_id,
+            timeout_height,
+            timeout_sec_offset,
+            memo,
+            None,
+        )),
+        wrapper,
+        args::ibc_transfer_tx_args,
+    )
+}
+
+/// Signature recorded in the key audit log of the wallet, see AuditEntry of the JS Sdk
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct AuditRecord {
+    /// Wrapper hash of the signed tx
+    pub hash: String,
+    /// Code tags of the inner txs, e.g. tx_transfer.wasm
+    pub tx_types: Vec<String>,
+    /// Public key of the signing key
+    pub signer: String,
+    /// Time of the signature, in RFC 3339
+    pub timestamp: String,
+}
+
+/// Key audit log of the wallet, implemented by the app to persist the records. Errors are
+/// handled by the app, so a failed write doesn't fail the signature.
+#[uniffi::export(with_foreign)]
+pub trait AuditLog: Send + Sync {
+    fn record(&self, record: AuditRecord);
+}
+
+/// Screening of counterparties before signing, see set_screening_list of the JS Sdk
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct Screening {
+    /// Addresses which may not be counterparties
+    pub blocked: Vec<String>,
+    /// Hashes of the tx codes registered on chain by code path, e.g. tx_transfer.wasm, as
+    /// returned by query_wasm_hashes of the JS Sdk. Inner txs of other codes are rejected.
+    pub code_hashes: HashMap<String, String>,
+}
+
+/// Signs a Borsh serialized Namada tx with the key of an implicit account, the raw header if
+/// `sign_inner` and always the wrapper, and records the signature in the audit log. Returns
+/// the signed tx bytes.
+///
+/// # Errors
+///
+/// Returns FfiError if the tx or the key can't be decoded, the tx is not a wrapper, or the
+/// screening rejects its counterparties
+#[uniffi::export]
+pub fn sign_tx(
+    tx: Vec<u8>,
+    private_key: String,
+    sign_inner: bool,
+    screening: Option<Screening>,
+    audit_log: Arc<dyn AuditLog>,
+) -> Result<Vec<u8>, FfiError> {
+    let mut tx: Tx = borsh::from_slice(&tx)?;
+    if tx.header.wrapper().is_none() {
+        return Err(JsError::new("Only wrapper txs can be signed").into());
+    }
+    if let Some(screening) = screening {
+        let counterparties = screening::counterparties(&tx, |hash| {
+            screening::code_of_hash(
+                screening
+                    .code_hashes
+                    .iter()
+                    .map(|(path, hash)| (path.as_str(), hash.as_str())),
+                hash,
+            )
+        })?;
+        let blocked: HashSet<String> = screening.blocked.into_iter().collect();
+        screening::check_blocklist(&counterparties, &blocked, &ChainPrefixes::default())?;
+    }
+    let key = common::SecretKey::Ed25519(
+        ed25519::SecretKey::from_str(&private_key).map_err(JsError::from)?,
+    );
+
+    if sign_inner {
+        let public_key = key.ref_to();
+        let owner = Address::from(&public_key);
+        tx.sign_raw(
+            vec![key.clone()],
+            AccountPublicKeysMap::from_iter([public_key]),
+            Some(owner),
+        );
+    }
+    let signer = key.ref_to().to_string();
+    tx.sign_wrapper(key);
+
+    let tx_types = tx
+        .commitments()
+        .iter()
+        .filter_map(|cmt| {
+            tx.get_section(cmt.code_sechash())
+                .and_then(|section| section.code_sec())
+                .and_then(|code| code.tag)
+        })
+        .collect();
+    audit_log.record(AuditRecord {
+        hash: tx
+            .wrapper_hash()
+            .unwrap_or_else(|| tx.header_hash())
+            .to_string(),
+        tx_types,
+        signer,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    });
+
+    Ok(borsh::to_vec(&tx)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const NATIVE_TOKEN: &str = "tnam1qxvg64psvhwumv3mwrrjfcz0h3t3274hwggyzcee";
+    const SOURCE: &str = "tnam1qz4sdx5jlh909j44uz46pl6f3d2ftsvkmcml0yuf";
+    const VALIDATOR: &str = "tnam1qpgk927uh2aqjs92yhycsh08n5yggvltn5nk92zp";
+
+    struct NoAuditLog;
+
+    impl AuditLog for NoAuditLog {
+        fn record(&self, _record: AuditRecord) {}
+    }
+
+    fn wrapper() -> WrapperTx {
+        WrapperTx {
+            token: NATIVE_TOKEN.to_string(),
+            fee_amount: "0.000001".to_string(),
+            gas_limit: "50000".to_string(),
+            chain_id: "namada-test.000000000000000".to_string(),
+            public_key: None,
+            memo: None,
+            expiration: None,
+            fee_payer: None,
+            signers: None,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn encodes_checked_msgs() {
+        let encoded = encode_bond(
+            SOURCE.to_string(),
+            VALIDATOR.to_string(),
+            "1.5".to_string(),
+            wrapper(),
+        )
+        .unwrap();
+        assert_eq!(
+            encoded.msg,
+            encode_tagged_msg(&BondMsg::new(
+                SOURCE.to_string(),
+                VALIDATOR.to_string(),
+                "1.5".to_string()
+            ))
+        );
+
+        let invalid = encode_bond(
+            SOURCE.to_string(),
+            "validator".to_string(),
+            "1.5".to_string(),
+            wrapper(),
+        );
+        assert!(matches!(invalid, Err(FfiError::Invalid { .. })));
+
+        let transfer = TransferData {
+            source: SOURCE.to_string(),
+            target: VALIDATOR.to_string(),
+            token: NATIVE_TOKEN.to_string(),
+            amount: "1".to_string(),
+        };
+        assert!(encode_transparent_transfer(vec![transfer.clone()], wrapper()).is_ok());
+        // Transparent sources can't be spent in a shielded transfer
+        assert!(encode_shielded_transfer(vec![transfer], None, wrapper()).is_err());
+        assert!(sign_tx(
+            vec![1, 2, 3],
+            String::new(),
+            true,
+            None,
+            Arc::new(NoAuditLog)
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,555 @@
+//! Indexer clients backing the account and validator queries
+//!
+//! Balances, validators and transfer history are served by a namada-indexer when its URL is
+//! configured on the Query, and by raw RPC queries otherwise, so the frontend gets the same
+//! data whichever infrastructure is available. The RPC backend scans recent blocks for the
+//! history, a page covering RPC_HISTORY_PAGE_BLOCKS blocks.
+//!
+//! The history of viewing keys is never known to the indexer, it's decoded from the locally
+//! synced shielded context and merged with the transparent history of an address into one
+//! timeline, in which every entry is annotated with whether it touches the shielded pool.
+//! Pages of the timeline are those of the transparent history, each with the shielded entries
+//! of the heights it covers, see merge_history_page.
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use namada_sdk::address::Address;
+use namada_sdk::io::Client;
+use namada_sdk::queries::RPC;
+use namada_sdk::rpc;
+use namada_sdk::token::Amount;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsError;
+
+use crate::query::wasm_hashes;
+use crate::rpc_client::HttpClient;
+use crate::sdk::report::{self, ReportEntry};
+use crate::types::storage::validator_state_name;
+
+/// Number of blocks scanned for a page of history by the RPC backend
+pub const RPC_HISTORY_PAGE_BLOCKS: u64 = 100;
+
+/// Number of shielded notes per page of the history of a viewing key
+pub const SHIELDED_HISTORY_PAGE_SIZE: usize = 20;
+
+/// Raw balance of a token
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalance {
+    pub token: String,
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorSummary {
+    pub address: String,
+    /// Raw bonded stake
+    pub voting_power: Option<String>,
+    /// One of "consensus", "belowCapacity", "belowThreshold", "inactive", "jailed"
+    pub state: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Page of transfer history, newest entries first. Pages start at 1.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage {
+    pub entries: Vec<ReportEntry>,
+    pub page: u64,
+    pub has_more: bool,
+    /// Lowest height covered by the page, 1 on the last page
+    pub from_height: u64,
+    /// Highest height covered by the page, None if it's only bounded by the previous page
+    pub to_height: Option<u64>,
+}
+
+/// Entry of the history timeline
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    #[serde(flatten)]
+    pub entry: ReportEntry,
+    /// Whether the entry moves funds in or out of the shielded pool, or within it
+    pub shielded: bool,
+}
+
+/// Page of the history timeline, newest entries first. Pages start at 1.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timeline {
+    pub entries: Vec<TimelineEntry>,
+    pub page: u64,
+    pub has_more: bool,
+}
+
+/// Whether a tx kind of the indexer or of the RPC reports touches the shielded pool
+fn is_shielded_kind(kind: &str) -> bool {
+    let kind = kind.to_lowercase();
+    kind.contains("shield") || kind == "mixedtransfer" || kind == "shielded_note"
+}
+
+/// Merges transparent entries and entries decoded from the shielded context into one
+/// timeline, newest entries first. Entries of the same height keep their order.
+pub fn merge_timeline(
+    transparent: Vec<ReportEntry>,
+    shielded: Vec<ReportEntry>,
+) -> Vec<TimelineEntry> {
+    let mut timeline: Vec<TimelineEntry> = transparent
+        .into_iter()
+        .map(|entry| TimelineEntry {
+            shielded: is_shielded_kind(&entry.kind),
+            entry,
+        })
+        .chain(shielded.into_iter().map(|entry| TimelineEntry {
+            entry,
+            shielded: true,
+        }))
+        .collect();
+    timeline.sort_by(|a, b| b.entry.height.cmp(&a.entry.height));
+    timeline
+}
+
+/// Merges a page of transparent history with the shielded entries of the heights it covers,
+/// from its from_height up to `to_height`, into a page of the timeline. Consecutive pages
+/// cover consecutive heights, so every shielded entry is on exactly one page.
+///
+/// # Arguments
+///
+/// * `transparent` - Page of transparent history
+/// * `to_height` - Highest height covered by the page, None for the first page
+/// * `shielded` - Shielded report entries of all heights
+pub fn merge_history_page(
+    transparent: HistoryPage,
+    to_height: Option<u64>,
+    shielded: Vec<ReportEntry>,
+) -> Timeline {
+    let shielded = shielded
+        .into_iter()
+        .filter(|entry| {
+            entry.height >= transparent.from_height
+                && to_height.map_or(true, |to_height| entry.height <= to_height)
+        })
+        .collect();
+
+    Timeline {
+        entries: merge_timeline(transparent.entries, shielded),
+        page: transparent.page,
+        has_more: transparent.has_more,
+    }
+}
+
+/// Returns a page of shielded report entries, newest first, and whether more pages follow
+///
+/// # Arguments
+///
+/// * `entries` - Shielded report entries, oldest first
+/// * `page` - Page number, starting at 1
+pub fn shielded_history_page(
+    mut entries: Vec<ReportEntry>,
+    page: u64,
+) -> Result<(Vec<ReportEntry>, bool), JsError> {
+    let skipped = usize::try_from(page)
+        .ok()
+        .and_then(|page| page.checked_sub(1))
+        .and_then(|pages| pages.checked_mul(SHIELDED_HISTORY_PAGE_SIZE))
+        .ok_or_else(|| JsError::new(&format!("Invalid history page {}", page)))?;
+    entries.reverse();
+    let has_more = entries.len() > skipped.saturating_add(SHIELDED_HISTORY_PAGE_SIZE);
+
+    Ok((
+        entries
+            .into_iter()
+            .skip(skipped)
+            .take(SHIELDED_HISTORY_PAGE_SIZE)
+            .collect(),
+        has_more,
+    ))
+}
+
+/// Backend of the account and validator queries
+#[async_trait(?Send)]
+pub trait IndexerClient {
+    /// Name of the backend, "indexer" or "rpc"
+    fn backend(&self) -> &'static str;
+
+    /// Returns the raw balances of tokens held by an owner, zero for tokens it doesn't hold
+    async fn balances(
+        &self,
+        owner: &Address,
+        tokens: &[Address],
+    ) -> Result<Vec<TokenBalance>, JsError>;
+
+    /// Returns all validators
+    async fn validators(&self) -> Result<Vec<ValidatorSummary>, JsError>;
+
+    /// Returns a page of the transparent transfer history of an address
+    async fn history(&self, address: &Address, page: u64) -> Result<HistoryPage, JsError>;
+}
+
+/// Creates the indexer client of the URL, falling back to RPC queries without one
+pub fn indexer_client(client: &HttpClient, indexer_url: Option<String>) -> Box<dyn IndexerClient> {
+    match indexer_url {
+        Some(url) => Box::new(RestIndexer::new(url)),
+        None => Box::new(RpcIndexer::new(client.clone())),
+    }
+}
+
+/// Queries the chain directly
+pub struct RpcIndexer {
+    client: HttpClient,
+}
+
+impl RpcIndexer {
+    pub fn new(client: HttpClient) -> RpcIndexer {
+        RpcIndexer { client }
+    }
+}
+
+#[async_trait(?Send)]
+impl IndexerClient for RpcIndexer {
+    fn backend(&self) -> &'static str {
+        "rpc"
+    }
+
+    async fn balances(
+        &self,
+        owner: &Address,
+        tokens: &[Address],
+    ) -> Result<Vec<TokenBalance>, JsError> {
+        let mut balances = vec![];
+        for token in tokens {
+            let amount = rpc::get_token_balance(&self.client, token, owner, None).await?;
+            balances.push(TokenBalance {
+                token: token.to_string(),
+                amount: amount.to_string(),
+            });
+        }
+        Ok(balances)
+    }
+
+    async fn validators(&self) -> Result<Vec<ValidatorSummary>, JsError> {
+        let addresses = RPC
+            .vp()
+            .pos()
+            .validator_addresses(&self.client, &None)
+            .await?;
+
+        let mut validators = vec![];
+        for address in addresses {
+            let stake = RPC
+                .vp()
+                .pos()
+                .validator_stake(&self.client, &address, &None)
+                .await?;
+            let (state, _) = rpc::get_validator_state(&self.client, &address, None).await?;
+            validators.push(ValidatorSummary {
+                address: address.to_string(),
+                voting_power: stake.map(|stake| stake.to_string()),
+                state: state.map(|state| validator_state_name(state).to_string()),
+                name: None,
+            });
+        }
+        Ok(validators)
+    }
+
+    async fn history(&self, address: &Address, page: u64) -> Result<HistoryPage, JsError> {
+        let latest = self
+            .client
+            .latest_block()
+            .await?
+            .block
+            .header
+            .height
+            .value();
+        let (from_height, to_height) = rpc_history_range(latest, page)?;
+        let wasm_hashes = wasm_hashes(&self.client).await;
+
+        let mut entries =
+            report::transparent_report(&self.client, address, from_height, to_height, &wasm_hashes)
+                .await?;
+        entries.reverse();
+
+        Ok(HistoryPage {
+            entries,
+            page,
+            has_more: from_height > 1,
+            from_height,
+            to_height: Some(to_height),
+        })
+    }
+}
+
+/// Returns the inclusive height range scanned for a page of history
+fn rpc_history_range(latest: u64, page: u64) -> Result<(u64, u64), JsError> {
+    let skipped = page
+        .checked_sub(1)
+        .and_then(|pages| pages.checked_mul(RPC_HISTORY_PAGE_BLOCKS))
+        .ok_or_else(|| JsError::new(&format!("Invalid history page {}", page)))?;
+    let to_height = latest
+        .checked_sub(skipped)
+        .filter(|height| *height > 0)
+        .ok_or_else(|| JsError::new(&format!("History page {} is before genesis", page)))?;
+    let from_height = to_height.saturating_sub(RPC_HISTORY_PAGE_BLOCKS - 1).max(1);
+
+    Ok((from_height, to_height))
+}
+
+/// Queries the REST API of a namada-indexer
+pub struct RestIndexer {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexerBalance {
+    token_address: String,
+    min_denom_amount: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexerValidator {
+    address: String,
+    voting_power: Option<String>,
+    state: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexerTx {
+    tx_id: String,
+    wrapper_id: String,
+    kind: String,
+    exit_code: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexerHistoryEntry {
+    tx: IndexerTx,
+    kind: String,
+    block_height: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexerPagination {
+    page: u64,
+    total_pages: u64,
+}
+
+#[derive(Deserialize)]
+struct IndexerHistory {
+    results: Vec<IndexerHistoryEntry>,
+    pagination: IndexerPagination,
+}
+
+impl RestIndexer {
+    pub fn new(url: String) -> RestIndexer {
+        RestIndexer {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn get<T: for<'de> Deserialize<'de>>(&self, path: &str) -> Result<T, JsError> {
+        let response = self
+            .client
+            .get(format!("{}/api/v1{}", self.url, path))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(serde_json::from_str(&response.text().await?)?)
+    }
+}
+
+#[async_trait(?Send)]
+impl IndexerClient for RestIndexer {
+    fn backend(&self) -> &'static str {
+        "indexer"
+    }
+
+    async fn balances(
+        &self,
+        owner: &Address,
+        tokens: &[Address],
+    ) -> Result<Vec<TokenBalance>, JsError> {
+        let held: Vec<IndexerBalance> = self.get(&format!("/account/{}", owner)).await?;
+
+        tokens
+            .iter()
+            .map(|token| {
+                let token_address = token.to_string();
+                let amount = match held
+                    .iter()
+                    .find(|balance| balance.token_address == token_address)
+                {
+                    Some(balance) => Amount::from_str(&balance.min_denom_amount, 0u8)?,
+                    None => Amount::zero(),
+                };
+                Ok(TokenBalance {
+                    token: token.to_string(),
+                    amount: amount.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    async fn validators(&self) -> Result<Vec<ValidatorSummary>, JsError> {
+        let validators: Vec<IndexerValidator> = self.get("/pos/validator/all").await?;
+
+        Ok(validators
+            .into_iter()
+            .map(|validator| ValidatorSummary {
+                address: validator.address,
+                voting_power: validator.voting_power,
+                state: validator.state,
+                name: validator.name,
+            })
+            .collect())
+    }
+
+    async fn history(&self, address: &Address, page: u64) -> Result<HistoryPage, JsError> {
+        let history: IndexerHistory = self
+            .get(&format!(
+                "/chain/history?addresses={}&page={}",
+                address, page
+            ))
+            .await?;
+
+        let entries = history
+            .results
+            .into_iter()
+            .map(|entry| ReportEntry {
+                height: entry.block_height,
+                wrapper_hash: entry.tx.wrapper_id,
+                inner_hash: entry.tx.tx_id,
+                kind: entry.tx.kind,
+                direction: entry.kind,
+                counterparty: None,
+                token: None,
+                amount: None,
+                fee_token: String::new(),
+                fee_amount_per_gas_unit: String::new(),
+                gas_limit: String::new(),
+                applied: entry.tx.exit_code == "applied",
+            })
+            .collect::<Vec<_>>();
+        let has_more = history.pagination.page < history.pagination.total_pages;
+        // Entries are newest first, the last page covers the heights down to genesis
+        let from_height = match entries.last() {
+            Some(entry) if has_more => entry.height,
+            _ => 1,
+        };
+
+        Ok(HistoryPage {
+            entries,
+            page: history.pagination.page,
+            has_more,
+            from_height,
+            to_height: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn entry(height: u64, kind: &str) -> ReportEntry {
+        ReportEntry {
+            height,
+            wrapper_hash: String::new(),
+            inner_hash: String::new(),
+            kind: kind.to_string(),
+            direction: String::from("in"),
+            counterparty: None,
+            token: None,
+            amount: None,
+            fee_token: String::new(),
+            fee_amount_per_gas_unit: String::new(),
+            gas_limit: String::new(),
+            applied: true,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn merges_timeline_newest_first() {
+        let timeline = merge_timeline(
+            vec![
+                entry(12, "transparentTransfer"),
+                entry(7, "shieldingTransfer"),
+            ],
+            vec![entry(3, "shielded_note"), entry(9, "shielded_note")],
+        );
+
+        let heights: Vec<u64> = timeline.iter().map(|e| e.entry.height).collect();
+        assert_eq!(heights, vec![12, 9, 7, 3]);
+        let shielded: Vec<bool> = timeline.iter().map(|e| e.shielded).collect();
+        assert_eq!(shielded, vec![false, true, true, true]);
+    }
+
+    #[wasm_bindgen_test]
+    fn merges_shielded_entries_of_the_page_heights() {
+        let page = |page: u64, heights: &[u64], from_height: u64| HistoryPage {
+            entries: heights
+                .iter()
+                .map(|h| entry(*h, "transparentTransfer"))
+                .collect(),
+            page,
+            has_more: from_height > 1,
+            from_height,
+            to_height: None,
+        };
+        let shielded: Vec<ReportEntry> = [2, 5, 8, 11, 14]
+            .into_iter()
+            .map(|height| entry(height, "shielded_note"))
+            .collect();
+
+        let first = merge_history_page(page(1, &[12, 10], 10), None, shielded.clone());
+        let heights: Vec<u64> = first.entries.iter().map(|e| e.entry.height).collect();
+        assert_eq!(heights, vec![14, 12, 11, 10]);
+        assert!(first.has_more);
+
+        // Up to the height before the lowest of the previous page
+        let second = merge_history_page(page(2, &[7, 4], 4), Some(9), shielded.clone());
+        let heights: Vec<u64> = second.entries.iter().map(|e| e.entry.height).collect();
+        assert_eq!(heights, vec![8, 7, 5, 4]);
+
+        let last = merge_history_page(page(3, &[3], 1), Some(3), shielded);
+        let heights: Vec<u64> = last.entries.iter().map(|e| e.entry.height).collect();
+        assert_eq!(heights, vec![3, 2]);
+        assert!(!last.has_more);
+    }
+
+    #[wasm_bindgen_test]
+    fn pages_shielded_history_backwards() {
+        let entries: Vec<ReportEntry> = (1..=SHIELDED_HISTORY_PAGE_SIZE as u64 + 5)
+            .map(|height| entry(height, "shielded_note"))
+            .collect();
+
+        let (first, has_more) = shielded_history_page(entries.clone(), 1).unwrap();
+        assert_eq!(first.len(), SHIELDED_HISTORY_PAGE_SIZE);
+        assert_eq!(first[0].height, SHIELDED_HISTORY_PAGE_SIZE as u64 + 5);
+        assert!(has_more);
+
+        let (second, has_more) = shielded_history_page(entries.clone(), 2).unwrap();
+        assert_eq!(second.len(), 5);
+        assert_eq!(second[4].height, 1);
+        assert!(!has_more);
+
+        assert!(shielded_history_page(entries, 0).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn pages_rpc_history_backwards() {
+        assert_eq!(rpc_history_range(1000, 1).unwrap(), (901, 1000));
+        assert_eq!(rpc_history_range(1000, 2).unwrap(), (801, 900));
+        assert_eq!(rpc_history_range(150, 2).unwrap(), (1, 50));
+        assert!(rpc_history_range(150, 3).is_err());
+        assert!(rpc_history_range(150, 0).is_err());
+    }
+}
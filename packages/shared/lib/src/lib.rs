@@ -1,22 +1,54 @@
 //! # shared
 //!
 //! A library of functions to integrate shared functionality from the Namada ecosystem
+//!
+//! The JS bindings are built with the web feature, and the nodejs feature on top of it for
+//! Node.js. Without them, only the Msgs and their conversion to namada_sdk args in sdk::args
+//! are built, for native Rust services and plain `cargo test`. The ffi feature adds uniffi
+//! bindings of them for mobile wallets.
 
+#[cfg(feature = "web")]
+pub mod crash_report;
+#[cfg(all(feature = "ffi", not(feature = "web")))]
+pub mod ffi;
+#[cfg(feature = "web")]
+pub mod indexer;
+#[cfg(feature = "web")]
+pub mod light_client;
+#[cfg(feature = "web")]
+pub mod paging;
+#[cfg(feature = "web")]
+pub mod proof;
+#[cfg(feature = "web")]
 pub mod query;
+#[cfg(feature = "web")]
 pub mod rpc_client;
+#[cfg(feature = "web")]
+pub mod scheduler;
 pub mod sdk;
+#[cfg(feature = "web")]
 pub mod types;
+#[cfg(feature = "web")]
 mod utils;
+#[cfg(feature = "web")]
+pub mod version;
+
+#[cfg(all(feature = "ffi", not(feature = "web")))]
+uniffi::setup_scaffolding!();
+
+// Tests of the web build run in a headless browser, see test-wasm:browser:ci
+#[cfg(all(test, feature = "web", not(feature = "nodejs")))]
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 
 #[cfg(feature = "multicore")]
 pub use wasm_bindgen_rayon::init_thread_pool;
 
 // Empty function for non-multicore builds
 // Simplifies imports in js code
-#[cfg(not(feature = "multicore"))]
+#[cfg(all(feature = "web", not(feature = "multicore")))]
 use wasm_bindgen::prelude::wasm_bindgen;
 
-#[cfg(not(feature = "multicore"))]
+#[cfg(all(feature = "web", not(feature = "multicore")))]
 #[allow(non_snake_case)]
 #[wasm_bindgen]
 pub async fn initThreadPool(_threads: u8) {}
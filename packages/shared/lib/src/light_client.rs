@@ -0,0 +1,319 @@
+//! Light client verification of CometBFT headers
+//!
+//! Allows using untrusted RPC endpoints by checking that headers of the blocks we read from
+//! were signed by enough voting power of validators we already trust.
+use std::collections::HashSet;
+
+use namada_sdk::borsh::BorshDeserialize;
+use namada_sdk::io::Client;
+use namada_sdk::key::{common, ed25519, SigScheme};
+use namada_sdk::tendermint::block::{signed_header::SignedHeader, CommitSig, Height};
+use namada_sdk::tendermint::validator::{Info, Set};
+use namada_sdk::tendermint::vote::{Type as VoteType, ValidatorIndex, Vote};
+use namada_sdk::tendermint::{Hash, PublicKey};
+use namada_sdk::tendermint_rpc::Paging;
+use thiserror::Error;
+
+use crate::rpc_client::HttpClient;
+
+#[derive(Debug, Error)]
+pub enum LightClientError {
+    #[error("RPC request failed: {0}")]
+    Rpc(String),
+    #[error("Header hash {0} does not match trusted hash {1}")]
+    TrustedHashMismatch(Hash, Hash),
+    #[error("Validator set hash does not match header at height {0}")]
+    ValidatorsHashMismatch(u64),
+    #[error("Commit is not for the header at height {0}")]
+    CommitMismatch(u64),
+    #[error("Header chain id {0} does not match trusted chain id {1}")]
+    ChainIdMismatch(String, String),
+    #[error("Insufficient voting power signed height {0}: {1}/{2}")]
+    InsufficientVotingPower(u64, u64, u64),
+    #[error("Not enough trusted validators signed height {0}: {1}/{2}")]
+    InsufficientTrust(u64, u64, u64),
+    #[error("Height {0} is below trusted height {1}")]
+    HeightBelowTrusted(u64, u64),
+    #[error("Trusted header at height {0} is older than the trusting period")]
+    TrustedHeaderExpired(u64),
+}
+
+impl From<namada_sdk::tendermint_rpc::Error> for LightClientError {
+    fn from(error: namada_sdk::tendermint_rpc::Error) -> Self {
+        LightClientError::Rpc(error.to_string())
+    }
+}
+
+impl From<namada_sdk::tendermint::Error> for LightClientError {
+    fn from(error: namada_sdk::tendermint::Error) -> Self {
+        LightClientError::Rpc(error.to_string())
+    }
+}
+
+/// Header that passed verification, together with the validators that signed it
+#[derive(Debug, Clone)]
+pub struct VerifiedBlock {
+    pub signed_header: SignedHeader,
+    pub validators: Set,
+}
+
+impl VerifiedBlock {
+    pub fn height(&self) -> u64 {
+        self.signed_header.header.height.value()
+    }
+
+    /// App hash of the state after executing the previous block
+    pub fn app_hash(&self) -> Vec<u8> {
+        self.signed_header.header.app_hash.as_bytes().to_vec()
+    }
+
+    /// Block time of the header, in seconds since the unix epoch
+    pub fn time(&self) -> i64 {
+        self.signed_header.header.time.unix_timestamp()
+    }
+}
+
+/// Default trusting period of two weeks, shorter than the unbonding period of validators so
+/// that a validator set we trust can still be slashed for signing a conflicting header
+pub const DEFAULT_TRUSTING_PERIOD_SECS: u64 = 14 * 24 * 60 * 60;
+
+/// Skipping light client with a trust level of 1/3, as in CometBFT light client spec
+#[derive(Clone)]
+pub struct LightClient {
+    client: HttpClient,
+    trusted: VerifiedBlock,
+    trusting_period_secs: u64,
+}
+
+impl LightClient {
+    /// Initializes the light client from a trusted height and header hash, obtained out of band
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - RPC client, it doesn't need to be trusted
+    /// * `trusted_height` - height of the trusted header
+    /// * `trusted_hash` - hash of the trusted header
+    /// * `trusting_period_secs` - how long a verified header can be trusted for
+    /// * `now` - current time, in seconds since the unix epoch
+    pub async fn new(
+        client: HttpClient,
+        trusted_height: u64,
+        trusted_hash: Hash,
+        trusting_period_secs: u64,
+        now: i64,
+    ) -> Result<LightClient, LightClientError> {
+        let (signed_header, validators) = fetch_block(&client, trusted_height).await?;
+        let header_hash = signed_header.header.hash();
+
+        if header_hash != trusted_hash {
+            return Err(LightClientError::TrustedHashMismatch(
+                header_hash,
+                trusted_hash,
+            ));
+        }
+        verify_commit(&signed_header, &validators)?;
+
+        let light_client = LightClient {
+            client,
+            trusted: VerifiedBlock {
+                signed_header,
+                validators,
+            },
+            trusting_period_secs,
+        };
+        light_client.ensure_within_trusting_period(now)?;
+
+        Ok(light_client)
+    }
+
+    pub fn trusted(&self) -> &VerifiedBlock {
+        &self.trusted
+    }
+
+    /// Checks that the trusted header is recent enough for its validators to still be bonded,
+    /// otherwise they could sign a fork without being slashed and a new trusted header has to
+    /// be obtained out of band
+    fn ensure_within_trusting_period(&self, now: i64) -> Result<(), LightClientError> {
+        let expires_at = self
+            .trusted
+            .time()
+            .saturating_add(self.trusting_period_secs as i64);
+        if expires_at <= now {
+            return Err(LightClientError::TrustedHeaderExpired(
+                self.trusted.height(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the header at a given height against the trusted state. On success the
+    /// trusted state is moved forward to the verified header.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - height of the header to verify
+    /// * `now` - current time, in seconds since the unix epoch
+    pub async fn verify_height(
+        &mut self,
+        height: u64,
+        now: i64,
+    ) -> Result<VerifiedBlock, LightClientError> {
+        self.ensure_within_trusting_period(now)?;
+
+        let trusted_height = self.trusted.height();
+        if height < trusted_height {
+            return Err(LightClientError::HeightBelowTrusted(height, trusted_height));
+        }
+        if height == trusted_height {
+            return Ok(self.trusted.clone());
+        }
+
+        let (signed_header, validators) = fetch_block(&self.client, height).await?;
+
+        let trusted_chain_id = self.trusted.signed_header.header.chain_id.to_string();
+        let chain_id = signed_header.header.chain_id.to_string();
+        if chain_id != trusted_chain_id {
+            return Err(LightClientError::ChainIdMismatch(
+                chain_id,
+                trusted_chain_id,
+            ));
+        }
+
+        // Make sure at least 1/3 of the voting power we already trust signed the new header
+        let (trusted_signed, trusted_total) =
+            signed_voting_power(&signed_header, &self.trusted.validators);
+        if trusted_signed * 3 <= trusted_total {
+            return Err(LightClientError::InsufficientTrust(
+                height,
+                trusted_signed,
+                trusted_total,
+            ));
+        }
+        verify_commit(&signed_header, &validators)?;
+
+        let verified = VerifiedBlock {
+            signed_header,
+            validators,
+        };
+        self.trusted = verified.clone();
+
+        Ok(verified)
+    }
+
+    /// Verifies the latest header available on the RPC endpoint
+    pub async fn verify_latest(&mut self, now: i64) -> Result<VerifiedBlock, LightClientError> {
+        let latest = self.client.latest_commit().await?;
+        let height = latest.signed_header.header.height.value();
+
+        self.verify_height(height, now).await
+    }
+}
+
+async fn fetch_block(
+    client: &HttpClient,
+    height: u64,
+) -> Result<(SignedHeader, Set), LightClientError> {
+    let height = Height::try_from(height)?;
+    let commit = client.commit(height).await?;
+    let validators = client.validators(height, Paging::All).await?;
+
+    let signed_header = commit.signed_header;
+    let validators = Set::without_proposer(validators.validators);
+
+    if validators.hash() != signed_header.header.validators_hash {
+        return Err(LightClientError::ValidatorsHashMismatch(height.value()));
+    }
+    if signed_header.commit.block_id.hash != signed_header.header.hash() {
+        return Err(LightClientError::CommitMismatch(height.value()));
+    }
+
+    Ok((signed_header, validators))
+}
+
+/// Checks that more than 2/3 of the voting power of the validator set signed the commit
+fn verify_commit(signed_header: &SignedHeader, validators: &Set) -> Result<(), LightClientError> {
+    let (signed, total) = signed_voting_power(signed_header, validators);
+
+    if signed * 3 <= total * 2 {
+        return Err(LightClientError::InsufficientVotingPower(
+            signed_header.header.height.value(),
+            signed,
+            total,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns the sum of voting power of validators in the set with a valid commit signature,
+/// and the total voting power of the set. Each validator is counted once, even if the commit
+/// contains several signatures with its address.
+fn signed_voting_power(signed_header: &SignedHeader, validators: &Set) -> (u64, u64) {
+    let commit = &signed_header.commit;
+    let chain_id = signed_header.header.chain_id.clone();
+    let total = validators.total_voting_power().value();
+    let mut signed = 0u64;
+    let mut seen = HashSet::new();
+
+    for (index, commit_sig) in commit.signatures.iter().enumerate() {
+        let (validator_address, timestamp, signature) = match commit_sig {
+            CommitSig::BlockIdFlagCommit {
+                validator_address,
+                timestamp,
+                signature: Some(signature),
+            } => (validator_address, timestamp, signature),
+            _ => continue,
+        };
+        let validator = match validators.validator(*validator_address) {
+            Some(validator) => validator,
+            None => continue,
+        };
+        let validator_index = match ValidatorIndex::try_from(index) {
+            Ok(validator_index) => validator_index,
+            Err(_) => continue,
+        };
+
+        let vote = Vote {
+            vote_type: VoteType::Precommit,
+            height: commit.height,
+            round: commit.round,
+            block_id: Some(commit.block_id),
+            timestamp: Some(*timestamp),
+            validator_address: *validator_address,
+            validator_index,
+            signature: Some(signature.clone()),
+            extension: vec![],
+            extension_signature: None,
+        };
+        let sign_bytes = match vote.to_signable_vec(chain_id.clone()) {
+            Ok(sign_bytes) => sign_bytes,
+            Err(_) => continue,
+        };
+
+        if verify_signature(&validator, &sign_bytes, signature.as_bytes())
+            && seen.insert(validator.address)
+        {
+            signed += validator.power.value();
+        }
+    }
+
+    (signed, total)
+}
+
+fn verify_signature(validator: &Info, sign_bytes: &[u8], signature: &[u8]) -> bool {
+    let pk = match validator.pub_key {
+        PublicKey::Ed25519(pk) => pk,
+        _ => return false,
+    };
+    let pk = match ed25519::PublicKey::try_from_slice(pk.as_bytes()) {
+        Ok(pk) => common::PublicKey::Ed25519(pk),
+        Err(_) => return false,
+    };
+    let sig = match ed25519::Signature::try_from_slice(signature) {
+        Ok(sig) => common::Signature::Ed25519(sig),
+        Err(_) => return false,
+    };
+
+    common::SigScheme::verify_signature_raw(&pk, sign_bytes, &sig).is_ok()
+}
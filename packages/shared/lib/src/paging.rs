@@ -0,0 +1,74 @@
+//! Paged transfer of large query results to JS
+//!
+//! Instead of serializing a whole result into one JS value, items are passed to a callback
+//! one page at a time. If the callback returns a Promise, the next page is only sent once it
+//! resolves, so JS can process or store each page before the next one is allocated.
+use js_sys::{Function, Promise};
+use serde::Serialize;
+use wasm_bindgen::{JsCast, JsError, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::utils::to_js_result;
+
+/// Default number of items per page
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+/// Maximum number of items per page
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Calls a JS callback `(page: T[], pageIndex: number) => void | Promise<void>` per page
+pub struct PageSink {
+    callback: Function,
+    page_size: usize,
+    page_index: u32,
+}
+
+impl PageSink {
+    pub fn new(callback: Function, page_size: Option<usize>) -> Result<PageSink, JsError> {
+        let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+        if page_size == 0 || page_size > MAX_PAGE_SIZE {
+            return Err(JsError::new(&format!(
+                "Page size must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )));
+        }
+
+        Ok(PageSink {
+            callback,
+            page_size,
+            page_index: 0,
+        })
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    pub fn pages_sent(&self) -> u32 {
+        self.page_index
+    }
+
+    /// Sends a single page, waiting for the callback if it returns a Promise
+    pub async fn send<T: Serialize>(&mut self, page: &[T]) -> Result<(), JsError> {
+        let page = to_js_result(page)?;
+        let result = self
+            .callback
+            .call2(&JsValue::NULL, &page, &JsValue::from(self.page_index))
+            .map_err(|e| JsError::new(&format!("Page callback failed: {:?}", e)))?;
+        self.page_index += 1;
+
+        if let Ok(promise) = result.dyn_into::<Promise>() {
+            JsFuture::from(promise)
+                .await
+                .map_err(|e| JsError::new(&format!("Page callback failed: {:?}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Sends all items split into pages. Returns the number of pages sent.
+    pub async fn send_all<T: Serialize>(&mut self, items: &[T]) -> Result<u32, JsError> {
+        for page in items.chunks(self.page_size) {
+            self.send(page).await?;
+        }
+        Ok(self.pages_sent())
+    }
+}
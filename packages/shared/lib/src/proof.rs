@@ -0,0 +1,168 @@
+//! Verification of storage proofs returned by ABCI queries
+//!
+//! Namada storage is committed to by a base merkle tree whose leaves are the roots of
+//! subtrees per store type. ABCI queries with `prove` return two ICS-23 proof ops:
+//! the existence proof of the key in its subtree and the proof of the subtree root
+//! in the base tree, which is committed to by the app hash.
+use ics23::{
+    calculate_existence_root, commitment_proof::Proof, verify_membership, verify_non_membership,
+    CommitmentProof, ExistenceProof, HostFunctionsManager, ProofSpec,
+};
+use namada_sdk::hash::Sha256Hasher;
+use namada_sdk::state::ics23_specs;
+use namada_sdk::state::{Key, StoreType};
+use namada_sdk::tendermint::merkle::proof::ProofOps;
+use prost::Message;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ProofError {
+    #[error("Expected 2 proof ops, got {0}")]
+    InvalidProofOps(usize),
+    #[error("Invalid commitment proof: {0}")]
+    InvalidCommitmentProof(String),
+    #[error("Invalid storage key: {0}")]
+    InvalidKey(String),
+}
+
+/// ICS-23 specs of the subtree of a store type and of the base tree, as used by the ledger
+/// to produce the proofs. The IBC subtree doesn't prehash its keys, so that counterparty
+/// chains can verify its proofs.
+fn proof_specs(store_type: &StoreType) -> (ProofSpec, ProofSpec) {
+    let specs = match store_type {
+        StoreType::Ibc => ics23_specs::ibc_proof_specs::<Sha256Hasher>(),
+        _ => ics23_specs::proof_specs::<Sha256Hasher>(),
+    };
+    let mut specs = specs.into_iter();
+    let sub_spec = specs.next().expect("Sub tree proof spec should be present");
+    let base_spec = specs
+        .next()
+        .expect("Base tree proof spec should be present");
+
+    (sub_spec, base_spec)
+}
+
+fn decode_commitment_proof(data: &[u8]) -> Result<CommitmentProof, ProofError> {
+    CommitmentProof::decode(data).map_err(|e| ProofError::InvalidCommitmentProof(e.to_string()))
+}
+
+/// Verifies that a storage key has the given value in the state committed to by app_hash.
+/// Returns false if the proof is valid but does not prove the value.
+///
+/// # Arguments
+///
+/// * `proof_ops` - proof ops returned by ABCI query with `prove` flag
+/// * `app_hash` - app hash of a verified header at the height following the queried one
+/// * `key` - queried storage key
+/// * `value` - returned storage value bytes
+///
+/// # Errors
+///
+/// Returns an error if the proof can't be decoded
+pub fn verify_storage_proof(
+    proof_ops: &ProofOps,
+    app_hash: &[u8],
+    key: &Key,
+    value: &[u8],
+) -> Result<bool, ProofError> {
+    let [sub_op, base_op] = match proof_ops.ops.as_slice() {
+        [sub_op, base_op] => [sub_op, base_op],
+        ops => return Err(ProofError::InvalidProofOps(ops.len())),
+    };
+
+    let (store_type, sub_key) =
+        StoreType::sub_key(key).map_err(|e| ProofError::InvalidKey(e.to_string()))?;
+    let (sub_spec, base_spec) = proof_specs(&store_type);
+
+    let sub_proof = decode_commitment_proof(&sub_op.data)?;
+    let sub_root = match &sub_proof.proof {
+        Some(Proof::Exist(existence_proof)) => existence_root(existence_proof)?,
+        _ => return Ok(false),
+    };
+    if !verify_membership::<HostFunctionsManager>(
+        &sub_proof,
+        &sub_spec,
+        &sub_root,
+        sub_key.to_string().as_bytes(),
+        value,
+    ) {
+        return Ok(false);
+    }
+
+    verify_sub_root(&base_op.data, &base_spec, app_hash, &store_type, &sub_root)
+}
+
+/// Verifies that a storage key has no value in the state committed to by app_hash, e.g. to
+/// trust a zero balance. Returns false if the proof is valid but does not prove the absence.
+///
+/// # Arguments
+///
+/// * `proof_ops` - proof ops returned by ABCI query with `prove` flag
+/// * `app_hash` - app hash of a verified header at the height following the queried one
+/// * `key` - queried storage key
+///
+/// # Errors
+///
+/// Returns an error if the proof can't be decoded
+pub fn verify_storage_absence(
+    proof_ops: &ProofOps,
+    app_hash: &[u8],
+    key: &Key,
+) -> Result<bool, ProofError> {
+    let [sub_op, base_op] = match proof_ops.ops.as_slice() {
+        [sub_op, base_op] => [sub_op, base_op],
+        ops => return Err(ProofError::InvalidProofOps(ops.len())),
+    };
+
+    let (store_type, sub_key) =
+        StoreType::sub_key(key).map_err(|e| ProofError::InvalidKey(e.to_string()))?;
+    let (sub_spec, base_spec) = proof_specs(&store_type);
+
+    let sub_proof = decode_commitment_proof(&sub_op.data)?;
+    // Root of the subtree is computed from either neighbour of the missing key
+    let neighbour = match &sub_proof.proof {
+        Some(Proof::Nonexist(non_existence_proof)) => non_existence_proof
+            .left
+            .as_ref()
+            .or(non_existence_proof.right.as_ref()),
+        _ => return Ok(false),
+    };
+    let sub_root = match neighbour {
+        Some(neighbour) => existence_root(neighbour)?,
+        None => return Ok(false),
+    };
+    if !verify_non_membership::<HostFunctionsManager>(
+        &sub_proof,
+        &sub_spec,
+        &sub_root,
+        sub_key.to_string().as_bytes(),
+    ) {
+        return Ok(false);
+    }
+
+    verify_sub_root(&base_op.data, &base_spec, app_hash, &store_type, &sub_root)
+}
+
+fn existence_root(existence_proof: &ExistenceProof) -> Result<Vec<u8>, ProofError> {
+    calculate_existence_root::<HostFunctionsManager>(existence_proof)
+        .map_err(|e| ProofError::InvalidCommitmentProof(e.to_string()))
+}
+
+/// Verifies the proof of a subtree root in the base tree committed to by app_hash
+fn verify_sub_root(
+    base_proof: &[u8],
+    base_spec: &ProofSpec,
+    app_hash: &[u8],
+    store_type: &StoreType,
+    sub_root: &[u8],
+) -> Result<bool, ProofError> {
+    let base_proof = decode_commitment_proof(base_proof)?;
+
+    Ok(verify_membership::<HostFunctionsManager>(
+        &base_proof,
+        base_spec,
+        &app_hash.to_vec(),
+        store_type.to_string().as_bytes(),
+        sub_root,
+    ))
+}
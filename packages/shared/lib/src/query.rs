@@ -1,16 +1,20 @@
 use gloo_utils::format::JsValueSerdeExt;
-use js_sys::Uint8Array;
+use js_sys::{Function, Uint8Array};
+use namada_sdk::account;
 use namada_sdk::address::Address;
-use namada_sdk::borsh::BorshSerialize;
-use namada_sdk::collections::{HashMap, HashSet};
+use namada_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use namada_sdk::chain::ChainId;
+use namada_sdk::collections::HashMap;
 use namada_sdk::eth_bridge::bridge_pool::query_signed_bridge_pool;
 use namada_sdk::eth_bridge_pool::TransferToEthereum;
 use namada_sdk::governance::storage::keys as governance_storage;
 use namada_sdk::governance::utils::{
-    compute_proposal_result, ProposalVotes, TallyResult, TallyType, VotePower,
+    compute_proposal_result, ProposalResult, ProposalVotes, TallyResult, TallyType, VotePower,
 };
 use namada_sdk::governance::{ProposalType, ProposalVote};
 use namada_sdk::hash::Hash;
+use namada_sdk::io::Client;
+use namada_sdk::key::common;
 use namada_sdk::masp::shielded_wallet::ShieldedApi;
 use namada_sdk::masp::utils::MaspClient as NamadaMaspClient;
 use namada_sdk::masp::utils::RetryStrategy;
@@ -21,36 +25,59 @@ use namada_sdk::masp_primitives::sapling::ViewingKey;
 use namada_sdk::masp_primitives::transaction::components::ValueSum;
 use namada_sdk::masp_primitives::zip32::ExtendedFullViewingKey;
 use namada_sdk::parameters::storage;
+use namada_sdk::proof_of_stake::storage_key as pos_storage_key;
+use namada_sdk::proof_of_stake::types::BondId;
 use namada_sdk::proof_of_stake::Epoch;
 use namada_sdk::queries::RPC;
 use namada_sdk::rpc::{
-    self, get_public_key_at, get_token_balance, get_total_staked_tokens,
-    is_steward, query_epoch, query_masp_epoch, query_native_token, query_proposal_by_id,
-    query_proposal_votes, query_storage_value,
+    self, get_public_key_at, get_total_staked_tokens, is_steward, query_epoch, query_masp_epoch,
+    query_native_token, query_proposal_by_id, query_proposal_votes, query_storage_value,
 };
 use namada_sdk::state::BlockHeight;
 use namada_sdk::state::Key;
+use namada_sdk::storage::KeySeg;
+use namada_sdk::tendermint::Hash as TmHash;
+use namada_sdk::time::{DateTimeUtc, DurationSecs};
 use namada_sdk::token;
-use namada_sdk::tx::{
-    TX_BOND_WASM, TX_CLAIM_REWARDS_WASM, TX_IBC_WASM, TX_REDELEGATE_WASM, TX_REVEAL_PK,
-    TX_TRANSFER_WASM, TX_UNBOND_WASM, TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM,
-};
+use namada_sdk::tx::data::compute_inner_tx_hash;
+use namada_sdk::tx::either::Either;
+use namada_sdk::tx::Tx;
 use namada_sdk::uint::I256;
 use namada_sdk::wallet::DatedKeypair;
 use namada_sdk::ExtendedViewingKey;
-use std::collections::BTreeMap;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
 use std::str::FromStr;
 use std::time::Duration;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsError;
 
-use crate::rpc_client::HttpClient;
+use crate::indexer::{self, IndexerClient};
+use crate::light_client::{
+    LightClient, LightClientError, VerifiedBlock, DEFAULT_TRUSTING_PERIOD_SECS,
+};
+use crate::paging::PageSink;
+use crate::proof;
+use crate::rpc_client::{HttpClient, MAX_BATCH_SIZE};
 use crate::sdk::{
+    code_paths::{self, TxCode},
+    eth_bridge, event_log, explorer, governance,
     io::WebIo,
-    masp::{sync, JSShieldedUtils},
+    masp::{
+        block_cache::{BlockCache, CachingMaspClient},
+        sync, JSShieldedUtils,
+    },
+    masp_estimate, note_export,
+    prefixes::ChainPrefixes,
+    report, uptime,
 };
-use crate::types::query::{ProposalInfo, WasmHash};
-use crate::utils::{set_panic_hook, to_js_result};
+use crate::types::query::{
+    AbciProofOp, AbciQueryResponse, ConversionComponent, MaspConversion, MaspRewardRate, PendingTx,
+    ProposalInfo, VoterRole, WasmHash,
+};
+use crate::utils::{set_panic_hook, to_js_configured};
 
 /// Progress bar names
 pub const SDK_SCANNED_PROGRESS_BAR: &str = "namada_sdk::progress_bar::scanned";
@@ -91,14 +118,20 @@ enum MaspClient {
 pub struct Query {
     client: HttpClient,
     masp_client: MaspClient,
+    light_client: RefCell<Option<LightClient>>,
+    block_cache: BlockCache,
+    indexer: Box<dyn IndexerClient>,
+    /// Prefixes of addresses and keys of the chain, see set_chain_prefixes
+    prefixes: RefCell<ChainPrefixes>,
 }
 
 const MAX_CONCURRENT_FETCHES: usize = 10;
+const MAX_UNCONFIRMED_TXS: u32 = 100;
 
 #[wasm_bindgen]
 impl Query {
     #[wasm_bindgen(constructor)]
-    pub fn new(url: String, masp_url: Option<String>) -> Query {
+    pub fn new(url: String, masp_url: Option<String>, indexer_url: Option<String>) -> Query {
         set_panic_hook();
         let client = HttpClient::new(url);
 
@@ -116,10 +149,455 @@ impl Query {
             ))
         };
 
+        let indexer = indexer::indexer_client(&client, indexer_url);
+
         Query {
             client,
             masp_client,
+            light_client: RefCell::new(None),
+            block_cache: BlockCache::default(),
+            indexer,
+            prefixes: RefCell::new(ChainPrefixes::default()),
+        }
+    }
+
+    /// Sets the prefixes of the chain, see Sdk.set_chain_prefixes. Addresses and keys with the
+    /// prefixes are accepted by the queries, and returned with them.
+    pub fn set_chain_prefixes(&self, prefixes: JsValue) -> Result<(), JsError> {
+        *self.prefixes.borrow_mut() = ChainPrefixes::from_js(prefixes)?;
+        Ok(())
+    }
+
+    /// Returns the prefixes of the chain, cloned so they can be replaced while a query is
+    /// awaited
+    fn prefixes(&self) -> ChainPrefixes {
+        self.prefixes.borrow().clone()
+    }
+
+    /// Returns a key or address with the configured prefix re-encoded with the Namada one
+    fn native(&self, s: &str) -> String {
+        self.prefixes.borrow().native(s).into_owned()
+    }
+
+    /// Serializes a query result with the configured prefixes, see to_js_configured
+    fn to_js<T: Serialize>(&self, result: T) -> Result<JsValue, JsError> {
+        to_js_configured(result, &self.prefixes())
+    }
+
+    /// Enables verification of block headers returned by the RPC endpoint,
+    /// starting from a header trusted out of band(e.g. from a block explorer).
+    ///
+    /// # Arguments
+    ///
+    /// * `trusted_height` - Height of the trusted header
+    /// * `trusted_hash` - Hex encoded hash of the trusted header
+    /// * `trusting_period_secs` - How long a verified header can be trusted for, two weeks if
+    ///   not provided. Should be shorter than the unbonding period of the chain.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the trusted header can't be fetched, does not match the hash or is
+    /// older than the trusting period
+    pub async fn enable_light_client(
+        &self,
+        trusted_height: u64,
+        trusted_hash: String,
+        trusting_period_secs: Option<u64>,
+    ) -> Result<(), JsError> {
+        let trusted_hash = TmHash::from_str(&trusted_hash.to_uppercase())?;
+        let light_client = LightClient::new(
+            self.client.clone(),
+            trusted_height,
+            trusted_hash,
+            trusting_period_secs.unwrap_or(DEFAULT_TRUSTING_PERIOD_SECS),
+            now_secs(),
+        )
+        .await?;
+        *self.light_client.borrow_mut() = Some(light_client);
+
+        Ok(())
+    }
+
+    /// Verifies the header at a given height and returns its hex encoded app hash.
+    /// Light client has to be enabled first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if light client is not enabled or the header can't be verified
+    pub async fn verify_block(&self, height: u64) -> Result<String, JsError> {
+        let verified = self
+            .with_light_client(|mut light_client| async move {
+                let verified = light_client.verify_height(height, now_secs()).await;
+                (light_client, verified)
+            })
+            .await?
+            .ok_or_else(|| JsError::new("Light client is not enabled"))?;
+
+        Ok(hex::encode(verified.app_hash()))
+    }
+
+    /// Verifies the latest header with the light client. Returns None if the light client is
+    /// not enabled.
+    async fn verify_latest_block(&self) -> Result<Option<VerifiedBlock>, JsError> {
+        self.with_light_client(|mut light_client| async move {
+            let verified = light_client.verify_latest(now_secs()).await;
+            (light_client, verified)
+        })
+        .await
+    }
+
+    /// Queries a storage value with a proof, verified against the latest header verified by
+    /// the light client. The value is returned unverified if the light client is not enabled.
+    async fn query_storage_proven(&self, key: &Key) -> Result<(Option<Vec<u8>>, bool), JsError> {
+        let verified = self.verify_latest_block().await?;
+
+        self.query_storage_proven_at(key, verified.as_ref()).await
+    }
+
+    /// Queries a storage value with a proof, verified against the app hash of a verified
+    /// header, so that several values can be read from the same state. The value is returned
+    /// unverified if no header is given.
+    async fn query_storage_proven_at(
+        &self,
+        key: &Key,
+        verified: Option<&VerifiedBlock>,
+    ) -> Result<(Option<Vec<u8>>, bool), JsError> {
+        let verified = match verified {
+            Some(verified) => verified,
+            None => {
+                let (value, _) =
+                    rpc::query_storage_value_bytes(&self.client, key, None, false).await?;
+                return Ok((value, false));
+            }
+        };
+
+        // App hash of a header commits to the state after the previous block
+        let height = BlockHeight(verified.height() - 1);
+        let (value, proof_ops) =
+            rpc::query_storage_value_bytes(&self.client, key, Some(height), true).await?;
+
+        let is_verified = match (&value, &proof_ops) {
+            (Some(value), Some(proof_ops)) => {
+                proof::verify_storage_proof(proof_ops, &verified.app_hash(), key, value)?
+            }
+            (None, Some(proof_ops)) => {
+                proof::verify_storage_absence(proof_ops, &verified.app_hash(), key)?
+            }
+            _ => false,
+        };
+
+        Ok((value, is_verified))
+    }
+
+    /// Queries a raw storage value and returns a tuple of hex encoded value and verified flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Storage key
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is invalid or the RPC call fails
+    pub async fn query_storage_verified(&self, key: String) -> Result<JsValue, JsError> {
+        let key = Key::parse(key)?;
+        let (value, verified) = self.query_storage_proven(&key).await?;
+
+        self.to_js((value.map(hex::encode), verified))
+    }
+
+    /// Queries the raw Borsh serialized value of any storage key, see
+    /// types::storage::decode_storage_value for decoding common value types.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Storage key, e.g. #tnam1.../balance/...
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is invalid or the RPC call fails
+    pub async fn query_storage_value(&self, key: String) -> Result<Option<Uint8Array>, JsError> {
+        let key = Key::parse(key)?;
+        let (value, _) = rpc::query_storage_value_bytes(&self.client, &key, None, false).await?;
+
+        Ok(value.map(|value| Uint8Array::from(value.as_slice())))
+    }
+
+    /// Queries the raw values of many storage keys in JSON-RPC batches of
+    /// rpc_client::MAX_BATCH_SIZE requests, querying duplicate keys once. Falls back to one
+    /// query per key if the node doesn't support batches. Values are returned in the order of
+    /// the keys, undefined for keys without a value.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - Storage keys
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a key is invalid or a query fails
+    pub async fn query_storage_values(&self, keys: Vec<String>) -> Result<js_sys::Array, JsError> {
+        let keys = keys
+            .into_iter()
+            .map(Key::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(storage_values(&self.client, &keys)
+            .await?
+            .into_iter()
+            .map(|value| match value {
+                Some(value) => JsValue::from(Uint8Array::from(value.as_slice())),
+                None => JsValue::UNDEFINED,
+            })
+            .collect())
+    }
+
+    /// Performs a raw ABCI query, as an escape hatch for features without dedicated bindings.
+    /// Returns the value together with the proof ops if requested, see AbciQueryResponse.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Query path, e.g. /shell/value/#tnam1...
+    /// * `data` - Query data
+    /// * `height` - Block height, latest if not provided
+    /// * `prove` - Include proof ops of the value
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC call fails or the query returns an error code
+    pub async fn abci_query(
+        &self,
+        path: String,
+        data: Option<Vec<u8>>,
+        height: Option<u64>,
+        prove: bool,
+    ) -> Result<JsValue, JsError> {
+        let response = self
+            .client
+            .request(path, data, height.map(BlockHeight), prove)
+            .await?;
+
+        let proof_ops = response.proof.map(|proof| {
+            proof
+                .ops
+                .into_iter()
+                .map(|op| AbciProofOp {
+                    field_type: op.field_type,
+                    key: op.key,
+                    data: op.data,
+                })
+                .collect()
+        });
+
+        self.to_js(AbciQueryResponse {
+            value: response.data,
+            proof_ops,
+            height: response.height.0,
+            info: response.info,
+        })
+    }
+
+    /// Queries transparent balances together with a flag indicating whether the balance was
+    /// verified with a storage proof, which is false only if the light client is not enabled.
+    /// Returns a vec of (token, amount, verified).
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - Transparent address
+    /// * `tokens` - Token addresses
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token is invalid or its balance fails verification
+    pub async fn query_balance_verified(
+        &self,
+        owner: String,
+        tokens: Box<[JsValue]>,
+    ) -> Result<JsValue, JsError> {
+        let owner = Address::from_str(&self.native(&owner))?;
+        let tokens = tokens
+            .iter()
+            .map(|address| {
+                let address = address
+                    .as_string()
+                    .ok_or_else(|| JsError::new("Token address must be a string"))?;
+                Ok(Address::from_str(&self.native(&address))?)
+            })
+            .collect::<Result<Vec<Address>, JsError>>()?;
+
+        let verified = self.verify_latest_block().await?;
+        let mut result: Vec<(Address, String, bool)> = vec![];
+        for token in tokens {
+            let key = token::storage_key::balance_key(&token, &owner);
+            let value = self.query_proven_value(&key, verified.as_ref()).await?;
+            let amount = match value {
+                Some(bytes) => token::Amount::try_from_slice(&bytes)?,
+                None => token::Amount::zero(),
+            };
+            result.push((token, amount.to_string(), verified.is_some()));
+        }
+
+        self.to_js(result)
+    }
+
+    /// Queries the bonds of a source to a validator together with a flag indicating whether
+    /// they were verified with storage proofs. Every returned bond is verified, but listing
+    /// the bonds is not covered by proofs, so the RPC endpoint could still leave some out.
+    /// Returns a tuple of a vec of (start epoch, amount) and the verified flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Address of the delegator, or the validator for self-bonds
+    /// * `validator` - Validator address
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an address is invalid or a bond fails verification
+    pub async fn query_bonds_verified(
+        &self,
+        source: String,
+        validator: String,
+    ) -> Result<JsValue, JsError> {
+        let bond_id = BondId {
+            source: Address::from_str(&self.native(&source))?,
+            validator: Address::from_str(&self.native(&validator))?,
+        };
+        let prefix = pos_storage_key::bond_key(&bond_id);
+
+        let verified = self.verify_latest_block().await?;
+        // Listed at the height the proofs of the verified block are read at, see
+        // query_storage_proven_at
+        let height = verified
+            .as_ref()
+            .map(|verified| BlockHeight(verified.height() - 1));
+        let entries = RPC
+            .shell()
+            .storage_prefix(&self.client, None, height, false, &prefix)
+            .await?
+            .data;
+
+        let mut bonds: Vec<(u64, String)> = vec![];
+        for entry in entries {
+            // Bond amounts are stored by their start epoch, next to the epoched data metadata
+            let epoch = match entry.key.last().map(|seg| Epoch::parse(seg.raw())) {
+                Some(Ok(epoch)) => epoch,
+                _ => continue,
+            };
+            let amount = match token::Amount::try_from_slice(&entry.value) {
+                Ok(amount) => amount,
+                Err(_) => continue,
+            };
+            if let Some(verified) = &verified {
+                let proven = self.query_proven_value(&entry.key, Some(verified)).await?;
+                if proven.as_deref() != Some(entry.value.as_slice()) {
+                    return Err(JsError::new(&format!(
+                        "Bond {} does not match its proven value",
+                        entry.key
+                    )));
+                }
+            }
+            bonds.push((epoch.0, amount.to_string()));
+        }
+
+        self.to_js((bonds, verified.is_some()))
+    }
+
+    /// Queries the public keys and signature threshold of an account together with a flag
+    /// indicating whether they were verified with storage proofs. Returns a tuple of
+    /// (public keys, threshold, verified), threshold is undefined for accounts without keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - Account address
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address is invalid or a value fails verification
+    pub async fn query_account_keys_verified(&self, owner: String) -> Result<JsValue, JsError> {
+        let owner = Address::from_str(&self.native(&owner))?;
+        let verified = self.verify_latest_block().await?;
+
+        // Keys are stored in a lazy vec, with its length next to the indexed keys
+        let pks_prefix = account::pks_key_prefix(&owner);
+        let len_key = pks_prefix.push(&"len".to_owned())?;
+        let len = match self.query_proven_value(&len_key, verified.as_ref()).await? {
+            Some(bytes) => u64::try_from_slice(&bytes)?,
+            None => 0,
+        };
+
+        let mut public_keys: Vec<String> = vec![];
+        for index in 0..len {
+            let key = pks_prefix.push(&"data".to_owned())?.push(&index)?;
+            let public_key = self
+                .query_proven_value(&key, verified.as_ref())
+                .await?
+                .ok_or_else(|| JsError::new(&format!("Public key {} is missing", index)))?;
+            public_keys.push(common::PublicKey::try_from_slice(&public_key)?.to_string());
+        }
+
+        let threshold_key = account::threshold_key(&owner);
+        let threshold = self
+            .query_proven_value(&threshold_key, verified.as_ref())
+            .await?
+            .map(|bytes| u8::try_from_slice(&bytes))
+            .transpose()?;
+
+        self.to_js((public_keys, threshold, verified.is_some()))
+    }
+
+    /// Queries a storage value with query_storage_proven_at, failing if the light client is
+    /// enabled and the value could not be verified
+    async fn query_proven_value(
+        &self,
+        key: &Key,
+        verified: Option<&VerifiedBlock>,
+    ) -> Result<Option<Vec<u8>>, JsError> {
+        let (value, is_verified) = self.query_storage_proven_at(key, verified).await?;
+        if verified.is_some() && !is_verified {
+            return Err(JsError::new(&format!(
+                "Storage value of {} could not be verified",
+                key
+            )));
         }
+
+        Ok(value)
+    }
+
+    /// Runs a verification with a copy of the light client, storing back the updated
+    /// trusted state. Returns None if the light client is not enabled.
+    async fn with_light_client<F, Fut>(&self, f: F) -> Result<Option<VerifiedBlock>, JsError>
+    where
+        F: FnOnce(LightClient) -> Fut,
+        Fut: Future<Output = (LightClient, Result<VerifiedBlock, LightClientError>)>,
+    {
+        let light_client = match self.light_client.borrow().clone() {
+            Some(light_client) => light_client,
+            None => return Ok(None),
+        };
+        let (light_client, verified) = f(light_client).await;
+        let verified = verified?;
+        *self.light_client.borrow_mut() = Some(light_client);
+
+        Ok(Some(verified))
+    }
+
+    /// Returns the RFC 3339 date, in UTC, N minutes after the time of the latest block, to be
+    /// used as a tx expiration. Block time is used instead of the client clock, which might be
+    /// skewed, so the tx doesn't expire early or late.
+    ///
+    /// # Arguments
+    ///
+    /// * `minutes` - Number of minutes the tx stays valid for
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the latest block can't be fetched
+    pub async fn query_tx_expiration(&self, minutes: u64) -> Result<String, JsError> {
+        let block = self.client.latest_block().await?;
+        let block_time = DateTimeUtc::from_str(&block.block.header.time.to_rfc3339())?;
+        let validity = minutes
+            .checked_mul(60)
+            .ok_or_else(|| JsError::new("Expiration overflow"))?;
+
+        Ok((block_time + DurationSecs(validity)).to_rfc3339())
     }
 
     /// Gets current epoch
@@ -145,7 +623,32 @@ impl Query {
             .validator_addresses(&self.client, &None)
             .await?;
 
-        to_js_result(validator_addresses)
+        self.to_js(validator_addresses)
+    }
+
+    /// Queries all validator addresses and passes them to a callback in pages, to avoid
+    /// copying the whole set across the wasm boundary at once. Returns the number of pages
+    /// sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_size` - Number of addresses per page, defaults to paging::DEFAULT_PAGE_SIZE
+    /// * `on_page` - Callback receiving the pages, may return a Promise
+    pub async fn query_all_validator_addresses_paged(
+        &self,
+        page_size: Option<usize>,
+        on_page: Function,
+    ) -> Result<u32, JsError> {
+        let mut sink = PageSink::new(on_page, page_size)?;
+        let validator_addresses: Vec<Address> = RPC
+            .vp()
+            .pos()
+            .validator_addresses(&self.client, &None)
+            .await?
+            .into_iter()
+            .collect();
+
+        sink.send_all(&validator_addresses).await
     }
 
     /// Gets total bonds by validator address
@@ -154,7 +657,7 @@ impl Query {
     ///
     /// Returns an error if the RPC call fails
     pub async fn query_total_bonds(&self, address: String) -> Result<JsValue, JsError> {
-        let address = Address::from_str(&address)?;
+        let address = Address::from_str(&self.native(&address))?;
 
         let total_bonds = RPC
             .vp()
@@ -162,7 +665,37 @@ impl Query {
             .validator_stake(&self.client, &address, &None)
             .await?;
 
-        to_js_result(total_bonds)
+        self.to_js(total_bonds)
+    }
+
+    /// Queries the uptime of validators over the last blocks, from the CometBFT commits. See
+    /// uptime::ValidatorUptime.
+    ///
+    /// # Arguments
+    ///
+    /// * `validators` - Validator addresses
+    /// * `window` - Number of blocks, defaults to uptime::DEFAULT_UPTIME_WINDOW
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an address is invalid or any RPC call fails
+    pub async fn query_validator_uptime(
+        &self,
+        validators: Box<[JsValue]>,
+        window: Option<u64>,
+    ) -> Result<JsValue, JsError> {
+        let validators = validators
+            .iter()
+            .map(|address| {
+                let address = address
+                    .as_string()
+                    .ok_or_else(|| JsError::new("Validator address is not a string"))?;
+                Address::from_str(&self.native(&address)).map_err(JsError::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let window = window.unwrap_or(uptime::DEFAULT_UPTIME_WINDOW);
+
+        self.to_js(uptime::validator_uptime(&self.client, &validators, window).await?)
     }
 
     /// Gets all delegations for every provided address.
@@ -185,13 +718,13 @@ impl Query {
             .map(|address| {
                 address
                     .as_string()
-                    .and_then(|address_str| Address::from_str(&address_str).ok())
+                    .and_then(|address_str| Address::from_str(&self.native(&address_str)).ok())
                     // TODO: we unwrap but we could also filter_map with warning or map to error
                     .unwrap()
             })
             .collect();
 
-        let mut validators_per_address: HashMap<Address, HashSet<Address>> = HashMap::new();
+        let mut validators_per_address: BTreeMap<Address, BTreeSet<Address>> = BTreeMap::new();
 
         for address in owner_addresses.into_iter() {
             let validators = RPC
@@ -200,7 +733,7 @@ impl Query {
                 .delegation_validators(&self.client, &address, &None)
                 .await?;
 
-            validators_per_address.insert(address, validators);
+            validators_per_address.insert(address, validators.into_iter().collect());
         }
 
         let mut result: Vec<(Address, Address, String, String, String)> = Vec::new();
@@ -227,7 +760,7 @@ impl Query {
             }
         }
 
-        to_js_result(result)
+        self.to_js(result)
     }
 
     fn get_decoded_balance(
@@ -250,10 +783,10 @@ impl Query {
         let owner_addresses: Vec<Address> = owner_addresses
             .iter()
             .filter_map(|address| address.as_string())
-            .filter_map(|address| Address::from_str(&address).ok())
+            .filter_map(|address| Address::from_str(&self.native(&address)).ok())
             .collect();
 
-        let mut validators_per_address: HashMap<Address, HashSet<Address>> = HashMap::new();
+        let mut validators_per_address: BTreeMap<Address, BTreeSet<Address>> = BTreeMap::new();
 
         for address in owner_addresses.into_iter() {
             let validators = RPC
@@ -262,7 +795,7 @@ impl Query {
                 .delegation_validators(&self.client, &address, &None)
                 .await?;
 
-            validators_per_address.insert(address, validators);
+            validators_per_address.insert(address, validators.into_iter().collect());
         }
 
         let mut bonds = vec![];
@@ -303,10 +836,10 @@ impl Query {
             }
         }
 
-        to_js_result((bonds, unbonds))
+        self.to_js((bonds, unbonds))
     }
 
-    /// Queries transparent balance for a given address
+    /// Queries transparent balance for a given address, from the indexer if one is configured
     ///
     /// # Arguments
     ///
@@ -316,23 +849,155 @@ impl Query {
         owner: Address,
         tokens: Vec<Address>,
     ) -> Result<Vec<(Address, token::Amount)>, JsError> {
+        let balances = self.indexer.balances(&owner, &tokens).await?;
+
+        tokens
+            .into_iter()
+            .zip(balances)
+            .map(|(token, balance)| Ok((token, token::Amount::from_str(&balance.amount, 0u8)?)))
+            .collect()
+    }
+
+    /// Queries transparent balance for a given address from storage, verifying every balance
+    /// against the app hash of the latest header verified by the light client
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a balance can't be verified
+    async fn query_transparent_balance_proven(
+        &self,
+        owner: Address,
+        tokens: Vec<Address>,
+    ) -> Result<Vec<(Address, token::Amount)>, JsError> {
+        let verified_block = self.verify_latest_block().await?;
         let mut result = vec![];
         for token in tokens {
-            let balances = get_token_balance(&self.client, &token, &owner, None).await?;
-            result.push((token, balances));
+            let key = token::storage_key::balance_key(&token, &owner);
+            let (value, verified) = self
+                .query_storage_proven_at(&key, verified_block.as_ref())
+                .await?;
+            if !verified {
+                return Err(JsError::new(&format!(
+                    "Balance of {} in {} could not be verified",
+                    owner, token
+                )));
+            }
+            let amount = match value {
+                Some(bytes) => token::Amount::try_from_slice(&bytes)?,
+                None => token::Amount::zero(),
+            };
+            result.push((token, amount));
         }
 
         Ok(result)
     }
 
+    /// Returns the backend of the balance, validator and history queries, "indexer" or "rpc"
+    pub fn indexer_backend(&self) -> String {
+        self.indexer.backend().to_string()
+    }
+
+    /// Queries all validators with their bonded stake, state and name. Names are only
+    /// known to the indexer backend.
+    pub async fn query_validators(&self) -> Result<JsValue, JsError> {
+        self.to_js(self.indexer.validators().await?)
+    }
+
+    /// Queries a page of the history of an address and viewing keys as one timeline, newest
+    /// entries first. Transparent history comes from the indexer, or from scanning recent
+    /// blocks without one, and the history of viewing keys from the synced shielded context.
+    /// Pages are those of the transparent history with the shielded entries of the heights
+    /// they cover, see indexer::merge_history_page, or of the shielded history alone without
+    /// an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Transparent address
+    /// * `viewing_keys` - Extended viewing keys
+    /// * `page` - Page number, starting at 1
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address, a viewing key or the page is invalid, or the history
+    /// can't be fetched
+    pub async fn query_history(
+        &self,
+        address: Option<String>,
+        viewing_keys: Vec<String>,
+        page: u64,
+    ) -> Result<JsValue, JsError> {
+        let mut notes = vec![];
+        if !viewing_keys.is_empty() {
+            let mut shielded: ShieldedContext<JSShieldedUtils> = ShieldedContext::default();
+            shielded.load().await?;
+            for xvk in viewing_keys {
+                let xvk = ExtendedViewingKey::from_str(&self.native(&xvk))?;
+                let vk = ExtendedFullViewingKey::from(xvk).fvk.vk;
+                notes.extend(report::shielded_report(&shielded, &vk, 1, u64::MAX));
+            }
+            // Oldest first, as the report of a single key
+            notes.sort_by_key(|entry| entry.height);
+        }
+
+        let timeline = match address {
+            Some(address) => {
+                let address = Address::from_str(&self.native(&address))?;
+                let history = self.indexer.history(&address, page).await?;
+                let to_height = match history.to_height {
+                    _ if page == 1 => None,
+                    Some(to_height) => Some(to_height),
+                    // Pages of the indexer are bounded by the lowest height of the previous one
+                    None if !notes.is_empty() => {
+                        let previous = self.indexer.history(&address, page - 1).await?;
+                        Some(previous.from_height.saturating_sub(1))
+                    }
+                    None => None,
+                };
+                indexer::merge_history_page(history, to_height, notes)
+            }
+            None => {
+                let (entries, has_more) = indexer::shielded_history_page(notes, page)?;
+                indexer::Timeline {
+                    entries: indexer::merge_timeline(vec![], entries),
+                    page,
+                    has_more,
+                }
+            }
+        };
+
+        self.to_js(timeline)
+    }
+
+    /// Drops the MASP blocks cached by previous shielded syncs
+    pub fn clear_block_cache(&self) {
+        self.block_cache.clear();
+    }
+
+    /// Marks cached responses of the latest state stale if the block is newer than the last
+    /// one observed, e.g. when the block subscription of the client receives a new block
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - Height of the new block
+    pub fn observe_block(&self, height: u64) {
+        self.client.response_cache().observe_height(height);
+    }
+
+    /// Drops all cached query responses
+    pub fn clear_response_cache(&self) {
+        self.client.response_cache().clear();
+    }
+
     pub async fn shielded_sync(&self, owners: Box<[JsValue]>) -> Result<(), JsError> {
         let owners: Vec<ViewingKey> = owners
             .iter()
             .filter_map(|owner| owner.as_string())
             .map(|o| {
-                ExtendedFullViewingKey::from(ExtendedViewingKey::from_str(&o).unwrap())
-                    .fvk
-                    .vk
+                ExtendedFullViewingKey::from(
+                    ExtendedViewingKey::from_str(&self.native(&o)).unwrap(),
+                )
+                .fvk
+                .vk
             })
             .collect();
 
@@ -377,6 +1042,10 @@ impl Query {
             MaspClient::Indexer(_) => 100,
         };
 
+        // Blocks fetched for previous syncs are served from the cache, so syncing a new
+        // viewing key only fetches blocks no other key has seen yet
+        let client = CachingMaspClient::new(client, self.block_cache.clone());
+
         let config = ShieldedSyncConfig::builder()
             .client(client)
             .scanned_tracker(progress_bar_scanned)
@@ -400,37 +1069,93 @@ impl Query {
         Ok(())
     }
 
-    /// Queries shielded balance for a given extended viewing key
+    /// Exports the scanned notes of a viewing key, encrypted to the viewing key, so another
+    /// wallet instance can import them instead of syncing from scratch
     ///
     /// # Arguments
     ///
     /// * `xvk` - Extended viewing key
-    async fn query_shielded_balance(
-        &self,
-        xvk: ExtendedViewingKey,
-        tokens: Vec<Address>,
-    ) -> Result<Vec<(Address, token::Amount)>, JsError> {
-        let viewing_key = ExtendedFullViewingKey::from(xvk).fvk.vk;
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the viewing key is invalid or not synced, or the shielded context
+    /// can't be loaded
+    pub async fn export_shielded_notes(&self, xvk: String) -> Result<Vec<u8>, JsError> {
+        let vk = ExtendedFullViewingKey::from(ExtendedViewingKey::from_str(&self.native(&xvk))?)
+            .fvk
+            .vk;
+        let chain_id = self.chain_id().await?;
 
-        // We are recreating shielded context to avoid multiple mutable borrows
         let mut shielded: ShieldedContext<JSShieldedUtils> = ShieldedContext::default();
         shielded.load().await?;
-        shielded
-            .precompute_asset_types(&self.client, tokens.iter().collect())
-            .await
-            .map_err(|e| JsError::new(&format!("{:?}", e)))?;
 
-        let epoch = query_masp_epoch(&self.client).await?;
-        let balance = shielded
-            .compute_exchanged_balance(&self.client, &WebIo, &viewing_key, epoch)
-            .await
-            .map_err(|e| JsError::new(&format!("{:?}", e)))?;
+        note_export::export_notes(&shielded, &vk, &chain_id)
+    }
 
-        let res = match balance {
-            Some(balance) => {
-                let decoded_balance = shielded
-                    .decode_combine_sum_to_epoch(&self.client, balance, epoch)
-                    .await;
+    /// Imports notes exported with export_shielded_notes, replacing the notes of the viewing
+    /// key and keeping those of the other viewing keys, see note_export::merge_notes.
+    ///
+    /// # Arguments
+    ///
+    /// * `xvk` - Extended viewing key the notes were exported for
+    /// * `export` - Exported notes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the export is malformed, of another viewing key or chain, other
+    /// viewing keys were synced to another height, or the shielded context can't be saved
+    pub async fn import_shielded_notes(&self, xvk: String, export: Vec<u8>) -> Result<(), JsError> {
+        let vk = ExtendedFullViewingKey::from(ExtendedViewingKey::from_str(&self.native(&xvk))?)
+            .fvk
+            .vk;
+        let chain_id = self.chain_id().await?;
+
+        let notes = note_export::import_notes(&export, &vk, &chain_id)?;
+
+        let mut shielded: ShieldedContext<JSShieldedUtils> = ShieldedContext::default();
+        shielded.load().await?;
+        note_export::merge_notes(&mut shielded, notes, &vk)?;
+        shielded.save().await?;
+
+        Ok(())
+    }
+
+    async fn chain_id(&self) -> Result<String, JsError> {
+        let block = self.client.latest_block().await?;
+        Ok(block.block.header.chain_id.to_string())
+    }
+
+    /// Queries shielded balance for a given extended viewing key
+    ///
+    /// # Arguments
+    ///
+    /// * `xvk` - Extended viewing key
+    async fn query_shielded_balance(
+        &self,
+        xvk: ExtendedViewingKey,
+        tokens: Vec<Address>,
+    ) -> Result<Vec<(Address, token::Amount)>, JsError> {
+        let viewing_key = ExtendedFullViewingKey::from(xvk).fvk.vk;
+
+        // We are recreating shielded context to avoid multiple mutable borrows
+        let mut shielded: ShieldedContext<JSShieldedUtils> = ShieldedContext::default();
+        shielded.load().await?;
+        shielded
+            .precompute_asset_types(&self.client, tokens.iter().collect())
+            .await
+            .map_err(|e| JsError::new(&format!("{:?}", e)))?;
+
+        let epoch = query_masp_epoch(&self.client).await?;
+        let balance = shielded
+            .compute_exchanged_balance(&self.client, &WebIo, &viewing_key, epoch)
+            .await
+            .map_err(|e| JsError::new(&format!("{:?}", e)))?;
+
+        let res = match balance {
+            Some(balance) => {
+                let decoded_balance = shielded
+                    .decode_combine_sum_to_epoch(&self.client, balance, epoch)
+                    .await;
 
                 Self::get_decoded_balance(decoded_balance)
             }
@@ -449,36 +1174,336 @@ impl Query {
             .iter()
             .map(|address| {
                 let address_str = address.as_string().unwrap();
-                Address::from_str(&address_str).unwrap()
+                Address::from_str(&self.native(&address_str)).unwrap()
             })
             .collect();
 
-        let result = match Address::from_str(&owner) {
+        let light_client_enabled = self.light_client.borrow().is_some();
+        let result = match Address::from_str(&self.native(&owner)) {
+            // When running against an untrusted RPC, balances are read with storage proofs
+            Ok(addr) if light_client_enabled => {
+                self.query_transparent_balance_proven(addr, tokens).await
+            }
             Ok(addr) => self.query_transparent_balance(addr, tokens).await,
-            Err(e1) => match ExtendedViewingKey::from_str(&owner) {
-                Ok(xvk) => self.query_shielded_balance(xvk, tokens).await,
+            Err(e1) => match ExtendedViewingKey::from_str(&self.native(&owner)) {
+                Ok(xvk) => {
+                    // Notes are not covered by storage proofs, only make sure the chain we
+                    // read from is verified
+                    self.verify_latest_block().await?;
+                    self.query_shielded_balance(xvk, tokens).await
+                }
                 Err(e2) => return Err(JsError::new(&format!("{} {}", e1, e2))),
             },
         }?;
 
         let mut mapped_result: Vec<(Address, String)> = vec![];
         for (token, amount) in result {
-            mapped_result.push((
-                token.clone(),
-                amount.to_string()
-            ))
+            mapped_result.push((token.clone(), amount.to_string()))
+        }
+
+        self.to_js(mapped_result)
+    }
+
+    /// Queries the current MASP epoch
+    pub async fn query_masp_epoch(&self) -> Result<String, JsError> {
+        Ok(query_masp_epoch(&self.client).await?.to_string())
+    }
+
+    /// Queries the allowed conversions of assets of past MASP epochs, which are applied to
+    /// shielded balances after an epoch rollover. Returns a vec of MaspConversion.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - Optional token address to filter the conversions by
+    pub async fn query_masp_conversions(&self, token: Option<String>) -> Result<JsValue, JsError> {
+        let token = token
+            .map(|t| Address::from_str(&self.native(&t)))
+            .transpose()?;
+        let conversions = rpc::query_conversions(&self.client).await?;
+
+        let describe = |asset_type: &AssetType| {
+            conversions
+                .get(asset_type)
+                .map(|(token, _, digit_pos, epoch, _, _)| (token.clone(), *digit_pos, *epoch))
+        };
+
+        let result: Vec<MaspConversion> = conversions
+            .iter()
+            .filter(|(_, (conv_token, ..))| token.as_ref().map_or(true, |t| t == conv_token))
+            .map(|(_, (conv_token, denom, digit_pos, epoch, conv, _))| {
+                let components = conv
+                    .components()
+                    .filter_map(|(asset_type, amount)| {
+                        let (token, digit_pos, epoch) = describe(asset_type)?;
+                        Some(ConversionComponent {
+                            token: token.to_string(),
+                            digit_pos: digit_pos as u8,
+                            epoch: epoch.to_string(),
+                            amount: amount.to_string(),
+                        })
+                    })
+                    .collect();
+
+                MaspConversion {
+                    token: conv_token.to_string(),
+                    denom: denom.0,
+                    digit_pos: *digit_pos as u8,
+                    epoch: epoch.to_string(),
+                    components,
+                }
+            })
+            .collect();
+
+        self.to_js(result)
+    }
+
+    /// Queries the reward parameters of tokens in the MASP together with their inflation and
+    /// locked amounts of the last MASP epoch, denominated in each token. Returns a vec of
+    /// MaspRewardRate, with no inflation and locked amount for tokens not yet rewarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC calls fail or a token has no denomination
+    pub async fn query_masp_reward_rates(&self) -> Result<JsValue, JsError> {
+        let reward_tokens = rpc::query_masp_reward_tokens(&self.client).await?;
+        let mut result: Vec<MaspRewardRate> = vec![];
+
+        for reward in reward_tokens {
+            let denom = rpc::query_denom(&self.client, &reward.address)
+                .await
+                .ok_or_else(|| JsError::new(&format!("Unknown token: {}", reward.address)))?;
+            let inflation_key = token::storage_key::masp_last_inflation_key(&reward.address);
+            let locked_key = token::storage_key::masp_last_locked_amount_key(&reward.address);
+            let last_inflation = self.query_denominated(&inflation_key, denom).await?;
+            let last_locked_amount = self.query_denominated(&locked_key, denom).await?;
+            let locked_amount_target = token::Amount::from_uint(reward.locked_amount_target, 0)?;
+
+            result.push(MaspRewardRate {
+                name: reward.name,
+                token: reward.address.to_string(),
+                max_reward_rate: reward.max_reward_rate.to_string(),
+                kp_gain: reward.kp_gain.to_string(),
+                kd_gain: reward.kd_gain.to_string(),
+                locked_amount_target: token::DenominatedAmount::new(locked_amount_target, denom)
+                    .to_string(),
+                last_inflation,
+                last_locked_amount,
+            });
         }
 
-        to_js_result(mapped_result)
+        self.to_js(result)
+    }
+
+    /// Queries an amount stored under the key, formatted with the denomination. Returns None
+    /// if the key has no value.
+    async fn query_denominated(
+        &self,
+        key: &Key,
+        denom: token::Denomination,
+    ) -> Result<Option<String>, JsError> {
+        let (value, _) = rpc::query_storage_value_bytes(&self.client, key, None, false).await?;
+        value
+            .map(|value| {
+                let amount = token::Amount::try_from_slice(&value)?;
+                Ok(token::DenominatedAmount::new(amount, denom).to_string())
+            })
+            .transpose()
+    }
+
+    /// Queries a block with all of its txs, returning raw tx bytes to be decoded with
+    /// deserialize_tx. See explorer::BlockInfo.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - Block height
+    pub async fn query_block_by_height(&self, height: u64) -> Result<JsValue, JsError> {
+        self.to_js(explorer::block_by_height(&self.client, height).await?)
+    }
+
+    /// Queries a tx included in a block by its CometBFT hash, as returned when broadcasting.
+    /// See explorer::BlockTx.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - Hex encoded sha256 hash of the tx bytes
+    pub async fn query_tx_by_hash(&self, hash: String) -> Result<JsValue, JsError> {
+        self.to_js(explorer::tx_by_hash(&self.client, &hash).await?)
+    }
+
+    /// Queries events emitted within a range of blocks, e.g. applied txs, IBC and governance
+    /// events, matching a filter. See event_log::EventFilter.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Event filter with an inclusive height range and optional event type, tx
+    ///   hash and address
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the filter is invalid or any RPC call fails
+    pub async fn query_events(&self, filter: JsValue) -> Result<JsValue, JsError> {
+        let filter: event_log::EventFilter = filter.into_serde()?;
+        let events = event_log::query_events(&self.client, &filter).await?;
+
+        self.to_js(events)
+    }
+
+    /// Builds an activity report for an address or a viewing key within an inclusive height
+    /// range. Reports for viewing keys are computed from the synced shielded context, reports
+    /// for addresses scan at most report::MAX_TRANSPARENT_REPORT_BLOCKS blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - Transparent address or extended viewing key
+    /// * `from_height` - First block height to include
+    /// * `to_height` - Last block height to include
+    /// * `format` - Either "csv" or "json"
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the owner or range is invalid, or any RPC call fails
+    pub async fn query_activity_report(
+        &self,
+        owner: String,
+        from_height: u64,
+        to_height: u64,
+        format: String,
+    ) -> Result<String, JsError> {
+        let format = report::ReportFormat::from_str(&format)?;
+        report::validate_range(from_height, to_height)?;
+
+        let entries = match report::report_owner(&self.native(&owner))? {
+            Either::Left(address) => {
+                let wasm_hashes = self.wasm_hashes().await;
+                report::transparent_report(
+                    &self.client,
+                    &address,
+                    from_height,
+                    to_height,
+                    &wasm_hashes,
+                )
+                .await?
+            }
+            Either::Right(vk) => {
+                let mut shielded: ShieldedContext<JSShieldedUtils> = ShieldedContext::default();
+                shielded.load().await?;
+                report::shielded_report(&shielded, &vk, from_height, to_height)
+            }
+        };
+
+        let prefixes = self.prefixes();
+        let entries: Vec<_> = entries
+            .into_iter()
+            .map(|entry| report::ReportEntry {
+                counterparty: entry.counterparty.map(|c| prefixes.configured(&c)),
+                token: entry.token.map(|t| prefixes.configured(&t)),
+                fee_token: prefixes.configured(&entry.fee_token),
+                ..entry
+            })
+            .collect();
+
+        report::format_report(&entries, format)
+    }
+
+    /// Exports an auditor package for a viewing key as JSON: the notes received in an
+    /// inclusive height range and, optionally, the incoming viewing key. The viewing key
+    /// itself is not exported, see report::AuditorPackage.
+    ///
+    /// # Arguments
+    ///
+    /// * `xvk` - Extended viewing key
+    /// * `from_height` - First block height to include
+    /// * `to_height` - Last block height to include
+    /// * `include_incoming_viewing_key` - Include the incoming viewing key, disclosing all
+    ///   notes received by the key instead of only the ones in the range
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the viewing key or range is invalid, or the shielded context
+    /// can't be loaded
+    pub async fn query_auditor_package(
+        &self,
+        xvk: String,
+        from_height: u64,
+        to_height: u64,
+        include_incoming_viewing_key: bool,
+    ) -> Result<String, JsError> {
+        let xvk = ExtendedViewingKey::from_str(&self.native(&xvk))?;
+        report::validate_range(from_height, to_height)?;
+
+        let mut shielded: ShieldedContext<JSShieldedUtils> = ShieldedContext::default();
+        shielded.load().await?;
+        let package = report::auditor_package(
+            &shielded,
+            &xvk,
+            from_height,
+            to_height,
+            include_incoming_viewing_key,
+        );
+
+        let package = self
+            .prefixes()
+            .configured_json(serde_json::to_value(&package)?);
+
+        Ok(serde_json::to_string(&package)?)
     }
 
     pub async fn query_public_key(&self, address: &str) -> Result<JsValue, JsError> {
-        let addr = Address::from_str(address).map_err(JsError::from)?;
+        let addr = Address::from_str(&self.native(address)).map_err(JsError::from)?;
         let pk = get_public_key_at(&self.client, &addr, 0).await?;
 
         let result = pk.map(|v| v.to_string());
 
-        to_js_result(result)
+        self.to_js(result)
+    }
+
+    /// Returns wrapper txs paid by the fee payer which were broadcast, but are not yet
+    /// included in a block.
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_payer` - Public key or implicit address of the fee payer
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fee payer is invalid or the mempool query fails
+    pub async fn query_unconfirmed_txs(&self, fee_payer: String) -> Result<JsValue, JsError> {
+        let fee_payer = match common::PublicKey::from_str(&self.native(&fee_payer)) {
+            Ok(pk) => Address::from(&pk),
+            Err(_) => Address::from_str(&self.native(&fee_payer))?,
+        };
+
+        let txs = self.client.unconfirmed_txs(MAX_UNCONFIRMED_TXS).await?;
+        let mut result: Vec<PendingTx> = vec![];
+
+        // Mempool can contain txs of other protocol versions, so undecodable ones are skipped
+        for tx in txs
+            .iter()
+            .filter_map(|bytes| Tx::try_from_slice(bytes).ok())
+        {
+            let wrapper = match tx.header.wrapper() {
+                Some(wrapper) if wrapper.fee_payer() == fee_payer => wrapper,
+                _ => continue,
+            };
+            let wrapper_hash = tx.wrapper_hash();
+            let inner_tx_hashes = tx
+                .commitments()
+                .iter()
+                .map(|cmt| {
+                    compute_inner_tx_hash(wrapper_hash.as_ref(), Either::Right(cmt)).to_string()
+                })
+                .collect();
+
+            result.push(PendingTx {
+                hash: tx.header_hash().to_string(),
+                inner_tx_hashes,
+                fee_token: wrapper.fee.token.to_string(),
+                fee_amount_per_gas_unit: wrapper.fee.amount_per_gas_unit.to_string(),
+                gas_limit: u64::from(wrapper.gas_limit).to_string(),
+            });
+        }
+
+        self.to_js(result)
     }
 
     pub async fn query_signed_bridge_pool(
@@ -490,7 +1515,7 @@ impl Query {
         let owner_addresses: Vec<Address> = owner_addresses
             .iter()
             .filter_map(|address| address.as_string())
-            .filter_map(|address| Address::from_str(&address).ok())
+            .filter_map(|address| Address::from_str(&self.native(&address)).ok())
             .collect();
 
         let result: Vec<TransferToEthereum> = bridge_pool
@@ -504,13 +1529,78 @@ impl Query {
             })
             .collect();
 
-        to_js_result(result)
+        self.to_js(result)
+    }
+
+    /// Queries the Namada token and decimals of a bridged ERC-20 asset.
+    /// Returns eth_bridge::WrappedAsset.
+    pub async fn query_wrapped_asset(&self, erc20: String) -> Result<JsValue, JsError> {
+        let erc20 = eth_bridge::parse_eth_address(&erc20)?;
+        let asset = eth_bridge::query_wrapped_asset(&self.client, &erc20).await?;
+
+        self.to_js(asset)
+    }
+
+    /// Queries the ERC-20 address of a Namada token, including wrapped NAM.
+    /// Returns None if the token is not bridged.
+    pub async fn query_erc20_of_token(&self, token: String) -> Result<Option<String>, JsError> {
+        let token = Address::from_str(&self.native(&token))?;
+        let erc20 = eth_bridge::query_erc20_of_token(&self.client, &token).await?;
+
+        Ok(erc20.map(|erc20| erc20.to_string()))
+    }
+
+    /// Queries the transfers in the bridge pool sent by any of the owners, with their
+    /// status. Returns a vec of eth_bridge::BridgePoolTransfer.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner_addresses` - senders of the transfers
+    pub async fn query_bridge_pool_transfers(
+        &self,
+        owner_addresses: Box<[JsValue]>,
+    ) -> Result<JsValue, JsError> {
+        let owner_addresses: Vec<Address> = owner_addresses
+            .iter()
+            .filter_map(|address| address.as_string())
+            .filter_map(|address| Address::from_str(&self.native(&address)).ok())
+            .collect();
+        let transfers = eth_bridge::bridge_pool_transfers(&self.client, &owner_addresses).await?;
+
+        self.to_js(transfers)
+    }
+
+    /// Queries the status of a bridge pool transfer by its keccak hash, one of "pending",
+    /// "signed", "relayed" or "notFound"
+    pub async fn query_bridge_pool_transfer_status(
+        &self,
+        hash: String,
+    ) -> Result<JsValue, JsError> {
+        let status = eth_bridge::transfer_status(&self.client, &hash).await?;
+        self.to_js(status)
+    }
+
+    /// Returns the ABI encoded relay payload of signed bridge pool transfers, to self-relay
+    /// them by calling `transferToErc` of the Ethereum bridge contract
+    ///
+    /// # Arguments
+    ///
+    /// * `hashes` - keccak hashes of the transfers
+    /// * `relayer` - address receiving the fees of the transfers
+    pub async fn query_bridge_pool_relay_proof(
+        &self,
+        hashes: Vec<String>,
+        relayer: String,
+    ) -> Result<Uint8Array, JsError> {
+        let relayer = Address::from_str(&self.native(&relayer))?;
+        let proof = eth_bridge::relay_proof(&self.client, &hashes, relayer).await?;
+        Ok(Uint8Array::from(proof.as_slice()))
     }
 
     pub async fn query_total_staked_tokens(&self, epoch: u64) -> Result<JsValue, JsError> {
         let total_staked_tokens = get_total_staked_tokens(&self.client, Epoch(epoch)).await?;
 
-        to_js_result(total_staked_tokens)
+        self.to_js(total_staked_tokens)
     }
 
     pub async fn query_proposal_counter(&self) -> Result<JsValue, JsError> {
@@ -520,10 +1610,52 @@ impl Query {
                 .await
                 .unwrap();
 
-        to_js_result(proposal_counter)
+        self.to_js(proposal_counter)
     }
 
     pub async fn query_proposal_by_id(&self, id: u64) -> Result<Uint8Array, JsError> {
+        let proposal_info = self.proposal_info(id).await?;
+
+        let mut writer = vec![];
+        BorshSerialize::serialize(&proposal_info, &mut writer)?;
+
+        Ok(Uint8Array::from(writer.as_slice()))
+    }
+
+    /// Queries all proposals starting from an id and passes them to a callback in pages of
+    /// ProposalInfo, see paging::PageSink. Returns the number of pages sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_id` - First proposal id to include
+    /// * `page_size` - Number of proposals per page, defaults to paging::DEFAULT_PAGE_SIZE
+    /// * `on_page` - Callback receiving the pages, may return a Promise
+    pub async fn query_proposals_paged(
+        &self,
+        from_id: u64,
+        page_size: Option<usize>,
+        on_page: Function,
+    ) -> Result<u32, JsError> {
+        let mut sink = PageSink::new(on_page, page_size)?;
+        let counter_key = governance_storage::get_counter_key();
+        let counter = query_storage_value::<HttpClient, u64>(&self.client, &counter_key).await?;
+
+        let mut page: Vec<ProposalInfo> = Vec::with_capacity(sink.page_size());
+        for id in from_id..counter {
+            page.push(self.proposal_info(id).await?);
+            if page.len() == sink.page_size() {
+                sink.send(&page).await?;
+                page.clear();
+            }
+        }
+        if !page.is_empty() {
+            sink.send(&page).await?;
+        }
+
+        Ok(sink.pages_sent())
+    }
+
+    async fn proposal_info(&self, id: u64) -> Result<ProposalInfo, JsError> {
         let proposal = query_proposal_by_id(&self.client, id)
             .await
             .unwrap()
@@ -565,10 +1697,7 @@ impl Query {
             data,
         };
 
-        let mut writer = vec![];
-        BorshSerialize::serialize(&proposal_info, &mut writer)?;
-
-        Ok(Uint8Array::from(writer.as_slice()))
+        Ok(proposal_info)
     }
 
     pub async fn query_proposal_votes(
@@ -613,7 +1742,7 @@ impl Query {
                 (address.clone(), String::from(vote), voting_power)
             }));
 
-        to_js_result((validator_votes, delegator_votes))
+        self.to_js((validator_votes, delegator_votes))
     }
 
     pub async fn query_proposal_result(
@@ -621,28 +1750,14 @@ impl Query {
         proposal_id: u64,
         epoch: u64,
     ) -> Result<JsValue, JsError> {
-        let epoch = Epoch(epoch);
-
-        let votes = compute_proposal_votes(&self.client, proposal_id, epoch).await;
-
-        let total_voting_power = get_total_staked_tokens(&self.client, epoch).await?;
-
-        let proposal = query_proposal_by_id(&self.client, proposal_id)
-            .await
-            .unwrap()
-            .expect("Proposal should be written to storage.");
-        let is_steward = is_steward(&self.client, &proposal.author).await;
-        let tally_type = proposal.get_tally_type(is_steward);
-
-        let proposal_result = compute_proposal_result(votes, total_voting_power, tally_type)
-            .expect("could compute proposal result");
+        let proposal_result = self.proposal_result(proposal_id, epoch).await?;
 
         let passed = match proposal_result.result {
             TallyResult::Passed => true,
             TallyResult::Rejected => false,
         };
 
-        to_js_result((
+        self.to_js((
             passed,
             proposal_result.total_yay_power,
             proposal_result.total_nay_power,
@@ -651,6 +1766,83 @@ impl Query {
         ))
     }
 
+    /// Computes the tally of a proposal at a given epoch
+    pub(crate) async fn proposal_result(
+        &self,
+        proposal_id: u64,
+        epoch: u64,
+    ) -> Result<ProposalResult, JsError> {
+        let epoch = Epoch(epoch);
+
+        let votes = compute_proposal_votes(&self.client, proposal_id, epoch).await;
+
+        let total_voting_power = get_total_staked_tokens(&self.client, epoch).await?;
+
+        let proposal = query_proposal_by_id(&self.client, proposal_id)
+            .await?
+            .ok_or_else(|| JsError::new(&format!("Proposal {} not found", proposal_id)))?;
+        let is_steward = is_steward(&self.client, &proposal.author).await;
+        let tally_type = proposal.get_tally_type(is_steward);
+
+        Ok(compute_proposal_result(
+            votes,
+            total_voting_power,
+            tally_type,
+        )?)
+    }
+
+    /// Queries the deposit of a proposal and whether it is locked, refunded or burned. See
+    /// governance::ProposalDeposit.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_id` - Proposal id
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the proposal doesn't exist or any RPC call fails
+    pub async fn query_proposal_deposit(&self, proposal_id: u64) -> Result<JsValue, JsError> {
+        let deposit = governance::proposal_deposit(&self.client, proposal_id)
+            .await?
+            .ok_or_else(|| JsError::new(&format!("Proposal {} not found", proposal_id)))?;
+
+        self.to_js(deposit)
+    }
+
+    /// Queries the deposits of all proposals of an author, with totals by status. See
+    /// governance::AuthorDeposits.
+    ///
+    /// # Arguments
+    ///
+    /// * `author` - Address of the proposal author
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address is invalid or any RPC call fails
+    pub async fn query_author_deposits(&self, author: String) -> Result<JsValue, JsError> {
+        let author = Address::from_str(&self.native(&author))?;
+
+        self.to_js(governance::author_deposits(&self.client, &author).await?)
+    }
+
+    /// Queries the active continuous PGF fundings. See governance::PgfFunding.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC call fails
+    pub async fn query_pgf_fundings(&self) -> Result<JsValue, JsError> {
+        self.to_js(governance::pgf_fundings(&self.client).await?)
+    }
+
+    /// Queries the PGF stewards with their commissions. See governance::PgfSteward.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the RPC call fails
+    pub async fn query_pgf_stewards(&self) -> Result<JsValue, JsError> {
+        self.to_js(governance::pgf_stewards(&self.client).await?)
+    }
+
     pub async fn query_proposal_code(&self, proposal_id: u64) -> Result<Uint8Array, JsError> {
         let proposal_code_key = governance_storage::get_proposal_code_key(proposal_id);
         let code =
@@ -673,12 +1865,12 @@ impl Query {
         let addresses: Vec<Address> = addresses
             .iter()
             .filter_map(|address| address.as_string())
-            .filter_map(|address| Address::from_str(&address).ok())
+            .filter_map(|address| Address::from_str(&self.native(&address)).ok())
             .collect();
 
         let epoch = epoch.map(Epoch);
 
-        let mut delegations: HashMap<Address, token::Amount> = HashMap::new();
+        let mut delegations: BTreeMap<Address, token::Amount> = BTreeMap::new();
 
         for address in addresses.into_iter() {
             let validators: HashMap<Address, token::Amount> = RPC
@@ -695,7 +1887,7 @@ impl Query {
             delegations.insert(address, sum_of_delegations);
         }
 
-        to_js_result(delegations)
+        self.to_js(delegations)
     }
 
     /// Returns list of delegators that already voted on a proposal
@@ -717,7 +1909,21 @@ impl Query {
             })
             .collect();
 
-        to_js_result(res)
+        self.to_js(res)
+    }
+
+    /// Returns the VoterRole of an address in the current epoch, so the UI can explain the
+    /// weight of its votes
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - address of the voter
+    pub async fn query_voter_role(&self, address: String) -> Result<JsValue, JsError> {
+        let address = Address::from_str(&self.native(&address))?;
+        let epoch = query_epoch(&self.client).await?;
+        let role = voter_role(&self.client, &address, epoch).await?;
+
+        self.to_js(role)
     }
 
     pub async fn query_gas_costs(&self) -> Result<JsValue, JsError> {
@@ -733,59 +1939,288 @@ impl Query {
             result.push((token.to_string(), gas_cost.to_string_native()));
         }
 
-        to_js_result(result)
+        self.to_js(result)
+    }
+
+    /// Estimates the size and minimum gas limit of a MASP tx before it's built and proven,
+    /// so the UI can warn about txs which won't fit in a block. Returns MaspTxEstimate.
+    ///
+    /// # Arguments
+    ///
+    /// * `spends` - Number of spent notes
+    /// * `converts` - Number of conversions of the spent notes
+    /// * `outputs` - Number of created notes, including change
+    /// * `base_gas_limit` - Gas limit of the tx without its MASP transaction, e.g. from the gas
+    ///   table of the indexer
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the parameter queries fails
+    pub async fn estimate_masp_tx(
+        &self,
+        spends: u64,
+        converts: u64,
+        outputs: u64,
+        base_gas_limit: u64,
+    ) -> Result<JsValue, JsError> {
+        let gas_scale =
+            query_storage_value::<HttpClient, u64>(&self.client, &storage::get_gas_scale_key())
+                .await?;
+        let max_block_gas =
+            query_storage_value::<HttpClient, u64>(&self.client, &storage::get_max_block_gas_key())
+                .await?;
+        let max_tx_bytes =
+            query_storage_value::<HttpClient, u32>(&self.client, &storage::get_max_tx_bytes_key())
+                .await?;
+
+        let descriptions = masp_estimate::Descriptions {
+            spends,
+            converts,
+            outputs,
+        };
+        self.to_js(masp_estimate::estimate(
+            descriptions,
+            base_gas_limit,
+            gas_scale,
+            max_tx_bytes.into(),
+            max_block_gas,
+        ))
+    }
+
+    /// Checks whether the shielded balances of gas spending keys are enough to unshield
+    /// the fees of a transaction. Returns a list of (viewing key, balance, required fee,
+    /// shortfall) with raw amounts of the fee token, shortfall being "0" when the balance
+    /// is sufficient.
+    ///
+    /// # Arguments
+    ///
+    /// * `viewing_keys` - Extended viewing keys of the gas spending keys
+    /// * `fee_token` - Address of the token used to pay fees
+    /// * `gas_limit` - Gas limit of the transaction
+    /// * `gas_price` - Denominated fee amount per gas unit, defaults to the minimum gas price
+    ///   of the fee token
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fee token can't be used for fees or any of the queries fails
+    pub async fn query_fee_unshielding_availability(
+        &self,
+        viewing_keys: Box<[JsValue]>,
+        fee_token: String,
+        gas_limit: u64,
+        gas_price: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        let fee_token = Address::from_str(&self.native(&fee_token))?;
+
+        let gas_price = match gas_price {
+            Some(gas_price) => {
+                let denom = rpc::query_denom(&self.client, &fee_token)
+                    .await
+                    .ok_or_else(|| JsError::new(&format!("Unknown token: {}", fee_token)))?;
+                token::DenominatedAmount::from_str(&gas_price)?
+                    .increase_precision(denom)?
+                    .amount()
+            }
+            None => {
+                let key = storage::get_gas_cost_key();
+                let gas_cost_table = query_storage_value::<
+                    HttpClient,
+                    BTreeMap<Address, token::Amount>,
+                >(&self.client, &key)
+                .await?;
+                *gas_cost_table.get(&fee_token).ok_or_else(|| {
+                    JsError::new(&format!("Token can't be used for fees: {}", fee_token))
+                })?
+            }
+        };
+        let required = gas_price
+            .checked_mul(token::Amount::from_u64(gas_limit))
+            .ok_or_else(|| JsError::new("Fee amount overflow"))?;
+
+        let mut result: Vec<(String, String, String, String)> = vec![];
+        for viewing_key in viewing_keys.iter().filter_map(|vk| vk.as_string()) {
+            let xvk = ExtendedViewingKey::from_str(&self.native(&viewing_key))?;
+            let balance = self
+                .query_shielded_balance(xvk, vec![fee_token.clone()])
+                .await?
+                .into_iter()
+                .find(|(token, _)| token == &fee_token)
+                .map(|(_, amount)| amount)
+                .unwrap_or_default();
+            let shortfall = required.checked_sub(balance).unwrap_or_default();
+
+            result.push((
+                viewing_key,
+                balance.to_string(),
+                required.to_string(),
+                shortfall.to_string(),
+            ));
+        }
+
+        self.to_js(result)
     }
 
     pub async fn query_native_token(&self) -> Result<JsValue, JsError> {
         let address = query_native_token(&self.client).await?;
-        to_js_result(address)
+        self.to_js(address)
     }
 
-    // Vec of code paths of supported transactions
-    pub fn code_paths() -> Vec<String> {
-        vec![
-            TX_TRANSFER_WASM.to_string(),
-            TX_BOND_WASM.to_string(),
-            TX_REDELEGATE_WASM.to_string(),
-            TX_UNBOND_WASM.to_string(),
-            TX_WITHDRAW_WASM.to_string(),
-            TX_CLAIM_REWARDS_WASM.to_string(),
-            TX_REVEAL_PK.to_string(),
-            TX_VOTE_PROPOSAL.to_string(),
-            TX_IBC_WASM.to_string(),
-        ]
+    // Vec of code paths of supported transactions on a chain, see code_paths::set_code_paths
+    pub fn code_paths(chain_id: Option<String>) -> Vec<String> {
+        let chain_id = chain_id.map(ChainId);
+        code_paths::code_paths(chain_id.as_ref())
+            .into_iter()
+            .filter(|(code, _)| *code != TxCode::BridgePool)
+            .map(|(_, path)| path)
+            .collect()
     }
 
     // Query supported wasm code-paths, and return a serialized vec of object containing path and hash
     pub async fn query_wasm_hashes(&self) -> Result<JsValue, JsError> {
-        let mut results: Vec<WasmHash> = vec![];
-        let code_paths = Query::code_paths();
-
-        for path in code_paths {
-            let hash = self.query_wasm_hash(&path).await;
-
-            if hash.is_some() {
-                let wasm_hash = WasmHash::new(path, hash.unwrap());
-                results.push(wasm_hash);
-            }
-        }
+        let results = self.wasm_hashes().await;
 
         Ok(JsValue::from_serde(&results).unwrap())
     }
 
+    async fn wasm_hashes(&self) -> Vec<WasmHash> {
+        wasm_hashes(&self.client).await
+    }
+
     // Query hash of wasm code on chain
     pub async fn query_wasm_hash(&self, tx_code_path: &str) -> Option<String> {
-        let hash_key = Key::wasm_hash(tx_code_path);
-        let (tx_code_res, _) = rpc::query_storage_value_bytes(&self.client, &hash_key, None, false)
-            .await
-            .ok()?;
-        if let Some(tx_code_bytes) = tx_code_res {
-            let tx_code = Hash::try_from(&tx_code_bytes[..]).expect("Invalid code hash");
-            Some(tx_code.to_string())
-        } else {
-            None
+        wasm_hash(&self.client, tx_code_path).await
+    }
+}
+
+/// Returns the raw values of storage keys, in the order of the keys. Queries are sent in
+/// JSON-RPC batches of MAX_BATCH_SIZE requests, duplicate keys once, and one by one if the
+/// node doesn't support batches.
+pub(crate) async fn storage_values(
+    client: &HttpClient,
+    keys: &[Key],
+) -> Result<Vec<Option<Vec<u8>>>, JsError> {
+    let mut unique: Vec<&Key> = vec![];
+    let mut indexes = Vec::with_capacity(keys.len());
+    for key in keys {
+        let index = match unique.iter().position(|k| *k == key) {
+            Some(index) => index,
+            None => {
+                unique.push(key);
+                unique.len() - 1
+            }
+        };
+        indexes.push(index);
+    }
+
+    let mut values: Vec<Option<Vec<u8>>> = Vec::with_capacity(unique.len());
+    for chunk in unique.chunks(MAX_BATCH_SIZE) {
+        let paths: Vec<String> = chunk
+            .iter()
+            .map(|key| format!("/shell/value/{}", key))
+            .collect();
+        match client.abci_query_batch(&paths).await {
+            Ok(results) => {
+                for result in results {
+                    values.push(result?);
+                }
+            }
+            Err(_) => {
+                for key in chunk {
+                    let (value, _) =
+                        rpc::query_storage_value_bytes(client, key, None, false).await?;
+                    values.push(value);
+                }
+            }
+        }
+    }
+
+    Ok(indexes
+        .into_iter()
+        .map(|index| values[index].clone())
+        .collect())
+}
+
+/// Returns the hashes of the supported wasm code paths known to the chain
+pub(crate) async fn wasm_hashes(client: &HttpClient) -> Vec<WasmHash> {
+    let mut results: Vec<WasmHash> = vec![];
+    // Code paths may be overridden for the chain, the defaults are used if it's unknown
+    let chain_id = client
+        .latest_block()
+        .await
+        .ok()
+        .map(|block| block.block.header.chain_id.to_string());
+    let code_paths = Query::code_paths(chain_id);
+
+    for path in code_paths {
+        let hash = wasm_hash(client, &path).await;
+
+        if hash.is_some() {
+            let wasm_hash = WasmHash::new(path, hash.unwrap());
+            results.push(wasm_hash);
         }
     }
+
+    results
+}
+
+/// Current time in seconds since the unix epoch, as checked by the light client
+fn now_secs() -> i64 {
+    (js_sys::Date::now() / 1000.0) as i64
+}
+
+async fn wasm_hash(client: &HttpClient, tx_code_path: &str) -> Option<String> {
+    let hash_key = Key::wasm_hash(tx_code_path);
+    let (tx_code_res, _) = rpc::query_storage_value_bytes(client, &hash_key, None, false)
+        .await
+        .ok()?;
+    if let Some(tx_code_bytes) = tx_code_res {
+        let tx_code = Hash::try_from(&tx_code_bytes[..]).expect("Invalid code hash");
+        Some(tx_code.to_string())
+    } else {
+        None
+    }
+}
+
+/// Returns whether an address is a validator, delegator and/or PGF steward at an epoch, with
+/// the voting power it would vote with
+pub async fn voter_role(
+    client: &HttpClient,
+    address: &Address,
+    epoch: Epoch,
+) -> Result<VoterRole, JsError> {
+    let is_validator = rpc::is_validator(client, address).await?;
+    let is_steward = is_steward(client, address).await;
+
+    let (is_delegator, voting_power) = if is_validator {
+        let stake = RPC
+            .vp()
+            .pos()
+            .validator_stake(client, address, &Some(epoch))
+            .await?
+            .unwrap_or_default();
+        (false, stake)
+    } else {
+        let delegations = RPC
+            .vp()
+            .pos()
+            .delegations(client, address, &Some(epoch))
+            .await?;
+        let total = delegations
+            .values()
+            .try_fold(token::Amount::zero(), |acc, amount| {
+                acc.checked_add(*amount)
+            })
+            .ok_or_else(|| JsError::new("Amount overflow"))?;
+        (!delegations.is_empty(), total)
+    };
+
+    Ok(VoterRole {
+        address: address.to_string(),
+        is_validator,
+        is_delegator,
+        is_steward,
+        voting_power: voting_power.to_string(),
+    })
 }
 
 //TODO: remove after moving this fn from apps to shared
@@ -5,7 +5,7 @@ use std::fmt::Display;
 use thiserror::Error;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
 use web_sys::Response;
 
 use namada_sdk::io::Client;
@@ -15,6 +15,8 @@ use namada_sdk::tendermint_rpc::{
     error::Error as TendermintRpcError, Response as RpcResponse, SimpleRequest,
 };
 
+use crate::sdk::response_cache::ResponseCache;
+
 #[wasm_bindgen(module = "/src/rpc_client.js")]
 extern "C" {
     #[wasm_bindgen(catch, js_name = "wasmFetch")]
@@ -54,15 +56,98 @@ impl From<namada_sdk::tendermint_rpc::Error> for RpcError {
     }
 }
 
+/// Maximum number of requests sent in one JSON-RPC batch
+pub const MAX_BATCH_SIZE: usize = 50;
+
+/// Returns the body of a JSON-RPC batch of abci_query requests, with ids the indexes of paths
+fn abci_query_batch_body(paths: &[String]) -> String {
+    let requests: Vec<serde_json::Value> = paths
+        .iter()
+        .enumerate()
+        .map(|(id, path)| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "abci_query",
+                "params": { "path": path, "data": "", "prove": false },
+            })
+        })
+        .collect();
+    serde_json::Value::Array(requests).to_string()
+}
+
+/// Returns the values of a JSON-RPC batch of abci_query responses, in the order of the
+/// requests. An empty value is None.
+///
+/// # Errors
+///
+/// Returns RpcError if the response is not a batch, e.g. the node or a proxy doesn't support
+/// batches, or a response is missing. Failures of single queries are returned in their slot.
+fn parse_abci_query_batch(
+    response: &serde_json::Value,
+    count: usize,
+) -> Result<Vec<Result<Option<Vec<u8>>, RpcError>>, RpcError> {
+    let responses = response
+        .as_array()
+        .ok_or_else(|| RpcError::new("JSON-RPC batches are not supported"))?;
+
+    let mut values: Vec<Option<Result<Option<Vec<u8>>, RpcError>>> = vec![None; count];
+    for response in responses {
+        let Some(id) = response["id"]
+            .as_u64()
+            .map(|id| id as usize)
+            .filter(|id| *id < count)
+        else {
+            continue;
+        };
+        let value = match response.get("error") {
+            Some(error) => Err(RpcError::new(&error.to_string())),
+            None => {
+                let result = &response["result"]["response"];
+                match result["code"].as_u64().unwrap_or_default() {
+                    0 => subtle_encoding::base64::decode(
+                        result["value"].as_str().unwrap_or_default(),
+                    )
+                    .map(|value| (!value.is_empty()).then_some(value))
+                    .map_err(|e| RpcError::new(&format!("Invalid value encoding: {}", e))),
+                    code => Err(RpcError::new(&format!(
+                        "Error code {}: {}",
+                        code,
+                        result["log"].as_str().unwrap_or_default()
+                    ))),
+                }
+            }
+        };
+        values[id] = Some(value);
+    }
+
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(id, value)| {
+            value.ok_or_else(|| RpcError::new(&format!("Missing response {} of batch", id)))
+        })
+        .collect()
+}
+
 #[derive(Clone)]
 pub struct HttpClient {
     url: String,
+    cache: ResponseCache,
 }
 
 /// HttpClient implementation using `window.fetch` API.
 impl HttpClient {
     pub fn new(url: String) -> HttpClient {
-        HttpClient { url }
+        HttpClient {
+            url,
+            cache: ResponseCache::default(),
+        }
+    }
+
+    /// Cache of the responses of `request`, see ResponseCache
+    pub fn response_cache(&self) -> &ResponseCache {
+        &self.cache
     }
 
     async fn fetch(&self, url: &str, method: &str, body: &str) -> Result<JsValue, JsValue> {
@@ -76,6 +161,88 @@ impl HttpClient {
         let resp: Response = resp_value.dyn_into()?;
         JsFuture::from(resp.json().unwrap()).await
     }
+
+    /// Returns the bytes of txs in the mempool of the node, using `unconfirmed_txs`
+    /// endpoint, which is not a part of the tendermint_rpc Client.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of txs to return
+    pub async fn unconfirmed_txs(&self, limit: u32) -> Result<Vec<Vec<u8>>, RpcError> {
+        let request_body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "",
+            "method": "unconfirmed_txs",
+            "params": { "limit": limit.to_string() },
+        })
+        .to_string();
+
+        let response = self
+            .fetch(&self.url[..], "POST", &request_body)
+            .await
+            .map_err(|e| {
+                let e: String = stringify(&e)
+                    .map(|e| e.into())
+                    .unwrap_or_else(|_| String::from("Mempool query failed"));
+                RpcError::new(&e)
+            })?;
+        let response_json: String = stringify(&response)
+            .expect("JS object to be serializable")
+            .into();
+        let response: serde_json::Value =
+            serde_json::from_str(&response_json).map_err(|e| RpcError::new(&e.to_string()))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(RpcError::new(&error.to_string()));
+        }
+
+        response["result"]["txs"]
+            .as_array()
+            .map(|txs| txs.iter().filter_map(|tx| tx.as_str()).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tx| {
+                subtle_encoding::base64::decode(tx)
+                    .map_err(|e| RpcError::new(&format!("Invalid tx encoding: {}", e)))
+            })
+            .collect()
+    }
+
+    /// Performs abci_query requests in one JSON-RPC batch, see parse_abci_query_batch. Values
+    /// are returned in the order of the paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - Query paths, at most MAX_BATCH_SIZE
+    pub async fn abci_query_batch(
+        &self,
+        paths: &[String],
+    ) -> Result<Vec<Result<Option<Vec<u8>>, RpcError>>, RpcError> {
+        if paths.len() > MAX_BATCH_SIZE {
+            return Err(RpcError::new(&format!(
+                "Batch of {} requests over the limit of {}",
+                paths.len(),
+                MAX_BATCH_SIZE
+            )));
+        }
+
+        let response = self
+            .fetch(&self.url[..], "POST", &abci_query_batch_body(paths))
+            .await
+            .map_err(|e| {
+                let e: String = stringify(&e)
+                    .map(|e| e.into())
+                    .unwrap_or_else(|_| String::from("Batch query failed"));
+                RpcError::new(&e)
+            })?;
+        let response_json: String = stringify(&response)
+            .expect("JS object to be serializable")
+            .into();
+        let response: serde_json::Value =
+            serde_json::from_str(&response_json).map_err(|e| RpcError::new(&e.to_string()))?;
+
+        parse_abci_query_batch(&response, paths.len())
+    }
 }
 
 #[async_trait::async_trait(?Send)]
@@ -101,6 +268,12 @@ impl Client for HttpClient {
         prove: bool,
     ) -> Result<EncodedResponseQuery, Self::Error> {
         let data = data.unwrap_or_default();
+        let cached_height = height.map(|height| height.0);
+        if !prove {
+            if let Some(response) = self.cache.get(&path, &data, cached_height) {
+                return Ok(response);
+            }
+        }
         let height = height
             .map(|height| tendermint::block::Height::try_from(height.0))
             .transpose()?;
@@ -108,7 +281,7 @@ impl Client for HttpClient {
         let response = self
             .abci_query(
                 Some(std::str::FromStr::from_str(&path).unwrap()),
-                data,
+                data.clone(),
                 height,
                 prove,
             )
@@ -117,12 +290,18 @@ impl Client for HttpClient {
         let code = Code::from(response.code);
 
         match code {
-            Code::Ok => Ok(EncodedResponseQuery {
-                data: response.value,
-                info: response.info,
-                proof: response.proof,
-                height: response.height.into(),
-            }),
+            Code::Ok => {
+                let response = EncodedResponseQuery {
+                    data: response.value,
+                    info: response.info,
+                    proof: response.proof,
+                    height: response.height.into(),
+                };
+                if !prove {
+                    self.cache.insert(&path, &data, cached_height, &response);
+                }
+                Ok(response)
+            }
             Code::Err(code) => Err(RpcError::new(&format!("Error code {}", code))),
         }
     }
@@ -155,3 +334,96 @@ impl Client for HttpClient {
         Ok(R::Response::from_string(&response_json).unwrap().into())
     }
 }
+
+/// Broadcasts a tx to all endpoints at once using `broadcast_tx_sync`. Resolves with the url
+/// of the first endpoint accepting the tx to its mempool and the returned tx hash.
+///
+/// # Arguments
+///
+/// * `urls` - RPC endpoints
+/// * `tx_bytes` - Signed tx bytes
+///
+/// # Errors
+///
+/// Returns RpcError containing the reasons if no endpoint accepts the tx
+pub async fn broadcast_tx_sync_any(
+    urls: Vec<String>,
+    tx_bytes: Vec<u8>,
+) -> Result<(String, String), RpcError> {
+    let promises = js_sys::Array::new();
+
+    for url in urls {
+        let tx_bytes = tx_bytes.clone();
+        promises.push(&future_to_promise(async move {
+            let client = HttpClient::new(url.clone());
+            let response = client
+                .broadcast_tx_sync(tx_bytes)
+                .await
+                .map_err(|e| JsValue::from_str(&format!("{}: {}", url, e)))?;
+
+            if response.code.is_err() {
+                return Err(JsValue::from_str(&format!("{}: {}", url, response.log)));
+            }
+
+            Ok(js_sys::Array::of2(
+                &JsValue::from_str(&url),
+                &JsValue::from_str(&response.hash.to_string()),
+            )
+            .into())
+        }));
+    }
+
+    let accepted = JsFuture::from(js_sys::Promise::any(&promises))
+        .await
+        .map_err(|e| {
+            // Promise.any rejects with an AggregateError listing all of the rejections
+            let errors = js_sys::Reflect::get(&e, &JsValue::from_str("errors"))
+                .map(|errors| js_sys::Array::from(&errors))
+                .unwrap_or_else(|_| js_sys::Array::new());
+            let errors: Vec<String> = errors.iter().filter_map(|e| e.as_string()).collect();
+            RpcError::new(&format!(
+                "Tx rejected by all endpoints: {}",
+                errors.join(", ")
+            ))
+        })?;
+    let accepted = js_sys::Array::from(&accepted);
+
+    Ok((
+        accepted.get(0).as_string().unwrap_or_default(),
+        accepted.get(1).as_string().unwrap_or_default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn parses_batch_responses_by_id() {
+        let paths = vec!["/shell/value/a".to_string(), "/shell/value/b".to_string()];
+        let body: serde_json::Value = serde_json::from_str(&abci_query_batch_body(&paths)).unwrap();
+        assert_eq!(body[1]["id"], 1);
+        assert_eq!(body[1]["params"]["path"], "/shell/value/b");
+
+        // Responses may come in any order
+        let response = serde_json::json!([
+            { "id": 1, "result": { "response": { "code": 0, "value": "" } } },
+            { "id": 0, "result": { "response": { "code": 0, "value": "AQI=" } } },
+        ]);
+        let values = parse_abci_query_batch(&response, 2).unwrap();
+        assert_eq!(values[0].as_ref().unwrap(), &Some(vec![1, 2]));
+        assert_eq!(values[1].as_ref().unwrap(), &None);
+
+        let failed = serde_json::json!([
+            { "id": 0, "result": { "response": { "code": 1, "log": "invalid key" } } },
+        ]);
+        let values = parse_abci_query_batch(&failed, 1).unwrap();
+        assert!(values[0].is_err());
+        // A missing response fails the batch
+        assert!(parse_abci_query_batch(&failed, 2).is_err());
+
+        let unsupported = serde_json::json!({ "error": { "code": -32600 } });
+        assert!(parse_abci_query_batch(&unsupported, 1).is_err());
+    }
+}
@@ -0,0 +1,490 @@
+//! Scheduler refreshing registered queries in the background
+//!
+//! JS drives the scheduler by calling `tick` periodically(e.g. from setInterval). Each tick runs
+//! queries that are due, skipping the ones still in flight, compares the result with the previous
+//! snapshot and dispatches a change event containing only the differences.
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+use gloo_utils::format::JsValueSerdeExt;
+use namada_sdk::borsh::BorshDeserialize;
+use namada_sdk::token::Amount;
+use rand::Rng;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::query::Query;
+use crate::sdk::events::EventDispatcher;
+use crate::types::query::ProposalInfo;
+
+#[derive(Debug, Clone)]
+enum RefreshKind {
+    Balance { owner: String, tokens: Vec<String> },
+    StakingPositions { owners: Vec<String> },
+    Proposal { proposal_id: u64 },
+}
+
+type Bond = (String, String, String, String);
+type Unbond = (String, String, String, String, String);
+
+/// Last known state of a registered query
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Snapshot {
+    Balance {
+        balances: BTreeMap<String, String>,
+    },
+    StakingPositions {
+        bonds: BTreeSet<Bond>,
+        unbonds: BTreeSet<Unbond>,
+    },
+    Proposal {
+        status: String,
+        yay: String,
+        nay: String,
+        abstain: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct BalanceDelta {
+    token: String,
+    previous: String,
+    current: String,
+    // Signed raw amount difference, e.g. "+100" or "-100"
+    delta: String,
+}
+
+/// Differences between two snapshots, delivered to JS
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Changes {
+    Balance {
+        deltas: Vec<BalanceDelta>,
+        // Tokens no longer watched, e.g. after registering the entry again with fewer tokens
+        removed_tokens: Vec<String>,
+    },
+    StakingPositions {
+        new_bonds: Vec<Bond>,
+        removed_bonds: Vec<Bond>,
+        new_unbonds: Vec<Unbond>,
+        removed_unbonds: Vec<Unbond>,
+    },
+    Proposal {
+        previous_status: Option<String>,
+        status: String,
+        yay: String,
+        nay: String,
+        abstain: String,
+    },
+}
+
+fn balance_delta(token: &str, previous: &str, current: &str) -> Result<BalanceDelta, JsError> {
+    let previous_amount = Amount::from_str(previous, 0u8)?;
+    let current_amount = Amount::from_str(current, 0u8)?;
+
+    let delta = if current_amount >= previous_amount {
+        format!("+{}", current_amount - previous_amount)
+    } else {
+        format!("-{}", previous_amount - current_amount)
+    };
+
+    Ok(BalanceDelta {
+        token: token.to_string(),
+        previous: previous.to_string(),
+        current: current.to_string(),
+        delta,
+    })
+}
+
+/// Computes changes between the previous and the current snapshot.
+/// Returns None if nothing changed.
+fn diff(previous: Option<&Snapshot>, current: &Snapshot) -> Result<Option<Changes>, JsError> {
+    if previous == Some(current) {
+        return Ok(None);
+    }
+
+    let changes = match current {
+        Snapshot::Balance { balances: current } => {
+            let empty = BTreeMap::new();
+            let previous = match previous {
+                Some(Snapshot::Balance { balances }) => balances,
+                _ => &empty,
+            };
+            let mut deltas = vec![];
+            for (token, amount) in current.iter() {
+                let previous_amount = previous.get(token).map(String::as_str).unwrap_or("0");
+                if previous_amount != amount {
+                    deltas.push(balance_delta(token, previous_amount, amount)?);
+                }
+            }
+            let removed_tokens = previous
+                .keys()
+                .filter(|token| !current.contains_key(*token))
+                .cloned()
+                .collect();
+            Changes::Balance {
+                deltas,
+                removed_tokens,
+            }
+        }
+        Snapshot::StakingPositions { bonds, unbonds } => {
+            let empty_bonds = BTreeSet::new();
+            let empty_unbonds = BTreeSet::new();
+            let (previous_bonds, previous_unbonds) = match previous {
+                Some(Snapshot::StakingPositions { bonds, unbonds }) => (bonds, unbonds),
+                _ => (&empty_bonds, &empty_unbonds),
+            };
+            Changes::StakingPositions {
+                new_bonds: bonds.difference(previous_bonds).cloned().collect(),
+                removed_bonds: previous_bonds.difference(bonds).cloned().collect(),
+                new_unbonds: unbonds.difference(previous_unbonds).cloned().collect(),
+                removed_unbonds: previous_unbonds.difference(unbonds).cloned().collect(),
+            }
+        }
+        Snapshot::Proposal {
+            status,
+            yay,
+            nay,
+            abstain,
+        } => {
+            let previous_status = match previous {
+                Some(Snapshot::Proposal { status, .. }) => Some(status.clone()),
+                _ => None,
+            };
+            Changes::Proposal {
+                previous_status,
+                status: status.clone(),
+                yay: yay.clone(),
+                nay: nay.clone(),
+                abstain: abstain.clone(),
+            }
+        }
+    };
+
+    Ok(Some(changes))
+}
+
+#[derive(Debug)]
+struct ScheduledQuery {
+    kind: RefreshKind,
+    interval_ms: f64,
+    jitter_ms: f64,
+    next_run_ms: f64,
+    in_flight: bool,
+    snapshot: Option<Snapshot>,
+}
+
+impl ScheduledQuery {
+    fn new(kind: RefreshKind, interval_ms: u32, jitter_ms: u32) -> ScheduledQuery {
+        ScheduledQuery {
+            kind,
+            interval_ms: interval_ms.into(),
+            jitter_ms: jitter_ms.into(),
+            // Run on the first tick
+            next_run_ms: 0.0,
+            in_flight: false,
+            snapshot: None,
+        }
+    }
+
+    /// Whether the snapshot of a previous entry with the given kind describes the same
+    /// query, so that changes can be computed against it
+    fn continues(&self, kind: &RefreshKind) -> bool {
+        match (&self.kind, kind) {
+            (
+                RefreshKind::Balance { owner, .. },
+                RefreshKind::Balance {
+                    owner: new_owner, ..
+                },
+            ) => owner == new_owner,
+            _ => false,
+        }
+    }
+
+    fn schedule_next(&mut self, now_ms: f64) {
+        let jitter = if self.jitter_ms > 0.0 {
+            rand::thread_rng().gen_range(0.0..self.jitter_ms)
+        } else {
+            0.0
+        };
+        self.next_run_ms = now_ms + self.interval_ms + jitter;
+    }
+}
+
+/// Marks the entries of a tick as no longer in flight when dropped, so that they are run again
+/// even if the tick returns early with an error or its future is dropped
+struct InFlight<'a> {
+    entries: &'a RefCell<BTreeMap<String, ScheduledQuery>>,
+    ids: Vec<String>,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let mut entries = self.entries.borrow_mut();
+        for id in &self.ids {
+            // Entry might have been unregistered in the meantime
+            if let Some(entry) = entries.get_mut(id) {
+                entry.in_flight = false;
+            }
+        }
+    }
+}
+
+fn to_js_array(values: &[String]) -> Box<[JsValue]> {
+    values
+        .iter()
+        .map(|value| JsValue::from_str(value))
+        .collect::<Vec<JsValue>>()
+        .into_boxed_slice()
+}
+
+#[wasm_bindgen]
+/// Refreshes registered queries at configurable intervals
+pub struct Scheduler {
+    query: Query,
+    entries: RefCell<BTreeMap<String, ScheduledQuery>>,
+}
+
+#[wasm_bindgen]
+impl Scheduler {
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: String, masp_url: Option<String>, indexer_url: Option<String>) -> Scheduler {
+        Scheduler {
+            query: Query::new(url, masp_url, indexer_url),
+            entries: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Registers balance refreshes for an address or viewing key. Registering an existing id
+    /// replaces the previous entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the entry, passed along with change events
+    /// * `owner` - Address or viewing key
+    /// * `tokens` - Token addresses
+    /// * `interval_ms` - Refresh interval
+    /// * `jitter_ms` - Maximum random delay added to each interval
+    pub fn register_balance(
+        &self,
+        id: String,
+        owner: String,
+        tokens: Box<[JsValue]>,
+        interval_ms: u32,
+        jitter_ms: u32,
+    ) {
+        let tokens = tokens
+            .iter()
+            .filter_map(|token| token.as_string())
+            .collect();
+        self.register(
+            id,
+            RefreshKind::Balance { owner, tokens },
+            interval_ms,
+            jitter_ms,
+        );
+    }
+
+    /// Registers staking positions refreshes for given addresses. Registering an existing id
+    /// replaces the previous entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the entry, passed along with change events
+    /// * `owners` - Addresses
+    /// * `interval_ms` - Refresh interval
+    /// * `jitter_ms` - Maximum random delay added to each interval
+    pub fn register_staking_positions(
+        &self,
+        id: String,
+        owners: Box<[JsValue]>,
+        interval_ms: u32,
+        jitter_ms: u32,
+    ) {
+        let owners = owners
+            .iter()
+            .filter_map(|owner| owner.as_string())
+            .collect();
+        self.register(
+            id,
+            RefreshKind::StakingPositions { owners },
+            interval_ms,
+            jitter_ms,
+        );
+    }
+
+    /// Registers proposal status and tally refreshes. Registering an existing id
+    /// replaces the previous entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Id of the entry, passed along with change events
+    /// * `proposal_id` - Id of the governance proposal
+    /// * `interval_ms` - Refresh interval
+    /// * `jitter_ms` - Maximum random delay added to each interval
+    pub fn register_proposal(
+        &self,
+        id: String,
+        proposal_id: u64,
+        interval_ms: u32,
+        jitter_ms: u32,
+    ) {
+        self.register(
+            id,
+            RefreshKind::Proposal { proposal_id },
+            interval_ms,
+            jitter_ms,
+        );
+    }
+
+    pub fn unregister(&self, id: String) {
+        self.entries.borrow_mut().remove(&id);
+    }
+
+    /// Runs all queries that are due and not already in flight, dispatching change events
+    /// for the ones whose state changed since the previous run.
+    ///
+    /// # Arguments
+    ///
+    /// * `now_ms` - Current timestamp in milliseconds, e.g. Date.now()
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the entries whose changes couldn't be computed or dispatched,
+    /// after all due entries ran. Failed queries are retried on the next interval.
+    pub async fn tick(&self, now_ms: f64) -> Result<(), JsError> {
+        let due: Vec<(String, RefreshKind)> = self
+            .entries
+            .borrow_mut()
+            .iter_mut()
+            .filter(|(_, entry)| !entry.in_flight && entry.next_run_ms <= now_ms)
+            .map(|(id, entry)| {
+                entry.in_flight = true;
+                entry.schedule_next(now_ms);
+                (id.clone(), entry.kind.clone())
+            })
+            .collect();
+
+        let _in_flight = InFlight {
+            entries: &self.entries,
+            ids: due.iter().map(|(id, _)| id.clone()).collect(),
+        };
+
+        let mut failed: Vec<String> = vec![];
+        for (id, kind) in due {
+            let snapshot = self.run(&kind).await;
+            if self.update(&id, snapshot).is_err() {
+                failed.push(id);
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(JsError::new(&format!(
+                "Changes of scheduler entries {} could not be dispatched",
+                failed.join(", ")
+            )))
+        }
+    }
+
+    /// Returns the last known state of an entry as JSON
+    pub fn last_result(&self, id: String) -> Result<Option<String>, JsError> {
+        self.entries
+            .borrow()
+            .get(&id)
+            .and_then(|entry| entry.snapshot.as_ref())
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(JsError::from)
+    }
+}
+
+impl Scheduler {
+    fn register(&self, id: String, kind: RefreshKind, interval_ms: u32, jitter_ms: u32) {
+        let mut entries = self.entries.borrow_mut();
+        let mut entry = ScheduledQuery::new(kind, interval_ms, jitter_ms);
+        // Keep the snapshot of the same query, so that e.g. removed tokens are reported
+        if let Some(previous) = entries.remove(&id) {
+            if previous.continues(&entry.kind) {
+                entry.snapshot = previous.snapshot;
+            }
+        }
+        entries.insert(id, entry);
+    }
+
+    /// Stores the snapshot of an entry and dispatches its changes
+    fn update(&self, id: &str, snapshot: Result<Snapshot, JsError>) -> Result<(), JsError> {
+        // Entry might have been unregistered in the meantime
+        let changes = match self.entries.borrow_mut().get_mut(id) {
+            Some(entry) => {
+                entry.in_flight = false;
+                match snapshot {
+                    Ok(snapshot) => {
+                        let changes = diff(entry.snapshot.as_ref(), &snapshot)?;
+                        entry.snapshot = Some(snapshot);
+                        changes
+                    }
+                    Err(_) => None,
+                }
+            }
+            None => None,
+        };
+
+        if let Some(changes) = changes {
+            EventDispatcher::new()
+                .scheduler_changed(id.to_string(), serde_json::to_string(&changes)?)?;
+        }
+        Ok(())
+    }
+
+    async fn run(&self, kind: &RefreshKind) -> Result<Snapshot, JsError> {
+        let snapshot = match kind {
+            RefreshKind::Balance { owner, tokens } => {
+                let balances: Vec<(String, String)> = self
+                    .query
+                    .query_balance(owner.clone(), to_js_array(tokens))
+                    .await?
+                    .into_serde()?;
+                Snapshot::Balance {
+                    balances: balances.into_iter().collect(),
+                }
+            }
+            RefreshKind::StakingPositions { owners } => {
+                let (bonds, unbonds): (Vec<Bond>, Vec<Unbond>) = self
+                    .query
+                    .query_staking_positions(to_js_array(owners))
+                    .await?
+                    .into_serde()?;
+                Snapshot::StakingPositions {
+                    bonds: bonds.into_iter().collect(),
+                    unbonds: unbonds.into_iter().collect(),
+                }
+            }
+            RefreshKind::Proposal { proposal_id } => {
+                let epoch = self.query.query_epoch().await?;
+                let proposal = self.query.query_proposal_by_id(*proposal_id).await?;
+                let proposal = ProposalInfo::try_from_slice(&proposal.to_vec())?;
+
+                let status = if epoch < proposal.start_epoch {
+                    "pending"
+                } else if epoch <= proposal.end_epoch {
+                    "ongoing"
+                } else {
+                    "ended"
+                };
+                let result = self.query.proposal_result(*proposal_id, epoch).await?;
+
+                Snapshot::Proposal {
+                    status: status.to_string(),
+                    yay: result.total_yay_power.to_string(),
+                    nay: result.total_nay_power.to_string(),
+                    abstain: result.total_abstain_power.to_string(),
+                }
+            }
+        };
+
+        Ok(snapshot)
+    }
+}
@@ -1,23 +1,32 @@
-use std::{path::PathBuf, str::FromStr};
+use std::str::FromStr;
 
-use namada_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use namada_sdk::borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use namada_sdk::ibc::core::host::types::identifiers::{ChannelId, PortId};
-use namada_sdk::ibc::IbcShieldingData;
-use namada_sdk::tendermint_rpc;
 use namada_sdk::tx::data::GasLimit;
 use namada_sdk::{
     address::Address,
     args::{self, InputAmount, TxExpiration},
     chain::ChainId,
+    dec::Dec,
     ethereum_events::EthAddress,
     key::common::PublicKey,
+    time::DateTimeUtc,
     token::{Amount, DenominatedAmount, NATIVE_MAX_DECIMAL_PLACES},
-    TransferSource,
+    TransferSource, TransferTarget,
 };
 use namada_sdk::{ExtendedSpendingKey, PaymentAddress};
-use wasm_bindgen::JsError;
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+use super::decode::decode_tagged_msg;
+use super::encrypted_memo;
+use super::error::{excerpt, ArgContext, JsError};
+use super::ibc::validate_shielding_data;
+use super::memo::validate_ibc_memo;
+use super::msg_migration::VersionedMsg;
+use super::prefixes::ChainPrefixes;
+use super::sdk_args;
+use super::shielded_key::spending_key;
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct RevealPkMsg {
     public_key: String,
@@ -29,7 +38,7 @@ impl RevealPkMsg {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct WrapperTxMsg {
     token: String,
@@ -39,9 +48,18 @@ pub struct WrapperTxMsg {
     public_key: Option<String>,
     memo: Option<String>,
     force: Option<bool>,
+    // RFC 3339 date, see Query::query_tx_expiration
+    expiration: Option<String>,
+    /// Public key paying the fee, defaults to the first signer
+    fee_payer: Option<String>,
+    /// Public keys signing the inner txs, defaults to public_key
+    signers: Option<Vec<String>>,
+    /// Hex encoded X25519 public key to encrypt the memo to, see encrypted_memo
+    memo_recipient: Option<String>,
 }
 
 impl WrapperTxMsg {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         token: String,
         fee_amount: String,
@@ -50,6 +68,10 @@ impl WrapperTxMsg {
         public_key: Option<String>,
         memo: Option<String>,
         force: Option<bool>,
+        expiration: Option<String>,
+        fee_payer: Option<String>,
+        signers: Option<Vec<String>>,
+        memo_recipient: Option<String>,
     ) -> WrapperTxMsg {
         WrapperTxMsg {
             token,
@@ -59,11 +81,24 @@ impl WrapperTxMsg {
             public_key,
             memo,
             force,
+            expiration,
+            fee_payer,
+            signers,
+            memo_recipient,
+        }
+    }
+
+    /// Returns a copy of the message with a different fee amount per gas unit and gas limit
+    pub fn with_fee(&self, fee_amount: String, gas_limit: String) -> WrapperTxMsg {
+        WrapperTxMsg {
+            fee_amount,
+            gas_limit,
+            ..self.clone()
         }
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct BondMsg {
     source: String,
@@ -92,8 +127,12 @@ impl BondMsg {
 ///
 /// Returns JsError if the tx_msg can't be deserialized or
 /// Rust structs can't be created.
-pub fn bond_tx_args(bond_msg: &[u8], tx_msg: &[u8]) -> Result<args::Bond, JsError> {
-    let bond_msg = BondMsg::try_from_slice(bond_msg)?;
+pub fn bond_tx_args(
+    bond_msg: &[u8],
+    tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<args::Bond, JsError> {
+    let bond_msg = decode_tagged_msg::<BondMsg>(bond_msg)?;
 
     let BondMsg {
         source,
@@ -101,23 +140,17 @@ pub fn bond_tx_args(bond_msg: &[u8], tx_msg: &[u8]) -> Result<args::Bond, JsErro
         amount,
     } = bond_msg;
 
-    let source = Address::from_str(&source)?;
-    let validator = Address::from_str(&validator)?;
-    let amount = Amount::from_str(&amount, NATIVE_MAX_DECIMAL_PLACES)?;
-    let tx = tx_msg_into_args(tx_msg)?;
-
-    let args = args::Bond {
-        tx,
-        validator,
-        amount,
-        source: Some(source),
-        tx_code_path: PathBuf::from("tx_bond.wasm"),
-    };
+    let source = Address::from_str(&prefixes.native(&source)).arg("bond", "source", &source)?;
+    let validator =
+        Address::from_str(&prefixes.native(&validator)).arg("bond", "validator", &validator)?;
+    let amount =
+        Amount::from_str(&amount, NATIVE_MAX_DECIMAL_PLACES).arg("bond", "amount", &amount)?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
 
-    Ok(args)
+    Ok(sdk_args::bond(tx, source, validator, amount))
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct UnbondMsg {
     source: String,
@@ -146,8 +179,12 @@ impl UnbondMsg {
 ///
 /// Returns JsError if the tx_msg can't be deserialized or
 /// Rust structs can't be created.
-pub fn unbond_tx_args(unbond_msg: &[u8], tx_msg: &[u8]) -> Result<args::Unbond, JsError> {
-    let unbond_msg = UnbondMsg::try_from_slice(unbond_msg)?;
+pub fn unbond_tx_args(
+    unbond_msg: &[u8],
+    tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<args::Unbond, JsError> {
+    let unbond_msg = decode_tagged_msg::<UnbondMsg>(unbond_msg)?;
 
     let UnbondMsg {
         source,
@@ -155,24 +192,18 @@ pub fn unbond_tx_args(unbond_msg: &[u8], tx_msg: &[u8]) -> Result<args::Unbond,
         amount,
     } = unbond_msg;
 
-    let source = Address::from_str(&source)?;
-    let validator = Address::from_str(&validator)?;
+    let source = Address::from_str(&prefixes.native(&source)).arg("unbond", "source", &source)?;
+    let validator =
+        Address::from_str(&prefixes.native(&validator)).arg("unbond", "validator", &validator)?;
 
-    let amount = Amount::from_str(&amount, NATIVE_MAX_DECIMAL_PLACES)?;
-    let tx = tx_msg_into_args(tx_msg)?;
-
-    let args = args::Unbond {
-        tx,
-        validator,
-        amount,
-        source: Some(source),
-        tx_code_path: PathBuf::from("tx_unbond.wasm"),
-    };
+    let amount =
+        Amount::from_str(&amount, NATIVE_MAX_DECIMAL_PLACES).arg("unbond", "amount", &amount)?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
 
-    Ok(args)
+    Ok(sdk_args::unbond(tx, source, validator, amount))
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct WithdrawMsg {
     source: String,
@@ -196,26 +227,24 @@ impl WithdrawMsg {
 ///
 /// Returns JsError if the tx_msg can't be deserialized or
 /// Rust structs can't be created.
-pub fn withdraw_tx_args(withdraw_msg: &[u8], tx_msg: &[u8]) -> Result<args::Withdraw, JsError> {
-    let withdraw_msg = WithdrawMsg::try_from_slice(withdraw_msg)?;
+pub fn withdraw_tx_args(
+    withdraw_msg: &[u8],
+    tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<args::Withdraw, JsError> {
+    let withdraw_msg = decode_tagged_msg::<WithdrawMsg>(withdraw_msg)?;
 
     let WithdrawMsg { source, validator } = withdraw_msg;
 
-    let source = Address::from_str(&source)?;
-    let validator = Address::from_str(&validator)?;
-    let tx = tx_msg_into_args(tx_msg)?;
-
-    let args = args::Withdraw {
-        tx,
-        validator,
-        source: Some(source),
-        tx_code_path: PathBuf::from("tx_withdraw.wasm"),
-    };
+    let source = Address::from_str(&prefixes.native(&source)).arg("withdraw", "source", &source)?;
+    let validator =
+        Address::from_str(&prefixes.native(&validator)).arg("withdraw", "validator", &validator)?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
 
-    Ok(args)
+    Ok(sdk_args::withdraw(tx, source, validator))
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct RedelegateMsg {
     owner: String,
@@ -254,8 +283,9 @@ impl RedelegateMsg {
 pub fn redelegate_tx_args(
     redelegate_msg: &[u8],
     tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
 ) -> Result<args::Redelegate, JsError> {
-    let redelegate_msg = RedelegateMsg::try_from_slice(redelegate_msg)?;
+    let redelegate_msg = decode_tagged_msg::<RedelegateMsg>(redelegate_msg)?;
 
     let RedelegateMsg {
         owner,
@@ -264,25 +294,69 @@ pub fn redelegate_tx_args(
         amount,
     } = redelegate_msg;
 
-    let owner = Address::from_str(&owner)?;
-    let src_validator = Address::from_str(&source_validator)?;
-    let dest_validator = Address::from_str(&destination_validator)?;
-    let amount = Amount::from_str(&amount, NATIVE_MAX_DECIMAL_PLACES)?;
-    let tx = tx_msg_into_args(tx_msg)?;
-
-    let args = args::Redelegate {
+    let owner = Address::from_str(&prefixes.native(&owner)).arg("redelegate", "owner", &owner)?;
+    let src_validator = Address::from_str(&prefixes.native(&source_validator)).arg(
+        "redelegate",
+        "source_validator",
+        &source_validator,
+    )?;
+    let dest_validator = Address::from_str(&prefixes.native(&destination_validator)).arg(
+        "redelegate",
+        "destination_validator",
+        &destination_validator,
+    )?;
+    let amount = Amount::from_str(&amount, NATIVE_MAX_DECIMAL_PLACES).arg(
+        "redelegate",
+        "amount",
+        &amount,
+    )?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
+
+    Ok(sdk_args::redelegate(
         tx,
+        owner,
         src_validator,
         dest_validator,
         amount,
-        owner,
-        tx_code_path: PathBuf::from("tx_redelegate.wasm"),
-    };
+    ))
+}
 
-    Ok(args)
+/// Vote on a governance proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalVote {
+    Yay,
+    Nay,
+    Abstain,
+}
+
+impl ProposalVote {
+    /// Vote as expected by namada_sdk
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProposalVote::Yay => "yay",
+            ProposalVote::Nay => "nay",
+            ProposalVote::Abstain => "abstain",
+        }
+    }
+}
+
+impl FromStr for ProposalVote {
+    type Err = JsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "yay" => Ok(ProposalVote::Yay),
+            "nay" => Ok(ProposalVote::Nay),
+            "abstain" => Ok(ProposalVote::Abstain),
+            _ => Err(JsError::new(&format!(
+                "vote proposal: invalid vote \"{}\": expected yay, nay or abstain",
+                excerpt(s)
+            ))),
+        }
+    }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct VoteProposalMsg {
     signer: String,
@@ -298,6 +372,10 @@ impl VoteProposalMsg {
             vote,
         }
     }
+
+    pub fn vote(&self) -> Result<ProposalVote, JsError> {
+        ProposalVote::from_str(&self.vote)
+    }
 }
 
 /// Maps serialized tx_msg into VoteProposalTx args.
@@ -314,29 +392,29 @@ impl VoteProposalMsg {
 pub fn vote_proposal_tx_args(
     vote_proposal_msg: &[u8],
     tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
 ) -> Result<args::VoteProposal, JsError> {
-    let vote_proposal_msg = VoteProposalMsg::try_from_slice(vote_proposal_msg)?;
+    let vote_proposal_msg = decode_tagged_msg::<VoteProposalMsg>(vote_proposal_msg)?;
+    let vote = vote_proposal_msg.vote()?;
 
     let VoteProposalMsg {
         signer,
         proposal_id,
-        vote,
+        ..
     } = vote_proposal_msg;
-    let tx = tx_msg_into_args(tx_msg)?;
-    let voter_address = Address::from_str(&signer)?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
+    let voter_address =
+        Address::from_str(&prefixes.native(&signer)).arg("vote proposal", "signer", &signer)?;
 
-    let args = args::VoteProposal {
+    Ok(sdk_args::vote_proposal(
         tx,
-        proposal_id,
-        vote,
         voter_address,
-        tx_code_path: PathBuf::from("tx_vote_proposal.wasm"),
-    };
-
-    Ok(args)
+        proposal_id,
+        vote.as_str().to_string(),
+    ))
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct ClaimRewardsMsg {
     validator: String,
@@ -363,26 +441,377 @@ impl ClaimRewardsMsg {
 pub fn claim_rewards_tx_args(
     claim_rewards_msg: &[u8],
     tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
 ) -> Result<args::ClaimRewards, JsError> {
-    let claim_rewards_msg = ClaimRewardsMsg::try_from_slice(claim_rewards_msg)?;
+    let claim_rewards_msg = decode_tagged_msg::<ClaimRewardsMsg>(claim_rewards_msg)?;
 
     let ClaimRewardsMsg { validator, source } = claim_rewards_msg;
-    let tx = tx_msg_into_args(tx_msg)?;
-
-    let validator_address = Address::from_str(&validator)?;
-    let source_address = source.map(|str| Address::from_str(&str).expect("valid address"));
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
+
+    let validator_address = Address::from_str(&prefixes.native(&validator)).arg(
+        "claim rewards",
+        "validator",
+        &validator,
+    )?;
+    let source_address = source
+        .map(|source| {
+            Address::from_str(&prefixes.native(&source)).arg("claim rewards", "source", &source)
+        })
+        .transpose()?;
 
-    let args = args::ClaimRewards {
+    Ok(sdk_args::claim_rewards(
         tx,
-        validator: validator_address,
-        source: source_address,
-        tx_code_path: PathBuf::from("tx_claim_rewards.wasm"),
+        validator_address,
+        source_address,
+    ))
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct InitAccountMsg {
+    public_keys: Vec<String>,
+    /// Number of signatures required to act for the account, all keys if left out
+    threshold: Option<u8>,
+}
+
+impl InitAccountMsg {
+    pub fn new(public_keys: Vec<String>, threshold: Option<u8>) -> InitAccountMsg {
+        InitAccountMsg {
+            public_keys,
+            threshold,
+        }
+    }
+}
+
+/// Parses the public keys of an account, rejecting repeated keys
+fn account_public_keys(
+    operation: &'static str,
+    public_keys: &[String],
+    prefixes: &ChainPrefixes,
+) -> Result<Vec<PublicKey>, JsError> {
+    let public_keys = public_keys
+        .iter()
+        .map(|key| PublicKey::from_str(&prefixes.native(key)).arg(operation, "public_keys", key))
+        .collect::<Result<Vec<PublicKey>, JsError>>()?;
+    if let Some(key) = public_keys
+        .iter()
+        .enumerate()
+        .find_map(|(i, key)| public_keys[..i].contains(key).then_some(key))
+    {
+        return Err(JsError::new(&format!(
+            "Public key is repeated in {}: {}",
+            operation, key
+        )));
+    }
+    Ok(public_keys)
+}
+
+/// Checks that a threshold is between 1 and the number of keys of the account
+fn check_threshold(operation: &str, threshold: u8, keys: usize) -> Result<(), JsError> {
+    if threshold == 0 || threshold as usize > keys {
+        return Err(JsError::new(&format!(
+            "{} threshold must be between 1 and {}, got {}",
+            operation, keys, threshold
+        )));
+    }
+    Ok(())
+}
+
+/// Checks the threshold of an update keeping the current public keys of the account
+///
+/// # Arguments
+///
+/// * `threshold` - New threshold
+/// * `current_keys` - Number of public keys of the account on chain
+///
+/// # Errors
+///
+/// Returns JsError if the threshold is 0 or exceeds the number of current keys
+pub fn check_current_threshold(threshold: u8, current_keys: usize) -> Result<(), JsError> {
+    check_threshold("Update account", threshold, current_keys)
+}
+
+/// Returns the threshold requiring all of the keys, which only fits in a u8 for up to 255 keys
+fn all_keys_threshold(operation: &str, keys: usize) -> Result<u8, JsError> {
+    u8::try_from(keys).map_err(|_| {
+        JsError::new(&format!(
+            "{} supports at most {} public keys, got {}",
+            operation,
+            u8::MAX,
+            keys
+        ))
+    })
+}
+
+/// Maps serialized init_account_msg into TxInitAccount args. The account is created with
+/// the user validity predicate.
+///
+/// # Arguments
+///
+/// * `init_account_msg` - Borsh serialized init_account_msg.
+/// * `tx_msg` - Borsh serialized tx_msg.
+///
+/// # Errors
+///
+/// Returns JsError if the tx_msg can't be deserialized, Rust structs can't be created, the
+/// keys are empty or repeated, or the threshold is not between 1 and the number of keys.
+pub fn init_account_tx_args(
+    init_account_msg: &[u8],
+    tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<args::TxInitAccount, JsError> {
+    let init_account_msg = decode_tagged_msg::<InitAccountMsg>(init_account_msg)?;
+
+    let InitAccountMsg {
+        public_keys,
+        threshold,
+    } = init_account_msg;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
+
+    let public_keys = account_public_keys("init account", &public_keys, prefixes)?;
+    if public_keys.is_empty() {
+        return Err(JsError::new(
+            "Init account requires at least one public key",
+        ));
+    }
+    let threshold = match threshold {
+        Some(threshold) => threshold,
+        None => all_keys_threshold("Init account", public_keys.len())?,
     };
+    check_threshold("Init account", threshold, public_keys.len())?;
 
-    Ok(args)
+    Ok(sdk_args::init_account(tx, public_keys, threshold))
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct UpdateAccountMsg {
+    address: String,
+    /// New public keys of the account, the current keys are kept if empty
+    public_keys: Vec<String>,
+    /// New number of signatures required to act for the account, unchanged if left out.
+    /// Required with new public keys.
+    threshold: Option<u8>,
+}
+
+impl UpdateAccountMsg {
+    pub fn new(
+        address: String,
+        public_keys: Vec<String>,
+        threshold: Option<u8>,
+    ) -> UpdateAccountMsg {
+        UpdateAccountMsg {
+            address,
+            public_keys,
+            threshold,
+        }
+    }
+}
+
+/// Maps serialized update_account_msg into TxUpdateAccount args. The validity predicate of
+/// the account is kept.
+///
+/// # Arguments
+///
+/// * `update_account_msg` - Borsh serialized update_account_msg.
+/// * `tx_msg` - Borsh serialized tx_msg.
+///
+/// # Errors
+///
+/// Returns JsError if the tx_msg can't be deserialized, Rust structs can't be created, the
+/// address isn't an established account, nothing is updated, the keys are repeated, or new
+/// keys come without a threshold between 1 and their number.
+pub fn update_account_tx_args(
+    update_account_msg: &[u8],
+    tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<args::TxUpdateAccount, JsError> {
+    let update_account_msg = decode_tagged_msg::<UpdateAccountMsg>(update_account_msg)?;
+
+    let UpdateAccountMsg {
+        address,
+        public_keys,
+        threshold,
+    } = update_account_msg;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
+
+    let addr =
+        Address::from_str(&prefixes.native(&address)).arg("update account", "address", &address)?;
+    // Implicit accounts are controlled by the key they're derived from
+    if !matches!(addr, Address::Established(_)) {
+        return Err(JsError::new(&format!(
+            "Update account requires an established account, got {}",
+            excerpt(&address)
+        )));
+    }
+    let public_keys = account_public_keys("update account", &public_keys, prefixes)?;
+    match threshold {
+        None if public_keys.is_empty() => {
+            return Err(JsError::new(
+                "Update account requires new public keys or a new threshold",
+            ))
+        }
+        // The current threshold may exceed the number of new keys, locking the account
+        None => {
+            return Err(JsError::new(
+                "Update account requires a threshold when changing the public keys",
+            ))
+        }
+        // Without new keys, the threshold is bounded by the current keys when building, see
+        // check_current_threshold
+        Some(0) if public_keys.is_empty() => {
+            return Err(JsError::new("Update account threshold must be at least 1"))
+        }
+        Some(_) if public_keys.is_empty() => (),
+        Some(threshold) => check_threshold("Update account", threshold, public_keys.len())?,
+    }
+
+    Ok(sdk_args::update_account(tx, addr, public_keys, threshold))
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Default)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct ValidatorMetadataMsg {
+    email: Option<String>,
+    description: Option<String>,
+    website: Option<String>,
+    discord_handle: Option<String>,
+    avatar: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct ValidatorMaintenanceMsg {
+    validator: String,
+    /// Decimal fraction, e.g. "0.05"
+    commission_rate: Option<String>,
+    /// Fields left out are unchanged, empty strings remove them
+    metadata: Option<ValidatorMetadataMsg>,
+    consensus_key: Option<String>,
+}
+
+impl ValidatorMaintenanceMsg {
+    pub fn new(
+        validator: String,
+        commission_rate: Option<String>,
+        metadata: Option<ValidatorMetadataMsg>,
+        consensus_key: Option<String>,
+    ) -> ValidatorMaintenanceMsg {
+        ValidatorMaintenanceMsg {
+            validator,
+            commission_rate,
+            metadata,
+            consensus_key,
+        }
+    }
+}
+
+/// Args of the inner txs of a validator maintenance batch, None for changes left out
+pub struct ValidatorMaintenanceArgs {
+    pub commission: Option<args::CommissionRateChange>,
+    pub metadata: Option<args::MetaDataChange>,
+    pub consensus_key: Option<args::ConsensusKeyChange>,
+}
+
+/// Maps serialized validator_maintenance_msg into the args of its inner txs.
+///
+/// # Arguments
+///
+/// * `validator_maintenance_msg` - Borsh serialized validator_maintenance_msg.
+/// * `tx_msg` - Borsh serialized tx_msg.
+///
+/// # Errors
+///
+/// Returns JsError if the tx_msg can't be deserialized, Rust structs can't be created or
+/// the msg changes nothing.
+pub fn validator_maintenance_tx_args(
+    validator_maintenance_msg: &[u8],
+    tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<ValidatorMaintenanceArgs, JsError> {
+    let validator_maintenance_msg =
+        decode_tagged_msg::<ValidatorMaintenanceMsg>(validator_maintenance_msg)?;
+
+    let ValidatorMaintenanceMsg {
+        validator,
+        commission_rate,
+        metadata,
+        consensus_key,
+    } = validator_maintenance_msg;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
+
+    let validator = Address::from_str(&prefixes.native(&validator)).arg(
+        "validator maintenance",
+        "validator",
+        &validator,
+    )?;
+
+    let commission = commission_rate
+        .map(|rate| {
+            let rate =
+                Dec::from_str(&rate).arg("validator maintenance", "commission_rate", &rate)?;
+            Ok::<_, JsError>(sdk_args::commission_rate_change(
+                tx.clone(),
+                validator.clone(),
+                rate,
+            ))
+        })
+        .transpose()?;
+
+    let metadata = metadata.map(|metadata| {
+        let ValidatorMetadataMsg {
+            email,
+            description,
+            website,
+            discord_handle,
+            avatar,
+            name,
+        } = metadata;
+        // The commission rate is changed by its own tx, see commission above
+        sdk_args::metadata_change(
+            tx.clone(),
+            validator.clone(),
+            sdk_args::ValidatorMetadata {
+                email,
+                description,
+                website,
+                discord_handle,
+                avatar,
+                name,
+            },
+        )
+    });
+
+    let consensus_key = consensus_key
+        .map(|key| {
+            let key = PublicKey::from_str(&prefixes.native(&key)).arg(
+                "validator maintenance",
+                "consensus_key",
+                &key,
+            )?;
+            Ok::<_, JsError>(sdk_args::consensus_key_change(
+                tx.clone(),
+                validator.clone(),
+                key,
+            ))
+        })
+        .transpose()?;
+
+    if commission.is_none() && metadata.is_none() && consensus_key.is_none() {
+        return Err(JsError::new(
+            "Validator maintenance requires at least one change",
+        ));
+    }
+
+    Ok(ValidatorMaintenanceArgs {
+        commission,
+        metadata,
+        consensus_key,
+    })
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct TransferDataMsg {
     owner: String,
@@ -400,7 +829,7 @@ impl TransferDataMsg {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct TransferMsg {
     sources: Vec<TransferDataMsg>,
@@ -422,7 +851,7 @@ impl TransferMsg {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct TransparentTransferDataMsg {
     source: String,
@@ -431,12 +860,34 @@ pub struct TransparentTransferDataMsg {
     amount: String,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+impl TransparentTransferDataMsg {
+    pub fn new(
+        source: String,
+        target: String,
+        token: String,
+        amount: String,
+    ) -> TransparentTransferDataMsg {
+        TransparentTransferDataMsg {
+            source,
+            target,
+            token,
+            amount,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct TransparentTransferMsg {
     data: Vec<TransparentTransferDataMsg>,
 }
 
+impl TransparentTransferMsg {
+    pub fn new(data: Vec<TransparentTransferDataMsg>) -> TransparentTransferMsg {
+        TransparentTransferMsg { data }
+    }
+}
+
 /// Maps serialized tx_msg into TransferTx args.
 ///
 /// # Arguments
@@ -451,56 +902,176 @@ pub struct TransparentTransferMsg {
 pub fn transparent_transfer_tx_args(
     transfer_msg: &[u8],
     tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
 ) -> Result<args::TxTransparentTransfer, JsError> {
-    let transfer_msg = TransparentTransferMsg::try_from_slice(transfer_msg)?;
+    let transfer_msg = decode_tagged_msg::<TransparentTransferMsg>(transfer_msg)?;
     let TransparentTransferMsg { data } = transfer_msg;
 
     let mut transfer_data: Vec<args::TxTransparentTransferData> = vec![];
 
     for transfer in data {
-        let source = Address::from_str(&transfer.source)?;
-        let target = Address::from_str(&transfer.target)?;
-        let token = Address::from_str(&transfer.token)?;
-        let denom_amount =
-            DenominatedAmount::from_str(&transfer.amount).expect("Amount to be valid.");
+        let source = Address::from_str(&prefixes.native(&transfer.source)).arg(
+            "transparent transfer",
+            "source",
+            &transfer.source,
+        )?;
+        let target = Address::from_str(&prefixes.native(&transfer.target)).arg(
+            "transparent transfer",
+            "target",
+            &transfer.target,
+        )?;
+        let token = Address::from_str(&prefixes.native(&transfer.token)).arg(
+            "transparent transfer",
+            "token",
+            &transfer.token,
+        )?;
+        let denom_amount = DenominatedAmount::from_str(&transfer.amount).arg(
+            "transparent transfer",
+            "amount",
+            &transfer.amount,
+        )?;
         let amount = InputAmount::Unvalidated(denom_amount);
 
-        transfer_data.push(args::TxTransparentTransferData {
-            source,
-            target,
-            token,
-            amount,
-        });
+        transfer_data.push(sdk_args::transparent_transfer_data(
+            source, target, token, amount,
+        ));
     }
 
-    let tx = tx_msg_into_args(tx_msg)?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
 
-    let args = args::TxTransparentTransfer {
-        tx,
-        data: transfer_data,
-        tx_code_path: PathBuf::from("tx_transfer.wasm"),
-    };
+    Ok(sdk_args::transparent_transfer(tx, transfer_data))
+}
 
-    Ok(args)
+/// Creates TransferTx args of a transfer template instance.
+/// Memo of the template is used unless tx_msg has its own memo.
+///
+/// # Arguments
+///
+/// * `source` - Source address
+/// * `target` - Target address
+/// * `token` - Token address
+/// * `amount` - Resolved amount
+/// * `memo` - Memo of the template
+/// * `tx_msg` - Borsh serialized tx_msg.
+///
+/// # Errors
+///
+/// Returns JsError if the tx_msg can't be deserialized or
+/// Rust structs can't be created.
+pub fn template_transfer_tx_args(
+    source: &str,
+    target: &str,
+    token: &str,
+    amount: DenominatedAmount,
+    memo: Option<String>,
+    tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<args::TxTransparentTransfer, JsError> {
+    let source =
+        Address::from_str(&prefixes.native(source)).arg("template transfer", "source", source)?;
+    let target =
+        Address::from_str(&prefixes.native(target)).arg("template transfer", "target", target)?;
+    let token =
+        Address::from_str(&prefixes.native(token)).arg("template transfer", "token", token)?;
+
+    let mut tx = tx_msg_into_args(tx_msg, prefixes)?;
+    if tx.memo.is_none() {
+        tx.memo = memo.map(|v| v.as_bytes().to_vec());
+    }
+
+    let data = sdk_args::transparent_transfer_data(
+        source,
+        target,
+        token,
+        InputAmount::Unvalidated(amount),
+    );
+
+    Ok(sdk_args::transparent_transfer(tx, vec![data]))
+}
+
+/// Creates TransferTx args sweeping the balances of a transparent address. The data is
+/// left empty, to be filled in once the balances are known.
+///
+/// # Arguments
+///
+/// * `source` - Swept address
+/// * `target` - Target address
+/// * `tx_msg` - Borsh serialized tx_msg.
+///
+/// # Errors
+///
+/// Returns JsError if the tx_msg can't be deserialized or
+/// Rust structs can't be created.
+pub fn transparent_sweep_tx_args(
+    source: &str,
+    target: &str,
+    tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<(Address, Address, args::TxTransparentTransfer), JsError> {
+    let source =
+        Address::from_str(&prefixes.native(source)).arg("transparent sweep", "source", source)?;
+    let target =
+        Address::from_str(&prefixes.native(target)).arg("transparent sweep", "target", target)?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
+
+    Ok((source, target, sdk_args::transparent_transfer(tx, vec![])))
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct ShieldedTransferDataMsg {
     source: String,
+    // Payment address or transparent address
     target: String,
     token: String,
     amount: String,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+impl ShieldedTransferDataMsg {
+    pub fn new(
+        source: String,
+        target: String,
+        token: String,
+        amount: String,
+    ) -> ShieldedTransferDataMsg {
+        ShieldedTransferDataMsg {
+            source,
+            target,
+            token,
+            amount,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct ShieldedTransferMsg {
     data: Vec<ShieldedTransferDataMsg>,
     gas_spending_key: Option<String>,
 }
 
-/// Maps serialized tx_msg into TxShieldedTransfer args.
+impl ShieldedTransferMsg {
+    pub fn new(
+        data: Vec<ShieldedTransferDataMsg>,
+        gas_spending_key: Option<String>,
+    ) -> ShieldedTransferMsg {
+        ShieldedTransferMsg {
+            data,
+            gas_spending_key,
+        }
+    }
+}
+
+/// Shielded transfer args split by the kind of target. Transfers to payment addresses are
+/// a single shielded transfer, transfers to transparent addresses unshielding transfers
+/// grouped by source. A transfer with more than one part is built as one MASP transaction,
+/// see mixed_transfer module.
+pub struct ShieldedTransferArgs {
+    pub shielded: Option<args::TxShieldedTransfer>,
+    pub unshielding: Vec<args::TxUnshieldingTransfer>,
+}
+
+/// Maps serialized tx_msg into ShieldedTransferArgs.
 ///
 /// # Arguments
 ///
@@ -514,49 +1085,107 @@ pub struct ShieldedTransferMsg {
 pub fn shielded_transfer_tx_args(
     shielded_transfer_msg: &[u8],
     tx_msg: &[u8],
-) -> Result<args::TxShieldedTransfer, JsError> {
-    let shielded_transfer_msg = ShieldedTransferMsg::try_from_slice(shielded_transfer_msg)?;
+    prefixes: &ChainPrefixes,
+) -> Result<ShieldedTransferArgs, JsError> {
+    let shielded_transfer_msg = decode_tagged_msg::<ShieldedTransferMsg>(shielded_transfer_msg)?;
     let ShieldedTransferMsg {
         data,
         gas_spending_key,
     } = shielded_transfer_msg;
 
+    // Entries of the same source, target and token are merged, so that each target gets a
+    // single note per token
+    let mut entries: Vec<((ExtendedSpendingKey, String, Address), DenominatedAmount)> = vec![];
+    for shielded_transfer in data {
+        let source = spending_key(&prefixes.native(&shielded_transfer.source))
+            .secret_arg("shielded transfer", "source")?;
+        let token = Address::from_str(&prefixes.native(&shielded_transfer.token)).arg(
+            "shielded transfer",
+            "token",
+            &shielded_transfer.token,
+        )?;
+        let denom_amount = DenominatedAmount::from_str(&shielded_transfer.amount).arg(
+            "shielded transfer",
+            "amount",
+            &shielded_transfer.amount,
+        )?;
+
+        let key = (source, shielded_transfer.target, token);
+        match entries.iter_mut().find(|(entry, _)| *entry == key) {
+            Some((_, amount)) => {
+                *amount = amount.checked_add(denom_amount).ok_or_else(|| {
+                    JsError::new(&format!("Shielded transfer amount overflow: {}", key.2))
+                })?
+            }
+            None => entries.push((key, denom_amount)),
+        }
+    }
+
+    // Tokens may differ between entries: transfers to payment addresses are all built in
+    // a single MASP transaction, and so are unshielding transfers from the same source
     let mut shielded_transfer_data: Vec<args::TxShieldedTransferData> = vec![];
+    let mut unshielding_transfer_data: Vec<(
+        ExtendedSpendingKey,
+        Vec<args::TxUnshieldingTransferData>,
+    )> = vec![];
 
-    for shielded_transfer in data {
-        let source = ExtendedSpendingKey::from_str(&shielded_transfer.source)?;
-        let target = PaymentAddress::from_str(&shielded_transfer.target)?;
-        let token = Address::from_str(&shielded_transfer.token)?;
-        let denom_amount =
-            DenominatedAmount::from_str(&shielded_transfer.amount).expect("Amount to be valid.");
+    for ((source, target, token), denom_amount) in entries {
         let amount = InputAmount::Unvalidated(denom_amount);
 
-        shielded_transfer_data.push(args::TxShieldedTransferData {
-            source,
-            target,
-            token,
-            amount,
-        });
+        match PaymentAddress::from_str(&prefixes.native(&target)) {
+            Ok(target) => shielded_transfer_data.push(sdk_args::shielded_transfer_data(
+                source, target, token, amount,
+            )),
+            Err(_) => {
+                let target = Address::from_str(&prefixes.native(&target)).arg(
+                    "shielded transfer",
+                    "target",
+                    &target,
+                )?;
+                let data = sdk_args::unshielding_transfer_data(target, token, amount);
+                match unshielding_transfer_data
+                    .iter_mut()
+                    .find(|(xsk, _)| *xsk == source)
+                {
+                    Some((_, source_data)) => source_data.push(data),
+                    None => unshielding_transfer_data.push((source, vec![data])),
+                }
+            }
+        }
     }
 
-    let tx = tx_msg_into_args(tx_msg)?;
-    let gas_spending_key = gas_spending_key
-        .map(|v| ExtendedSpendingKey::from_str(&v))
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
+    // Fees are unshielded only once, by the first built transfer
+    let mut gas_spending_key = gas_spending_key
+        .map(|v| {
+            spending_key(&prefixes.native(&v)).secret_arg("shielded transfer", "gas_spending_key")
+        })
         .transpose()?;
 
-    let args = args::TxShieldedTransfer {
-        data: shielded_transfer_data,
-        tx,
-        tx_code_path: PathBuf::from("tx_transfer.wasm"),
-        // TODO: false for now
-        disposable_signing_key: false,
-        gas_spending_key,
+    let shielded = if shielded_transfer_data.is_empty() {
+        None
+    } else {
+        Some(sdk_args::shielded_transfer(
+            tx.clone(),
+            shielded_transfer_data,
+            gas_spending_key.take(),
+        ))
     };
 
-    Ok(args)
+    let unshielding = unshielding_transfer_data
+        .into_iter()
+        .map(|(source, data)| {
+            sdk_args::unshielding_transfer(tx.clone(), source, data, gas_spending_key.take())
+        })
+        .collect();
+
+    Ok(ShieldedTransferArgs {
+        shielded,
+        unshielding,
+    })
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct ShieldingTransferDataMsg {
     source: String,
@@ -564,7 +1193,7 @@ pub struct ShieldingTransferDataMsg {
     amount: String,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct ShieldingTransferMsg {
     target: String,
@@ -585,40 +1214,49 @@ pub struct ShieldingTransferMsg {
 pub fn shielding_transfer_tx_args(
     shielding_transfer_msg: &[u8],
     tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
 ) -> Result<args::TxShieldingTransfer, JsError> {
-    let shielding_transfer_msg = ShieldingTransferMsg::try_from_slice(shielding_transfer_msg)?;
+    let shielding_transfer_msg = decode_tagged_msg::<ShieldingTransferMsg>(shielding_transfer_msg)?;
     let ShieldingTransferMsg { target, data } = shielding_transfer_msg;
-    let target = PaymentAddress::from_str(&target)?;
+    let target = PaymentAddress::from_str(&prefixes.native(&target)).arg(
+        "shielding transfer",
+        "target",
+        &target,
+    )?;
 
     let mut shielding_transfer_data: Vec<args::TxShieldingTransferData> = vec![];
 
     for shielding_transfer in data {
-        let source = Address::from_str(&shielding_transfer.source)?;
-        let token = Address::from_str(&shielding_transfer.token)?;
-        let denom_amount =
-            DenominatedAmount::from_str(&shielding_transfer.amount).expect("Amount to be valid.");
+        let source = Address::from_str(&prefixes.native(&shielding_transfer.source)).arg(
+            "shielding transfer",
+            "source",
+            &shielding_transfer.source,
+        )?;
+        let token = Address::from_str(&prefixes.native(&shielding_transfer.token)).arg(
+            "shielding transfer",
+            "token",
+            &shielding_transfer.token,
+        )?;
+        let denom_amount = DenominatedAmount::from_str(&shielding_transfer.amount).arg(
+            "shielding transfer",
+            "amount",
+            &shielding_transfer.amount,
+        )?;
         let amount = InputAmount::Unvalidated(denom_amount);
 
-        shielding_transfer_data.push(args::TxShieldingTransferData {
-            source,
-            token,
-            amount,
-        });
+        shielding_transfer_data.push(sdk_args::shielding_transfer_data(source, token, amount));
     }
 
-    let tx = tx_msg_into_args(tx_msg)?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
 
-    let args = args::TxShieldingTransfer {
-        data: shielding_transfer_data,
-        target,
+    Ok(sdk_args::shielding_transfer(
         tx,
-        tx_code_path: PathBuf::from("tx_transfer.wasm"),
-    };
-
-    Ok(args)
+        target,
+        shielding_transfer_data,
+    ))
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct UnshieldingTransferDataMsg {
     target: String,
@@ -626,7 +1264,7 @@ pub struct UnshieldingTransferDataMsg {
     amount: String,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct UnshieldingTransferMsg {
     source: String,
@@ -648,51 +1286,217 @@ pub struct UnshieldingTransferMsg {
 pub fn unshielding_transfer_tx_args(
     unshielding_transfer_msg: &[u8],
     tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
 ) -> Result<args::TxUnshieldingTransfer, JsError> {
     let unshielding_transfer_msg =
-        UnshieldingTransferMsg::try_from_slice(unshielding_transfer_msg)?;
+        decode_tagged_msg::<UnshieldingTransferMsg>(unshielding_transfer_msg)?;
     let UnshieldingTransferMsg {
         source,
         data,
         gas_spending_key,
     } = unshielding_transfer_msg;
-    let source = ExtendedSpendingKey::from_str(&source)?;
+    let source =
+        spending_key(&prefixes.native(&source)).secret_arg("unshielding transfer", "source")?;
 
     let mut unshielding_transfer_data: Vec<args::TxUnshieldingTransferData> = vec![];
 
     for unshielding_transfer in data {
-        let target = Address::from_str(&unshielding_transfer.target)?;
-        let token = Address::from_str(&unshielding_transfer.token)?;
-        let denom_amount =
-            DenominatedAmount::from_str(&unshielding_transfer.amount).expect("Amount to be valid.");
+        let target = Address::from_str(&prefixes.native(&unshielding_transfer.target)).arg(
+            "unshielding transfer",
+            "target",
+            &unshielding_transfer.target,
+        )?;
+        let token = Address::from_str(&prefixes.native(&unshielding_transfer.token)).arg(
+            "unshielding transfer",
+            "token",
+            &unshielding_transfer.token,
+        )?;
+        let denom_amount = DenominatedAmount::from_str(&unshielding_transfer.amount).arg(
+            "unshielding transfer",
+            "amount",
+            &unshielding_transfer.amount,
+        )?;
         let amount = InputAmount::Unvalidated(denom_amount);
 
-        unshielding_transfer_data.push(args::TxUnshieldingTransferData {
-            target,
-            token,
-            amount,
-        });
+        unshielding_transfer_data.push(sdk_args::unshielding_transfer_data(target, token, amount));
     }
 
     let gas_spending_key = gas_spending_key
-        .map(|v| ExtendedSpendingKey::from_str(&v))
+        .map(|v| {
+            spending_key(&prefixes.native(&v))
+                .secret_arg("unshielding transfer", "gas_spending_key")
+        })
         .transpose()?;
-    let tx = tx_msg_into_args(tx_msg)?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
+
+    Ok(sdk_args::unshielding_transfer(
+        tx,
+        source,
+        unshielding_transfer_data,
+        gas_spending_key,
+    ))
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct MaxUnshieldingTransferMsg {
+    source: String,
+    target: String,
+    token: String,
+    gas_spending_key: Option<String>,
+}
 
-    let args = args::TxUnshieldingTransfer {
-        data: unshielding_transfer_data,
+/// Maps serialized tx_msg into TxUnshieldingTransfer args unshielding the whole balance of
+/// a token. The amount is left at zero, to be filled in once the spendable balance is known.
+///
+/// # Arguments
+///
+/// * `max_unshielding_transfer_msg` - Borsh serialized MaxUnshieldingTransferMsg.
+/// * `tx_msg` - Borsh serialized tx_msg.
+///
+/// # Errors
+///
+/// Returns JsError if the tx_msg can't be deserialized or
+/// Rust structs can't be created.
+pub fn max_unshielding_transfer_tx_args(
+    max_unshielding_transfer_msg: &[u8],
+    tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<args::TxUnshieldingTransfer, JsError> {
+    let MaxUnshieldingTransferMsg {
         source,
+        target,
+        token,
+        gas_spending_key,
+    } = decode_tagged_msg::<MaxUnshieldingTransferMsg>(max_unshielding_transfer_msg)?;
+    let source =
+        spending_key(&prefixes.native(&source)).secret_arg("max unshielding transfer", "source")?;
+    let target = Address::from_str(&prefixes.native(&target)).arg(
+        "max unshielding transfer",
+        "target",
+        &target,
+    )?;
+    let token = Address::from_str(&prefixes.native(&token)).arg(
+        "max unshielding transfer",
+        "token",
+        &token,
+    )?;
+    let gas_spending_key = gas_spending_key
+        .map(|v| {
+            spending_key(&prefixes.native(&v))
+                .secret_arg("max unshielding transfer", "gas_spending_key")
+        })
+        .transpose()?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
+
+    let data = sdk_args::unshielding_transfer_data(
+        target,
+        token,
+        InputAmount::Unvalidated(DenominatedAmount::native(Amount::zero())),
+    );
+    Ok(sdk_args::unshielding_transfer(
         tx,
+        source,
+        vec![data],
         gas_spending_key,
-        // TODO: false for now
-        disposable_signing_key: false,
-        tx_code_path: PathBuf::from("tx_transfer.wasm"),
+    ))
+}
+
+/// Transfer of any kind, dispatched by Sdk::build_transfer
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub enum TransferKindMsg {
+    Transparent(TransparentTransferMsg),
+    Shielded(ShieldedTransferMsg),
+    Shielding(ShieldingTransferMsg),
+    Unshielding(UnshieldingTransferMsg),
+}
+
+/// Source, target and wrapper args of an emergency sweep, see Sdk::build_emergency_sweep
+pub struct SweepArgs {
+    pub source: ExtendedSpendingKey,
+    /// Payment address or transparent address receiving the swept funds
+    pub target: TransferTarget,
+    pub tx: args::Tx,
+}
+
+impl SweepArgs {
+    /// Creates the transfer args of a sweep tx, with the fees unshielded from the source
+    pub fn transfer_args(&self, swept: Vec<(Address, DenominatedAmount)>) -> ShieldedTransferArgs {
+        match &self.target {
+            TransferTarget::PaymentAddress(target) => ShieldedTransferArgs {
+                shielded: Some(sdk_args::shielded_transfer(
+                    self.tx.clone(),
+                    swept
+                        .into_iter()
+                        .map(|(token, amount)| {
+                            sdk_args::shielded_transfer_data(
+                                self.source.clone(),
+                                target.clone(),
+                                token,
+                                InputAmount::Validated(amount),
+                            )
+                        })
+                        .collect(),
+                    Some(self.source.clone()),
+                )),
+                unshielding: vec![],
+            },
+            TransferTarget::Address(target) => ShieldedTransferArgs {
+                shielded: None,
+                unshielding: vec![sdk_args::unshielding_transfer(
+                    self.tx.clone(),
+                    self.source.clone(),
+                    swept
+                        .into_iter()
+                        .map(|(token, amount)| {
+                            sdk_args::unshielding_transfer_data(
+                                target.clone(),
+                                token,
+                                InputAmount::Validated(amount),
+                            )
+                        })
+                        .collect(),
+                    Some(self.source.clone()),
+                )],
+            },
+        }
+    }
+}
+
+/// Creates SweepArgs moving all funds of a spending key to a safe target
+///
+/// # Arguments
+///
+/// * `source` - Extended spending key to sweep
+/// * `target` - Payment address or transparent address
+/// * `tx_msg` - Borsh serialized tx_msg.
+///
+/// # Errors
+///
+/// Returns JsError if the tx_msg can't be deserialized or
+/// Rust structs can't be created.
+pub fn sweep_tx_args(
+    source: &str,
+    target: &str,
+    tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<SweepArgs, JsError> {
+    let source = spending_key(&prefixes.native(source)).secret_arg("emergency sweep", "source")?;
+    let target = match PaymentAddress::from_str(&prefixes.native(target)) {
+        Ok(target) => TransferTarget::PaymentAddress(target),
+        Err(_) => TransferTarget::Address(Address::from_str(&prefixes.native(target)).arg(
+            "emergency sweep",
+            "target",
+            target,
+        )?),
     };
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
 
-    Ok(args)
+    Ok(SweepArgs { source, target, tx })
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct IbcTransferMsg {
     source: String,
@@ -749,8 +1553,9 @@ impl IbcTransferMsg {
 pub fn ibc_transfer_tx_args(
     ibc_transfer_msg: &[u8],
     tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
 ) -> Result<args::TxIbcTransfer, JsError> {
-    let ibc_transfer_msg = IbcTransferMsg::try_from_slice(ibc_transfer_msg)?;
+    let ibc_transfer_msg = decode_tagged_msg::<IbcTransferMsg>(ibc_transfer_msg)?;
     let IbcTransferMsg {
         source,
         receiver,
@@ -764,47 +1569,50 @@ pub fn ibc_transfer_tx_args(
         shielding_data,
     } = ibc_transfer_msg;
 
-    let source_address = Address::from_str(&source)?;
+    let source_address =
+        Address::from_str(&prefixes.native(&source)).arg("IBC transfer", "source", &source)?;
     let source = TransferSource::Address(source_address);
-    let token = Address::from_str(&token)?;
-    let amount = Amount::from_str(&amount_in_base_denom, 0u8).expect("Amount to be valid.");
+    let token = Address::from_str(&prefixes.native(&token)).arg("IBC transfer", "token", &token)?;
+    let amount = Amount::from_str(&amount_in_base_denom, 0u8).arg(
+        "IBC transfer",
+        "amount_in_base_denom",
+        &amount_in_base_denom,
+    )?;
     // Using InputAmount::Validated because the amount is already in the base
     // denom. If Unvalidated is used, the SDK will change the denom based on the
     // token address, which complicates knowing which amount to pass to this function.
-    let amount = InputAmount::Validated(amount.into());
-    let port_id = PortId::from_str(&port_id).expect("Port id to be valid");
-    let channel_id = ChannelId::from_str(&channel_id).expect("Channel id to be valid");
     let ibc_shielding_data = match shielding_data {
-        Some(v) => Some(IbcShieldingData::try_from_slice(&v)?),
+        Some(v) => Some(validate_shielding_data(&v, &receiver, &amount)?),
         None => None,
     };
+    if let Some(memo) = &memo {
+        validate_ibc_memo(memo)?;
+    }
+    let amount = InputAmount::Validated(amount.into());
+    let port_id = PortId::from_str(&port_id).arg("IBC transfer", "port_id", &port_id)?;
+    let channel_id =
+        ChannelId::from_str(&channel_id).arg("IBC transfer", "channel_id", &channel_id)?;
 
-    let tx = tx_msg_into_args(tx_msg)?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
 
-    let args = args::TxIbcTransfer {
+    Ok(sdk_args::ibc_transfer(
         tx,
-        ibc_memo: memo,
-        ibc_shielding_data,
-        source,
-        receiver,
-        token,
-        amount,
-        port_id,
-        channel_id,
-        timeout_height,
-        timeout_sec_offset,
-        // TODO: false for now
-        disposable_signing_key: false,
-        tx_code_path: PathBuf::from("tx_ibc.wasm"),
-        refund_target: None,
-        // We do not support ibc unshielding for now
-        gas_spending_key: None,
-    };
-
-    Ok(args)
+        sdk_args::IbcTransfer {
+            source,
+            receiver,
+            token,
+            amount,
+            port_id,
+            channel_id,
+            timeout_height,
+            timeout_sec_offset,
+            memo,
+            shielding_data: ibc_shielding_data,
+        },
+    ))
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct EthBridgeTransferMsg {
     nut: bool,
@@ -820,8 +1628,10 @@ pub struct EthBridgeTransferMsg {
 pub fn eth_bridge_transfer_tx_args(
     eth_bridge_transfer_msg: &[u8],
     tx_msg: &[u8],
+    prefixes: &ChainPrefixes,
 ) -> Result<args::EthereumBridgePool, JsError> {
-    let eth_bridge_transfer_msg = EthBridgeTransferMsg::try_from_slice(eth_bridge_transfer_msg)?;
+    let eth_bridge_transfer_msg =
+        decode_tagged_msg::<EthBridgeTransferMsg>(eth_bridge_transfer_msg)?;
     let EthBridgeTransferMsg {
         nut,
         asset,
@@ -833,37 +1643,55 @@ pub fn eth_bridge_transfer_tx_args(
         fee_token,
     } = eth_bridge_transfer_msg;
 
-    let tx = tx_msg_into_args(tx_msg)?;
+    let tx = tx_msg_into_args(tx_msg, prefixes)?;
     let asset = EthAddress::from_str(&asset).map_err(|e| JsError::new(&format!("{}", e)))?;
     let recipient =
         EthAddress::from_str(&recipient).map_err(|e| JsError::new(&format!("{}", e)))?;
-    let sender = Address::from_str(&sender)?;
-    let denom_amount = DenominatedAmount::from_str(&amount).expect("Amount to be valid.");
+    let sender = Address::from_str(&prefixes.native(&sender)).arg(
+        "Ethereum bridge transfer",
+        "sender",
+        &sender,
+    )?;
+    let denom_amount =
+        DenominatedAmount::from_str(&amount).arg("Ethereum bridge transfer", "amount", &amount)?;
     let amount = InputAmount::Unvalidated(denom_amount);
-    let denom_amount = DenominatedAmount::from_str(&fee_amount).expect("Amount to be valid.");
+    let denom_amount = DenominatedAmount::from_str(&fee_amount).arg(
+        "Ethereum bridge transfer",
+        "fee_amount",
+        &fee_amount,
+    )?;
     let fee_amount = InputAmount::Unvalidated(denom_amount);
-    let fee_payer = fee_payer.map(|v| Address::from_str(&v)).transpose()?;
-    let fee_token = Address::from_str(&fee_token)?;
-    let code_path = PathBuf::from("tx_bridge_pool.wasm");
+    let fee_payer = fee_payer
+        .map(|v| {
+            Address::from_str(&prefixes.native(&v)).arg("Ethereum bridge transfer", "fee_payer", &v)
+        })
+        .transpose()?;
+    let fee_token = Address::from_str(&prefixes.native(&fee_token)).arg(
+        "Ethereum bridge transfer",
+        "fee_token",
+        &fee_token,
+    )?;
 
-    let args = args::EthereumBridgePool {
-        nut,
+    Ok(sdk_args::eth_bridge_pool(
         tx,
-        asset,
-        recipient,
-        sender,
-        amount,
-        fee_amount,
-        fee_payer,
-        fee_token,
-        code_path,
-    };
-
-    Ok(args)
+        sdk_args::EthBridgeTransfer {
+            nut,
+            asset,
+            recipient,
+            sender,
+            amount,
+            fee_amount,
+            fee_payer,
+            fee_token,
+        },
+    ))
 }
 
-pub fn tx_args_from_slice(tx_msg_bytes: &[u8]) -> Result<args::Tx, JsError> {
-    let args = tx_msg_into_args(tx_msg_bytes)?;
+pub fn tx_args_from_slice(
+    tx_msg_bytes: &[u8],
+    prefixes: &ChainPrefixes,
+) -> Result<args::Tx, JsError> {
+    let args = tx_msg_into_args(tx_msg_bytes, prefixes)?;
 
     Ok(args)
 }
@@ -878,8 +1706,8 @@ pub fn tx_args_from_slice(tx_msg_bytes: &[u8]) -> Result<args::Tx, JsError> {
 /// # Errors
 ///
 /// Returns JsError if token address is invalid.
-fn tx_msg_into_args(tx_msg: &[u8]) -> Result<args::Tx, JsError> {
-    let tx_msg = WrapperTxMsg::try_from_slice(tx_msg)?;
+fn tx_msg_into_args(tx_msg: &[u8], prefixes: &ChainPrefixes) -> Result<args::Tx, JsError> {
+    let tx_msg = WrapperTxMsg::decode(tx_msg)?;
     let WrapperTxMsg {
         token,
         fee_amount,
@@ -888,61 +1716,679 @@ fn tx_msg_into_args(tx_msg: &[u8]) -> Result<args::Tx, JsError> {
         public_key,
         memo,
         force,
+        expiration,
+        fee_payer,
+        signers,
+        memo_recipient,
     } = tx_msg;
 
-    let token = Address::from_str(&token)?;
+    let token = Address::from_str(&prefixes.native(&token)).arg("wrapper tx", "token", &token)?;
+    let gas_limit = GasLimit::from_str(&gas_limit).arg("wrapper tx", "gas_limit", &gas_limit)?;
 
-    let fee_amount = DenominatedAmount::from_str(&fee_amount)
-        .expect(format!("Fee amount has to be valid. Received {}", fee_amount).as_str());
+    let fee_amount =
+        DenominatedAmount::from_str(&fee_amount).arg("wrapper tx", "fee_amount", &fee_amount)?;
     let fee_input_amount = InputAmount::Unvalidated(fee_amount);
 
     let public_key = match public_key {
         Some(v) => {
-            let pk = PublicKey::from_str(&v)?;
+            let pk = PublicKey::from_str(&prefixes.native(&v)).arg("wrapper tx", "v", &v)?;
             Some(pk)
         }
         _ => None,
     };
 
-    let signing_keys: Vec<PublicKey> = match public_key {
-        Some(v) => vec![v.clone()],
+    let signing_keys: Vec<PublicKey> = match (signers, public_key) {
+        (Some(signers), _) if !signers.is_empty() => signers
+            .iter()
+            .map(|signer| {
+                PublicKey::from_str(&prefixes.native(signer)).arg("wrapper tx", "signers", signer)
+            })
+            .collect::<Result<_, _>>()?,
+        (_, Some(v)) => vec![v.clone()],
         _ => vec![],
     };
 
-    // Ledger address is not used in the SDK.
-    // We can leave it as whatever as long as it's valid url.
-    let ledger_address = tendermint_rpc::Url::from_str("http://notinuse:13337").unwrap();
+    let wrapper_fee_payer = match fee_payer {
+        Some(fee_payer) => Some(PublicKey::from_str(&prefixes.native(&fee_payer)).arg(
+            "wrapper tx",
+            "fee_payer",
+            &fee_payer,
+        )?),
+        None => None,
+    };
 
+    let memo = match (memo, memo_recipient) {
+        (Some(memo), Some(recipient)) => Some(encrypted_memo::encrypt(&memo, &recipient)?),
+        (memo, _) => memo,
+    };
     let memo = memo.map(|v| v.as_bytes().to_vec());
 
     let force = force.unwrap_or(false);
 
-    let args = args::Tx {
-        dry_run: false,
-        dry_run_wrapper: false,
-        dump_tx: false,
-        dump_wrapper_tx: false,
+    let expiration = match expiration {
+        Some(expiration) => TxExpiration::Custom(DateTimeUtc::from_str(&expiration).arg(
+            "wrapper tx",
+            "expiration",
+            &expiration,
+        )?),
+        None => TxExpiration::Default,
+    };
+
+    Ok(sdk_args::tx(sdk_args::WrapperArgs {
         force,
-        broadcast_only: false,
-        ledger_address,
-        wallet_alias_force: false,
-        initialized_account_alias: None,
-        fee_amount: Some(fee_input_amount),
-        fee_token: token.clone(),
-        gas_limit: GasLimit::from_str(&gas_limit).expect("Gas limit to be valid"),
-        wrapper_fee_payer: None,
-        output_folder: None,
-        expiration: TxExpiration::Default,
-        chain_id: Some(ChainId(String::from(chain_id))),
-        signatures: vec![],
-        wrapper_signature: None,
+        fee_amount: fee_input_amount,
+        fee_token: token,
+        gas_limit,
+        fee_payer: wrapper_fee_payer,
+        expiration,
+        chain_id: ChainId(chain_id),
         signing_keys,
-        tx_reveal_code_path: PathBuf::from("tx_reveal_pk.wasm"),
-        use_device: false,
-        password: None,
         memo,
-        device_transport: Default::default(),
-    };
+    }))
+}
 
-    Ok(args)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::decode::{encode_tagged_msg, MsgTag, TaggedMsg};
+    use namada_sdk::tx::VP_USER_WASM;
+    use std::path::PathBuf;
+    use wasm_bindgen_test::*;
+
+    const NATIVE_TOKEN: &str = "tnam1qxvg64psvhwumv3mwrrjfcz0h3t3274hwggyzcee";
+    const ADDRESS_1: &str = "tnam1qz4sdx5jlh909j44uz46pf29ty0ztftfzc98s8dx";
+    const ADDRESS_2: &str = "tnam1qry3lnk03j965y92np6e25jvadk3kw9u7cvwjclp";
+    const PUBLIC_KEY: &str = "tpknam1qptrn64myunqr4847yq4cn0uwek5ecwc7eeexjfc5npmd5kmg6ex563n5as";
+    const PAYMENT_ADDRESS: &str =
+        "znam1wzrkk8tcz8zxxv0j3ssckjnantguzj2gnzypvvmg0xwy6k45w4glvv2s9yhl8magw8dx7kz0u4h";
+    const SPENDING_KEY: &str = concat!(
+        "zsknam1qwhq8dchqqqqpq9kgajamqucsjt0gy8h9hdel8ga3ugynqs8lewj4ayqdnlk2reuf50h324g5rky8kq",
+        "my9u8v3306q03fa2ad32hdq8uacwrtzmn67gsz22vfw06r86vxyr9kk8ajr2n3ds5hsx0ks8fuuespr9y2v4ah9",
+        "grz7snc33jhqgscvn7vwzpxplq5jwsmw5v6sew55er3y0s2mjw7q388w9xg8hdjfkd0n0hqdm32dl9mjvxkes5",
+        "djmvq60u3dgka4jsyfgpxafgg"
+    );
+    const INVALID_ADDRESS: &str = "tnam1invalid";
+
+    fn tx_msg() -> Vec<u8> {
+        msg(WrapperTxMsg::new(
+            NATIVE_TOKEN.to_string(),
+            "0.000001".to_string(),
+            "50000".to_string(),
+            "namada-test.000000000000000000000".to_string(),
+            Some(PUBLIC_KEY.to_string()),
+            Some("memo".to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+        ))
+    }
+
+    fn msg<T: TaggedMsg>(msg: T) -> Vec<u8> {
+        encode_tagged_msg(&msg)
+    }
+
+    #[wasm_bindgen_test]
+    fn tx_args_from_wrapper_msg() {
+        let namada = ChainPrefixes::default();
+        let args = tx_args_from_slice(&tx_msg(), &namada).unwrap();
+        assert_eq!(args.fee_token, Address::from_str(NATIVE_TOKEN).unwrap());
+        assert_eq!(
+            args.signing_keys,
+            vec![PublicKey::from_str(PUBLIC_KEY).unwrap()]
+        );
+        assert_eq!(args.memo, Some(b"memo".to_vec()));
+        assert!(!args.force);
+
+        assert!(tx_args_from_slice(&[], &namada).is_err());
+        let invalid_token = WrapperTxMsg {
+            token: INVALID_ADDRESS.to_string(),
+            ..WrapperTxMsg::decode(&tx_msg()).unwrap()
+        };
+        assert!(tx_args_from_slice(&msg(invalid_token), &namada).is_err());
+        let invalid_expiration = WrapperTxMsg {
+            expiration: Some("tomorrow".to_string()),
+            ..WrapperTxMsg::decode(&tx_msg()).unwrap()
+        };
+        assert!(tx_args_from_slice(&msg(invalid_expiration), &namada).is_err());
+
+        let fee_payer_key = "tpknam1qzm68sfdcryvwj9tqaf9kuq3y2ugh4u0vqx8vdpdyle9uhujg3xduf408cn";
+        let separate_fee_payer = WrapperTxMsg {
+            fee_payer: Some(fee_payer_key.to_string()),
+            signers: Some(vec![PUBLIC_KEY.to_string()]),
+            ..WrapperTxMsg::decode(&tx_msg()).unwrap()
+        };
+        let args = tx_args_from_slice(&msg(separate_fee_payer), &namada).unwrap();
+        assert_eq!(
+            args.wrapper_fee_payer,
+            Some(PublicKey::from_str(fee_payer_key).unwrap())
+        );
+        assert_eq!(
+            args.signing_keys,
+            vec![PublicKey::from_str(PUBLIC_KEY).unwrap()]
+        );
+        let invalid_signer = WrapperTxMsg {
+            signers: Some(vec!["tpknam1invalid".to_string()]),
+            ..WrapperTxMsg::decode(&tx_msg()).unwrap()
+        };
+        assert!(tx_args_from_slice(&msg(invalid_signer), &namada).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn bond_unbond_withdraw_tx_args() {
+        let namada = ChainPrefixes::default();
+        let bond = msg(BondMsg::new(
+            ADDRESS_1.to_string(),
+            ADDRESS_2.to_string(),
+            "1.5".to_string(),
+        ));
+        let args = bond_tx_args(&bond, &tx_msg(), &namada).unwrap();
+        assert_eq!(args.amount, Amount::from_u64(1_500_000));
+        assert_eq!(args.source, Some(Address::from_str(ADDRESS_1).unwrap()));
+        assert!(bond_tx_args(&bond, &[], &namada).is_err());
+        let invalid = msg(BondMsg::new(
+            INVALID_ADDRESS.to_string(),
+            ADDRESS_2.to_string(),
+            "1".to_string(),
+        ));
+        assert!(bond_tx_args(&invalid, &tx_msg(), &namada).is_err());
+
+        let fork = ChainPrefixes {
+            address: "tfork".to_string(),
+            ..ChainPrefixes::default()
+        };
+        let fork_bond = msg(BondMsg::new(
+            fork.configured(ADDRESS_1),
+            fork.configured(ADDRESS_2),
+            "1.5".to_string(),
+        ));
+        let args = bond_tx_args(&fork_bond, &tx_msg(), &fork).unwrap();
+        assert_eq!(args.source, Some(Address::from_str(ADDRESS_1).unwrap()));
+        assert!(bond_tx_args(&fork_bond, &tx_msg(), &namada).is_err());
+
+        let unbond = msg(UnbondMsg::new(
+            ADDRESS_1.to_string(),
+            ADDRESS_2.to_string(),
+            "2".to_string(),
+        ));
+        assert_eq!(
+            unbond_tx_args(&unbond, &tx_msg(), &namada).unwrap().amount,
+            Amount::from_u64(2_000_000)
+        );
+        let invalid = msg(UnbondMsg::new(
+            ADDRESS_1.to_string(),
+            ADDRESS_2.to_string(),
+            "not an amount".to_string(),
+        ));
+        assert!(unbond_tx_args(&invalid, &tx_msg(), &namada).is_err());
+
+        let withdraw = msg(WithdrawMsg::new(
+            ADDRESS_1.to_string(),
+            ADDRESS_2.to_string(),
+        ));
+        assert_eq!(
+            withdraw_tx_args(&withdraw, &tx_msg(), &namada)
+                .unwrap()
+                .validator,
+            Address::from_str(ADDRESS_2).unwrap()
+        );
+        let invalid = msg(WithdrawMsg::new(
+            ADDRESS_1.to_string(),
+            INVALID_ADDRESS.to_string(),
+        ));
+        assert!(withdraw_tx_args(&invalid, &tx_msg(), &namada).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn redelegate_vote_claim_tx_args() {
+        let namada = ChainPrefixes::default();
+        let redelegate = msg(RedelegateMsg::new(
+            ADDRESS_1.to_string(),
+            ADDRESS_2.to_string(),
+            NATIVE_TOKEN.to_string(),
+            "3".to_string(),
+        ));
+        let args = redelegate_tx_args(&redelegate, &tx_msg(), &namada).unwrap();
+        assert_eq!(
+            args.dest_validator,
+            Address::from_str(NATIVE_TOKEN).unwrap()
+        );
+        assert!(redelegate_tx_args(&redelegate[1..], &tx_msg(), &namada).is_err());
+
+        let vote = msg(VoteProposalMsg::new(
+            ADDRESS_1.to_string(),
+            7,
+            "yay".to_string(),
+        ));
+        let args = vote_proposal_tx_args(&vote, &tx_msg(), &namada).unwrap();
+        assert_eq!(args.proposal_id, 7);
+        let invalid = msg(VoteProposalMsg::new(
+            INVALID_ADDRESS.to_string(),
+            7,
+            "yay".to_string(),
+        ));
+        assert!(vote_proposal_tx_args(&invalid, &tx_msg(), &namada).is_err());
+        let uppercase = msg(VoteProposalMsg::new(
+            ADDRESS_1.to_string(),
+            7,
+            "Abstain".to_string(),
+        ));
+        let args = vote_proposal_tx_args(&uppercase, &tx_msg(), &namada).unwrap();
+        assert_eq!(args.vote, "abstain");
+        let invalid_vote = msg(VoteProposalMsg::new(
+            ADDRESS_1.to_string(),
+            7,
+            "yes".to_string(),
+        ));
+        assert!(vote_proposal_tx_args(&invalid_vote, &tx_msg(), &namada).is_err());
+
+        let claim = msg(ClaimRewardsMsg::new(ADDRESS_2.to_string(), None));
+        assert_eq!(
+            claim_rewards_tx_args(&claim, &tx_msg(), &namada)
+                .unwrap()
+                .source,
+            None
+        );
+        let invalid = msg(ClaimRewardsMsg::new(INVALID_ADDRESS.to_string(), None));
+        assert!(claim_rewards_tx_args(&invalid, &tx_msg(), &namada).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn validator_maintenance_tx_args_skips_unchanged() {
+        let namada = ChainPrefixes::default();
+        let maintenance = |commission_rate: Option<&str>, metadata, consensus_key: Option<&str>| {
+            msg(ValidatorMaintenanceMsg::new(
+                ADDRESS_2.to_string(),
+                commission_rate.map(String::from),
+                metadata,
+                consensus_key.map(String::from),
+            ))
+        };
+        let metadata = ValidatorMetadataMsg {
+            website: Some("https://validator.example".to_string()),
+            ..ValidatorMetadataMsg::default()
+        };
+
+        let args = validator_maintenance_tx_args(
+            &maintenance(Some("0.05"), Some(metadata), None),
+            &tx_msg(),
+            &namada,
+        )
+        .unwrap();
+        assert_eq!(
+            args.commission.map(|commission| commission.rate),
+            Some(Dec::from_str("0.05").unwrap())
+        );
+        let metadata = args.metadata.unwrap();
+        assert_eq!(
+            metadata.website,
+            Some("https://validator.example".to_string())
+        );
+        assert_eq!(metadata.email, None);
+        assert!(args.consensus_key.is_none());
+
+        let args = validator_maintenance_tx_args(
+            &maintenance(None, None, Some(PUBLIC_KEY)),
+            &tx_msg(),
+            &namada,
+        )
+        .unwrap();
+        assert!(args.commission.is_none() && args.metadata.is_none());
+        assert_eq!(
+            args.consensus_key.unwrap().consensus_key,
+            Some(PublicKey::from_str(PUBLIC_KEY).unwrap())
+        );
+
+        assert!(
+            validator_maintenance_tx_args(&maintenance(None, None, None), &tx_msg(), &namada)
+                .is_err()
+        );
+        assert!(validator_maintenance_tx_args(
+            &maintenance(Some("five"), None, None),
+            &tx_msg(),
+            &namada
+        )
+        .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn transparent_and_template_transfer_tx_args() {
+        let namada = ChainPrefixes::default();
+        let transfer = |source: &str| {
+            msg(TransparentTransferMsg {
+                data: vec![TransparentTransferDataMsg {
+                    source: source.to_string(),
+                    target: ADDRESS_2.to_string(),
+                    token: NATIVE_TOKEN.to_string(),
+                    amount: "10".to_string(),
+                }],
+            })
+        };
+        let args = transparent_transfer_tx_args(&transfer(ADDRESS_1), &tx_msg(), &namada).unwrap();
+        assert_eq!(args.data.len(), 1);
+        assert_eq!(args.data[0].target, Address::from_str(ADDRESS_2).unwrap());
+        assert!(
+            transparent_transfer_tx_args(&transfer(INVALID_ADDRESS), &tx_msg(), &namada).is_err()
+        );
+
+        let amount = DenominatedAmount::from_str("1").unwrap();
+        let args = template_transfer_tx_args(
+            ADDRESS_1,
+            ADDRESS_2,
+            NATIVE_TOKEN,
+            amount,
+            None,
+            &tx_msg(),
+            &namada,
+        )
+        .unwrap();
+        // The memo of the wrapper msg takes precedence over the template memo
+        assert_eq!(args.tx.memo, Some(b"memo".to_vec()));
+        assert!(template_transfer_tx_args(
+            ADDRESS_1,
+            INVALID_ADDRESS,
+            NATIVE_TOKEN,
+            amount,
+            None,
+            &tx_msg(),
+            &namada
+        )
+        .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn shielded_transfer_tx_args_splits_unshielding() {
+        let namada = ChainPrefixes::default();
+        let data = |target: &str| ShieldedTransferDataMsg {
+            source: SPENDING_KEY.to_string(),
+            target: target.to_string(),
+            token: NATIVE_TOKEN.to_string(),
+            amount: "1".to_string(),
+        };
+        let transfer = msg(ShieldedTransferMsg {
+            data: vec![data(PAYMENT_ADDRESS), data(ADDRESS_1), data(ADDRESS_2)],
+            gas_spending_key: Some(SPENDING_KEY.to_string()),
+        });
+        let args = shielded_transfer_tx_args(&transfer, &tx_msg(), &namada).unwrap();
+        let shielded = args.shielded.unwrap();
+        assert_eq!(shielded.data.len(), 1);
+        assert!(shielded.gas_spending_key.is_some());
+        // Unshielding from the same source is grouped in one transfer
+        assert_eq!(args.unshielding.len(), 1);
+        assert_eq!(args.unshielding[0].data.len(), 2);
+        // Fees are only paid by the first transfer
+        assert!(args.unshielding[0].gas_spending_key.is_none());
+
+        let invalid = msg(ShieldedTransferMsg {
+            data: vec![data(INVALID_ADDRESS)],
+            gas_spending_key: None,
+        });
+        assert!(shielded_transfer_tx_args(&invalid, &tx_msg(), &namada).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn init_account_tx_args_checks_threshold() {
+        let namada = ChainPrefixes::default();
+        let init_account = |public_keys: &[&str], threshold: Option<u8>| {
+            msg(InitAccountMsg::new(
+                public_keys.iter().map(|key| key.to_string()).collect(),
+                threshold,
+            ))
+        };
+
+        let args =
+            init_account_tx_args(&init_account(&[PUBLIC_KEY], None), &tx_msg(), &namada).unwrap();
+        assert_eq!(args.public_keys.len(), 1);
+        assert_eq!(args.threshold, Some(1));
+        assert_eq!(args.vp_code_path, PathBuf::from(VP_USER_WASM));
+
+        assert_eq!(all_keys_threshold("Init account", 255).unwrap(), 255);
+        assert!(all_keys_threshold("Init account", 256).is_err());
+
+        for invalid in [
+            init_account(&[], None),
+            init_account(&[PUBLIC_KEY], Some(0)),
+            init_account(&[PUBLIC_KEY], Some(2)),
+            init_account(&[PUBLIC_KEY, PUBLIC_KEY], Some(1)),
+            init_account(&[INVALID_ADDRESS], Some(1)),
+        ] {
+            assert!(init_account_tx_args(&invalid, &tx_msg(), &namada).is_err());
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn update_account_tx_args_checks_changes() {
+        let namada = ChainPrefixes::default();
+        let update_account = |address: &str, public_keys: &[&str], threshold: Option<u8>| {
+            msg(UpdateAccountMsg::new(
+                address.to_string(),
+                public_keys.iter().map(|key| key.to_string()).collect(),
+                threshold,
+            ))
+        };
+
+        let args = update_account_tx_args(
+            &update_account(NATIVE_TOKEN, &[PUBLIC_KEY], Some(1)),
+            &tx_msg(),
+            &namada,
+        )
+        .unwrap();
+        assert_eq!(args.addr, Address::from_str(NATIVE_TOKEN).unwrap());
+        assert_eq!(args.public_keys.len(), 1);
+        assert_eq!(args.threshold, Some(1));
+        assert_eq!(args.vp_code_path, None);
+
+        let args = update_account_tx_args(
+            &update_account(NATIVE_TOKEN, &[], Some(2)),
+            &tx_msg(),
+            &namada,
+        )
+        .unwrap();
+        assert!(args.public_keys.is_empty());
+        assert_eq!(args.threshold, Some(2));
+
+        for invalid in [
+            update_account(NATIVE_TOKEN, &[], None),
+            update_account(NATIVE_TOKEN, &[], Some(0)),
+            update_account(NATIVE_TOKEN, &[PUBLIC_KEY], Some(2)),
+            // New keys without a threshold
+            update_account(NATIVE_TOKEN, &[PUBLIC_KEY], None),
+            update_account(NATIVE_TOKEN, &[PUBLIC_KEY, PUBLIC_KEY], Some(1)),
+            // Implicit account
+            update_account(ADDRESS_1, &[PUBLIC_KEY], Some(1)),
+            update_account(INVALID_ADDRESS, &[PUBLIC_KEY], Some(1)),
+        ] {
+            assert!(update_account_tx_args(&invalid, &tx_msg(), &namada).is_err());
+        }
+
+        // A threshold-only update is bounded by the current keys of the account
+        assert!(check_current_threshold(2, 2).is_ok());
+        assert!(check_current_threshold(1, 2).is_ok());
+        assert!(check_current_threshold(3, 2).is_err());
+        assert!(check_current_threshold(0, 2).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn multi_asset_shielded_transfer_tx_args() {
+        let namada = ChainPrefixes::default();
+        let data = |target: &str, token: &str, amount: &str| ShieldedTransferDataMsg {
+            source: SPENDING_KEY.to_string(),
+            target: target.to_string(),
+            token: token.to_string(),
+            amount: amount.to_string(),
+        };
+        let transfer = msg(ShieldedTransferMsg {
+            data: vec![
+                data(PAYMENT_ADDRESS, NATIVE_TOKEN, "1"),
+                data(PAYMENT_ADDRESS, ADDRESS_2, "2"),
+                data(PAYMENT_ADDRESS, NATIVE_TOKEN, "0.5"),
+                data(ADDRESS_1, NATIVE_TOKEN, "1"),
+                data(ADDRESS_1, ADDRESS_2, "1"),
+            ],
+            gas_spending_key: None,
+        });
+        let args = shielded_transfer_tx_args(&transfer, &tx_msg(), &namada).unwrap();
+
+        // Both tokens go to the payment address in one transfer, the same token merged
+        let shielded = args.shielded.unwrap();
+        assert_eq!(shielded.data.len(), 2);
+        match &shielded.data[0].amount {
+            InputAmount::Unvalidated(amount) => assert_eq!(amount.to_string(), "1.5"),
+            InputAmount::Validated(_) => panic!("Amounts are validated when building"),
+        }
+        assert_eq!(args.unshielding.len(), 1);
+        assert_eq!(args.unshielding[0].data.len(), 2);
+    }
+
+    #[cfg(feature = "web")]
+    #[wasm_bindgen_test]
+    fn mixed_shielded_transfer_data() {
+        use crate::sdk::mixed_transfer::{is_mixed, transfer_data};
+
+        let namada = ChainPrefixes::default();
+        let data = |target: &str| ShieldedTransferDataMsg {
+            source: SPENDING_KEY.to_string(),
+            target: target.to_string(),
+            token: NATIVE_TOKEN.to_string(),
+            amount: "1".to_string(),
+        };
+        let transfer = |targets: &[&str]| {
+            msg(ShieldedTransferMsg {
+                data: targets.iter().map(|target| data(target)).collect(),
+                gas_spending_key: None,
+            })
+        };
+
+        let single =
+            shielded_transfer_tx_args(&transfer(&[ADDRESS_1, ADDRESS_2]), &tx_msg(), &namada)
+                .unwrap();
+        assert!(!is_mixed(&single));
+
+        // Both kinds of targets end up in the data of one MASP transaction
+        let mixed =
+            shielded_transfer_tx_args(&transfer(&[PAYMENT_ADDRESS, ADDRESS_1]), &tx_msg(), &namada)
+                .unwrap();
+        assert!(is_mixed(&mixed));
+        let data = transfer_data(&mixed);
+        assert_eq!(data.len(), 2);
+        assert!(matches!(data[0].target, TransferTarget::PaymentAddress(_)));
+        assert!(matches!(data[1].target, TransferTarget::Address(_)));
+    }
+
+    #[wasm_bindgen_test]
+    fn shielding_and_unshielding_tx_args() {
+        let namada = ChainPrefixes::default();
+        let shielding = |target: &str| {
+            msg(ShieldingTransferMsg {
+                target: target.to_string(),
+                data: vec![ShieldingTransferDataMsg {
+                    source: ADDRESS_1.to_string(),
+                    token: NATIVE_TOKEN.to_string(),
+                    amount: "1".to_string(),
+                }],
+            })
+        };
+        let args =
+            shielding_transfer_tx_args(&shielding(PAYMENT_ADDRESS), &tx_msg(), &namada).unwrap();
+        assert_eq!(args.data.len(), 1);
+        assert!(shielding_transfer_tx_args(&shielding(ADDRESS_2), &tx_msg(), &namada).is_err());
+
+        let unshielding = |source: &str| {
+            msg(UnshieldingTransferMsg {
+                source: source.to_string(),
+                data: vec![UnshieldingTransferDataMsg {
+                    target: ADDRESS_1.to_string(),
+                    token: NATIVE_TOKEN.to_string(),
+                    amount: "1".to_string(),
+                }],
+                gas_spending_key: None,
+            })
+        };
+        let args =
+            unshielding_transfer_tx_args(&unshielding(SPENDING_KEY), &tx_msg(), &namada).unwrap();
+        assert_eq!(args.data[0].target, Address::from_str(ADDRESS_1).unwrap());
+        assert!(unshielding_transfer_tx_args(&unshielding(ADDRESS_1), &tx_msg(), &namada).is_err());
+
+        let max_unshielding = |target: &str| {
+            msg(MaxUnshieldingTransferMsg {
+                source: SPENDING_KEY.to_string(),
+                target: target.to_string(),
+                token: NATIVE_TOKEN.to_string(),
+                gas_spending_key: Some(SPENDING_KEY.to_string()),
+            })
+        };
+        let args =
+            max_unshielding_transfer_tx_args(&max_unshielding(ADDRESS_1), &tx_msg(), &namada)
+                .unwrap();
+        assert!(args.gas_spending_key.is_some());
+        assert!(max_unshielding_transfer_tx_args(
+            &max_unshielding(INVALID_ADDRESS),
+            &tx_msg(),
+            &namada
+        )
+        .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn transfer_kind_msg_is_tagged() {
+        let namada = ChainPrefixes::default();
+        let transfer = TransferKindMsg::Shielding(ShieldingTransferMsg {
+            target: PAYMENT_ADDRESS.to_string(),
+            data: vec![],
+        });
+        let bytes = msg(transfer);
+        assert_eq!(bytes[..2], [MsgTag::TransferKind as u8, 2]);
+
+        let decoded = decode_tagged_msg::<TransferKindMsg>(&bytes).unwrap();
+        let TransferKindMsg::Shielding(shielding) = decoded else {
+            panic!("Expected a shielding transfer");
+        };
+        assert!(shielding_transfer_tx_args(&msg(shielding), &tx_msg(), &namada).is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn ibc_and_eth_bridge_tx_args() {
+        let namada = ChainPrefixes::default();
+        let ibc = |source: &str, shielding_data: Option<Vec<u8>>| {
+            msg(IbcTransferMsg::new(
+                source.to_string(),
+                "cosmos1receiver".to_string(),
+                NATIVE_TOKEN.to_string(),
+                "1000".to_string(),
+                "transfer".to_string(),
+                "channel-0".to_string(),
+                None,
+                Some(600),
+                None,
+                shielding_data,
+            ))
+        };
+        let args = ibc_transfer_tx_args(&ibc(ADDRESS_1, None), &tx_msg(), &namada).unwrap();
+        assert_eq!(args.channel_id, ChannelId::from_str("channel-0").unwrap());
+        assert_eq!(args.timeout_sec_offset, Some(600));
+        assert!(ibc_transfer_tx_args(&ibc(INVALID_ADDRESS, None), &tx_msg(), &namada).is_err());
+        assert!(ibc_transfer_tx_args(&ibc(ADDRESS_1, Some(vec![1])), &tx_msg(), &namada).is_err());
+
+        let bridge = |recipient: &str| {
+            msg(EthBridgeTransferMsg {
+                nut: false,
+                asset: "0x0000000000000000000000000000000000000001".to_string(),
+                recipient: recipient.to_string(),
+                sender: ADDRESS_1.to_string(),
+                amount: "1".to_string(),
+                fee_amount: "0.1".to_string(),
+                fee_payer: None,
+                fee_token: NATIVE_TOKEN.to_string(),
+            })
+        };
+        let args = eth_bridge_transfer_tx_args(
+            &bridge("0x0000000000000000000000000000000000000002"),
+            &tx_msg(),
+            &namada,
+        )
+        .unwrap();
+        assert_eq!(args.sender, Address::from_str(ADDRESS_1).unwrap());
+        assert!(eth_bridge_transfer_tx_args(&bridge("0x02"), &tx_msg(), &namada).is_err());
+    }
 }
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
+
+use super::AuditEntry;
+use crate::utils::to_bytes;
+
+const FILE_NAME: &str = "audit_log.jsonl";
+
+/// Stores entries as JSON lines in a file next to the wallet, only ever appended to
+#[derive(Debug, Clone)]
+pub struct NodeAuditLogStorage {
+    store_dir: PathBuf,
+}
+
+impl NodeAuditLogStorage {
+    pub fn new(store_dir: &str) -> Self {
+        Self {
+            store_dir: PathBuf::from(store_dir),
+        }
+    }
+
+    fn path(&self) -> JsValue {
+        JsValue::from_str(self.store_dir.join(FILE_NAME).to_str().unwrap())
+    }
+
+    fn to_js_err(e: JsValue) -> JsError {
+        JsError::new(&e.as_string().unwrap_or_else(|| format!("{:?}", e)))
+    }
+
+    pub async fn load(&self) -> Result<Vec<AuditEntry>, JsError> {
+        let exists = exists_sync(self.path())
+            .map_err(Self::to_js_err)?
+            .as_bool()
+            .unwrap_or_default();
+        if !exists {
+            return Ok(vec![]);
+        }
+
+        let bytes = to_bytes(read_file_sync(self.path()).map_err(Self::to_js_err)?);
+        String::from_utf8(bytes)?
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_str(line).map_err(JsError::from))
+            .collect()
+    }
+
+    pub async fn append(&self, entries: &[AuditEntry]) -> Result<(), JsError> {
+        let mut lines = String::new();
+        for entry in entries {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        let uint8_array = js_sys::Uint8Array::from(lines.as_bytes());
+
+        append_file_sync(self.path(), uint8_array.into()).map_err(Self::to_js_err)?;
+
+        Ok(())
+    }
+}
+
+#[wasm_bindgen(module = "/src/sdk/masp/masp.node.js")]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = "appendFileSync")]
+    fn append_file_sync(path: JsValue, content: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = "readFileSync")]
+    fn read_file_sync(path: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = "existsSync")]
+    fn exists_sync(path: JsValue) -> Result<JsValue, JsValue>;
+}
@@ -0,0 +1,72 @@
+use gloo_utils::format::JsValueSerdeExt;
+use rexie::{Error, ObjectStore, Rexie, TransactionMode};
+use wasm_bindgen::{JsError, JsValue};
+
+use super::AuditEntry;
+
+const DB_PREFIX: &str = "namada_sdk::audit_log";
+const ENTRIES_TABLE: &str = "Entries";
+
+/// Stores entries in IndexedDB under auto incremented keys, in a database per wallet db name
+#[derive(Debug, Clone)]
+pub struct WebAuditLogStorage {
+    db_name: String,
+}
+
+impl WebAuditLogStorage {
+    pub fn new(db_name: &str) -> Self {
+        Self {
+            db_name: format!("{}::{}", DB_PREFIX, db_name),
+        }
+    }
+
+    async fn build_database(&self) -> Result<Rexie, Error> {
+        let rexie = Rexie::builder(&self.db_name)
+            .version(1)
+            .add_object_store(ObjectStore::new(ENTRIES_TABLE).auto_increment(true))
+            .build()
+            .await?;
+
+        Ok(rexie)
+    }
+
+    fn to_js_err(e: Error) -> JsError {
+        JsError::new(&e.to_string())
+    }
+
+    pub async fn load(&self) -> Result<Vec<AuditEntry>, JsError> {
+        let db = self.build_database().await.map_err(Self::to_js_err)?;
+        let transaction = db
+            .transaction(&[ENTRIES_TABLE], TransactionMode::ReadOnly)
+            .map_err(Self::to_js_err)?;
+        let store = transaction.store(ENTRIES_TABLE).map_err(Self::to_js_err)?;
+
+        let stored = store
+            .get_all(None, None, None, None)
+            .await
+            .map_err(Self::to_js_err)?;
+
+        stored
+            .into_iter()
+            .map(|(_, value)| value.into_serde().map_err(JsError::from))
+            .collect()
+    }
+
+    pub async fn append(&self, entries: &[AuditEntry]) -> Result<(), JsError> {
+        let db = self.build_database().await.map_err(Self::to_js_err)?;
+        let transaction = db
+            .transaction(&[ENTRIES_TABLE], TransactionMode::ReadWrite)
+            .map_err(Self::to_js_err)?;
+        let store = transaction.store(ENTRIES_TABLE).map_err(Self::to_js_err)?;
+
+        for entry in entries {
+            store
+                .add(&JsValue::from_serde(entry)?, None)
+                .await
+                .map_err(Self::to_js_err)?;
+        }
+        transaction.done().await.map_err(Self::to_js_err)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+//! Key usage audit log
+//!
+//! Every signature produced by the Sdk is recorded with the signed hash, the kind of tx,
+//! the signers and the time, giving users an audit trail of what their keys signed. The log
+//! can only be appended to and exported. Entries are written in the background as soon as
+//! they are recorded, so that synchronous functions log their signatures as well. A failed
+//! write doesn't fail the signature, the entries are kept and written with the next one.
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use namada_sdk::hash::Hash;
+use namada_sdk::key::{common, RefTo};
+use namada_sdk::masp_primitives::zip32::{self, ExtendedFullViewingKey};
+use namada_sdk::tx::Tx;
+use namada_sdk::{ExtendedSpendingKey, PaymentAddress};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsError;
+
+#[cfg(not(feature = "nodejs"))]
+mod audit_log_web;
+
+#[cfg(not(feature = "nodejs"))]
+pub use audit_log_web::WebAuditLogStorage as JSAuditLogStorage;
+
+#[cfg(feature = "nodejs")]
+mod audit_log_node;
+
+#[cfg(feature = "nodejs")]
+pub use audit_log_node::NodeAuditLogStorage as JSAuditLogStorage;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureKind {
+    /// Signature of a tx, both the raw header and the wrapper
+    Tx,
+    /// Re-signed wrapper of a tx with a replaced fee
+    FeeReplacement,
+    /// Signature of arbitrary data
+    Arbitrary,
+    /// Spend authorization signatures of the MASP transactions of a tx, made with spending
+    /// keys while building it
+    MaspSpend,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub kind: SignatureKind,
+    /// Wrapper hash of signed txs, or the hash of signed arbitrary data
+    pub hash: String,
+    /// Code tags of the inner txs, e.g. tx_transfer.wasm
+    pub tx_types: Vec<String>,
+    /// Public keys of the signing keys
+    pub signers: Vec<String>,
+    /// Time of the signature, in RFC 3339
+    pub timestamp: String,
+    /// Hash of the origin of the signing request, see hash_origin
+    pub origin: Option<String>,
+}
+
+/// Hashes the origin of a dApp requesting a signature, e.g. https://app.example.com. Only the
+/// hash is stored, so the log doesn't reveal browsing history, while a signature can still be
+/// attributed to a known phishing origin by hashing it.
+pub fn hash_origin(origin: &str) -> String {
+    let origin = origin.trim().trim_end_matches('/').to_lowercase();
+    Hash::sha256(origin).to_string().to_lowercase()
+}
+
+impl AuditEntry {
+    pub fn new(
+        kind: SignatureKind,
+        hash: String,
+        tx_types: Vec<String>,
+        keys: &[common::SecretKey],
+    ) -> Self {
+        let mut signers: Vec<String> = keys.iter().map(|k| k.ref_to().to_string()).collect();
+        signers.dedup();
+
+        AuditEntry {
+            kind,
+            hash,
+            tx_types,
+            signers,
+            timestamp: String::from(js_sys::Date::new_0().to_iso_string()),
+            origin: None,
+        }
+    }
+
+    /// Binds the entry to the origin of the signing request
+    pub fn with_origin(mut self, origin: Option<&str>) -> Self {
+        self.origin = origin.map(hash_origin);
+        self
+    }
+
+    /// Entry of a signed tx, keyed by its wrapper hash
+    pub fn for_tx(kind: SignatureKind, tx: &Tx, keys: &[common::SecretKey]) -> Self {
+        let hash = tx
+            .wrapper_hash()
+            .unwrap_or_else(|| tx.header_hash())
+            .to_string();
+
+        Self::new(kind, hash, tx_types(tx), keys)
+    }
+
+    /// Entry of the MASP spends of a built tx. Spending keys have no public key, so the
+    /// signers are the default payment addresses of the spending keys.
+    pub fn for_masp(tx: &Tx, sources: &[ExtendedSpendingKey]) -> Self {
+        let mut entry = Self::for_tx(SignatureKind::MaspSpend, tx, &[]);
+        for source in sources {
+            let xfvk =
+                ExtendedFullViewingKey::from(&zip32::ExtendedSpendingKey::from(source.clone()));
+            let address = PaymentAddress::from(xfvk.default_address().1).to_string();
+            if !entry.signers.contains(&address) {
+                entry.signers.push(address);
+            }
+        }
+        entry
+    }
+}
+
+/// Code tags of the inner txs
+pub fn tx_types(tx: &Tx) -> Vec<String> {
+    tx.commitments()
+        .iter()
+        .filter_map(|cmt| {
+            tx.get_section(cmt.code_sechash())
+                .and_then(|section| section.code_sec())
+                .and_then(|code| code.tag)
+        })
+        .collect()
+}
+
+/// Append-only log of signatures, backed by JSAuditLogStorage
+#[derive(Clone)]
+pub struct AuditLog {
+    storage: JSAuditLogStorage,
+    /// Entries not yet written to the storage
+    pending: Rc<RefCell<Vec<AuditEntry>>>,
+}
+
+impl AuditLog {
+    pub fn new(path_or_db_name: &str) -> Self {
+        Self {
+            storage: JSAuditLogStorage::new(path_or_db_name),
+            pending: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
+    /// Buffers an entry and writes it in the background. Write errors are only reported to
+    /// the console, the entry stays buffered until the next flush.
+    pub fn record(&self, entry: AuditEntry) {
+        self.pending.borrow_mut().push(entry);
+
+        let log = self.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = log.flush().await {
+                web_sys::console::error_2(&"Failed to write key audit log:".into(), &e.into());
+            }
+        });
+    }
+
+    /// Writes all buffered entries to the storage. Entries are put back if the write fails.
+    pub async fn flush(&self) -> Result<(), JsError> {
+        let entries = self.pending.take();
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        if let Err(e) = self.storage.append(&entries).await {
+            let mut pending = self.pending.borrow_mut();
+            let newer = std::mem::replace(&mut *pending, entries);
+            pending.extend(newer);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Returns all entries, oldest first
+    pub async fn export(&self) -> Result<Vec<AuditEntry>, JsError> {
+        self.flush().await?;
+        self.storage.load().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn normalizes_origins_before_hashing() {
+        let hash = hash_origin("https://app.example.com");
+        assert_eq!(hash, hash_origin(" HTTPS://App.Example.com/ "));
+        assert_ne!(hash, hash_origin("https://app.example.co"));
+        assert_eq!(hash.len(), 64);
+    }
+}
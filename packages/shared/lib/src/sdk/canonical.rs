@@ -0,0 +1,94 @@
+//! Audit of deterministic serialization
+//!
+//! Tx bytes are hashed and signed, so serializing the same value has to produce the same bytes
+//! on every build and run. Borsh sorts the entries of HashMaps, and JSON results are
+//! canonicalized by to_js_result. A value is canonical if decoding and encoding it again
+//! reproduces its bytes, which fails with high probability if the order of a HashMap leaks,
+//! as the decoded HashMap has a different random state.
+use namada_sdk::account::AccountPublicKeysMap;
+use namada_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use wasm_bindgen::prelude::*;
+
+use super::tx;
+use crate::utils::to_js_result;
+
+/// Returns true if bytes decode as T and encoding it again gives the same bytes
+pub fn is_canonical<T: BorshSerialize + BorshDeserialize>(bytes: &[u8]) -> bool {
+    borsh::from_slice::<T>(bytes)
+        .and_then(|value| borsh::to_vec(&value))
+        .is_ok_and(|encoded| encoded == bytes)
+}
+
+/// Returns the parts of a built tx which are not canonically serialized
+pub fn audit_tx(bytes: &[u8]) -> Vec<&'static str> {
+    let mut issues = vec![];
+    if !is_canonical::<tx::Tx>(bytes) {
+        issues.push("tx");
+    }
+    let Ok(built_tx) = borsh::from_slice::<tx::Tx>(bytes) else {
+        return issues;
+    };
+    if !is_canonical::<namada_sdk::tx::Tx>(built_tx.tx_bytes()) {
+        issues.push("namada tx");
+    }
+    if built_tx
+        .account_public_keys_maps()
+        .any(|map| !is_canonical::<AccountPublicKeysMap>(map))
+    {
+        issues.push("account public keys map");
+    }
+    issues
+}
+
+/// Checks that a Borsh serialized built Tx, as returned by the build functions, is
+/// canonically serialized. Returns the names of non-canonical parts, empty if none.
+#[wasm_bindgen]
+pub fn audit_tx_serialization(tx_bytes: &[u8]) -> Result<JsValue, JsError> {
+    to_js_result(audit_tx(tx_bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::canonical_json;
+    use namada_sdk::key::common::PublicKey;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use wasm_bindgen_test::*;
+
+    const PUBLIC_KEYS: [&str; 2] = [
+        "tpknam1qzm68sfdcryvwj9tqaf9kuq3y2ugh4u0vqx8vdpdyle9uhujg3xduf408cn",
+        "tpknam1qptrn64myunqr4847yq4cn0uwek5ecwc7eeexjfc5npmd5kmg6ex563n5as",
+    ];
+
+    #[wasm_bindgen_test]
+    fn account_public_keys_maps_are_canonical() {
+        let public_keys: Vec<PublicKey> = PUBLIC_KEYS
+            .iter()
+            .map(|pk| PublicKey::from_str(pk).unwrap())
+            .collect();
+        let map = AccountPublicKeysMap::from_iter(public_keys);
+
+        assert!(is_canonical::<AccountPublicKeysMap>(
+            &borsh::to_vec(&map).unwrap()
+        ));
+        assert!(!is_canonical::<AccountPublicKeysMap>(&[1, 2, 3]));
+    }
+
+    #[wasm_bindgen_test]
+    fn sorts_json_keys() {
+        let map: HashMap<String, u8> = ('a'..='z').map(|c| (c.to_string(), 0)).collect();
+        let json = canonical_json(&map).unwrap().to_string();
+        let keys: Vec<String> = serde_json::from_str::<serde_json::Value>(&json)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+    }
+}
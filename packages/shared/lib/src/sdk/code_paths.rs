@@ -0,0 +1,215 @@
+//! Registry of tx code paths
+//!
+//! Txs reference their wasm code by the hash stored on chain under the code path, e.g.
+//! "tx_transfer.wasm". Forks and testnets may deploy the code under other names, so the paths
+//! can be overridden per chain with set_code_paths, and are otherwise the defaults of
+//! namada_sdk.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[cfg(feature = "web")]
+use gloo_utils::format::JsValueSerdeExt;
+use namada_sdk::chain::ChainId;
+use namada_sdk::tx::{
+    TX_BOND_WASM, TX_BRIDGE_POOL_WASM, TX_CHANGE_COMMISSION_WASM, TX_CHANGE_CONSENSUS_KEY_WASM,
+    TX_CHANGE_METADATA_WASM, TX_CLAIM_REWARDS_WASM, TX_IBC_WASM, TX_INIT_ACCOUNT_WASM,
+    TX_REDELEGATE_WASM, TX_REVEAL_PK, TX_TRANSFER_WASM, TX_UNBOND_WASM, TX_UPDATE_ACCOUNT_WASM,
+    TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "web")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "web")]
+use crate::utils::to_js_result;
+
+thread_local! {
+    /// Code paths by chain id
+    static OVERRIDES: RefCell<HashMap<String, HashMap<TxCode, String>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Tx codes used by the SDK
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TxCode {
+    Transfer,
+    Bond,
+    Redelegate,
+    Unbond,
+    Withdraw,
+    ClaimRewards,
+    RevealPk,
+    VoteProposal,
+    Ibc,
+    BridgePool,
+    ChangeCommission,
+    ChangeMetadata,
+    ChangeConsensusKey,
+    InitAccount,
+    UpdateAccount,
+}
+
+impl TxCode {
+    #[cfg(any(feature = "web", feature = "ffi"))]
+    pub const ALL: [TxCode; 15] = [
+        TxCode::Transfer,
+        TxCode::Bond,
+        TxCode::Redelegate,
+        TxCode::Unbond,
+        TxCode::Withdraw,
+        TxCode::ClaimRewards,
+        TxCode::RevealPk,
+        TxCode::VoteProposal,
+        TxCode::Ibc,
+        TxCode::BridgePool,
+        TxCode::ChangeCommission,
+        TxCode::ChangeMetadata,
+        TxCode::ChangeConsensusKey,
+        TxCode::InitAccount,
+        TxCode::UpdateAccount,
+    ];
+
+    pub fn default_path(&self) -> &'static str {
+        match self {
+            TxCode::Transfer => TX_TRANSFER_WASM,
+            TxCode::Bond => TX_BOND_WASM,
+            TxCode::Redelegate => TX_REDELEGATE_WASM,
+            TxCode::Unbond => TX_UNBOND_WASM,
+            TxCode::Withdraw => TX_WITHDRAW_WASM,
+            TxCode::ClaimRewards => TX_CLAIM_REWARDS_WASM,
+            TxCode::RevealPk => TX_REVEAL_PK,
+            TxCode::VoteProposal => TX_VOTE_PROPOSAL,
+            TxCode::Ibc => TX_IBC_WASM,
+            TxCode::BridgePool => TX_BRIDGE_POOL_WASM,
+            TxCode::ChangeCommission => TX_CHANGE_COMMISSION_WASM,
+            TxCode::ChangeMetadata => TX_CHANGE_METADATA_WASM,
+            TxCode::ChangeConsensusKey => TX_CHANGE_CONSENSUS_KEY_WASM,
+            TxCode::InitAccount => TX_INIT_ACCOUNT_WASM,
+            TxCode::UpdateAccount => TX_UPDATE_ACCOUNT_WASM,
+        }
+    }
+}
+
+/// Returns the code path of a tx on a chain, the default one if the chain has no override
+pub fn code_path(code: TxCode, chain_id: Option<&ChainId>) -> PathBuf {
+    let path = chain_id.and_then(|chain_id| {
+        OVERRIDES.with(|overrides| {
+            overrides
+                .borrow()
+                .get(chain_id.as_str())
+                .and_then(|paths| paths.get(&code).cloned())
+        })
+    });
+    PathBuf::from(path.unwrap_or_else(|| code.default_path().to_string()))
+}
+
+/// Returns the code paths of all txs on a chain
+#[cfg(feature = "web")]
+pub fn code_paths(chain_id: Option<&ChainId>) -> Vec<(TxCode, String)> {
+    TxCode::ALL
+        .iter()
+        .map(|code| {
+            (
+                *code,
+                code_path(*code, chain_id).to_string_lossy().into_owned(),
+            )
+        })
+        .collect()
+}
+
+/// Returns the tx code of a path, looking up the defaults and the overrides of all chains
+#[cfg(any(feature = "web", feature = "ffi"))]
+pub fn tx_code_of_path(path: &str) -> Option<TxCode> {
+    TxCode::ALL
+        .into_iter()
+        .find(|code| code.default_path() == path)
+        .or_else(|| {
+            OVERRIDES.with(|overrides| {
+                overrides.borrow().values().find_map(|paths| {
+                    paths
+                        .iter()
+                        .find(|(_, override_path)| override_path.as_str() == path)
+                        .map(|(code, _)| *code)
+                })
+            })
+        })
+}
+
+/// Overrides code paths of a chain, e.g. `{ transfer: "tx_transfer_v2.wasm" }`. Codes left out
+/// use the defaults, an empty object resets the chain.
+///
+/// # Errors
+///
+/// Returns JsError if the overrides are not a map of TxCode to non-empty paths
+#[cfg(feature = "web")]
+#[wasm_bindgen]
+pub fn set_code_paths(chain_id: String, paths: JsValue) -> Result<(), JsError> {
+    let paths: HashMap<TxCode, String> = paths.into_serde()?;
+    if let Some((code, _)) = paths.iter().find(|(_, path)| path.trim().is_empty()) {
+        return Err(JsError::new(&format!("Empty code path of {:?}", code)));
+    }
+
+    OVERRIDES.with(|overrides| {
+        let mut overrides = overrides.borrow_mut();
+        if paths.is_empty() {
+            overrides.remove(&chain_id);
+        } else {
+            overrides.insert(chain_id, paths);
+        }
+    });
+    Ok(())
+}
+
+/// Returns the code paths of a chain as an object of TxCode to path, the defaults if chain_id
+/// is not provided
+#[cfg(feature = "web")]
+#[wasm_bindgen]
+pub fn get_code_paths(chain_id: Option<String>) -> Result<JsValue, JsError> {
+    let chain_id = chain_id.map(ChainId);
+    let paths: HashMap<TxCode, String> = code_paths(chain_id.as_ref()).into_iter().collect();
+    to_js_result(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn overrides_code_paths_per_chain() {
+        let chain = ChainId(String::from("fork-testnet"));
+        let other = ChainId(String::from("namada"));
+        OVERRIDES.with(|overrides| {
+            overrides.borrow_mut().insert(
+                chain.to_string(),
+                HashMap::from([(TxCode::Transfer, String::from("tx_transfer_v2.wasm"))]),
+            )
+        });
+
+        assert_eq!(
+            code_path(TxCode::Transfer, Some(&chain)),
+            PathBuf::from("tx_transfer_v2.wasm")
+        );
+        assert_eq!(
+            code_path(TxCode::Bond, Some(&chain)),
+            PathBuf::from(TX_BOND_WASM)
+        );
+        assert_eq!(
+            code_path(TxCode::Transfer, Some(&other)),
+            PathBuf::from(TX_TRANSFER_WASM)
+        );
+        #[cfg(any(feature = "web", feature = "ffi"))]
+        {
+            assert_eq!(
+                tx_code_of_path("tx_transfer_v2.wasm"),
+                Some(TxCode::Transfer)
+            );
+            assert_eq!(tx_code_of_path(TX_IBC_WASM), Some(TxCode::Ibc));
+            assert_eq!(tx_code_of_path("tx_unknown.wasm"), None);
+        }
+
+        OVERRIDES.with(|overrides| overrides.borrow_mut().clear());
+    }
+}
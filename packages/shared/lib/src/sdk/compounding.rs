@@ -0,0 +1,213 @@
+//! Auto-compounding of staking rewards
+//!
+//! Claiming rewards and bonding them back grows the stake, but every claim + bond batch pays a
+//! wrapper fee, so compounding small rewards loses tokens. compound_decision decides from the
+//! claimable rewards whether compounding now is worth it, and which claims and bonds the batch
+//! contains. The frontend only schedules the checks and builds the batch it returns.
+use std::str::FromStr;
+
+use gloo_utils::format::JsValueSerdeExt;
+use namada_sdk::address::Address;
+use namada_sdk::token::{Amount, NATIVE_MAX_DECIMAL_PLACES};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
+
+use super::rebalance::StakeChange;
+use crate::utils::to_js_result;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorRewards {
+    pub validator: String,
+    /// Claimable rewards in NAM
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompoundParams {
+    pub rewards: Vec<ValidatorRewards>,
+    /// Minimum rewards in NAM to compound, after fees
+    pub threshold: String,
+    /// Estimated fee of the claim + bond batch in NAM
+    pub fee: Option<String>,
+    pub epoch: Option<u64>,
+    pub last_compounded_epoch: Option<u64>,
+    /// Epochs to wait between two compounds
+    #[serde(default)]
+    pub min_epochs_between: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompoundReason {
+    Ready,
+    NoRewards,
+    BelowThreshold,
+    TooSoon,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompoundDecision {
+    pub compound: bool,
+    pub reason: CompoundReason,
+    /// Claimable rewards in NAM, before fees
+    pub total: String,
+    /// Validators to claim the rewards of
+    pub claims: Vec<String>,
+    /// Rewards bonded back to the validators paying them
+    pub bonds: Vec<StakeChange>,
+}
+
+fn native_amount(field: &str, amount: &str) -> Result<Amount, JsError> {
+    Amount::from_str(amount, NATIVE_MAX_DECIMAL_PLACES)
+        .map_err(|e| JsError::new(&format!("Invalid {} {}: {}", field, amount, e)))
+}
+
+/// Decides whether to compound rewards now
+///
+/// # Errors
+///
+/// Returns JsError if an amount or validator address is invalid
+pub fn decide(params: &CompoundParams) -> Result<CompoundDecision, JsError> {
+    let mut rewards = vec![];
+    for reward in &params.rewards {
+        Address::from_str(&reward.validator)?;
+        let amount = native_amount("rewards", &reward.amount)?;
+        if !amount.is_zero() {
+            rewards.push((reward.validator.clone(), amount));
+        }
+    }
+    let total = rewards
+        .iter()
+        .try_fold(Amount::zero(), |total, (_, amount)| {
+            total.checked_add(*amount)
+        })
+        .ok_or_else(|| JsError::new("Rewards overflow"))?;
+    let threshold = native_amount("threshold", &params.threshold)?;
+    let fee = match &params.fee {
+        Some(fee) => native_amount("fee", fee)?,
+        None => Amount::zero(),
+    };
+
+    let too_soon = match (params.epoch, params.last_compounded_epoch) {
+        (Some(epoch), Some(last)) => epoch < last.saturating_add(params.min_epochs_between),
+        _ => false,
+    };
+    let reason = if rewards.is_empty() {
+        CompoundReason::NoRewards
+    } else if too_soon {
+        CompoundReason::TooSoon
+    } else if total
+        .checked_sub(fee)
+        .map_or(true, |net| net.is_zero() || net < threshold)
+    {
+        CompoundReason::BelowThreshold
+    } else {
+        CompoundReason::Ready
+    };
+
+    let compound = reason == CompoundReason::Ready;
+    let (claims, bonds) = if compound {
+        rewards
+            .into_iter()
+            .map(|(validator, amount)| {
+                let bond = StakeChange {
+                    validator: validator.clone(),
+                    amount: amount.to_string_native(),
+                };
+                (validator, bond)
+            })
+            .unzip()
+    } else {
+        (vec![], vec![])
+    };
+
+    Ok(CompoundDecision {
+        compound,
+        reason,
+        total: total.to_string_native(),
+        claims,
+        bonds,
+    })
+}
+
+/// Decides whether to compound rewards now, see CompoundParams for the fields of params
+///
+/// # Errors
+///
+/// Returns JsError if params can't be deserialized or an amount is invalid
+#[wasm_bindgen]
+pub fn compound_decision(params: JsValue) -> Result<JsValue, JsError> {
+    let params: CompoundParams = params.into_serde()?;
+    to_js_result(decide(&params)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const VALIDATOR_1: &str = "tnam1qz4sdx5jlh909j44uz46pl6f3d2ftsvkmcml0yuf";
+    const VALIDATOR_2: &str = "tnam1qpgk927uh2aqjs92yhycsh08n5yggvltn5nk92zp";
+
+    fn params(rewards: &[(&str, &str)], threshold: &str, fee: Option<&str>) -> CompoundParams {
+        CompoundParams {
+            rewards: rewards
+                .iter()
+                .map(|(validator, amount)| ValidatorRewards {
+                    validator: validator.to_string(),
+                    amount: amount.to_string(),
+                })
+                .collect(),
+            threshold: threshold.to_string(),
+            fee: fee.map(String::from),
+            epoch: None,
+            last_compounded_epoch: None,
+            min_epochs_between: 0,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn compounds_rewards_above_threshold() {
+        let decision = decide(&params(
+            &[(VALIDATOR_1, "3"), (VALIDATOR_2, "0")],
+            "2",
+            Some("0.5"),
+        ))
+        .unwrap();
+        assert!(decision.compound);
+        assert_eq!(decision.claims, vec![VALIDATOR_1.to_string()]);
+        assert_eq!(
+            decision.bonds,
+            vec![StakeChange {
+                validator: VALIDATOR_1.to_string(),
+                amount: Amount::from_u64(3_000_000).to_string_native(),
+            }]
+        );
+
+        let below = decide(&params(&[(VALIDATOR_1, "2.2")], "2", Some("0.5"))).unwrap();
+        assert_eq!(below.reason, CompoundReason::BelowThreshold);
+        assert!(below.claims.is_empty() && below.bonds.is_empty());
+
+        let fee_only = decide(&params(&[(VALIDATOR_1, "0.1")], "0", Some("0.5"))).unwrap();
+        assert_eq!(fee_only.reason, CompoundReason::BelowThreshold);
+
+        let none = decide(&params(&[(VALIDATOR_1, "0")], "0", None)).unwrap();
+        assert_eq!(none.reason, CompoundReason::NoRewards);
+    }
+
+    #[wasm_bindgen_test]
+    fn waits_between_compounds() {
+        let mut too_soon = params(&[(VALIDATOR_1, "3")], "1", None);
+        too_soon.epoch = Some(12);
+        too_soon.last_compounded_epoch = Some(10);
+        too_soon.min_epochs_between = 3;
+        assert_eq!(decide(&too_soon).unwrap().reason, CompoundReason::TooSoon);
+
+        too_soon.epoch = Some(13);
+        assert!(decide(&too_soon).unwrap().compound);
+        assert!(decide(&params(&[(VALIDATOR_1, "x")], "1", None)).is_err());
+    }
+}
@@ -0,0 +1,401 @@
+//! Decoding of Msgs with the location of failures
+//!
+//! Borsh errors only say that the input ended early or a value was invalid, not where. When a
+//! Msg fails to decode, the bytes are walked again along the BorshSchema of the Msg to find
+//! the field and offset of the failure, e.g. "data[1].amount at byte 92". A field of the TS
+//! schema in the wrong order or of the wrong type then shows up as the first field that
+//! doesn't match.
+//!
+//! Msgs passed to the tx builders start with a MsgTag byte, so that the common mistake of
+//! swapping the WrapperTxMsg and the specific Msg, or encoding a Msg twice, is rejected as
+//! such instead of failing somewhere in the fields or, worse, decoding as the other Msg. The
+//! TS packages add the tag in Message.encode.
+use std::fmt;
+
+use namada_sdk::borsh::schema::{BorshSchemaContainer, Declaration, Definition, Fields};
+use namada_sdk::borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+use super::args::{
+    BondMsg, ClaimRewardsMsg, EthBridgeTransferMsg, IbcTransferMsg, InitAccountMsg,
+    MaxUnshieldingTransferMsg, RedelegateMsg, ShieldedTransferMsg, ShieldingTransferMsg,
+    TransferKindMsg, TransparentTransferMsg, UnbondMsg, UnshieldingTransferMsg, UpdateAccountMsg,
+    ValidatorMaintenanceMsg, VoteProposalMsg, WithdrawMsg, WrapperTxMsg,
+};
+use super::error::JsError;
+
+/// First byte of a tagged Msg, keep in sync with MSG_TAGS of packages/types
+///
+/// Untagged Msgs start with the length of a string, usually an address, so tags are above the
+/// lengths of such strings to tell untagged bytes apart as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MsgTag {
+    WrapperTx = 0xa0,
+    Bond = 0xa1,
+    Unbond = 0xa2,
+    Withdraw = 0xa3,
+    Redelegate = 0xa4,
+    VoteProposal = 0xa5,
+    ClaimRewards = 0xa6,
+    ValidatorMaintenance = 0xa7,
+    TransparentTransfer = 0xa8,
+    ShieldedTransfer = 0xa9,
+    ShieldingTransfer = 0xaa,
+    UnshieldingTransfer = 0xab,
+    MaxUnshieldingTransfer = 0xac,
+    IbcTransfer = 0xad,
+    EthBridgeTransfer = 0xae,
+    TransferKind = 0xaf,
+    InitAccount = 0xb0,
+    UpdateAccount = 0xb1,
+}
+
+/// Msg starting with its MsgTag
+pub trait TaggedMsg: BorshSerialize + BorshDeserialize + BorshSchema {
+    const TAG: MsgTag;
+}
+
+macro_rules! tagged_msgs {
+    ($($msg:ident => $tag:ident),* $(,)?) => {
+        impl MsgTag {
+            pub const ALL: &'static [MsgTag] = &[$(MsgTag::$tag),*];
+
+            pub(crate) fn from_byte(byte: u8) -> Option<MsgTag> {
+                MsgTag::ALL.iter().copied().find(|tag| *tag as u8 == byte)
+            }
+
+            /// Name of the tagged Msg
+            pub fn msg_type(self) -> &'static str {
+                match self {
+                    $(MsgTag::$tag => stringify!($msg)),*
+                }
+            }
+
+            /// BorshSchema of the tagged Msg, without the tag
+            pub fn schema(self) -> BorshSchemaContainer {
+                match self {
+                    $(MsgTag::$tag => BorshSchemaContainer::for_type::<$msg>()),*
+                }
+            }
+        }
+
+        $(impl TaggedMsg for $msg {
+            const TAG: MsgTag = MsgTag::$tag;
+        })*
+    };
+}
+
+tagged_msgs! {
+    WrapperTxMsg => WrapperTx,
+    BondMsg => Bond,
+    UnbondMsg => Unbond,
+    WithdrawMsg => Withdraw,
+    RedelegateMsg => Redelegate,
+    VoteProposalMsg => VoteProposal,
+    ClaimRewardsMsg => ClaimRewards,
+    ValidatorMaintenanceMsg => ValidatorMaintenance,
+    TransparentTransferMsg => TransparentTransfer,
+    ShieldedTransferMsg => ShieldedTransfer,
+    ShieldingTransferMsg => ShieldingTransfer,
+    UnshieldingTransferMsg => UnshieldingTransfer,
+    MaxUnshieldingTransferMsg => MaxUnshieldingTransfer,
+    IbcTransferMsg => IbcTransfer,
+    EthBridgeTransferMsg => EthBridgeTransfer,
+    TransferKindMsg => TransferKind,
+    InitAccountMsg => InitAccount,
+    UpdateAccountMsg => UpdateAccount,
+}
+
+/// Location of a decoding failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    /// Name of the decoded Msg
+    pub msg_type: String,
+    /// Field path, e.g. "data[1].amount", empty for the Msg itself
+    pub path: String,
+    /// Offset of the failing value in the bytes
+    pub offset: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            String::new()
+        } else {
+            format!(".{}", self.path.trim_start_matches('.'))
+        };
+        write!(
+            f,
+            "Invalid {}{} at byte {}: {}",
+            self.msg_type, path, self.offset, self.reason
+        )
+    }
+}
+
+impl From<DecodeError> for JsError {
+    fn from(error: DecodeError) -> JsError {
+        JsError::new(&error.to_string())
+    }
+}
+
+struct Walker<'a> {
+    schema: &'a BorshSchemaContainer,
+    bytes: &'a [u8],
+    offset: usize,
+    path: String,
+}
+
+type WalkResult = Result<(), (usize, String)>;
+
+impl<'a> Walker<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], (usize, String)> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| {
+                (
+                    self.offset,
+                    format!(
+                        "expected {} bytes, {} left",
+                        len,
+                        self.bytes.len() - self.offset
+                    ),
+                )
+            })?;
+        let taken = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(taken)
+    }
+
+    fn take_uint(&mut self, width: u8) -> Result<u64, (usize, String)> {
+        let bytes = self.take(width as usize)?;
+        let mut value = [0u8; 8];
+        value[..bytes.len()].copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(value))
+    }
+
+    /// Walks into a nested value, restoring the path afterwards
+    fn nested(&mut self, segment: &str, declaration: &Declaration) -> WalkResult {
+        let len = self.path.len();
+        self.path.push_str(segment);
+        self.walk(declaration)?;
+        self.path.truncate(len);
+        Ok(())
+    }
+
+    fn walk(&mut self, declaration: &Declaration) -> WalkResult {
+        let start = self.offset;
+        let definition = match self.schema.get_definition(declaration) {
+            Some(definition) => definition,
+            None => return Err((start, format!("no schema of {}", declaration))),
+        };
+
+        match definition {
+            Definition::Primitive(size) => {
+                let bytes = self.take(*size as usize)?;
+                if declaration == "bool" && bytes[0] > 1 {
+                    return Err((start, format!("invalid bool {}", bytes[0])));
+                }
+            }
+            Definition::Sequence {
+                length_width,
+                length_range,
+                elements,
+            } => {
+                let len = if *length_width == 0 {
+                    *length_range.start()
+                } else {
+                    self.take_uint(*length_width)?
+                };
+                if !length_range.contains(&len) {
+                    return Err((start, format!("invalid length {}", len)));
+                }
+                let elements_start = self.offset;
+                for i in 0..len {
+                    self.nested(&format!("[{}]", i), elements)?;
+                }
+                if declaration == "String"
+                    && std::str::from_utf8(&self.bytes[elements_start..self.offset]).is_err()
+                {
+                    return Err((start, "invalid UTF-8".to_string()));
+                }
+            }
+            Definition::Tuple { elements } => {
+                for (i, element) in elements.iter().enumerate() {
+                    self.nested(&format!(".{}", i), element)?;
+                }
+            }
+            Definition::Enum {
+                tag_width,
+                variants,
+            } => {
+                let tag = self.take_uint(*tag_width)?;
+                let (_, name, variant) = variants
+                    .iter()
+                    .find(|(discriminant, _, _)| *discriminant as u64 == tag)
+                    .ok_or_else(|| (start, format!("invalid variant {}", tag)))?;
+                // Option has no name in the TS schema
+                let segment = if name == "Some" || name == "None" {
+                    String::new()
+                } else {
+                    format!("::{}", name)
+                };
+                self.nested(&segment, variant)?;
+            }
+            Definition::Struct { fields } => match fields {
+                Fields::NamedFields(fields) => {
+                    for (name, field) in fields {
+                        self.nested(&format!(".{}", name), field)?;
+                    }
+                }
+                Fields::UnnamedFields(fields) => {
+                    for (i, field) in fields.iter().enumerate() {
+                        self.nested(&format!(".{}", i), field)?;
+                    }
+                }
+                Fields::Empty => {}
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Returns where decoding bytes as `T` fails, or None if they are valid
+pub fn locate_error<T: BorshSchema>(bytes: &[u8]) -> Option<DecodeError> {
+    let schema = BorshSchemaContainer::for_type::<T>();
+    let mut walker = Walker {
+        schema: &schema,
+        bytes,
+        offset: 0,
+        path: String::new(),
+    };
+    let msg_type = schema.declaration().to_string();
+
+    let (offset, reason) = match walker.walk(schema.declaration()) {
+        Err(failure) => failure,
+        Ok(()) if walker.offset < bytes.len() => {
+            walker.path.clear();
+            (
+                walker.offset,
+                format!("{} trailing bytes", bytes.len() - walker.offset),
+            )
+        }
+        Ok(()) => return None,
+    };
+    Some(DecodeError {
+        msg_type,
+        path: walker.path,
+        offset,
+        reason,
+    })
+}
+
+/// Decodes a Borsh serialized Msg
+///
+/// # Errors
+///
+/// Returns JsError with the field and offset of the failure, see locate_error
+pub fn decode_msg<T: BorshDeserialize + BorshSchema>(bytes: &[u8]) -> Result<T, JsError> {
+    decode_from(bytes, 0)
+}
+
+/// Decodes the bytes after `start`, with offsets of failures into all of the bytes
+fn decode_from<T: BorshDeserialize + BorshSchema>(
+    bytes: &[u8],
+    start: usize,
+) -> Result<T, JsError> {
+    let body = &bytes[start..];
+    T::try_from_slice(body).map_err(|e| match locate_error::<T>(body) {
+        Some(mut located) => {
+            located.offset += start;
+            located.into()
+        }
+        // Valid layout, but a value is rejected by its type
+        None => JsError::from(e),
+    })
+}
+
+/// Decodes a Msg starting with its MsgTag
+///
+/// # Errors
+///
+/// Returns JsError naming the Msg found if the tag is of another Msg, or the location of the
+/// failure if the Msg can't be decoded
+pub fn decode_tagged_msg<T: TaggedMsg>(bytes: &[u8]) -> Result<T, JsError> {
+    let expected = T::TAG.msg_type();
+    let tag = bytes
+        .first()
+        .copied()
+        .ok_or_else(|| JsError::new(&format!("Expected {}, got no bytes", expected)))?;
+    if tag == T::TAG as u8 {
+        return decode_from(bytes, 1);
+    }
+    Err(JsError::new(&match MsgTag::from_byte(tag) {
+        Some(found) => format!(
+            "Expected {}, got {}: are the Msgs swapped?",
+            expected,
+            found.msg_type()
+        ),
+        None => format!(
+            "Expected {}, got bytes without a Msg tag (first byte {}): are they encoded with \
+             Message.encode?",
+            expected, tag
+        ),
+    }))
+}
+
+/// Encodes a Msg starting with its MsgTag, as decoded by decode_tagged_msg
+pub fn encode_tagged_msg<T: TaggedMsg>(msg: &T) -> Vec<u8> {
+    let mut bytes = vec![T::TAG as u8];
+    msg.serialize(&mut bytes)
+        .expect("Serializing into a Vec shouldn't fail");
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::args::{BondMsg, WrapperTxMsg};
+    use namada_sdk::borsh;
+    use wasm_bindgen_test::*;
+
+    fn bond_bytes() -> Vec<u8> {
+        borsh::to_vec(&BondMsg::new(
+            "source".to_string(),
+            "validator".to_string(),
+            "1.5".to_string(),
+        ))
+        .unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn locates_failing_field() {
+        assert_eq!(locate_error::<BondMsg>(&bond_bytes()), None);
+
+        let bytes = bond_bytes();
+        let truncated = locate_error::<BondMsg>(&bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(truncated.msg_type, "BondMsg");
+        assert!(truncated.path.starts_with(".amount"));
+
+        let mut trailing = bond_bytes();
+        trailing.push(0);
+        let error = locate_error::<BondMsg>(&trailing).unwrap();
+        assert_eq!((error.path.as_str(), error.offset), ("", bytes.len()));
+
+        // Valid fields up to an invalid option tag of the public key
+        let mut wrapper = borsh::to_vec(&"tnam1".to_string()).unwrap();
+        wrapper.extend(borsh::to_vec(&("1", "2", "chain")).unwrap());
+        wrapper.push(7);
+        let error = locate_error::<WrapperTxMsg>(&wrapper).unwrap();
+        assert_eq!(error.path, ".public_key");
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Invalid WrapperTxMsg.public_key at byte {}: invalid variant 7",
+                wrapper.len() - 1
+            )
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! Memos encrypted to a recipient
+//!
+//! Memos are public once a tx is applied. To attach private data, e.g. order ids, a memo is
+//! encrypted with XChaCha20-Poly1305 using a key agreed with X25519 between an ephemeral key
+//! and the public key of the recipient, who decrypts it with decrypt_memo.
+//!
+//! Encrypted memos are `ENCRYPTED_MEMO_PREFIX` followed by the hex encoded ephemeral public
+//! key, nonce and ciphertext.
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit};
+#[cfg(feature = "web")]
+use chacha20poly1305::XNonce;
+use chacha20poly1305::{Key, XChaCha20Poly1305};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+#[cfg(feature = "web")]
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+#[cfg(feature = "web")]
+use x25519_dalek::StaticSecret;
+use x25519_dalek::{EphemeralSecret, PublicKey};
+#[cfg(feature = "web")]
+use zeroize::Zeroize;
+
+use super::error::JsError;
+#[cfg(feature = "web")]
+use crate::utils::to_js_result;
+
+pub const ENCRYPTED_MEMO_PREFIX: &str = "enc1:";
+
+/// Domain separation of the derived keys
+const KDF_CONTEXT: &[u8] = b"namada-interface/encrypted-memo/v1";
+const KEY_LEN: usize = 32;
+#[cfg(feature = "web")]
+const NONCE_LEN: usize = 24;
+
+fn parse_key(hex_key: &str, what: &str) -> Result<[u8; KEY_LEN], JsError> {
+    hex::decode(hex_key)
+        .ok()
+        .and_then(|bytes| <[u8; KEY_LEN]>::try_from(bytes).ok())
+        .ok_or_else(|| JsError::new(&format!("Invalid {}, expected 32 hex encoded bytes", what)))
+}
+
+fn derive_key(shared_secret: &[u8], ephemeral_pk: &PublicKey, recipient_pk: &PublicKey) -> Key {
+    let mut hasher = Sha256::new();
+    hasher.update(KDF_CONTEXT);
+    hasher.update(shared_secret);
+    hasher.update(ephemeral_pk.as_bytes());
+    hasher.update(recipient_pk.as_bytes());
+    hasher.finalize()
+}
+
+#[cfg(feature = "web")]
+pub fn is_encrypted_memo(memo: &str) -> bool {
+    memo.starts_with(ENCRYPTED_MEMO_PREFIX)
+}
+
+/// Encrypts a memo to the X25519 public key of a recipient
+///
+/// # Errors
+///
+/// Returns JsError if the public key is invalid or encryption fails
+pub fn encrypt(memo: &str, recipient_public_key: &str) -> Result<String, JsError> {
+    let recipient_pk = PublicKey::from(parse_key(recipient_public_key, "recipient public key")?);
+
+    let ephemeral_sk = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_pk = PublicKey::from(&ephemeral_sk);
+    let shared_secret = ephemeral_sk.diffie_hellman(&recipient_pk);
+    let key = derive_key(shared_secret.as_bytes(), &ephemeral_pk, &recipient_pk);
+
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(&key)
+        .encrypt(&nonce, memo.as_bytes())
+        .map_err(|_| JsError::new("Failed to encrypt memo"))?;
+
+    let mut payload = ephemeral_pk.as_bytes().to_vec();
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+
+    Ok(format!("{}{}", ENCRYPTED_MEMO_PREFIX, hex::encode(payload)))
+}
+
+/// Decrypts a memo encrypted to the public key of an X25519 secret key
+///
+/// # Errors
+///
+/// Returns JsError if the memo is not encrypted, malformed, or encrypted to another key
+#[cfg(feature = "web")]
+pub fn decrypt(memo: &str, secret_key: &str) -> Result<String, JsError> {
+    let payload = memo
+        .strip_prefix(ENCRYPTED_MEMO_PREFIX)
+        .ok_or_else(|| JsError::new("Memo is not encrypted"))?;
+    let payload = hex::decode(payload).map_err(|_| JsError::new("Malformed encrypted memo"))?;
+    if payload.len() < KEY_LEN + NONCE_LEN {
+        return Err(JsError::new("Malformed encrypted memo"));
+    }
+    let (ephemeral_pk, rest) = payload.split_at(KEY_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let ephemeral_pk =
+        PublicKey::from(<[u8; KEY_LEN]>::try_from(ephemeral_pk).expect("length was checked"));
+
+    let mut secret_key_bytes = parse_key(secret_key, "secret key")?;
+    let secret_key = StaticSecret::from(secret_key_bytes);
+    secret_key_bytes.zeroize();
+    let recipient_pk = PublicKey::from(&secret_key);
+    let shared_secret = secret_key.diffie_hellman(&ephemeral_pk);
+    let key = derive_key(shared_secret.as_bytes(), &ephemeral_pk, &recipient_pk);
+
+    let plaintext = XChaCha20Poly1305::new(&key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| JsError::new("Memo can't be decrypted with this key"))?;
+
+    String::from_utf8(plaintext).map_err(|_| JsError::new("Decrypted memo is not valid UTF-8"))
+}
+
+/// Generates a random X25519 key pair for receiving encrypted memos.
+/// Returns a tuple of hex encoded (secret key, public key).
+#[cfg(feature = "web")]
+#[wasm_bindgen]
+pub fn generate_memo_keypair() -> Result<JsValue, JsError> {
+    let secret_key = StaticSecret::random_from_rng(OsRng);
+    let public_key = PublicKey::from(&secret_key);
+
+    to_js_result((
+        hex::encode(secret_key.to_bytes()),
+        hex::encode(public_key.as_bytes()),
+    ))
+}
+
+/// Encrypts a memo to a hex encoded X25519 public key, see encrypt
+#[cfg(feature = "web")]
+#[wasm_bindgen]
+pub fn encrypt_memo(memo: &str, recipient_public_key: &str) -> Result<String, JsError> {
+    encrypt(memo, recipient_public_key)
+}
+
+/// Decrypts a memo with a hex encoded X25519 secret key, see decrypt
+#[cfg(feature = "web")]
+#[wasm_bindgen]
+pub fn decrypt_memo(memo: &str, secret_key: &str) -> Result<String, JsError> {
+    decrypt(memo, secret_key)
+}
+
+#[cfg(all(test, feature = "web"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn can_round_trip_encrypted_memo() {
+        let secret_key = StaticSecret::random_from_rng(OsRng);
+        let public_key = hex::encode(PublicKey::from(&secret_key).as_bytes());
+        let secret_key = hex::encode(secret_key.to_bytes());
+
+        let memo = encrypt("order #42", &public_key).unwrap();
+        assert!(is_encrypted_memo(&memo));
+        assert!(!memo.contains("order"));
+        assert_eq!(decrypt(&memo, &secret_key).unwrap(), "order #42");
+
+        let other_key = hex::encode(StaticSecret::random_from_rng(OsRng).to_bytes());
+        assert!(decrypt(&memo, &other_key).is_err());
+        assert!(decrypt("order #42", &secret_key).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Errors with the context of the failed operation
+//!
+//! Errors of namada_sdk and of parsing arguments only describe what went wrong, e.g.
+//! "Invalid address". They are wrapped with the operation, the argument and an excerpt of the
+//! input, keeping the original error as the source, before being converted to JsError.
+//!
+//! Native builds, without the web feature, have no JS to create errors with, so JsError is
+//! replaced by a plain error with the same constructors.
+use thiserror::Error;
+
+#[cfg(feature = "web")]
+pub use wasm_bindgen::JsError;
+
+/// Maximum number of characters of an input included in an error message
+const MAX_EXCERPT_LEN: usize = 24;
+
+#[derive(Debug, Error)]
+pub enum SdkError {
+    /// An argument could not be parsed
+    #[error("{operation}: invalid {field}{}: {source}", excerpt_suffix(.input))]
+    InvalidArgument {
+        operation: &'static str,
+        field: &'static str,
+        /// Excerpt of the input, None for secrets
+        input: Option<String>,
+        #[source]
+        source: Box<dyn std::error::Error>,
+    },
+    /// namada_sdk failed to perform an operation
+    #[error("{operation}: {source}")]
+    Namada {
+        operation: &'static str,
+        #[source]
+        source: namada_sdk::error::Error,
+    },
+}
+
+fn excerpt_suffix(input: &Option<String>) -> String {
+    input
+        .as_ref()
+        .map(|input| format!(" \"{}\"", input))
+        .unwrap_or_default()
+}
+
+/// Shortens an input to at most MAX_EXCERPT_LEN characters
+pub fn excerpt(input: &str) -> String {
+    match input.char_indices().nth(MAX_EXCERPT_LEN) {
+        Some((end, _)) => format!("{}...", &input[..end]),
+        None => input.to_string(),
+    }
+}
+
+/// Error of native builds, mirroring wasm_bindgen::JsError
+#[cfg(not(feature = "web"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsError {
+    message: String,
+}
+
+#[cfg(not(feature = "web"))]
+impl JsError {
+    pub fn new(message: &str) -> JsError {
+        JsError {
+            message: message.to_string(),
+        }
+    }
+}
+
+// Like wasm_bindgen::JsError, it doesn't implement Error itself, which would conflict with
+// From<T> for T
+#[cfg(not(feature = "web"))]
+impl<E: std::error::Error> From<E> for JsError {
+    fn from(error: E) -> JsError {
+        JsError::new(&error.to_string())
+    }
+}
+
+#[cfg(not(feature = "web"))]
+impl std::fmt::Display for JsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Adds the argument context to errors of parsing user input
+pub trait ArgContext<T> {
+    /// Adds the operation, the argument and an excerpt of the input
+    fn arg(self, operation: &'static str, field: &'static str, input: &str) -> Result<T, SdkError>;
+
+    /// Adds the operation and the argument, leaving out the input, e.g. for spending keys
+    fn secret_arg(self, operation: &'static str, field: &'static str) -> Result<T, SdkError>;
+}
+
+impl<T, E: std::error::Error + 'static> ArgContext<T> for Result<T, E> {
+    fn arg(self, operation: &'static str, field: &'static str, input: &str) -> Result<T, SdkError> {
+        self.map_err(|e| SdkError::InvalidArgument {
+            operation,
+            field,
+            input: Some(excerpt(input)),
+            source: Box::new(e),
+        })
+    }
+
+    fn secret_arg(self, operation: &'static str, field: &'static str) -> Result<T, SdkError> {
+        self.map_err(|e| SdkError::InvalidArgument {
+            operation,
+            field,
+            input: None,
+            source: Box::new(e),
+        })
+    }
+}
+
+/// Adds the operation to errors of namada_sdk
+pub trait NamadaContext<T> {
+    fn context(self, operation: &'static str) -> Result<T, SdkError>;
+}
+
+impl<T> NamadaContext<T> for Result<T, namada_sdk::error::Error> {
+    fn context(self, operation: &'static str) -> Result<T, SdkError> {
+        self.map_err(|source| SdkError::Namada { operation, source })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use namada_sdk::address::Address;
+    use std::error::Error;
+    use std::str::FromStr;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn adds_argument_context() {
+        let input = "tnam1notavalidaddressatallreally";
+        let error = Address::from_str(input)
+            .arg("bond", "source", input)
+            .unwrap_err();
+
+        let message = error.to_string();
+        assert!(message.starts_with("bond: invalid source \"tnam1notavalidaddressata...\": "));
+        assert!(error.source().is_some());
+
+        let error = Address::from_str(input)
+            .secret_arg("shielded transfer", "source")
+            .unwrap_err();
+        assert!(!error.to_string().contains(input));
+    }
+
+    #[wasm_bindgen_test]
+    fn shortens_inputs() {
+        assert_eq!(excerpt("short"), "short");
+        assert_eq!(excerpt(&"ą".repeat(30)), format!("{}...", "ą".repeat(24)));
+    }
+}
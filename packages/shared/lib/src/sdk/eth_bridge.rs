@@ -0,0 +1,407 @@
+//! Ethereum bridge helpers
+//!
+//! Transfers to Ethereum are added to the bridge pool. ERC-20 assets have to be whitelisted
+//! and wrapped NAM transfers are capped by the amount of NAM escrowed by the bridge, otherwise
+//! the tx is rejected by the validity predicates after the fees are paid. These are checked
+//! up front from the whitelist in storage.
+//!
+//! Transfers wait in the bridge pool until validators sign a merkle root including them, after
+//! which anyone can relay them to Ethereum with a proof of inclusion, see relay_proof.
+//!
+//! ERC-20 assets are minted on Namada as internal token addresses, wrapped NAM being the
+//! exception which maps back to the native token. Amounts of an asset use the denomination of
+//! its whitelist entry, see query_wrapped_asset.
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use namada_sdk::address::{Address, InternalAddress, ETH_BRIDGE};
+use namada_sdk::args::{EthereumBridgePool, InputAmount};
+use namada_sdk::borsh::{BorshDeserialize, BorshSerializeExt};
+use namada_sdk::eth_abi::Encode;
+use namada_sdk::eth_bridge::bridge_pool::{query_bridge_pool, query_signed_bridge_pool};
+use namada_sdk::eth_bridge::storage::native_erc20_key;
+use namada_sdk::eth_bridge::storage::whitelist::{Key as WhitelistKey, KeyType};
+use namada_sdk::eth_bridge_pool::{erc20_nut_address, erc20_token_address, PendingTransfer};
+use namada_sdk::ethereum_events::EthAddress;
+use namada_sdk::keccak::KeccakHash;
+use namada_sdk::queries::shell::eth_bridge::{GenBridgePoolProofReq, GenBridgePoolProofRsp};
+use namada_sdk::queries::RPC;
+use namada_sdk::rpc::{self, get_token_balance, query_native_token};
+use namada_sdk::storage::Key;
+use namada_sdk::token::{Amount, DenominatedAmount, Denomination};
+use serde::Serialize;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsError;
+
+use super::io::WebIo;
+use crate::rpc_client::HttpClient;
+
+/// Whitelist entry of an ERC-20 asset
+#[derive(Debug, Clone)]
+pub struct WhitelistEntry {
+    pub whitelisted: bool,
+    /// Maximum supply of the wrapped token on Namada, or of escrowed NAM for wrapped NAM
+    pub cap: Amount,
+    pub denom: Denomination,
+}
+
+async fn query_optional<T: BorshDeserialize>(
+    client: &HttpClient,
+    key: &Key,
+) -> Result<Option<T>, JsError> {
+    let (value, _) = rpc::query_storage_value_bytes(client, key, None, false).await?;
+    Ok(value.map(|bytes| T::try_from_slice(&bytes)).transpose()?)
+}
+
+fn whitelist_key(asset: &EthAddress, suffix: KeyType) -> Key {
+    WhitelistKey {
+        asset: *asset,
+        suffix,
+    }
+    .into()
+}
+
+/// Queries the whitelist entry of an ERC-20 asset, None if it was never whitelisted
+pub async fn query_whitelist(
+    client: &HttpClient,
+    asset: &EthAddress,
+) -> Result<Option<WhitelistEntry>, JsError> {
+    let whitelisted = query_optional::<bool>(client, &whitelist_key(asset, KeyType::Whitelisted))
+        .await?
+        .unwrap_or(false);
+    let cap = query_optional::<Amount>(client, &whitelist_key(asset, KeyType::Cap)).await?;
+    let denom =
+        query_optional::<Denomination>(client, &whitelist_key(asset, KeyType::Denomination))
+            .await?;
+
+    Ok(match (cap, denom) {
+        (Some(cap), Some(denom)) => Some(WhitelistEntry {
+            whitelisted,
+            cap,
+            denom,
+        }),
+        _ => None,
+    })
+}
+
+/// Queries the ERC-20 address of wrapped NAM
+pub async fn query_native_erc20(client: &HttpClient) -> Result<EthAddress, JsError> {
+    query_optional::<EthAddress>(client, &native_erc20_key())
+        .await?
+        .ok_or_else(|| JsError::new("Ethereum bridge is not enabled"))
+}
+
+pub fn parse_eth_address(address: &str) -> Result<EthAddress, JsError> {
+    EthAddress::from_str(address)
+        .map_err(|e| JsError::new(&format!("Invalid ERC-20 address {}: {}", address, e)))
+}
+
+/// Namada token of a bridged ERC-20 asset and its decimals
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WrappedAsset {
+    pub erc20: String,
+    /// Token address on Namada, the native token for wrapped NAM
+    pub token: String,
+    /// Address of non-usable tokens, minted instead of the token above the cap
+    pub nut: String,
+    pub decimals: u8,
+    pub whitelisted: bool,
+    pub is_native: bool,
+}
+
+/// Queries the Namada token and decimals of a bridged ERC-20 asset
+///
+/// # Errors
+///
+/// Returns JsError if the asset was never whitelisted on the bridge
+pub async fn query_wrapped_asset(
+    client: &HttpClient,
+    erc20: &EthAddress,
+) -> Result<WrappedAsset, JsError> {
+    let entry = query_whitelist(client, erc20).await?.ok_or_else(|| {
+        JsError::new(&format!(
+            "ERC-20 {} is not whitelisted on the Ethereum bridge",
+            erc20
+        ))
+    })?;
+    let is_native = *erc20 == query_native_erc20(client).await?;
+    let token = if is_native {
+        query_native_token(client).await?
+    } else {
+        erc20_token_address(erc20)
+    };
+
+    Ok(WrappedAsset {
+        erc20: erc20.to_string(),
+        token: token.to_string(),
+        nut: erc20_nut_address(erc20).to_string(),
+        decimals: entry.denom.0,
+        whitelisted: entry.whitelisted,
+        is_native,
+    })
+}
+
+/// Queries the ERC-20 asset of a Namada token, None if the token is not bridged
+pub async fn query_erc20_of_token(
+    client: &HttpClient,
+    token: &Address,
+) -> Result<Option<EthAddress>, JsError> {
+    if let Some(erc20) = erc20_of_token(token) {
+        return Ok(Some(erc20));
+    }
+    if *token == query_native_token(client).await? {
+        return Ok(query_optional::<EthAddress>(client, &native_erc20_key()).await?);
+    }
+    Ok(None)
+}
+
+/// Returns the ERC-20 asset of a wrapped token or NUT, None for other tokens
+pub fn erc20_of_token(token: &Address) -> Option<EthAddress> {
+    match token {
+        Address::Internal(InternalAddress::Erc20(erc20))
+        | Address::Internal(InternalAddress::Nut(erc20)) => Some(*erc20),
+        _ => None,
+    }
+}
+
+/// Returns the Namada address of a bridged ERC-20 asset, or of its NUT if nut is set.
+/// Wrapped NAM maps to the native token instead, see Query.query_wrapped_asset.
+#[wasm_bindgen]
+pub fn wrapped_erc20_address(erc20: &str, nut: bool) -> Result<String, JsError> {
+    let erc20 = parse_eth_address(erc20)?;
+    let token = if nut {
+        erc20_nut_address(&erc20)
+    } else {
+        erc20_token_address(&erc20)
+    };
+    Ok(token.to_string())
+}
+
+/// Returns the ERC-20 address of a wrapped token or NUT, None for other tokens
+#[wasm_bindgen]
+pub fn erc20_address(token: &str) -> Result<Option<String>, JsError> {
+    let token = Address::from_str(token)?;
+    Ok(erc20_of_token(&token).map(|erc20| erc20.to_string()))
+}
+
+fn denominated(amount: &InputAmount) -> DenominatedAmount {
+    match amount {
+        InputAmount::Validated(amount) | InputAmount::Unvalidated(amount) => *amount,
+    }
+}
+
+/// Checks that a bridge pool transfer can be accepted: the asset has to be whitelisted, and
+/// for wrapped NAM the escrowed NAM may not exceed the cap.
+///
+/// # Errors
+///
+/// Returns JsError describing why the transfer would be rejected
+pub async fn ensure_transferable(
+    client: &HttpClient,
+    args: &EthereumBridgePool,
+) -> Result<(), JsError> {
+    let entry = query_whitelist(client, &args.asset).await?;
+    let entry = match entry {
+        Some(entry) if entry.whitelisted => entry,
+        _ => {
+            return Err(JsError::new(&format!(
+                "ERC-20 {} is not whitelisted on the Ethereum bridge",
+                args.asset
+            )))
+        }
+    };
+
+    if args.nut || args.asset != query_native_erc20(client).await? {
+        return Ok(());
+    }
+
+    let native_token: Address = query_native_token(client).await?;
+    let escrowed = get_token_balance(client, &native_token, &ETH_BRIDGE, None).await?;
+    let amount = denominated(&args.amount)
+        .increase_precision(entry.denom)
+        .map_err(|e| JsError::new(&format!("Invalid amount: {}", e)))?
+        .amount();
+    let headroom = entry.cap.checked_sub(escrowed).unwrap_or_default();
+    if amount > headroom {
+        return Err(JsError::new(&format!(
+            "Transfer exceeds the wrapped NAM cap, at most {} NAM can be transferred",
+            DenominatedAmount::new(headroom, entry.denom)
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BridgePoolTransferStatus {
+    /// Waiting in the bridge pool for validators to sign a root including it
+    Pending,
+    /// Included in a signed root, it can be relayed with relay_proof
+    Signed,
+    /// Relayed to Ethereum, waiting for validators to confirm it
+    Relayed,
+    /// Not in the bridge pool, i.e. completed or expired and refunded
+    NotFound,
+}
+
+/// Transfer in the bridge pool with its status
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgePoolTransfer {
+    /// Keccak hash of the pending transfer, used to request its proof
+    pub hash: String,
+    pub asset: String,
+    pub recipient: String,
+    pub sender: String,
+    pub amount: String,
+    pub fee_token: String,
+    pub fee_amount: String,
+    pub fee_payer: String,
+    pub status: BridgePoolTransferStatus,
+}
+
+fn bridge_pool_transfer(
+    hash: String,
+    pending: &PendingTransfer,
+    status: BridgePoolTransferStatus,
+) -> BridgePoolTransfer {
+    BridgePoolTransfer {
+        hash,
+        asset: pending.transfer.asset.to_string(),
+        recipient: pending.transfer.recipient.to_string(),
+        sender: pending.transfer.sender.to_string(),
+        amount: pending.transfer.amount.to_string(),
+        fee_token: pending.gas_fee.token.to_string(),
+        fee_amount: pending.gas_fee.amount.to_string(),
+        fee_payer: pending.gas_fee.payer.to_string(),
+        status,
+    }
+}
+
+/// Returns the transfers in the bridge pool sent by any of the senders, with their status
+pub async fn bridge_pool_transfers(
+    client: &HttpClient,
+    senders: &[Address],
+) -> Result<Vec<BridgePoolTransfer>, JsError> {
+    let pool = query_bridge_pool(client, &WebIo).await?;
+    let signed = query_signed_bridge_pool(client, &WebIo).await?;
+    let relayed: Vec<String> = RPC
+        .shell()
+        .eth_bridge()
+        .transfer_to_ethereum_progress(client)
+        .await?
+        .keys()
+        .map(|pending| pending.keccak256().to_string())
+        .collect();
+
+    Ok(pool
+        .into_iter()
+        .filter(|(_, pending)| senders.contains(&pending.transfer.sender))
+        .map(|(hash, pending)| {
+            let status = if relayed.contains(&hash) {
+                BridgePoolTransferStatus::Relayed
+            } else if signed.contains_key(&hash) {
+                BridgePoolTransferStatus::Signed
+            } else {
+                BridgePoolTransferStatus::Pending
+            };
+            bridge_pool_transfer(hash, &pending, status)
+        })
+        .collect())
+}
+
+/// Returns the status of a bridge pool transfer by its keccak hash. Relayed transfers stay in
+/// the signed bridge pool until validators confirm them on Ethereum, so they are looked up
+/// first, then the signed pool, then the whole pool.
+pub async fn transfer_status(
+    client: &HttpClient,
+    hash: &str,
+) -> Result<BridgePoolTransferStatus, JsError> {
+    let hash = KeccakHash::try_from(hash)
+        .map_err(|e| JsError::new(&format!("Invalid transfer hash: {}", e)))?;
+    let key = hash.to_string();
+
+    let in_progress = RPC
+        .shell()
+        .eth_bridge()
+        .transfer_to_ethereum_progress(client)
+        .await?;
+    if in_progress
+        .keys()
+        .any(|pending| pending.keccak256() == hash)
+    {
+        return Ok(BridgePoolTransferStatus::Relayed);
+    }
+    let signed = query_signed_bridge_pool(client, &WebIo).await?;
+    if signed.contains_key(&key) {
+        return Ok(BridgePoolTransferStatus::Signed);
+    }
+    let pool = query_bridge_pool(client, &WebIo).await?;
+    if pool.contains_key(&key) {
+        return Ok(BridgePoolTransferStatus::Pending);
+    }
+    Ok(BridgePoolTransferStatus::NotFound)
+}
+
+/// Returns the ABI encoded arguments of `transferToErc` of the Ethereum bridge contract, i.e.
+/// the validator set, signatures and proof of inclusion of signed transfers, so they can be
+/// relayed by anyone.
+///
+/// # Arguments
+///
+/// * `hashes` - keccak hashes of signed transfers
+/// * `relayer` - address receiving the fees of the transfers
+///
+/// # Errors
+///
+/// Returns JsError if any of the transfers is not in the signed bridge pool
+pub async fn relay_proof(
+    client: &HttpClient,
+    hashes: &[String],
+    relayer: Address,
+) -> Result<Vec<u8>, JsError> {
+    let transfers = hashes
+        .iter()
+        .map(|hash| KeccakHash::try_from(hash.as_str()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| JsError::new(&format!("Invalid transfer hash: {}", e)))?;
+
+    let request = GenBridgePoolProofReq {
+        transfers: Cow::Owned(transfers),
+        relayer: Cow::Owned(relayer),
+        with_appendix: false,
+    };
+    let response = RPC
+        .shell()
+        .eth_bridge()
+        .generate_bridge_pool_proof(client, Some(request.serialize_to_vec()), None, false)
+        .await?;
+    let proof = GenBridgePoolProofRsp::try_from_slice(&response.data)?;
+
+    Ok(proof.abi_encoded_args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const ERC20: &str = "0x6b175474e89094c44da98b954eedeac495271d0f";
+
+    #[wasm_bindgen_test]
+    fn maps_wrapped_tokens_to_erc20() {
+        let token = wrapped_erc20_address(ERC20, false).unwrap();
+        let nut = wrapped_erc20_address(ERC20, true).unwrap();
+        assert_ne!(token, nut);
+
+        let erc20 = parse_eth_address(ERC20).unwrap().to_string();
+        assert_eq!(erc20_address(&token).unwrap(), Some(erc20.clone()));
+        assert_eq!(erc20_address(&nut).unwrap(), Some(erc20));
+        assert_eq!(
+            erc20_address("tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e").unwrap(),
+            None
+        );
+        assert!(wrapped_erc20_address("0x01", false).is_err());
+    }
+}
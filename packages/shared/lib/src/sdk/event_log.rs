@@ -0,0 +1,192 @@
+//! Event log queries
+//!
+//! Events emitted by the node, e.g. applied txs, IBC packets and governance outcomes, are
+//! read from the block results of a range of blocks and matched against a filter, so all
+//! kinds of events can be fetched with one API.
+use std::collections::BTreeMap;
+
+use namada_sdk::io::Client;
+use namada_sdk::tendermint::{self, abci::Event};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsError;
+
+use crate::rpc_client::HttpClient;
+
+/// Maximum number of blocks scanned by a single query
+pub const MAX_EVENT_QUERY_BLOCKS: u64 = 100;
+
+/// Matcher of events, all of the provided fields have to match
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventFilter {
+    pub from_height: u64,
+    pub to_height: u64,
+    /// Event type or its prefix, e.g. "tx/applied" or "ibc"
+    pub event_type: Option<String>,
+    /// Hash in any of the hash attributes, e.g. wrapper or inner tx hash
+    pub tx_hash: Option<String>,
+    /// Address in any of the attribute values
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggedEvent {
+    pub height: u64,
+    pub event_type: String,
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl EventFilter {
+    fn validate(&self) -> Result<(), JsError> {
+        if self.from_height > self.to_height {
+            return Err(JsError::new("fromHeight must not be greater than toHeight"));
+        }
+        if self.to_height - self.from_height >= MAX_EVENT_QUERY_BLOCKS {
+            return Err(JsError::new(&format!(
+                "Can't query events of more than {} blocks at once",
+                MAX_EVENT_QUERY_BLOCKS
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, event: &LoggedEvent) -> bool {
+        let event_type = self.event_type.as_ref().map_or(true, |event_type| {
+            event.event_type.starts_with(event_type.as_str())
+        });
+        let tx_hash = self.tx_hash.as_ref().map_or(true, |hash| {
+            event.attributes.iter().any(|(key, value)| {
+                key.to_lowercase().contains("hash") && value.eq_ignore_ascii_case(hash)
+            })
+        });
+        let address = self.address.as_ref().map_or(true, |address| {
+            event
+                .attributes
+                .values()
+                .any(|value| value.contains(address.as_str()))
+        });
+
+        event_type && tx_hash && address
+    }
+}
+
+pub(crate) fn logged_event(height: u64, event: &Event) -> LoggedEvent {
+    let attributes = event
+        .attributes
+        .iter()
+        .filter_map(|attr| {
+            let key = attr.key_str().ok()?;
+            let value = attr.value_str().ok()?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect();
+
+    LoggedEvent {
+        height,
+        event_type: event.kind.clone(),
+        attributes,
+    }
+}
+
+/// Returns events of the blocks within the inclusive height range of the filter, in the
+/// order they were emitted.
+///
+/// # Errors
+///
+/// Returns JsError if the range is invalid or any of the block results can't be fetched
+pub async fn query_events(
+    client: &HttpClient,
+    filter: &EventFilter,
+) -> Result<Vec<LoggedEvent>, JsError> {
+    filter.validate()?;
+    let mut events: Vec<LoggedEvent> = vec![];
+
+    for height in filter.from_height..=filter.to_height {
+        let tm_height = tendermint::block::Height::try_from(height)?;
+        let block_results = client.block_results(tm_height).await?;
+
+        let tx_events = block_results
+            .txs_results
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|result| result.events);
+        let block_events = block_results
+            .begin_block_events
+            .unwrap_or_default()
+            .into_iter()
+            .chain(tx_events)
+            .chain(block_results.end_block_events.unwrap_or_default())
+            .chain(block_results.finalize_block_events);
+
+        events.extend(
+            block_events
+                .map(|event| logged_event(height, &event))
+                .filter(|event| filter.matches(event)),
+        );
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn event(event_type: &str, attributes: &[(&str, &str)]) -> LoggedEvent {
+        LoggedEvent {
+            height: 1,
+            event_type: event_type.to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn matches_events() {
+        let applied = event(
+            "tx/applied",
+            &[
+                ("hash", "ABCD"),
+                ("batch", "tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e"),
+            ],
+        );
+        let filter =
+            |event_type: Option<&str>, tx_hash: Option<&str>, address: Option<&str>| EventFilter {
+                event_type: event_type.map(String::from),
+                tx_hash: tx_hash.map(String::from),
+                address: address.map(String::from),
+                ..EventFilter::default()
+            };
+
+        assert!(filter(None, None, None).matches(&applied));
+        assert!(filter(Some("tx"), Some("abcd"), None).matches(&applied));
+        assert!(!filter(Some("ibc"), None, None).matches(&applied));
+        assert!(!filter(None, Some("ABCE"), None).matches(&applied));
+        assert!(filter(
+            None,
+            None,
+            Some("tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e")
+        )
+        .matches(&applied));
+    }
+
+    #[wasm_bindgen_test]
+    fn limits_height_range() {
+        let filter = EventFilter {
+            from_height: 1,
+            to_height: MAX_EVENT_QUERY_BLOCKS,
+            ..EventFilter::default()
+        };
+        assert!(filter.validate().is_ok());
+
+        let filter = EventFilter {
+            to_height: MAX_EVENT_QUERY_BLOCKS + 1,
+            ..filter
+        };
+        assert!(filter.validate().is_err());
+    }
+}
@@ -44,10 +44,25 @@ impl ProgressIncrement {
     }
 }
 
+#[wasm_bindgen]
+#[derive(Debug, Serialize)]
+pub struct SchedulerChanged {
+    id: String,
+    changes: String,
+}
+
+impl SchedulerChanged {
+    pub fn to_json(&self) -> JsValue {
+        let json = serde_json::to_value(&self).unwrap();
+        JsValue::from_str(&json.to_string())
+    }
+}
+
 /// Event constants for use in Rust
 pub const SDK_EVENT_PROGRESS_BAR_STARTED: &str = "namada_sdk::progress_bar::started";
 pub const SDK_EVENT_PROGRESS_BAR_INCREMENTED: &str = "namada_sdk::progress_bar::incremented";
 pub const SDK_EVENT_PROGRESS_BAR_FINISHED: &str = "namada_sdk::progress_bar::finished";
+pub const SDK_EVENT_SCHEDULER_CHANGED: &str = "namada_sdk::scheduler::changed";
 
 // This will generate proper enum in TypeScript, the downisde is that we need to copy the values.
 // Unfortunately we can't use macros here.
@@ -57,6 +72,7 @@ export enum SdkEvents {
     ProgressBarStarted = "namada_sdk::progress_bar::started",
     ProgressBarIncremented = "namada_sdk::progress_bar::incremented",
     ProgressBarFinished = "namada_sdk::progress_bar::finished",
+    SchedulerChanged = "namada_sdk::scheduler::changed",
 }
 "#;
 
@@ -82,6 +98,12 @@ impl SdkEvents {
     pub fn ProgressBarFinished() -> String {
         SDK_EVENT_PROGRESS_BAR_FINISHED.to_string()
     }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(getter)]
+    pub fn SchedulerChanged() -> String {
+        SDK_EVENT_SCHEDULER_CHANGED.to_string()
+    }
 }
 
 pub struct EventDispatcher {
@@ -148,4 +170,15 @@ impl EventDispatcher {
 
         self.dispatch_custom_event(event)
     }
+
+    pub fn scheduler_changed(&self, id: String, changes: String) -> Result<JsValue, JsError> {
+        let changed = SchedulerChanged { id, changes };
+        let mut options = CustomEventInit::new();
+        options.detail(&changed.to_json());
+
+        let event =
+            CustomEvent::new_with_event_init_dict(SDK_EVENT_SCHEDULER_CHANGED, &options).unwrap();
+
+        self.dispatch_custom_event(event)
+    }
 }
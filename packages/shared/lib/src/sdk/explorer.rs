@@ -0,0 +1,107 @@
+//! Block and transaction explorer primitives
+//!
+//! Blocks and txs are returned with raw tx bytes, which the frontend decodes with
+//! deserialize_tx, so a lightweight explorer view doesn't need a third-party indexer.
+use std::str::FromStr;
+
+use namada_sdk::borsh::BorshDeserialize;
+use namada_sdk::hash::Hash;
+use namada_sdk::io::Client;
+use namada_sdk::tendermint;
+use namada_sdk::tendermint_rpc::endpoint::tx as tm_tx;
+use namada_sdk::tx::Tx;
+use serde::Serialize;
+use wasm_bindgen::JsError;
+
+use crate::rpc_client::HttpClient;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTx {
+    /// CometBFT tx hash, i.e. sha256 of the tx bytes
+    pub hash: String,
+    /// Namada wrapper hash, for wrapper txs only
+    pub wrapper_hash: Option<String>,
+    pub height: u64,
+    pub index: u32,
+    pub applied: bool,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockInfo {
+    pub height: u64,
+    pub hash: String,
+    /// Block time in RFC 3339
+    pub time: String,
+    pub proposer: String,
+    pub txs: Vec<BlockTx>,
+}
+
+fn wrapper_hash(bytes: &[u8]) -> Option<String> {
+    Tx::try_from_slice(bytes)
+        .ok()
+        .and_then(|tx| tx.wrapper_hash())
+        .map(|hash| hash.to_string())
+}
+
+/// Returns the block at a height with all of its txs.
+///
+/// # Errors
+///
+/// Returns JsError if the block or its results can't be fetched
+pub async fn block_by_height(client: &HttpClient, height: u64) -> Result<BlockInfo, JsError> {
+    let tm_height = tendermint::block::Height::try_from(height)?;
+    let block = client.block(tm_height).await?;
+    let block_results = client.block_results(tm_height).await?;
+    let tx_results = block_results.txs_results.unwrap_or_default();
+
+    let txs = block
+        .block
+        .data
+        .iter()
+        .enumerate()
+        .map(|(index, bytes)| BlockTx {
+            hash: Hash::sha256(bytes).to_string(),
+            wrapper_hash: wrapper_hash(bytes),
+            height,
+            index: index as u32,
+            applied: tx_results
+                .get(index)
+                .map(|res| res.code.is_ok())
+                .unwrap_or(false),
+            bytes: bytes.clone(),
+        })
+        .collect();
+
+    Ok(BlockInfo {
+        height,
+        hash: block.block_id.hash.to_string(),
+        time: block.block.header.time.to_rfc3339(),
+        proposer: block.block.header.proposer_address.to_string(),
+        txs,
+    })
+}
+
+/// Returns a tx included in a block by its CometBFT hash, see BlockTx::hash.
+///
+/// # Errors
+///
+/// Returns JsError if the hash is invalid or the tx is not found
+pub async fn tx_by_hash(client: &HttpClient, hash: &str) -> Result<BlockTx, JsError> {
+    let hash = tendermint::Hash::from_str(&hash.to_uppercase())?;
+    let response = client
+        .perform(tm_tx::Request::new(hash, false))
+        .await
+        .map_err(|e| JsError::new(&format!("Tx {} not found: {}", hash, e)))?;
+
+    Ok(BlockTx {
+        hash: response.hash.to_string(),
+        wrapper_hash: wrapper_hash(&response.tx),
+        height: response.height.value(),
+        index: response.index,
+        applied: response.tx_result.code.is_ok(),
+        bytes: response.tx,
+    })
+}
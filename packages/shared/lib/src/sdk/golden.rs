@@ -0,0 +1,91 @@
+//! Compatibility suite against txs dumped by the Namada CLI
+//!
+//! fixtures/golden_txs.json holds txs built by `namadac --dump-tx` at the pinned namada_sdk
+//! version, see scripts/golden-fixtures.js. Decoding, re-encoding and hashing them with the
+//! namada_sdk used by this crate has to reproduce the CLI results, so incompatibilities show
+//! up as soon as namada_sdk is bumped.
+use namada_sdk::borsh::{self, BorshDeserialize};
+use namada_sdk::tx::Tx;
+use serde::Deserialize;
+use wasm_bindgen_test::*;
+
+use super::audit_log::tx_types;
+
+const GOLDEN_TXS: &str = include_str!("../../fixtures/golden_txs.json");
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoldenTxs {
+    namada_version: String,
+    txs: Vec<GoldenTx>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoldenTx {
+    name: String,
+    /// Tx as dumped by namadac
+    tx: serde_json::Value,
+    /// Header hash, taken from the name of the dumped file
+    header_hash: String,
+    /// Code tags of the inner txs
+    code_tags: Vec<String>,
+}
+
+fn golden_txs() -> GoldenTxs {
+    serde_json::from_str(GOLDEN_TXS).expect("golden_txs.json to be valid")
+}
+
+#[wasm_bindgen_test]
+fn golden_txs_match_pinned_sdk_version() {
+    let golden = golden_txs();
+    let cargo_toml = include_str!("../../Cargo.toml");
+    let pinned = cargo_toml
+        .lines()
+        .find(|line| line.starts_with("namada_sdk"))
+        .expect("namada_sdk dependency");
+
+    assert!(
+        pinned.contains(&format!("\"{}\"", golden.namada_version)),
+        "Golden txs were dumped with namada {}, regenerate them for: {}",
+        golden.namada_version,
+        pinned
+    );
+}
+
+#[wasm_bindgen_test]
+fn golden_txs_round_trip() {
+    let golden_txs = golden_txs().txs;
+    assert!(
+        !golden_txs.is_empty(),
+        "No golden txs, generate them with scripts/golden-fixtures.js"
+    );
+
+    for golden in golden_txs {
+        let tx: Tx = serde_json::from_value(golden.tx)
+            .unwrap_or_else(|e| panic!("{}: can't decode the dumped tx: {}", golden.name, e));
+
+        let bytes = borsh::to_vec(&tx).unwrap();
+        let decoded = Tx::try_from_slice(&bytes)
+            .unwrap_or_else(|e| panic!("{}: can't decode the tx bytes: {}", golden.name, e));
+        assert_eq!(
+            borsh::to_vec(&decoded).unwrap(),
+            bytes,
+            "{}: borsh round trip",
+            golden.name
+        );
+
+        assert_eq!(
+            tx.header_hash().to_string().to_lowercase(),
+            golden.header_hash.to_lowercase(),
+            "{}: header hash",
+            golden.name
+        );
+        assert_eq!(
+            tx_types(&tx),
+            golden.code_tags,
+            "{}: code tags",
+            golden.name
+        );
+    }
+}
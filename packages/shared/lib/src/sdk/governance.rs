@@ -0,0 +1,417 @@
+//! Tracking of governance proposal deposits and PGF funding
+//!
+//! Authors lock a deposit in the governance account when submitting a proposal. It stays
+//! locked until the tally at the activation epoch, after which it is refunded to the author,
+//! unless the proposal is rejected by two thirds of nay votes over two thirds of the voting
+//! power, in which case it is burned. The status is derived from the tally result stored on
+//! chain, so authors can follow their NAM without recomputing votes.
+//!
+//! Passed PGF proposals add continuous fundings, paid to their target every epoch until
+//! removed, and stewards, who may share their rewards with other addresses by commission.
+use namada_sdk::address::Address;
+use namada_sdk::borsh::BorshDeserialize;
+use namada_sdk::dec::Dec;
+use namada_sdk::governance::pgf::storage::steward::StewardDetail;
+use namada_sdk::governance::storage::keys as governance_storage;
+use namada_sdk::governance::storage::proposal::{PGFTarget, StoragePgfFunding};
+use namada_sdk::governance::utils::{ProposalResult, TallyResult};
+use namada_sdk::proof_of_stake::Epoch;
+use namada_sdk::rpc::{query_epoch, query_pgf_fundings, query_pgf_stewards, query_storage_value};
+use namada_sdk::state::Key;
+use namada_sdk::token;
+use serde::Serialize;
+use wasm_bindgen::JsError;
+
+use crate::query::storage_values;
+use crate::rpc_client::HttpClient;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DepositStatus {
+    /// Tally pending, the deposit is held by the governance account
+    Locked,
+    Refunded,
+    Burned,
+    /// Past the activation epoch without a stored tally result
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalDeposit {
+    pub proposal_id: u64,
+    pub author: String,
+    /// Native amount of the deposit
+    pub amount: String,
+    pub activation_epoch: u64,
+    pub status: DepositStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorDeposits {
+    pub deposits: Vec<ProposalDeposit>,
+    /// Native amounts by status
+    pub locked: String,
+    pub refunded: String,
+    pub burned: String,
+}
+
+/// Continuous PGF funding, paid every epoch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PgfFunding {
+    pub id: u64,
+    /// Namada address, or address on the counterparty chain of IBC fundings
+    pub target: String,
+    /// Native amount paid per epoch
+    pub amount_per_epoch: String,
+    /// IBC port and channel, for fundings to other chains
+    pub ibc: Option<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StewardCommission {
+    pub address: String,
+    /// Share of the steward rewards, from 0 to 1
+    pub share: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PgfSteward {
+    pub address: String,
+    pub commissions: Vec<StewardCommission>,
+}
+
+impl From<StoragePgfFunding> for PgfFunding {
+    fn from(funding: StoragePgfFunding) -> PgfFunding {
+        let (target, amount, ibc) = match funding.detail {
+            PGFTarget::Internal(internal) => (internal.target.to_string(), internal.amount, None),
+            PGFTarget::Ibc(ibc) => (
+                ibc.target,
+                ibc.amount,
+                Some((ibc.port_id.to_string(), ibc.channel_id.to_string())),
+            ),
+        };
+        PgfFunding {
+            id: funding.id,
+            target,
+            amount_per_epoch: amount.to_string_native(),
+            ibc,
+        }
+    }
+}
+
+impl From<StewardDetail> for PgfSteward {
+    fn from(steward: StewardDetail) -> PgfSteward {
+        let mut commissions: Vec<(Address, Dec)> =
+            steward.reward_distribution.into_iter().collect();
+        // Largest shares first, stable across queries
+        commissions.sort_by(|(a, a_share), (b, b_share)| b_share.cmp(a_share).then(a.cmp(b)));
+        PgfSteward {
+            address: steward.address.to_string(),
+            commissions: commissions
+                .into_iter()
+                .map(|(address, share)| StewardCommission {
+                    address: address.to_string(),
+                    share: share.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Returns the active continuous PGF fundings, by id
+///
+/// # Errors
+///
+/// Returns JsError if the fundings can't be queried
+pub async fn pgf_fundings(client: &HttpClient) -> Result<Vec<PgfFunding>, JsError> {
+    let mut fundings: Vec<PgfFunding> = query_pgf_fundings(client)
+        .await?
+        .into_iter()
+        .map(PgfFunding::from)
+        .collect();
+    fundings.sort_by_key(|funding| funding.id);
+    Ok(fundings)
+}
+
+/// Returns the PGF stewards with their commissions
+///
+/// # Errors
+///
+/// Returns JsError if the stewards can't be queried
+pub async fn pgf_stewards(client: &HttpClient) -> Result<Vec<PgfSteward>, JsError> {
+    Ok(query_pgf_stewards(client)
+        .await?
+        .into_iter()
+        .map(PgfSteward::from)
+        .collect())
+}
+
+/// Returns the status of a deposit from the tally result of its proposal
+fn deposit_status(
+    result: Option<&ProposalResult>,
+    current_epoch: Epoch,
+    activation_epoch: Epoch,
+) -> DepositStatus {
+    match result {
+        Some(result) => match result.result {
+            TallyResult::Rejected if result.two_thirds_nay_over_two_thirds_total() => {
+                DepositStatus::Burned
+            }
+            _ => DepositStatus::Refunded,
+        },
+        None if current_epoch < activation_epoch => DepositStatus::Locked,
+        None => DepositStatus::Unknown,
+    }
+}
+
+/// Sums the deposits by status, returning locked, refunded and burned amounts
+fn totals(
+    deposits: &[ProposalDeposit],
+) -> Result<(token::Amount, token::Amount, token::Amount), JsError> {
+    let mut locked = token::Amount::zero();
+    let mut refunded = token::Amount::zero();
+    let mut burned = token::Amount::zero();
+    for deposit in deposits {
+        let amount = token::Amount::from_str(&deposit.amount, token::NATIVE_MAX_DECIMAL_PLACES)?;
+        let total = match deposit.status {
+            DepositStatus::Locked => &mut locked,
+            DepositStatus::Refunded => &mut refunded,
+            DepositStatus::Burned => &mut burned,
+            DepositStatus::Unknown => continue,
+        };
+        *total = total
+            .checked_add(amount)
+            .ok_or_else(|| JsError::new("Deposit total overflow"))?;
+    }
+    Ok((locked, refunded, burned))
+}
+
+/// Storage keys of the author, activation epoch, funds and tally result of a proposal, read
+/// by deposit
+fn deposit_keys(proposal_id: u64) -> [Key; 4] {
+    [
+        governance_storage::get_author_key(proposal_id),
+        governance_storage::get_activation_epoch_key(proposal_id),
+        governance_storage::get_funds_key(proposal_id),
+        governance_storage::get_proposal_result_key(proposal_id),
+    ]
+}
+
+fn decode<T: BorshDeserialize>(value: &Option<Vec<u8>>) -> Result<Option<T>, JsError> {
+    Ok(value.as_deref().map(T::try_from_slice).transpose()?)
+}
+
+/// Returns the deposit of a proposal from the values of its deposit_keys, None if there is no
+/// such proposal
+fn deposit(
+    proposal_id: u64,
+    values: &[Option<Vec<u8>>],
+    current_epoch: Epoch,
+) -> Result<Option<ProposalDeposit>, JsError> {
+    let [author, activation_epoch, funds, result] = values else {
+        return Err(JsError::new("Expected 4 deposit values"));
+    };
+    let Some(author) = decode::<Address>(author)? else {
+        return Ok(None);
+    };
+    let missing = |field: &str| JsError::new(&format!("Proposal {} has no {}", proposal_id, field));
+    let activation_epoch =
+        decode::<Epoch>(activation_epoch)?.ok_or_else(|| missing("activation epoch"))?;
+    let amount = decode::<token::Amount>(funds)?.ok_or_else(|| missing("deposit"))?;
+    let result = decode::<ProposalResult>(result)?;
+
+    Ok(Some(ProposalDeposit {
+        proposal_id,
+        author: author.to_string(),
+        amount: amount.to_string_native(),
+        activation_epoch: activation_epoch.0,
+        status: deposit_status(result.as_ref(), current_epoch, activation_epoch),
+    }))
+}
+
+/// Returns the deposit of a proposal, None if there is no such proposal
+///
+/// # Errors
+///
+/// Returns JsError if the proposal or the epoch can't be queried or decoded
+pub async fn proposal_deposit(
+    client: &HttpClient,
+    proposal_id: u64,
+) -> Result<Option<ProposalDeposit>, JsError> {
+    let current_epoch = query_epoch(client).await?;
+    let values = storage_values(client, &deposit_keys(proposal_id)).await?;
+    deposit(proposal_id, &values, current_epoch)
+}
+
+/// Returns the deposits of all proposals of an author, with totals by status. The storage
+/// values of all proposals are queried in batches, see query::storage_values.
+///
+/// # Errors
+///
+/// Returns JsError if the proposals or the epoch can't be queried or decoded
+pub async fn author_deposits(
+    client: &HttpClient,
+    author: &Address,
+) -> Result<AuthorDeposits, JsError> {
+    let current_epoch = query_epoch(client).await?;
+    let counter_key = governance_storage::get_counter_key();
+    let counter = query_storage_value::<HttpClient, u64>(client, &counter_key).await?;
+
+    let keys: Vec<Key> = (0..counter).flat_map(deposit_keys).collect();
+    let values = storage_values(client, &keys).await?;
+
+    let mut deposits = vec![];
+    for (proposal_id, values) in (0..counter).zip(values.chunks(4)) {
+        match deposit(proposal_id, values, current_epoch)? {
+            Some(deposit) if deposit.author == author.to_string() => deposits.push(deposit),
+            _ => {}
+        }
+    }
+    let (locked, refunded, burned) = totals(&deposits)?;
+
+    Ok(AuthorDeposits {
+        deposits,
+        locked: locked.to_string_native(),
+        refunded: refunded.to_string_native(),
+        burned: burned.to_string_native(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use namada_sdk::collections::HashMap;
+    use namada_sdk::governance::storage::proposal::PGFInternalTarget;
+    use namada_sdk::governance::utils::TallyType;
+    use std::str::FromStr;
+    use wasm_bindgen_test::*;
+
+    fn result(result: TallyResult, nay: u64) -> ProposalResult {
+        ProposalResult {
+            result,
+            tally_type: TallyType::TwoFifths,
+            total_voting_power: token::Amount::from_u64(90),
+            total_yay_power: token::Amount::zero(),
+            total_nay_power: token::Amount::from_u64(nay),
+            total_abstain_power: token::Amount::zero(),
+        }
+    }
+
+    fn entry(amount: &str, status: DepositStatus) -> ProposalDeposit {
+        ProposalDeposit {
+            proposal_id: 0,
+            author: "tnam1qz4sdx5jlh909j44uz46pl6f3d2ftsvkmcml0yuf".to_string(),
+            amount: amount.to_string(),
+            activation_epoch: 10,
+            status,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn derives_deposit_status() {
+        let (before, after) = (Epoch(5), Epoch(12));
+        assert_eq!(
+            deposit_status(None, before, Epoch(10)),
+            DepositStatus::Locked
+        );
+        assert_eq!(
+            deposit_status(None, after, Epoch(10)),
+            DepositStatus::Unknown
+        );
+
+        let passed = result(TallyResult::Passed, 0);
+        assert_eq!(
+            deposit_status(Some(&passed), after, Epoch(10)),
+            DepositStatus::Refunded
+        );
+        let rejected = result(TallyResult::Rejected, 30);
+        assert_eq!(
+            deposit_status(Some(&rejected), after, Epoch(10)),
+            DepositStatus::Refunded
+        );
+        let vetoed = result(TallyResult::Rejected, 90);
+        assert_eq!(
+            deposit_status(Some(&vetoed), after, Epoch(10)),
+            DepositStatus::Burned
+        );
+
+        let (locked, refunded, burned) = totals(&[
+            entry("50", DepositStatus::Locked),
+            entry("50", DepositStatus::Refunded),
+            entry("25.5", DepositStatus::Refunded),
+            entry("10", DepositStatus::Unknown),
+        ])
+        .unwrap();
+        assert_eq!(locked.to_string_native(), "50.000000");
+        assert_eq!(refunded.to_string_native(), "75.500000");
+        assert!(burned.is_zero());
+    }
+
+    #[wasm_bindgen_test]
+    fn decodes_deposit_values() {
+        let author = Address::from_str("tnam1qz4sdx5jlh909j44uz46pl6f3d2ftsvkmcml0yuf").unwrap();
+        let values = [
+            Some(borsh::to_vec(&author).unwrap()),
+            Some(borsh::to_vec(&Epoch(10)).unwrap()),
+            Some(borsh::to_vec(&token::Amount::native_whole(50)).unwrap()),
+            Some(borsh::to_vec(&result(TallyResult::Rejected, 90)).unwrap()),
+        ];
+        let proposal = deposit(3, &values, Epoch(12)).unwrap().unwrap();
+        assert_eq!(proposal.author, author.to_string());
+        assert_eq!(proposal.amount, "50.000000");
+        assert_eq!(proposal.status, DepositStatus::Burned);
+
+        // No author, no proposal
+        assert!(deposit(3, &[None, None, None, None], Epoch(12))
+            .unwrap()
+            .is_none());
+        // Undecodable and missing values of a proposal are errors
+        let mut corrupt = values.clone();
+        corrupt[2] = Some(vec![1]);
+        assert!(deposit(3, &corrupt, Epoch(12)).is_err());
+        let mut missing = values;
+        missing[1] = None;
+        assert!(deposit(3, &missing, Epoch(12)).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn converts_pgf_fundings_and_stewards() {
+        let target = Address::from_str("tnam1qz4sdx5jlh909j44uz46pl6f3d2ftsvkmcml0yuf").unwrap();
+        let funding = PgfFunding::from(StoragePgfFunding {
+            detail: PGFTarget::Internal(PGFInternalTarget {
+                target: target.clone(),
+                amount: token::Amount::native_whole(10),
+            }),
+            id: 3,
+        });
+        assert_eq!(funding.target, target.to_string());
+        assert_eq!(funding.amount_per_epoch, "10.000000");
+        assert_eq!(funding.ibc, None);
+
+        let other = Address::from_str("tnam1qpgk927uh2aqjs92yhycsh08n5yggvltn5nk92zp").unwrap();
+        let steward = PgfSteward::from(StewardDetail {
+            address: target.clone(),
+            reward_distribution: HashMap::from_iter([
+                (target.clone(), Dec::from_str("0.25").unwrap()),
+                (other.clone(), Dec::from_str("0.75").unwrap()),
+            ]),
+        });
+        let shares: Vec<_> = steward
+            .commissions
+            .iter()
+            .map(|c| (c.address.clone(), c.share.clone()))
+            .collect();
+        assert_eq!(
+            shares,
+            [
+                (other.to_string(), "0.75".to_string()),
+                (target.to_string(), "0.25".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,451 @@
+//! IBC helpers
+//!
+//! Utilities for working with IBC transfers initiated on counterparty chains, e.g. from Keplr,
+//! and for inspecting the light clients of counterparty chains before building transfers.
+use namada_sdk::address::MASP;
+use namada_sdk::borsh::BorshDeserialize;
+#[cfg(feature = "web")]
+use namada_sdk::ibc::clients::tendermint::types::{
+    ClientState as TmClientState, ConsensusState as TmConsensusState,
+};
+#[cfg(feature = "web")]
+use namada_sdk::ibc::core::channel::types::channel::ChannelEnd;
+#[cfg(feature = "web")]
+use namada_sdk::ibc::core::connection::types::ConnectionEnd;
+#[cfg(feature = "web")]
+use namada_sdk::ibc::core::host::types::identifiers::{ChannelId, ClientId, PortId};
+#[cfg(feature = "web")]
+use namada_sdk::ibc::primitives::proto::{Any, Protobuf};
+#[cfg(feature = "web")]
+use namada_sdk::ibc::storage::{
+    channel_key, client_state_key, connection_key, consensus_state_key,
+};
+use namada_sdk::ibc::IbcShieldingData;
+#[cfg(feature = "web")]
+use namada_sdk::rpc;
+#[cfg(feature = "web")]
+use namada_sdk::storage::Key;
+use namada_sdk::token::Amount;
+#[cfg(feature = "web")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "web")]
+use serde_json::Value;
+
+use super::error::JsError;
+#[cfg(feature = "web")]
+use crate::rpc_client::HttpClient;
+
+#[cfg(feature = "web")]
+pub const MSG_TRANSFER_AMINO_TYPE: &str = "cosmos-sdk/MsgTransfer";
+#[cfg(feature = "web")]
+pub const MSG_TRANSFER_TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransfer";
+
+/// Timeout of IBC transfers built without timeouts, in seconds
+#[cfg(feature = "web")]
+pub const DEFAULT_TIMEOUT_SEC_OFFSET: u64 = 3600;
+
+/// MsgTransfer as found in amino JSON or cosmjs EncodeObject SignDocs
+#[cfg(feature = "web")]
+#[derive(Debug, Deserialize)]
+pub struct CosmosMsgTransfer {
+    #[serde(alias = "sourcePort")]
+    pub source_port: String,
+    #[serde(alias = "sourceChannel")]
+    pub source_channel: String,
+    pub token: CosmosCoin,
+    pub sender: String,
+    pub receiver: String,
+}
+
+#[cfg(feature = "web")]
+#[derive(Debug, Deserialize)]
+pub struct CosmosCoin {
+    pub denom: String,
+    pub amount: String,
+}
+
+/// Returns a mutable reference to the value of the first MsgTransfer in a SignDoc.
+/// Both amino ("msgs" with "type"/"value") and cosmjs ("messages" with "typeUrl"/"value")
+/// layouts are supported.
+///
+/// # Arguments
+///
+/// * `sign_doc` - SignDoc parsed as JSON
+///
+/// # Errors
+///
+/// Returns JsError if the SignDoc does not contain a MsgTransfer.
+#[cfg(feature = "web")]
+pub fn find_msg_transfer(sign_doc: &mut Value) -> Result<&mut Value, JsError> {
+    let key = if sign_doc.get("msgs").is_some() {
+        "msgs"
+    } else {
+        "messages"
+    };
+    let msgs = sign_doc
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| JsError::new("SignDoc does not contain any messages"))?;
+
+    msgs.iter_mut()
+        .find(|msg| {
+            let msg_type = msg
+                .get("type")
+                .or_else(|| msg.get("typeUrl"))
+                .and_then(Value::as_str);
+            matches!(
+                msg_type,
+                Some(MSG_TRANSFER_AMINO_TYPE) | Some(MSG_TRANSFER_TYPE_URL)
+            )
+        })
+        .and_then(|msg| msg.get_mut("value"))
+        .ok_or_else(|| JsError::new("SignDoc does not contain a MsgTransfer"))
+}
+
+/// Returns the denom of the token as it will be seen on the receiving chain, following ICS-20
+/// denom trace rules. If the token is returning to the chain it originated from, the source
+/// prefix is removed, otherwise the destination prefix is added.
+#[cfg(feature = "web")]
+pub fn received_denom(
+    source_port: &str,
+    source_channel: &str,
+    dest_port: &str,
+    dest_channel: &str,
+    denom: &str,
+) -> String {
+    let source_prefix = format!("{}/{}/", source_port, source_channel);
+
+    match denom.strip_prefix(&source_prefix) {
+        Some(unwound) => unwound.to_string(),
+        None => format!("{}/{}/{}", dest_port, dest_channel, denom),
+    }
+}
+
+/// Returns the non-zero 64 bit digits of an amount, as moved by MASP transparent inputs
+fn masp_digits(amount: &Amount) -> Vec<u64> {
+    let mut digits: Vec<u64> = amount
+        .raw_amount()
+        .0
+        .iter()
+        .copied()
+        .filter(|digit| *digit != 0)
+        .collect();
+    digits.sort_unstable();
+    digits
+}
+
+/// Decodes the shielding data of an IBC transfer and checks it against the transfer, reporting
+/// every mismatch. The asset types of the inputs depend on the MASP epoch of the destination
+/// chain, so the token is only checked to be a single asset, one per digit of the amount.
+///
+/// # Arguments
+///
+/// * `shielding_data` - Borsh serialized IbcShieldingData
+/// * `receiver` - receiver of the transfer
+/// * `amount` - amount of the transfer in the base denom
+///
+/// # Errors
+///
+/// Returns JsError if the data can't be decoded or doesn't match the transfer
+pub fn validate_shielding_data(
+    shielding_data: &[u8],
+    receiver: &str,
+    amount: &Amount,
+) -> Result<IbcShieldingData, JsError> {
+    let data = IbcShieldingData::try_from_slice(shielding_data)
+        .map_err(|e| JsError::new(&format!("Invalid IBC shielding data: {}", e)))?;
+    let masp_tx = &data.0;
+    let mut mismatches: Vec<String> = vec![];
+
+    if receiver != MASP.to_string() {
+        mismatches.push(format!(
+            "receiver: shielded IBC transfers are received by {}, not {}",
+            MASP, receiver
+        ));
+    }
+
+    let outputs = masp_tx
+        .sapling_bundle()
+        .map(|bundle| bundle.shielded_outputs.len())
+        .unwrap_or_default();
+    if outputs == 0 {
+        mismatches.push(String::from(
+            "payment address: shielding data has no shielded outputs",
+        ));
+    }
+
+    let inputs = masp_tx
+        .transparent_bundle()
+        .map(|bundle| bundle.vin.as_slice())
+        .unwrap_or_default();
+    let mut asset_types: Vec<_> = inputs.iter().map(|input| input.asset_type).collect();
+    asset_types.sort_unstable();
+    asset_types.dedup();
+    if asset_types.len() != inputs.len() {
+        mismatches.push(format!(
+            "token: shielding data has {} inputs of only {} asset types, expected one per digit",
+            inputs.len(),
+            asset_types.len()
+        ));
+    }
+
+    let mut input_digits: Vec<u64> = inputs.iter().map(|input| input.value).collect();
+    input_digits.sort_unstable();
+    let expected_digits = masp_digits(amount);
+    if input_digits != expected_digits {
+        mismatches.push(format!(
+            "amount: shielding data moves digits {:?}, the transfer of {} has digits {:?}",
+            input_digits,
+            amount.raw_amount(),
+            expected_digits
+        ));
+    }
+
+    if mismatches.is_empty() {
+        Ok(data)
+    } else {
+        Err(JsError::new(&format!(
+            "IBC shielding data does not match the transfer: {}",
+            mismatches.join("; ")
+        )))
+    }
+}
+
+/// Timeouts of an IBC transfer, as used to build it
+#[cfg(feature = "web")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IbcTimeouts {
+    pub timeout_height: Option<u64>,
+    pub timeout_sec_offset: Option<u64>,
+}
+
+/// Light client of the counterparty chain of a channel, as stored on Namada
+#[cfg(feature = "web")]
+#[derive(Debug, Clone)]
+pub struct CounterpartyClient {
+    pub channel: ChannelEnd,
+    pub client_id: ClientId,
+    pub client_state: TmClientState,
+    /// Consensus state at the latest height of the client
+    pub consensus_state: TmConsensusState,
+}
+
+#[cfg(feature = "web")]
+impl CounterpartyClient {
+    /// Unix time in seconds at which the client expires unless it is updated
+    pub fn expires_at(&self) -> i64 {
+        let trusting_period = self.client_state.trusting_period.as_secs() as i64;
+        self.consensus_state.timestamp.unix_timestamp() + trusting_period
+    }
+
+    /// Checks that packets sent through the channel can be relayed, i.e. the channel is open
+    /// and the client is neither frozen nor expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current unix time in seconds
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError describing why transfers through the channel would never be relayed
+    pub fn ensure_usable(
+        &self,
+        port_id: &PortId,
+        channel_id: &ChannelId,
+        now: i64,
+    ) -> Result<(), JsError> {
+        if !self.channel.is_open() {
+            return Err(JsError::new(&format!(
+                "IBC channel {}/{} is not open, its state is {}",
+                port_id, channel_id, self.channel.state
+            )));
+        }
+        if let Some(height) = self.client_state.frozen_height {
+            return Err(JsError::new(&format!(
+                "IBC client {} of channel {}/{} is frozen since height {}",
+                self.client_id, port_id, channel_id, height
+            )));
+        }
+        if self.expires_at() <= now {
+            return Err(JsError::new(&format!(
+                "IBC client {} of channel {}/{} has expired, it has to be updated by a relayer",
+                self.client_id, port_id, channel_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "web")]
+async fn query_ibc_value(client: &HttpClient, key: &Key, what: &str) -> Result<Vec<u8>, JsError> {
+    let (value, _) = rpc::query_storage_value_bytes(client, key, None, false).await?;
+    value.ok_or_else(|| JsError::new(&format!("IBC {} not found", what)))
+}
+
+/// Queries the light client of the counterparty chain of a channel.
+///
+/// # Errors
+///
+/// Returns JsError if the channel, its connection or client can't be found or decoded
+#[cfg(feature = "web")]
+pub async fn query_counterparty_client(
+    client: &HttpClient,
+    port_id: &PortId,
+    channel_id: &ChannelId,
+) -> Result<CounterpartyClient, JsError> {
+    let what = format!("channel {}/{}", port_id, channel_id);
+    let channel = query_ibc_value(client, &channel_key(port_id, channel_id), &what).await?;
+    let channel = ChannelEnd::decode_vec(&channel)
+        .map_err(|e| JsError::new(&format!("Invalid IBC {}: {}", what, e)))?;
+
+    let connection_id = channel
+        .connection_hops
+        .first()
+        .ok_or_else(|| JsError::new(&format!("IBC {} has no connection", what)))?;
+    let what = format!("connection {}", connection_id);
+    let connection = query_ibc_value(client, &connection_key(connection_id), &what).await?;
+    let connection = ConnectionEnd::decode_vec(&connection)
+        .map_err(|e| JsError::new(&format!("Invalid IBC {}: {}", what, e)))?;
+
+    let client_id = connection.client_id().clone();
+    let what = format!("client {}", client_id);
+    let client_state = query_ibc_value(client, &client_state_key(&client_id), &what).await?;
+    let client_state = <TmClientState as Protobuf<Any>>::decode_vec(&client_state)
+        .map_err(|e| JsError::new(&format!("Invalid IBC {}: {}", what, e)))?;
+
+    let key = consensus_state_key(&client_id, client_state.latest_height);
+    let consensus_state = query_ibc_value(client, &key, &what).await?;
+    let consensus_state = <TmConsensusState as Protobuf<Any>>::decode_vec(&consensus_state)
+        .map_err(|e| JsError::new(&format!("Invalid IBC {}: {}", what, e)))?;
+
+    Ok(CounterpartyClient {
+        channel,
+        client_id,
+        client_state,
+        consensus_state,
+    })
+}
+
+/// Fills in the timeouts of a transfer built without any. The transfer times out after
+/// DEFAULT_TIMEOUT_SEC_OFFSET, or before the counterparty client expires, as neither the
+/// packet nor its timeout can be proven with an expired client. The timeout height is left
+/// unset, because namada_sdk builds it with revision 0, which most counterparty chains don't
+/// use.
+///
+/// # Arguments
+///
+/// * `timeouts` - Timeouts given by the user
+/// * `client_expires_at` - Unix time in seconds at which the counterparty client expires
+/// * `now` - Current unix time in seconds
+///
+/// # Errors
+///
+/// Returns JsError if the client has already expired
+#[cfg(feature = "web")]
+pub fn default_timeouts(
+    timeouts: IbcTimeouts,
+    client_expires_at: i64,
+    now: i64,
+) -> Result<IbcTimeouts, JsError> {
+    if timeouts.timeout_height.is_some() || timeouts.timeout_sec_offset.is_some() {
+        return Ok(timeouts);
+    }
+    let remaining = u64::try_from(client_expires_at - now)
+        .ok()
+        .filter(|remaining| *remaining > 0)
+        .ok_or_else(|| JsError::new("IBC client of the counterparty chain has expired"))?;
+
+    Ok(IbcTimeouts {
+        timeout_height: None,
+        timeout_sec_offset: Some(DEFAULT_TIMEOUT_SEC_OFFSET.min(remaining)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use wasm_bindgen_test::*;
+
+    #[cfg(feature = "web")]
+    #[wasm_bindgen_test]
+    fn can_compute_received_denom() {
+        assert_eq!(
+            received_denom("transfer", "channel-1", "transfer", "channel-0", "uosmo"),
+            "transfer/channel-0/uosmo"
+        );
+        assert_eq!(
+            received_denom(
+                "transfer",
+                "channel-1",
+                "transfer",
+                "channel-0",
+                "transfer/channel-1/tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e"
+            ),
+            "tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e"
+        );
+    }
+
+    #[cfg(feature = "web")]
+    #[wasm_bindgen_test]
+    fn defaults_timeouts_within_client_validity() {
+        let unset = IbcTimeouts {
+            timeout_height: None,
+            timeout_sec_offset: None,
+        };
+        let now = 1_700_000_000;
+
+        let timeouts = default_timeouts(unset, now + 86400, now).unwrap();
+        assert_eq!(
+            timeouts.timeout_sec_offset,
+            Some(DEFAULT_TIMEOUT_SEC_OFFSET)
+        );
+        assert_eq!(timeouts.timeout_height, None);
+
+        let timeouts = default_timeouts(unset, now + 600, now).unwrap();
+        assert_eq!(timeouts.timeout_sec_offset, Some(600));
+
+        assert!(default_timeouts(unset, now - 1, now).is_err());
+
+        let given = IbcTimeouts {
+            timeout_height: Some(100),
+            timeout_sec_offset: None,
+        };
+        assert_eq!(default_timeouts(given, now - 1, now).unwrap(), given);
+    }
+
+    #[wasm_bindgen_test]
+    fn splits_amounts_into_masp_digits() {
+        assert_eq!(masp_digits(&Amount::from_u64(100)), vec![100]);
+        assert!(masp_digits(&Amount::zero()).is_empty());
+
+        let amount = Amount::from_str("18446744073709551621", 0u8).unwrap();
+        assert_eq!(masp_digits(&amount), vec![1, 5]);
+    }
+
+    #[cfg(feature = "web")]
+    #[wasm_bindgen_test]
+    fn can_find_msg_transfer_in_amino_sign_doc() {
+        let mut sign_doc = serde_json::json!({
+            "chain_id": "osmosis-1",
+            "memo": "",
+            "msgs": [{
+                "type": "cosmos-sdk/MsgTransfer",
+                "value": {
+                    "source_port": "transfer",
+                    "source_channel": "channel-1",
+                    "token": { "denom": "uosmo", "amount": "100" },
+                    "sender": "osmo1",
+                    "receiver": "znam1"
+                }
+            }]
+        });
+
+        let value = find_msg_transfer(&mut sign_doc).unwrap();
+        let msg: CosmosMsgTransfer = serde_json::from_value(value.clone()).unwrap();
+
+        assert_eq!(msg.token.denom, "uosmo");
+        assert_eq!(msg.receiver, "znam1");
+    }
+}
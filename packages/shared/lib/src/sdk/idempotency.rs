@@ -0,0 +1,131 @@
+//! Idempotency keys for broadcasting txs
+//!
+//! Every submitted tx can carry a key chosen by the caller, e.g. per user intent. Submitting
+//! the same key again within the window is refused, which protects against double clicks
+//! and retry bugs resulting in double sends. Keys of txs whose broadcast failed are kept as
+//! well, since e.g. a timeout doesn't tell whether the tx reached the mempool.
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use namada_sdk::hash::Hash;
+use namada_sdk::tx::Tx;
+use thiserror::Error;
+
+/// Default time window in which a key can't be reused, in milliseconds
+pub const IDEMPOTENCY_WINDOW_MS: f64 = 10.0 * 60.0 * 1000.0;
+
+/// Hash of the inner txs data, which stays the same when a tx with the same content is
+/// rebuilt, unlike the tx hashes which depend on timestamps, salts and signatures.
+pub fn tx_fingerprint(tx: &Tx) -> Hash {
+    let data: Vec<u8> = tx
+        .commitments()
+        .iter()
+        .flat_map(|cmt| {
+            let data = tx.data(cmt).unwrap_or_default();
+            let tag = tx
+                .get_section(cmt.code_sechash())
+                .and_then(|section| section.code_sec())
+                .and_then(|code| code.tag)
+                .unwrap_or_default();
+            [tag.into_bytes(), data].concat()
+        })
+        .collect();
+
+    Hash::sha256(data)
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum IdempotencyError {
+    #[error("Duplicate tx refused, already submitted with idempotency key: {0}")]
+    Duplicate(String),
+    #[error("Idempotency key {0} is already used for a different tx")]
+    KeyConflict(String),
+}
+
+#[derive(Default)]
+pub struct IdempotencyGuard {
+    /// Idempotency key -> (tx fingerprint, time until which the key is kept)
+    submitted: RefCell<HashMap<String, (Hash, f64)>>,
+}
+
+impl IdempotencyGuard {
+    /// Records the submission of a tx under the key.
+    ///
+    /// # Errors
+    ///
+    /// Returns IdempotencyError::Duplicate if the key is still kept for the same tx, or
+    /// IdempotencyError::KeyConflict if it is kept for a different one
+    pub fn acquire(&self, key: &str, fingerprint: Hash, now: f64) -> Result<(), IdempotencyError> {
+        let mut submitted = self.submitted.borrow_mut();
+        submitted.retain(|_, (_, kept_until)| now < *kept_until);
+
+        match submitted.get(key) {
+            Some((previous, _)) if *previous == fingerprint => {
+                Err(IdempotencyError::Duplicate(key.to_string()))
+            }
+            Some(_) => Err(IdempotencyError::KeyConflict(key.to_string())),
+            None => {
+                submitted.insert(key.to_string(), (fingerprint, now + IDEMPOTENCY_WINDOW_MS));
+                Ok(())
+            }
+        }
+    }
+
+    /// Keeps the key at least until the given time, e.g. the expiration of a tx whose
+    /// broadcast outcome is unknown and which could still be applied until then
+    pub fn keep_until(&self, key: &str, until: f64) {
+        if let Some((_, kept_until)) = self.submitted.borrow_mut().get_mut(key) {
+            *kept_until = kept_until.max(until);
+        }
+    }
+
+    /// Releases the key, once the tx submitted with it is known not to be applied
+    pub fn release(&self, key: &str) {
+        self.submitted.borrow_mut().remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn refuses_keys_reused_within_window() {
+        let guard = IdempotencyGuard::default();
+        let fingerprint = Hash::sha256(b"transfer");
+
+        assert!(guard.acquire("key", fingerprint, 0.0).is_ok());
+        assert_eq!(
+            guard.acquire("key", fingerprint, 1000.0),
+            Err(IdempotencyError::Duplicate("key".to_string()))
+        );
+        assert_eq!(
+            guard.acquire("key", Hash::sha256(b"other"), 1000.0),
+            Err(IdempotencyError::KeyConflict("key".to_string()))
+        );
+        assert!(guard.acquire("other", fingerprint, 1000.0).is_ok());
+        assert!(guard
+            .acquire("key", fingerprint, IDEMPOTENCY_WINDOW_MS)
+            .is_ok());
+
+        guard.release("key");
+        assert!(guard
+            .acquire("key", fingerprint, IDEMPOTENCY_WINDOW_MS)
+            .is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn keeps_keys_until_given_time() {
+        let guard = IdempotencyGuard::default();
+        let fingerprint = Hash::sha256(b"transfer");
+        let expiration = 2.0 * IDEMPOTENCY_WINDOW_MS;
+
+        assert!(guard.acquire("key", fingerprint, 0.0).is_ok());
+        guard.keep_until("key", expiration);
+        assert!(guard
+            .acquire("key", fingerprint, IDEMPOTENCY_WINDOW_MS)
+            .is_err());
+        assert!(guard.acquire("key", fingerprint, expiration).is_ok());
+    }
+}
@@ -0,0 +1,454 @@
+//! Checks of dApp-initiated transfers against the intent declared by the dApp
+//!
+//! A dApp submits a transfer msg to be built and signed on behalf of the user, and declares
+//! separately what the transfer does, e.g. "pay 10 NAM to tnam1...". The approval UI shows the
+//! declared intent, so a malicious dApp could swap the payload behind it. The decoded transfers
+//! of the tx are compared against the intent and every mismatch is reported. Amounts are
+//! summed per receiver and token over all inner txs, so splitting a payment across a batch
+//! can't hide a different total.
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use gloo_utils::format::JsValueSerdeExt;
+use namada_sdk::address::MASP;
+use namada_sdk::borsh::BorshDeserialize;
+use namada_sdk::masp::MaspTxId;
+use namada_sdk::token::{Amount, DenominatedAmount, Denomination};
+use namada_sdk::tx::{self, MaspBuilder, Section};
+use namada_sdk::PaymentAddress;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
+
+use super::prefixes::ChainPrefixes;
+use super::transaction::TransactionKind;
+use super::tx::{wasm_hash_to_tx_type, TxType};
+use crate::types::query::WasmHash;
+use crate::utils::to_js_result;
+
+/// Transfer a dApp declares it requests
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferIntent {
+    pub token: String,
+    /// Denominated amount, e.g. "1.5"
+    pub amount: String,
+    /// Transparent address, payment address or counterparty chain address for IBC transfers
+    pub receiver: String,
+    /// Denomination of the token. IBC packets carry amounts in the smallest unit, which are
+    /// compared to the declared amount in this denomination, or in the smallest unit if None.
+    #[serde(default)]
+    pub denomination: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntentMismatch {
+    /// Hash of the inner tx, None for mismatches of the whole tx
+    pub commitment: Option<String>,
+    pub field: &'static str,
+    pub declared: String,
+    pub actual: String,
+}
+
+/// Amounts received by (receiver, token) over all inner txs
+type Received = BTreeMap<(String, String), DenominatedAmount>;
+
+fn mismatch(
+    commitment: &str,
+    field: &'static str,
+    declared: &str,
+    actual: impl ToString,
+) -> IntentMismatch {
+    IntentMismatch {
+        commitment: Some(commitment.to_string()),
+        field,
+        declared: declared.to_string(),
+        actual: actual.to_string(),
+    }
+}
+
+fn amounts_equal(a: &DenominatedAmount, b: &DenominatedAmount) -> bool {
+    a.checked_sub(*b).is_some_and(|diff| diff.is_zero())
+        && b.checked_sub(*a).is_some_and(|diff| diff.is_zero())
+}
+
+fn add_received(
+    received: &mut Received,
+    receiver: String,
+    token: String,
+    amount: DenominatedAmount,
+) -> Result<(), JsError> {
+    let total = match received.remove(&(receiver.clone(), token.clone())) {
+        Some(total) => total
+            .checked_add(amount)
+            .ok_or_else(|| JsError::new("Transferred amount overflow"))?,
+        None => amount,
+    };
+    received.insert((receiver, token), total);
+    Ok(())
+}
+
+/// Adds the outputs of a MASP transaction to a payment address receiver, read from its
+/// builder section. Outputs to other payment addresses can't be told apart from change, so
+/// only the declared receiver is checked.
+fn check_shielded_outputs(
+    commitment: &str,
+    builder: &MaspBuilder,
+    receiver: &PaymentAddress,
+    intent: &TransferIntent,
+    received: &mut Received,
+) -> Result<Vec<IntentMismatch>, JsError> {
+    let mut mismatches = vec![];
+    for output in builder.builder.sapling_outputs() {
+        if PaymentAddress::from(output.address()) != *receiver {
+            continue;
+        }
+        let asset_data = builder
+            .asset_types
+            .iter()
+            .find(|data| data.encode().ok() == Some(output.asset_type()));
+        let asset_data = match asset_data {
+            Some(asset_data) if asset_data.token.to_string() == intent.token => asset_data,
+            Some(asset_data) => {
+                mismatches.push(mismatch(
+                    commitment,
+                    "token",
+                    &intent.token,
+                    &asset_data.token,
+                ));
+                continue;
+            }
+            None => {
+                mismatches.push(mismatch(commitment, "token", &intent.token, "unknown"));
+                continue;
+            }
+        };
+        let amount = Amount::from_masp_denominated(output.value(), asset_data.position);
+        add_received(
+            received,
+            intent.receiver.clone(),
+            intent.token.clone(),
+            DenominatedAmount::new(amount, asset_data.denom),
+        )?;
+    }
+    Ok(mismatches)
+}
+
+/// Compares the receivers and tokens of a transfer against the intent and adds the amounts
+/// sent to the receiver. Shielded receivers are checked against the outputs of the MASP
+/// builder section, as payment addresses are not part of the transfer.
+fn check_transfer(
+    commitment: &str,
+    transfer: &namada_sdk::token::Transfer,
+    builder: Option<&MaspBuilder>,
+    intent: &TransferIntent,
+    received: &mut Received,
+) -> Result<Vec<IntentMismatch>, JsError> {
+    let mut mismatches = vec![];
+
+    for (account, amount) in transfer.targets.iter() {
+        // Shielded outputs, including change, are checked in the MASP builder section
+        if account.owner == MASP {
+            continue;
+        }
+        if account.owner.to_string() != intent.receiver {
+            mismatches.push(mismatch(
+                commitment,
+                "receiver",
+                &intent.receiver,
+                &account.owner,
+            ));
+            continue;
+        }
+        if account.token.to_string() != intent.token {
+            mismatches.push(mismatch(commitment, "token", &intent.token, &account.token));
+            continue;
+        }
+        add_received(
+            received,
+            intent.receiver.clone(),
+            intent.token.clone(),
+            *amount,
+        )?;
+    }
+
+    if let Ok(receiver) = PaymentAddress::from_str(&intent.receiver) {
+        match builder {
+            Some(builder) => mismatches.extend(check_shielded_outputs(
+                commitment, builder, &receiver, intent, received,
+            )?),
+            None if transfer.shielded_section_hash.is_some() => mismatches.push(mismatch(
+                commitment,
+                "receiver",
+                &intent.receiver,
+                "unverifiable without MASP builder",
+            )),
+            None => {}
+        }
+    }
+
+    Ok(mismatches)
+}
+
+/// Amount of an IBC packet, in the smallest unit, in the denomination of the intent
+fn packet_amount(amount: &str, intent: &TransferIntent) -> Result<DenominatedAmount, JsError> {
+    let amount = Amount::from_str(amount, 0u8)?;
+    Ok(DenominatedAmount::new(
+        amount,
+        Denomination(intent.denomination.unwrap_or_default()),
+    ))
+}
+
+fn check_ibc_transfer(
+    commitment: &str,
+    packet_data: &namada_sdk::ibc::apps::transfer::types::packet::PacketData,
+    intent: &TransferIntent,
+    received: &mut Received,
+) -> Result<Vec<IntentMismatch>, JsError> {
+    let mut mismatches = vec![];
+    let receiver = packet_data.receiver.to_string();
+    if receiver != intent.receiver && receiver != MASP.to_string() {
+        mismatches.push(mismatch(commitment, "receiver", &intent.receiver, receiver));
+        return Ok(mismatches);
+    }
+    // Packets carry the address of Namada tokens and the IBC denom of others
+    let denom = packet_data.token.denom.to_string();
+    if denom != intent.token {
+        mismatches.push(mismatch(commitment, "token", &intent.token, denom));
+        return Ok(mismatches);
+    }
+    add_received(
+        received,
+        intent.receiver.clone(),
+        intent.token.clone(),
+        packet_amount(&packet_data.token.amount.to_string(), intent)?,
+    )?;
+    Ok(mismatches)
+}
+
+/// Compares the total received by the declared receiver against the declared amount
+fn check_received(
+    received: &Received,
+    intent: &TransferIntent,
+    intent_amount: &DenominatedAmount,
+    mismatches: &mut Vec<IntentMismatch>,
+) {
+    let total = received.get(&(intent.receiver.clone(), intent.token.clone()));
+    let (field, declared, actual) = match total {
+        Some(total) if amounts_equal(total, intent_amount) => return,
+        Some(total) => ("amount", &intent.amount, total.to_string()),
+        None if mismatches.is_empty() => ("receiver", &intent.receiver, String::from("none")),
+        None => return,
+    };
+    mismatches.push(IntentMismatch {
+        commitment: None,
+        field,
+        declared: declared.clone(),
+        actual,
+    });
+}
+
+fn masp_builder(tx: &tx::Tx, masp_tx_id: MaspTxId) -> Option<&MaspBuilder> {
+    tx.sections.iter().find_map(|section| match section {
+        Section::MaspBuilder(builder) if builder.target == masp_tx_id => Some(builder),
+        _ => None,
+    })
+}
+
+/// Decodes the transfers of a tx and compares them against the intent of the dApp.
+///
+/// # Errors
+///
+/// Returns JsError if the tx or the intent can't be decoded
+pub fn check_intent(
+    tx: &tx::Tx,
+    wasm_hashes: &Vec<WasmHash>,
+    intent: &TransferIntent,
+) -> Result<Vec<IntentMismatch>, JsError> {
+    let intent_amount = DenominatedAmount::from_str(&intent.amount)?;
+    let mut mismatches = vec![];
+    let mut received = Received::new();
+    let mut transfers = 0;
+
+    for cmt in tx.commitments() {
+        let hash = cmt.get_hash().to_string();
+        let tx_type = tx
+            .get_section(cmt.code_sechash())
+            .and_then(|s| s.code_sec())
+            .map(|s| hex::encode(s.code.hash().0))
+            .and_then(|code_hash| wasm_hash_to_tx_type(&code_hash, wasm_hashes));
+        let data = tx.data(&cmt).unwrap_or_default();
+
+        match tx_type {
+            Some(TxType::Transfer) => {
+                transfers += 1;
+                let transfer = namada_sdk::token::Transfer::try_from_slice(&data)?;
+                let builder = transfer
+                    .shielded_section_hash
+                    .and_then(|masp_tx_id| masp_builder(tx, masp_tx_id));
+                mismatches.extend(check_transfer(
+                    &hash,
+                    &transfer,
+                    builder,
+                    intent,
+                    &mut received,
+                )?);
+            }
+            Some(TxType::IBCTransfer) => {
+                transfers += 1;
+                if let TransactionKind::IbcTransfer(msg) =
+                    TransactionKind::from(TxType::IBCTransfer, &data)
+                {
+                    mismatches.extend(check_ibc_transfer(
+                        &hash,
+                        &msg.message.packet_data,
+                        intent,
+                        &mut received,
+                    )?);
+                }
+            }
+            // Any other inner tx is unexpected for a transfer
+            Some(tx_type) => mismatches.push(mismatch(
+                &hash,
+                "txType",
+                "transfer",
+                format!("{:?}", tx_type),
+            )),
+            None => mismatches.push(mismatch(&hash, "txType", "transfer", "unknown")),
+        }
+    }
+
+    if transfers == 0 {
+        mismatches.push(IntentMismatch {
+            commitment: None,
+            field: "txType",
+            declared: String::from("transfer"),
+            actual: String::from("none"),
+        });
+        return Ok(mismatches);
+    }
+    check_received(&received, intent, &intent_amount, &mut mismatches);
+    Ok(mismatches)
+}
+
+/// Compares the transfers of Borsh serialized Namada Tx bytes against the TransferIntent
+/// declared by the dApp. Returns a list of IntentMismatch, empty if the tx matches.
+/// Addresses are compared and reported with the prefixes of the Sdk, see Sdk.chain_prefixes,
+/// or the Namada prefixes if undefined.
+#[wasm_bindgen]
+pub fn check_transfer_intent(
+    tx_bytes: &[u8],
+    wasm_hashes: JsValue,
+    intent: JsValue,
+    prefixes: JsValue,
+) -> Result<JsValue, JsError> {
+    let tx = tx::Tx::try_from_slice(tx_bytes)?;
+    let wasm_hashes: Vec<WasmHash> = wasm_hashes.into_serde()?;
+    let prefixes = ChainPrefixes::from_js(prefixes)?;
+    let mut intent: TransferIntent = intent.into_serde()?;
+    intent.token = prefixes.native(&intent.token).into_owned();
+    intent.receiver = prefixes.native(&intent.receiver).into_owned();
+
+    let mismatches: Vec<IntentMismatch> = check_intent(&tx, &wasm_hashes, &intent)?
+        .into_iter()
+        .map(|mismatch| IntentMismatch {
+            declared: prefixes.configured(&mismatch.declared),
+            actual: prefixes.configured(&mismatch.actual),
+            ..mismatch
+        })
+        .collect();
+    to_js_result(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use namada_sdk::address::Address;
+    use namada_sdk::token::{Account, Transfer};
+    use wasm_bindgen_test::*;
+
+    const SOURCE: &str = "tnam1qz4sdx5jlh909j44uz46pl6f3d2ftsvkmcml0yuf";
+    const RECEIVER: &str = "tnam1qpgk927uh2aqjs92yhycsh08n5yggvltn5nk92zp";
+    const TOKEN: &str = "tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e";
+
+    fn transfer(receiver: &str, amount: &str) -> Transfer {
+        let account = |owner: &str| Account {
+            owner: Address::from_str(owner).unwrap(),
+            token: Address::from_str(TOKEN).unwrap(),
+        };
+        let amount = DenominatedAmount::from_str(amount).unwrap();
+        Transfer {
+            sources: [(account(SOURCE), amount)].into(),
+            targets: [(account(receiver), amount)].into(),
+            shielded_section_hash: None,
+        }
+    }
+
+    fn intent(amount: &str) -> TransferIntent {
+        TransferIntent {
+            token: TOKEN.to_string(),
+            amount: amount.to_string(),
+            receiver: RECEIVER.to_string(),
+            denomination: None,
+        }
+    }
+
+    fn check(transfers: &[Transfer], intent: &TransferIntent) -> Vec<IntentMismatch> {
+        let amount = DenominatedAmount::from_str(&intent.amount).unwrap();
+        let mut received = Received::new();
+        let mut mismatches = vec![];
+        for transfer in transfers {
+            mismatches
+                .extend(check_transfer("hash", transfer, None, intent, &mut received).unwrap());
+        }
+        check_received(&received, intent, &amount, &mut mismatches);
+        mismatches
+    }
+
+    #[wasm_bindgen_test]
+    fn matches_declared_transfer() {
+        assert!(check(&[transfer(RECEIVER, "1.5")], &intent("1.50")).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn flags_swapped_receiver_and_amount() {
+        let mismatches = check(&[transfer(SOURCE, "1.5")], &intent("1.5"));
+        assert_eq!(mismatches[0].field, "receiver");
+        assert_eq!(mismatches[0].actual, SOURCE);
+
+        let mismatches = check(&[transfer(RECEIVER, "15")], &intent("1.5"));
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].field, "amount");
+        assert_eq!(mismatches[0].actual, "15");
+    }
+
+    #[wasm_bindgen_test]
+    fn sums_transfers_of_a_batch() {
+        let transfers = [transfer(RECEIVER, "1.5"), transfer(RECEIVER, "1.5")];
+        let mismatches = check(&transfers, &intent("1.5"));
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].field, "amount");
+        let actual = DenominatedAmount::from_str(&mismatches[0].actual).unwrap();
+        assert!(amounts_equal(
+            &actual,
+            &DenominatedAmount::from_str("3").unwrap()
+        ));
+
+        assert!(check(&transfers, &intent("3")).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn denominates_packet_amounts() {
+        let mut intent = intent("1.5");
+        intent.denomination = Some(6);
+        let amount = packet_amount("1500000", &intent).unwrap();
+        assert!(amounts_equal(
+            &amount,
+            &DenominatedAmount::from_str("1.5").unwrap()
+        ));
+        assert!(!amounts_equal(
+            &amount,
+            &DenominatedAmount::from_str("15").unwrap()
+        ));
+    }
+}
@@ -0,0 +1,194 @@
+//! Compact block cache shared across viewing keys
+//!
+//! Shielded sync trial-decrypts every fetched block for all of the keys passed to it, but
+//! keys added later, or synced in separate calls, would fetch the same blocks again from
+//! their birthdays. CachingMaspClient keeps the fetched MASP txs by height for the lifetime
+//! of the Query, so the blocks are only fetched once and then replayed for any key. The
+//! cache is bounded, the lowest heights are evicted first.
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use namada_sdk::masp::utils::{IndexedNoteEntry, MaspClient, MaspClientCapabilities};
+use namada_sdk::masp::MaspIndexedTx;
+use namada_sdk::masp_primitives::merkle_tree::{CommitmentTree, IncrementalWitness};
+use namada_sdk::masp_primitives::sapling::Node;
+use namada_sdk::state::BlockHeight;
+
+/// Maximum number of cached MASP txs
+const MAX_CACHED_TXS: usize = 10_000;
+
+#[derive(Default)]
+struct Cache {
+    /// Fetched MASP txs by height, keyed by their index in the block
+    entries: BTreeMap<BlockHeight, BTreeMap<MaspIndexedTx, IndexedNoteEntry>>,
+    /// Number of cached MASP txs
+    len: usize,
+    /// Disjoint, sorted inclusive ranges of heights which were fetched
+    fetched: Vec<(BlockHeight, BlockHeight)>,
+}
+
+impl Cache {
+    fn covers(&self, from: BlockHeight, to: BlockHeight) -> bool {
+        self.fetched
+            .iter()
+            .any(|(start, end)| *start <= from && to <= *end)
+    }
+
+    fn get(&self, from: BlockHeight, to: BlockHeight) -> Vec<IndexedNoteEntry> {
+        self.entries
+            .range(from..=to)
+            .flat_map(|(_, entries)| entries.values().cloned())
+            .collect()
+    }
+
+    fn insert(&mut self, from: BlockHeight, to: BlockHeight, entries: Vec<IndexedNoteEntry>) {
+        for entry in entries {
+            let replaced = self
+                .entries
+                .entry(entry.0.indexed_tx.height)
+                .or_default()
+                .insert(entry.0, entry);
+            if replaced.is_none() {
+                self.len += 1;
+            }
+        }
+
+        // Merge the new range with overlapping or adjacent ones
+        let (mut start, mut end) = (from, to);
+        self.fetched.retain(|(s, e)| {
+            let disjoint = e.0 + 1 < start.0 || end.0 + 1 < s.0;
+            if !disjoint {
+                start = start.min(*s);
+                end = end.max(*e);
+            }
+            disjoint
+        });
+        self.fetched.push((start, end));
+        self.fetched.sort();
+
+        self.evict(MAX_CACHED_TXS);
+    }
+
+    /// Evicts the lowest heights until at most max_len MASP txs are cached, shrinking the
+    /// fetched ranges accordingly
+    fn evict(&mut self, max_len: usize) {
+        while self.len > max_len {
+            let Some((height, entries)) = self.entries.pop_first() else {
+                break;
+            };
+            self.len -= entries.len();
+            self.fetched.retain_mut(|(start, end)| {
+                *start = (*start).max(BlockHeight(height.0 + 1));
+                start <= end
+            });
+        }
+    }
+}
+
+/// Compact block cache, cheap to clone and shared by all syncs of a Query
+#[derive(Clone, Default)]
+pub struct BlockCache(Arc<Mutex<Cache>>);
+
+impl BlockCache {
+    /// Drops all cached blocks, e.g. after switching chains
+    pub fn clear(&self) {
+        *self.0.lock().unwrap() = Cache::default();
+    }
+}
+
+/// MaspClient serving shielded transfers from the BlockCache when possible
+#[derive(Clone)]
+pub struct CachingMaspClient<C> {
+    client: C,
+    cache: BlockCache,
+}
+
+impl<C> CachingMaspClient<C> {
+    pub fn new(client: C, cache: BlockCache) -> Self {
+        Self { client, cache }
+    }
+}
+
+impl<C: MaspClient> MaspClient for CachingMaspClient<C> {
+    type Error = C::Error;
+
+    async fn last_block_height(&self) -> Result<Option<BlockHeight>, Self::Error> {
+        self.client.last_block_height().await
+    }
+
+    async fn fetch_shielded_transfers(
+        &self,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<Vec<IndexedNoteEntry>, Self::Error> {
+        {
+            let cache = self.cache.0.lock().unwrap();
+            if cache.covers(from, to) {
+                return Ok(cache.get(from, to));
+            }
+        }
+
+        let entries = self.client.fetch_shielded_transfers(from, to).await?;
+        self.cache
+            .0
+            .lock()
+            .unwrap()
+            .insert(from, to, entries.clone());
+
+        Ok(entries)
+    }
+
+    fn capabilities(&self) -> MaspClientCapabilities {
+        self.client.capabilities()
+    }
+
+    async fn fetch_commitment_tree(
+        &self,
+        height: BlockHeight,
+    ) -> Result<CommitmentTree<Node>, Self::Error> {
+        self.client.fetch_commitment_tree(height).await
+    }
+
+    async fn fetch_note_index(
+        &self,
+        height: BlockHeight,
+    ) -> Result<BTreeMap<MaspIndexedTx, usize>, Self::Error> {
+        self.client.fetch_note_index(height).await
+    }
+
+    async fn fetch_witness_map(
+        &self,
+        height: BlockHeight,
+    ) -> Result<HashMap<usize, IncrementalWitness<Node>>, Self::Error> {
+        self.client.fetch_witness_map(height).await
+    }
+
+    async fn commitment_anchor_exists(&self, root: &Node) -> Result<bool, Self::Error> {
+        self.client.commitment_anchor_exists(root).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn h(height: u64) -> BlockHeight {
+        BlockHeight(height)
+    }
+
+    #[wasm_bindgen_test]
+    fn merges_fetched_ranges() {
+        let mut cache = Cache::default();
+        cache.insert(h(1), h(10), vec![]);
+        cache.insert(h(21), h(30), vec![]);
+        assert!(cache.covers(h(2), h(10)));
+        assert!(!cache.covers(h(5), h(25)));
+
+        // Adjacent range joins both neighbours
+        cache.insert(h(11), h(20), vec![]);
+        assert_eq!(cache.fetched, vec![(h(1), h(30))]);
+        assert!(cache.covers(h(5), h(25)));
+        assert!(!cache.covers(h(5), h(31)));
+    }
+}
@@ -0,0 +1,277 @@
+//! Cache of proven shielded builds
+//!
+//! Proving the MASP transaction dominates the time of a shielded build. When broadcasting
+//! fails transiently, e.g. on a network error, retrying builds the same msgs again, so
+//! builds are cached by a hash of the msgs and the anchor of the local commitment tree. The
+//! retry, requested explicitly by the caller, reuses the proven tx as long as it's still
+//! valid: its wrapper hasn't expired, its anchors are known to the chain, none of its notes
+//! were spent since and the MASP epoch of its conversions is unchanged. Syncing new notes
+//! changes the anchor and thus the key, so builds never reuse a stale view of the notes.
+//!
+//! Builds are checked again before signing, as the user may take a while to approve them,
+//! and rebuilt from the same msgs if the MASP epoch changed, an anchor is unknown or the
+//! wrapper expired.
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use namada_sdk::borsh;
+use namada_sdk::chain::MaspEpoch;
+use namada_sdk::hash::Hash;
+use namada_sdk::masp_primitives::sapling::Node;
+use namada_sdk::masp_primitives::transaction::Transaction as MaspTransaction;
+use namada_sdk::rpc::{self, query_masp_epoch};
+use namada_sdk::state::Key;
+use namada_sdk::time::DateTimeUtc;
+use namada_sdk::token::storage_key::{masp_commitment_anchor_key, masp_nullifier_key};
+use wasm_bindgen::JsError;
+
+use crate::rpc_client::HttpClient;
+use crate::sdk::tx;
+
+/// Maximum number of cached builds, the oldest are dropped first
+pub const MAX_CACHED_BUILDS: usize = 8;
+
+/// Returns the cache key of a build of the msgs at the anchor
+pub fn build_key(msgs: &[&[u8]], anchor: &Node) -> Result<Hash, JsError> {
+    let mut data: Vec<u8> = vec![];
+    for msg in msgs {
+        // Length prefixed, so that msgs can't be shifted between each other
+        data.extend_from_slice(&(msg.len() as u64).to_le_bytes());
+        data.extend_from_slice(msg);
+    }
+    data.extend(borsh::to_vec(anchor)?);
+
+    Ok(Hash::sha256(data))
+}
+
+/// Msg a shielded tx was built from
+#[derive(Debug, Clone)]
+pub enum BuildMsg {
+    /// Borsh serialized ShieldedTransferMsg
+    Shielded(Vec<u8>),
+    /// Borsh serialized UnshieldingTransferMsg
+    Unshielding(Vec<u8>),
+}
+
+/// Shielded tx cached by the Sdk, with the msgs to rebuild it from once stale
+#[derive(Debug, Clone)]
+pub struct ShieldedBuild {
+    pub msg: BuildMsg,
+    pub wrapper_tx_msg: Vec<u8>,
+    pub tx: tx::Tx,
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedBuild<T> {
+    pub value: T,
+    masp_epoch: MaspEpoch,
+    /// Expiration of the wrapper, in milliseconds since the epoch
+    expiration: Option<i64>,
+    /// Storage keys of the anchors of the spends, which must exist
+    anchor_keys: Vec<Key>,
+    /// Storage keys of the nullifiers of the spends, which must not exist
+    nullifier_keys: Vec<Key>,
+}
+
+impl<T> CachedBuild<T> {
+    pub fn new(
+        value: T,
+        masp_txs: &[MaspTransaction],
+        masp_epoch: MaspEpoch,
+        expiration: Option<DateTimeUtc>,
+    ) -> CachedBuild<T> {
+        let spends = masp_txs
+            .iter()
+            .filter_map(|masp_tx| masp_tx.sapling_bundle())
+            .flat_map(|bundle| bundle.shielded_spends.iter());
+        let (anchor_keys, nullifier_keys) = spends
+            .map(|spend| {
+                (
+                    masp_commitment_anchor_key(spend.anchor),
+                    masp_nullifier_key(&spend.nullifier),
+                )
+            })
+            .unzip();
+
+        CachedBuild {
+            value,
+            masp_epoch,
+            expiration: expiration.map(|expiration| expiration.0.timestamp_millis()),
+            anchor_keys,
+            nullifier_keys,
+        }
+    }
+
+    /// Checks whether the build would still be accepted by the chain
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if any of the queries fail
+    pub async fn freshness(&self, client: &HttpClient) -> Result<Freshness, JsError> {
+        if self.is_expired(js_sys::Date::now() as i64) {
+            return Ok(Freshness::Expired);
+        }
+        for key in self.nullifier_keys.iter() {
+            if rpc::query_has_storage_key(client, key).await? {
+                return Ok(Freshness::Spent);
+            }
+        }
+        if query_masp_epoch(client).await? != self.masp_epoch {
+            return Ok(Freshness::StaleEpoch);
+        }
+        for key in self.anchor_keys.iter() {
+            if !rpc::query_has_storage_key(client, key).await? {
+                return Ok(Freshness::StaleAnchor);
+            }
+        }
+
+        Ok(Freshness::Fresh)
+    }
+
+    /// Whether the wrapper expired at `now`, in milliseconds since the epoch
+    fn is_expired(&self, now: i64) -> bool {
+        self.expiration.is_some_and(|expiration| expiration <= now)
+    }
+
+    pub async fn is_valid(&self, client: &HttpClient) -> Result<bool, JsError> {
+        Ok(self.freshness(client).await? == Freshness::Fresh)
+    }
+}
+
+/// Whether a build would still be accepted by the chain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Freshness {
+    Fresh,
+    /// The MASP epoch changed, so the conversions of the notes are outdated
+    StaleEpoch,
+    /// An anchor is unknown to the chain
+    StaleAnchor,
+    /// A note was spent since, e.g. by a previous broadcast of the tx
+    Spent,
+    /// The wrapper expired, so the chain would reject the tx
+    Expired,
+}
+
+impl Freshness {
+    /// Reason passed to the rebuild callbacks
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Freshness::Fresh => "fresh",
+            Freshness::StaleEpoch => "epoch",
+            Freshness::StaleAnchor => "anchor",
+            Freshness::Spent => "spent",
+            Freshness::Expired => "expired",
+        }
+    }
+}
+
+pub struct BuildCache<T> {
+    entries: RefCell<VecDeque<(Hash, CachedBuild<T>)>>,
+}
+
+impl<T> Default for BuildCache<T> {
+    fn default() -> Self {
+        BuildCache {
+            entries: RefCell::new(VecDeque::new()),
+        }
+    }
+}
+
+impl<T: Clone> BuildCache<T> {
+    pub fn get(&self, key: &Hash) -> Option<CachedBuild<T>> {
+        self.entries
+            .borrow()
+            .iter()
+            .find(|(cached, _)| cached == key)
+            .map(|(_, build)| build.clone())
+    }
+
+    /// Returns the first build matching the predicate together with its key
+    pub fn find(&self, predicate: impl Fn(&T) -> bool) -> Option<(Hash, CachedBuild<T>)> {
+        self.entries
+            .borrow()
+            .iter()
+            .find(|(_, build)| predicate(&build.value))
+            .cloned()
+    }
+
+    /// Caches a build, replacing a previous build of the same key
+    pub fn insert(&self, key: Hash, build: CachedBuild<T>) {
+        let mut entries = self.entries.borrow_mut();
+        entries.retain(|(cached, _)| *cached != key);
+        if entries.len() == MAX_CACHED_BUILDS {
+            entries.pop_front();
+        }
+        entries.push_back((key, build));
+    }
+
+    pub fn remove(&self, key: &Hash) {
+        self.entries
+            .borrow_mut()
+            .retain(|(cached, _)| cached != key);
+    }
+
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use namada_sdk::masp_proofs::bls12_381::Scalar;
+    use std::str::FromStr;
+    use wasm_bindgen_test::*;
+
+    fn build(value: u8) -> CachedBuild<u8> {
+        CachedBuild::new(value, &[], MaspEpoch::default(), None)
+    }
+
+    #[wasm_bindgen_test]
+    fn expires_with_the_wrapper() {
+        assert!(!build(0).is_expired(i64::MAX));
+
+        let expiration = DateTimeUtc::from_str("2023-11-14T22:13:20Z").unwrap();
+        let build = CachedBuild::new(0u8, &[], MaspEpoch::default(), Some(expiration));
+        assert!(!build.is_expired(1_699_999_999_999));
+        assert!(build.is_expired(1_700_000_000_000));
+    }
+
+    #[wasm_bindgen_test]
+    fn keys_builds_by_msgs_and_anchor() {
+        let anchor = Node::from_scalar(Scalar::from(1u64));
+        let other_anchor = Node::from_scalar(Scalar::from(2u64));
+        let key = build_key(&[b"transfer", b"wrapper"], &anchor).unwrap();
+
+        assert_eq!(key, build_key(&[b"transfer", b"wrapper"], &anchor).unwrap());
+        assert_ne!(
+            key,
+            build_key(&[b"transfer", b"wrapper"], &other_anchor).unwrap()
+        );
+        assert_ne!(key, build_key(&[b"transferwrapper"], &anchor).unwrap());
+        assert_ne!(key, build_key(&[b"transfe", b"rwrapper"], &anchor).unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    fn evicts_oldest_builds() {
+        let cache: BuildCache<u8> = BuildCache::default();
+        let keys: Vec<Hash> = (0..=MAX_CACHED_BUILDS as u8)
+            .map(|i| Hash::sha256([i]))
+            .collect();
+
+        for (i, key) in keys.iter().enumerate() {
+            cache.insert(*key, build(i as u8));
+        }
+        assert!(cache.get(&keys[0]).is_none());
+        assert_eq!(cache.get(&keys[1]).unwrap().value, 1);
+
+        cache.insert(keys[1], build(42));
+        assert_eq!(cache.get(&keys[1]).unwrap().value, 42);
+        cache.remove(&keys[1]);
+        assert!(cache.get(&keys[1]).is_none());
+        assert_eq!(cache.find(|value| *value == 2).unwrap().0, keys[2]);
+        assert!(cache.find(|value| *value == 1).is_none());
+        cache.clear();
+        assert!(cache.get(&keys[MAX_CACHED_BUILDS]).is_none());
+    }
+}
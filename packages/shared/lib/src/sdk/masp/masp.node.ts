@@ -0,0 +1,50 @@
+import * as nodeFs from "node:fs";
+
+/**
+ * File system callbacks used by the Node.js storage of the Sdk, with the
+ * signatures of their node:fs counterparts
+ */
+type FileSystem = {
+  readFileSync: (path: string) => Uint8Array;
+  writeFileSync: (path: string, data: Uint8Array) => void;
+  appendFileSync: (path: string, data: Uint8Array) => void;
+  renameSync: (oldPath: string, newPath: string) => void;
+  unlinkSync: (path: string) => void;
+  existsSync: (path: string) => boolean;
+};
+
+let fs: FileSystem = nodeFs;
+
+/**
+ * Replaces the file system, missing callbacks fall back to node:fs
+ * @param [callbacks] - File system callbacks, node:fs is restored if undefined
+ */
+export function setFs(callbacks?: Partial<FileSystem>): void {
+  fs = callbacks ? { ...nodeFs, ...callbacks } : nodeFs;
+}
+
+export function writeFileSync(path: string, ui8a: Uint8Array): void {
+  fs.writeFileSync(path, Buffer.from(ui8a));
+}
+
+export function readFileSync(path: string): Uint8Array {
+  // Small buffers share a pooled ArrayBuffer, so view only their own bytes
+  const buffer = Buffer.from(fs.readFileSync(path));
+  return new Uint8Array(buffer.buffer, buffer.byteOffset, buffer.length);
+}
+
+export function appendFileSync(path: string, ui8a: Uint8Array): void {
+  fs.appendFileSync(path, Buffer.from(ui8a));
+}
+
+export function renameSync(pathA: string, pathB: string): void {
+  fs.renameSync(pathA, pathB);
+}
+
+export function unlinkSync(path: string): void {
+  fs.unlinkSync(path);
+}
+
+export function existsSync(path: string): boolean {
+  return fs.existsSync(path);
+}
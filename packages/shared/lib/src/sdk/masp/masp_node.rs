@@ -1,3 +1,9 @@
+//! Node.js backend of the shielded context
+//!
+//! MASP params, the shielded context and the sync cache are stored as files in the context
+//! dir. Files are accessed through the JS callbacks of masp.node.js, node:fs by default, which
+//! set_node_fs replaces, e.g. to keep the files in a sandboxed or remote file system. Server
+//! side tx construction then runs the same code as the browser.
 use async_trait::async_trait;
 use namada_sdk::{
     borsh::{BorshDeserialize, BorshSerialize},
@@ -5,10 +11,12 @@ use namada_sdk::{
     masp_proofs::prover::LocalTxProver,
     ShieldedWallet,
 };
-use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
 
+use std::io;
 use std::path::PathBuf;
 
+use super::prover_cache::ProverCache;
 use crate::utils::to_bytes;
 
 /// Spend circuit name
@@ -32,6 +40,8 @@ const CACHE_FILE_TMP_PREFIX: &str = "shielded_sync.cache.tmp";
 pub struct NodeShieldedUtils {
     #[borsh(skip)]
     context_dir: PathBuf,
+    #[borsh(skip)]
+    prover: ProverCache,
 }
 
 impl NodeShieldedUtils {
@@ -51,13 +61,16 @@ impl NodeShieldedUtils {
             Self::fetch_params(output_path, OUTPUT_NAME).await;
         }
 
-        let sync_status = if std::fs::read(context_dir.join(SPECULATIVE_FILE_NAME)).is_ok() {
+        let sync_status = if file_exists(context_dir.join(SPECULATIVE_FILE_NAME)) {
             ContextSyncStatus::Speculative
         } else {
             ContextSyncStatus::Confirmed
         };
 
-        let utils = Self { context_dir };
+        let utils = Self {
+            context_dir,
+            prover: ProverCache::default(),
+        };
 
         ShieldedWallet {
             utils,
@@ -83,7 +96,16 @@ impl NodeShieldedUtils {
 #[async_trait(?Send)]
 impl ShieldedUtils for NodeShieldedUtils {
     fn local_tx_prover(&self) -> LocalTxProver {
-        LocalTxProver::with_default_location().expect("unable to load MASP Parameters")
+        self.prover.get_or_init(|| {
+            let read_params = |name: &str| {
+                read_file(self.context_dir.join(name)).expect("unable to load MASP Parameters")
+            };
+            LocalTxProver::from_bytes(
+                &read_params(SPEND_NAME),
+                &read_params(OUTPUT_NAME),
+                &read_params(CONVERT_NAME),
+            )
+        })
     }
 
     async fn load<U: ShieldedUtils>(
@@ -100,13 +122,18 @@ impl ShieldedUtils for NodeShieldedUtils {
             }
         };
 
-        let path = path_buf_to_js_value(self.context_dir.join(file_name));
-        //TODO: change to_bytes to sth more descripive, add "from_bytes"
-        let bytes = to_bytes(read_file_sync(path).unwrap().into());
+        let path = self.context_dir.join(file_name);
+        // Nothing was saved yet, e.g. before the first sync
+        let context = if file_exists(path.clone()) {
+            let bytes = read_file(path)?;
+            ShieldedWallet::<U>::deserialize(&mut &bytes[..])?
+        } else {
+            ShieldedWallet::default()
+        };
 
         *ctx = ShieldedWallet {
             utils: ctx.utils.clone(),
-            ..ShieldedWallet::<U>::deserialize(&mut &bytes[..])?
+            ..context
         };
         Ok(())
     }
@@ -117,24 +144,20 @@ impl ShieldedUtils for NodeShieldedUtils {
             ContextSyncStatus::Speculative => (SPECULATIVE_TMP_FILE_NAME, SPECULATIVE_FILE_NAME),
         };
 
-        let tmp_path = path_buf_to_js_value(self.context_dir.join(tmp_file_name));
-        {
-            let mut bytes = Vec::new();
-            ctx.serialize(&mut bytes)
-                .expect("cannot serialize shielded context");
-            let uint8_array = js_sys::Uint8Array::from(&bytes[..]);
-
-            write_file_sync(tmp_path.clone(), uint8_array.into()).unwrap();
-        }
-
-        let new_path = path_buf_to_js_value(self.context_dir.join(file_name));
-        renameSync(tmp_path, new_path).unwrap();
+        let mut bytes = Vec::new();
+        ctx.serialize(&mut bytes)
+            .expect("cannot serialize shielded context");
+        write_file_atomic(
+            self.context_dir.join(tmp_file_name),
+            self.context_dir.join(file_name),
+            &bytes,
+        )?;
 
+        let speculative_path = self.context_dir.join(SPECULATIVE_FILE_NAME);
         if let ContextSyncStatus::Confirmed = ctx.sync_status {
-            unlinkSync(path_buf_to_js_value(
-                self.context_dir.join(SPECULATIVE_FILE_NAME),
-            ))
-            .unwrap();
+            if file_exists(speculative_path.clone()) {
+                unlinkSync(path_buf_to_js_value(speculative_path)).map_err(to_io_err)?;
+            }
         }
 
         Ok(())
@@ -143,26 +166,20 @@ impl ShieldedUtils for NodeShieldedUtils {
     /// Save a cache of data as part of shielded sync if that
     /// process gets interrupted.
     async fn cache_save(&self, cache: &DispatcherCache) -> std::io::Result<()> {
-        let tmp_path = path_buf_to_js_value(self.context_dir.join(CACHE_FILE_TMP_PREFIX));
-        {
-            let mut bytes = Vec::new();
-            cache.serialize(&mut bytes).expect("cannot serialize cache");
-            let uint8_array = js_sys::Uint8Array::from(&bytes[..]);
-
-            write_file_sync(tmp_path.clone(), uint8_array.into()).unwrap();
-        }
-
-        let new_path = path_buf_to_js_value(self.context_dir.join(CACHE_FILE_NAME));
-        renameSync(tmp_path, new_path).unwrap();
-
-        Ok(())
+        let mut bytes = Vec::new();
+        cache.serialize(&mut bytes).expect("cannot serialize cache");
+        write_file_atomic(
+            self.context_dir.join(CACHE_FILE_TMP_PREFIX),
+            self.context_dir.join(CACHE_FILE_NAME),
+            &bytes,
+        )
     }
 
     /// Load a cache of data as part of shielded sync if that
     /// process gets interrupted.
     async fn cache_load(&self) -> std::io::Result<DispatcherCache> {
-        // TODO:
-        todo!()
+        let bytes = read_file(self.context_dir.join(CACHE_FILE_NAME))?;
+        DispatcherCache::try_from_slice(&bytes)
     }
 }
 
@@ -170,15 +187,60 @@ fn path_buf_to_js_value(path: PathBuf) -> JsValue {
     JsValue::from_str(path.to_str().unwrap())
 }
 
+fn to_io_err(e: JsValue) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        e.as_string().unwrap_or_else(|| format!("{:?}", e)),
+    )
+}
+
 fn file_exists(path: PathBuf) -> bool {
     exists_sync(path_buf_to_js_value(path))
-        .unwrap()
-        .as_bool()
-        .unwrap()
+        .ok()
+        .and_then(|exists| exists.as_bool())
+        .unwrap_or_default()
+}
+
+fn read_file(path: PathBuf) -> io::Result<Vec<u8>> {
+    let bytes = read_file_sync(path_buf_to_js_value(path)).map_err(to_io_err)?;
+    Ok(to_bytes(bytes))
+}
+
+/// Writes to a temporary file first, so a failed write doesn't lose the previous content
+fn write_file_atomic(tmp_path: PathBuf, path: PathBuf, bytes: &[u8]) -> io::Result<()> {
+    let tmp_path = path_buf_to_js_value(tmp_path);
+    let uint8_array = js_sys::Uint8Array::from(bytes);
+    write_file_sync(tmp_path.clone(), uint8_array.into()).map_err(to_io_err)?;
+    renameSync(tmp_path, path_buf_to_js_value(path)).map_err(to_io_err)?;
+    Ok(())
+}
+
+/// Replaces the file system used by the Node.js storage of the Sdk: MASP params, shielded
+/// context, wallet, templates and audit log. Callbacks have the signatures of their node:fs
+/// counterparts, missing ones fall back to node:fs. Pass undefined to restore node:fs.
+///
+/// # Arguments
+///
+/// * `callbacks` - Object with readFileSync, writeFileSync, appendFileSync, renameSync,
+///   unlinkSync and existsSync callbacks
+///
+/// # Errors
+///
+/// Returns JsError if callbacks is neither an object nor undefined
+#[wasm_bindgen]
+pub fn set_node_fs(callbacks: JsValue) -> Result<(), JsError> {
+    if !(callbacks.is_undefined() || callbacks.is_null() || callbacks.is_object()) {
+        return Err(JsError::new("File system callbacks must be an object"));
+    }
+    set_fs(callbacks);
+    Ok(())
 }
 
 #[wasm_bindgen(module = "/src/sdk/masp/masp.node.js")]
 extern "C" {
+    #[wasm_bindgen(js_name = "setFs")]
+    fn set_fs(callbacks: JsValue);
+
     #[wasm_bindgen(catch, js_name = "writeFileSync")]
     fn write_file_sync(path: JsValue, content: JsValue) -> Result<JsValue, JsValue>;
 
@@ -7,6 +7,7 @@ use namada_sdk::ShieldedWallet;
 use rexie::{Error, ObjectStore, Rexie, TransactionMode};
 use wasm_bindgen::{JsError, JsValue};
 
+use super::prover_cache::ProverCache;
 use crate::utils::to_bytes;
 
 const DB_PREFIX: &str = "namada_sdk::MASP";
@@ -21,6 +22,8 @@ pub struct WebShieldedUtils {
     spend_param_bytes: Vec<u8>,
     output_param_bytes: Vec<u8>,
     convert_param_bytes: Vec<u8>,
+    #[borsh(skip)]
+    prover: ProverCache,
 }
 
 impl WebShieldedUtils {
@@ -33,6 +36,7 @@ impl WebShieldedUtils {
             spend_param_bytes,
             output_param_bytes,
             convert_param_bytes,
+            prover: ProverCache::default(),
         };
 
         let db = Self::build_database().await?;
@@ -132,11 +136,13 @@ fn get_confirmed(status: &ContextSyncStatus) -> bool {
 #[async_trait(?Send)]
 impl ShieldedUtils for WebShieldedUtils {
     fn local_tx_prover(&self) -> LocalTxProver {
-        LocalTxProver::from_bytes(
-            &self.spend_param_bytes,
-            &self.output_param_bytes,
-            &self.convert_param_bytes,
-        )
+        self.prover.get_or_init(|| {
+            LocalTxProver::from_bytes(
+                &self.spend_param_bytes,
+                &self.output_param_bytes,
+                &self.convert_param_bytes,
+            )
+        })
     }
 
     async fn load<U: ShieldedUtils>(
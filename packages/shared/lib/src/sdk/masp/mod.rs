@@ -1,7 +1,7 @@
-#[cfg(feature = "web")]
+#[cfg(not(feature = "nodejs"))]
 mod masp_web;
 
-#[cfg(feature = "web")]
+#[cfg(not(feature = "nodejs"))]
 pub use masp_web::WebShieldedUtils as JSShieldedUtils;
 
 #[cfg(feature = "nodejs")]
@@ -10,4 +10,9 @@ mod masp_node;
 #[cfg(feature = "nodejs")]
 pub use masp_node::NodeShieldedUtils as JSShieldedUtils;
 
+pub mod block_cache;
+pub mod build_cache;
+pub mod prover_cache;
+pub mod signing_plan;
 pub mod sync;
+pub mod verify;
@@ -0,0 +1,37 @@
+//! Reuse of the MASP prover context across shielded builds
+//!
+//! Parsing the MASP parameters and preparing the verifying keys dominates the latency of a
+//! shielded build. The prepared LocalTxProver is kept by the ShieldedUtils, which are shared
+//! by clones of the shielded context, so only the first build pays for it.
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use namada_sdk::masp_proofs::prover::LocalTxProver;
+
+#[derive(Clone, Default)]
+pub struct ProverCache(Rc<RefCell<Option<LocalTxProver>>>);
+
+impl ProverCache {
+    /// Returns the cached prover, building it with `init` on first use
+    pub fn get_or_init(&self, init: impl FnOnce() -> LocalTxProver) -> LocalTxProver {
+        self.0.borrow_mut().get_or_insert_with(init).clone()
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.0.borrow().is_some()
+    }
+
+    /// Drops the cached prover, e.g. when the parameters change
+    pub fn clear(&self) {
+        self.0.borrow_mut().take();
+    }
+}
+
+impl fmt::Debug for ProverCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProverCache")
+            .field("initialized", &self.is_initialized())
+            .finish()
+    }
+}
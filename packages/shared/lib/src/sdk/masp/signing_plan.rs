@@ -0,0 +1,343 @@
+//! Signing plans of MASP transactions
+//!
+//! The spends of a MASP transaction are shuffled when it's built, so the spend description
+//! at a position of the transaction is matched to the builder input holding its key through
+//! the metadata of the MaspBuilder section. Signers which don't hold the builder, e.g. a
+//! Ledger or a remote signing service, are given a plan instead: the sighash every spend
+//! authorization signs, and for each spend description the builder input and viewing key
+//! it belongs to.
+//!
+//! Signatures made from a plan are applied with apply_spend_signatures, which checks each
+//! of them and the binding signature of the resulting bundle, so a bad substitution fails
+//! here rather than at broadcast.
+use std::ops::Deref;
+
+use namada_sdk::masp::MaspTxId;
+use namada_sdk::masp_primitives::constants::{
+    SPENDING_KEY_GENERATOR, VALUE_COMMITMENT_RANDOMNESS_GENERATOR,
+};
+use namada_sdk::masp_primitives::jubjub;
+use namada_sdk::masp_primitives::sapling::redjubjub::{PrivateKey, PublicKey, Signature};
+use namada_sdk::masp_primitives::transaction::components::sapling::fees::InputView;
+use namada_sdk::masp_primitives::transaction::sighash::{signature_hash, SignableInput};
+use namada_sdk::masp_primitives::transaction::txid::TxIdDigester;
+use namada_sdk::masp_primitives::transaction::{Transaction as MaspTransaction, TransactionData};
+use namada_sdk::tx::{Section, Tx};
+use namada_sdk::ExtendedViewingKey;
+use rand::rngs::OsRng;
+use serde::Serialize;
+use thiserror::Error;
+use wasm_bindgen::JsError;
+
+use super::verify::{verify_proofs, VerifyingKeys};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SigningPlanError {
+    #[error("Tx has no MASP builder for MASP transaction {0}")]
+    MissingBuilder(String),
+    #[error("MASP builder metadata maps {0} spends, but the transaction has {1}")]
+    SpendCount(usize, usize),
+    #[error("MASP transaction has no spend at position {0}")]
+    UnknownSpend(usize),
+    #[error("Signature of the spend at position {0} is invalid")]
+    InvalidSpendSignature(usize),
+    #[error("Binding signing key is malformed")]
+    MalformedBindingKey,
+    #[error("MASP transaction doesn't verify with the signatures and binding signature")]
+    InvalidBundle,
+}
+
+/// Spend description to authorize
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedSpend {
+    /// Position of the spend description in the MASP transaction
+    pub tx_index: usize,
+    /// Position of the input in the builder
+    pub builder_index: usize,
+    /// Viewing key of the note, to pick the spending key
+    pub viewing_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaspSigningPlan {
+    /// Borsh serialized MaspTxId of the MASP transaction
+    pub masp_tx_id: Vec<u8>,
+    /// Hex encoded sighash signed by the spend authorizations and the binding signature
+    pub sighash: String,
+    pub spends: Vec<PlannedSpend>,
+}
+
+/// Returns the sighash of a MASP transaction. The txid digest doesn't cover the
+/// authorizations, so it's the same before and after signing.
+pub fn sighash(masp_tx: &MaspTransaction) -> [u8; 32] {
+    let tx_data = masp_tx.deref();
+    let txid_parts = tx_data.digest(TxIdDigester);
+    *signature_hash(tx_data, &SignableInput::Shielded, &txid_parts).as_ref()
+}
+
+/// Reverses the spend metadata of a builder: maps the position of each spend description
+/// in the transaction to the position of its input in the builder
+///
+/// # Arguments
+///
+/// * `spend_index` - Position in the transaction of the nth builder input
+/// * `spends` - Number of spend descriptions of the transaction
+///
+/// # Errors
+///
+/// Returns SigningPlanError::SpendCount if the metadata doesn't map every spend exactly once
+pub fn descriptor_map(
+    spend_index: impl Fn(usize) -> Option<usize>,
+    spends: usize,
+) -> Result<Vec<usize>, SigningPlanError> {
+    let mut descriptors: Vec<Option<usize>> = vec![None; spends];
+    let mut mapped = 0;
+    while let Some(tx_index) = spend_index(mapped) {
+        match descriptors.get_mut(tx_index) {
+            Some(descriptor @ None) => *descriptor = Some(mapped),
+            _ => return Err(SigningPlanError::SpendCount(mapped + 1, spends)),
+        }
+        mapped += 1;
+    }
+
+    descriptors
+        .into_iter()
+        .collect::<Option<Vec<usize>>>()
+        .ok_or(SigningPlanError::SpendCount(mapped, spends))
+}
+
+/// Returns the signing plans of the MASP transactions of a tx
+///
+/// # Errors
+///
+/// Returns SigningPlanError if a MASP transaction has no builder, or the builder doesn't
+/// match its spends
+pub fn signing_plans(tx: &Tx) -> Result<Vec<MaspSigningPlan>, SigningPlanError> {
+    let masp_txs = tx.sections.iter().filter_map(|section| match section {
+        Section::MaspTx(masp_tx) => Some(masp_tx),
+        _ => None,
+    });
+
+    let mut plans = vec![];
+    for masp_tx in masp_txs {
+        let masp_tx_id = MaspTxId::from(masp_tx.txid());
+        let builder = tx
+            .sections
+            .iter()
+            .find_map(|section| match section {
+                Section::MaspBuilder(builder) if builder.target == masp_tx_id => Some(builder),
+                _ => None,
+            })
+            .ok_or_else(|| SigningPlanError::MissingBuilder(masp_tx_id.to_string()))?;
+
+        let spends = masp_tx
+            .sapling_bundle()
+            .map_or(0, |bundle| bundle.shielded_spends.len());
+        let inputs = builder.builder.sapling_inputs();
+        if inputs.len() != spends {
+            return Err(SigningPlanError::SpendCount(inputs.len(), spends));
+        }
+        let descriptors = descriptor_map(|i| builder.metadata.spend_index(i), spends)?;
+
+        plans.push(MaspSigningPlan {
+            masp_tx_id: namada_sdk::borsh::to_vec(&masp_tx_id)
+                .expect("MaspTxId serialization shouldn't fail"),
+            sighash: hex::encode(sighash(masp_tx)),
+            spends: descriptors
+                .into_iter()
+                .enumerate()
+                .map(|(tx_index, builder_index)| PlannedSpend {
+                    tx_index,
+                    builder_index,
+                    viewing_key: ExtendedViewingKey::from(*inputs[builder_index].key()).to_string(),
+                })
+                .collect(),
+        });
+    }
+
+    Ok(plans)
+}
+
+/// Returns the message signed under a key: the key followed by the sighash, as for both
+/// spend authorization and binding signatures
+fn signed_message(key: &PublicKey, sighash: &[u8; 32]) -> [u8; 64] {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(&key.0.to_bytes());
+    data[32..].copy_from_slice(sighash);
+    data
+}
+
+/// Checks a spend authorization signature against the randomized key of its spend
+fn verify_spend_signature(rk: &PublicKey, sighash: &[u8; 32], signature: &Signature) -> bool {
+    rk.verify(
+        &signed_message(rk, sighash),
+        signature,
+        SPENDING_KEY_GENERATOR,
+    )
+}
+
+/// Computes the binding signature of a bundle from its binding signing key, the sum of the
+/// value commitment randomness of its descriptions
+fn binding_signature(bsk: &[u8; 32], sighash: &[u8; 32]) -> Result<Signature, SigningPlanError> {
+    let bsk = Option::from(jubjub::Fr::from_bytes(bsk))
+        .map(PrivateKey)
+        .ok_or(SigningPlanError::MalformedBindingKey)?;
+    let bvk = PublicKey::from_private(&bsk, VALUE_COMMITMENT_RANDOMNESS_GENERATOR);
+
+    Ok(bsk.sign(
+        &signed_message(&bvk, sighash),
+        &mut OsRng,
+        VALUE_COMMITMENT_RANDOMNESS_GENERATOR,
+    ))
+}
+
+/// Substitutes spend authorization signatures made from a signing plan. Each signature is
+/// checked against the randomized key of its spend, over the key and the sighash, then the
+/// whole bundle is verified. If
+/// the binding signature doesn't verify and the binding signing key is given, it's
+/// recomputed and the bundle verified again.
+///
+/// # Arguments
+///
+/// * `masp_tx` - MASP transaction to sign
+/// * `signatures` - Position of the spend description and its signature
+/// * `bsk` - Binding signing key of the bundle
+/// * `keys` - MASP verifying keys
+///
+/// # Errors
+///
+/// Returns JsError if a signature is invalid or for an unknown spend, or the signed bundle
+/// doesn't verify
+pub fn apply_spend_signatures(
+    masp_tx: &MaspTransaction,
+    signatures: &[(usize, Signature)],
+    bsk: Option<[u8; 32]>,
+    keys: &VerifyingKeys,
+) -> Result<MaspTransaction, JsError> {
+    let sighash = sighash(masp_tx);
+    let tx_data = masp_tx.deref();
+    let mut bundle = tx_data
+        .sapling_bundle()
+        .cloned()
+        .ok_or(SigningPlanError::UnknownSpend(0))?;
+
+    for (tx_index, signature) in signatures {
+        let spend = bundle
+            .shielded_spends
+            .get_mut(*tx_index)
+            .ok_or(SigningPlanError::UnknownSpend(*tx_index))?;
+        if !verify_spend_signature(&spend.rk, &sighash, signature) {
+            return Err(SigningPlanError::InvalidSpendSignature(*tx_index).into());
+        }
+        spend.spend_auth_sig = *signature;
+    }
+
+    let freeze = |bundle| {
+        TransactionData::from_parts(
+            tx_data.version(),
+            tx_data.consensus_branch_id(),
+            tx_data.lock_time(),
+            tx_data.expiry_height(),
+            tx_data.transparent_bundle().cloned(),
+            Some(bundle),
+        )
+        .freeze()
+    };
+    let signed = freeze(bundle.clone())?;
+    if verify_proofs(&signed, keys)? {
+        return Ok(signed);
+    }
+
+    let bsk = bsk.ok_or(SigningPlanError::InvalidBundle)?;
+    bundle.authorization.binding_sig = binding_signature(&bsk, &sighash)?;
+    let signed = freeze(bundle)?;
+    if !verify_proofs(&signed, keys)? {
+        return Err(SigningPlanError::InvalidBundle.into());
+    }
+
+    Ok(signed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn reverses_spend_metadata() {
+        // Builder inputs 0, 1 and 2 were shuffled to positions 2, 0 and 1
+        let shuffled = [2, 0, 1];
+        let descriptors = descriptor_map(|i| shuffled.get(i).copied(), 3).unwrap();
+        assert_eq!(descriptors, vec![1, 2, 0]);
+
+        assert_eq!(descriptor_map(|_| None, 0), Ok(vec![]));
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_mismatched_metadata() {
+        let missing = [1, 0];
+        assert_eq!(
+            descriptor_map(|i| missing.get(i).copied(), 3),
+            Err(SigningPlanError::SpendCount(2, 3))
+        );
+
+        let duplicate = [0, 0];
+        assert_eq!(
+            descriptor_map(|i| duplicate.get(i).copied(), 2),
+            Err(SigningPlanError::SpendCount(2, 2))
+        );
+
+        let out_of_range = [0, 5];
+        assert_eq!(
+            descriptor_map(|i| out_of_range.get(i).copied(), 2),
+            Err(SigningPlanError::SpendCount(2, 2))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn verifies_spend_signatures_over_rk_and_sighash() {
+        let rsk = PrivateKey(jubjub::Fr::from(5u64));
+        let rk = PublicKey::from_private(&rsk, SPENDING_KEY_GENERATOR);
+        let sighash = [7u8; 32];
+
+        // As signed by spend authorizations, e.g. of a Ledger
+        let signature = rsk.sign(
+            &signed_message(&rk, &sighash),
+            &mut OsRng,
+            SPENDING_KEY_GENERATOR,
+        );
+        assert!(verify_spend_signature(&rk, &sighash, &signature));
+        assert!(!verify_spend_signature(&rk, &[8u8; 32], &signature));
+
+        // Signatures of the bare sighash are rejected
+        let bare = rsk.sign(&sighash, &mut OsRng, SPENDING_KEY_GENERATOR);
+        assert!(!verify_spend_signature(&rk, &sighash, &bare));
+
+        let other =
+            PublicKey::from_private(&PrivateKey(jubjub::Fr::from(6u64)), SPENDING_KEY_GENERATOR);
+        assert!(!verify_spend_signature(&other, &sighash, &signature));
+    }
+
+    #[wasm_bindgen_test]
+    fn binding_signature_verifies_under_bvk() {
+        let bsk = jubjub::Fr::from(42u64);
+        let bvk = PublicKey::from_private(&PrivateKey(bsk), VALUE_COMMITMENT_RANDOMNESS_GENERATOR);
+        let sighash = [7u8; 32];
+        let signature = binding_signature(&bsk.to_bytes(), &sighash).unwrap();
+
+        let data = signed_message(&bvk, &sighash);
+        assert!(bvk.verify(&data, &signature, VALUE_COMMITMENT_RANDOMNESS_GENERATOR));
+        assert!(!bvk.verify(
+            &[0u8; 64],
+            &signature,
+            VALUE_COMMITMENT_RANDOMNESS_GENERATOR
+        ));
+
+        assert_eq!(
+            binding_signature(&[0xff; 32], &sighash).err(),
+            Some(SigningPlanError::MalformedBindingKey)
+        );
+    }
+}
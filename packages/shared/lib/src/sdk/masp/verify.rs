@@ -0,0 +1,92 @@
+//! Verification of MASP transactions
+//!
+//! Checks the proofs and signatures of a sapling bundle like the chain does, so a bad bundle
+//! is rejected before it's broadcast or accepted from a third party.
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::masp_primitives::transaction::components::sapling::{Authorized, Bundle};
+use namada_sdk::masp_primitives::transaction::Transaction as MaspTransaction;
+use namada_sdk::masp_proofs::bellman::groth16::{PreparedVerifyingKey, Proof};
+use namada_sdk::masp_proofs::bls12_381::Bls12;
+use namada_sdk::masp_proofs::sapling::SaplingVerificationContext;
+use wasm_bindgen::JsError;
+
+use super::signing_plan;
+
+/// Verifying keys of the MASP circuits
+pub struct VerifyingKeys {
+    pub spend: PreparedVerifyingKey<Bls12>,
+    pub convert: PreparedVerifyingKey<Bls12>,
+    pub output: PreparedVerifyingKey<Bls12>,
+}
+
+impl VerifyingKeys {
+    /// Returns the verifying keys of the MASP params held by the shielded utils. The params
+    /// have to be loaded first.
+    pub fn load<U: ShieldedUtils>(utils: &U) -> Self {
+        let (spend, convert, output) = utils.local_tx_prover().verifying_keys();
+        Self {
+            spend,
+            convert,
+            output,
+        }
+    }
+}
+
+/// Checks spend, convert and output proofs, spend authorization signatures and the binding
+/// signature of a sapling bundle, all signed over the given sighash
+pub fn verify_bundle(
+    bundle: &Bundle<Authorized>,
+    sighash: &[u8; 32],
+    keys: &VerifyingKeys,
+) -> Result<bool, JsError> {
+    let mut ctx = SaplingVerificationContext::new(true);
+
+    for spend in bundle.shielded_spends.iter() {
+        let zkproof = Proof::read(&spend.zkproof[..])?;
+        if !ctx.check_spend(
+            spend.cv,
+            spend.anchor,
+            &spend.nullifier.0,
+            spend.rk,
+            sighash,
+            spend.spend_auth_sig,
+            zkproof,
+            &keys.spend,
+        ) {
+            return Ok(false);
+        }
+    }
+    for convert in bundle.shielded_converts.iter() {
+        let zkproof = Proof::read(&convert.zkproof[..])?;
+        if !ctx.check_convert(convert.cv, convert.anchor, zkproof, &keys.convert) {
+            return Ok(false);
+        }
+    }
+    for output in bundle.shielded_outputs.iter() {
+        let zkproof = Proof::read(&output.zkproof[..])?;
+        if !ctx.check_output(
+            output.cv,
+            output.cmu,
+            output.ephemeral_key.clone(),
+            zkproof,
+            &keys.output,
+        ) {
+            return Ok(false);
+        }
+    }
+
+    Ok(ctx.final_check(
+        bundle.value_balance.clone(),
+        sighash,
+        bundle.authorization.binding_sig,
+    ))
+}
+
+/// Checks the sapling bundle of a MASP transaction against its sighash. A transaction
+/// without a sapling bundle has nothing to verify.
+pub fn verify_proofs(masp_tx: &MaspTransaction, keys: &VerifyingKeys) -> Result<bool, JsError> {
+    match masp_tx.sapling_bundle() {
+        Some(bundle) => verify_bundle(bundle, &signing_plan::sighash(masp_tx), keys),
+        None => Ok(true),
+    }
+}
@@ -0,0 +1,35 @@
+//! Detailed errors of MASP transaction building
+//!
+//! Note selection of the SDK fails with a generic error. The shortfall per token of every
+//! source is computed afterwards and returned as a JSON error message, so the UI can suggest
+//! syncing or converting notes of older MASP epochs.
+use serde::Serialize;
+use wasm_bindgen::JsError;
+
+/// Shortfall of a token of a shielded source, with raw amounts
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetShortfall {
+    pub token: String,
+    pub required: String,
+    /// Balance with all notes converted to the current MASP epoch
+    pub available: String,
+    pub shortfall: String,
+    /// Balances of notes from older MASP epochs, as (epoch, amount) before conversion
+    pub unconverted: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MaspBuildError {
+    InsufficientFunds { shortfalls: Vec<AssetShortfall> },
+}
+
+impl From<MaspBuildError> for JsError {
+    fn from(error: MaspBuildError) -> Self {
+        match serde_json::to_string(&error) {
+            Ok(message) => JsError::new(&message),
+            Err(e) => JsError::new(&e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Size and gas estimates of MASP transactions
+//!
+//! Proving takes long enough that a shielded tx should be known to fit in a block before it's
+//! built. Both the size and the verification gas of a MASP transaction are dominated by its
+//! descriptions, so they are estimated from the numbers of spends, converts and outputs.
+//! Sizes are those of the serialized v5 descriptions and verification gas is charged as by
+//! the MASP VP: a fixed cost per batch of descriptions of a kind, a variable cost per
+//! description and the checks of each description, see namada_sdk::gas. The gas of the rest
+//! of the tx depends on its wasm code and storage, so its gas limit without the MASP
+//! transaction is given by the caller, e.g. from the gas table of the indexer. The numbers of
+//! descriptions of a built tx are returned by Descriptions::of_tx.
+use namada_sdk::gas::{
+    MASP_CONVERT_CHECK_GAS, MASP_FINAL_CHECK_GAS, MASP_FIXED_CONVERT_GAS, MASP_FIXED_OUTPUT_GAS,
+    MASP_FIXED_SPEND_GAS, MASP_OUTPUT_CHECK_GAS, MASP_SPEND_CHECK_GAS, MASP_VARIABLE_CONVERT_GAS,
+    MASP_VARIABLE_OUTPUT_GAS, MASP_VARIABLE_SPEND_GAS, TX_SIZE_GAS_PER_BYTE,
+};
+use namada_sdk::masp_primitives::transaction::components::sapling::GROTH_PROOF_SIZE;
+use namada_sdk::tx::{Section, Tx};
+use serde::Serialize;
+
+/// Size of a Jubjub point, nullifier, note commitment or ephemeral key
+const FIELD_BYTES: u64 = 32;
+/// Size of a RedJubjub signature
+const SIGNATURE_BYTES: u64 = 64;
+/// Size of an encrypted MASP note: lead byte, diversifier, value, rseed, asset type and memo,
+/// with the AEAD tag
+const ENC_CIPHERTEXT_BYTES: u64 = 1 + 11 + 8 + 32 + 32 + 512 + 16;
+/// Size of the encrypted outgoing key material
+const OUT_CIPHERTEXT_BYTES: u64 = 80;
+
+/// Serialized size of a spend description: value commitment, nullifier, randomized key,
+/// proof and spend authorization signature
+pub const SPEND_BYTES: u64 = 3 * FIELD_BYTES + GROTH_PROOF_SIZE as u64 + SIGNATURE_BYTES;
+/// Serialized size of a convert description: value commitment and proof
+pub const CONVERT_BYTES: u64 = FIELD_BYTES + GROTH_PROOF_SIZE as u64;
+/// Serialized size of an output description: value commitment, note commitment, ephemeral
+/// key, note and outgoing ciphertexts and proof
+pub const OUTPUT_BYTES: u64 =
+    3 * FIELD_BYTES + ENC_CIPHERTEXT_BYTES + OUT_CIPHERTEXT_BYTES + GROTH_PROOF_SIZE as u64;
+/// Size of the rest of the tx: header, code and data sections, signatures and the MASP
+/// transaction framing, including the anchors and binding signature
+pub const BASE_BYTES: u64 = 2_048;
+
+/// Numbers of descriptions of a MASP transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Descriptions {
+    pub spends: u64,
+    pub converts: u64,
+    pub outputs: u64,
+}
+
+impl Descriptions {
+    /// Returns the numbers of descriptions of the MASP transactions of a built tx
+    pub fn of_tx(tx: &Tx) -> Descriptions {
+        tx.sections
+            .iter()
+            .filter_map(|section| match section {
+                Section::MaspTx(masp_tx) => masp_tx.sapling_bundle(),
+                _ => None,
+            })
+            .fold(Descriptions::default(), |total, bundle| Descriptions {
+                spends: total.spends + bundle.shielded_spends.len() as u64,
+                converts: total.converts + bundle.shielded_converts.len() as u64,
+                outputs: total.outputs + bundle.shielded_outputs.len() as u64,
+            })
+    }
+
+    /// Returns the raw gas of verifying the descriptions
+    fn verification_gas(&self) -> u64 {
+        let batch = |count: u64, fixed: u64, variable: u64, check: u64| match count {
+            0 => 0,
+            count => fixed.saturating_add(count.saturating_mul(variable.saturating_add(check))),
+        };
+        batch(
+            self.spends,
+            MASP_FIXED_SPEND_GAS,
+            MASP_VARIABLE_SPEND_GAS,
+            MASP_SPEND_CHECK_GAS,
+        )
+        .saturating_add(batch(
+            self.converts,
+            MASP_FIXED_CONVERT_GAS,
+            MASP_VARIABLE_CONVERT_GAS,
+            MASP_CONVERT_CHECK_GAS,
+        ))
+        .saturating_add(batch(
+            self.outputs,
+            MASP_FIXED_OUTPUT_GAS,
+            MASP_VARIABLE_OUTPUT_GAS,
+            MASP_OUTPUT_CHECK_GAS,
+        ))
+        .saturating_add(MASP_FINAL_CHECK_GAS)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaspTxEstimate {
+    /// Estimated size of the tx in bytes
+    pub bytes: u64,
+    /// Estimated minimum gas limit of the tx
+    pub gas_limit: u64,
+    pub max_tx_bytes: u64,
+    pub max_block_gas: u64,
+    /// Whether the tx is expected to exceed the maximum tx size
+    pub exceeds_tx_bytes: bool,
+    /// Whether the tx is expected to exceed the gas of a block
+    pub exceeds_block_gas: bool,
+}
+
+/// Estimates the size and minimum gas limit of a tx with the descriptions, and checks them
+/// against the chain limits
+///
+/// # Arguments
+///
+/// * `descriptions` - Numbers of spends, converts and outputs
+/// * `base_gas_limit` - Gas limit of the tx without its MASP transaction
+/// * `gas_scale` - Raw gas per unit of gas limit
+/// * `max_tx_bytes` - Maximum size of a tx
+/// * `max_block_gas` - Maximum gas of a block, in units of gas limit
+pub fn estimate(
+    descriptions: Descriptions,
+    base_gas_limit: u64,
+    gas_scale: u64,
+    max_tx_bytes: u64,
+    max_block_gas: u64,
+) -> MaspTxEstimate {
+    let Descriptions {
+        spends,
+        converts,
+        outputs,
+    } = descriptions;
+    let masp_bytes = spends
+        .saturating_mul(SPEND_BYTES)
+        .saturating_add(converts.saturating_mul(CONVERT_BYTES))
+        .saturating_add(outputs.saturating_mul(OUTPUT_BYTES));
+    let bytes = BASE_BYTES.saturating_add(masp_bytes);
+    // The size of the rest of the tx is already charged by the base gas limit
+    let masp_gas = descriptions
+        .verification_gas()
+        .saturating_add(masp_bytes.saturating_mul(TX_SIZE_GAS_PER_BYTE));
+    let gas_limit = base_gas_limit.saturating_add(masp_gas.div_ceil(gas_scale.max(1)));
+
+    MaspTxEstimate {
+        bytes,
+        gas_limit,
+        max_tx_bytes,
+        max_block_gas,
+        exceeds_tx_bytes: bytes > max_tx_bytes,
+        exceeds_block_gas: gas_limit > max_block_gas,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const BASE_GAS_LIMIT: u64 = 50_000;
+    const GAS_SCALE: u64 = 10_000;
+
+    #[wasm_bindgen_test]
+    fn estimates_grow_with_descriptions() {
+        let transfer = Descriptions {
+            spends: 2,
+            converts: 1,
+            outputs: 2,
+        };
+        let small = estimate(transfer, BASE_GAS_LIMIT, GAS_SCALE, 1_048_576, 20_000_000);
+        assert_eq!(
+            small.bytes,
+            BASE_BYTES + 2 * SPEND_BYTES + CONVERT_BYTES + 2 * OUTPUT_BYTES
+        );
+        assert!(!small.exceeds_tx_bytes && !small.exceeds_block_gas);
+
+        let consolidation = Descriptions {
+            spends: 1_000,
+            ..transfer
+        };
+        let large = estimate(
+            consolidation,
+            BASE_GAS_LIMIT,
+            GAS_SCALE,
+            262_144,
+            small.gas_limit + 1,
+        );
+        assert!(large.gas_limit > small.gas_limit);
+        assert!(large.exceeds_tx_bytes);
+        assert!(large.exceeds_block_gas);
+    }
+
+    #[wasm_bindgen_test]
+    fn charges_fixed_verification_gas_per_batch() {
+        let one = Descriptions {
+            spends: 1,
+            ..Descriptions::default()
+        };
+        let two = Descriptions { spends: 2, ..one };
+        assert_eq!(
+            one.verification_gas(),
+            MASP_FIXED_SPEND_GAS
+                + MASP_VARIABLE_SPEND_GAS
+                + MASP_SPEND_CHECK_GAS
+                + MASP_FINAL_CHECK_GAS
+        );
+        assert_eq!(
+            two.verification_gas() - one.verification_gas(),
+            MASP_VARIABLE_SPEND_GAS + MASP_SPEND_CHECK_GAS
+        );
+        assert_eq!(SPEND_BYTES, 352);
+        assert_eq!(CONVERT_BYTES, 224);
+        assert_eq!(OUTPUT_BYTES, 980);
+    }
+
+    #[wasm_bindgen_test]
+    fn adds_masp_gas_to_base_gas_limit() {
+        let empty = |gas_scale| {
+            estimate(
+                Descriptions::default(),
+                BASE_GAS_LIMIT,
+                gas_scale,
+                u64::MAX,
+                u64::MAX,
+            )
+        };
+        assert_eq!(
+            empty(GAS_SCALE).gas_limit,
+            BASE_GAS_LIMIT + MASP_FINAL_CHECK_GAS.div_ceil(GAS_SCALE)
+        );
+        assert_eq!(empty(0).gas_limit, BASE_GAS_LIMIT + MASP_FINAL_CHECK_GAS);
+        assert_eq!(empty(GAS_SCALE).bytes, BASE_BYTES);
+    }
+}
@@ -0,0 +1,486 @@
+//! Parsing of memos following known conventions
+//!
+//! Memos are free text, but some follow conventions that history views render as labels
+//! rather than raw strings:
+//! - IBC shielding memos, the hex encoded Borsh IbcShieldingData of a shielded IBC transfer
+//! - packet forward middleware memos, JSON forwarding the transfer to other chains, possibly
+//!   over several hops
+//! - exchange deposit tags, short numeric ids identifying the depositor at an exchange
+//! - encrypted memos, see encrypted_memo.rs
+//!
+//! Anything else is plain text.
+//!
+//! Memos forwarding a transfer are built from structured fields rather than written by hand,
+//! see build_ibc_memo: a forward with a typo in a key is taken for text by the middleware,
+//! leaving the funds on the intermediate chain.
+#[cfg(feature = "web")]
+use std::str::FromStr;
+
+#[cfg(feature = "web")]
+use gloo_utils::format::JsValueSerdeExt;
+#[cfg(feature = "web")]
+use namada_sdk::borsh::BorshDeserialize;
+#[cfg(feature = "web")]
+use namada_sdk::ibc::core::host::types::identifiers::{ChannelId, PortId};
+#[cfg(feature = "web")]
+use namada_sdk::ibc::IbcShieldingData;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "web")]
+use serde_json::json;
+use serde_json::Value;
+#[cfg(feature = "web")]
+use thiserror::Error;
+#[cfg(feature = "web")]
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+
+#[cfg(feature = "web")]
+use super::encrypted_memo::is_encrypted_memo;
+#[cfg(feature = "web")]
+use super::error::excerpt;
+use super::error::JsError;
+#[cfg(feature = "web")]
+use crate::utils::to_js_result;
+
+/// Deposit tags longer than this are taken for text
+#[cfg(feature = "web")]
+const MAX_DEPOSIT_TAG_LEN: usize = 20;
+
+/// Hop of a packet forward
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ForwardHop {
+    pub receiver: String,
+    pub port: String,
+    pub channel: String,
+}
+
+#[cfg(feature = "web")]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ParsedMemo {
+    Text { text: String },
+    Encrypted,
+    IbcShielding { shielded_outputs: usize },
+    PacketForward { hops: Vec<ForwardHop> },
+    DepositTag { tag: String },
+}
+
+#[cfg(feature = "web")]
+impl ParsedMemo {
+    /// Short description of the memo for history views
+    pub fn label(&self) -> String {
+        match self {
+            ParsedMemo::Text { text } => text.clone(),
+            ParsedMemo::Encrypted => "Encrypted memo".to_string(),
+            ParsedMemo::IbcShielding { shielded_outputs } => {
+                format!("Shielding to {} shielded output(s)", shielded_outputs)
+            }
+            ParsedMemo::PacketForward { hops } => {
+                let route: Vec<String> = hops
+                    .iter()
+                    .map(|hop| format!("{} via {}", hop.receiver, hop.channel))
+                    .collect();
+                format!("Forwarded to {}", route.join(", then "))
+            }
+            ParsedMemo::DepositTag { tag } => format!("Deposit tag {}", tag),
+        }
+    }
+}
+
+/// Returns the hops of a packet forward memo, nested forwards in order
+fn forward_hops(json: &Value) -> Option<Vec<ForwardHop>> {
+    let mut hops = vec![];
+    let mut next = json;
+    while let Some(forward) = next.get("forward") {
+        let field = |name: &str| forward.get(name)?.as_str().map(str::to_string);
+        hops.push(ForwardHop {
+            receiver: field("receiver")?,
+            port: field("port")?,
+            channel: field("channel")?,
+        });
+        next = match forward.get("next") {
+            // `next` is either an object or its JSON string
+            Some(Value::String(nested)) => match serde_json::from_str(nested) {
+                Ok(nested) => return forward_hops(&nested).map(|nested| [hops, nested].concat()),
+                Err(_) => break,
+            },
+            Some(nested) => nested,
+            None => break,
+        };
+    }
+    (!hops.is_empty()).then_some(hops)
+}
+
+#[cfg(feature = "web")]
+fn shielded_outputs(memo: &str) -> Option<usize> {
+    let bytes = hex::decode(memo).ok()?;
+    let data = IbcShieldingData::try_from_slice(&bytes).ok()?;
+    Some(
+        data.0
+            .sapling_bundle()
+            .map(|bundle| bundle.shielded_outputs.len())
+            .unwrap_or_default(),
+    )
+}
+
+/// Parses a memo following one of the known conventions, or as text
+#[cfg(feature = "web")]
+pub fn parse(memo: &str) -> ParsedMemo {
+    let trimmed = memo.trim();
+    if is_encrypted_memo(trimmed) {
+        return ParsedMemo::Encrypted;
+    }
+    if !trimmed.is_empty()
+        && trimmed.len() <= MAX_DEPOSIT_TAG_LEN
+        && trimmed.bytes().all(|b| b.is_ascii_digit())
+    {
+        return ParsedMemo::DepositTag {
+            tag: trimmed.to_string(),
+        };
+    }
+    if let Some(hops) = serde_json::from_str::<Value>(trimmed)
+        .ok()
+        .as_ref()
+        .and_then(forward_hops)
+    {
+        return ParsedMemo::PacketForward { hops };
+    }
+    if let Some(shielded_outputs) = shielded_outputs(trimmed) {
+        return ParsedMemo::IbcShielding { shielded_outputs };
+    }
+    ParsedMemo::Text {
+        text: memo.to_string(),
+    }
+}
+
+#[cfg(feature = "web")]
+#[derive(Debug, Error, PartialEq)]
+pub enum IbcMemoError {
+    #[error("Forward hop {0} has an empty receiver")]
+    EmptyHopReceiver(usize),
+    #[error("Forward hop {0} has an invalid port: {1}")]
+    InvalidPort(usize, String),
+    #[error("Forward hop {0} has an invalid channel: {1}")]
+    InvalidChannel(usize, String),
+    #[error("Forwarding needs a fallback receiver on the first chain")]
+    MissingFallbackReceiver,
+    #[error("Fallback receiver is only used when forwarding")]
+    UnusedFallbackReceiver,
+    #[error("Last forward hop receiver {0} differs from the receiver {1}")]
+    ReceiverMismatch(String, String),
+    #[error("Wasm hook contract {0} differs from the final receiver {1}")]
+    ContractMismatch(String, String),
+    #[error("Wasm hook msg must be a JSON object")]
+    InvalidWasmMsg,
+}
+
+/// Contract call of the ibc-hooks middleware on the final chain
+#[cfg(feature = "web")]
+#[derive(Deserialize, Debug, Clone)]
+pub struct WasmHook {
+    pub contract: String,
+    pub msg: Value,
+}
+
+/// Structured fields of an IBC transfer memo
+#[cfg(feature = "web")]
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct IbcMemoFields {
+    /// Hops forwarding the transfer from the first chain, in order. The receiver of each hop
+    /// is on the chain it forwards to, the last one being the receiver of the transfer.
+    #[serde(default)]
+    pub forward: Vec<ForwardHop>,
+    /// Receiver of the transfer on the first chain, keeping the funds if forwarding fails
+    pub fallback_receiver: Option<String>,
+    pub wasm: Option<WasmHook>,
+}
+
+/// Memo and receiver of the MsgTransfer
+#[cfg(feature = "web")]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct IbcMemo {
+    pub receiver: String,
+    pub memo: String,
+}
+
+/// Builds the memo of an IBC transfer to `receiver` from its fields, checking that the funds
+/// reach the receiver. The MsgTransfer is sent to the fallback receiver when forwarding.
+///
+/// # Errors
+///
+/// Returns IbcMemoError if a hop is invalid, the fallback receiver is missing or unused, or
+/// the route doesn't end at the receiver or at the wasm hook contract
+#[cfg(feature = "web")]
+pub fn ibc_memo(receiver: &str, fields: IbcMemoFields) -> Result<IbcMemo, IbcMemoError> {
+    let IbcMemoFields {
+        forward,
+        fallback_receiver,
+        wasm,
+    } = fields;
+
+    for (i, hop) in forward.iter().enumerate() {
+        if hop.receiver.trim().is_empty() {
+            return Err(IbcMemoError::EmptyHopReceiver(i));
+        }
+        PortId::from_str(&hop.port)
+            .map_err(|_| IbcMemoError::InvalidPort(i, excerpt(&hop.port)))?;
+        ChannelId::from_str(&hop.channel)
+            .map_err(|_| IbcMemoError::InvalidChannel(i, excerpt(&hop.channel)))?;
+    }
+    let transfer_receiver = match (forward.last(), fallback_receiver) {
+        (None, None) => receiver.to_string(),
+        (None, Some(_)) => return Err(IbcMemoError::UnusedFallbackReceiver),
+        (Some(_), None) => return Err(IbcMemoError::MissingFallbackReceiver),
+        (Some(last), Some(_)) if last.receiver != receiver => {
+            return Err(IbcMemoError::ReceiverMismatch(
+                last.receiver.clone(),
+                receiver.to_string(),
+            ))
+        }
+        (Some(_), Some(fallback)) if fallback.trim().is_empty() => {
+            return Err(IbcMemoError::MissingFallbackReceiver)
+        }
+        (Some(_), Some(fallback)) => fallback,
+    };
+
+    // The ibc-hooks middleware only calls the contract a transfer is sent to
+    let mut next = match wasm {
+        Some(WasmHook { contract, .. }) if contract != receiver => {
+            return Err(IbcMemoError::ContractMismatch(
+                contract,
+                receiver.to_string(),
+            ))
+        }
+        Some(WasmHook { msg, .. }) if !msg.is_object() => return Err(IbcMemoError::InvalidWasmMsg),
+        Some(WasmHook { contract, msg }) => {
+            Some(json!({ "wasm": { "contract": contract, "msg": msg } }))
+        }
+        None => None,
+    };
+    for hop in forward.into_iter().rev() {
+        let mut forward = json!({
+            "receiver": hop.receiver,
+            "port": hop.port,
+            "channel": hop.channel,
+        });
+        if let Some(next) = next {
+            forward["next"] = next;
+        }
+        next = Some(json!({ "forward": forward }));
+    }
+
+    Ok(IbcMemo {
+        receiver: transfer_receiver,
+        memo: next.map(|memo| memo.to_string()).unwrap_or_default(),
+    })
+}
+
+/// Checks the memo of an IBC transfer. JSON memos with a forward must be complete packet
+/// forwards, else the forward is ignored and the funds stay on the first chain.
+///
+/// # Errors
+///
+/// Returns JsError if the memo has an incomplete forward
+pub fn validate_ibc_memo(memo: &str) -> Result<(), JsError> {
+    match serde_json::from_str::<Value>(memo.trim()) {
+        Ok(json) if json.get("forward").is_some() && forward_hops(&json).is_none() => Err(
+            JsError::new("IBC memo has an incomplete forward, build it from its fields instead"),
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(feature = "web")]
+#[derive(Serialize)]
+struct LabeledMemo {
+    #[serde(flatten)]
+    memo: ParsedMemo,
+    label: String,
+}
+
+/// Parses a memo, see parse. Returns the ParsedMemo with its label.
+///
+/// # Errors
+///
+/// Returns JsError if the result can't be serialized
+#[cfg(feature = "web")]
+#[wasm_bindgen]
+pub fn parse_memo(memo: &str) -> Result<JsValue, JsError> {
+    let memo = parse(memo);
+    to_js_result(LabeledMemo {
+        label: memo.label(),
+        memo,
+    })
+}
+
+/// Builds the memo of an IBC transfer from its fields, see ibc_memo. Returns the IbcMemo with
+/// the receiver to send the MsgTransfer to.
+///
+/// # Arguments
+///
+/// * `receiver` - Final receiver of the transfer
+/// * `fields` - IbcMemoFields
+///
+/// # Errors
+///
+/// Returns JsError if the fields are invalid
+#[cfg(feature = "web")]
+#[wasm_bindgen]
+pub fn build_ibc_memo(receiver: String, fields: JsValue) -> Result<JsValue, JsError> {
+    let fields: IbcMemoFields = fields.into_serde()?;
+    let memo = ibc_memo(&receiver, fields).map_err(|e| JsError::new(&e.to_string()))?;
+    to_js_result(memo)
+}
+
+#[cfg(all(test, feature = "web"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn parses_memo_conventions() {
+        assert_eq!(
+            parse(" 104857 "),
+            ParsedMemo::DepositTag {
+                tag: "104857".to_string()
+            }
+        );
+        assert_eq!(parse("enc1:00ff"), ParsedMemo::Encrypted);
+        assert_eq!(
+            parse("thanks for lunch"),
+            ParsedMemo::Text {
+                text: "thanks for lunch".to_string()
+            }
+        );
+        assert!(matches!(parse("ABCDEF0123"), ParsedMemo::Text { .. }));
+
+        let pfm = r#"{"forward":{"receiver":"osmo1receiver","port":"transfer","channel":"channel-1",
+            "next":"{\"forward\":{\"receiver\":\"cosmos1final\",\"port\":\"transfer\",\"channel\":\"channel-7\"}}"}}"#;
+        let parsed = parse(pfm);
+        let ParsedMemo::PacketForward { hops } = &parsed else {
+            panic!("Expected a packet forward memo");
+        };
+        assert_eq!(hops.len(), 2);
+        assert_eq!(hops[1].receiver, "cosmos1final");
+        assert_eq!(
+            parsed.label(),
+            "Forwarded to osmo1receiver via channel-1, then cosmos1final via channel-7"
+        );
+
+        // JSON without a complete forward is text
+        assert!(matches!(
+            parse(r#"{"forward":{"receiver":"osmo1receiver"}}"#),
+            ParsedMemo::Text { .. }
+        ));
+    }
+
+    fn hop(receiver: &str, channel: &str) -> ForwardHop {
+        ForwardHop {
+            receiver: receiver.to_string(),
+            port: "transfer".to_string(),
+            channel: channel.to_string(),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn builds_forward_memos() {
+        let fields = IbcMemoFields {
+            forward: vec![
+                hop("osmo1receiver", "channel-1"),
+                hop("cosmos1final", "channel-7"),
+            ],
+            fallback_receiver: Some("noble1fallback".to_string()),
+            wasm: None,
+        };
+        let IbcMemo { receiver, memo } = ibc_memo("cosmos1final", fields).unwrap();
+        assert_eq!(receiver, "noble1fallback");
+        assert!(validate_ibc_memo(&memo).is_ok());
+        assert_eq!(
+            parse(&memo),
+            ParsedMemo::PacketForward {
+                hops: vec![
+                    hop("osmo1receiver", "channel-1"),
+                    hop("cosmos1final", "channel-7")
+                ]
+            }
+        );
+
+        let direct = ibc_memo("osmo1final", IbcMemoFields::default()).unwrap();
+        assert_eq!(direct.receiver, "osmo1final");
+        assert_eq!(direct.memo, "");
+
+        let hook = IbcMemoFields {
+            wasm: Some(WasmHook {
+                contract: "osmo1contract".to_string(),
+                msg: json!({ "swap": { "min_out": "1" } }),
+            }),
+            ..IbcMemoFields::default()
+        };
+        let memo: Value =
+            serde_json::from_str(&ibc_memo("osmo1contract", hook).unwrap().memo).unwrap();
+        assert_eq!(memo["wasm"]["contract"], "osmo1contract");
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_stranding_memos() {
+        let forward = |fallback: Option<&str>| IbcMemoFields {
+            forward: vec![hop("cosmos1final", "channel-7")],
+            fallback_receiver: fallback.map(str::to_string),
+            wasm: None,
+        };
+        assert_eq!(
+            ibc_memo("cosmos1final", forward(None)),
+            Err(IbcMemoError::MissingFallbackReceiver)
+        );
+        assert!(matches!(
+            ibc_memo("cosmos1other", forward(Some("osmo1fallback"))),
+            Err(IbcMemoError::ReceiverMismatch(..))
+        ));
+        assert_eq!(
+            ibc_memo(
+                "osmo1final",
+                IbcMemoFields {
+                    fallback_receiver: Some("osmo1fallback".to_string()),
+                    ..IbcMemoFields::default()
+                }
+            ),
+            Err(IbcMemoError::UnusedFallbackReceiver)
+        );
+
+        let bad_channel = IbcMemoFields {
+            forward: vec![hop("cosmos1final", "chanel-7")],
+            ..forward(Some("osmo1fallback"))
+        };
+        assert!(matches!(
+            ibc_memo("cosmos1final", bad_channel),
+            Err(IbcMemoError::InvalidChannel(0, _))
+        ));
+
+        let hook = |msg: Value| IbcMemoFields {
+            wasm: Some(WasmHook {
+                contract: "osmo1contract".to_string(),
+                msg,
+            }),
+            ..IbcMemoFields::default()
+        };
+        assert!(matches!(
+            ibc_memo("osmo1receiver", hook(json!({}))),
+            Err(IbcMemoError::ContractMismatch(..))
+        ));
+        assert_eq!(
+            ibc_memo("osmo1contract", hook(json!("swap"))),
+            Err(IbcMemoError::InvalidWasmMsg)
+        );
+
+        // A hand written forward with a misspelled key
+        assert!(validate_ibc_memo(
+            r#"{"forward":{"reciever":"osmo1","port":"transfer","channel":"channel-1"}}"#
+        )
+        .is_err());
+        assert!(validate_ibc_memo("thanks for lunch").is_ok());
+    }
+}
@@ -0,0 +1,194 @@
+//! Mixed shielded transfers
+//!
+//! namada_sdk builds transfers to payment addresses and unshielding transfers as separate
+//! txs, and an unshielding transfer spends the notes of a single source. A transfer with
+//! both kinds of targets, or unshielding from several sources, is assembled here instead:
+//! every source and target goes into one MASP transaction, so notes are selected once and
+//! the transfer has one set of proofs and one fee. The transfer data credits the transparent
+//! targets from the MASP address.
+use std::collections::HashSet;
+use std::path::Path;
+
+use namada_sdk::address::{Address, MASP};
+use namada_sdk::args::{self, InputAmount};
+use namada_sdk::masp::shielded_wallet::ShieldedApi;
+use namada_sdk::masp::{AssetData, MaspFeeData, MaspTransferData, ShieldedTransfer};
+use namada_sdk::masp_primitives::transaction::components::sapling::fees::{InputView, OutputView};
+use namada_sdk::signing::{self, SigningTxData};
+use namada_sdk::token::{self, Amount, DenominatedAmount};
+use namada_sdk::tx::{prepare_tx, MaspBuilder, Tx};
+use namada_sdk::{rpc, ExtendedSpendingKey, Namada, TransferSource, TransferTarget};
+use wasm_bindgen::JsError;
+
+use crate::sdk::args::ShieldedTransferArgs;
+
+/// Returns true if the parts of the transfer would be separate MASP transactions
+pub fn is_mixed(args: &ShieldedTransferArgs) -> bool {
+    args.unshielding.len() + usize::from(args.shielded.is_some()) > 1
+}
+
+fn amount(amount: &InputAmount) -> DenominatedAmount {
+    match amount {
+        InputAmount::Validated(amount) | InputAmount::Unvalidated(amount) => *amount,
+    }
+}
+
+/// Returns the sources and targets of every part of the transfer
+pub fn transfer_data(args: &ShieldedTransferArgs) -> Vec<MaspTransferData> {
+    let shielded = args.shielded.iter().flat_map(|shielded| {
+        shielded.data.iter().map(|data| MaspTransferData {
+            source: TransferSource::ExtendedSpendingKey(data.source.clone()),
+            target: TransferTarget::PaymentAddress(data.target),
+            token: data.token.clone(),
+            amount: amount(&data.amount),
+        })
+    });
+    let unshielding = args.unshielding.iter().flat_map(|unshielding| {
+        unshielding.data.iter().map(|data| MaspTransferData {
+            source: TransferSource::ExtendedSpendingKey(unshielding.source.clone()),
+            target: TransferTarget::Address(data.target.clone()),
+            token: data.token.clone(),
+            amount: amount(&data.amount),
+        })
+    });
+
+    shielded.chain(unshielding).collect()
+}
+
+/// Returns the wrapper args, code path and gas spending key shared by the parts
+pub fn common_args(
+    args: &ShieldedTransferArgs,
+) -> Option<(&args::Tx, &Path, Option<&ExtendedSpendingKey>)> {
+    if let Some(shielded) = &args.shielded {
+        return Some((
+            &shielded.tx,
+            shielded.tx_code_path.as_path(),
+            shielded.gas_spending_key.as_ref(),
+        ));
+    }
+    let unshielding = args.unshielding.first()?;
+    Some((
+        &unshielding.tx,
+        unshielding.tx_code_path.as_path(),
+        args.unshielding
+            .iter()
+            .find_map(|unshielding| unshielding.gas_spending_key.as_ref()),
+    ))
+}
+
+/// Builds all parts of a transfer as one tx with a single MASP transaction. Amounts have to
+/// be validated first.
+///
+/// # Arguments
+///
+/// * `context` - Namada context
+/// * `data` - Sources and targets of the parts, see transfer_data
+/// * `tx_args` - Wrapper args
+/// * `tx_code_path` - Code path of the transfer tx
+/// * `gas_spending_key` - Spending key the fee is unshielded from, if any
+///
+/// # Errors
+///
+/// Returns JsError if the notes of the sources don't cover the transfer or the tx can't be
+/// built
+pub async fn build_mixed_transfer<N: Namada>(
+    context: &N,
+    data: Vec<MaspTransferData>,
+    tx_args: &args::Tx,
+    tx_code_path: &Path,
+    gas_spending_key: Option<&ExtendedSpendingKey>,
+) -> Result<(Tx, SigningTxData), JsError> {
+    let signing_data =
+        signing::aux_signing_data(context, tx_args, Some(MASP), Some(MASP), vec![], false).await?;
+    let fee_per_gas_unit = signing::validate_fee(context, tx_args).await?;
+    let fee_data = match gas_spending_key {
+        Some(gas_spending_key) => {
+            let fee = fee_per_gas_unit
+                .amount()
+                .checked_mul(Amount::from_u64(u64::from(tx_args.gas_limit)))
+                .ok_or_else(|| JsError::new("Fee amount overflow"))?;
+            Some(MaspFeeData {
+                sources: vec![gas_spending_key.clone()],
+                target: Address::from(&signing_data.fee_payer),
+                token: tx_args.fee_token.clone(),
+                amount: DenominatedAmount::new(fee, fee_per_gas_unit.denom()),
+            })
+        }
+        None => None,
+    };
+
+    let mut transfer = token::Transfer::default();
+    let credits = data.iter().filter_map(|data| match &data.target {
+        TransferTarget::Address(target) => Some((target, &data.token, data.amount)),
+        _ => None,
+    });
+    let fee_credit = fee_data
+        .as_ref()
+        .map(|fee| (&fee.target, &fee.token, fee.amount));
+    for (target, token, amount) in credits.chain(fee_credit) {
+        transfer = transfer
+            .transfer(MASP, target.clone(), token.clone(), amount)
+            .ok_or_else(|| JsError::new(&format!("Mixed transfer amount overflow: {}", token)))?;
+    }
+
+    let ShieldedTransfer {
+        builder,
+        masp_tx,
+        metadata,
+        ..
+    } = {
+        let mut shielded = context.shielded_mut().await;
+        shielded
+            .gen_shielded_transfer(context, data, fee_data, tx_args.expiration.to_datetime())
+            .await
+            .map_err(|e| JsError::new(&e.to_string()))?
+            .ok_or_else(|| JsError::new("Mixed transfer doesn't spend any notes"))?
+    };
+    let asset_types = {
+        let shielded = context.shielded().await;
+        let spent = builder
+            .sapling_inputs()
+            .iter()
+            .map(|input| *input.asset_type());
+        let sent = builder
+            .sapling_outputs()
+            .iter()
+            .map(|output| *output.asset_type());
+        spent
+            .chain(sent)
+            .filter_map(|asset_type| shielded.asset_types.get(&asset_type).cloned())
+            .collect::<HashSet<AssetData>>()
+    };
+
+    let chain_id = tx_args
+        .chain_id
+        .clone()
+        .ok_or_else(|| JsError::new("Mixed transfer requires a chain id"))?;
+    let mut tx = Tx::new(chain_id, tx_args.expiration.to_datetime());
+    if let Some(memo) = &tx_args.memo {
+        tx.add_memo(memo);
+    }
+    let code_path = tx_code_path.to_string_lossy().into_owned();
+    let code_hash = rpc::query_wasm_code_hash(context, &code_path).await?;
+    tx.add_code_from_hash(code_hash, Some(code_path));
+
+    let section_hash = tx.add_masp_tx_section(masp_tx).1;
+    tx.add_masp_builder(MaspBuilder {
+        asset_types,
+        metadata,
+        builder,
+        target: section_hash,
+    });
+    transfer.shielded_section_hash = Some(section_hash);
+    tx.add_data(transfer);
+
+    prepare_tx(
+        tx_args,
+        &mut tx,
+        fee_per_gas_unit,
+        signing_data.fee_payer.clone(),
+    )
+    .await?;
+
+    Ok((tx, signing_data))
+}
@@ -0,0 +1,238 @@
+//! Migration of older Msg layouts
+//!
+//! Msgs are Borsh encoded by the TS packages, which may be of an older release than the wasm
+//! lib, e.g. a dApp pinning an older @namada/sdk next to an updated extension. When a Msg gains
+//! fields, its previous layout is kept here and decoded messages of it are migrated to the
+//! current one, until the old layout is dropped in a major API version, see version.rs.
+//!
+//! Layouts are told apart by length: Borsh decoding of a struct fails on missing or trailing
+//! bytes, so a message decodes as exactly one layout. Releases before the MsgTags of decode.rs
+//! sent the layouts untagged, which are recognized by a first byte that is no MsgTag.
+//! Migrations destructure the old layout and pass every field to the constructor of the
+//! current one, so adding a field to a Msg fails to compile here until its migration is
+//! decided.
+//!
+//! Changes of the namada_sdk args on the other side are contained by the adapter in
+//! sdk_args.rs, which the conversions of the Msgs in args.rs construct the args through.
+use namada_sdk::borsh::{BorshDeserialize, BorshSerialize};
+
+use super::args::WrapperTxMsg;
+use super::decode::{decode_tagged_msg, MsgTag, TaggedMsg};
+use super::error::JsError;
+
+/// Msg whose older layouts are still accepted
+pub trait VersionedMsg: BorshDeserialize + Sized {
+    /// Decodes the current layout, or an older one migrated to it
+    ///
+    /// # Errors
+    ///
+    /// Returns the located error of decoding the current layout if no layout matches, see
+    /// decode.rs
+    fn decode(bytes: &[u8]) -> Result<Self, JsError>;
+}
+
+/// Decodes bytes as the layout `T` and migrates it, if they match it
+fn migrate<T, M>(bytes: &[u8]) -> Option<M>
+where
+    T: BorshDeserialize + Into<M>,
+{
+    T::try_from_slice(bytes).ok().map(Into::into)
+}
+
+/// WrapperTxMsg before tx expiration
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct WrapperTxMsgV0 {
+    token: String,
+    fee_amount: String,
+    gas_limit: String,
+    chain_id: String,
+    public_key: Option<String>,
+    memo: Option<String>,
+    force: Option<bool>,
+}
+
+impl From<WrapperTxMsgV0> for WrapperTxMsg {
+    fn from(msg: WrapperTxMsgV0) -> WrapperTxMsg {
+        let WrapperTxMsgV0 {
+            token,
+            fee_amount,
+            gas_limit,
+            chain_id,
+            public_key,
+            memo,
+            force,
+        } = msg;
+        WrapperTxMsg::new(
+            token, fee_amount, gas_limit, chain_id, public_key, memo, force, None, None, None, None,
+        )
+    }
+}
+
+/// WrapperTxMsg before explicit fee payer and signers
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct WrapperTxMsgV1 {
+    token: String,
+    fee_amount: String,
+    gas_limit: String,
+    chain_id: String,
+    public_key: Option<String>,
+    memo: Option<String>,
+    force: Option<bool>,
+    expiration: Option<String>,
+}
+
+impl From<WrapperTxMsgV1> for WrapperTxMsg {
+    fn from(msg: WrapperTxMsgV1) -> WrapperTxMsg {
+        let WrapperTxMsgV1 {
+            token,
+            fee_amount,
+            gas_limit,
+            chain_id,
+            public_key,
+            memo,
+            force,
+            expiration,
+        } = msg;
+        WrapperTxMsg::new(
+            token, fee_amount, gas_limit, chain_id, public_key, memo, force, expiration, None,
+            None, None,
+        )
+    }
+}
+
+/// WrapperTxMsg before encrypted memos
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct WrapperTxMsgV2 {
+    token: String,
+    fee_amount: String,
+    gas_limit: String,
+    chain_id: String,
+    public_key: Option<String>,
+    memo: Option<String>,
+    force: Option<bool>,
+    expiration: Option<String>,
+    fee_payer: Option<String>,
+    signers: Option<Vec<String>>,
+}
+
+impl From<WrapperTxMsgV2> for WrapperTxMsg {
+    fn from(msg: WrapperTxMsgV2) -> WrapperTxMsg {
+        let WrapperTxMsgV2 {
+            token,
+            fee_amount,
+            gas_limit,
+            chain_id,
+            public_key,
+            memo,
+            force,
+            expiration,
+            fee_payer,
+            signers,
+        } = msg;
+        WrapperTxMsg::new(
+            token, fee_amount, gas_limit, chain_id, public_key, memo, force, expiration, fee_payer,
+            signers, None,
+        )
+    }
+}
+
+impl VersionedMsg for WrapperTxMsg {
+    fn decode(bytes: &[u8]) -> Result<WrapperTxMsg, JsError> {
+        let error = match decode_tagged_msg::<WrapperTxMsg>(bytes) {
+            Ok(msg) => return Ok(msg),
+            Err(e) => e,
+        };
+        // Older layouts are tagged like the current one, or untagged if encoded by a release
+        // before the tags
+        let body = match bytes.split_first() {
+            Some((tag, body)) if *tag == WrapperTxMsg::TAG as u8 => body,
+            Some((tag, _)) if MsgTag::from_byte(*tag).is_none() => {
+                if let Ok(msg) = WrapperTxMsg::try_from_slice(bytes) {
+                    return Ok(msg);
+                }
+                bytes
+            }
+            _ => return Err(error),
+        };
+        migrate::<WrapperTxMsgV2, _>(body)
+            .or_else(|| migrate::<WrapperTxMsgV1, _>(body))
+            .or_else(|| migrate::<WrapperTxMsgV0, _>(body))
+            .ok_or(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use namada_sdk::borsh;
+    use wasm_bindgen_test::*;
+
+    const NATIVE_TOKEN: &str = "tnam1qxvg64psvhwumv3mwrrjfcz0h3t3274hwggyzcee";
+
+    fn tagged<T: BorshSerialize>(layout: &T) -> Vec<u8> {
+        let mut bytes = vec![WrapperTxMsg::TAG as u8];
+        bytes.extend(borsh::to_vec(layout).unwrap());
+        bytes
+    }
+
+    fn v1() -> WrapperTxMsgV1 {
+        WrapperTxMsgV1 {
+            token: NATIVE_TOKEN.to_string(),
+            fee_amount: "0.000001".to_string(),
+            gas_limit: "50000".to_string(),
+            chain_id: "namada-test.000000000000000".to_string(),
+            public_key: None,
+            memo: Some("memo".to_string()),
+            force: Some(true),
+            expiration: Some("2024-01-01T00:00:00Z".to_string()),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn migrates_older_wrapper_layouts() {
+        let migrated = WrapperTxMsg::decode(&tagged(&v1())).unwrap();
+        let expected = WrapperTxMsg::from(v1());
+        assert_eq!(
+            borsh::to_vec(&migrated).unwrap(),
+            borsh::to_vec(&expected).unwrap()
+        );
+
+        let current = tagged(&expected);
+        assert_eq!(tagged(&WrapperTxMsg::decode(&current).unwrap()), current);
+
+        let v0 = WrapperTxMsgV0 {
+            token: NATIVE_TOKEN.to_string(),
+            fee_amount: "0.000001".to_string(),
+            gas_limit: "50000".to_string(),
+            chain_id: "namada-test.000000000000000".to_string(),
+            public_key: None,
+            memo: None,
+            force: None,
+        };
+        assert!(WrapperTxMsg::decode(&tagged(&v0)).is_ok());
+        assert!(WrapperTxMsg::decode(&[1, 2, 3]).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn migrates_untagged_wrapper_layouts() {
+        let migrated = WrapperTxMsg::decode(&borsh::to_vec(&v1()).unwrap()).unwrap();
+        assert_eq!(
+            borsh::to_vec(&migrated).unwrap(),
+            borsh::to_vec(&WrapperTxMsg::from(v1())).unwrap()
+        );
+
+        let current = borsh::to_vec(&WrapperTxMsg::from(v1())).unwrap();
+        assert_eq!(
+            borsh::to_vec(&WrapperTxMsg::decode(&current).unwrap()).unwrap(),
+            current
+        );
+
+        // Other Msgs are still rejected as swapped
+        let mut swapped = tagged(&v1());
+        swapped[0] = MsgTag::Bond as u8;
+        assert!(WrapperTxMsg::decode(&swapped).is_err());
+    }
+}
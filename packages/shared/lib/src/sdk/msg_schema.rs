@@ -0,0 +1,188 @@
+//! Self-describing schema of the Msgs
+//!
+//! describe_messages returns the Borsh layout of every Msg the lib decodes: its MsgTag, and the
+//! definitions of its type and of the types nested in it, with fields in encoding order.
+//! Client libraries in other languages generate their encoders from it, and compare the hash
+//! of each Msg with the one they were generated from to detect layout changes at runtime,
+//! next to the API version of version.rs.
+use std::collections::BTreeMap;
+
+use namada_sdk::borsh::schema::{BorshSchemaContainer, Definition, Fields};
+use namada_sdk::borsh::{self, BorshSchema};
+use namada_sdk::hash::Hash;
+use serde::Serialize;
+
+use super::args::{RevealPkMsg, TransferMsg};
+use super::decode::MsgTag;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum TypeDefinition {
+    /// Fixed size little endian value, e.g. u64
+    Primitive {
+        size: u8,
+    },
+    /// Length prefixed sequence, or of fixed length if length_width is 0
+    Sequence {
+        length_width: u8,
+        min_length: u64,
+        max_length: u64,
+        elements: String,
+    },
+    Tuple {
+        elements: Vec<String>,
+    },
+    Enum {
+        tag_width: u8,
+        variants: Vec<VariantDefinition>,
+    },
+    Struct {
+        fields: Vec<FieldDefinition>,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VariantDefinition {
+    pub discriminant: i64,
+    pub name: String,
+    pub declaration: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FieldDefinition {
+    /// None for fields of tuple structs
+    pub name: Option<String>,
+    pub declaration: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MsgSchema {
+    pub name: String,
+    /// First byte of the encoded Msg, None for Msgs without a tag
+    pub tag: Option<u8>,
+    /// Hex sha256 of the Borsh serialized schema, changes with the layout
+    pub hash: String,
+    /// Definitions of the Msg and of the types nested in it, by declaration
+    pub definitions: BTreeMap<String, TypeDefinition>,
+}
+
+impl From<&Definition> for TypeDefinition {
+    fn from(definition: &Definition) -> TypeDefinition {
+        match definition {
+            Definition::Primitive(size) => TypeDefinition::Primitive { size: *size },
+            Definition::Sequence {
+                length_width,
+                length_range,
+                elements,
+            } => TypeDefinition::Sequence {
+                length_width: *length_width,
+                min_length: *length_range.start(),
+                max_length: *length_range.end(),
+                elements: elements.clone(),
+            },
+            Definition::Tuple { elements } => TypeDefinition::Tuple {
+                elements: elements.clone(),
+            },
+            Definition::Enum {
+                tag_width,
+                variants,
+            } => TypeDefinition::Enum {
+                tag_width: *tag_width,
+                variants: variants
+                    .iter()
+                    .map(|(discriminant, name, declaration)| VariantDefinition {
+                        discriminant: *discriminant,
+                        name: name.clone(),
+                        declaration: declaration.clone(),
+                    })
+                    .collect(),
+            },
+            Definition::Struct { fields } => TypeDefinition::Struct {
+                fields: match fields {
+                    Fields::NamedFields(fields) => fields
+                        .iter()
+                        .map(|(name, declaration)| FieldDefinition {
+                            name: Some(name.clone()),
+                            declaration: declaration.clone(),
+                        })
+                        .collect(),
+                    Fields::UnnamedFields(fields) => fields
+                        .iter()
+                        .map(|declaration| FieldDefinition {
+                            name: None,
+                            declaration: declaration.clone(),
+                        })
+                        .collect(),
+                    Fields::Empty => vec![],
+                },
+            },
+        }
+    }
+}
+
+fn describe(schema: BorshSchemaContainer, tag: Option<MsgTag>) -> MsgSchema {
+    let hash = borsh::to_vec(&schema)
+        .map(|bytes| Hash::sha256(bytes).to_string())
+        .expect("Serializing into a Vec shouldn't fail");
+    MsgSchema {
+        name: schema.declaration().to_string(),
+        tag: tag.map(|tag| tag as u8),
+        hash,
+        definitions: schema
+            .definitions()
+            .map(|(declaration, definition)| (declaration.clone(), definition.into()))
+            .collect(),
+    }
+}
+
+/// Returns the MsgSchema of an untagged Msg
+pub fn describe_untagged<T: BorshSchema>() -> MsgSchema {
+    describe(BorshSchemaContainer::for_type::<T>(), None)
+}
+
+/// Returns the MsgSchema of the tagged Msgs and of the untagged Msgs built natively
+pub fn describe_messages() -> Vec<MsgSchema> {
+    MsgTag::ALL
+        .iter()
+        .map(|tag| describe(tag.schema(), Some(*tag)))
+        .chain([
+            describe_untagged::<RevealPkMsg>(),
+            describe_untagged::<TransferMsg>(),
+        ])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn describes_fields_in_order() {
+        let schemas = describe_messages();
+        assert_eq!(schemas.len(), MsgTag::ALL.len() + 2);
+
+        let bond = schemas.iter().find(|s| s.name == "BondMsg").unwrap();
+        assert_eq!(bond.tag, Some(MsgTag::Bond as u8));
+        let TypeDefinition::Struct { fields } = &bond.definitions["BondMsg"] else {
+            panic!("BondMsg should be a struct");
+        };
+        let names: Vec<_> = fields.iter().filter_map(|f| f.name.as_deref()).collect();
+        assert_eq!(names, ["source", "validator", "amount"]);
+        assert!(bond.definitions.contains_key("String"));
+
+        // Hashes tell layouts apart
+        let unbond = schemas.iter().find(|s| s.name == "UnbondMsg").unwrap();
+        assert_ne!(bond.hash, unbond.hash);
+        assert_eq!(bond.hash, describe(MsgTag::Bond.schema(), None).hash);
+
+        let json = serde_json::to_value(bond).unwrap();
+        assert_eq!(json["definitions"]["BondMsg"]["kind"], "struct");
+        assert_eq!(json["definitions"]["String"]["lengthWidth"], 4);
+    }
+}
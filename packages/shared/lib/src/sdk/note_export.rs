@@ -0,0 +1,230 @@
+//! Export of scanned shielded notes
+//!
+//! Syncing a viewing key from its birthday takes a long time, so the scanned state of a
+//! viewing key, i.e. its notes, their witnesses and the scan cursor, is exported from one
+//! wallet instance and imported into another, e.g. when switching browsers.
+//!
+//! Note positions and witnesses are only valid with the commitment tree they were scanned
+//! with, so imported notes are merged into a context with other viewing keys only if both
+//! were synced to the same tree. A context without other viewing keys is replaced.
+//!
+//! Exports are `EXPORT_MAGIC` followed by the nonce and the XChaCha20-Poly1305 ciphertext of
+//! the Borsh serialized shielded context, filtered to the notes of the viewing key. The key is
+//! derived from the viewing key, which can read these notes anyway, and the chain id is
+//! authenticated so that an export can't be imported on another chain.
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use namada_sdk::borsh::{self, BorshDeserialize};
+use namada_sdk::masp::{ContextSyncStatus, ShieldedUtils};
+use namada_sdk::masp_primitives::sapling::ViewingKey;
+use namada_sdk::ShieldedWallet;
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::JsError;
+
+pub const EXPORT_MAGIC: &[u8] = b"NAMADA-NOTES-V1";
+
+/// Domain separation of the derived keys
+const KDF_CONTEXT: &[u8] = b"namada-interface/note-export/v1";
+const NONCE_LEN: usize = 24;
+
+fn derive_key(vk: &ViewingKey) -> Key {
+    let mut hasher = Sha256::new();
+    hasher.update(KDF_CONTEXT);
+    hasher.update(vk.to_bytes());
+    hasher.finalize()
+}
+
+/// Drops the notes and scan cursors of all viewing keys but `vk`. The commitment tree is
+/// kept as the witnesses of the notes of `vk` are only valid with it.
+pub fn retain_viewing_key<U: ShieldedUtils>(ctx: &mut ShieldedWallet<U>, vk: &ViewingKey) {
+    let positions = ctx.pos_map.get(vk).cloned().unwrap_or_default();
+    ctx.pos_map.retain(|key, _| key == vk);
+    ctx.vk_heights.retain(|key, _| key == vk);
+    ctx.vk_map.retain(|pos, _| positions.contains(pos));
+    ctx.note_map.retain(|pos, _| positions.contains(pos));
+    ctx.memo_map.retain(|pos, _| positions.contains(pos));
+    ctx.div_map.retain(|pos, _| positions.contains(pos));
+    ctx.witness_map.retain(|pos, _| positions.contains(pos));
+    ctx.nf_map.retain(|_, pos| positions.contains(pos));
+    ctx.spents.retain(|pos| positions.contains(pos));
+}
+
+/// Serializes and encrypts the scanned notes of a viewing key
+///
+/// # Errors
+///
+/// Returns JsError if the viewing key has not been synced or encryption fails
+pub fn export_notes<U: ShieldedUtils>(
+    ctx: &ShieldedWallet<U>,
+    vk: &ViewingKey,
+    chain_id: &str,
+) -> Result<Vec<u8>, JsError> {
+    if !ctx.vk_heights.contains_key(vk) {
+        return Err(JsError::new("Viewing key has not been synced yet"));
+    }
+    // Borsh roundtrip copies the context, the utils are skipped
+    let mut notes = ShieldedWallet::<U>::try_from_slice(&borsh::to_vec(ctx)?)?;
+    retain_viewing_key(&mut notes, vk);
+    seal(&borsh::to_vec(&notes)?, vk, chain_id)
+}
+
+/// Decrypts and deserializes notes exported with export_notes, returning a confirmed context
+///
+/// # Errors
+///
+/// Returns JsError if the export is malformed, of another viewing key or of another chain
+pub fn import_notes<U: ShieldedUtils>(
+    export: &[u8],
+    vk: &ViewingKey,
+    chain_id: &str,
+) -> Result<ShieldedWallet<U>, JsError> {
+    let mut ctx = ShieldedWallet::<U>::try_from_slice(&open(export, vk, chain_id)?)
+        .map_err(|_| JsError::new("Malformed shielded notes export"))?;
+    ctx.sync_status = ContextSyncStatus::Confirmed;
+    Ok(ctx)
+}
+
+/// Merges notes imported with import_notes into a shielded context, replacing the previous
+/// notes of `vk` and keeping those of the other viewing keys
+///
+/// # Errors
+///
+/// Returns JsError if other viewing keys of the context were synced to another commitment
+/// tree than the imported notes
+pub fn merge_notes<U: ShieldedUtils>(
+    ctx: &mut ShieldedWallet<U>,
+    mut notes: ShieldedWallet<U>,
+    vk: &ViewingKey,
+) -> Result<(), JsError> {
+    if ctx.vk_heights.keys().all(|key| key == vk) {
+        *ctx = ShieldedWallet {
+            utils: ctx.utils.clone(),
+            ..notes
+        };
+        return Ok(());
+    }
+    if borsh::to_vec(&ctx.tree)? != borsh::to_vec(&notes.tree)? {
+        return Err(JsError::new(
+            "Shielded context was synced to another height than the export, sync it to the \
+             same height or import into an empty context",
+        ));
+    }
+
+    // Same tree, so note positions and witnesses of both contexts agree
+    retain_viewing_key(&mut notes, vk);
+    let positions = notes.pos_map.get(vk).cloned().unwrap_or_default();
+    ctx.pos_map.insert(*vk, positions);
+    ctx.vk_heights.extend(notes.vk_heights);
+    ctx.vk_map.extend(notes.vk_map);
+    ctx.note_map.extend(notes.note_map);
+    ctx.memo_map.extend(notes.memo_map);
+    ctx.div_map.extend(notes.div_map);
+    ctx.witness_map.extend(notes.witness_map);
+    ctx.nf_map.extend(notes.nf_map);
+    ctx.spents.extend(notes.spents);
+    ctx.sync_status = ContextSyncStatus::Confirmed;
+    Ok(())
+}
+
+fn seal(plaintext: &[u8], vk: &ViewingKey, chain_id: &str) -> Result<Vec<u8>, JsError> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(&derive_key(vk))
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: chain_id.as_bytes(),
+            },
+        )
+        .map_err(|_| JsError::new("Failed to encrypt shielded notes"))?;
+
+    let mut export = EXPORT_MAGIC.to_vec();
+    export.extend_from_slice(&nonce);
+    export.extend_from_slice(&ciphertext);
+    Ok(export)
+}
+
+fn open(export: &[u8], vk: &ViewingKey, chain_id: &str) -> Result<Vec<u8>, JsError> {
+    let payload = export
+        .strip_prefix(EXPORT_MAGIC)
+        .filter(|payload| payload.len() >= NONCE_LEN)
+        .ok_or_else(|| JsError::new("Not a shielded notes export"))?;
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+
+    XChaCha20Poly1305::new(&derive_key(vk))
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: chain_id.as_bytes(),
+            },
+        )
+        .map_err(|_| JsError::new("Export is of another viewing key or chain"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::masp::JSShieldedUtils;
+    use namada_sdk::masp_primitives::sapling::Node;
+    use namada_sdk::masp_primitives::zip32::{ExtendedFullViewingKey, ExtendedSpendingKey};
+    use namada_sdk::masp_proofs::bls12_381::Scalar;
+    use wasm_bindgen_test::*;
+
+    fn viewing_key(seed: u8) -> ViewingKey {
+        ExtendedFullViewingKey::from(&ExtendedSpendingKey::master(&[seed; 32]))
+            .fvk
+            .vk
+    }
+
+    fn clone(ctx: &ShieldedWallet<JSShieldedUtils>) -> ShieldedWallet<JSShieldedUtils> {
+        ShieldedWallet::try_from_slice(&borsh::to_vec(ctx).unwrap()).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn merges_notes_into_contexts_of_the_same_tree() {
+        let (vk, other_vk) = (viewing_key(1), viewing_key(2));
+        let mut notes = ShieldedWallet::<JSShieldedUtils>::default();
+        notes.vk_heights.insert(vk, None);
+        notes.pos_map.insert(vk, [0].into_iter().collect());
+
+        // Empty contexts are replaced
+        let mut ctx = ShieldedWallet::<JSShieldedUtils>::default();
+        merge_notes(&mut ctx, clone(&notes), &vk).unwrap();
+        assert!(ctx.vk_heights.contains_key(&vk));
+
+        // Notes of other viewing keys are kept
+        let mut ctx = ShieldedWallet::<JSShieldedUtils>::default();
+        ctx.vk_heights.insert(other_vk, None);
+        ctx.pos_map.insert(other_vk, [1].into_iter().collect());
+        merge_notes(&mut ctx, clone(&notes), &vk).unwrap();
+        assert_eq!(ctx.pos_map.len(), 2);
+        assert!(ctx.vk_heights.contains_key(&other_vk));
+
+        // Contexts synced to another tree are refused
+        let mut diverged = clone(&notes);
+        diverged
+            .tree
+            .append(Node::from_scalar(Scalar::from(1u64)))
+            .unwrap();
+        let mut ctx = ShieldedWallet::<JSShieldedUtils>::default();
+        ctx.vk_heights.insert(other_vk, None);
+        assert!(merge_notes(&mut ctx, diverged, &vk).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn seals_to_viewing_key_and_chain() {
+        let (vk, other_vk) = (viewing_key(1), viewing_key(2));
+        let chain_id = "namada.5f5de2dd1b88cba30586420";
+
+        let export = seal(b"notes", &vk, chain_id).unwrap();
+        assert!(export.starts_with(EXPORT_MAGIC));
+        assert_eq!(open(&export, &vk, chain_id).unwrap(), b"notes");
+
+        assert!(open(&export, &other_vk, chain_id).is_err());
+        assert!(open(&export, &vk, "namada-dryrun.abaaeaf7b78cb3ac").is_err());
+        assert!(open(&export[..EXPORT_MAGIC.len() + 4], &vk, chain_id).is_err());
+        assert!(open(b"notes", &vk, chain_id).is_err());
+    }
+}
@@ -0,0 +1,464 @@
+//! Payment request URIs
+//!
+//! ZIP-321 style URIs describing one or more payments, used for "request payment" links and
+//! QR codes, e.g. `namada:znam1...?amount=1.5&token=tnam1...&memo=Invoice%2042`.
+//! Parameters of additional payments are suffixed with their index, e.g. `address.1`.
+//!
+//! Payments to a single payment address are also encoded as compact QR payloads,
+//! `NAMADA:ZNAM1...:1.5:TNAM1...` with optional amount and token. Bech32m addresses may be
+//! upper case, so the whole payload fits the alphanumeric mode of QR codes, which is denser
+//! than the byte mode URIs need.
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use namada_sdk::address::Address;
+use namada_sdk::borsh::{self, BorshDeserialize, BorshSchema, BorshSerialize};
+use namada_sdk::token::DenominatedAmount;
+use namada_sdk::PaymentAddress;
+use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
+
+use super::prefixes::ChainPrefixes;
+
+pub const PAYMENT_REQUEST_SCHEME: &str = "namada";
+/// Separator of the fields of QR payloads, in the QR alphanumeric charset
+const QR_SEPARATOR: &str = ":";
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Default)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct PaymentMsg {
+    /// Payment address or transparent address
+    pub recipient: String,
+    /// Denominated amount
+    pub amount: Option<String>,
+    pub token: Option<String>,
+    pub memo: Option<String>,
+    pub label: Option<String>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct PaymentRequestMsg {
+    pub payments: Vec<PaymentMsg>,
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            if is_unreserved(byte) {
+                (byte as char).to_string()
+            } else {
+                format!("%{:02X}", byte)
+            }
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> Result<String, JsError> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value
+                .get(i + 1..i + 3)
+                .ok_or_else(|| JsError::new("Invalid percent encoding"))?;
+            decoded.push(
+                u8::from_str_radix(hex, 16)
+                    .map_err(|_| JsError::new("Invalid percent encoding"))?,
+            );
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| JsError::new("Invalid UTF-8 in payment request"))
+}
+
+fn validate_payment(payment: &PaymentMsg, prefixes: &ChainPrefixes) -> Result<(), JsError> {
+    let recipient = prefixes.native(&payment.recipient);
+    if PaymentAddress::from_str(&recipient).is_err() {
+        Address::from_str(&recipient)
+            .map_err(|_| JsError::new(&format!("Invalid recipient: {}", payment.recipient)))?;
+    }
+    if let Some(amount) = &payment.amount {
+        DenominatedAmount::from_str(amount)
+            .map_err(|_| JsError::new(&format!("Invalid amount: {}", amount)))?;
+    }
+    if let Some(token) = &payment.token {
+        Address::from_str(&prefixes.native(token))
+            .map_err(|_| JsError::new(&format!("Invalid token: {}", token)))?;
+    }
+
+    Ok(())
+}
+
+/// Splits a parameter name into its name and payment index, e.g. "amount.1" -> ("amount", 1)
+fn param_index(name: &str) -> Result<(&str, usize), JsError> {
+    match name.split_once('.') {
+        Some((name, index)) => {
+            // Leading zeros are not allowed, and index 0 is written without a suffix
+            if index.starts_with('0') {
+                return Err(JsError::new(&format!("Invalid parameter index: {}", index)));
+            }
+            let index = index
+                .parse::<usize>()
+                .map_err(|_| JsError::new(&format!("Invalid parameter index: {}", index)))?;
+            Ok((name, index))
+        }
+        None => Ok((name, 0)),
+    }
+}
+
+fn encode(request: &PaymentRequestMsg, prefixes: &ChainPrefixes) -> Result<String, JsError> {
+    let first = request
+        .payments
+        .first()
+        .ok_or_else(|| JsError::new("Payment request has no payments"))?;
+
+    let mut params: Vec<String> = vec![];
+    for (index, payment) in request.payments.iter().enumerate() {
+        validate_payment(payment, prefixes)?;
+
+        let suffix = if index == 0 {
+            String::new()
+        } else {
+            format!(".{}", index)
+        };
+        // The first recipient is a part of the URI path
+        if index > 0 {
+            params.push(format!("address{}={}", suffix, payment.recipient));
+        }
+        let optional = [
+            ("amount", &payment.amount),
+            ("token", &payment.token),
+            ("memo", &payment.memo),
+            ("label", &payment.label),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                params.push(format!("{}{}={}", name, suffix, percent_encode(value)));
+            }
+        }
+    }
+
+    let mut uri = format!("{}:{}", PAYMENT_REQUEST_SCHEME, first.recipient);
+    if !params.is_empty() {
+        uri.push('?');
+        uri.push_str(&params.join("&"));
+    }
+
+    Ok(uri)
+}
+
+fn decode(uri: &str, prefixes: &ChainPrefixes) -> Result<PaymentRequestMsg, JsError> {
+    let rest = uri
+        .strip_prefix(PAYMENT_REQUEST_SCHEME)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .ok_or_else(|| JsError::new("Not a Namada payment request"))?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let mut payments: BTreeMap<usize, PaymentMsg> = BTreeMap::new();
+    if !path.is_empty() {
+        payments.entry(0).or_default().recipient = path.to_string();
+    }
+
+    for param in query.split('&').filter(|param| !param.is_empty()) {
+        let (name, value) = param
+            .split_once('=')
+            .ok_or_else(|| JsError::new(&format!("Invalid parameter: {}", param)))?;
+        let (name, index) = param_index(name)?;
+        let value = percent_decode(value)?;
+        let payment = payments.entry(index).or_default();
+
+        let field = match name {
+            "address" => {
+                if !payment.recipient.is_empty() {
+                    return Err(JsError::new(&format!(
+                        "Duplicate address for payment {}",
+                        index
+                    )));
+                }
+                payment.recipient = value;
+                continue;
+            }
+            "amount" => &mut payment.amount,
+            "token" => &mut payment.token,
+            "memo" => &mut payment.memo,
+            "label" => &mut payment.label,
+            // Required parameters we don't understand make the request invalid
+            name if name.starts_with("req-") => {
+                return Err(JsError::new(&format!(
+                    "Unsupported required parameter: {}",
+                    name
+                )))
+            }
+            _ => continue,
+        };
+        if field.replace(value).is_some() {
+            return Err(JsError::new(&format!("Duplicate parameter: {}", param)));
+        }
+    }
+
+    if payments.is_empty() {
+        return Err(JsError::new("Payment request has no payments"));
+    }
+    // Indices have to be contiguous, so no payment is silently dropped
+    for (expected, (index, payment)) in payments.iter().enumerate() {
+        if *index != expected || payment.recipient.is_empty() {
+            return Err(JsError::new(&format!(
+                "Missing address for payment {}",
+                expected
+            )));
+        }
+        validate_payment(payment, prefixes)?;
+    }
+
+    Ok(PaymentRequestMsg {
+        payments: payments.into_values().collect(),
+    })
+}
+
+fn validate_qr_payment(payment: &PaymentMsg, prefixes: &ChainPrefixes) -> Result<(), JsError> {
+    PaymentAddress::from_str(&prefixes.native(&payment.recipient)).map_err(|_| {
+        JsError::new(&format!(
+            "QR payloads are for payment addresses, not {}",
+            payment.recipient
+        ))
+    })?;
+    validate_payment(payment, prefixes)
+}
+
+fn encode_qr(payment: &PaymentMsg, prefixes: &ChainPrefixes) -> Result<String, JsError> {
+    validate_qr_payment(payment, prefixes)?;
+    if payment.memo.is_some() || payment.label.is_some() {
+        return Err(JsError::new(
+            "QR payloads have no memo or label, use a payment request URI",
+        ));
+    }
+
+    let mut fields = vec![PAYMENT_REQUEST_SCHEME, payment.recipient.as_str()];
+    match (&payment.amount, &payment.token) {
+        (None, None) => {}
+        (Some(amount), None) => fields.push(amount),
+        // Token without amount leaves the amount empty
+        (amount, Some(token)) => {
+            fields.extend([amount.as_deref().unwrap_or_default(), token.as_str()])
+        }
+    }
+    Ok(fields.join(QR_SEPARATOR).to_uppercase())
+}
+
+/// Decodes a QR payload, a payment request URI of a single payment or a bare payment address
+fn decode_qr(payload: &str, prefixes: &ChainPrefixes) -> Result<PaymentMsg, JsError> {
+    let payload = payload.trim();
+    if payload.contains('?') {
+        let mut request = decode(payload, prefixes)?;
+        if request.payments.len() != 1 {
+            return Err(JsError::new("QR payloads have a single payment"));
+        }
+        let payment = request.payments.remove(0);
+        validate_qr_payment(&payment, prefixes)?;
+        return Ok(payment);
+    }
+
+    let payload = payload.to_lowercase();
+    let fields: Vec<&str> = payload.split(QR_SEPARATOR).collect();
+    let optional = |field: Option<&&str>| {
+        field
+            .filter(|field| !field.is_empty())
+            .map(|field| field.to_string())
+    };
+    let payment = match fields.as_slice() {
+        [recipient] => PaymentMsg {
+            recipient: recipient.to_string(),
+            ..Default::default()
+        },
+        [scheme, recipient, rest @ ..] if *scheme == PAYMENT_REQUEST_SCHEME && rest.len() <= 2 => {
+            PaymentMsg {
+                recipient: recipient.to_string(),
+                amount: optional(rest.first()),
+                token: optional(rest.get(1)),
+                ..Default::default()
+            }
+        }
+        _ => return Err(JsError::new("Not a Namada QR payload")),
+    };
+    validate_qr_payment(&payment, prefixes)?;
+    Ok(payment)
+}
+
+/// Encodes a Borsh serialized PaymentRequestMsg into a payment request URI. Addresses are
+/// validated with `prefixes`, see Sdk.chain_prefixes, or the Namada prefixes if undefined.
+#[wasm_bindgen]
+pub fn encode_payment_request(msg: &[u8], prefixes: JsValue) -> Result<String, JsError> {
+    let request = PaymentRequestMsg::try_from_slice(msg)?;
+    encode(&request, &ChainPrefixes::from_js(prefixes)?)
+}
+
+/// Parses and validates a payment request URI into a Borsh serialized PaymentRequestMsg
+#[wasm_bindgen]
+pub fn decode_payment_request(uri: &str, prefixes: JsValue) -> Result<Vec<u8>, JsError> {
+    let request = decode(uri, &ChainPrefixes::from_js(prefixes)?)?;
+    Ok(borsh::to_vec(&request)?)
+}
+
+/// Returns true if the URI is a valid payment request
+#[wasm_bindgen]
+pub fn validate_payment_request(uri: &str, prefixes: JsValue) -> bool {
+    ChainPrefixes::from_js(prefixes).is_ok_and(|prefixes| decode(uri, &prefixes).is_ok())
+}
+
+/// Encodes a payment to a payment address as a compact, upper case QR payload
+///
+/// # Arguments
+///
+/// * `payment_address` - Payment address of the recipient
+/// * `amount` - Denominated amount, optional
+/// * `token` - Address of the token, optional
+/// * `prefixes` - ChainPrefixes, see Sdk.chain_prefixes, Namada prefixes if undefined
+///
+/// # Errors
+///
+/// Returns JsError if the recipient is not a payment address, or the amount or token is invalid
+#[wasm_bindgen]
+pub fn encode_qr_payload(
+    payment_address: String,
+    amount: Option<String>,
+    token: Option<String>,
+    prefixes: JsValue,
+) -> Result<String, JsError> {
+    encode_qr(
+        &PaymentMsg {
+            recipient: payment_address,
+            amount,
+            token,
+            ..Default::default()
+        },
+        &ChainPrefixes::from_js(prefixes)?,
+    )
+}
+
+/// Decodes a scanned QR payload into a Borsh serialized PaymentMsg, to prefill a transfer
+///
+/// Compact payloads, payment request URIs of a single payment and bare payment addresses are
+/// accepted, so payloads of other wallets prefill the same fields.
+///
+/// # Errors
+///
+/// Returns JsError if the payload is not one of those, or is not to a payment address
+#[wasm_bindgen]
+pub fn decode_qr_payload(payload: &str, prefixes: JsValue) -> Result<Vec<u8>, JsError> {
+    let payment = decode_qr(payload, &ChainPrefixes::from_js(prefixes)?)?;
+    Ok(borsh::to_vec(&payment)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const ADDRESS: &str = "tnam1qzegy7g6y4ajqcl8ax2e3en4tj9xkp0mz5yrwgaf";
+    const TOKEN: &str = "tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e";
+    const PAYMENT_ADDRESS: &str =
+        "znam1wzrkk8tcz8zxxv0j3ssckjnantguzj2gnzypvvmg0xwy6k45w4glvv2s9yhl8magw8dx7kz0u4h";
+
+    #[wasm_bindgen_test]
+    fn can_round_trip_payment_request() {
+        let namada = ChainPrefixes::default();
+        let request = PaymentRequestMsg {
+            payments: vec![
+                PaymentMsg {
+                    recipient: ADDRESS.to_string(),
+                    amount: Some("1.5".to_string()),
+                    token: Some(TOKEN.to_string()),
+                    memo: Some("Invoice #42 & more".to_string()),
+                    label: None,
+                },
+                PaymentMsg {
+                    recipient: TOKEN.to_string(),
+                    amount: Some("2".to_string()),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let uri = encode(&request, &namada).unwrap();
+        assert_eq!(
+            uri,
+            format!(
+                "namada:{}?amount=1.5&token={}&memo=Invoice%20%2342%20%26%20more&address.1={}&amount.1=2",
+                ADDRESS, TOKEN, TOKEN
+            )
+        );
+        assert_eq!(decode(&uri, &namada).unwrap(), request);
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_invalid_payment_requests() {
+        let namada = ChainPrefixes::default();
+        assert!(decode(&format!("bitcoin:{}", ADDRESS), &namada).is_err());
+        assert!(decode(&format!("namada:{}?amount=abc", ADDRESS), &namada).is_err());
+        assert!(decode(&format!("namada:{}?amount=1&amount=2", ADDRESS), &namada).is_err());
+        assert!(decode(&format!("namada:{}?req-expiry=10", ADDRESS), &namada).is_err());
+        assert!(decode(&format!("namada:{}?amount.2=1", ADDRESS), &namada).is_err());
+        assert!(decode(
+            &format!("namada:{}?address.01={}", ADDRESS, ADDRESS),
+            &namada
+        )
+        .is_err());
+        assert!(decode(&format!("namada:{}?foo=bar", ADDRESS), &namada).is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn can_round_trip_qr_payload() {
+        let namada = ChainPrefixes::default();
+        let payment = PaymentMsg {
+            recipient: PAYMENT_ADDRESS.to_string(),
+            amount: Some("1.5".to_string()),
+            token: Some(TOKEN.to_string()),
+            ..Default::default()
+        };
+        let payload = encode_qr(&payment, &namada).unwrap();
+        assert_eq!(
+            payload,
+            format!("NAMADA:{}:1.5:{}", PAYMENT_ADDRESS, TOKEN).to_uppercase()
+        );
+        assert_eq!(decode_qr(&payload, &namada).unwrap(), payment);
+
+        let token_only = PaymentMsg {
+            amount: None,
+            ..payment.clone()
+        };
+        let payload = encode_qr(&token_only, &namada).unwrap();
+        assert!(payload.contains("::"));
+        assert_eq!(decode_qr(&payload, &namada).unwrap(), token_only);
+
+        // Other wallets may scan a URI or a bare address
+        let uri = format!("namada:{}?amount=1.5&token={}", PAYMENT_ADDRESS, TOKEN);
+        assert_eq!(decode_qr(&uri, &namada).unwrap(), payment);
+        assert_eq!(
+            decode_qr(PAYMENT_ADDRESS, &namada).unwrap().recipient,
+            PAYMENT_ADDRESS
+        );
+
+        assert!(decode_qr(ADDRESS, &namada).is_err());
+        assert!(decode_qr(&format!("NAMADA:{}:ABC", PAYMENT_ADDRESS), &namada).is_err());
+        assert!(decode_qr(&format!("BITCOIN:{}", PAYMENT_ADDRESS), &namada).is_err());
+        assert!(encode_qr(
+            &PaymentMsg {
+                memo: Some("memo".to_string()),
+                ..payment
+            },
+            &namada
+        )
+        .is_err());
+    }
+}
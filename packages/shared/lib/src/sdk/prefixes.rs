@@ -0,0 +1,192 @@
+//! Registry of chain prefixes
+//!
+//! namada_sdk encodes addresses, public keys and shielded keys with fixed bech32m HRPs, e.g.
+//! "tnam", and wallets derive keys with the Namada SLIP-44 coin type. Forks and testnets with
+//! other prefixes configure theirs on the Sdk and Query instances, see set_chain_prefixes:
+//! strings with the configured HRPs are re-encoded with the namada_sdk HRPs where they are
+//! parsed, see ChainPrefixes::native, and strings they output are re-encoded with the configured
+//! HRPs, see ChainPrefixes::configured. The coin type is read by the TS Keys when deriving keys.
+use std::borrow::Cow;
+
+use bech32::{Bech32m, Hrp};
+#[cfg(feature = "web")]
+use gloo_utils::format::JsValueSerdeExt;
+use namada_sdk::string_encoding::{
+    ADDRESS_HRP, COMMON_PK_HRP, MASP_EXT_FULL_VIEWING_KEY_HRP, MASP_EXT_SPENDING_KEY_HRP,
+    MASP_PAYMENT_ADDRESS_HRP,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "web")]
+use wasm_bindgen::prelude::*;
+
+/// SLIP-44 coin type of Namada
+pub const NAMADA_COIN_TYPE: u32 = 877;
+
+/// Bech32m HRPs and SLIP-44 coin type of a chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChainPrefixes {
+    /// Transparent addresses, e.g. "tnam"
+    pub address: String,
+    pub public_key: String,
+    pub payment_address: String,
+    pub viewing_key: String,
+    pub spending_key: String,
+    pub coin_type: u32,
+}
+
+impl Default for ChainPrefixes {
+    fn default() -> Self {
+        ChainPrefixes {
+            address: ADDRESS_HRP.to_string(),
+            public_key: COMMON_PK_HRP.to_string(),
+            payment_address: MASP_PAYMENT_ADDRESS_HRP.to_string(),
+            viewing_key: MASP_EXT_FULL_VIEWING_KEY_HRP.to_string(),
+            spending_key: MASP_EXT_SPENDING_KEY_HRP.to_string(),
+            coin_type: NAMADA_COIN_TYPE,
+        }
+    }
+}
+
+impl ChainPrefixes {
+    /// Pairs of configured and namada_sdk HRPs
+    fn hrps(&self) -> [(&str, &'static str); 5] {
+        [
+            (&self.address, ADDRESS_HRP),
+            (&self.public_key, COMMON_PK_HRP),
+            (&self.payment_address, MASP_PAYMENT_ADDRESS_HRP),
+            (&self.viewing_key, MASP_EXT_FULL_VIEWING_KEY_HRP),
+            (&self.spending_key, MASP_EXT_SPENDING_KEY_HRP),
+        ]
+    }
+
+    /// Checks that the HRPs are valid, lowercase and distinct
+    pub fn validate(&self) -> Result<(), String> {
+        let hrps = self.hrps();
+        for (i, (hrp, _)) in hrps.iter().enumerate() {
+            Hrp::parse(hrp).map_err(|e| format!("Invalid prefix \"{}\": {}", hrp, e))?;
+            if hrp.to_lowercase() != *hrp {
+                return Err(format!("Prefix \"{}\" is not lowercase", hrp));
+            }
+            if hrps[..i].iter().any(|(other, _)| other == hrp) {
+                return Err(format!("Prefix \"{}\" is used twice", hrp));
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses prefixes passed from JS, e.g. `{ address: "tfork", coinType: 1 }`. Prefixes left
+    /// out are those of Namada, undefined or null are all of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if a prefix is invalid or used twice
+    #[cfg(feature = "web")]
+    pub fn from_js(prefixes: JsValue) -> Result<ChainPrefixes, JsError> {
+        if prefixes.is_undefined() || prefixes.is_null() {
+            return Ok(ChainPrefixes::default());
+        }
+        let prefixes: ChainPrefixes = prefixes.into_serde()?;
+        prefixes.validate().map_err(|e| JsError::new(&e))?;
+        Ok(prefixes)
+    }
+
+    /// Re-encodes a bech32m string with the HRP paired to its own, or returns it as is
+    fn reencode<'a>(&self, s: &'a str, pair: impl Fn(&str) -> Option<String>) -> Cow<'a, str> {
+        if *self == ChainPrefixes::default() {
+            return Cow::Borrowed(s);
+        }
+        let Ok((hrp, data)) = bech32::decode(s) else {
+            return Cow::Borrowed(s);
+        };
+        pair(hrp.as_str())
+            .and_then(|target| Hrp::parse(&target).ok())
+            .and_then(|target| bech32::encode::<Bech32m>(target, &data).ok())
+            .map_or(Cow::Borrowed(s), Cow::Owned)
+    }
+
+    /// Returns a string with a configured HRP re-encoded with the namada_sdk HRP, for parsing
+    pub fn native<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        self.reencode(s, |hrp| {
+            self.hrps()
+                .into_iter()
+                .find(|(configured, _)| *configured == hrp)
+                .map(|(_, native)| native.to_string())
+        })
+    }
+
+    /// Returns a string encoded by namada_sdk re-encoded with the configured HRP, for outputs
+    pub fn configured(&self, s: &str) -> String {
+        self.reencode(s, |hrp| {
+            self.hrps()
+                .into_iter()
+                .find(|(_, native)| *native == hrp)
+                .map(|(configured, _)| configured.to_string())
+        })
+        .into_owned()
+    }
+
+    /// Re-encodes the strings of a JSON value, object keys included, see configured
+    pub fn configured_json(&self, value: serde_json::Value) -> serde_json::Value {
+        if *self == ChainPrefixes::default() {
+            return value;
+        }
+        match value {
+            serde_json::Value::String(s) => serde_json::Value::String(self.configured(&s)),
+            serde_json::Value::Array(values) => serde_json::Value::Array(
+                values
+                    .into_iter()
+                    .map(|value| self.configured_json(value))
+                    .collect(),
+            ),
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (self.configured(&key), self.configured_json(value)))
+                    .collect(),
+            ),
+            value => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const ADDRESS: &str = "tnam1qz4sdx5jlh909j44uz46pl6f3d2ftsvkmcml0yuf";
+
+    #[wasm_bindgen_test]
+    fn reencodes_configured_prefixes() {
+        let namada = ChainPrefixes::default();
+        assert_eq!(namada.native(ADDRESS), ADDRESS);
+        assert_eq!(namada.configured(ADDRESS), ADDRESS);
+
+        let fork = ChainPrefixes {
+            address: "tfork".to_string(),
+            coin_type: 1,
+            ..ChainPrefixes::default()
+        };
+        fork.validate().unwrap();
+
+        let fork_address = fork.configured(ADDRESS);
+        assert!(fork_address.starts_with("tfork1"));
+        assert_eq!(fork.native(&fork_address), ADDRESS);
+        // Other prefixes are left as is
+        assert_eq!(fork.native("cosmos1xyz"), "cosmos1xyz");
+        // Instances with other prefixes don't affect each other
+        assert_eq!(namada.configured(ADDRESS), ADDRESS);
+
+        let json = serde_json::json!({ ADDRESS: [ADDRESS, "1.5", 2] });
+        let fork_json = fork.configured_json(json.clone());
+        assert_eq!(fork_json[&fork_address][0], fork_address.as_str());
+        assert_eq!(fork_json[&fork_address][1], "1.5");
+        assert_eq!(namada.configured_json(json.clone()), json);
+
+        let twice = ChainPrefixes {
+            public_key: ADDRESS_HRP.to_string(),
+            ..ChainPrefixes::default()
+        };
+        assert!(twice.validate().is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Price providers for displaying fiat values of token amounts
+use std::str::FromStr;
+
+use js_sys::Function;
+use namada_sdk::token::DenominatedAmount;
+use namada_sdk::uint::Uint;
+use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
+
+use super::transaction::TransactionKind;
+
+/// Number of decimal places of returned fiat values
+pub const FIAT_DECIMAL_PLACES: u8 = 2;
+
+/// Source of token prices. Prices are expressed as decimal strings in fiat per whole token.
+pub trait PriceProvider {
+    /// Returns the price of the token, or None if the price is not known
+    fn price(&self, token: &str) -> Option<String>;
+
+    /// Returns the native token address used for staking amounts
+    fn native_token(&self) -> Option<String>;
+}
+
+/// PriceProvider calling into a synchronous JS callback: `(token: string) => string | undefined`
+pub struct JsPriceProvider {
+    callback: Function,
+    native_token: Option<String>,
+}
+
+impl JsPriceProvider {
+    pub fn new(callback: Function, native_token: Option<String>) -> JsPriceProvider {
+        JsPriceProvider {
+            callback,
+            native_token,
+        }
+    }
+}
+
+impl PriceProvider for JsPriceProvider {
+    fn price(&self, token: &str) -> Option<String> {
+        self.callback
+            .call1(&JsValue::NULL, &JsValue::from_str(token))
+            .ok()
+            .and_then(|price| price.as_string())
+    }
+
+    fn native_token(&self) -> Option<String> {
+        self.native_token.clone()
+    }
+}
+
+/// Multiplies a denominated amount by a decimal price, rounding down to FIAT_DECIMAL_PLACES
+///
+/// # Arguments
+///
+/// * `amount` - Amount denominated in token decimals
+/// * `price` - Price per whole token, e.g. "1.2345"
+///
+/// # Errors
+///
+/// Returns JsError if the price is not a valid decimal or the result overflows
+pub fn fiat_value(amount: &DenominatedAmount, price: &str) -> Result<String, JsError> {
+    let price = DenominatedAmount::from_str(price)?;
+    let decimals = amount.denom().0 as usize + price.denom().0 as usize;
+
+    let mut value = amount
+        .amount()
+        .raw_amount()
+        .checked_mul(price.amount().raw_amount())
+        .ok_or_else(|| JsError::new("Fiat value overflow"))?;
+
+    let fiat_decimals = FIAT_DECIMAL_PLACES as usize;
+    if decimals > fiat_decimals {
+        value /= Uint::exp10(decimals - fiat_decimals);
+    } else {
+        value *= Uint::exp10(fiat_decimals - decimals);
+    }
+
+    let scale = Uint::exp10(fiat_decimals);
+    Ok(format!(
+        "{}.{:0width$}",
+        value / scale,
+        (value % scale).as_u64(),
+        width = fiat_decimals
+    ))
+}
+
+/// Converts a token amount to fiat using the provided PriceProvider.
+/// Returns None if the price of the token is not known.
+pub fn convert_to_fiat(
+    provider: &dyn PriceProvider,
+    amount: &DenominatedAmount,
+    token: &str,
+) -> Result<Option<String>, JsError> {
+    provider
+        .price(token)
+        .map(|price| fiat_value(amount, &price))
+        .transpose()
+}
+
+/// Returns the fiat value of the amounts moved by a decoded transaction, if all of the
+/// involved token prices are known.
+pub fn transaction_fiat_value(
+    provider: &dyn PriceProvider,
+    tx_kind: &TransactionKind,
+) -> Result<Option<String>, JsError> {
+    let mut values: Vec<(DenominatedAmount, String)> = vec![];
+
+    match tx_kind {
+        TransactionKind::Transfer(transfer) => {
+            for (account, amount) in transfer.targets.iter() {
+                values.push((*amount, account.token.to_string()));
+            }
+        }
+        TransactionKind::Bond(bond) => {
+            values.push((bond.amount.native_denominated(), native_token(provider)?))
+        }
+        TransactionKind::Unbond(unbond) => {
+            values.push((unbond.amount.native_denominated(), native_token(provider)?))
+        }
+        TransactionKind::Redelegation(redelegation) => values.push((
+            redelegation.amount.native_denominated(),
+            native_token(provider)?,
+        )),
+        _ => return Ok(None),
+    };
+
+    let mut total = DenominatedAmount::from_str("0")?;
+    for (amount, token) in values {
+        match convert_to_fiat(provider, &amount, &token)? {
+            Some(value) => {
+                total = total
+                    .checked_add(DenominatedAmount::from_str(&value)?)
+                    .ok_or_else(|| JsError::new("Fiat value overflow"))?;
+            }
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(fiat_value(&total, "1")?))
+}
+
+fn native_token(provider: &dyn PriceProvider) -> Result<String, JsError> {
+    provider
+        .native_token()
+        .ok_or_else(|| JsError::new("Native token is required for staking fiat values"))
+}
+
+/// Converts a token amount to fiat using a JS price callback
+///
+/// # Arguments
+///
+/// * `amount` - Amount denominated in token decimals, e.g. "1.5"
+/// * `token` - Token address or IBC denom passed to the callback
+/// * `price_callback` - `(token: string) => string | undefined`
+///
+/// # Errors
+///
+/// Returns JsError if amount or returned price are invalid
+#[wasm_bindgen(js_name = "convertToFiat")]
+pub fn convert_to_fiat_js(
+    amount: &str,
+    token: &str,
+    price_callback: Function,
+) -> Result<Option<String>, JsError> {
+    let provider = JsPriceProvider::new(price_callback, None);
+    let amount = DenominatedAmount::from_str(amount)?;
+
+    convert_to_fiat(&provider, &amount, token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn can_compute_fiat_value() {
+        let amount = DenominatedAmount::from_str("1.5").unwrap();
+        assert_eq!(fiat_value(&amount, "2.1234").unwrap(), "3.18");
+
+        let amount = DenominatedAmount::from_str("10").unwrap();
+        assert_eq!(fiat_value(&amount, "3").unwrap(), "30.00");
+
+        let amount = DenominatedAmount::from_str("0.000001").unwrap();
+        assert_eq!(fiat_value(&amount, "100").unwrap(), "0.00");
+    }
+}
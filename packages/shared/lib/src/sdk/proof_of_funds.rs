@@ -0,0 +1,348 @@
+//! Proof of funds for shielded accounts
+//!
+//! A proof of funds is a sapling bundle holding only spend descriptions of unspent notes of
+//! the prover, all under one anchor of the MASP commitment tree. The spend proofs show that
+//! the notes exist and belong to the prover without disclosing them, and the binding
+//! signature shows the value balance, i.e. the total of the notes per asset type. The
+//! verifier learns that total, the nullifiers and the anchor, but neither the viewing key
+//! nor the notes.
+//!
+//! Spend authorization and binding signatures are made over a hash of the statement (token,
+//! claimed amount, verifier challenge, anchor and asset types) instead of a transaction
+//! sighash, so the bundle can't be broadcast. Creating spend proofs needs the proof
+//! generation key, which a viewing key doesn't hold, so proofs are made with the spending key.
+use namada_sdk::address::Address;
+use namada_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use namada_sdk::masp::{AssetData, ShieldedContext, ShieldedUtils};
+use namada_sdk::masp_primitives::consensus::{BlockHeight, BranchId};
+use namada_sdk::masp_primitives::jubjub;
+use namada_sdk::masp_primitives::merkle_tree::MerklePath;
+use namada_sdk::masp_primitives::sapling::prover::TxProver;
+use namada_sdk::masp_primitives::sapling::redjubjub::PrivateKey;
+use namada_sdk::masp_primitives::sapling::{spend_sig, Diversifier, Node, Note};
+use namada_sdk::masp_primitives::transaction::components::sapling::{
+    Authorized, Bundle, SpendDescription,
+};
+use namada_sdk::masp_primitives::transaction::components::I128Sum;
+use namada_sdk::masp_primitives::transaction::{
+    Transaction as MaspTransaction, TransactionData, TxVersion,
+};
+use namada_sdk::masp_primitives::zip32::{ExtendedFullViewingKey, ExtendedSpendingKey};
+use namada_sdk::masp_proofs::bls12_381;
+use namada_sdk::rpc;
+use namada_sdk::state::Key;
+use namada_sdk::token::storage_key::{masp_commitment_anchor_key, masp_nullifier_key};
+use namada_sdk::token::Amount;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::JsError;
+
+use crate::rpc_client::HttpClient;
+use crate::sdk::masp::verify::{verify_bundle, VerifyingKeys};
+
+/// Domain separator of the statement hash, so proof signatures never sign a transaction
+const STATEMENT_DOMAIN: &[u8] = b"Namada proof of funds";
+
+/// Serialized proof of funds, shared with the verifier
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct ProofOfFunds {
+    pub token: String,
+    /// Claimed raw amount
+    pub amount: String,
+    /// Challenge chosen by the verifier, so the proof can't be replayed to someone else
+    pub challenge: String,
+    /// Root of the commitment tree all spends are anchored to
+    pub anchor: Node,
+    /// Decoded asset types of the value balance
+    pub asset_data: Vec<AssetData>,
+    /// MASP transaction holding only the spends, signed over the statement hash
+    pub masp_tx: MaspTransaction,
+}
+
+/// Hashes the statement the spend authorization and binding signatures are made over
+fn statement_hash(
+    token: &str,
+    amount: &str,
+    challenge: &str,
+    anchor: &Node,
+    asset_data: &[AssetData],
+) -> Result<[u8; 32], JsError> {
+    let statement = borsh::to_vec(&(token, amount, challenge, anchor, asset_data))?;
+    let mut hasher = Sha256::new();
+    hasher.update(STATEMENT_DOMAIN);
+    hasher.update(statement);
+
+    Ok(hasher.finalize().into())
+}
+
+fn random_scalar() -> jubjub::Fr {
+    let mut bytes = [0u8; 64];
+    OsRng.fill_bytes(&mut bytes);
+    jubjub::Fr::from_bytes_wide(&bytes)
+}
+
+/// Selects unspent notes of a token held by a spending key, largest first, until their sum
+/// covers the amount, and proves them with spend descriptions anchored to the root of the
+/// synced commitment tree. Requires MASP params to be loaded.
+///
+/// # Arguments
+///
+/// * `shielded` - Synced shielded context
+/// * `xsk` - Spending key holding the notes
+/// * `token` - Token address
+/// * `amount` - Raw amount to prove
+/// * `challenge` - Challenge chosen by the verifier
+///
+/// # Errors
+///
+/// Returns JsError if the key does not hold enough unspent notes of the token or a proof
+/// can't be created
+pub fn proof_of_funds<U: ShieldedUtils>(
+    shielded: &ShieldedContext<U>,
+    xsk: &ExtendedSpendingKey,
+    token: &Address,
+    amount: Amount,
+    challenge: String,
+) -> Result<ProofOfFunds, JsError> {
+    let vk = ExtendedFullViewingKey::from(xsk).fvk.vk;
+    let anchor = shielded.tree.root();
+    let mut candidates: Vec<(Amount, usize, &AssetData)> = vec![];
+    for position in shielded.pos_map.get(&vk).into_iter().flatten() {
+        if shielded.spents.contains(position) {
+            continue;
+        }
+        let note = match shielded.note_map.get(position) {
+            Some(note) => note,
+            None => continue,
+        };
+        let asset_data = match shielded.asset_types.get(&note.asset_type) {
+            Some(asset_data) if &asset_data.token == token => asset_data,
+            _ => continue,
+        };
+        let value = Amount::from_masp_denominated(note.value, asset_data.position);
+        candidates.push((value, *position, asset_data));
+    }
+    candidates.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut total = Amount::zero();
+    let mut selected: Vec<(&Note, Diversifier, MerklePath<Node>)> = vec![];
+    let mut asset_data: Vec<AssetData> = vec![];
+    for (value, position, data) in candidates {
+        if total >= amount {
+            break;
+        }
+        let note = &shielded.note_map[&position];
+        let diversifier = shielded
+            .div_map
+            .get(&position)
+            .ok_or_else(|| JsError::new("Diversifier of a note is missing, sync first"))?;
+        let merkle_path = shielded
+            .witness_map
+            .get(&position)
+            .filter(|witness| witness.root() == anchor)
+            .and_then(|witness| witness.path())
+            .ok_or_else(|| JsError::new("Witness of a note is missing, sync first"))?;
+
+        selected.push((note, *diversifier, merkle_path));
+        if !asset_data.contains(data) {
+            asset_data.push(data.clone());
+        }
+        total = total
+            .checked_add(value)
+            .ok_or_else(|| JsError::new("Proof of funds amount overflow"))?;
+    }
+    if total < amount {
+        return Err(JsError::new(&format!(
+            "Spending key holds only {} of {}",
+            total, token
+        )));
+    }
+
+    let token = token.to_string();
+    let amount = amount.to_string();
+    let sighash = statement_hash(&token, &amount, &challenge, &anchor, &asset_data)?;
+
+    let prover = shielded.utils.local_tx_prover();
+    let mut ctx = prover.new_sapling_proving_context();
+    let proof_generation_key = xsk.expsk.proof_generation_key();
+    let mut shielded_spends = vec![];
+    let mut value_balance = I128Sum::zero();
+    for (note, diversifier, merkle_path) in selected {
+        let ar = random_scalar();
+        let nullifier = note.nf(&vk.nk, merkle_path.position);
+        let (zkproof, cv, rk) = prover
+            .spend_proof(
+                &mut ctx,
+                proof_generation_key.clone(),
+                diversifier,
+                note.rseed,
+                ar,
+                note.asset_type,
+                note.value,
+                anchor.into(),
+                merkle_path,
+                random_scalar(),
+            )
+            .map_err(|_| JsError::new("Failed to create a spend proof"))?;
+
+        shielded_spends.push(SpendDescription {
+            cv,
+            anchor: anchor.into(),
+            nullifier,
+            rk,
+            zkproof,
+            spend_auth_sig: spend_sig(PrivateKey(xsk.expsk.ask), ar, &sighash, &mut OsRng),
+        });
+        value_balance += I128Sum::from_pair(note.asset_type, i128::from(note.value));
+    }
+    let binding_sig = prover
+        .binding_sig(&mut ctx, &value_balance, &sighash)
+        .map_err(|_| JsError::new("Failed to create the binding signature"))?;
+
+    let bundle = Bundle {
+        shielded_spends,
+        shielded_converts: vec![],
+        shielded_outputs: vec![],
+        value_balance,
+        authorization: Authorized { binding_sig },
+    };
+    let masp_tx = TransactionData::from_parts(
+        TxVersion::MASPv5,
+        BranchId::MASP,
+        0,
+        BlockHeight::from_u32(0),
+        None,
+        Some(bundle),
+    )
+    .freeze()?;
+
+    Ok(ProofOfFunds {
+        token,
+        amount,
+        challenge,
+        anchor,
+        asset_data,
+        masp_tx,
+    })
+}
+
+/// Sums the value balance of the token. Returns None if a value is negative, i.e. the
+/// bundle doesn't only spend notes.
+fn disclosed_amount(
+    value_balance: &I128Sum,
+    token: &Address,
+    asset_data: &[AssetData],
+) -> Result<Option<Amount>, JsError> {
+    let mut total = Amount::zero();
+    for (asset_type, value) in value_balance.components() {
+        let value = match u64::try_from(*value) {
+            Ok(value) => value,
+            Err(_) => return Ok(None),
+        };
+        let data = asset_data
+            .iter()
+            .find(|data| data.encode().ok() == Some(*asset_type))
+            .ok_or_else(|| JsError::new("Unknown asset type in proof of funds"))?;
+        if &data.token != token {
+            continue;
+        }
+
+        let amount = Amount::from_masp_denominated(value, data.position);
+        total = total
+            .checked_add(amount)
+            .ok_or_else(|| JsError::new("Proof of funds amount overflow"))?;
+    }
+
+    Ok(Some(total))
+}
+
+/// Checks the shape of the bundle of a proof and returns the nullifier keys of its spends.
+/// Returns None if the proof has anything but spends, spends a note twice or has spends
+/// under another anchor than the one of the proof.
+fn spent_nullifiers(proof: &ProofOfFunds) -> Option<Vec<Key>> {
+    if proof.masp_tx.transparent_bundle().is_some() {
+        return None;
+    }
+    let bundle = proof.masp_tx.sapling_bundle()?;
+    if !bundle.shielded_converts.is_empty() || !bundle.shielded_outputs.is_empty() {
+        return None;
+    }
+
+    let anchor: bls12_381::Scalar = proof.anchor.into();
+    let mut nullifiers = vec![];
+    for spend in bundle.shielded_spends.iter() {
+        if spend.anchor != anchor || nullifiers.contains(&spend.nullifier) {
+            return None;
+        }
+        nullifiers.push(spend.nullifier);
+    }
+
+    Some(nullifiers.iter().map(masp_nullifier_key).collect())
+}
+
+/// Verifies a proof of funds against the current chain state. Returns false if the proof
+/// was made for another challenge, its spend proofs or signatures don't verify, the anchor
+/// is unknown, any of the notes was already spent or the disclosed amount is lower than the
+/// claimed one. Requires MASP params to be loaded.
+///
+/// # Arguments
+///
+/// * `client` - RPC client
+/// * `keys` - MASP verifying keys
+/// * `proof` - Borsh serialized ProofOfFunds
+/// * `challenge` - Challenge the proof was requested with
+///
+/// # Errors
+///
+/// Returns JsError if the proof can't be deserialized or queries fail
+pub async fn verify_proof_of_funds(
+    client: &HttpClient,
+    keys: &VerifyingKeys,
+    proof: &[u8],
+    challenge: &str,
+) -> Result<bool, JsError> {
+    let proof = ProofOfFunds::try_from_slice(proof)?;
+    if proof.challenge != challenge {
+        return Ok(false);
+    }
+    let token: Address = proof.token.parse()?;
+    let claimed = Amount::from_str(&proof.amount, 0u8)?;
+
+    let nullifier_keys = match spent_nullifiers(&proof) {
+        Some(nullifier_keys) => nullifier_keys,
+        None => return Ok(false),
+    };
+    let bundle = match proof.masp_tx.sapling_bundle() {
+        Some(bundle) => bundle,
+        None => return Ok(false),
+    };
+    match disclosed_amount(&bundle.value_balance, &token, &proof.asset_data)? {
+        Some(disclosed) if disclosed >= claimed => {}
+        _ => return Ok(false),
+    }
+
+    let sighash = statement_hash(
+        &proof.token,
+        &proof.amount,
+        &proof.challenge,
+        &proof.anchor,
+        &proof.asset_data,
+    )?;
+    if !verify_bundle(bundle, &sighash, keys)? {
+        return Ok(false);
+    }
+
+    let anchor_key = masp_commitment_anchor_key(proof.anchor);
+    if !rpc::query_has_storage_key(client, &anchor_key).await? {
+        return Ok(false);
+    }
+    for nullifier_key in nullifier_keys.iter() {
+        if rpc::query_has_storage_key(client, nullifier_key).await? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,318 @@
+//! Chunked QR codes for air-gapped signing
+//!
+//! Built txs are too large for a single QR code, so they are split into parts shown as an
+//! animated QR code and scanned by the air-gapped device, which returns its SignatureMsg the
+//! same way. Parts follow the Uniform Resources layout, upper case so that they fit the
+//! alphanumeric mode of QR codes:
+//!
+//! `UR:NAMADA-TX/3-10/8F1A2B3C/<base32 fragment>`
+//!
+//! with the sequence number and total of parts, and the checksum of the whole payload, the
+//! first 4 bytes of its sha256. Parts are scanned in any order, repeated parts are ignored.
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+use crate::utils::to_js_result;
+
+const UR_PREFIX: &str = "UR:";
+/// Default number of payload bytes per part, fitting a QR code of version ~15
+pub const DEFAULT_FRAGMENT_LEN: usize = 200;
+/// RFC 4648 base32 alphabet, in the QR alphanumeric charset
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Kind of a chunked payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PayloadKind {
+    /// Borsh serialized tx::Tx as built by the Sdk
+    Tx,
+    /// Borsh serialized SignatureMsg, see Sdk::append_signature
+    Signature,
+}
+
+impl PayloadKind {
+    fn ur_type(self) -> &'static str {
+        match self {
+            PayloadKind::Tx => "NAMADA-TX",
+            PayloadKind::Signature => "NAMADA-SIG",
+        }
+    }
+
+    fn from_ur_type(ur_type: &str) -> Option<PayloadKind> {
+        [PayloadKind::Tx, PayloadKind::Signature]
+            .into_iter()
+            .find(|kind| kind.ur_type() == ur_type)
+    }
+
+    fn parse(kind: &str) -> Result<PayloadKind, JsError> {
+        match kind {
+            "tx" => Ok(PayloadKind::Tx),
+            "signature" => Ok(PayloadKind::Signature),
+            _ => Err(JsError::new(&format!("Unknown payload kind {}", kind))),
+        }
+    }
+}
+
+fn checksum(bytes: &[u8]) -> String {
+    hex::encode_upper(&Sha256::digest(bytes)[..4])
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    encoded
+}
+
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(encoded.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in encoded.bytes() {
+        let value = BASE32_ALPHABET.iter().position(|a| *a == c)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Splits a payload into QR parts of at most `fragment_len` payload bytes
+pub fn encode_parts(kind: PayloadKind, payload: &[u8], fragment_len: usize) -> Vec<String> {
+    let fragment_len = fragment_len.max(1);
+    let checksum = checksum(payload);
+    let fragments: Vec<&[u8]> = if payload.is_empty() {
+        vec![&[]]
+    } else {
+        payload.chunks(fragment_len).collect()
+    };
+    let total = fragments.len();
+    fragments
+        .into_iter()
+        .enumerate()
+        .map(|(i, fragment)| {
+            format!(
+                "{}{}/{}-{}/{}/{}",
+                UR_PREFIX,
+                kind.ur_type(),
+                i + 1,
+                total,
+                checksum,
+                base32_encode(fragment)
+            )
+        })
+        .collect()
+}
+
+struct Part {
+    kind: PayloadKind,
+    seq: usize,
+    total: usize,
+    checksum: String,
+    fragment: Vec<u8>,
+}
+
+fn parse_part(part: &str) -> Result<Part, String> {
+    let part = part.trim().to_uppercase();
+    let body = part
+        .strip_prefix(UR_PREFIX)
+        .ok_or_else(|| "Not a UR part".to_string())?;
+    let fields: Vec<&str> = body.split('/').collect();
+    let [ur_type, position, checksum, fragment] = fields[..] else {
+        return Err("Invalid UR part".to_string());
+    };
+    let kind =
+        PayloadKind::from_ur_type(ur_type).ok_or_else(|| format!("Unknown UR type {}", ur_type))?;
+    let (seq, total) = position
+        .split_once('-')
+        .and_then(|(seq, total)| Some((seq.parse().ok()?, total.parse().ok()?)))
+        .filter(|(seq, total): &(usize, usize)| *seq >= 1 && seq <= total)
+        .ok_or_else(|| format!("Invalid part position {}", position))?;
+    let fragment = base32_decode(fragment).ok_or_else(|| "Invalid part encoding".to_string())?;
+
+    Ok(Part {
+        kind,
+        seq,
+        total,
+        checksum: checksum.to_string(),
+        fragment,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeProgress {
+    pub kind: PayloadKind,
+    pub received: usize,
+    pub total: usize,
+    pub complete: bool,
+}
+
+/// Collects scanned QR parts into the payload
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct QrPartsDecoder {
+    /// Kind, total and checksum of the first part, shared by all parts
+    header: Option<(PayloadKind, usize, String)>,
+    fragments: BTreeMap<usize, Vec<u8>>,
+}
+
+impl QrPartsDecoder {
+    fn receive_part(&mut self, part: &str) -> Result<DecodeProgress, String> {
+        let part = parse_part(part)?;
+        let header = (part.kind, part.total, part.checksum);
+        match &self.header {
+            Some(current) if *current != header => {
+                return Err("Part of another payload, reset the decoder first".to_string())
+            }
+            _ => self.header = Some(header.clone()),
+        }
+        self.fragments.entry(part.seq).or_insert(part.fragment);
+
+        let (kind, total, _) = header;
+        Ok(DecodeProgress {
+            kind,
+            received: self.fragments.len(),
+            total,
+            complete: self.fragments.len() == total,
+        })
+    }
+
+    fn payload(&self) -> Result<Option<Vec<u8>>, String> {
+        let Some((_, total, checksum)) = &self.header else {
+            return Ok(None);
+        };
+        if self.fragments.len() < *total {
+            return Ok(None);
+        }
+        let payload = self
+            .fragments
+            .values()
+            .flatten()
+            .copied()
+            .collect::<Vec<u8>>();
+        if self::checksum(&payload) != *checksum {
+            return Err("Checksum mismatch, the parts are corrupted".to_string());
+        }
+        Ok(Some(payload))
+    }
+}
+
+#[wasm_bindgen]
+impl QrPartsDecoder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> QrPartsDecoder {
+        QrPartsDecoder::default()
+    }
+
+    /// Adds a scanned part, returning the DecodeProgress
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the part is invalid or of another payload than the previous parts
+    pub fn receive(&mut self, part: &str) -> Result<JsValue, JsError> {
+        let progress = self.receive_part(part).map_err(|e| JsError::new(&e))?;
+        to_js_result(progress)
+    }
+
+    /// Returns the payload once all parts are received, undefined before
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the payload doesn't match the checksum of the parts
+    pub fn result(&self) -> Result<Option<Vec<u8>>, JsError> {
+        self.payload().map_err(|e| JsError::new(&e))
+    }
+
+    /// Drops the received parts, to scan another payload
+    pub fn reset(&mut self) {
+        *self = QrPartsDecoder::default();
+    }
+}
+
+/// Splits a payload into the parts of an animated QR code
+///
+/// # Arguments
+///
+/// * `kind` - "tx" for built txs, "signature" for SignatureMsgs
+/// * `payload` - Payload bytes
+/// * `fragment_len` - Payload bytes per part, defaults to DEFAULT_FRAGMENT_LEN
+///
+/// # Errors
+///
+/// Returns JsError if the kind is unknown
+#[wasm_bindgen]
+pub fn encode_qr_parts(
+    kind: &str,
+    payload: &[u8],
+    fragment_len: Option<usize>,
+) -> Result<Vec<String>, JsError> {
+    let kind = PayloadKind::parse(kind)?;
+    Ok(encode_parts(
+        kind,
+        payload,
+        fragment_len.unwrap_or(DEFAULT_FRAGMENT_LEN),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn encodes_and_decodes_parts_in_any_order() {
+        for len in [0, 1, 4, 5, 13] {
+            let bytes: Vec<u8> = (0..len).map(|i| i * 37).collect();
+            assert_eq!(base32_decode(&base32_encode(&bytes)).unwrap(), bytes);
+        }
+
+        let payload: Vec<u8> = (0..=255).collect();
+        let parts = encode_parts(PayloadKind::Tx, &payload, 100);
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].starts_with("UR:NAMADA-TX/1-3/"));
+        assert!(parts.iter().all(|part| part
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || "-:/".contains(c))));
+
+        let mut decoder = QrPartsDecoder::default();
+        decoder.receive_part(&parts[2]).unwrap();
+        // Repeated parts of animated codes are ignored, lower case scans accepted
+        let progress = decoder.receive_part(&parts[2].to_lowercase()).unwrap();
+        assert_eq!((progress.received, progress.complete), (1, false));
+        assert_eq!(decoder.payload().unwrap(), None);
+        decoder.receive_part(&parts[0]).unwrap();
+        let progress = decoder.receive_part(&parts[1]).unwrap();
+        assert!(progress.complete);
+        assert_eq!(decoder.payload().unwrap().unwrap(), payload);
+
+        let signature = encode_parts(PayloadKind::Signature, &[1, 2, 3], 100);
+        assert!(decoder.receive_part(&signature[0]).is_err());
+        assert!(decoder
+            .receive_part("UR:NAMADA-TX/4-3/00000000/AE")
+            .is_err());
+
+        // A corrupted fragment fails the checksum
+        let mut corrupted = QrPartsDecoder::default();
+        let mut part = signature[0].clone();
+        part.pop();
+        part.push('B');
+        corrupted.receive_part(&part).unwrap();
+        assert!(corrupted.payload().is_err());
+    }
+}
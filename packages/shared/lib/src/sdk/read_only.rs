@@ -0,0 +1,84 @@
+//! Read-only bindings of the Sdk to JS
+//!
+//! Deployments embedding the wasm purely for queries and decoding, e.g. explorers and
+//! dashboards, use ReadOnlySdk instead of Sdk. It wraps an Sdk without a wallet or other
+//! storage and only forwards the entrypoints which neither sign txs nor handle keys, so
+//! signing is not reachable from it.
+use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
+
+use super::web::Sdk;
+
+/// Sdk for queries, decoding and broadcasting txs signed elsewhere
+#[wasm_bindgen]
+pub struct ReadOnlySdk {
+    sdk: Sdk,
+}
+
+#[wasm_bindgen]
+impl ReadOnlySdk {
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: String, native_token: String) -> Self {
+        ReadOnlySdk {
+            sdk: Sdk::new(url, native_token, String::new()),
+        }
+    }
+
+    /// See Sdk::set_chain_prefixes
+    pub fn set_chain_prefixes(&self, prefixes: JsValue) -> Result<(), JsError> {
+        self.sdk.set_chain_prefixes(prefixes)
+    }
+
+    /// See Sdk::chain_prefixes
+    pub fn chain_prefixes(&self) -> Result<JsValue, JsError> {
+        self.sdk.chain_prefixes()
+    }
+
+    /// See Sdk::tx_counterparties
+    pub async fn tx_counterparties(&self, tx_bytes: &[u8]) -> Result<JsValue, JsError> {
+        self.sdk.tx_counterparties(tx_bytes).await
+    }
+
+    /// See Sdk::masp_descriptions
+    pub fn masp_descriptions(&self, tx: &[u8]) -> Result<JsValue, JsError> {
+        self.sdk.masp_descriptions(tx)
+    }
+
+    /// See Sdk::verify_arbitrary
+    pub fn verify_arbitrary(
+        &self,
+        public_key: String,
+        signed_hash: String,
+        signature: String,
+    ) -> Result<(), JsError> {
+        self.sdk
+            .verify_arbitrary(public_key, signed_hash, signature)
+    }
+
+    pub fn masp_address(&self) -> String {
+        self.sdk.masp_address()
+    }
+
+    /// See Sdk::process_tx
+    pub async fn process_tx(
+        &self,
+        tx_bytes: &[u8],
+        tx_msg: &[u8],
+        idempotency_key: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        self.sdk.process_tx(tx_bytes, tx_msg, idempotency_key).await
+    }
+
+    /// See Sdk::release_idempotency_key
+    pub fn release_idempotency_key(&self, key: String) {
+        self.sdk.release_idempotency_key(key)
+    }
+
+    /// See Sdk::broadcast_tx_to_endpoints
+    pub async fn broadcast_tx_to_endpoints(
+        &self,
+        tx_bytes: &[u8],
+        urls: Box<[JsValue]>,
+    ) -> Result<JsValue, JsError> {
+        self.sdk.broadcast_tx_to_endpoints(tx_bytes, urls).await
+    }
+}
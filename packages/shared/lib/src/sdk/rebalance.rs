@@ -0,0 +1,404 @@
+//! Delegation portfolio rebalancing
+//!
+//! Delegators spreading their stake over several validators drift away from their target
+//! weights as rewards, slashes and new bonds add up. plan_rebalance compares the bonds of an
+//! owner with target weights per validator and returns the redelegations, bonds and unbonds
+//! reaching them, which Sdk.build_rebalance turns into a single batch tx.
+//!
+//! Surpluses are matched to deficits largest first, which keeps the number of messages low.
+//! Tokens redelegated to a validator can't be redelegated again until the slashes of the
+//! source validator are processed, see namada_sdk::tx::build_redelegation. Surpluses of such
+//! validators are unbonded first when the total shrinks, and otherwise reported as blocked.
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use namada_sdk::address::Address;
+use namada_sdk::queries::RPC;
+use namada_sdk::rpc::{self, query_epoch};
+use namada_sdk::token::{Amount, NATIVE_MAX_DECIMAL_PLACES};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsError;
+
+use super::prefixes::ChainPrefixes;
+use crate::rpc_client::HttpClient;
+
+/// Precision of target weights
+const WEIGHT_PARTS: f64 = 1_000_000.0;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Redelegation {
+    pub source: String,
+    pub destination: String,
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeChange {
+    pub validator: String,
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebalancePlan {
+    pub owner: String,
+    pub unbonds: Vec<StakeChange>,
+    pub redelegations: Vec<Redelegation>,
+    pub bonds: Vec<StakeChange>,
+    /// Surpluses which can't be redelegated yet, left in place
+    pub blocked: Vec<StakeChange>,
+}
+
+impl RebalancePlan {
+    pub fn is_empty(&self) -> bool {
+        self.unbonds.is_empty() && self.redelegations.is_empty() && self.bonds.is_empty()
+    }
+
+    /// Re-encodes the addresses of the plan with the configured prefixes
+    fn with_prefixes(self, prefixes: &ChainPrefixes) -> RebalancePlan {
+        let changes = |changes: Vec<StakeChange>| {
+            changes
+                .into_iter()
+                .map(|change| StakeChange {
+                    validator: prefixes.configured(&change.validator),
+                    ..change
+                })
+                .collect()
+        };
+        RebalancePlan {
+            owner: prefixes.configured(&self.owner),
+            unbonds: changes(self.unbonds),
+            redelegations: self
+                .redelegations
+                .into_iter()
+                .map(|redelegation| Redelegation {
+                    source: prefixes.configured(&redelegation.source),
+                    destination: prefixes.configured(&redelegation.destination),
+                    ..redelegation
+                })
+                .collect(),
+            bonds: changes(self.bonds),
+            blocked: changes(self.blocked),
+        }
+    }
+}
+
+fn native(amount: u128) -> String {
+    Amount::from_u128(amount).to_string_native()
+}
+
+/// Converts target weights to integer parts
+///
+/// # Errors
+///
+/// Returns JsError if there are no targets or a weight is not a positive number
+pub fn weight_parts(targets: &BTreeMap<String, f64>) -> Result<BTreeMap<String, u128>, JsError> {
+    if targets.is_empty() {
+        return Err(JsError::new("Rebalancing requires at least one target"));
+    }
+    targets
+        .iter()
+        .map(|(validator, weight)| {
+            let parts = (weight * WEIGHT_PARTS).round();
+            if !weight.is_finite() || parts < 1.0 {
+                return Err(JsError::new(&format!(
+                    "Invalid weight {} of validator {}",
+                    weight, validator
+                )));
+            }
+            Ok((validator.clone(), parts as u128))
+        })
+        .collect()
+}
+
+/// Splits total by weights, the rounding remainder going to the largest weight
+pub fn target_amounts(weights: &BTreeMap<String, u128>, total: u128) -> BTreeMap<String, u128> {
+    let sum: u128 = weights.values().sum();
+    let mut targets: BTreeMap<String, u128> = weights
+        .iter()
+        .map(|(validator, weight)| (validator.clone(), total * weight / sum))
+        .collect();
+
+    let remainder = total - targets.values().sum::<u128>();
+    if let Some((largest, _)) = weights.iter().max_by_key(|(_, weight)| **weight) {
+        *targets.get_mut(largest).expect("Target of every weight") += remainder;
+    }
+    targets
+}
+
+/// Sorts differences largest first, by address on ties
+fn largest_first(mut differences: Vec<(String, u128)>) -> Vec<(String, u128)> {
+    differences.sort_by(|(a, a_amount), (b, b_amount)| b_amount.cmp(a_amount).then(a.cmp(b)));
+    differences
+}
+
+/// Plans the messages moving the current bonds of an owner to the target amounts
+///
+/// # Arguments
+///
+/// * `current` - bonded amounts by validator
+/// * `locked` - validators whose bonds can't be redelegated yet
+/// * `weights` - target weight parts by validator, see weight_parts
+/// * `total` - total amount to have bonded afterwards
+pub fn plan(
+    owner: &str,
+    current: &BTreeMap<String, u128>,
+    locked: &BTreeSet<String>,
+    weights: &BTreeMap<String, u128>,
+    total: u128,
+) -> RebalancePlan {
+    let targets = target_amounts(weights, total);
+    let amount_of = |amounts: &BTreeMap<String, u128>, validator: &String| {
+        amounts.get(validator).copied().unwrap_or_default()
+    };
+    let validators: BTreeSet<&String> = current.keys().chain(targets.keys()).collect();
+
+    let mut surpluses = vec![];
+    let mut deficits = vec![];
+    for validator in validators {
+        let (bonded, target) = (
+            amount_of(current, validator),
+            amount_of(&targets, validator),
+        );
+        if bonded > target {
+            surpluses.push((validator.clone(), bonded - target));
+        } else if target > bonded {
+            deficits.push((validator.clone(), target - bonded));
+        }
+    }
+
+    let mut plan = RebalancePlan {
+        owner: owner.to_string(),
+        ..RebalancePlan::default()
+    };
+
+    // Unbond what can't be redelegated first
+    let current_total: u128 = current.values().sum();
+    let mut to_unbond = current_total.saturating_sub(total);
+    surpluses = largest_first(surpluses);
+    surpluses.sort_by_key(|(validator, _)| !locked.contains(validator));
+    for (validator, surplus) in surpluses.iter_mut() {
+        let amount = (*surplus).min(to_unbond);
+        if amount > 0 {
+            plan.unbonds.push(StakeChange {
+                validator: validator.clone(),
+                amount: native(amount),
+            });
+            *surplus -= amount;
+            to_unbond -= amount;
+        }
+    }
+
+    let (blocked, free): (Vec<_>, Vec<_>) = surpluses
+        .into_iter()
+        .filter(|(_, surplus)| *surplus > 0)
+        .partition(|(validator, _)| locked.contains(validator));
+    plan.blocked = blocked
+        .into_iter()
+        .map(|(validator, amount)| StakeChange {
+            validator,
+            amount: native(amount),
+        })
+        .collect();
+
+    let mut deficits = largest_first(deficits).into_iter();
+    let mut deficit = deficits.next();
+    for (source, mut surplus) in largest_first(free) {
+        while surplus > 0 {
+            let Some((destination, missing)) = deficit.as_mut() else {
+                break;
+            };
+            let amount = surplus.min(*missing);
+            plan.redelegations.push(Redelegation {
+                source: source.clone(),
+                destination: destination.clone(),
+                amount: native(amount),
+            });
+            surplus -= amount;
+            *missing -= amount;
+            if *missing == 0 {
+                deficit = deficits.next();
+            }
+        }
+    }
+
+    let mut to_bond = total.saturating_sub(current_total);
+    for (validator, missing) in deficit.into_iter().chain(deficits) {
+        let amount = missing.min(to_bond);
+        if amount > 0 {
+            plan.bonds.push(StakeChange {
+                validator,
+                amount: native(amount),
+            });
+            to_bond -= amount;
+        }
+    }
+
+    plan
+}
+
+/// Queries the bonds of an owner and the validators whose bonds can't be redelegated yet
+pub async fn query_bonds(
+    client: &HttpClient,
+    owner: &Address,
+) -> Result<(BTreeMap<String, u128>, BTreeSet<String>), JsError> {
+    let epoch = query_epoch(client).await?;
+    let params = rpc::get_pos_params(client).await?;
+    let validators = RPC
+        .vp()
+        .pos()
+        .delegation_validators(client, owner, &None)
+        .await?;
+
+    let mut bonds = BTreeMap::new();
+    let mut locked = BTreeSet::new();
+    for validator in validators {
+        let amount = rpc::get_bond_amount_at(client, owner, &validator, epoch).await?;
+        if amount.is_zero() {
+            continue;
+        }
+        // Native amounts fit in u128
+        bonds.insert(validator.to_string(), amount.raw_amount().as_u128());
+
+        let incoming = rpc::query_incoming_redelegations(client, &validator, owner).await?;
+        if incoming.is_some_and(|end| end + params.slash_processing_epoch_offset() > epoch) {
+            locked.insert(validator.to_string());
+        }
+    }
+    Ok((bonds, locked))
+}
+
+/// Plans the rebalancing of the bonds of owner to target weights by validator address. The
+/// addresses of the plan have the configured prefixes.
+///
+/// # Arguments
+///
+/// * `total` - amount to have bonded afterwards, defaults to the current total
+///
+/// # Errors
+///
+/// Returns JsError if the weights are invalid or the bonds can't be queried
+pub async fn plan_rebalance(
+    client: &HttpClient,
+    owner: &str,
+    targets: &BTreeMap<String, f64>,
+    total: Option<&str>,
+    prefixes: &ChainPrefixes,
+) -> Result<RebalancePlan, JsError> {
+    let targets: BTreeMap<String, f64> = targets
+        .iter()
+        .map(|(validator, weight)| (prefixes.native(validator).into_owned(), *weight))
+        .collect();
+    let weights = weight_parts(&targets)?;
+    for validator in weights.keys() {
+        Address::from_str(validator)?;
+    }
+    let owner = prefixes.native(owner);
+    let (current, locked) = query_bonds(client, &Address::from_str(&owner)?).await?;
+    let total = match total {
+        Some(total) => Amount::from_str(total, NATIVE_MAX_DECIMAL_PLACES)?
+            .raw_amount()
+            .as_u128(),
+        None => current.values().sum(),
+    };
+
+    Ok(plan(&owner, &current, &locked, &weights, total).with_prefixes(prefixes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const NAM: u128 = 1_000_000;
+
+    fn amounts(amounts: &[(&str, u128)]) -> BTreeMap<String, u128> {
+        amounts
+            .iter()
+            .map(|(validator, amount)| (validator.to_string(), *amount))
+            .collect()
+    }
+
+    fn change(validator: &str, amount: u128) -> StakeChange {
+        StakeChange {
+            validator: validator.to_string(),
+            amount: native(amount),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn splits_targets_by_weight() {
+        let targets = target_amounts(&amounts(&[("a", 1), ("b", 2)]), 100);
+        assert_eq!(targets, amounts(&[("a", 33), ("b", 67)]));
+        assert!(weight_parts(&BTreeMap::new()).is_err());
+        assert!(weight_parts(&[("a".to_string(), -1.0)].into()).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn redelegates_surplus_to_deficits() {
+        let current = amounts(&[("a", 60 * NAM), ("b", 40 * NAM)]);
+        let weights = amounts(&[("b", 1), ("c", 1)]);
+        let plan = plan("owner", &current, &BTreeSet::new(), &weights, 100 * NAM);
+
+        assert_eq!(
+            plan.redelegations,
+            vec![
+                Redelegation {
+                    source: "a".to_string(),
+                    destination: "c".to_string(),
+                    amount: native(50 * NAM),
+                },
+                Redelegation {
+                    source: "a".to_string(),
+                    destination: "b".to_string(),
+                    amount: native(10 * NAM),
+                },
+            ]
+        );
+        assert!(plan.bonds.is_empty() && plan.unbonds.is_empty() && plan.blocked.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn bonds_and_unbonds_total_changes() {
+        let current = amounts(&[("a", 50 * NAM), ("b", 50 * NAM)]);
+        let weights = amounts(&[("a", 1), ("b", 1)]);
+
+        let plan_more = plan("owner", &current, &BTreeSet::new(), &weights, 120 * NAM);
+        assert_eq!(
+            plan_more.bonds,
+            vec![change("a", 10 * NAM), change("b", 10 * NAM)]
+        );
+        assert!(plan_more.redelegations.is_empty());
+
+        let plan_less = plan("owner", &current, &BTreeSet::new(), &weights, 80 * NAM);
+        assert_eq!(
+            plan_less.unbonds,
+            vec![change("a", 10 * NAM), change("b", 10 * NAM)]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn unbonds_or_blocks_locked_surplus() {
+        let current = amounts(&[("a", 50 * NAM), ("b", 50 * NAM)]);
+        let locked: BTreeSet<String> = ["b".to_string()].into();
+        let weights = amounts(&[("a", 1), ("c", 1)]);
+
+        let plan_same = plan("owner", &current, &locked, &weights, 100 * NAM);
+        assert_eq!(plan_same.blocked, vec![change("b", 50 * NAM)]);
+        assert!(plan_same.redelegations.is_empty() && plan_same.bonds.is_empty());
+
+        let plan_less = plan("owner", &current, &locked, &weights, 60 * NAM);
+        assert_eq!(plan_less.unbonds, vec![change("b", 40 * NAM)]);
+        assert_eq!(plan_less.blocked, vec![change("b", 10 * NAM)]);
+        assert_eq!(
+            plan_less.redelegations,
+            vec![Redelegation {
+                source: "a".to_string(),
+                destination: "c".to_string(),
+                amount: native(20 * NAM),
+            }]
+        );
+    }
+}
@@ -0,0 +1,554 @@
+//! Account activity reports
+//!
+//! Scans a range of blocks and collects all inner transactions involving a given address,
+//! and the fees it paid, so they can be exported as CSV or JSON for accounting and tax
+//! tooling.
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use namada_sdk::address::{Address, ImplicitAddress};
+use namada_sdk::borsh::BorshDeserialize;
+use namada_sdk::io::Client;
+use namada_sdk::masp::{ShieldedContext, ShieldedUtils};
+use namada_sdk::masp_primitives::sapling::ViewingKey;
+use namada_sdk::masp_primitives::zip32::ExtendedFullViewingKey;
+use namada_sdk::tendermint;
+use namada_sdk::token::{Amount, DenominatedAmount};
+use namada_sdk::tx::{self, data::compute_inner_tx_hash, either::Either};
+use namada_sdk::uint::Uint;
+use namada_sdk::{ExtendedViewingKey, PaymentAddress};
+use serde::Serialize;
+use wasm_bindgen::JsError;
+
+use super::event_log::{logged_event, LoggedEvent};
+use super::transaction::TransactionKind;
+use super::tx::wasm_hash_to_tx_type;
+use crate::rpc_client::HttpClient;
+use crate::types::query::WasmHash;
+
+/// Maximum number of blocks scanned by a single transparent report
+pub const MAX_TRANSPARENT_REPORT_BLOCKS: u64 = 1_000;
+
+/// Descriptors of the token events paying out withdrawn bonds and claimed rewards
+const WITHDRAW_DESCRIPTOR: &str = "pos-withdraw";
+const CLAIM_REWARDS_DESCRIPTOR: &str = "pos-claim-rewards";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = JsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ReportFormat::Csv),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(JsError::new(&format!("Unsupported report format: {}", s))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportEntry {
+    pub height: u64,
+    pub wrapper_hash: String,
+    pub inner_hash: String,
+    pub kind: String,
+    pub direction: String,
+    pub counterparty: Option<String>,
+    pub token: Option<String>,
+    pub amount: Option<String>,
+    pub fee_token: String,
+    pub fee_amount_per_gas_unit: String,
+    pub gas_limit: String,
+    pub applied: bool,
+}
+
+const CSV_HEADER: &str = "height,wrapper_hash,inner_hash,kind,direction,counterparty,token,amount,\
+    fee_token,fee_amount_per_gas_unit,gas_limit,applied";
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl ReportEntry {
+    fn to_csv_row(&self) -> String {
+        [
+            self.height.to_string(),
+            self.wrapper_hash.clone(),
+            self.inner_hash.clone(),
+            self.kind.clone(),
+            self.direction.clone(),
+            self.counterparty.clone().unwrap_or_default(),
+            self.token.clone().unwrap_or_default(),
+            self.amount.clone().unwrap_or_default(),
+            self.fee_token.clone(),
+            self.fee_amount_per_gas_unit.clone(),
+            self.gas_limit.clone(),
+            self.applied.to_string(),
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<String>>()
+        .join(",")
+    }
+}
+
+/// Serializes report entries in the requested format
+pub fn format_report(entries: &[ReportEntry], format: ReportFormat) -> Result<String, JsError> {
+    match format {
+        ReportFormat::Json => Ok(serde_json::to_string(entries)?),
+        ReportFormat::Csv => {
+            let mut lines = vec![CSV_HEADER.to_string()];
+            lines.extend(entries.iter().map(ReportEntry::to_csv_row));
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+// Partial entry describing how a single inner tx relates to the owner
+struct Involvement {
+    kind: &'static str,
+    direction: &'static str,
+    counterparty: Option<String>,
+    token: Option<String>,
+    amount: Option<String>,
+}
+
+fn involvements(owner: &Address, tx_kind: &TransactionKind) -> Vec<Involvement> {
+    let mut result = vec![];
+
+    match tx_kind {
+        TransactionKind::Transfer(transfer) => {
+            for (account, amount) in transfer.sources.iter() {
+                if &account.owner == owner {
+                    result.push(Involvement {
+                        kind: "transfer",
+                        direction: "out",
+                        counterparty: None,
+                        token: Some(account.token.to_string()),
+                        amount: Some(amount.to_string()),
+                    });
+                }
+            }
+            for (account, amount) in transfer.targets.iter() {
+                if &account.owner == owner {
+                    result.push(Involvement {
+                        kind: "transfer",
+                        direction: "in",
+                        counterparty: None,
+                        token: Some(account.token.to_string()),
+                        amount: Some(amount.to_string()),
+                    });
+                }
+            }
+        }
+        TransactionKind::Bond(bond) if bond.source.as_ref() == Some(owner) => {
+            result.push(Involvement {
+                kind: "bond",
+                direction: "out",
+                counterparty: Some(bond.validator.to_string()),
+                token: None,
+                amount: Some(bond.amount.to_string_native()),
+            })
+        }
+        TransactionKind::Unbond(unbond) if unbond.source.as_ref() == Some(owner) => {
+            result.push(Involvement {
+                kind: "unbond",
+                direction: "none",
+                counterparty: Some(unbond.validator.to_string()),
+                token: None,
+                amount: Some(unbond.amount.to_string_native()),
+            })
+        }
+        TransactionKind::Withdraw(withdraw) if withdraw.source.as_ref() == Some(owner) => result
+            .push(Involvement {
+                kind: "withdraw",
+                direction: "in",
+                counterparty: Some(withdraw.validator.to_string()),
+                token: None,
+                amount: None,
+            }),
+        TransactionKind::Redelegation(redelegation) if &redelegation.owner == owner => {
+            result.push(Involvement {
+                kind: "redelegate",
+                direction: "none",
+                counterparty: Some(redelegation.dest_validator.to_string()),
+                token: None,
+                amount: Some(redelegation.amount.to_string_native()),
+            })
+        }
+        TransactionKind::ClaimRewards(claim) => {
+            let source = claim.source.as_ref().unwrap_or(&claim.validator);
+            if source == owner {
+                result.push(Involvement {
+                    kind: "claim_rewards",
+                    direction: "in",
+                    counterparty: Some(claim.validator.to_string()),
+                    token: None,
+                    amount: None,
+                })
+            }
+        }
+        TransactionKind::ProposalVote(vote) if &vote.voter == owner => result.push(Involvement {
+            kind: "vote_proposal",
+            direction: "none",
+            counterparty: Some(vote.id.to_string()),
+            token: None,
+            amount: None,
+        }),
+        TransactionKind::RevealPk(pk) => {
+            if &Address::Implicit(ImplicitAddress::from(pk)) == owner {
+                result.push(Involvement {
+                    kind: "reveal_pk",
+                    direction: "none",
+                    counterparty: None,
+                    token: None,
+                    amount: None,
+                })
+            }
+        }
+        TransactionKind::IbcTransfer(msg_transfer) => {
+            let packet_data = &msg_transfer.message.packet_data;
+            if packet_data.sender.to_string() == owner.to_string() {
+                result.push(Involvement {
+                    kind: "ibc_transfer",
+                    direction: "out",
+                    counterparty: Some(packet_data.receiver.to_string()),
+                    token: Some(packet_data.token.denom.to_string()),
+                    amount: Some(packet_data.token.amount.to_string()),
+                })
+            }
+        }
+        _ => {}
+    }
+
+    result
+}
+
+/// Returns the amounts of the token events of a tx paying the owner, with the given
+/// descriptor, in the order they were emitted
+fn payouts(owner: &Address, events: &[LoggedEvent], descriptor: &str) -> VecDeque<String> {
+    let owner = owner.to_string();
+    events
+        .iter()
+        .filter(|event| event.event_type.starts_with("token/"))
+        .filter(|event| {
+            event
+                .attributes
+                .get("token-event-descriptor")
+                .map(String::as_str)
+                == Some(descriptor)
+        })
+        .filter(|event| {
+            event
+                .attributes
+                .get("target")
+                .map_or(false, |target| target.contains(&owner))
+        })
+        .filter_map(|event| event.attributes.get("amount"))
+        .filter_map(|amount| Amount::from_str(amount, 0u8).ok())
+        .map(|amount| amount.to_string_native())
+        .collect()
+}
+
+/// Collects report entries for a transparent address within an inclusive height range of at
+/// most MAX_TRANSPARENT_REPORT_BLOCKS blocks. Besides the inner transactions involving the
+/// address, every fee it paid is reported as a "fee" entry, also when it's not involved in
+/// the inner transactions. Block results are only fetched for blocks involving the address.
+///
+/// # Arguments
+///
+/// * `client` - RPC client
+/// * `owner` - Address to build the report for
+/// * `from_height` - First block height to scan
+/// * `to_height` - Last block height to scan
+/// * `wasm_hashes` - Supported wasm code paths and their hashes
+///
+/// # Errors
+///
+/// Returns JsError if the range is too long or any of the blocks can't be fetched.
+pub async fn transparent_report(
+    client: &HttpClient,
+    owner: &Address,
+    from_height: u64,
+    to_height: u64,
+    wasm_hashes: &Vec<WasmHash>,
+) -> Result<Vec<ReportEntry>, JsError> {
+    validate_range(from_height, to_height)?;
+    if to_height - from_height >= MAX_TRANSPARENT_REPORT_BLOCKS {
+        return Err(JsError::new(&format!(
+            "Can't report more than {} blocks of a transparent address at once",
+            MAX_TRANSPARENT_REPORT_BLOCKS
+        )));
+    }
+    let mut entries: Vec<ReportEntry> = vec![];
+
+    for height in from_height..=to_height {
+        let tm_height = tendermint::block::Height::try_from(height)?;
+        let block = client.block(tm_height).await?;
+        let mut tx_results = None;
+
+        for (index, tx_bytes) in block.block.data.iter().enumerate() {
+            let tx = match tx::Tx::try_from_slice(tx_bytes) {
+                Ok(tx) => tx,
+                Err(_) => continue,
+            };
+            let wrapper = match tx.header().tx_type {
+                tx::data::TxType::Wrapper(wrapper) => wrapper,
+                _ => continue,
+            };
+            let wrapper_hash = tx.wrapper_hash();
+
+            let mut tx_involvements: Vec<(String, Involvement)> = vec![];
+            // Fees are paid by every wrapper included in a block
+            if &wrapper.fee_payer() == owner {
+                tx_involvements.push((
+                    String::new(),
+                    Involvement {
+                        kind: "fee",
+                        direction: "out",
+                        counterparty: None,
+                        token: Some(wrapper.fee.token.to_string()),
+                        amount: wrapper.get_tx_fee().ok().map(|fee| fee.to_string()),
+                    },
+                ));
+            }
+            for cmt in tx.commitments() {
+                let tx_code_id = tx
+                    .get_section(cmt.code_sechash())
+                    .and_then(|s| s.code_sec())
+                    .map(|s| s.code.hash().0)
+                    .map(|bytes| String::from_utf8(subtle_encoding::hex::encode(bytes)).unwrap());
+                let tx_type = match tx_code_id
+                    .and_then(|code_id| wasm_hash_to_tx_type(&code_id, wasm_hashes))
+                {
+                    Some(tx_type) => tx_type,
+                    None => continue,
+                };
+                let tx_data = tx.data(cmt).unwrap_or_default();
+                let tx_kind = TransactionKind::from(tx_type, &tx_data);
+                let inner_hash = compute_inner_tx_hash(wrapper_hash.as_ref(), Either::Right(cmt));
+
+                tx_involvements.extend(
+                    involvements(owner, &tx_kind)
+                        .into_iter()
+                        .map(|involvement| (inner_hash.to_string(), involvement)),
+                );
+            }
+            if tx_involvements.is_empty() {
+                continue;
+            }
+
+            if tx_results.is_none() {
+                let block_results = client.block_results(tm_height).await?;
+                tx_results = Some(block_results.txs_results.unwrap_or_default());
+            }
+            let tx_result = tx_results.as_ref().and_then(|results| results.get(index));
+            let applied = tx_result.map_or(false, |result| result.code.is_ok());
+            let events: Vec<LoggedEvent> = tx_result
+                .map(|result| {
+                    result
+                        .events
+                        .iter()
+                        .map(|event| logged_event(height, event))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let mut withdrawals = payouts(owner, &events, WITHDRAW_DESCRIPTOR);
+            let mut claims = payouts(owner, &events, CLAIM_REWARDS_DESCRIPTOR);
+
+            for (inner_hash, mut involvement) in tx_involvements {
+                let is_fee = involvement.kind == "fee";
+                involvement.amount = match involvement.kind {
+                    "withdraw" => withdrawals.pop_front(),
+                    "claim_rewards" => claims.pop_front(),
+                    _ => involvement.amount,
+                };
+                entries.push(ReportEntry {
+                    height,
+                    wrapper_hash: wrapper_hash.map(|h| h.to_string()).unwrap_or_default(),
+                    inner_hash,
+                    kind: involvement.kind.to_string(),
+                    direction: involvement.direction.to_string(),
+                    counterparty: involvement.counterparty,
+                    token: involvement.token,
+                    amount: involvement.amount,
+                    fee_token: wrapper.fee.token.to_string(),
+                    fee_amount_per_gas_unit: wrapper.fee.amount_per_gas_unit.to_string(),
+                    gas_limit: Uint::from(wrapper.gas_limit).to_string(),
+                    applied: is_fee || applied,
+                });
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Collects report entries for notes received by a viewing key within an inclusive height
+/// range, using the already synced shielded context. Spent notes are marked with "spent"
+/// direction as the shielded context does not track at which height a note was spent.
+pub fn shielded_report<U: ShieldedUtils>(
+    shielded: &ShieldedContext<U>,
+    vk: &ViewingKey,
+    from_height: u64,
+    to_height: u64,
+) -> Vec<ReportEntry> {
+    let mut entries: Vec<ReportEntry> = vec![];
+    let owned_notes = match shielded.pos_map.get(vk) {
+        Some(notes) => notes,
+        None => return entries,
+    };
+
+    let mut note_index = shielded.note_index.iter().peekable();
+    while let Some((indexed_tx, first_note)) = note_index.next() {
+        let last_note = note_index
+            .peek()
+            .map(|(_, next_first_note)| **next_first_note)
+            .unwrap_or(usize::MAX);
+        let height = indexed_tx.height.0;
+
+        if height < from_height || height > to_height {
+            continue;
+        }
+
+        for note_pos in owned_notes.range(*first_note..last_note) {
+            let note = match shielded.note_map.get(note_pos) {
+                Some(note) => note,
+                None => continue,
+            };
+            let asset_data = shielded.asset_types.get(&note.asset_type);
+            let direction = if shielded.spents.contains(note_pos) {
+                "spent"
+            } else {
+                "in"
+            };
+
+            entries.push(ReportEntry {
+                height,
+                wrapper_hash: String::new(),
+                inner_hash: String::new(),
+                kind: String::from("shielded_note"),
+                direction: direction.to_string(),
+                counterparty: None,
+                token: asset_data.map(|data| data.token.to_string()),
+                amount: asset_data.map(|data| {
+                    let amount = Amount::from_masp_denominated(note.value, data.position);
+                    DenominatedAmount::new(amount, data.denom).to_string()
+                }),
+                fee_token: String::new(),
+                fee_amount_per_gas_unit: String::new(),
+                gas_limit: String::new(),
+                applied: true,
+            });
+        }
+    }
+
+    entries
+}
+
+/// Read-only package handed to auditors, disclosing only the notes received in the height
+/// range. The incoming viewing key is included only on request, as it reveals all notes
+/// ever received by the key. Neither the full nor the outgoing viewing key is exported.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditorPackage {
+    pub incoming_viewing_key: Option<String>,
+    pub default_payment_address: String,
+    pub from_height: u64,
+    pub to_height: u64,
+    pub entries: Vec<ReportEntry>,
+}
+
+/// Builds an auditor package for an extended viewing key from the synced shielded context
+pub fn auditor_package<U: ShieldedUtils>(
+    shielded: &ShieldedContext<U>,
+    xvk: &ExtendedViewingKey,
+    from_height: u64,
+    to_height: u64,
+    include_incoming_viewing_key: bool,
+) -> AuditorPackage {
+    let xfvk = ExtendedFullViewingKey::from(*xvk);
+    let vk = xfvk.fvk.vk;
+    let (_, default_address) = xfvk.default_address();
+
+    AuditorPackage {
+        incoming_viewing_key: include_incoming_viewing_key.then(|| hex::encode(vk.ivk().to_repr())),
+        default_payment_address: PaymentAddress::from(default_address).to_string(),
+        from_height,
+        to_height,
+        entries: shielded_report(shielded, &vk, from_height, to_height),
+    }
+}
+
+/// Parses the owner of a report, which is either a transparent address or a viewing key
+pub fn report_owner(owner: &str) -> Result<Either<Address, ViewingKey>, JsError> {
+    match Address::from_str(owner) {
+        Ok(address) => Ok(Either::Left(address)),
+        Err(e1) => match ExtendedViewingKey::from_str(owner) {
+            Ok(xvk) => Ok(Either::Right(ExtendedFullViewingKey::from(xvk).fvk.vk)),
+            Err(e2) => Err(JsError::new(&format!("{} {}", e1, e2))),
+        },
+    }
+}
+
+/// Makes sure the report range is valid
+pub fn validate_range(from_height: u64, to_height: u64) -> Result<(), JsError> {
+    if from_height == 0 || from_height > to_height {
+        return Err(JsError::new(&format!(
+            "Invalid height range {}..{}",
+            from_height, to_height
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use wasm_bindgen_test::*;
+
+    const OWNER: &str = "tnam1qz4sdx5jlh909j44uz46pf29ty0ztftfzc98s8dx";
+    const OTHER: &str = "tnam1qry3lnk03j965y92np6e25jvadk3kw9u7cvwjclp";
+
+    fn token_event(descriptor: &str, target: &str, amount: &str) -> LoggedEvent {
+        LoggedEvent {
+            height: 1,
+            event_type: "token/transfer".to_string(),
+            attributes: BTreeMap::from([
+                ("token-event-descriptor".to_string(), descriptor.to_string()),
+                ("target".to_string(), target.to_string()),
+                ("amount".to_string(), amount.to_string()),
+            ]),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn reads_payouts_of_the_owner() {
+        let owner = Address::from_str(OWNER).unwrap();
+        let events = [
+            token_event(WITHDRAW_DESCRIPTOR, OWNER, "1500000"),
+            token_event(CLAIM_REWARDS_DESCRIPTOR, OWNER, "20"),
+            token_event(WITHDRAW_DESCRIPTOR, OTHER, "1"),
+            token_event(WITHDRAW_DESCRIPTOR, OWNER, "2000000"),
+        ];
+
+        assert_eq!(
+            payouts(&owner, &events, WITHDRAW_DESCRIPTOR),
+            VecDeque::from(["1.500000".to_string(), "2.000000".to_string()])
+        );
+        assert_eq!(
+            payouts(&owner, &events, CLAIM_REWARDS_DESCRIPTOR),
+            VecDeque::from(["0.000020".to_string()])
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Cache of ABCI query responses
+//!
+//! UI renders repeat the same queries of the latest state many times per block. Responses are
+//! cached by path, data and height: responses of queries at a given height never change and
+//! are kept until evicted, while responses of queries of the latest state are reused for at
+//! most LATEST_RESPONSE_TTL_MS, or until a newer block is observed, either from the height of
+//! a response or from the block subscription of the client with Query::observe_block. Queries
+//! with proofs are not cached.
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use namada_sdk::queries::EncodedResponseQuery;
+
+/// Maximum number of cached responses, the oldest are evicted first
+pub const MAX_CACHED_RESPONSES: usize = 1000;
+
+/// Milliseconds responses of queries of the latest state are reused for, well under the block
+/// time so that a missed block only serves stale responses within a render burst
+pub const LATEST_RESPONSE_TTL_MS: f64 = 1_000.0;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    path: String,
+    data: Vec<u8>,
+    /// Height the response was read at
+    height: u64,
+    /// Queried at the latest height, stale after a newer block
+    latest: bool,
+}
+
+#[derive(Default)]
+struct Cache {
+    /// Latest observed block height
+    height: Option<u64>,
+    /// Responses with the time they were cached at, in milliseconds
+    entries: HashMap<CacheKey, (EncodedResponseQuery, f64)>,
+    /// Keys in insertion order, for eviction
+    order: VecDeque<CacheKey>,
+}
+
+impl Cache {
+    fn key(&self, path: &str, data: &[u8], height: Option<u64>) -> Option<CacheKey> {
+        let (height, latest) = match height {
+            Some(height) if height > 0 => (height, false),
+            // Latest state can only be cached once a block height is known
+            _ => (self.height?, true),
+        };
+        Some(CacheKey {
+            path: path.to_string(),
+            data: data.to_vec(),
+            height,
+            latest,
+        })
+    }
+
+    fn observe_height(&mut self, height: u64) {
+        if self.height.is_some_and(|current| current >= height) {
+            return;
+        }
+        self.height = Some(height);
+        self.entries.retain(|key, _| !key.latest);
+        self.order.retain(|key| !key.latest);
+    }
+
+    fn get(
+        &self,
+        path: &str,
+        data: &[u8],
+        height: Option<u64>,
+        now: f64,
+    ) -> Option<EncodedResponseQuery> {
+        let key = self.key(path, data, height)?;
+        let (response, cached_at) = self.entries.get(&key)?;
+        if key.latest && now - cached_at > LATEST_RESPONSE_TTL_MS {
+            return None;
+        }
+        Some(response.clone())
+    }
+
+    fn insert(
+        &mut self,
+        path: &str,
+        data: &[u8],
+        height: Option<u64>,
+        response: &EncodedResponseQuery,
+        now: f64,
+    ) {
+        let is_latest = !matches!(height, Some(height) if height > 0);
+        if is_latest {
+            self.observe_height(response.height.0);
+        }
+        let Some(key) = self.key(path, data, height) else {
+            return;
+        };
+        // A response older than the observed height is stale already
+        if is_latest && response.height.0 < key.height {
+            return;
+        }
+
+        if self.entries.len() >= MAX_CACHED_RESPONSES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        if self
+            .entries
+            .insert(key.clone(), (response.clone(), now))
+            .is_none()
+        {
+            self.order.push_back(key);
+        }
+    }
+}
+
+/// Response cache, cheap to clone and shared by the clones of an HttpClient
+#[derive(Clone, Default)]
+pub struct ResponseCache(Arc<Mutex<Cache>>);
+
+impl ResponseCache {
+    /// Returns the cached response of a query, `height` None or 0 for the latest state
+    pub fn get(
+        &self,
+        path: &str,
+        data: &[u8],
+        height: Option<u64>,
+    ) -> Option<EncodedResponseQuery> {
+        self.0
+            .lock()
+            .unwrap()
+            .get(path, data, height, js_sys::Date::now())
+    }
+
+    /// Caches the response of a query, observing its height
+    pub fn insert(
+        &self,
+        path: &str,
+        data: &[u8],
+        height: Option<u64>,
+        response: &EncodedResponseQuery,
+    ) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(path, data, height, response, js_sys::Date::now());
+    }
+
+    /// Marks responses of the latest state stale if the block is newer than the observed one
+    pub fn observe_height(&self, height: u64) {
+        self.0.lock().unwrap().observe_height(height);
+    }
+
+    /// Drops all cached responses, e.g. after switching chains
+    pub fn clear(&self) {
+        *self.0.lock().unwrap() = Cache::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use namada_sdk::state::BlockHeight;
+    use wasm_bindgen_test::*;
+
+    fn response(data: u8, height: u64) -> EncodedResponseQuery {
+        EncodedResponseQuery {
+            data: vec![data],
+            height: BlockHeight(height),
+            ..EncodedResponseQuery::default()
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn expires_latest_responses() {
+        let mut cache = Cache::default();
+        let path = "/shell/value/#tnam1q/balance";
+
+        cache.insert(path, &[], None, &response(1, 10), 0.0);
+        cache.insert(path, &[], Some(10), &response(2, 10), 0.0);
+        assert_eq!(
+            cache
+                .get(path, &[], None, LATEST_RESPONSE_TTL_MS)
+                .unwrap()
+                .data,
+            [1]
+        );
+
+        // Without a newer block observed, latest responses expire while the ones at a height
+        // are kept
+        let later = LATEST_RESPONSE_TTL_MS + 1.0;
+        assert!(cache.get(path, &[], None, later).is_none());
+        assert_eq!(cache.get(path, &[], Some(10), later).unwrap().data, [2]);
+        cache.insert(path, &[], None, &response(3, 10), later);
+        assert_eq!(cache.get(path, &[], None, later).unwrap().data, [3]);
+    }
+
+    #[wasm_bindgen_test]
+    fn invalidates_latest_responses_on_new_blocks() {
+        let cache = ResponseCache::default();
+        let path = "/shell/value/#tnam1q/balance";
+
+        cache.insert(path, &[], None, &response(1, 10));
+        assert_eq!(cache.get(path, &[], None).unwrap().data, [1]);
+        assert!(cache.get(path, &[7], None).is_none());
+
+        // Responses at a height outlive new blocks
+        cache.insert(path, &[], Some(9), &response(2, 9));
+        cache.observe_height(11);
+        assert!(cache.get(path, &[], None).is_none());
+        assert_eq!(cache.get(path, &[], Some(9)).unwrap().data, [2]);
+
+        // Older blocks don't invalidate, older responses aren't cached as latest
+        cache.insert(path, &[], None, &response(3, 11));
+        cache.observe_height(10);
+        assert_eq!(cache.get(path, &[], None).unwrap().data, [3]);
+        cache.insert("/shell/epoch", &[], None, &response(4, 10));
+        assert!(cache.get("/shell/epoch", &[], None).is_none());
+
+        // A response from a newer block invalidates the others
+        cache.insert("/shell/epoch", &[], None, &response(5, 12));
+        assert!(cache.get(path, &[], None).is_none());
+        assert_eq!(cache.get("/shell/epoch", &[], Some(0)).unwrap().data, [5]);
+    }
+}
@@ -0,0 +1,204 @@
+//! Safety rails for large amounts
+//!
+//! A typo in an amount, e.g. an extra zero, can send most of a balance away. When limits are
+//! set with Sdk.set_amount_limits, transfers sending more than a fraction of the balance of
+//! the source, or more than a threshold of the token, are flagged so the UI asks for an
+//! explicit confirmation. The tx is built either way.
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use namada_sdk::address::{Address, MASP};
+use namada_sdk::borsh::BorshDeserialize;
+use namada_sdk::rpc;
+use namada_sdk::token::{Amount, DenominatedAmount, Transfer};
+use namada_sdk::tx::Tx;
+use namada_sdk::uint::Uint;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsError;
+
+use crate::rpc_client::HttpClient;
+
+/// Precision of balance fractions
+const BASIS_POINTS: u64 = 10_000;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmountLimits {
+    /// Fraction of the balance of a transparent source, between 0 and 1
+    pub max_balance_fraction: Option<f64>,
+    /// Denominated amounts by token address
+    #[serde(default)]
+    pub thresholds: HashMap<String, String>,
+}
+
+impl AmountLimits {
+    /// # Errors
+    ///
+    /// Returns JsError if the fraction is out of range or a threshold is not an amount
+    pub fn validate(&self) -> Result<(), JsError> {
+        if let Some(fraction) = self.max_balance_fraction {
+            if !(fraction > 0.0 && fraction <= 1.0) {
+                return Err(JsError::new(&format!(
+                    "Invalid max balance fraction {}, expected a number in (0, 1]",
+                    fraction
+                )));
+            }
+        }
+        for (token, threshold) in &self.thresholds {
+            Address::from_str(token)?;
+            DenominatedAmount::from_str(threshold)?;
+        }
+        Ok(())
+    }
+
+    fn basis_points(&self) -> Option<u64> {
+        self.max_balance_fraction
+            .map(|fraction| (fraction * BASIS_POINTS as f64).round() as u64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LimitKind {
+    BalanceFraction,
+    Threshold,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlaggedAmount {
+    pub owner: String,
+    pub token: String,
+    pub amount: String,
+    pub limit: LimitKind,
+    /// Balance of the owner for BalanceFraction, the threshold for Threshold
+    pub reference: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmountCheck {
+    pub requires_confirmation: bool,
+    pub flagged: Vec<FlaggedAmount>,
+}
+
+/// Returns the (owner, token, amount) sent by the transfers of a tx
+pub fn transfer_sources(tx: &Tx) -> Vec<(Address, Address, DenominatedAmount)> {
+    tx.commitments()
+        .iter()
+        .filter_map(|cmt| tx.data(cmt))
+        .filter_map(|data| Transfer::try_from_slice(&data).ok())
+        .flat_map(|transfer| {
+            transfer
+                .sources
+                .into_iter()
+                .map(|(account, amount)| (account.owner, account.token, amount))
+        })
+        .collect()
+}
+
+/// Returns true if amount is larger than basis_points / BASIS_POINTS of the balance
+pub fn exceeds_fraction(amount: Amount, balance: Amount, basis_points: u64) -> bool {
+    let scaled_amount = amount.raw_amount().checked_mul(Uint::from(BASIS_POINTS));
+    let scaled_balance = balance.raw_amount().checked_mul(Uint::from(basis_points));
+    match (scaled_amount, scaled_balance) {
+        (Some(amount), Some(balance)) => amount > balance,
+        // Only amounts close to the max can overflow
+        _ => true,
+    }
+}
+
+/// Returns true if amount is larger than the threshold, comparing them at the larger
+/// precision of both
+pub fn exceeds_threshold(
+    amount: &DenominatedAmount,
+    threshold: &DenominatedAmount,
+) -> Result<bool, JsError> {
+    let denom = amount.denom().max(threshold.denom());
+    Ok(amount.increase_precision(denom)?.amount() > threshold.increase_precision(denom)?.amount())
+}
+
+/// Checks the amounts sent by the transfers of a tx against the limits
+///
+/// # Errors
+///
+/// Returns JsError if a balance can't be queried
+pub async fn check_amounts(
+    client: &HttpClient,
+    limits: &AmountLimits,
+    tx: &Tx,
+) -> Result<AmountCheck, JsError> {
+    let mut flagged = vec![];
+
+    for (owner, token, amount) in transfer_sources(tx) {
+        let flag = |limit, reference: String| FlaggedAmount {
+            owner: owner.to_string(),
+            token: token.to_string(),
+            amount: amount.to_string(),
+            limit,
+            reference,
+        };
+
+        if let Some(threshold) = limits.thresholds.get(&token.to_string()) {
+            let threshold = DenominatedAmount::from_str(threshold)?;
+            if exceeds_threshold(&amount, &threshold)? {
+                flagged.push(flag(LimitKind::Threshold, threshold.to_string()));
+            }
+        }
+
+        // Shielded balances are not known here
+        if let (Some(basis_points), false) = (limits.basis_points(), owner == MASP) {
+            let balance = rpc::get_token_balance(client, &token, &owner, None).await?;
+            if exceeds_fraction(amount.amount(), balance, basis_points) {
+                let balance = DenominatedAmount::new(balance, amount.denom());
+                flagged.push(flag(LimitKind::BalanceFraction, balance.to_string()));
+            }
+        }
+    }
+
+    Ok(AmountCheck {
+        requires_confirmation: !flagged.is_empty(),
+        flagged,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn amount(amount: &str) -> DenominatedAmount {
+        DenominatedAmount::from_str(amount).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn flags_large_fractions_of_balance() {
+        let balance = Amount::from_u64(1_000);
+        assert!(!exceeds_fraction(Amount::from_u64(500), balance, 5_000));
+        assert!(exceeds_fraction(Amount::from_u64(501), balance, 5_000));
+        assert!(!exceeds_fraction(
+            Amount::from_u64(1_000),
+            balance,
+            BASIS_POINTS
+        ));
+        assert!(exceeds_fraction(Amount::from_u64(1), Amount::zero(), 5_000));
+    }
+
+    #[wasm_bindgen_test]
+    fn compares_thresholds_across_precisions() {
+        assert!(exceeds_threshold(&amount("100.5"), &amount("100")).unwrap());
+        assert!(!exceeds_threshold(&amount("100"), &amount("100.000")).unwrap());
+        assert!(!exceeds_threshold(&amount("99.99"), &amount("100")).unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    fn validates_limits() {
+        let limits = |fraction| AmountLimits {
+            max_balance_fraction: Some(fraction),
+            thresholds: HashMap::new(),
+        };
+        assert!(limits(0.5).validate().is_ok());
+        assert!(limits(0.0).validate().is_err());
+        assert!(limits(1.5).validate().is_err());
+    }
+}
@@ -0,0 +1,367 @@
+//! Screening of counterparties before signing
+//!
+//! Regulated integrators have to refuse txs involving sanctioned or otherwise blocked
+//! addresses. Before a tx is signed, the counterparties of its inner txs are decoded and passed
+//! to the screening set on the Sdk, either a list of blocked addresses or a JS callback. If the
+//! screening rejects them, signing fails with a policy error.
+//!
+//! Inner txs are identified by the hash of their code, resolved through the code hashes
+//! registered on chain, as the tag of a code section is not authenticated. Inner txs whose
+//! code is unknown or whose data can't be decoded are rejected, so the screening can't be
+//! bypassed with a tx it doesn't understand. Decoding is also built without the web feature,
+//! for the screening of the ffi bindings.
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+#[cfg(feature = "web")]
+use js_sys::{Function, Promise};
+use namada_sdk::address::MASP;
+use namada_sdk::borsh::BorshDeserialize;
+use namada_sdk::eth_bridge_pool::PendingTransfer;
+use namada_sdk::ibc::MsgTransfer;
+use namada_sdk::token::Transfer;
+use namada_sdk::tx::data::pos::{
+    Bond, ClaimRewards, CommissionChange, ConsensusKeyChange, MetaDataChange, Redelegation, Unbond,
+    Withdraw,
+};
+use namada_sdk::tx::Tx;
+use serde::Serialize;
+#[cfg(feature = "web")]
+use wasm_bindgen::{JsCast, JsValue};
+#[cfg(feature = "web")]
+use wasm_bindgen_futures::JsFuture;
+
+use super::code_paths::{tx_code_of_path, TxCode};
+use super::error::JsError;
+use super::prefixes::ChainPrefixes;
+#[cfg(feature = "web")]
+use crate::types::query::WasmHash;
+#[cfg(feature = "web")]
+use crate::utils::to_js_result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CounterpartyRole {
+    Source,
+    Target,
+    Validator,
+    /// Receiver on another chain, of IBC or Ethereum bridge transfers
+    Receiver,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Counterparty {
+    pub address: String,
+    pub role: CounterpartyRole,
+    /// Hash of the inner tx
+    pub commitment: String,
+}
+
+#[cfg(feature = "web")]
+pub enum Screening {
+    /// Addresses which may not be counterparties
+    Blocklist(HashSet<String>),
+    /// `(counterparties: Counterparty[]) => boolean | Promise<boolean>`, true to allow
+    Callback(Function),
+}
+
+fn transfer_counterparties(transfer: &Transfer) -> Vec<(String, CounterpartyRole)> {
+    let sources = transfer
+        .sources
+        .keys()
+        .map(|account| (account, CounterpartyRole::Source));
+    let targets = transfer
+        .targets
+        .keys()
+        .map(|account| (account, CounterpartyRole::Target));
+    sources
+        .chain(targets)
+        // Shielded owners are not part of the tx
+        .filter(|(account, _)| account.owner != MASP)
+        .map(|(account, role)| (account.owner.to_string(), role))
+        .collect()
+}
+
+fn decode_counterparties(
+    code: TxCode,
+    data: &[u8],
+) -> Result<Vec<(String, CounterpartyRole)>, std::io::Error> {
+    let validator = |validator: &namada_sdk::address::Address| {
+        vec![(validator.to_string(), CounterpartyRole::Validator)]
+    };
+    let counterparties = match code {
+        TxCode::Transfer => transfer_counterparties(&Transfer::try_from_slice(data)?),
+        TxCode::Ibc => {
+            let msg = MsgTransfer::<Transfer>::try_from_slice(data)?;
+            let mut counterparties = msg
+                .transfer
+                .as_ref()
+                .map(transfer_counterparties)
+                .unwrap_or_default();
+            counterparties.push((
+                msg.message.packet_data.receiver.to_string(),
+                CounterpartyRole::Receiver,
+            ));
+            counterparties
+        }
+        TxCode::BridgePool => {
+            let pending = PendingTransfer::try_from_slice(data)?;
+            vec![(
+                pending.transfer.recipient.to_string(),
+                CounterpartyRole::Receiver,
+            )]
+        }
+        TxCode::Bond => validator(&Bond::try_from_slice(data)?.validator),
+        TxCode::Unbond => validator(&Unbond::try_from_slice(data)?.validator),
+        TxCode::Withdraw => validator(&Withdraw::try_from_slice(data)?.validator),
+        TxCode::ClaimRewards => validator(&ClaimRewards::try_from_slice(data)?.validator),
+        TxCode::Redelegate => {
+            let redelegation = Redelegation::try_from_slice(data)?;
+            [
+                validator(&redelegation.src_validator),
+                validator(&redelegation.dest_validator),
+            ]
+            .concat()
+        }
+        TxCode::ChangeCommission => validator(&CommissionChange::try_from_slice(data)?.validator),
+        TxCode::ChangeMetadata => validator(&MetaDataChange::try_from_slice(data)?.validator),
+        TxCode::ChangeConsensusKey => {
+            validator(&ConsensusKeyChange::try_from_slice(data)?.validator)
+        }
+        TxCode::RevealPk | TxCode::VoteProposal | TxCode::InitAccount | TxCode::UpdateAccount => {
+            vec![]
+        }
+    };
+    Ok(counterparties)
+}
+
+/// Decodes the counterparties of the inner txs of a tx. The code of each inner tx is resolved
+/// from the hash of its code section with `code_of_hash`, given the hex encoded hash.
+///
+/// # Errors
+///
+/// Returns JsError if the code of an inner tx is unknown or its data can't be decoded
+pub fn counterparties(
+    tx: &Tx,
+    code_of_hash: impl Fn(&str) -> Option<TxCode>,
+) -> Result<Vec<Counterparty>, JsError> {
+    let mut counterparties = vec![];
+    for cmt in tx.commitments() {
+        let commitment = cmt.get_hash().to_string();
+        let code_hash = tx
+            .get_section(cmt.code_sechash())
+            .and_then(|section| section.code_sec())
+            .map(|code| hex::encode(code.code.hash().0))
+            .ok_or_else(|| policy_error(&format!("inner tx {} has no code", commitment)))?;
+        let code = code_of_hash(&code_hash)
+            .ok_or_else(|| policy_error(&format!("inner tx {} has unknown code", commitment)))?;
+        let data = tx.data(cmt).unwrap_or_default();
+        let decoded = decode_counterparties(code, &data).map_err(|_| {
+            policy_error(&format!("data of inner tx {} can't be decoded", commitment))
+        })?;
+
+        counterparties.extend(decoded.into_iter().map(|(address, role)| Counterparty {
+            address,
+            role,
+            commitment: commitment.clone(),
+        }));
+    }
+    Ok(counterparties)
+}
+
+/// Resolves a code hash through the given code paths and hashes, e.g. (tx_transfer.wasm,
+/// hash) as registered on chain
+pub fn code_of_hash<'a>(
+    code_hashes: impl IntoIterator<Item = (&'a str, &'a str)>,
+    hash: &str,
+) -> Option<TxCode> {
+    code_hashes
+        .into_iter()
+        .filter(|(_, code_hash)| code_hash.eq_ignore_ascii_case(hash))
+        .find_map(|(path, _)| tx_code_of_path(path))
+}
+
+/// Decodes the counterparties of a tx, resolving its codes through the wasm hashes of the
+/// chain, see query::wasm_hashes
+#[cfg(feature = "web")]
+pub fn wasm_counterparties(
+    tx: &Tx,
+    wasm_hashes: &[WasmHash],
+) -> Result<Vec<Counterparty>, JsError> {
+    let code_hashes: Vec<(String, String)> = wasm_hashes
+        .iter()
+        .map(|wasm_hash| (wasm_hash.path(), wasm_hash.hash()))
+        .collect();
+    counterparties(tx, |hash| {
+        code_of_hash(
+            code_hashes
+                .iter()
+                .map(|(path, hash)| (path.as_str(), hash.as_str())),
+            hash,
+        )
+    })
+}
+
+fn policy_error(reason: &str) -> JsError {
+    JsError::new(&format!("Rejected by screening policy: {}", reason))
+}
+
+/// Re-encodes the addresses of the counterparties with the configured prefixes, except for
+/// receivers on other chains
+#[cfg(feature = "web")]
+pub fn with_prefixes(
+    counterparties: Vec<Counterparty>,
+    prefixes: &ChainPrefixes,
+) -> Vec<Counterparty> {
+    counterparties
+        .into_iter()
+        .map(|counterparty| match counterparty.role {
+            CounterpartyRole::Receiver => counterparty,
+            _ => Counterparty {
+                address: prefixes.configured(&counterparty.address),
+                ..counterparty
+            },
+        })
+        .collect()
+}
+
+/// Rejects the counterparties if any of them is blocked. Blocked addresses may have either the
+/// configured or the Namada prefixes.
+pub fn check_blocklist(
+    counterparties: &[Counterparty],
+    blocked: &HashSet<String>,
+    prefixes: &ChainPrefixes,
+) -> Result<(), JsError> {
+    let blocked: HashSet<Cow<'_, str>> = blocked
+        .iter()
+        .map(|address| prefixes.native(address))
+        .collect();
+    match counterparties
+        .iter()
+        .find(|counterparty| blocked.contains(counterparty.address.as_str()))
+    {
+        Some(counterparty) => Err(policy_error(&format!(
+            "{} is blocked",
+            prefixes.configured(&counterparty.address)
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(feature = "web")]
+impl Screening {
+    /// Screens the counterparties of a tx, resolving its codes through the wasm hashes of the
+    /// chain
+    ///
+    /// # Errors
+    ///
+    /// Returns a policy JsError if an inner tx can't be decoded, a counterparty is blocked,
+    /// or the callback rejects them, throws or doesn't return a boolean
+    pub async fn screen(
+        &self,
+        tx: &Tx,
+        wasm_hashes: &[WasmHash],
+        prefixes: &ChainPrefixes,
+    ) -> Result<(), JsError> {
+        let counterparties = wasm_counterparties(tx, wasm_hashes)?;
+
+        match self {
+            Screening::Blocklist(blocked) => check_blocklist(&counterparties, blocked, prefixes),
+            Screening::Callback(callback) => {
+                let counterparties = with_prefixes(counterparties, prefixes);
+                let mut result = callback
+                    .call1(&JsValue::NULL, &to_js_result(counterparties)?)
+                    .map_err(|_| policy_error("screening callback failed"))?;
+                if let Some(promise) = result.dyn_ref::<Promise>() {
+                    result = JsFuture::from(promise.clone())
+                        .await
+                        .map_err(|_| policy_error("screening callback failed"))?;
+                }
+                match result.as_bool() {
+                    Some(true) => Ok(()),
+                    Some(false) => Err(policy_error("counterparties were rejected")),
+                    None => Err(policy_error("screening callback must return a boolean")),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use namada_sdk::address::Address;
+    use namada_sdk::token::{Account, DenominatedAmount};
+    use std::str::FromStr;
+    use wasm_bindgen_test::*;
+
+    const SOURCE: &str = "tnam1qz4sdx5jlh909j44uz46pl6f3d2ftsvkmcml0yuf";
+    const TARGET: &str = "tnam1qpgk927uh2aqjs92yhycsh08n5yggvltn5nk92zp";
+    const TOKEN: &str = "tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e";
+
+    #[wasm_bindgen_test]
+    fn resolves_codes_by_hash() {
+        let code_hashes = [("tx_transfer.wasm", "AB12"), ("tx_bond.wasm", "CD34")];
+        assert_eq!(code_of_hash(code_hashes, "ab12"), Some(TxCode::Transfer));
+        assert_eq!(code_of_hash(code_hashes, "ef56"), None);
+    }
+
+    #[wasm_bindgen_test]
+    fn decodes_transfer_counterparties() {
+        let account = |owner: Address| Account {
+            owner,
+            token: Address::from_str(TOKEN).unwrap(),
+        };
+        let amount = DenominatedAmount::from_str("1").unwrap();
+        let transfer = Transfer {
+            sources: [(account(Address::from_str(SOURCE).unwrap()), amount)].into(),
+            targets: [
+                (account(Address::from_str(TARGET).unwrap()), amount),
+                (account(MASP), amount),
+            ]
+            .into(),
+            shielded_section_hash: None,
+        };
+
+        let counterparties = decode_counterparties(
+            TxCode::Transfer,
+            &namada_sdk::borsh::to_vec(&transfer).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            counterparties,
+            vec![
+                (SOURCE.to_string(), CounterpartyRole::Source),
+                (TARGET.to_string(), CounterpartyRole::Target),
+            ]
+        );
+        assert!(decode_counterparties(TxCode::Transfer, &[1, 2, 3]).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn checks_blocklist_with_configured_prefixes() {
+        let fork = ChainPrefixes {
+            address: "tfork".to_string(),
+            ..ChainPrefixes::default()
+        };
+        let counterparties = vec![Counterparty {
+            address: SOURCE.to_string(),
+            role: CounterpartyRole::Source,
+            commitment: String::new(),
+        }];
+
+        let blocked = HashSet::from([fork.configured(SOURCE)]);
+        assert!(check_blocklist(&counterparties, &blocked, &fork).is_err());
+        let blocked = HashSet::from([SOURCE.to_string()]);
+        assert!(check_blocklist(&counterparties, &blocked, &fork).is_err());
+        let blocked = HashSet::from([TARGET.to_string()]);
+        assert!(check_blocklist(&counterparties, &blocked, &fork).is_ok());
+
+        #[cfg(feature = "web")]
+        {
+            let configured = with_prefixes(counterparties, &fork);
+            assert!(configured[0].address.starts_with("tfork1"));
+        }
+    }
+}
@@ -0,0 +1,495 @@
+//! Adapter between the Msgs and namada_sdk::args
+//!
+//! The conversions of the Msgs in args.rs, and the txs built by the Sdk from other inputs,
+//! parse their fields and construct the namada_sdk args through the functions here. Fields
+//! the Msgs don't carry, e.g. the dry run flags of args::Tx or disposable signing keys, are
+//! set here, and every struct is constructed with each field spelled out and no
+//! `..Default::default()`. A namada_sdk bump changing the shape of an args struct fails to
+//! compile in this module only, where the new field is decided for every caller, while older
+//! Msg layouts keep decoding through msg_migration.
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use namada_sdk::address::Address;
+use namada_sdk::args::{self, InputAmount, TxExpiration};
+use namada_sdk::chain::ChainId;
+use namada_sdk::dec::Dec;
+use namada_sdk::ethereum_events::EthAddress;
+use namada_sdk::ibc::core::host::types::identifiers::{ChannelId, PortId};
+use namada_sdk::ibc::IbcShieldingData;
+use namada_sdk::key::common::PublicKey;
+use namada_sdk::tendermint_rpc;
+use namada_sdk::token::Amount;
+use namada_sdk::tx::data::GasLimit;
+use namada_sdk::tx::VP_USER_WASM;
+#[cfg(feature = "web")]
+use namada_sdk::TransferTarget;
+use namada_sdk::{ExtendedSpendingKey, PaymentAddress, TransferSource};
+
+use super::code_paths::{code_path, TxCode};
+
+fn tx_code_path(code: TxCode, tx: &args::Tx) -> PathBuf {
+    code_path(code, tx.chain_id.as_ref())
+}
+
+/// Parsed fields of a WrapperTxMsg
+pub struct WrapperArgs {
+    pub force: bool,
+    pub fee_amount: InputAmount,
+    pub fee_token: Address,
+    pub gas_limit: GasLimit,
+    pub fee_payer: Option<PublicKey>,
+    pub expiration: TxExpiration,
+    pub chain_id: ChainId,
+    pub signing_keys: Vec<PublicKey>,
+    pub memo: Option<Vec<u8>>,
+}
+
+pub fn tx(wrapper: WrapperArgs) -> args::Tx {
+    let WrapperArgs {
+        force,
+        fee_amount,
+        fee_token,
+        gas_limit,
+        fee_payer,
+        expiration,
+        chain_id,
+        signing_keys,
+        memo,
+    } = wrapper;
+
+    // Ledger address is not used in the SDK.
+    // We can leave it as whatever as long as it's valid url.
+    let ledger_address = tendermint_rpc::Url::from_str("http://notinuse:13337").unwrap();
+    let tx_reveal_code_path = code_path(TxCode::RevealPk, Some(&chain_id));
+
+    args::Tx {
+        dry_run: false,
+        dry_run_wrapper: false,
+        dump_tx: false,
+        dump_wrapper_tx: false,
+        force,
+        broadcast_only: false,
+        ledger_address,
+        wallet_alias_force: false,
+        initialized_account_alias: None,
+        fee_amount: Some(fee_amount),
+        fee_token,
+        gas_limit,
+        wrapper_fee_payer: fee_payer,
+        output_folder: None,
+        expiration,
+        chain_id: Some(chain_id),
+        signatures: vec![],
+        wrapper_signature: None,
+        signing_keys,
+        tx_reveal_code_path,
+        use_device: false,
+        password: None,
+        memo,
+        device_transport: Default::default(),
+    }
+}
+
+pub fn bond(tx: args::Tx, source: Address, validator: Address, amount: Amount) -> args::Bond {
+    let tx_code_path = tx_code_path(TxCode::Bond, &tx);
+    args::Bond {
+        tx,
+        validator,
+        amount,
+        source: Some(source),
+        tx_code_path,
+    }
+}
+
+pub fn unbond(tx: args::Tx, source: Address, validator: Address, amount: Amount) -> args::Unbond {
+    let tx_code_path = tx_code_path(TxCode::Unbond, &tx);
+    args::Unbond {
+        tx,
+        validator,
+        amount,
+        source: Some(source),
+        tx_code_path,
+    }
+}
+
+pub fn withdraw(tx: args::Tx, source: Address, validator: Address) -> args::Withdraw {
+    let tx_code_path = tx_code_path(TxCode::Withdraw, &tx);
+    args::Withdraw {
+        tx,
+        validator,
+        source: Some(source),
+        tx_code_path,
+    }
+}
+
+pub fn redelegate(
+    tx: args::Tx,
+    owner: Address,
+    src_validator: Address,
+    dest_validator: Address,
+    amount: Amount,
+) -> args::Redelegate {
+    let tx_code_path = tx_code_path(TxCode::Redelegate, &tx);
+    args::Redelegate {
+        tx,
+        src_validator,
+        dest_validator,
+        amount,
+        owner,
+        tx_code_path,
+    }
+}
+
+pub fn vote_proposal(
+    tx: args::Tx,
+    voter_address: Address,
+    proposal_id: u64,
+    vote: String,
+) -> args::VoteProposal {
+    let tx_code_path = tx_code_path(TxCode::VoteProposal, &tx);
+    args::VoteProposal {
+        tx,
+        proposal_id,
+        vote,
+        voter_address,
+        tx_code_path,
+    }
+}
+
+pub fn claim_rewards(
+    tx: args::Tx,
+    validator: Address,
+    source: Option<Address>,
+) -> args::ClaimRewards {
+    let tx_code_path = tx_code_path(TxCode::ClaimRewards, &tx);
+    args::ClaimRewards {
+        tx,
+        validator,
+        source,
+        tx_code_path,
+    }
+}
+
+/// Args of a new account with the user validity predicate
+pub fn init_account(
+    tx: args::Tx,
+    public_keys: Vec<PublicKey>,
+    threshold: u8,
+) -> args::TxInitAccount {
+    let tx_code_path = tx_code_path(TxCode::InitAccount, &tx);
+    args::TxInitAccount {
+        tx,
+        vp_code_path: PathBuf::from(VP_USER_WASM),
+        tx_code_path,
+        public_keys,
+        threshold: Some(threshold),
+    }
+}
+
+/// Args of an account update keeping the validity predicate of the account
+pub fn update_account(
+    tx: args::Tx,
+    addr: Address,
+    public_keys: Vec<PublicKey>,
+    threshold: Option<u8>,
+) -> args::TxUpdateAccount {
+    let tx_code_path = tx_code_path(TxCode::UpdateAccount, &tx);
+    args::TxUpdateAccount {
+        tx,
+        vp_code_path: None,
+        tx_code_path,
+        addr,
+        public_keys,
+        threshold,
+    }
+}
+
+pub fn commission_rate_change(
+    tx: args::Tx,
+    validator: Address,
+    rate: Dec,
+) -> args::CommissionRateChange {
+    let tx_code_path = tx_code_path(TxCode::ChangeCommission, &tx);
+    args::CommissionRateChange {
+        tx,
+        validator,
+        rate,
+        tx_code_path,
+    }
+}
+
+/// Metadata fields of a validator, None for fields left unchanged
+pub struct ValidatorMetadata {
+    pub email: Option<String>,
+    pub description: Option<String>,
+    pub website: Option<String>,
+    pub discord_handle: Option<String>,
+    pub avatar: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Args of a metadata change, the commission rate is changed by its own tx
+pub fn metadata_change(
+    tx: args::Tx,
+    validator: Address,
+    metadata: ValidatorMetadata,
+) -> args::MetaDataChange {
+    let ValidatorMetadata {
+        email,
+        description,
+        website,
+        discord_handle,
+        avatar,
+        name,
+    } = metadata;
+    let tx_code_path = tx_code_path(TxCode::ChangeMetadata, &tx);
+    args::MetaDataChange {
+        tx,
+        validator,
+        email,
+        description,
+        website,
+        discord_handle,
+        avatar,
+        name,
+        commission_rate: None,
+        tx_code_path,
+    }
+}
+
+pub fn consensus_key_change(
+    tx: args::Tx,
+    validator: Address,
+    consensus_key: PublicKey,
+) -> args::ConsensusKeyChange {
+    let tx_code_path = tx_code_path(TxCode::ChangeConsensusKey, &tx);
+    args::ConsensusKeyChange {
+        tx,
+        validator,
+        consensus_key: Some(consensus_key),
+        unsafe_dont_encrypt: false,
+        tx_code_path,
+    }
+}
+
+pub fn transparent_transfer_data(
+    source: Address,
+    target: Address,
+    token: Address,
+    amount: InputAmount,
+) -> args::TxTransparentTransferData {
+    args::TxTransparentTransferData {
+        source,
+        target,
+        token,
+        amount,
+    }
+}
+
+pub fn transparent_transfer(
+    tx: args::Tx,
+    data: Vec<args::TxTransparentTransferData>,
+) -> args::TxTransparentTransfer {
+    let tx_code_path = tx_code_path(TxCode::Transfer, &tx);
+    args::TxTransparentTransfer {
+        tx,
+        data,
+        tx_code_path,
+    }
+}
+
+pub fn shielded_transfer_data(
+    source: ExtendedSpendingKey,
+    target: PaymentAddress,
+    token: Address,
+    amount: InputAmount,
+) -> args::TxShieldedTransferData {
+    args::TxShieldedTransferData {
+        source,
+        target,
+        token,
+        amount,
+    }
+}
+
+pub fn shielded_transfer(
+    tx: args::Tx,
+    data: Vec<args::TxShieldedTransferData>,
+    gas_spending_key: Option<ExtendedSpendingKey>,
+) -> args::TxShieldedTransfer {
+    let tx_code_path = tx_code_path(TxCode::Transfer, &tx);
+    args::TxShieldedTransfer {
+        data,
+        tx,
+        tx_code_path,
+        // TODO: false for now
+        disposable_signing_key: false,
+        gas_spending_key,
+    }
+}
+
+pub fn shielding_transfer_data(
+    source: Address,
+    token: Address,
+    amount: InputAmount,
+) -> args::TxShieldingTransferData {
+    args::TxShieldingTransferData {
+        source,
+        token,
+        amount,
+    }
+}
+
+pub fn shielding_transfer(
+    tx: args::Tx,
+    target: PaymentAddress,
+    data: Vec<args::TxShieldingTransferData>,
+) -> args::TxShieldingTransfer {
+    let tx_code_path = tx_code_path(TxCode::Transfer, &tx);
+    args::TxShieldingTransfer {
+        data,
+        target,
+        tx,
+        tx_code_path,
+    }
+}
+
+pub fn unshielding_transfer_data(
+    target: Address,
+    token: Address,
+    amount: InputAmount,
+) -> args::TxUnshieldingTransferData {
+    args::TxUnshieldingTransferData {
+        target,
+        token,
+        amount,
+    }
+}
+
+pub fn unshielding_transfer(
+    tx: args::Tx,
+    source: ExtendedSpendingKey,
+    data: Vec<args::TxUnshieldingTransferData>,
+    gas_spending_key: Option<ExtendedSpendingKey>,
+) -> args::TxUnshieldingTransfer {
+    let tx_code_path = tx_code_path(TxCode::Transfer, &tx);
+    args::TxUnshieldingTransfer {
+        data,
+        source,
+        tx,
+        gas_spending_key,
+        // TODO: false for now
+        disposable_signing_key: false,
+        tx_code_path,
+    }
+}
+
+/// Parsed fields of an IbcTransferMsg
+pub struct IbcTransfer {
+    pub source: TransferSource,
+    pub receiver: String,
+    pub token: Address,
+    pub amount: InputAmount,
+    pub port_id: PortId,
+    pub channel_id: ChannelId,
+    pub timeout_height: Option<u64>,
+    pub timeout_sec_offset: Option<u64>,
+    pub memo: Option<String>,
+    pub shielding_data: Option<IbcShieldingData>,
+}
+
+pub fn ibc_transfer(tx: args::Tx, transfer: IbcTransfer) -> args::TxIbcTransfer {
+    let IbcTransfer {
+        source,
+        receiver,
+        token,
+        amount,
+        port_id,
+        channel_id,
+        timeout_height,
+        timeout_sec_offset,
+        memo,
+        shielding_data,
+    } = transfer;
+    let tx_code_path = tx_code_path(TxCode::Ibc, &tx);
+    args::TxIbcTransfer {
+        tx,
+        ibc_memo: memo,
+        ibc_shielding_data: shielding_data,
+        source,
+        receiver,
+        token,
+        amount,
+        port_id,
+        channel_id,
+        timeout_height,
+        timeout_sec_offset,
+        // TODO: false for now
+        disposable_signing_key: false,
+        tx_code_path,
+        refund_target: None,
+        // We do not support ibc unshielding for now
+        gas_spending_key: None,
+    }
+}
+
+/// Parsed fields of an EthBridgeTransferMsg
+pub struct EthBridgeTransfer {
+    pub nut: bool,
+    pub asset: EthAddress,
+    pub recipient: EthAddress,
+    pub sender: Address,
+    pub amount: InputAmount,
+    pub fee_amount: InputAmount,
+    pub fee_payer: Option<Address>,
+    pub fee_token: Address,
+}
+
+pub fn eth_bridge_pool(tx: args::Tx, transfer: EthBridgeTransfer) -> args::EthereumBridgePool {
+    let EthBridgeTransfer {
+        nut,
+        asset,
+        recipient,
+        sender,
+        amount,
+        fee_amount,
+        fee_payer,
+        fee_token,
+    } = transfer;
+    let code_path = tx_code_path(TxCode::BridgePool, &tx);
+    args::EthereumBridgePool {
+        nut,
+        tx,
+        asset,
+        recipient,
+        sender,
+        amount,
+        fee_amount,
+        fee_payer,
+        fee_token,
+        code_path,
+    }
+}
+
+/// Args generating the MASP transaction of a shielding IBC transfer, see
+/// gen_ibc_shielding_transfer
+#[cfg(feature = "web")]
+pub fn gen_ibc_shielding_transfer(
+    ledger_address: tendermint_rpc::Url,
+    target: TransferTarget,
+    token: String,
+    amount: InputAmount,
+    channel_id: ChannelId,
+) -> args::GenIbcShieldingTransfer {
+    args::GenIbcShieldingTransfer {
+        query: args::Query { ledger_address },
+        output_folder: None,
+        target,
+        token,
+        amount,
+        port_id: PortId::transfer(),
+        channel_id,
+        expiration: TxExpiration::Default,
+    }
+}
@@ -0,0 +1,291 @@
+//! Manipulation of the sections of built txs
+//!
+//! Sections are committed to by hash, from the header for the code, data and memo of each
+//! inner tx, and from the signatures for everything they sign. Changing a section without
+//! updating these references produces txs that are rejected by the chain, often only after
+//! the fees are paid, so sections are only changed through these helpers:
+//!
+//! - Commitments of the inner txs follow replaced sections, and referenced sections can't
+//!   be removed.
+//! - Signatures are always the last sections, and signatures covering a changed section or
+//!   header are dropped, to be signed again.
+//! - Signatures can only target the header or existing sections.
+use namada_sdk::hash::Hash;
+use namada_sdk::tx::{Section, Tx};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SectionError {
+    #[error("Tx has no section with hash {0}")]
+    NotFound(Hash),
+    #[error("Tx already has a section with hash {0}")]
+    Duplicate(Hash),
+    #[error("Section {0} is committed to by an inner tx, replace it instead")]
+    Referenced(Hash),
+    #[error("Can't replace a {0} section with a {1} section")]
+    KindMismatch(&'static str, &'static str),
+    #[error("Header sections can't be changed")]
+    Header,
+    #[error("Signatures are added with add_authorization")]
+    Authorization,
+    #[error("Signature targets unknown hash {0}")]
+    UnknownTarget(Hash),
+}
+
+/// Name of the kind of a section
+pub fn section_kind(section: &Section) -> &'static str {
+    match section {
+        Section::Data(_) => "data",
+        Section::ExtraData(_) => "extra_data",
+        Section::Code(_) => "code",
+        Section::Authorization(_) => "authorization",
+        Section::MaspTx(_) => "masp_tx",
+        Section::MaspBuilder(_) => "masp_builder",
+        Section::Header(_) => "header",
+    }
+}
+
+/// Returns the kind and hash of every section, in order
+pub fn list_sections(tx: &Tx) -> Vec<(&'static str, Hash)> {
+    tx.sections
+        .iter()
+        .map(|section| (section_kind(section), section.get_hash()))
+        .collect()
+}
+
+/// Hashes of the sections committed to by the inner txs
+fn committed_hashes(tx: &Tx) -> Vec<Hash> {
+    tx.header
+        .batch
+        .iter()
+        .flat_map(|cmt| [cmt.code_hash, cmt.data_hash, cmt.memo_hash])
+        .collect()
+}
+
+fn check_changeable(section: &Section) -> Result<(), SectionError> {
+    match section {
+        Section::Header(_) => Err(SectionError::Header),
+        Section::Authorization(_) => Err(SectionError::Authorization),
+        _ => Ok(()),
+    }
+}
+
+/// Drops the signatures covering any of the hashes
+pub fn invalidate_signatures(tx: &mut Tx, hashes: &[Hash]) {
+    tx.sections.retain(|section| match section {
+        Section::Authorization(authorization) => !authorization
+            .targets
+            .iter()
+            .any(|target| hashes.contains(target)),
+        _ => true,
+    });
+}
+
+/// Adds a section before the signatures. The wrapper signature, which covers all sections,
+/// is dropped.
+///
+/// # Errors
+///
+/// Returns SectionError if the section is a header or signature, or already in the tx
+pub fn add_section(tx: &mut Tx, section: Section) -> Result<Hash, SectionError> {
+    check_changeable(&section)?;
+    let hash = section.get_hash();
+    if tx.get_section(&hash).is_some() {
+        return Err(SectionError::Duplicate(hash));
+    }
+
+    let header_hash = tx.header_hash();
+    let index = tx
+        .sections
+        .iter()
+        .position(|section| matches!(section, Section::Authorization(_)))
+        .unwrap_or(tx.sections.len());
+    tx.sections.insert(index, section);
+    invalidate_signatures(tx, &[header_hash]);
+
+    Ok(hash)
+}
+
+/// Removes a section, along with the signatures covering it and the wrapper signature.
+/// Removing a signature leaves the other sections untouched.
+///
+/// # Errors
+///
+/// Returns SectionError if there's no such section, it's the header or it's committed to
+/// by an inner tx
+pub fn remove_section(tx: &mut Tx, hash: Hash) -> Result<Section, SectionError> {
+    let index = tx
+        .sections
+        .iter()
+        .position(|section| section.get_hash() == hash)
+        .ok_or(SectionError::NotFound(hash))?;
+    match &tx.sections[index] {
+        Section::Header(_) => return Err(SectionError::Header),
+        Section::Authorization(_) => return Ok(tx.sections.remove(index)),
+        _ => {}
+    }
+    if committed_hashes(tx).contains(&hash) {
+        return Err(SectionError::Referenced(hash));
+    }
+
+    let header_hash = tx.header_hash();
+    let section = tx.sections.remove(index);
+    invalidate_signatures(tx, &[hash, header_hash]);
+
+    Ok(section)
+}
+
+/// Replaces a section in place by one of the same kind, updating the commitments of the
+/// inner txs to it. Signatures covering the old section or the old header are dropped.
+///
+/// The MASP section of a transfer is referenced from its data, so replacing it goes along
+/// with replacing the data.
+///
+/// # Errors
+///
+/// Returns SectionError if there's no such section, or either section is a header or
+/// signature, or their kinds differ
+pub fn replace_section(tx: &mut Tx, hash: Hash, section: Section) -> Result<Hash, SectionError> {
+    check_changeable(&section)?;
+    let index = tx
+        .sections
+        .iter()
+        .position(|section| section.get_hash() == hash)
+        .ok_or(SectionError::NotFound(hash))?;
+    check_changeable(&tx.sections[index])?;
+    let (old_kind, new_kind) = (section_kind(&tx.sections[index]), section_kind(&section));
+    if old_kind != new_kind {
+        return Err(SectionError::KindMismatch(old_kind, new_kind));
+    }
+    let new_hash = section.get_hash();
+    if new_hash != hash && tx.get_section(&new_hash).is_some() {
+        return Err(SectionError::Duplicate(new_hash));
+    }
+
+    let old_headers = [tx.header_hash(), tx.raw_header_hash()];
+    tx.sections[index] = section;
+    let replace = |sechash: Hash| if sechash == hash { new_hash } else { sechash };
+    tx.header.batch = tx
+        .header
+        .batch
+        .iter()
+        .cloned()
+        .map(|mut cmt| {
+            cmt.code_hash = replace(cmt.code_hash);
+            cmt.data_hash = replace(cmt.data_hash);
+            cmt.memo_hash = replace(cmt.memo_hash);
+            cmt
+        })
+        .collect();
+    invalidate_signatures(tx, &[hash, old_headers[0], old_headers[1]]);
+
+    Ok(new_hash)
+}
+
+/// Appends a signature section
+///
+/// # Errors
+///
+/// Returns SectionError if the section is not a signature or signs anything but the
+/// headers and sections of the tx
+pub fn add_authorization(tx: &mut Tx, section: Section) -> Result<(), SectionError> {
+    let authorization = match &section {
+        Section::Authorization(authorization) => authorization,
+        _ => return Err(SectionError::Authorization),
+    };
+    let known = [tx.header_hash(), tx.raw_header_hash()];
+    if let Some(target) = authorization
+        .targets
+        .iter()
+        .find(|target| !known.contains(target) && tx.get_section(target).is_none())
+    {
+        return Err(SectionError::UnknownTarget(*target));
+    }
+
+    tx.sections.push(section);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use namada_sdk::tx::data::TxType;
+    use namada_sdk::tx::{Authorization, Data, Signer};
+
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn transfer_tx() -> Tx {
+        let mut tx = Tx::from_type(TxType::Raw);
+        tx.add_code(b"code".to_vec(), None);
+        tx.add_serialized_data(b"data".to_vec());
+        tx
+    }
+
+    fn signature(targets: Vec<Hash>) -> Section {
+        Section::Authorization(Authorization {
+            targets,
+            signer: Signer::PubKeys(vec![]),
+            signatures: BTreeMap::new(),
+        })
+    }
+
+    #[wasm_bindgen_test]
+    fn keeps_signatures_last() {
+        let mut tx = transfer_tx();
+        let data_hash = tx.first_commitments().unwrap().data_hash;
+        add_authorization(&mut tx, signature(vec![tx.raw_header_hash(), data_hash])).unwrap();
+
+        let extra = Section::ExtraData(namada_sdk::tx::Code::new(b"extra".to_vec(), None));
+        let extra_hash = add_section(&mut tx, extra.clone()).unwrap();
+        let kinds: Vec<&str> = list_sections(&tx).iter().map(|(kind, _)| *kind).collect();
+        assert_eq!(kinds, vec!["code", "data", "extra_data", "authorization"]);
+
+        assert_eq!(
+            add_section(&mut tx, extra),
+            Err(SectionError::Duplicate(extra_hash))
+        );
+        assert_eq!(
+            add_authorization(&mut tx, signature(vec![Hash::sha256(b"unknown")])),
+            Err(SectionError::UnknownTarget(Hash::sha256(b"unknown")))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn follows_replaced_sections() {
+        let mut tx = transfer_tx();
+        let data_hash = tx.first_commitments().unwrap().data_hash;
+        add_authorization(&mut tx, signature(vec![tx.raw_header_hash(), data_hash])).unwrap();
+
+        assert_eq!(
+            remove_section(&mut tx, data_hash),
+            Err(SectionError::Referenced(data_hash))
+        );
+        let new_hash = replace_section(
+            &mut tx,
+            data_hash,
+            Section::Data(Data::new(b"new".to_vec())),
+        )
+        .unwrap();
+
+        let cmt = tx.first_commitments().unwrap().clone();
+        assert_eq!(cmt.data_hash, new_hash);
+        assert_eq!(tx.data(&cmt), Some(b"new".to_vec()));
+        assert!(tx.get_section(&data_hash).is_none());
+        // The signature covered the replaced data
+        assert!(list_sections(&tx)
+            .iter()
+            .all(|(kind, _)| *kind != "authorization"));
+
+        let code_hash = cmt.code_hash;
+        assert_eq!(
+            replace_section(
+                &mut tx,
+                code_hash,
+                Section::Data(Data::new(b"code".to_vec()))
+            ),
+            Err(SectionError::KindMismatch("code", "data"))
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Shielded keys with their capability
+//!
+//! Extended spending and viewing keys are both passed around as strings, and a viewing key
+//! given where a spending key is expected failed as an unparsable spending key, without a hint
+//! that a spending key is required. ShieldedKey carries whether a key can spend, so builders
+//! reject view-only keys as such before doing any work.
+use std::fmt;
+use std::str::FromStr;
+
+use namada_sdk::masp_primitives::zip32::{self, ExtendedFullViewingKey};
+use namada_sdk::{ExtendedSpendingKey, ExtendedViewingKey};
+use thiserror::Error;
+
+/// What a shielded key allows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// Scanning notes and computing balances
+    View,
+    /// Viewing, and spending notes
+    Spend,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capability::View => f.write_str("view"),
+            Capability::Spend => f.write_str("spend"),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ShieldedKeyError {
+    #[error("Not an extended spending key or viewing key")]
+    Malformed,
+    #[error("Viewing keys can't spend, a spending key is required")]
+    ViewOnly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Key {
+    Spending(ExtendedSpendingKey),
+    Viewing(ExtendedViewingKey),
+}
+
+/// Extended spending key or viewing key
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShieldedKey(Key);
+
+impl ShieldedKey {
+    pub fn capability(&self) -> Capability {
+        match self.0 {
+            Key::Spending(_) => Capability::Spend,
+            Key::Viewing(_) => Capability::View,
+        }
+    }
+
+    /// Returns the viewing key, derived from the spending key for spend capable keys
+    pub fn viewing_key(&self) -> ExtendedViewingKey {
+        match &self.0 {
+            Key::Spending(xsk) => {
+                ExtendedFullViewingKey::from(&zip32::ExtendedSpendingKey::from(xsk.clone())).into()
+            }
+            Key::Viewing(xvk) => *xvk,
+        }
+    }
+
+    /// Returns the spending key
+    ///
+    /// # Errors
+    ///
+    /// Returns ShieldedKeyError::ViewOnly for viewing keys
+    pub fn spending_key(&self) -> Result<ExtendedSpendingKey, ShieldedKeyError> {
+        match &self.0 {
+            Key::Spending(xsk) => Ok(xsk.clone()),
+            Key::Viewing(_) => Err(ShieldedKeyError::ViewOnly),
+        }
+    }
+}
+
+impl From<ExtendedSpendingKey> for ShieldedKey {
+    fn from(xsk: ExtendedSpendingKey) -> ShieldedKey {
+        ShieldedKey(Key::Spending(xsk))
+    }
+}
+
+impl From<ExtendedViewingKey> for ShieldedKey {
+    fn from(xvk: ExtendedViewingKey) -> ShieldedKey {
+        ShieldedKey(Key::Viewing(xvk))
+    }
+}
+
+impl FromStr for ShieldedKey {
+    type Err = ShieldedKeyError;
+
+    /// Parses a spending key or viewing key, with either the current or the legacy prefix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(xsk) = ExtendedSpendingKey::from_str(s) {
+            return Ok(xsk.into());
+        }
+        ExtendedViewingKey::from_str(s)
+            .map(ShieldedKey::from)
+            .map_err(|_| ShieldedKeyError::Malformed)
+    }
+}
+
+/// Parses a key which has to be spend capable
+///
+/// # Errors
+///
+/// Returns ShieldedKeyError if the key is malformed or a viewing key
+pub fn spending_key(key: &str) -> Result<ExtendedSpendingKey, ShieldedKeyError> {
+    ShieldedKey::from_str(key)?.spending_key()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const SPENDING_KEY: &str = concat!(
+        "zsknam1qwhq8dchqqqqpq9kgajamqucsjt0gy8h9hdel8ga3ugynqs8lewj4ayqdnlk2reuf50h324g5rky8kq",
+        "my9u8v3306q03fa2ad32hdq8uacwrtzmn67gsz22vfw06r86vxyr9kk8ajr2n3ds5hsx0ks8fuuespr9y2v4ah9",
+        "grz7snc33jhqgscvn7vwzpxplq5jwsmw5v6sew55er3y0s2mjw7q388w9xg8hdjfkd0n0hqdm32dl9mjvxkes5",
+        "djmvq60u3dgka4jsyfgpxafgg"
+    );
+
+    #[wasm_bindgen_test]
+    fn rejects_spending_with_viewing_keys() {
+        let key = ShieldedKey::from_str(SPENDING_KEY).unwrap();
+        assert_eq!(key.capability(), Capability::Spend);
+        assert!(spending_key(SPENDING_KEY).is_ok());
+
+        let viewing_key = key.viewing_key().to_string();
+        let view_only = ShieldedKey::from_str(&viewing_key).unwrap();
+        assert_eq!(view_only.capability(), Capability::View);
+        assert_eq!(view_only.viewing_key(), key.viewing_key());
+        assert_eq!(spending_key(&viewing_key), Err(ShieldedKeyError::ViewOnly));
+
+        assert_eq!(
+            ShieldedKey::from_str("zsknam1invalid"),
+            Err(ShieldedKeyError::Malformed)
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use namada_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use namada_sdk::borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use namada_sdk::{
     key::common::{PublicKey, Signature},
     tx::{CompressedAuthorization, Section, Signer, Tx},
@@ -6,7 +6,9 @@ use namada_sdk::{
 use std::collections::BTreeMap;
 use wasm_bindgen::JsError;
 
-#[derive(BorshSerialize, BorshDeserialize)]
+use super::sections;
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct SignatureMsg {
     pub pubkey: Vec<u8>,
@@ -46,3 +48,28 @@ pub fn construct_signature_section(
 
     Ok(Section::Authorization(compressed_signature.expand(&tx)))
 }
+
+/// Adds the raw header and wrapper signatures of a SignatureMsg to a tx
+///
+/// # Errors
+///
+/// Returns JsError if the public key or signatures can't be decoded
+pub fn append_signature(tx: &mut Tx, sig_msg: SignatureMsg) -> Result<(), JsError> {
+    let SignatureMsg {
+        pubkey,
+        raw_indices,
+        raw_signature,
+        wrapper_indices,
+        wrapper_signature,
+    } = sig_msg;
+
+    let raw_sig_section = construct_signature_section(&pubkey, &raw_indices, &raw_signature, tx)?;
+    sections::add_authorization(tx, raw_sig_section)?;
+
+    let wrapper_sig_section =
+        construct_signature_section(&pubkey, &wrapper_indices, &wrapper_signature, tx)?;
+    sections::add_authorization(tx, wrapper_sig_section)?;
+
+    tx.protocol_filter();
+    Ok(())
+}
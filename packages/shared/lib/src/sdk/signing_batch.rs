@@ -0,0 +1,168 @@
+//! Batched signing of many independent txs with a hardware wallet
+//!
+//! Validators doing bulk operations sign dozens of small txs in a row. plan_batch_signing
+//! orders them so that txs of the same key are approved one after the other, without
+//! switching accounts on the device, and apply_batch_signatures matches the returned
+//! signatures back to their txs by wrapper hash, so the order of approval doesn't matter.
+use std::collections::HashMap;
+
+use namada_sdk::borsh::{self, BorshDeserialize};
+use namada_sdk::tx::Tx;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use super::signature::{self, SignatureMsg};
+use super::tx;
+use crate::utils::{to_bytes, to_js_result};
+
+/// Tx to be approved on the device
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningRequest {
+    /// Index of the tx in the batch
+    pub index: usize,
+    /// Wrapper hash, identifying the tx when its signature is applied
+    pub hash: String,
+    /// Public key signing the tx
+    pub signer: String,
+    /// Borsh serialized Namada tx to pass to the device
+    pub tx_bytes: Vec<u8>,
+}
+
+fn decode_batch(txs: &[JsValue]) -> Result<Vec<(tx::Tx, Tx)>, JsError> {
+    txs.iter()
+        .map(|bytes| {
+            let built_tx: tx::Tx = borsh::from_slice(&to_bytes(bytes.clone()))?;
+            let namada_tx = Tx::try_from_slice(built_tx.tx_bytes())?;
+            Ok((built_tx, namada_tx))
+        })
+        .collect()
+}
+
+fn wrapper_hash(tx: &Tx) -> Result<String, JsError> {
+    tx.wrapper_hash()
+        .map(|hash| hash.to_string())
+        .ok_or_else(|| JsError::new("Only wrapper txs can be signed in a batch"))
+}
+
+/// Orders signing requests by signer, in the order each signer first appears, keeping the
+/// order of the batch for txs of the same signer
+pub fn order_requests(mut requests: Vec<SigningRequest>) -> Vec<SigningRequest> {
+    let mut first_seen: HashMap<String, usize> = HashMap::new();
+    for request in &requests {
+        let next = first_seen.len();
+        first_seen.entry(request.signer.clone()).or_insert(next);
+    }
+    // Stable sort, ties keep the batch order
+    requests.sort_by_key(|request| first_seen[&request.signer]);
+    requests
+}
+
+/// Returns the signing requests of a batch of built txs, in the order to approve them.
+///
+/// # Arguments
+///
+/// * `txs` - Borsh serialized tx::Tx, as returned by the build functions
+///
+/// # Errors
+///
+/// Returns JsError if a tx can't be decoded, or the batch contains the same tx twice
+#[wasm_bindgen]
+pub fn plan_batch_signing(txs: Box<[JsValue]>) -> Result<JsValue, JsError> {
+    let mut requests = vec![];
+    for (index, (built_tx, namada_tx)) in decode_batch(&txs)?.into_iter().enumerate() {
+        let hash = wrapper_hash(&namada_tx)?;
+        if requests
+            .iter()
+            .any(|request: &SigningRequest| request.hash == hash)
+        {
+            return Err(JsError::new(&format!("Duplicate tx in batch: {}", hash)));
+        }
+        requests.push(SigningRequest {
+            index,
+            hash,
+            signer: built_tx.required_signatures().fee_payer,
+            tx_bytes: built_tx.tx_bytes().to_vec(),
+        });
+    }
+
+    to_js_result(order_requests(requests))
+}
+
+/// Applies the signatures returned by the device to a batch of built txs. Returns the signed
+/// Namada txs in the order of the batch, screened by Sdk::apply_batch_signatures.
+///
+/// # Arguments
+///
+/// * `txs` - Borsh serialized tx::Tx, as passed to plan_batch_signing
+/// * `hashes` - Wrapper hashes of the signed txs
+/// * `signatures` - Borsh serialized SignatureMsg of each hash
+///
+/// # Errors
+///
+/// Returns JsError if a signature belongs to no tx of the batch, or a tx was not signed
+pub(crate) fn apply_batch_signatures(
+    txs: &[JsValue],
+    hashes: Vec<String>,
+    signatures: &[JsValue],
+) -> Result<Vec<Tx>, JsError> {
+    if hashes.len() != signatures.len() {
+        return Err(JsError::new(&format!(
+            "Got {} hashes for {} signatures",
+            hashes.len(),
+            signatures.len()
+        )));
+    }
+    let mut signatures: HashMap<String, SignatureMsg> = hashes
+        .into_iter()
+        .zip(signatures.iter())
+        .map(|(hash, sig_msg)| {
+            let sig_msg = SignatureMsg::try_from_slice(&to_bytes(sig_msg.clone()))?;
+            Ok((hash, sig_msg))
+        })
+        .collect::<Result<_, JsError>>()?;
+
+    let mut signed = vec![];
+    for (_, mut namada_tx) in decode_batch(txs)? {
+        let hash = wrapper_hash(&namada_tx)?;
+        let sig_msg = signatures
+            .remove(&hash)
+            .ok_or_else(|| JsError::new(&format!("Missing signature of tx {}", hash)))?;
+        signature::append_signature(&mut namada_tx, sig_msg)?;
+        signed.push(namada_tx);
+    }
+
+    if let Some(hash) = signatures.keys().next() {
+        return Err(JsError::new(&format!(
+            "Signature of tx {} not in the batch",
+            hash
+        )));
+    }
+    Ok(signed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn groups_requests_by_signer() {
+        let request = |index: usize, signer: &str| SigningRequest {
+            index,
+            hash: index.to_string(),
+            signer: signer.to_string(),
+            tx_bytes: vec![],
+        };
+        let ordered = order_requests(vec![
+            request(0, "a"),
+            request(1, "b"),
+            request(2, "a"),
+            request(3, "c"),
+            request(4, "b"),
+        ]);
+
+        let indices: Vec<usize> = ordered.iter().map(|request| request.index).collect();
+        assert_eq!(indices, vec![0, 2, 1, 4, 3]);
+    }
+}
@@ -0,0 +1,221 @@
+//! Emergency sweeps of compromised spending keys
+//!
+//! Once a spending key leaks, its notes belong to whoever spends them first. A sweep moves
+//! the whole shielded balance of the key to a safe target, paying the fees from the same
+//! notes, so that nothing has to be shielded to the compromised key first.
+//!
+//! Every token adds spend and convert descriptions to the MASP transaction, so a sweep
+//! moves at most MAX_SWEEP_TOKENS tokens per tx. Notes spent by a tx are only known to be
+//! spent once it's applied, so further txs are built after syncing again, each call
+//! sweeping the next tokens. The fee token is swept last, as it pays the fees of every tx.
+//!
+//! Transparent sweeps drain the balances of a transparent address, e.g. to migrate an
+//! account, in a single transfer tx, leaving the fee when the address pays it.
+use namada_sdk::address::Address;
+use namada_sdk::token::Amount;
+use thiserror::Error;
+
+/// Maximum number of tokens swept by a single tx
+pub const MAX_SWEEP_TOKENS: usize = 8;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SweepError {
+    #[error("Nothing to sweep, the shielded balance is empty")]
+    NothingToSweep,
+    #[error("Shielded balance of {token} does not cover the fee of {fee}")]
+    InsufficientFee { token: Address, fee: Amount },
+}
+
+/// Tokens swept by the next tx
+#[derive(Debug, PartialEq)]
+pub struct SweepPlan {
+    /// Tokens and raw amounts, the fee token last
+    pub swept: Vec<(Address, Amount)>,
+    /// Number of tokens left for the following txs
+    pub remaining: usize,
+}
+
+/// Plans the next tx of a sweep
+///
+/// # Arguments
+///
+/// * `balances` - Raw shielded balances of the swept key per token
+/// * `fee` - Fee token and raw fee amount, unshielded from the swept key
+///
+/// # Errors
+///
+/// Returns SweepError if the balance is empty or doesn't cover the fee
+pub fn plan_sweep(
+    balances: &[(Address, Amount)],
+    fee: (Address, Amount),
+) -> Result<SweepPlan, SweepError> {
+    let (fee_token, fee_amount) = fee;
+    let fee_balance = balances
+        .iter()
+        .find(|(token, _)| *token == fee_token)
+        .map(|(_, amount)| *amount)
+        .unwrap_or_default();
+    let mut tokens: Vec<(Address, Amount)> = balances
+        .iter()
+        .filter(|(token, amount)| *token != fee_token && !amount.is_zero())
+        .cloned()
+        .collect();
+
+    if tokens.is_empty() && fee_balance.is_zero() {
+        return Err(SweepError::NothingToSweep);
+    }
+    let fee_left =
+        fee_balance
+            .checked_sub(fee_amount)
+            .ok_or_else(|| SweepError::InsufficientFee {
+                token: fee_token.clone(),
+                fee: fee_amount,
+            })?;
+    if !fee_left.is_zero() {
+        tokens.push((fee_token, fee_left));
+    }
+    if tokens.is_empty() {
+        return Err(SweepError::NothingToSweep);
+    }
+
+    // The fee token is last, so it's only swept by the last tx
+    let remaining = tokens.len().saturating_sub(MAX_SWEEP_TOKENS);
+    tokens.truncate(MAX_SWEEP_TOKENS);
+
+    Ok(SweepPlan {
+        swept: tokens,
+        remaining,
+    })
+}
+
+/// Plans a transparent sweep, returning the tokens and raw amounts to transfer
+///
+/// # Arguments
+///
+/// * `balances` - Raw balances of the swept address per token
+/// * `fee` - Fee token and raw fee amount, if the fee is paid by the swept address
+///
+/// # Errors
+///
+/// Returns SweepError if the balances are empty or don't cover the fee
+pub fn plan_transparent_sweep(
+    balances: &[(Address, Amount)],
+    fee: Option<(Address, Amount)>,
+) -> Result<Vec<(Address, Amount)>, SweepError> {
+    let mut swept = vec![];
+    for (token, balance) in balances {
+        let amount = match &fee {
+            Some((fee_token, fee_amount)) if fee_token == token => balance
+                .checked_sub(*fee_amount)
+                .ok_or_else(|| SweepError::InsufficientFee {
+                    token: token.clone(),
+                    fee: *fee_amount,
+                })?,
+            _ => *balance,
+        };
+        if !amount.is_zero() {
+            swept.push((token.clone(), amount));
+        }
+    }
+    if swept.is_empty() {
+        return Err(SweepError::NothingToSweep);
+    }
+
+    Ok(swept)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use namada_sdk::address::InternalAddress;
+    use namada_sdk::ethereum_events::EthAddress;
+
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const FEE_TOKEN: &str = "tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e";
+
+    fn amount(amount: u64) -> Amount {
+        Amount::from_u64(amount)
+    }
+
+    fn erc20(i: u8) -> Address {
+        Address::Internal(InternalAddress::Erc20(EthAddress([i; 20])))
+    }
+
+    #[wasm_bindgen_test]
+    fn sweeps_fee_token_last() {
+        let fee_token = Address::from_str(FEE_TOKEN).unwrap();
+        let fee = (fee_token.clone(), amount(10));
+        let token = erc20(0);
+
+        let plan = plan_sweep(
+            &[(fee_token.clone(), amount(100)), (token.clone(), amount(5))],
+            fee.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            plan.swept,
+            vec![(token, amount(5)), (fee_token.clone(), amount(90))]
+        );
+        assert_eq!(plan.remaining, 0);
+
+        // The fee token stays until all other tokens are swept
+        let balances: Vec<_> = (0..MAX_SWEEP_TOKENS + 2)
+            .map(|i| (erc20(i as u8), amount(1)))
+            .chain([(fee_token.clone(), amount(30))])
+            .collect();
+        let plan = plan_sweep(&balances, fee.clone()).unwrap();
+        assert_eq!(plan.swept.len(), MAX_SWEEP_TOKENS);
+        assert!(plan.swept.iter().all(|(token, _)| *token != fee_token));
+        assert_eq!(plan.remaining, 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_balances_not_covering_fees() {
+        let fee_token = Address::from_str(FEE_TOKEN).unwrap();
+        let fee = (fee_token.clone(), amount(10));
+
+        assert_eq!(
+            plan_sweep(&[], fee.clone()),
+            Err(SweepError::NothingToSweep)
+        );
+        assert_eq!(
+            plan_sweep(&[(fee_token.clone(), amount(10))], fee.clone()),
+            Err(SweepError::NothingToSweep)
+        );
+        assert_eq!(
+            plan_sweep(&[(erc20(0), amount(5))], fee.clone()),
+            Err(SweepError::InsufficientFee {
+                token: fee_token,
+                fee: amount(10),
+            })
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn leaves_fee_of_transparent_sweeps() {
+        let fee_token = Address::from_str(FEE_TOKEN).unwrap();
+        let fee = (fee_token.clone(), amount(10));
+        let balances = [
+            (fee_token.clone(), amount(25)),
+            (erc20(0), amount(0)),
+            (erc20(1), amount(7)),
+        ];
+
+        assert_eq!(
+            plan_transparent_sweep(&balances, Some(fee.clone())),
+            Ok(vec![(fee_token.clone(), amount(15)), (erc20(1), amount(7))])
+        );
+        assert_eq!(
+            plan_transparent_sweep(&balances, None),
+            Ok(vec![(fee_token.clone(), amount(25)), (erc20(1), amount(7))])
+        );
+        assert!(plan_transparent_sweep(&balances[..1], Some((fee_token, amount(30)))).is_err());
+        assert_eq!(
+            plan_transparent_sweep(&balances[1..2], None),
+            Err(SweepError::NothingToSweep)
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Transfer templates for recurring payments
+//!
+//! A template stores the parameters of a transparent transfer with an amount formula,
+//! which is resolved against the current balance of the source whenever the template is
+//! instantiated into a fresh tx.
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use namada_sdk::address::Address;
+use namada_sdk::borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use namada_sdk::token::{Amount, DenominatedAmount};
+use serde::Serialize;
+use wasm_bindgen::JsError;
+
+use super::prefixes::ChainPrefixes;
+
+#[cfg(not(feature = "nodejs"))]
+mod templates_web;
+
+#[cfg(not(feature = "nodejs"))]
+pub use templates_web::WebTemplateStorage as JSTemplateStorage;
+
+#[cfg(feature = "nodejs")]
+mod templates_node;
+
+#[cfg(feature = "nodejs")]
+pub use templates_node::NodeTemplateStorage as JSTemplateStorage;
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Serialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct TransferTemplateMsg {
+    pub id: String,
+    pub source: String,
+    pub target: String,
+    pub token: String,
+    /// Amount formula, see AmountFormula
+    pub amount: String,
+    pub memo: Option<String>,
+}
+
+impl TransferTemplateMsg {
+    pub fn validate(&self, prefixes: &ChainPrefixes) -> Result<(), JsError> {
+        if self.id.is_empty() {
+            return Err(JsError::new("Template id can't be empty"));
+        }
+        Address::from_str(&prefixes.native(&self.source))?;
+        Address::from_str(&prefixes.native(&self.target))?;
+        Address::from_str(&prefixes.native(&self.token))?;
+        AmountFormula::from_str(&self.amount)?;
+
+        Ok(())
+    }
+}
+
+/// Templates by their id
+pub type Templates = BTreeMap<String, TransferTemplateMsg>;
+
+/// Amount of a templated transfer, one of:
+/// * `12.5` - fixed denominated amount
+/// * `25%` - percentage of the source balance
+/// * `max` - whole balance of the source
+/// * `max-1.5` - balance of the source, keeping the given amount
+#[derive(Debug, Clone, PartialEq)]
+pub enum AmountFormula {
+    Fixed(DenominatedAmount),
+    Percentage(u8),
+    Max,
+    MaxKeeping(DenominatedAmount),
+}
+
+impl FromStr for AmountFormula {
+    type Err = JsError;
+
+    fn from_str(formula: &str) -> Result<Self, Self::Err> {
+        let formula = formula.trim();
+        let invalid = || JsError::new(&format!("Invalid amount formula: {}", formula));
+
+        if formula == "max" {
+            return Ok(AmountFormula::Max);
+        }
+        if let Some(reserve) = formula.strip_prefix("max-") {
+            let reserve = DenominatedAmount::from_str(reserve).map_err(|_| invalid())?;
+            return Ok(AmountFormula::MaxKeeping(reserve));
+        }
+        if let Some(percentage) = formula.strip_suffix('%') {
+            let percentage = percentage.parse::<u8>().map_err(|_| invalid())?;
+            if percentage == 0 || percentage > 100 {
+                return Err(invalid());
+            }
+            return Ok(AmountFormula::Percentage(percentage));
+        }
+
+        DenominatedAmount::from_str(formula)
+            .map(AmountFormula::Fixed)
+            .map_err(|_| invalid())
+    }
+}
+
+impl AmountFormula {
+    /// Resolves the formula into an amount of the token.
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - Current balance of the source
+    /// * `denom` - Denomination of the token
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the resolved amount is zero or exceeds the balance
+    pub fn resolve(&self, balance: Amount, denom: u8) -> Result<DenominatedAmount, JsError> {
+        let amount = match self {
+            AmountFormula::Fixed(amount) => amount.increase_precision(denom.into())?.amount(),
+            AmountFormula::Percentage(percentage) => balance
+                .checked_mul(Amount::from_u64(u64::from(*percentage)))
+                .and_then(|amount| amount.checked_div(Amount::from_u64(100)))
+                .ok_or_else(|| JsError::new("Amount overflow"))?,
+            AmountFormula::Max => balance,
+            AmountFormula::MaxKeeping(reserve) => {
+                let reserve = reserve.increase_precision(denom.into())?.amount();
+                balance.checked_sub(reserve).unwrap_or_default()
+            }
+        };
+
+        if amount.is_zero() {
+            return Err(JsError::new("Resolved amount is zero"));
+        }
+        if amount > balance {
+            return Err(JsError::new("Resolved amount exceeds the balance"));
+        }
+
+        Ok(DenominatedAmount::new(amount, denom.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn can_resolve_amount_formulas() {
+        let balance = Amount::from_u64(10_000_000);
+        let resolve = |formula: &str| {
+            AmountFormula::from_str(formula)
+                .and_then(|formula| formula.resolve(balance, 6))
+                .map(|amount| amount.amount().raw_amount().as_u64())
+                .ok()
+        };
+
+        assert_eq!(resolve("2.5"), Some(2_500_000));
+        assert_eq!(resolve("25%"), Some(2_500_000));
+        assert_eq!(resolve("max"), Some(10_000_000));
+        assert_eq!(resolve("max-1.5"), Some(8_500_000));
+        assert_eq!(resolve("11"), None);
+        assert_eq!(resolve("max-10"), None);
+        assert_eq!(resolve("101%"), None);
+        assert_eq!(resolve("abc"), None);
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use namada_sdk::borsh;
+use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
+
+use super::Templates;
+use crate::utils::to_bytes;
+
+const FILE_NAME: &str = "templates.dat";
+const TMP_FILE_NAME: &str = "templates.tmp";
+
+/// Stores templates in a file next to the wallet
+#[derive(Debug, Clone)]
+pub struct NodeTemplateStorage {
+    store_dir: PathBuf,
+}
+
+impl NodeTemplateStorage {
+    pub fn new(store_dir: &str) -> Self {
+        Self {
+            store_dir: PathBuf::from(store_dir),
+        }
+    }
+
+    fn path(&self, file_name: &str) -> JsValue {
+        JsValue::from_str(self.store_dir.join(file_name).to_str().unwrap())
+    }
+
+    fn to_js_err(e: JsValue) -> JsError {
+        JsError::new(&e.as_string().unwrap_or_else(|| format!("{:?}", e)))
+    }
+
+    pub async fn load(&self) -> Result<Templates, JsError> {
+        let exists = exists_sync(self.path(FILE_NAME))
+            .map_err(Self::to_js_err)?
+            .as_bool()
+            .unwrap_or_default();
+        if !exists {
+            return Ok(Templates::default());
+        }
+
+        let bytes = to_bytes(read_file_sync(self.path(FILE_NAME)).map_err(Self::to_js_err)?);
+        Ok(borsh::from_slice(&bytes)?)
+    }
+
+    pub async fn save(&self, templates: &Templates) -> Result<(), JsError> {
+        let bytes = borsh::to_vec(templates)?;
+        let uint8_array = js_sys::Uint8Array::from(&bytes[..]);
+
+        // Write to a temporary file first, so a failed write doesn't lose the templates
+        write_file_sync(self.path(TMP_FILE_NAME), uint8_array.into()).map_err(Self::to_js_err)?;
+        rename_sync(self.path(TMP_FILE_NAME), self.path(FILE_NAME)).map_err(Self::to_js_err)?;
+
+        Ok(())
+    }
+}
+
+#[wasm_bindgen(module = "/src/sdk/masp/masp.node.js")]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = "writeFileSync")]
+    fn write_file_sync(path: JsValue, content: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = "readFileSync")]
+    fn read_file_sync(path: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = "renameSync")]
+    fn rename_sync(path_a: JsValue, path_b: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = "existsSync")]
+    fn exists_sync(path: JsValue) -> Result<JsValue, JsValue>;
+}
@@ -0,0 +1,82 @@
+use gloo_utils::format::JsValueSerdeExt;
+use namada_sdk::borsh;
+use rexie::{Error, ObjectStore, Rexie, TransactionMode};
+use wasm_bindgen::{JsError, JsValue};
+
+use super::Templates;
+use crate::utils::to_bytes;
+
+const DB_PREFIX: &str = "namada_sdk::templates";
+const TEMPLATES_TABLE: &str = "Templates";
+const TEMPLATES_KEY: &str = "transfer-templates";
+
+/// Stores templates in IndexedDB, in a database per wallet db name
+#[derive(Debug, Clone)]
+pub struct WebTemplateStorage {
+    db_name: String,
+}
+
+impl WebTemplateStorage {
+    pub fn new(db_name: &str) -> Self {
+        Self {
+            db_name: format!("{}::{}", DB_PREFIX, db_name),
+        }
+    }
+
+    async fn build_database(&self) -> Result<Rexie, Error> {
+        let rexie = Rexie::builder(&self.db_name)
+            .version(1)
+            .add_object_store(ObjectStore::new(TEMPLATES_TABLE))
+            .build()
+            .await?;
+
+        Ok(rexie)
+    }
+
+    fn to_js_err(e: Error) -> JsError {
+        JsError::new(&e.to_string())
+    }
+
+    pub async fn load(&self) -> Result<Templates, JsError> {
+        let db = self.build_database().await.map_err(Self::to_js_err)?;
+        let transaction = db
+            .transaction(&[TEMPLATES_TABLE], TransactionMode::ReadOnly)
+            .map_err(Self::to_js_err)?;
+        let store = transaction
+            .store(TEMPLATES_TABLE)
+            .map_err(Self::to_js_err)?;
+
+        let stored = store
+            .get(&JsValue::from_str(TEMPLATES_KEY))
+            .await
+            .map_err(Self::to_js_err)?;
+        let bytes = to_bytes(stored);
+
+        if bytes.is_empty() {
+            Ok(Templates::default())
+        } else {
+            Ok(borsh::from_slice(&bytes)?)
+        }
+    }
+
+    pub async fn save(&self, templates: &Templates) -> Result<(), JsError> {
+        let bytes = borsh::to_vec(templates)?;
+        let db = self.build_database().await.map_err(Self::to_js_err)?;
+        let transaction = db
+            .transaction(&[TEMPLATES_TABLE], TransactionMode::ReadWrite)
+            .map_err(Self::to_js_err)?;
+        let store = transaction
+            .store(TEMPLATES_TABLE)
+            .map_err(Self::to_js_err)?;
+
+        store
+            .put(
+                &JsValue::from_serde(&bytes)?,
+                Some(&JsValue::from_str(TEMPLATES_KEY)),
+            )
+            .await
+            .map_err(Self::to_js_err)?;
+
+        Ok(())
+    }
+}
@@ -1,22 +1,28 @@
-use std::collections::HashMap;
 use std::str::FromStr;
 
+use bytes::Bytes;
 use gloo_utils::format::JsValueSerdeExt;
+use js_sys::Function;
+use namada_sdk::account::AccountPublicKeysMap;
 use namada_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use namada_sdk::masp::MaspTxId;
+use namada_sdk::masp_primitives::transaction::Transaction as MaspTransaction;
 use namada_sdk::signing::SigningTxData;
-use namada_sdk::tx::data::compute_inner_tx_hash;
+use namada_sdk::token::{Amount, DenominatedAmount, Transfer};
+use namada_sdk::tx;
+use namada_sdk::tx::data::{compute_inner_tx_hash, GasLimit, TxType as NamadaTxType};
 use namada_sdk::tx::either::Either;
-use namada_sdk::tx::{
-    self, TX_BOND_WASM, TX_CLAIM_REWARDS_WASM, TX_IBC_WASM, TX_REDELEGATE_WASM, TX_REVEAL_PK,
-    TX_TRANSFER_WASM, TX_UNBOND_WASM, TX_VOTE_PROPOSAL, TX_WITHDRAW_WASM,
-};
 use namada_sdk::uint::Uint;
 use namada_sdk::{address::Address, key::common::PublicKey};
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
 
 use super::args::WrapperTxMsg;
-use crate::sdk::transaction;
+use super::audit_log::hash_origin;
+use super::code_paths::{tx_code_of_path, TxCode};
+use crate::sdk::{price, sections, transaction};
 use crate::types::query::WasmHash;
+use crate::utils::to_js_result;
 
 #[wasm_bindgen]
 #[derive(BorshSerialize, BorshDeserialize, Copy, Clone, Debug)]
@@ -105,13 +111,107 @@ impl SigningData {
     }
 }
 
-/// Serializable Tx for exported build functions
+/// JSON representation of SigningData, used to pass it between contexts, e.g. from the
+/// webapp to the extension background script. Account public keys map is expanded into
+/// the public keys ordered by their index.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningDataJson {
+    owner: Option<String>,
+    public_keys: Vec<String>,
+    threshold: u8,
+    account_public_keys: Option<Vec<String>>,
+    fee_payer: String,
+}
+
+impl SigningData {
+    pub fn to_json(&self) -> Result<SigningDataJson, JsError> {
+        let signing_tx_data = self.to_signing_tx_data()?;
+        let account_public_keys = signing_tx_data.account_public_keys_map.map(|pk_map| {
+            let mut idx_to_pk: Vec<(u8, PublicKey)> = pk_map.idx_to_pk.into_iter().collect();
+            idx_to_pk.sort_by_key(|(idx, _)| *idx);
+            idx_to_pk
+                .into_iter()
+                .map(|(_, pk)| pk.to_string())
+                .collect()
+        });
+
+        Ok(SigningDataJson {
+            owner: self.owner.clone(),
+            public_keys: self.public_keys.clone(),
+            threshold: self.threshold,
+            account_public_keys,
+            fee_payer: self.fee_payer.clone(),
+        })
+    }
+
+    pub fn from_json(json: SigningDataJson) -> Result<SigningData, JsError> {
+        let account_public_keys_map = match json.account_public_keys {
+            Some(pks) => {
+                let pks = pks
+                    .iter()
+                    .map(|pk| PublicKey::from_str(pk))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Some(borsh::to_vec(&AccountPublicKeysMap::from_iter(pks))?)
+            }
+            None => None,
+        };
+        let signing_data = SigningData {
+            owner: json.owner,
+            public_keys: json.public_keys,
+            threshold: json.threshold,
+            account_public_keys_map,
+            fee_payer: json.fee_payer,
+        };
+        // Make sure all of the addresses and keys are valid
+        signing_data.to_signing_tx_data()?;
+
+        Ok(signing_data)
+    }
+}
+
+/// Converts Borsh serialized SigningData into its JSON representation
+#[wasm_bindgen]
+pub fn signing_data_to_json(signing_data: &[u8]) -> Result<String, JsError> {
+    let signing_data: SigningData = borsh::from_slice(signing_data)?;
+    Ok(serde_json::to_string(&signing_data.to_json()?)?)
+}
+
+/// Converts JSON representation of SigningData into Borsh serialized SigningData
+#[wasm_bindgen]
+pub fn signing_data_from_json(json: &str) -> Result<Vec<u8>, JsError> {
+    let json: SigningDataJson = serde_json::from_str(json)?;
+    Ok(borsh::to_vec(&SigningData::from_json(json)?)?)
+}
+
+/// Borsh (de)serialization of Bytes, in the layout of Vec<u8>
+mod borsh_bytes {
+    use std::io::{Read, Result, Write};
+
+    use bytes::Bytes;
+    use namada_sdk::borsh::{BorshDeserialize, BorshSerialize};
+
+    pub fn serialize<W: Write>(bytes: &Bytes, writer: &mut W) -> Result<()> {
+        BorshSerialize::serialize(bytes.as_ref(), writer)
+    }
+
+    pub fn deserialize<R: Read>(reader: &mut R) -> Result<Bytes> {
+        Vec::<u8>::deserialize_reader(reader).map(Bytes::from)
+    }
+}
+
+/// Serializable Tx for exported build functions. The serialized Namada tx is kept in Bytes,
+/// so clones of large MASP txs, e.g. in the build cache, share a single buffer.
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
 #[borsh(crate = "namada_sdk::borsh")]
 pub struct Tx {
     args: WrapperTxMsg,
     hash: String,
-    bytes: Vec<u8>,
+    #[borsh(
+        serialize_with = "borsh_bytes::serialize",
+        deserialize_with = "borsh_bytes::deserialize"
+    )]
+    bytes: Bytes,
     signing_data: Vec<SigningData>,
 }
 
@@ -128,7 +228,7 @@ impl Tx {
             signing_data.push(sd);
         }
         let hash = tx.wrapper_hash();
-        let bytes: Vec<u8> = borsh::to_vec(&tx)?;
+        let bytes = Bytes::from(borsh::to_vec(&tx)?);
 
         Ok(Tx {
             args,
@@ -138,13 +238,13 @@ impl Tx {
         })
     }
 
-    pub fn tx_bytes(&self) -> Vec<u8> {
-        self.bytes.clone()
+    pub fn tx_bytes(&self) -> &[u8] {
+        &self.bytes
     }
 
     pub fn signing_tx_data(&self) -> Result<Vec<SigningTxData>, JsError> {
         let mut signing_tx_data: Vec<SigningTxData> = vec![];
-        for sd in self.signing_data.clone().iter() {
+        for sd in self.signing_data.iter() {
             signing_tx_data.push(sd.to_signing_tx_data()?);
         }
 
@@ -154,6 +254,150 @@ impl Tx {
     pub fn args(&self) -> WrapperTxMsg {
         self.args.clone()
     }
+
+    /// Borsh serialized account public keys maps of the signing data
+    pub fn account_public_keys_maps(&self) -> impl Iterator<Item = &[u8]> {
+        self.signing_data
+            .iter()
+            .filter_map(|sd| sd.account_public_keys_map.as_deref())
+    }
+}
+
+/// Signers of the inner txs of an owner, `threshold` of `public_keys` have to sign
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InnerSigners {
+    pub owner: Option<String>,
+    pub public_keys: Vec<String>,
+    pub threshold: u8,
+}
+
+/// Signatures required before a built tx can be broadcast
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequiredSignatures {
+    /// Signatures of the raw header, per owner of the inner txs
+    pub inner: Vec<InnerSigners>,
+    /// Public key signing the wrapper and paying the fee
+    pub fee_payer: String,
+}
+
+impl Tx {
+    /// Returns who has to sign the tx and in which role
+    pub fn required_signatures(&self) -> RequiredSignatures {
+        let inner = self
+            .signing_data
+            .iter()
+            .filter(|sd| !sd.public_keys.is_empty())
+            .map(|sd| InnerSigners {
+                owner: sd.owner.clone(),
+                public_keys: sd.public_keys.clone(),
+                threshold: sd.threshold,
+            })
+            .collect();
+        let fee_payer = self
+            .signing_data
+            .first()
+            .map(|sd| sd.fee_payer.clone())
+            .unwrap_or_default();
+
+        RequiredSignatures { inner, fee_payer }
+    }
+}
+
+/// Returns the RequiredSignatures of a Borsh serialized Tx
+#[wasm_bindgen]
+pub fn required_signatures(tx: &[u8]) -> Result<JsValue, JsError> {
+    let tx: Tx = borsh::from_slice(tx)?;
+    to_js_result(tx.required_signatures())
+}
+
+/// Replaces the fee of a wrapper tx and removes the wrapper signatures, which no longer
+/// match the header. Inner tx signatures only cover the raw header, so they stay valid and
+/// replay protection makes sure only one of the wrappers gets its inner txs applied.
+///
+/// This does not cancel the original tx: both wrappers stay valid, and whichever is included
+/// first gets its inner txs applied. The other one is rejected, but may still be charged its
+/// fee if it gets included in a block.
+///
+/// # Errors
+///
+/// Returns JsError if the tx is not a wrapper, the fee per gas unit is not strictly higher or
+/// the gas limit would be lowered
+pub fn replace_wrapper_fee(
+    tx: &mut tx::Tx,
+    fee_amount: DenominatedAmount,
+    gas_limit: GasLimit,
+) -> Result<(), JsError> {
+    let header_hash = tx.header_hash();
+
+    let wrapper = match &mut tx.header.tx_type {
+        NamadaTxType::Wrapper(wrapper) => wrapper,
+        _ => return Err(JsError::new("Only wrapper txs can be replaced")),
+    };
+    match fee_amount.checked_sub(wrapper.fee.amount_per_gas_unit) {
+        Some(increase) if !increase.is_zero() => {}
+        _ => {
+            return Err(JsError::new(
+                "Fee amount per gas unit has to be higher than the replaced one",
+            ))
+        }
+    }
+    if u64::from(gas_limit) < u64::from(wrapper.gas_limit) {
+        return Err(JsError::new("Gas limit can't be lowered"));
+    }
+    wrapper.fee.amount_per_gas_unit = fee_amount;
+    wrapper.gas_limit = gas_limit;
+
+    sections::invalidate_signatures(tx, &[header_hash]);
+
+    Ok(())
+}
+
+/// Attaches a MASP transaction built elsewhere, e.g. in a worker, to an unsigned transfer.
+/// The shielded section hash of the transfer data is set to the id of the MASP transaction,
+/// which has to match the expected hash.
+///
+/// # Errors
+///
+/// Returns JsError if the section doesn't decode or match the hash, or the tx is not a
+/// single transfer without a shielded section
+pub fn attach_masp_section(
+    tx: &mut tx::Tx,
+    shielded_section_hash: &[u8],
+    masp_section: &[u8],
+) -> Result<MaspTxId, JsError> {
+    let masp_tx = MaspTransaction::try_from_slice(masp_section)?;
+    let masp_tx_id = MaspTxId::from(masp_tx.txid());
+    if masp_tx_id != MaspTxId::try_from_slice(shielded_section_hash)? {
+        return Err(JsError::new(
+            "MASP section does not match the shielded section hash",
+        ));
+    }
+
+    let cmt = match tx.commitments().as_slice() {
+        [cmt] => cmt.clone(),
+        _ => {
+            return Err(JsError::new(
+                "MASP sections can only be attached to txs with a single inner tx",
+            ))
+        }
+    };
+    let data = tx
+        .data(&cmt)
+        .ok_or_else(|| JsError::new("Tx has no data section"))?;
+    let mut transfer = Transfer::try_from_slice(&data)
+        .map_err(|_| JsError::new("MASP sections can only be attached to transfers"))?;
+    if transfer.shielded_section_hash.is_some() {
+        return Err(JsError::new("Transfer already has a shielded section"));
+    }
+    transfer.shielded_section_hash = Some(masp_tx_id);
+
+    let data = tx::Section::Data(tx::Data::new(borsh::to_vec(&transfer)?));
+    sections::replace_section(tx, *cmt.data_sechash(), data)?;
+    sections::add_section(tx, tx::Section::MaspTx(masp_tx))?;
+
+    Ok(masp_tx_id)
 }
 
 // Given the bytes of a Namada Tx, return all inner Tx hashes
@@ -173,35 +417,50 @@ pub fn get_inner_tx_hashes(tx_bytes: &[u8]) -> Result<Vec<String>, JsError> {
 }
 
 pub fn wasm_hash_to_tx_type(wasm_hash: &str, wasm_hashes: &Vec<WasmHash>) -> Option<TxType> {
-    let type_map: HashMap<String, TxType> = HashMap::from([
-        (TX_TRANSFER_WASM.to_string(), TxType::Transfer),
-        (TX_BOND_WASM.to_string(), TxType::Bond),
-        (TX_REDELEGATE_WASM.to_string(), TxType::Redelegate),
-        (TX_UNBOND_WASM.to_string(), TxType::Unbond),
-        (TX_WITHDRAW_WASM.to_string(), TxType::Withdraw),
-        (TX_CLAIM_REWARDS_WASM.to_string(), TxType::ClaimRewards),
-        (TX_REVEAL_PK.to_string(), TxType::RevealPK),
-        (TX_VOTE_PROPOSAL.to_string(), TxType::VoteProposal),
-        (TX_IBC_WASM.to_string(), TxType::IBCTransfer),
-    ]);
-
-    for wh in wasm_hashes {
-        if wh.hash() == wasm_hash {
-            let tx_type = type_map.get(&wh.path());
-
-            if tx_type.is_some() {
-                return Some(*tx_type.unwrap());
-            }
-        }
-    }
-
-    None
+    wasm_hashes
+        .iter()
+        .filter(|wh| wh.hash() == wasm_hash)
+        .find_map(|wh| tx_code_of_path(&wh.path()))
+        .and_then(|code| match code {
+            TxCode::Transfer => Some(TxType::Transfer),
+            TxCode::Bond => Some(TxType::Bond),
+            TxCode::Redelegate => Some(TxType::Redelegate),
+            TxCode::Unbond => Some(TxType::Unbond),
+            TxCode::Withdraw => Some(TxType::Withdraw),
+            TxCode::ClaimRewards => Some(TxType::ClaimRewards),
+            TxCode::RevealPk => Some(TxType::RevealPK),
+            TxCode::VoteProposal => Some(TxType::VoteProposal),
+            TxCode::Ibc => Some(TxType::IBCTransfer),
+            TxCode::BridgePool
+            | TxCode::ChangeCommission
+            | TxCode::ChangeMetadata
+            | TxCode::ChangeConsensusKey
+            | TxCode::InitAccount
+            | TxCode::UpdateAccount => None,
+        })
 }
 
-// Deserialize Tx commitments into Borsh-serialized struct
+// Deserialize Tx commitments into Borsh-serialized struct. If price_callback is provided, fiat
+// values of commitments are computed with it, see price::JsPriceProvider. The origin of the
+// dApp requesting the signature is included hashed, matching the key audit log.
 #[wasm_bindgen]
-pub fn deserialize_tx(tx_bytes: Vec<u8>, wasm_hashes: JsValue) -> Result<Vec<u8>, JsError> {
-    let tx = TxDetails::from_bytes(tx_bytes, wasm_hashes)?;
+pub fn deserialize_tx(
+    tx_bytes: Vec<u8>,
+    wasm_hashes: JsValue,
+    price_callback: Option<Function>,
+    native_token: Option<String>,
+    origin: Option<String>,
+) -> Result<Vec<u8>, JsError> {
+    let price_provider =
+        price_callback.map(|callback| price::JsPriceProvider::new(callback, native_token));
+    let mut tx = TxDetails::from_bytes(
+        tx_bytes,
+        wasm_hashes,
+        price_provider
+            .as_ref()
+            .map(|provider| provider as &dyn price::PriceProvider),
+    )?;
+    tx.origin_hash = origin.as_deref().map(hash_origin);
     Ok(borsh::to_vec(&tx)?)
 }
 
@@ -213,6 +472,7 @@ pub struct Commitment {
     tx_code_id: String,
     data: Vec<u8>,
     memo: Option<String>,
+    fiat_value: Option<String>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -220,10 +480,15 @@ pub struct Commitment {
 pub struct TxDetails {
     wrapper_tx: WrapperTxMsg,
     commitments: Vec<Commitment>,
+    origin_hash: Option<String>,
 }
 
 impl TxDetails {
-    pub fn from_bytes(tx_bytes: Vec<u8>, wasm_hashes: JsValue) -> Result<TxDetails, JsError> {
+    pub fn from_bytes(
+        tx_bytes: Vec<u8>,
+        wasm_hashes: JsValue,
+        price_provider: Option<&dyn price::PriceProvider>,
+    ) -> Result<TxDetails, JsError> {
         let tx: tx::Tx = borsh::from_slice(&tx_bytes)?;
         let chain_id = tx.header().chain_id.to_string();
 
@@ -233,8 +498,17 @@ impl TxDetails {
                 let gas_limit = Uint::from(wrapper.gas_limit).to_string();
                 let token = wrapper.fee.token.to_string();
 
-                let wrapper_tx =
-                    WrapperTxMsg::new(token, fee_amount, gas_limit, chain_id, None, None, None);
+                let expiration = tx
+                    .header()
+                    .expiration
+                    .map(|expiration| expiration.to_string());
+
+                let fee_payer = Some(wrapper.pk.to_string());
+
+                let wrapper_tx = WrapperTxMsg::new(
+                    token, fee_amount, gas_limit, chain_id, None, None, None, expiration,
+                    fee_payer, None, None,
+                );
                 let mut commitments: Vec<Commitment> = vec![];
                 let wasm_hashes: Vec<WasmHash> = wasm_hashes.into_serde().unwrap();
 
@@ -261,6 +535,12 @@ impl TxDetails {
                             let tx_data = tx.data(&cmt).unwrap_or_default();
                             let tx_kind = transaction::TransactionKind::from(tx_type, &tx_data);
                             let data = tx_kind.to_bytes()?;
+                            let fiat_value = match price_provider {
+                                Some(provider) => {
+                                    price::transaction_fiat_value(provider, &tx_kind)?
+                                }
+                                None => None,
+                            };
 
                             commitments.push(Commitment {
                                 tx_type,
@@ -268,6 +548,7 @@ impl TxDetails {
                                 tx_code_id,
                                 data,
                                 memo,
+                                fiat_value,
                             });
                         }
                     }
@@ -276,6 +557,7 @@ impl TxDetails {
                 Ok(TxDetails {
                     wrapper_tx,
                     commitments,
+                    origin_hash: None,
                 })
             }
             _ => Err(JsError::new("Invalid transaction type!")),
@@ -299,6 +581,56 @@ impl BatchTxResult {
     }
 }
 
+/// Gas and fee accounting of an applied wrapper tx, with denominated amounts. The fee is
+/// charged on the gas limit up front and paid in full to the block proposer, so unused gas
+/// is not refunded and no part of the fee is burned.
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct FeeAccounting {
+    token: String,
+    gas_limit: String,
+    gas_used: String,
+    fee_per_gas_unit: String,
+    charged: String,
+    refunded: String,
+    paid_to_proposer: String,
+    burned: String,
+}
+
+impl FeeAccounting {
+    /// Computes the accounting of a wrapper tx from its fee and the gas used by the
+    /// applied tx, as reported by its applied event
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the fee overflows
+    pub fn new(
+        token: &Address,
+        fee_per_gas_unit: DenominatedAmount,
+        gas_limit: u64,
+        gas_used: u64,
+    ) -> Result<FeeAccounting, JsError> {
+        let denom = fee_per_gas_unit.denom();
+        let charged = fee_per_gas_unit
+            .amount()
+            .checked_mul(Amount::from_u64(gas_limit))
+            .ok_or_else(|| JsError::new("Fee amount overflow"))?;
+        let charged = DenominatedAmount::new(charged, denom);
+        let zero = DenominatedAmount::new(Amount::zero(), denom);
+
+        Ok(FeeAccounting {
+            token: token.to_string(),
+            gas_limit: gas_limit.to_string(),
+            gas_used: gas_used.to_string(),
+            fee_per_gas_unit: fee_per_gas_unit.to_string(),
+            charged: charged.to_string(),
+            refunded: zero.to_string(),
+            paid_to_proposer: charged.to_string(),
+            burned: zero.to_string(),
+        })
+    }
+}
+
 /// Serializable response for process_tx calls
 #[wasm_bindgen]
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -311,9 +643,11 @@ pub struct TxResponse {
     height: String,
     info: String,
     log: String,
+    fee: Option<FeeAccounting>,
 }
 
 impl TxResponse {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         code: String,
         commitments: Vec<BatchTxResult>,
@@ -322,6 +656,7 @@ impl TxResponse {
         height: String,
         info: String,
         log: String,
+        fee: Option<FeeAccounting>,
     ) -> TxResponse {
         TxResponse {
             code,
@@ -331,6 +666,96 @@ impl TxResponse {
             height,
             info,
             log,
+            fee,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const NATIVE_TOKEN: &str = "tnam1qxvg64psvhwumv3mwrrjfcz0h3t3274hwggyzcee";
+
+    #[wasm_bindgen_test]
+    fn charges_fee_on_gas_limit() {
+        let token = Address::from_str(NATIVE_TOKEN).unwrap();
+        let fee_per_gas_unit = DenominatedAmount::from_str("0.000001").unwrap();
+        let accounting = FeeAccounting::new(&token, fee_per_gas_unit, 50_000, 31_200).unwrap();
+
+        let amount = |amount: &str| DenominatedAmount::from_str(amount).unwrap().to_string();
+        assert_eq!(accounting.gas_used, "31200");
+        assert_eq!(accounting.charged, amount("0.050000"));
+        assert_eq!(accounting.paid_to_proposer, accounting.charged);
+        assert_eq!(accounting.refunded, amount("0.000000"));
+        assert_eq!(accounting.burned, amount("0.000000"));
+    }
+
+    #[wasm_bindgen_test]
+    fn serializes_bytes_as_vec() {
+        let bytes = Bytes::from_static(&[1, 2, 3]);
+        let mut serialized = vec![];
+        borsh_bytes::serialize(&bytes, &mut serialized).unwrap();
+        assert_eq!(serialized, borsh::to_vec(&vec![1u8, 2, 3]).unwrap());
+        assert_eq!(
+            borsh_bytes::deserialize(&mut serialized.as_slice()).unwrap(),
+            bytes
+        );
+    }
+
+    const PUBLIC_KEY: &str = "tpknam1qzm68sfdcryvwj9tqaf9kuq3y2ugh4u0vqx8vdpdyle9uhujg3xduf408cn";
+
+    #[wasm_bindgen_test]
+    fn lists_required_signatures() {
+        let signing_data = |owner: &str| SigningData {
+            owner: Some(owner.to_string()),
+            public_keys: vec![PUBLIC_KEY.to_string()],
+            threshold: 1,
+            account_public_keys_map: None,
+            fee_payer: PUBLIC_KEY.to_string(),
+        };
+        let tx = Tx {
+            args: WrapperTxMsg::new(
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ),
+            hash: String::new(),
+            bytes: vec![],
+            signing_data: vec![signing_data("tnam1a"), signing_data("tnam1b")],
+        };
+
+        let required = tx.required_signatures();
+        assert_eq!(required.inner.len(), 2);
+        assert_eq!(required.inner[1].owner, Some("tnam1b".to_string()));
+        assert_eq!(required.fee_payer, PUBLIC_KEY);
+    }
+
+    #[wasm_bindgen_test]
+    fn can_round_trip_signing_data_json() {
+        let json = SigningDataJson {
+            owner: Some(String::from(
+                "tnam1qpgk927uh2aqjs92yhycsh08n5yggvltn5nk92zp",
+            )),
+            public_keys: vec![PUBLIC_KEY.to_string()],
+            threshold: 1,
+            account_public_keys: Some(vec![PUBLIC_KEY.to_string()]),
+            fee_payer: PUBLIC_KEY.to_string(),
+        };
+        let json_string = serde_json::to_string(&json).unwrap();
+
+        let bytes = signing_data_from_json(&json_string).unwrap();
+        let signing_data: SigningData = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(signing_data.to_json().unwrap(), json);
+        assert_eq!(signing_data_to_json(&bytes).unwrap(), json_string);
+    }
+}
@@ -0,0 +1,195 @@
+//! Validation of unshielding transfers with multiple transparent targets
+//!
+//! All targets are paid from the notes of a single spending key in one MASP transaction,
+//! with the change of every token returned to the source. Amounts requested per token,
+//! together with the fee when it's unshielded from the same key, have to be covered by the
+//! shielded balance of the source.
+use std::collections::{BTreeMap, BTreeSet};
+
+use namada_sdk::address::Address;
+use namada_sdk::token::Amount;
+use thiserror::Error;
+
+/// Maximum number of transparent targets of a single unshielding transfer, keeping the
+/// MASP transaction within reasonable size and gas
+pub const MAX_UNSHIELDING_TARGETS: usize = 16;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum UnshieldingError {
+    #[error("Unshielding transfer has no targets")]
+    NoTargets,
+    #[error(
+        "Unshielding transfer can't have more than {MAX_UNSHIELDING_TARGETS} targets, got {0}"
+    )]
+    TooManyTargets(usize),
+    #[error("Duplicate unshielding of {token} to {target}")]
+    DuplicateTarget { target: Address, token: Address },
+    #[error("Zero amount of {token} unshielded to {target}")]
+    ZeroAmount { target: Address, token: Address },
+    #[error("Amount overflow of {0}")]
+    Overflow(Address),
+    #[error(
+        "Insufficient shielded balance of {token}: required {required}, available {available}"
+    )]
+    InsufficientBalance {
+        token: Address,
+        required: Amount,
+        available: Amount,
+    },
+}
+
+/// Unshielding target with a raw amount
+pub struct UnshieldingTarget {
+    pub target: Address,
+    pub token: Address,
+    pub amount: Amount,
+}
+
+/// Validates the targets and returns the raw amounts required from the source per token.
+///
+/// # Arguments
+///
+/// * `targets` - Unshielding targets
+/// * `fee` - Fee token and raw fee amount, if the fee is unshielded from the source
+///
+/// # Errors
+///
+/// Returns UnshieldingError if the targets are empty, too many, duplicate or zero
+pub fn required_amounts(
+    targets: &[UnshieldingTarget],
+    fee: Option<(Address, Amount)>,
+) -> Result<BTreeMap<Address, Amount>, UnshieldingError> {
+    if targets.is_empty() {
+        return Err(UnshieldingError::NoTargets);
+    }
+    if targets.len() > MAX_UNSHIELDING_TARGETS {
+        return Err(UnshieldingError::TooManyTargets(targets.len()));
+    }
+
+    let mut seen: BTreeSet<(&Address, &Address)> = BTreeSet::new();
+    let mut required: BTreeMap<Address, Amount> = BTreeMap::new();
+
+    for UnshieldingTarget {
+        target,
+        token,
+        amount,
+    } in targets
+    {
+        if amount.is_zero() {
+            return Err(UnshieldingError::ZeroAmount {
+                target: target.clone(),
+                token: token.clone(),
+            });
+        }
+        // Duplicate targets are most likely a UI bug resulting in a double payment
+        if !seen.insert((target, token)) {
+            return Err(UnshieldingError::DuplicateTarget {
+                target: target.clone(),
+                token: token.clone(),
+            });
+        }
+        add_amount(&mut required, token, *amount)?;
+    }
+
+    if let Some((fee_token, fee_amount)) = fee {
+        add_amount(&mut required, &fee_token, fee_amount)?;
+    }
+
+    Ok(required)
+}
+
+fn add_amount(
+    amounts: &mut BTreeMap<Address, Amount>,
+    token: &Address,
+    amount: Amount,
+) -> Result<(), UnshieldingError> {
+    let total = amounts.entry(token.clone()).or_default();
+    *total = total
+        .checked_add(amount)
+        .ok_or_else(|| UnshieldingError::Overflow(token.clone()))?;
+    Ok(())
+}
+
+/// Checks that the balance covers the required amount of a token
+pub fn check_balance(
+    token: &Address,
+    required: Amount,
+    available: Amount,
+) -> Result<(), UnshieldingError> {
+    if available < required {
+        return Err(UnshieldingError::InsufficientBalance {
+            token: token.clone(),
+            required,
+            available,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const TARGET_A: &str = "tnam1qzegy7g6y4ajqcl8ax2e3en4tj9xkp0mz5yrwgaf";
+    const TARGET_B: &str = "tnam1qpgk927uh2aqjs92yhycsh08n5yggvltn5nk92zp";
+    const TOKEN: &str = "tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e";
+
+    fn target(target: &str, amount: u64) -> UnshieldingTarget {
+        UnshieldingTarget {
+            target: Address::from_str(target).unwrap(),
+            token: Address::from_str(TOKEN).unwrap(),
+            amount: Amount::from_u64(amount),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn sums_amounts_of_multiple_targets_and_fee() {
+        let token = Address::from_str(TOKEN).unwrap();
+        let targets = [target(TARGET_A, 100), target(TARGET_B, 50)];
+
+        let required = required_amounts(&targets, None).unwrap();
+        assert_eq!(required.get(&token), Some(&Amount::from_u64(150)));
+
+        let required = required_amounts(&targets, Some((token.clone(), Amount::from_u64(10))));
+        assert_eq!(required.unwrap().get(&token), Some(&Amount::from_u64(160)));
+
+        assert!(check_balance(&token, Amount::from_u64(160), Amount::from_u64(160)).is_ok());
+        assert_eq!(
+            check_balance(&token, Amount::from_u64(160), Amount::from_u64(159)),
+            Err(UnshieldingError::InsufficientBalance {
+                token,
+                required: Amount::from_u64(160),
+                available: Amount::from_u64(159),
+            })
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_invalid_targets() {
+        assert_eq!(
+            required_amounts(&[], None),
+            Err(UnshieldingError::NoTargets)
+        );
+        assert!(matches!(
+            required_amounts(&[target(TARGET_A, 1), target(TARGET_A, 2)], None),
+            Err(UnshieldingError::DuplicateTarget { .. })
+        ));
+        assert!(matches!(
+            required_amounts(&[target(TARGET_A, 0)], None),
+            Err(UnshieldingError::ZeroAmount { .. })
+        ));
+
+        let targets: Vec<_> = (0..=MAX_UNSHIELDING_TARGETS)
+            .map(|_| target(TARGET_A, 1))
+            .collect();
+        assert_eq!(
+            required_amounts(&targets, None),
+            Err(UnshieldingError::TooManyTargets(
+                MAX_UNSHIELDING_TARGETS + 1
+            ))
+        );
+    }
+}
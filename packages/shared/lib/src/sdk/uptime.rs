@@ -0,0 +1,154 @@
+//! Validator uptime from CometBFT commits
+//!
+//! PoS only records slashes and jails, not how reliably a validator signs blocks. The uptime
+//! of a validator is read from the commits of the last blocks: its consensus key is looked up
+//! in PoS and hashed to the CometBFT address the commit signatures refer to. Validators out of
+//! the CometBFT validator set of the latest block have no uptime, as they are not expected to
+//! sign.
+use std::collections::BTreeSet;
+
+use namada_sdk::address::Address;
+use namada_sdk::io::Client;
+use namada_sdk::key::tm_consensus_key_raw_hash;
+use namada_sdk::queries::RPC;
+use namada_sdk::tendermint::block::{CommitSig, Height};
+use namada_sdk::tendermint_rpc::Paging;
+use serde::Serialize;
+use wasm_bindgen::JsError;
+
+use crate::rpc_client::HttpClient;
+
+/// Default number of blocks the uptime is computed over
+pub const DEFAULT_UPTIME_WINDOW: u64 = 100;
+/// Bounds the number of commits fetched per query
+pub const MAX_UPTIME_WINDOW: u64 = 1000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorUptime {
+    pub address: String,
+    /// Bech32 consensus public key, None if the validator has none
+    pub consensus_key: Option<String>,
+    /// Hex CometBFT address of the consensus key
+    pub tm_address: Option<String>,
+    /// True if the validator is in the CometBFT validator set of the latest block
+    pub active: bool,
+    pub signed_blocks: u64,
+    pub missed_blocks: u64,
+    /// Number of blocks the uptime is computed over
+    pub window: u64,
+    /// Share of signed blocks in the window from 0 to 1, None if not active
+    pub reliability: Option<f64>,
+}
+
+/// Returns the CometBFT addresses that signed a commit, upper case hex
+fn signers(signatures: &[CommitSig]) -> BTreeSet<String> {
+    signatures
+        .iter()
+        .filter_map(|signature| match signature {
+            CommitSig::BlockIdFlagCommit {
+                validator_address, ..
+            } => Some(validator_address.to_string().to_uppercase()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Counts the blocks signed by a CometBFT address, returning signed and missed blocks
+fn tally(signers_per_block: &[BTreeSet<String>], tm_address: &str) -> (u64, u64) {
+    let tm_address = tm_address.to_uppercase();
+    let signed = signers_per_block
+        .iter()
+        .filter(|signers| signers.contains(&tm_address))
+        .count() as u64;
+    (signed, signers_per_block.len() as u64 - signed)
+}
+
+/// Returns the share of signed blocks, None for an empty window
+fn reliability(signed: u64, window: u64) -> Option<f64> {
+    (window > 0).then(|| signed as f64 / window as f64)
+}
+
+/// Returns the uptime of validators over the last `window` blocks, capped to
+/// MAX_UPTIME_WINDOW.
+///
+/// # Errors
+///
+/// Returns JsError if a consensus key, commit or the validator set can't be fetched
+pub async fn validator_uptime(
+    client: &HttpClient,
+    validators: &[Address],
+    window: u64,
+) -> Result<Vec<ValidatorUptime>, JsError> {
+    let latest = client.latest_commit().await?;
+    let latest_height = latest.signed_header.header.height.value();
+    let window = window.clamp(1, MAX_UPTIME_WINDOW).min(latest_height);
+
+    let active: BTreeSet<String> = client
+        .validators(Height::try_from(latest_height)?, Paging::All)
+        .await?
+        .validators
+        .iter()
+        .map(|validator| validator.address.to_string().to_uppercase())
+        .collect();
+
+    let mut signers_per_block = vec![signers(&latest.signed_header.commit.signatures)];
+    for height in (latest_height + 1 - window..latest_height).rev() {
+        let commit = client.commit(Height::try_from(height)?).await?;
+        signers_per_block.push(signers(&commit.signed_header.commit.signatures));
+    }
+
+    let mut uptimes = vec![];
+    for validator in validators {
+        let consensus_key = RPC.vp().pos().consensus_key(client, validator).await?;
+        let tm_address = consensus_key.as_ref().map(tm_consensus_key_raw_hash);
+        let is_active = tm_address
+            .as_ref()
+            .is_some_and(|tm_address| active.contains(&tm_address.to_uppercase()));
+        let (signed_blocks, missed_blocks) = match (&tm_address, is_active) {
+            (Some(tm_address), true) => tally(&signers_per_block, tm_address),
+            _ => (0, 0),
+        };
+
+        uptimes.push(ValidatorUptime {
+            address: validator.to_string(),
+            consensus_key: consensus_key.map(|key| key.to_string()),
+            tm_address,
+            active: is_active,
+            signed_blocks,
+            missed_blocks,
+            window,
+            reliability: if is_active {
+                reliability(signed_blocks, window)
+            } else {
+                None
+            },
+        });
+    }
+    Ok(uptimes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn tallies_signed_blocks() {
+        let block = |signers: &[&str]| -> BTreeSet<String> {
+            signers.iter().map(|signer| signer.to_string()).collect()
+        };
+        let blocks = [
+            block(&["AA", "BB"]),
+            block(&["AA"]),
+            block(&["BB"]),
+            block(&["AA"]),
+        ];
+
+        assert_eq!(tally(&blocks, "aa"), (3, 1));
+        assert_eq!(tally(&blocks, "BB"), (2, 2));
+        assert_eq!(tally(&blocks, "CC"), (0, 4));
+        assert_eq!(reliability(3, 4), Some(0.75));
+        assert_eq!(reliability(0, 0), None);
+    }
+}
@@ -7,10 +7,10 @@ use namada_sdk::{
 use std::str::FromStr;
 use zeroize::Zeroizing;
 
-#[cfg(feature = "web")]
+#[cfg(not(feature = "nodejs"))]
 mod wallet_web;
 
-#[cfg(feature = "web")]
+#[cfg(not(feature = "nodejs"))]
 pub use wallet_web::BrowserWalletUtils as JSWalletUtils;
 
 #[cfg(feature = "nodejs")]
@@ -0,0 +1,313 @@
+//! WalletConnect v2 payload encoding/decoding
+//!
+//! Translates WalletConnect session requests for the `namada_signTx` and
+//! `namada_signArbitrary` methods to and from the Borsh-serialized Msg types
+//! used by the rest of the Sdk.
+use namada_sdk::borsh::{self, BorshDeserialize, BorshSchema, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::wasm_bindgen, JsError};
+
+use super::tx;
+
+/// WalletConnect namespace used for CAIP-2 chain ids, e.g. "namada:housefire-cotton.d3c912fee7462"
+pub const WC_NAMESPACE: &str = "namada";
+pub const WC_METHOD_SIGN_TX: &str = "namada_signTx";
+pub const WC_METHOD_SIGN_ARBITRARY: &str = "namada_signArbitrary";
+
+const JSON_RPC_VERSION: &str = "2.0";
+
+#[derive(Serialize, Deserialize)]
+struct SessionRequest {
+    id: u64,
+    topic: String,
+    params: SessionRequestParams,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionRequestParams {
+    request: RequestArguments,
+    chain_id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RequestArguments {
+    method: String,
+    params: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignTxParams {
+    signer: String,
+    // Hex-encoded, Borsh serialized Sdk Tx
+    txs: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignArbitraryParams {
+    signer: String,
+    data: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonRpcResponse<T> {
+    id: u64,
+    jsonrpc: String,
+    result: T,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignTxResult {
+    // Hex-encoded, Borsh serialized namada Tx
+    signed_txs: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignArbitraryResult {
+    hash: String,
+    signature: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub enum WcRequestKind {
+    SignTx { txs: Vec<Vec<u8>> },
+    SignArbitrary { data: String },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct WcSessionRequestMsg {
+    id: u64,
+    topic: String,
+    chain_id: String,
+    signer: String,
+    request: WcRequestKind,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct WcSignArbitraryResponseMsg {
+    id: u64,
+    hash: String,
+    signature: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+#[borsh(crate = "namada_sdk::borsh")]
+pub struct WcSignTxResponseMsg {
+    id: u64,
+    signed_txs: Vec<Vec<u8>>,
+}
+
+/// Strips the "namada:" CAIP-2 namespace from a WalletConnect chain id
+fn chain_id_from_caip2(caip2: &str) -> Result<String, JsError> {
+    match caip2.split_once(':') {
+        Some((WC_NAMESPACE, chain_id)) if !chain_id.is_empty() => Ok(chain_id.to_string()),
+        _ => Err(JsError::new(&format!(
+            "Invalid WalletConnect chain id {}, expected {}:<chain_id>",
+            caip2, WC_NAMESPACE
+        ))),
+    }
+}
+
+fn decode_hex_list(values: Vec<String>) -> Result<Vec<Vec<u8>>, JsError> {
+    let mut bytes: Vec<Vec<u8>> = vec![];
+    for value in values {
+        bytes.push(hex::decode(value.trim_start_matches("0x"))?);
+    }
+
+    Ok(bytes)
+}
+
+/// Decodes a WalletConnect session request JSON payload into a Borsh serialized WcSessionRequestMsg.
+///
+/// # Arguments
+///
+/// * `payload` - JSON payload of the `session_request` event
+///
+/// # Errors
+///
+/// Returns JsError if the payload is not valid JSON, the method is not supported or
+/// any of the txs is not a valid Borsh serialized Tx.
+#[wasm_bindgen]
+pub fn decode_wc_session_request(payload: &str) -> Result<Vec<u8>, JsError> {
+    let SessionRequest { id, topic, params } = serde_json::from_str(payload)?;
+    let chain_id = chain_id_from_caip2(&params.chain_id)?;
+    let RequestArguments { method, params } = params.request;
+
+    let (signer, request) = match method.as_str() {
+        WC_METHOD_SIGN_TX => {
+            let SignTxParams { signer, txs } = serde_json::from_value(params)?;
+            let txs = decode_hex_list(txs)?;
+
+            // Make sure we were given valid txs before handing them over
+            for tx_bytes in txs.iter() {
+                borsh::from_slice::<tx::Tx>(tx_bytes)?;
+            }
+
+            (signer, WcRequestKind::SignTx { txs })
+        }
+        WC_METHOD_SIGN_ARBITRARY => {
+            let SignArbitraryParams { signer, data } = serde_json::from_value(params)?;
+            (signer, WcRequestKind::SignArbitrary { data })
+        }
+        _ => {
+            return Err(JsError::new(&format!(
+                "Unsupported WalletConnect method: {}",
+                method
+            )))
+        }
+    };
+
+    let msg = WcSessionRequestMsg {
+        id,
+        topic,
+        chain_id,
+        signer,
+        request,
+    };
+
+    Ok(borsh::to_vec(&msg)?)
+}
+
+/// Encodes a Borsh serialized WcSessionRequestMsg into a WalletConnect session request JSON payload.
+///
+/// # Arguments
+///
+/// * `msg` - Borsh serialized WcSessionRequestMsg
+///
+/// # Errors
+///
+/// Returns JsError if the msg can't be deserialized.
+#[wasm_bindgen]
+pub fn encode_wc_session_request(msg: &[u8]) -> Result<String, JsError> {
+    let WcSessionRequestMsg {
+        id,
+        topic,
+        chain_id,
+        signer,
+        request,
+    } = WcSessionRequestMsg::try_from_slice(msg)?;
+
+    let (method, params) = match request {
+        WcRequestKind::SignTx { txs } => (
+            WC_METHOD_SIGN_TX,
+            serde_json::to_value(SignTxParams {
+                signer,
+                txs: txs.iter().map(hex::encode).collect(),
+            })?,
+        ),
+        WcRequestKind::SignArbitrary { data } => (
+            WC_METHOD_SIGN_ARBITRARY,
+            serde_json::to_value(SignArbitraryParams { signer, data })?,
+        ),
+    };
+
+    let request = SessionRequest {
+        id,
+        topic,
+        params: SessionRequestParams {
+            request: RequestArguments {
+                method: method.to_string(),
+                params,
+            },
+            chain_id: format!("{}:{}", WC_NAMESPACE, chain_id),
+        },
+    };
+
+    Ok(serde_json::to_string(&request)?)
+}
+
+/// Encodes a Borsh serialized WcSignTxResponseMsg into a JSON-RPC response payload
+#[wasm_bindgen]
+pub fn encode_wc_sign_tx_response(msg: &[u8]) -> Result<String, JsError> {
+    let WcSignTxResponseMsg { id, signed_txs } = WcSignTxResponseMsg::try_from_slice(msg)?;
+    let response = JsonRpcResponse {
+        id,
+        jsonrpc: JSON_RPC_VERSION.to_string(),
+        result: SignTxResult {
+            signed_txs: signed_txs.iter().map(hex::encode).collect(),
+        },
+    };
+
+    Ok(serde_json::to_string(&response)?)
+}
+
+/// Decodes a JSON-RPC `namada_signTx` response payload into a Borsh serialized WcSignTxResponseMsg
+#[wasm_bindgen]
+pub fn decode_wc_sign_tx_response(payload: &str) -> Result<Vec<u8>, JsError> {
+    let JsonRpcResponse { id, result, .. }: JsonRpcResponse<SignTxResult> =
+        serde_json::from_str(payload)?;
+    let signed_txs = decode_hex_list(result.signed_txs)?;
+
+    Ok(borsh::to_vec(&WcSignTxResponseMsg { id, signed_txs })?)
+}
+
+/// Encodes a Borsh serialized WcSignArbitraryResponseMsg into a JSON-RPC response payload
+#[wasm_bindgen]
+pub fn encode_wc_sign_arbitrary_response(msg: &[u8]) -> Result<String, JsError> {
+    let WcSignArbitraryResponseMsg {
+        id,
+        hash,
+        signature,
+    } = WcSignArbitraryResponseMsg::try_from_slice(msg)?;
+    let response = JsonRpcResponse {
+        id,
+        jsonrpc: JSON_RPC_VERSION.to_string(),
+        result: SignArbitraryResult { hash, signature },
+    };
+
+    Ok(serde_json::to_string(&response)?)
+}
+
+/// Decodes a JSON-RPC `namada_signArbitrary` response payload into a Borsh serialized
+/// WcSignArbitraryResponseMsg
+#[wasm_bindgen]
+pub fn decode_wc_sign_arbitrary_response(payload: &str) -> Result<Vec<u8>, JsError> {
+    let JsonRpcResponse { id, result, .. }: JsonRpcResponse<SignArbitraryResult> =
+        serde_json::from_str(payload)?;
+    let SignArbitraryResult { hash, signature } = result;
+
+    Ok(borsh::to_vec(&WcSignArbitraryResponseMsg {
+        id,
+        hash,
+        signature,
+    })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn can_round_trip_sign_arbitrary_request() {
+        let msg = WcSessionRequestMsg {
+            id: 1,
+            topic: String::from("topic"),
+            chain_id: String::from("namada-test.000000000000"),
+            signer: String::from("tnam1qpgk927uh2aqjs92yhycsh08n5yggvltn5nk92zp"),
+            request: WcRequestKind::SignArbitrary {
+                data: String::from("hello"),
+            },
+        };
+
+        let payload = encode_wc_session_request(&borsh::to_vec(&msg).unwrap()).unwrap();
+        assert!(payload.contains("\"chainId\":\"namada:namada-test.000000000000\""));
+
+        let decoded = decode_wc_session_request(&payload).unwrap();
+        assert_eq!(WcSessionRequestMsg::try_from_slice(&decoded).unwrap(), msg);
+    }
+
+    #[wasm_bindgen_test]
+    fn rejects_foreign_chain_namespace() {
+        assert!(chain_id_from_caip2("cosmos:cosmoshub-4").is_err());
+        assert!(chain_id_from_caip2("namada:").is_err());
+        assert_eq!(
+            chain_id_from_caip2("namada:namada-test.000000000000").unwrap(),
+            "namada-test.000000000000"
+        );
+    }
+}
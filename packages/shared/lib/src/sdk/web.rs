@@ -0,0 +1,2571 @@
+//! Bindings of the Sdk to JS
+//!
+//! The Sdk struct wraps NamadaImpl with the web or Node.js backends of the wallet, MASP params
+//! and storage, and exposes building, signing and broadcasting txs to JS.
+use super::audit_log::{AuditEntry, AuditLog, SignatureKind};
+use super::decode::{decode_msg, decode_tagged_msg, encode_tagged_msg};
+use super::error::{ArgContext, NamadaContext};
+use super::idempotency::{tx_fingerprint, IdempotencyGuard};
+use super::io::WebIo;
+use super::masp::build_cache::{self, BuildCache, BuildMsg, CachedBuild, Freshness, ShieldedBuild};
+use super::masp::signing_plan;
+use super::masp::verify::VerifyingKeys;
+use super::masp_estimate::Descriptions;
+use super::msg_migration::VersionedMsg;
+use super::prefixes::ChainPrefixes;
+use super::{
+    args, eth_bridge, ibc, masp, masp_build_error, mixed_transfer, proof_of_funds, rebalance,
+    safety, screening, sdk_args, sections, signature, signing_batch, sweep, templates, tx,
+    unshielding, wallet,
+};
+use crate::crash_report;
+use crate::query::{self, voter_role};
+use crate::rpc_client::{broadcast_tx_sync_any, HttpClient};
+use crate::utils::set_panic_hook;
+use crate::utils::to_bytes;
+use crate::utils::{to_js_bytes, to_js_result};
+use gloo_utils::format::JsValueSerdeExt;
+use js_sys::{Array, Function};
+use namada_sdk::address::{Address, MASP};
+use namada_sdk::args::InputAmount;
+use namada_sdk::borsh::{self, BorshDeserialize};
+use namada_sdk::eth_bridge::bridge_pool::build_bridge_pool_tx;
+use namada_sdk::hash::Hash;
+use namada_sdk::ibc::convert_masp_tx_to_ibc_memo;
+use namada_sdk::ibc::core::host::types::identifiers::{ChannelId, PortId};
+use namada_sdk::io::NamadaIo;
+use namada_sdk::key::{common, ed25519, SigScheme};
+use namada_sdk::masp::shielded_wallet::ShieldedApi;
+use namada_sdk::masp::{MaspTxId, ShieldedContext, ShieldedUtils};
+use namada_sdk::masp_primitives::sapling::redjubjub::Signature;
+use namada_sdk::masp_primitives::zip32::{self, DiversifierIndex, ExtendedFullViewingKey};
+use namada_sdk::proof_of_stake::Epoch;
+use namada_sdk::rpc::{self, query_epoch, query_masp_epoch, InnerTxResult};
+use namada_sdk::signing::SigningTxData;
+use namada_sdk::string_encoding::Format;
+use namada_sdk::tendermint_rpc::Url;
+use namada_sdk::token::{Amount, DenominatedAmount, NATIVE_MAX_DECIMAL_PLACES};
+use namada_sdk::tx::data::GasLimit;
+use namada_sdk::tx::{
+    build_batch, build_bond, build_change_consensus_key, build_claim_rewards, build_ibc_transfer,
+    build_init_account, build_redelegation, build_reveal_pk, build_shielded_transfer,
+    build_shielding_transfer, build_transparent_transfer, build_unbond, build_unshielding_transfer,
+    build_update_account, build_validator_commission_change, build_validator_metadata_change,
+    build_vote_proposal, build_withdraw,
+    data::{compute_inner_tx_hash, TxType},
+    either::Either,
+    gen_ibc_shielding_transfer, process_tx, Code, ProcessTxResponse, Section, Tx,
+};
+use namada_sdk::wallet::{Store, Wallet};
+use namada_sdk::{
+    ExtendedSpendingKey, ExtendedViewingKey, Namada, NamadaImpl, PaymentAddress, TransferTarget,
+};
+use rand::Rng;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::str::FromStr;
+use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
+
+/// Represents the Sdk public API.
+#[wasm_bindgen]
+pub struct Sdk {
+    namada: NamadaImpl<HttpClient, wallet::JSWalletUtils, masp::JSShieldedUtils, WebIo>,
+    rpc_url: String,
+    idempotency: IdempotencyGuard,
+    templates: templates::JSTemplateStorage,
+    audit_log: AuditLog,
+    /// MASP params to load on first shielded use, see defer_masp_params
+    masp_params_location: RefCell<Option<String>>,
+    /// Limits above which transfers require a confirmation, see set_amount_limits
+    amount_limits: RefCell<Option<safety::AmountLimits>>,
+    /// Screening of counterparties before signing, see set_screening_list
+    screening: RefCell<Option<Rc<screening::Screening>>>,
+    /// Validates shielded transfer amounts before building, see set_amount_validation
+    validate_amounts: Cell<bool>,
+    /// Proven shielded builds reused by retries, see masp::build_cache
+    build_cache: BuildCache<ShieldedBuild>,
+    /// Prefixes of addresses and keys of the chain, see set_chain_prefixes
+    prefixes: RefCell<ChainPrefixes>,
+}
+
+#[wasm_bindgen]
+/// Sdk mostly wraps the logic of the Sdk struct members, making it a part of public API.
+/// For more details, navigate to the corresponding modules.
+impl Sdk {
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: String, native_token: String, path_or_db_name: String) -> Self {
+        set_panic_hook();
+        let client: HttpClient = HttpClient::new(url.clone());
+        let wallet: Wallet<wallet::JSWalletUtils> = Wallet::new(
+            wallet::JSWalletUtils::new_utils(&path_or_db_name),
+            Store::default(),
+        );
+        let shielded_ctx: ShieldedContext<masp::JSShieldedUtils> = ShieldedContext::default();
+
+        let namada = NamadaImpl::native_new(
+            client,
+            wallet,
+            shielded_ctx.into(),
+            WebIo,
+            //NAM address
+            Address::from_str(&native_token).unwrap(),
+        );
+
+        Sdk {
+            namada,
+            rpc_url: url,
+            idempotency: IdempotencyGuard::default(),
+            templates: templates::JSTemplateStorage::new(&path_or_db_name),
+            audit_log: AuditLog::new(&path_or_db_name),
+            masp_params_location: RefCell::new(None),
+            amount_limits: RefCell::new(None),
+            screening: RefCell::new(None),
+            validate_amounts: Cell::new(false),
+            build_cache: BuildCache::default(),
+            prefixes: RefCell::new(ChainPrefixes::default()),
+        }
+    }
+
+    pub async fn has_masp_params() -> Result<JsValue, JsValue> {
+        let has = has_masp_params().await?;
+
+        Ok(js_sys::Boolean::from(has.as_bool().unwrap()).into())
+    }
+
+    pub async fn fetch_and_store_masp_params(url: Option<String>) -> Result<(), JsValue> {
+        fetch_and_store_masp_params(url).await?;
+        Ok(())
+    }
+
+    /// Loads MASP params right away
+    pub async fn load_masp_params(&self, path_or_db_name: JsValue) -> Result<(), JsValue> {
+        let path_or_db_name = path_or_db_name
+            .as_string()
+            .ok_or("Path or db name must be a string")?;
+        let mut shielded = self.namada.shielded_mut().await;
+        *shielded = self.masp_params_context(&path_or_db_name).await?;
+        self.masp_params_location.borrow_mut().take();
+
+        Ok(())
+    }
+
+    /// Records where to load MASP params from, without loading them. Params are loaded by the
+    /// first call that builds or verifies a shielded tx, so transparent-only users don't pay
+    /// for them at startup.
+    pub fn defer_masp_params(&self, path_or_db_name: String) {
+        *self.masp_params_location.borrow_mut() = Some(path_or_db_name);
+    }
+
+    /// Returns true if no MASP params are waiting to be loaded, see defer_masp_params
+    pub fn masp_params_loaded(&self) -> bool {
+        self.masp_params_location.borrow().is_none()
+    }
+
+    pub async fn add_spending_key(&self, xsk: String, alias: String) -> Result<(), JsError> {
+        let mut wallet = self.namada.wallet_mut().await;
+        wallet::add_spending_key(&mut wallet, self.native(&xsk), alias);
+        Ok(())
+    }
+
+    pub async fn add_viewing_key(&self, xvk: String, alias: String) {
+        let mut wallet = self.namada.wallet_mut().await;
+        wallet::add_viewing_key(&mut wallet, self.native(&xvk), alias)
+    }
+
+    pub async fn add_payment_address(&self, pa: String, alias: String) {
+        let mut wallet = self.namada.wallet_mut().await;
+        wallet::add_payment_address(&mut wallet, self.native(&pa), alias)
+    }
+
+    pub async fn add_default_payment_address(&self, xvk: String, alias: String) {
+        let mut wallet = self.namada.wallet_mut().await;
+        wallet::add_default_payment_address(&mut wallet, self.native(&xvk), alias)
+    }
+
+    pub async fn add_keypair(
+        &self,
+        secret_key: String,
+        alias: String,
+        password: Option<String>,
+    ) -> Result<(), JsError> {
+        let mut wallet = self.namada.wallet_mut().await;
+        wallet::add_keypair(&mut wallet, secret_key, alias, password);
+        Ok(())
+    }
+
+    pub async fn save_wallet(&self) -> Result<(), JsValue> {
+        let wallet = self.namada.wallet_mut().await;
+        wallet.save().map_err(JsError::from)?;
+
+        Ok(())
+    }
+
+    pub async fn load_wallet(&self) -> Result<(), JsValue> {
+        let mut wallet = self.namada.wallet_mut().await;
+        wallet.load().map_err(JsError::from)?;
+
+        Ok(())
+    }
+
+    /// Signs a tx built by the Sdk. The origin of the requesting dApp, if any, is recorded
+    /// hashed in the key audit log.
+    pub async fn sign_tx(
+        &self,
+        tx: Vec<u8>,
+        private_key: Option<String>,
+        chain_id: Option<String>,
+        origin: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("sign_tx");
+        let tx: tx::Tx = borsh::from_slice(&tx)?;
+        let mut namada_tx: Tx = borsh::from_slice(tx.tx_bytes())?;
+        self.screen(&namada_tx).await?;
+
+        // If chain_id is provided, validate this against value in Tx header
+        if let Some(c) = chain_id {
+            if c != namada_tx.header.chain_id.to_string() {
+                return Err(JsError::new(&format!(
+                    "chain_id {} does not match Tx header chain_id {}",
+                    &c,
+                    namada_tx.header.chain_id.as_str()
+                )));
+            }
+        }
+
+        let signing_keys = match private_key.clone() {
+            Some(private_key) => vec![common::SecretKey::Ed25519(ed25519::SecretKey::from_str(
+                &private_key,
+            )?)],
+            // If no private key is provided, we assume masp source and return empty vec
+            None => vec![],
+        };
+
+        for signing_tx_data in tx.signing_tx_data()? {
+            if let Some(account_public_keys_map) = signing_tx_data.account_public_keys_map.clone() {
+                // We only sign the raw header for transfers from transparent source
+                if !signing_keys.is_empty() {
+                    // Sign the raw header
+                    namada_tx.sign_raw(
+                        signing_keys.clone(),
+                        account_public_keys_map,
+                        signing_tx_data.owner.clone(),
+                    );
+                }
+            }
+        }
+
+        // The key is either passed private key for transparent sources or the disposable signing
+        // key for shielded sources
+        let key = signing_keys[0].clone();
+
+        // Sign the fee header
+        namada_tx.sign_wrapper(key);
+
+        self.audit_log.record(
+            AuditEntry::for_tx(SignatureKind::Tx, &namada_tx, &signing_keys)
+                .with_origin(origin.as_deref()),
+        );
+
+        Ok(to_js_bytes(&borsh::to_vec(&namada_tx)?))
+    }
+
+    /// Sets the prefixes of the chain, e.g. `{ address: "tfork", coinType: 1 }`, for forks and
+    /// testnets with other prefixes than Namada. Addresses and keys with the prefixes are
+    /// accepted by the builders and returned by the Sdk. Prefixes left out are those of Namada,
+    /// undefined resets all of them.
+    pub fn set_chain_prefixes(&self, prefixes: JsValue) -> Result<(), JsError> {
+        *self.prefixes.borrow_mut() = ChainPrefixes::from_js(prefixes)?;
+        Ok(())
+    }
+
+    /// Returns the prefixes of the chain, see ChainPrefixes
+    pub fn chain_prefixes(&self) -> Result<JsValue, JsError> {
+        to_js_result(self.prefixes())
+    }
+
+    /// Refuses to sign txs with any of the addresses as a counterparty, or removes the
+    /// screening if empty. Replaces a screening callback.
+    pub fn set_screening_list(&self, blocked: Vec<String>) {
+        let screening = (!blocked.is_empty()).then(|| {
+            Rc::new(screening::Screening::Blocklist(
+                blocked.into_iter().collect(),
+            ))
+        });
+        *self.screening.borrow_mut() = screening;
+    }
+
+    /// Sets a callback `(counterparties: Counterparty[]) => boolean | Promise<boolean>` called
+    /// before signing, which has to return true for the tx to be signed, or removes it.
+    /// Replaces a screening list.
+    pub fn set_screening_callback(&self, callback: Option<Function>) {
+        *self.screening.borrow_mut() =
+            callback.map(|callback| Rc::new(screening::Screening::Callback(callback)));
+    }
+
+    /// Returns the counterparties of a Borsh serialized Namada Tx, as passed to the screening
+    /// callback
+    pub async fn tx_counterparties(&self, tx_bytes: &[u8]) -> Result<JsValue, JsError> {
+        let tx = Tx::try_from_slice(tx_bytes)?;
+        let wasm_hashes = query::wasm_hashes(self.namada.client()).await;
+        let counterparties = screening::wasm_counterparties(&tx, &wasm_hashes)?;
+        to_js_result(screening::with_prefixes(counterparties, &self.prefixes()))
+    }
+
+    /// Returns all signatures produced by the Sdk, oldest first
+    pub async fn key_audit_log(&self) -> Result<JsValue, JsError> {
+        to_js_result(self.audit_log.export().await?)
+    }
+
+    /// Replaces the fee of a broadcast but unconfirmed tx and re-signs the wrapper. Returns the
+    /// replacement tx bytes, the updated WrapperTxMsg to broadcast it with, and the wrapper
+    /// hashes of the replaced and the replacement tx. The original tx is not cancelled, see
+    /// tx::replace_wrapper_fee.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_bytes` - Signed tx bytes
+    /// * `wrapper_tx_msg` - Borsh serialized WrapperTxMsg the tx was built with
+    /// * `fee_amount` - New fee amount per gas unit
+    /// * `gas_limit` - New gas limit
+    /// * `private_key` - Private key of the fee payer
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the fee per gas unit is not strictly higher or the gas limit would
+    /// be lowered
+    pub async fn replace_tx_fee(
+        &self,
+        tx_bytes: &[u8],
+        wrapper_tx_msg: &[u8],
+        fee_amount: String,
+        gas_limit: String,
+        private_key: String,
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("replace_tx_fee");
+        let mut namada_tx = Tx::try_from_slice(tx_bytes)?;
+        self.screen(&namada_tx).await?;
+        let replaced_hash = namada_tx
+            .wrapper_hash()
+            .ok_or_else(|| JsError::new("Only wrapper txs can be replaced"))?;
+
+        tx::replace_wrapper_fee(
+            &mut namada_tx,
+            DenominatedAmount::from_str(&fee_amount)?,
+            GasLimit::from_str(&gas_limit)?,
+        )?;
+        let key = common::SecretKey::Ed25519(ed25519::SecretKey::from_str(&private_key)?);
+        namada_tx.sign_wrapper(key.clone());
+        self.audit_log.record(AuditEntry::for_tx(
+            SignatureKind::FeeReplacement,
+            &namada_tx,
+            &[key],
+        ));
+
+        let hash = namada_tx
+            .wrapper_hash()
+            .ok_or_else(|| JsError::new("Only wrapper txs can be replaced"))?;
+        let wrapper_tx_msg =
+            args::WrapperTxMsg::decode(wrapper_tx_msg)?.with_fee(fee_amount, gas_limit);
+
+        to_js_result((
+            borsh::to_vec(&namada_tx)?,
+            encode_tagged_msg(&wrapper_tx_msg),
+            replaced_hash.to_string(),
+            hash.to_string(),
+        ))
+    }
+
+    /// Broadcasts a signed tx to the Sdk RPC endpoint and additional endpoints at the same
+    /// time, returning the url of the first endpoint accepting the tx and the tx hash. Unlike
+    /// process_tx, it doesn't wait for the tx to be applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_bytes` - Signed tx bytes
+    /// * `urls` - Additional RPC endpoints
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if none of the endpoints accepts the tx
+    pub async fn broadcast_tx_to_endpoints(
+        &self,
+        tx_bytes: &[u8],
+        urls: Box<[JsValue]>,
+    ) -> Result<JsValue, JsError> {
+        let mut endpoints = vec![self.rpc_url.clone()];
+        for url in urls.iter().filter_map(|url| url.as_string()) {
+            if !endpoints.contains(&url) {
+                endpoints.push(url);
+            }
+        }
+
+        let accepted = broadcast_tx_sync_any(endpoints, tx_bytes.to_vec()).await?;
+        to_js_result(accepted)
+    }
+
+    /// Releases an idempotency key, so that it can be used again before the end of the
+    /// idempotency window. Only to be called once the tx submitted with it is known not to be
+    /// applied, e.g. after it expired.
+    pub fn release_idempotency_key(&self, key: String) {
+        self.idempotency.release(&key);
+    }
+
+    // Broadcast Tx
+    // Txs submitted with an idempotency key are refused if the key was already used
+    // within the idempotency window
+    pub async fn process_tx(
+        &self,
+        tx_bytes: &[u8],
+        tx_msg: &[u8],
+        idempotency_key: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        let args = args::tx_args_from_slice(tx_msg, &self.prefixes())?;
+        let tx = Tx::try_from_slice(tx_bytes)?;
+        let cmts = tx.commitments().clone();
+        let wrapper_hash = tx.wrapper_hash();
+
+        if let Some(key) = &idempotency_key {
+            self.idempotency
+                .acquire(key, tx_fingerprint(&tx), js_sys::Date::now())?;
+        }
+        let resp = match process_tx(&self.namada, &args, tx.clone()).await {
+            Ok(resp) => resp,
+            Err(e) => {
+                // The tx may still have reached the mempool, e.g. on a timeout, so the key is
+                // kept until the tx expires or release_idempotency_key is called
+                if let (Some(key), Some(expiration)) = (&idempotency_key, tx.header.expiration) {
+                    self.idempotency
+                        .keep_until(key, expiration.0.timestamp_millis() as f64);
+                }
+                return Err(e.into());
+            }
+        };
+
+        let mut batch_tx_results: Vec<tx::BatchTxResult> = vec![];
+
+        // Collect results and return
+        match resp {
+            ProcessTxResponse::Applied(tx_response) => {
+                let code = tx_response.code.to_string();
+                let gas_used = tx_response.gas_used.to_string();
+                let height = tx_response.height.to_string();
+                let info = tx_response.info.to_string();
+                let log = tx_response.log.to_string();
+
+                for cmt in cmts {
+                    let hash = compute_inner_tx_hash(wrapper_hash.as_ref(), Either::Right(&cmt));
+
+                    if let Some(InnerTxResult::Success(_)) = tx_response.batch_result().get(&hash) {
+                        batch_tx_results.push(tx::BatchTxResult::new(hash.to_string(), true));
+                    } else {
+                        batch_tx_results.push(tx::BatchTxResult::new(hash.to_string(), false));
+                    }
+                }
+
+                let fee = match &tx.header().tx_type {
+                    TxType::Wrapper(wrapper) => Some(tx::FeeAccounting::new(
+                        &wrapper.fee.token,
+                        wrapper.fee.amount_per_gas_unit,
+                        u64::from(wrapper.gas_limit),
+                        u64::from(tx_response.gas_used),
+                    )?),
+                    _ => None,
+                };
+
+                let response = tx::TxResponse::new(
+                    code,
+                    batch_tx_results,
+                    gas_used,
+                    wrapper_hash.unwrap().to_string(),
+                    height,
+                    info,
+                    log,
+                    fee,
+                );
+                Ok(to_js_bytes(&borsh::to_vec(&response)?))
+            }
+            _ => Err(JsError::new(&format!(
+                "Tx not applied: {}",
+                &wrapper_hash.unwrap().to_string()
+            ))),
+        }
+    }
+
+    /// Build a batch Tx from built transactions, passed as Uint8Arrays, and return the bytes
+    pub fn build_batch(txs: Box<[JsValue]>) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_batch");
+        let mut built_txs: Vec<tx::Tx> = vec![];
+
+        for bytes in txs.iter() {
+            let tx: tx::Tx = borsh::from_slice(&to_bytes(bytes.clone()))?;
+            built_txs.push(tx);
+        }
+
+        // Get wrapper args
+        let first_tx = built_txs
+            .first()
+            .expect("At least one Tx is required for building batches!");
+
+        let args = first_tx.args();
+
+        let mut txs: Vec<(Tx, SigningTxData)> = vec![];
+
+        // Iterate through provided tx::Tx and deserialize bytes to Namada Tx
+        for built_tx in built_txs.into_iter() {
+            let signing_tx_data = built_tx.signing_tx_data()?;
+            let tx: Tx = Tx::try_from_slice(built_tx.tx_bytes())?;
+            let first_signing_data = signing_tx_data
+                .first()
+                .expect("At least one signing data should be present on a Tx");
+
+            txs.push((tx, first_signing_data.to_owned()));
+        }
+
+        let (tx, signing_data) = build_batch(txs.clone())?;
+
+        let tx = tx::Tx::new(tx, &borsh::to_vec(&args)?, signing_data)?;
+        Ok(to_js_bytes(&borsh::to_vec(&tx)?))
+    }
+
+    // Append signatures and return tx bytes
+    pub async fn append_signature(
+        &self,
+        tx_bytes: &[u8],
+        sig_msg_bytes: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let mut tx: Tx = Tx::try_from_slice(tx_bytes)?;
+        self.screen(&tx).await?;
+        let sig_msg = decode_msg::<signature::SignatureMsg>(sig_msg_bytes)?;
+        signature::append_signature(&mut tx, sig_msg)?;
+
+        Ok(to_js_bytes(&borsh::to_vec(&tx)?))
+    }
+
+    /// Applies the signatures returned by a hardware wallet to a batch of built txs, see
+    /// plan_batch_signing. Returns the signed Namada txs bytes in the order of the batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `txs` - Borsh serialized tx::Tx, as passed to plan_batch_signing
+    /// * `hashes` - Wrapper hashes of the signed txs
+    /// * `signatures` - Borsh serialized SignatureMsg of each hash
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if a signature belongs to no tx of the batch, a tx was not signed or the
+    /// screening rejects one of them
+    pub async fn apply_batch_signatures(
+        &self,
+        txs: Box<[JsValue]>,
+        hashes: Vec<String>,
+        signatures: Box<[JsValue]>,
+    ) -> Result<JsValue, JsError> {
+        let signed = signing_batch::apply_batch_signatures(&txs, hashes, &signatures)?;
+        for tx in &signed {
+            self.screen(tx).await?;
+        }
+
+        let signed = signed
+            .iter()
+            .map(|tx| Ok(to_js_bytes(&borsh::to_vec(tx)?)))
+            .collect::<Result<Array, JsError>>()?;
+        Ok(signed.into())
+    }
+
+    /// Attaches a pre-built MASP transaction to a built, unsigned transfer and returns the
+    /// updated tx bytes, so proving can happen in a separate worker or process.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Borsh serialized tx::Tx of a single transfer
+    /// * `shielded_section_hash` - Borsh serialized MaspTxId of the MASP transaction
+    /// * `masp_section` - Borsh serialized MASP transaction
+    pub fn attach_masp_section(
+        tx: &[u8],
+        shielded_section_hash: &[u8],
+        masp_section: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let built_tx: tx::Tx = borsh::from_slice(tx)?;
+        let mut namada_tx: Tx = borsh::from_slice(built_tx.tx_bytes())?;
+        tx::attach_masp_section(&mut namada_tx, shielded_section_hash, masp_section)?;
+
+        let tx = tx::Tx::new(
+            namada_tx,
+            &borsh::to_vec(&built_tx.args())?,
+            built_tx.signing_tx_data()?,
+        )?;
+        Ok(to_js_bytes(&borsh::to_vec(&tx)?))
+    }
+
+    /// Returns the kind and hash of every section of a built tx, in order, see sections module
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Borsh serialized tx::Tx
+    pub fn tx_sections(tx: &[u8]) -> Result<JsValue, JsError> {
+        let built_tx: tx::Tx = borsh::from_slice(tx)?;
+        let namada_tx: Tx = borsh::from_slice(built_tx.tx_bytes())?;
+        let sections: Vec<(&str, String)> = sections::list_sections(&namada_tx)
+            .into_iter()
+            .map(|(kind, hash)| (kind, hash.to_string()))
+            .collect();
+
+        to_js_result(sections)
+    }
+
+    /// Returns the signing plans of the MASP transactions of a built tx, so that signers
+    /// without the MASP builder can authorize its spends, see signing_plan module
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Borsh serialized tx::Tx
+    pub fn masp_signing_plans(tx: &[u8]) -> Result<JsValue, JsError> {
+        let built_tx: tx::Tx = borsh::from_slice(tx)?;
+        let namada_tx: Tx = borsh::from_slice(built_tx.tx_bytes())?;
+
+        to_js_result(signing_plan::signing_plans(&namada_tx)?)
+    }
+
+    /// Adds an extra data section to a built tx, e.g. a document referenced by a proposal.
+    /// Returns the updated tx bytes and the hash of the section. The wrapper signature, if
+    /// any, is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Borsh serialized tx::Tx
+    /// * `data` - Content of the section
+    pub fn add_tx_extra_data(tx: &[u8], data: Vec<u8>) -> Result<JsValue, JsError> {
+        Self::update_tx_sections(tx, |namada_tx| {
+            let section = Section::ExtraData(Code::new(data, None));
+            Ok(sections::add_section(namada_tx, section)?.to_string())
+        })
+    }
+
+    /// Removes a section of a built tx, along with the signatures covering it. Returns the
+    /// updated tx bytes and the hash of the removed section.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Borsh serialized tx::Tx
+    /// * `section_hash` - Hash of the section, as returned by tx_sections
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if there's no such section or it's committed to by an inner tx
+    pub fn remove_tx_section(tx: &[u8], section_hash: String) -> Result<JsValue, JsError> {
+        let hash = Hash::from_str(&section_hash).arg(
+            "remove tx section",
+            "section hash",
+            &section_hash,
+        )?;
+        Self::update_tx_sections(tx, |namada_tx| {
+            sections::remove_section(namada_tx, hash)?;
+            Ok(section_hash)
+        })
+    }
+
+    /// Builds a transfer of any kind from a Borsh serialized TransferKindMsg, dispatching to
+    /// the builder of the kind. The builders of single kinds are kept for the old message
+    /// layouts.
+    /// Builds a transfer of any kind. `retry` reuses the proven build of a previous call with
+    /// the same msgs for shielded and unshielding transfers, see build_shielded_transfer.
+    pub async fn build_transfer(
+        &self,
+        transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+        retry: Option<bool>,
+    ) -> Result<JsValue, JsError> {
+        match decode_tagged_msg::<args::TransferKindMsg>(transfer_msg)? {
+            args::TransferKindMsg::Transparent(msg) => {
+                self.build_transparent_transfer(&encode_tagged_msg(&msg), wrapper_tx_msg)
+                    .await
+            }
+            args::TransferKindMsg::Shielded(msg) => {
+                self.build_shielded_transfer(&encode_tagged_msg(&msg), wrapper_tx_msg, retry)
+                    .await
+            }
+            args::TransferKindMsg::Shielding(msg) => {
+                self.build_shielding_transfer(&encode_tagged_msg(&msg), wrapper_tx_msg)
+                    .await
+            }
+            args::TransferKindMsg::Unshielding(msg) => {
+                self.build_unshielding_transfer(&encode_tagged_msg(&msg), wrapper_tx_msg, retry)
+                    .await
+            }
+        }
+    }
+
+    pub async fn build_transparent_transfer(
+        &self,
+        transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_transparent_transfer");
+        let mut args =
+            args::transparent_transfer_tx_args(transfer_msg, wrapper_tx_msg, &self.prefixes())?;
+        let (tx, signing_data) = build_transparent_transfer(&self.namada, &mut args)
+            .await
+            .context("build transparent transfer")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    /// Builds a single transfer tx draining the balances of the given tokens held by a
+    /// transparent address to a target. When the address pays the fee, the fee is left out
+    /// of the fee token balance. Returns the serialized tx and the swept tokens and amounts.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Swept transparent address
+    /// * `target` - Target address
+    /// * `tokens` - Tokens to sweep, tokens without balance are skipped
+    /// * `wrapper_tx_msg` - Borsh serialized WrapperTxMsg
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if no token has a balance, the fee isn't covered or the tx can't be
+    /// built
+    pub async fn build_transparent_sweep(
+        &self,
+        source: String,
+        target: String,
+        tokens: Vec<String>,
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_transparent_sweep");
+        let prefixes = self.prefixes();
+        let (source, target, mut args) =
+            args::transparent_sweep_tx_args(&source, &target, wrapper_tx_msg, &prefixes)?;
+        let client = self.namada.client();
+
+        let mut balances = vec![];
+        for token in tokens {
+            let token = Address::from_str(&prefixes.native(&token)).arg(
+                "transparent sweep",
+                "token",
+                &token,
+            )?;
+            let balance = rpc::get_token_balance(client, &token, &source, None).await?;
+            balances.push((token, balance));
+        }
+        let fee_payer = args
+            .tx
+            .wrapper_fee_payer
+            .as_ref()
+            .or_else(|| args.tx.signing_keys.first())
+            .map(Address::from);
+        let fee = if fee_payer.as_ref() == Some(&source) {
+            let fee = self.raw_fee(&args.tx).await?;
+            Some((args.tx.fee_token.clone(), fee))
+        } else {
+            None
+        };
+
+        let mut swept = vec![];
+        for (token, amount) in sweep::plan_transparent_sweep(&balances, fee)? {
+            let denom = rpc::query_denom(client, &token)
+                .await
+                .ok_or_else(|| JsError::new(&format!("Unknown token: {}", token)))?;
+            let amount = DenominatedAmount::new(amount, denom);
+            swept.push((prefixes.configured(&token.to_string()), amount.to_string()));
+            args.data.push(sdk_args::transparent_transfer_data(
+                source.clone(),
+                target.clone(),
+                token,
+                InputAmount::Validated(amount),
+            ));
+        }
+
+        let (tx, signing_data) = build_transparent_transfer(&self.namada, &mut args)
+            .await
+            .context("build transparent sweep")?;
+        let tx = tx::Tx::new(tx, wrapper_tx_msg, vec![signing_data])?;
+
+        to_js_result((borsh::to_vec(&tx)?, swept))
+    }
+
+    /// Enables validating the amounts of shielded, shielding and unshielding transfers against
+    /// the denomination of their tokens before building. Amounts with more decimal places
+    /// than the token then fail right away, instead of after generating the proofs.
+    pub fn set_amount_validation(&self, enabled: bool) {
+        self.validate_amounts.set(enabled);
+    }
+
+    /// Validates an amount against the denomination of a token. Returns the amount with the
+    /// decimal places of the token.
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the token is unknown, or the amount has more decimal places than it
+    pub async fn validate_amount(&self, token: String, amount: String) -> Result<String, JsError> {
+        let token = Address::from_str(&self.prefixes().native(&token)).arg(
+            "validate amount",
+            "token",
+            &token,
+        )?;
+        let amount =
+            DenominatedAmount::from_str(&amount).arg("validate amount", "amount", &amount)?;
+        match self
+            .validated_amount(&token, InputAmount::Unvalidated(amount))
+            .await?
+        {
+            InputAmount::Validated(amount) | InputAmount::Unvalidated(amount) => {
+                Ok(amount.to_string())
+            }
+        }
+    }
+
+    /// Sets the limits above which transfers built with build_transfer_with_amount_check
+    /// require an explicit confirmation, or removes them if null
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - `{ maxBalanceFraction?: number, thresholds?: { [token]: amount } }`
+    pub fn set_amount_limits(&self, limits: JsValue) -> Result<(), JsError> {
+        let limits: Option<safety::AmountLimits> = limits.into_serde()?;
+        if let Some(limits) = &limits {
+            limits.validate()?;
+        }
+        *self.amount_limits.borrow_mut() = limits;
+        Ok(())
+    }
+
+    /// Builds a transfer like build_transfer and checks the amounts sent against the limits
+    /// set with set_amount_limits. Returns a tuple of the Borsh serialized Tx and an
+    /// AmountCheck, which never requires a confirmation if no limits are set.
+    pub async fn build_transfer_with_amount_check(
+        &self,
+        transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let built = self
+            .build_transfer(transfer_msg, wrapper_tx_msg, None)
+            .await?;
+        let bytes = to_bytes(built);
+
+        let limits = self.amount_limits.borrow().clone();
+        let check = match limits {
+            Some(limits) => {
+                let built_tx: tx::Tx = borsh::from_slice(&bytes)?;
+                let namada_tx = Tx::try_from_slice(built_tx.tx_bytes())?;
+                safety::check_amounts(self.namada.client(), &limits, &namada_tx).await?
+            }
+            None => safety::AmountCheck::default(),
+        };
+
+        to_js_result((bytes, check))
+    }
+
+    /// Saves a transfer template, replacing a template with the same id
+    ///
+    /// # Arguments
+    ///
+    /// * `template_msg` - Borsh serialized TransferTemplateMsg
+    pub async fn save_transfer_template(&self, template_msg: &[u8]) -> Result<(), JsError> {
+        let template = templates::TransferTemplateMsg::try_from_slice(template_msg)?;
+        template.validate(&self.prefixes())?;
+
+        let mut templates = self.templates.load().await?;
+        templates.insert(template.id.clone(), template);
+        self.templates.save(&templates).await
+    }
+
+    pub async fn remove_transfer_template(&self, id: String) -> Result<(), JsError> {
+        let mut templates = self.templates.load().await?;
+        if templates.remove(&id).is_some() {
+            self.templates.save(&templates).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns all saved templates
+    pub async fn transfer_templates(&self) -> Result<JsValue, JsError> {
+        let templates: Vec<templates::TransferTemplateMsg> =
+            self.templates.load().await?.into_values().collect();
+        to_js_result(templates)
+    }
+
+    /// Instantiates a saved template into a fresh transparent transfer, resolving its amount
+    /// formula against the current balance of the source
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Template id
+    /// * `wrapper_tx_msg` - Borsh serialized WrapperTxMsg
+    pub async fn build_transfer_from_template(
+        &self,
+        id: String,
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_transfer_from_template");
+        let template = self
+            .templates
+            .load()
+            .await?
+            .remove(&id)
+            .ok_or_else(|| JsError::new(&format!("Unknown template: {}", id)))?;
+
+        let prefixes = self.prefixes();
+        let source = Address::from_str(&prefixes.native(&template.source))?;
+        let token = Address::from_str(&prefixes.native(&template.token))?;
+        let balance = rpc::get_token_balance(self.namada.client(), &token, &source, None).await?;
+        let denom = rpc::query_denom(self.namada.client(), &token)
+            .await
+            .ok_or_else(|| JsError::new(&format!("Unknown token: {}", token)))?;
+        let amount =
+            templates::AmountFormula::from_str(&template.amount)?.resolve(balance, denom.0)?;
+
+        let mut args = args::template_transfer_tx_args(
+            &template.source,
+            &template.target,
+            &template.token,
+            amount,
+            template.memo,
+            wrapper_tx_msg,
+            &prefixes,
+        )?;
+        let (tx, signing_data) = build_transparent_transfer(&self.namada, &mut args)
+            .await
+            .context("build transparent transfer")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    /// Builds a shielded transfer. Proven builds are cached, and reused by a call with the
+    /// same msgs and `retry` set, e.g. after a failed broadcast, while the chain still accepts
+    /// them, see masp::build_cache.
+    pub async fn build_shielded_transfer(
+        &self,
+        shielded_transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+        retry: Option<bool>,
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_shielded_transfer");
+        self.ensure_masp_params().await?;
+        let key = self
+            .build_key(&[shielded_transfer_msg, wrapper_tx_msg])
+            .await?;
+        if let Some(tx) = self.cached_build(&key, retry).await? {
+            return Ok(to_js_bytes(&borsh::to_vec(&tx)?));
+        }
+
+        let args = args::shielded_transfer_tx_args(
+            shielded_transfer_msg,
+            wrapper_tx_msg,
+            &self.prefixes(),
+        )?;
+        let tx = self
+            .build_shielded_transfer_args(args, wrapper_tx_msg)
+            .await?;
+        let msg = BuildMsg::Shielded(shielded_transfer_msg.to_vec());
+        self.cache_build(key, msg, wrapper_tx_msg, &tx).await?;
+
+        Ok(to_js_bytes(&borsh::to_vec(&tx)?))
+    }
+
+    /// Drops the proven shielded builds kept for retries
+    pub fn clear_build_cache(&self) {
+        self.build_cache.clear();
+    }
+
+    /// Checks that a shielded or unshielding transfer built by this Sdk is still accepted by
+    /// the chain before signing it, as the user may take a while to approve it. If the MASP
+    /// epoch changed, an anchor is unknown or the wrapper expired, the tx is rebuilt from the
+    /// same msgs, which may select other notes, and `on_rebuild` is called first with the
+    /// reason, "epoch", "anchor" or "expired", so the UI can tell about the delay. Returns the
+    /// serialized tx to sign.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Serialized tx as returned by the build functions
+    /// * `on_rebuild` - Optional callback `(reason: string) => void`
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the tx is unknown to the build cache, its notes were spent since or
+    /// rebuilding fails
+    pub async fn refresh_shielded_tx(
+        &self,
+        tx: &[u8],
+        on_rebuild: Option<Function>,
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("refresh_shielded_tx");
+        let built: tx::Tx = borsh::from_slice(tx)?;
+        let (key, build) = self
+            .build_cache
+            .find(|cached| cached.tx.tx_bytes() == built.tx_bytes())
+            .ok_or_else(|| JsError::new("Tx was not built by this Sdk or is no longer cached"))?;
+
+        let freshness = build.freshness(self.namada.client()).await?;
+        match freshness {
+            Freshness::Fresh => return Ok(to_js_bytes(tx)),
+            Freshness::Spent => {
+                self.build_cache.remove(&key);
+                return Err(JsError::new(
+                    "Notes of the tx were spent since it was built, sync before building again",
+                ));
+            }
+            Freshness::StaleEpoch | Freshness::StaleAnchor | Freshness::Expired => {}
+        }
+
+        self.build_cache.remove(&key);
+        if let Some(on_rebuild) = on_rebuild {
+            let _ = on_rebuild.call1(&JsValue::NULL, &JsValue::from_str(freshness.as_str()));
+        }
+        let ShieldedBuild {
+            msg,
+            wrapper_tx_msg,
+            ..
+        } = build.value;
+        match msg {
+            BuildMsg::Shielded(msg) => {
+                self.build_shielded_transfer(&msg, &wrapper_tx_msg, None)
+                    .await
+            }
+            BuildMsg::Unshielding(msg) => {
+                self.build_unshielding_transfer(&msg, &wrapper_tx_msg, None)
+                    .await
+            }
+        }
+    }
+
+    /// Returns the numbers of spends, converts and outputs of the MASP transactions of a tx
+    /// built by the Sdk, see masp_estimate::Descriptions, e.g. to estimate a rebuild of the
+    /// same msgs with Query.estimate_masp_tx
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Serialized tx as returned by the build functions
+    pub fn masp_descriptions(&self, tx: &[u8]) -> Result<JsValue, JsError> {
+        let built: tx::Tx = borsh::from_slice(tx)?;
+        let tx = Tx::try_from_slice(built.tx_bytes())?;
+        to_js_result(Descriptions::of_tx(&tx))
+    }
+
+    /// Builds a shielded transfer sending the change of each source to a freshly derived
+    /// diversified payment address instead of the default one. Returns the serialized tx
+    /// together with the derived change addresses.
+    ///
+    /// All of the notes of the transferred tokens are spent, so the change is an explicit
+    /// output to the derived address, in the same MASP transaction. All targets have to be
+    /// payment addresses.
+    pub async fn build_shielded_transfer_with_change_rotation(
+        &self,
+        shielded_transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_shielded_transfer_with_change_rotation");
+        self.ensure_masp_params().await?;
+        let mut args = args::shielded_transfer_tx_args(
+            shielded_transfer_msg,
+            wrapper_tx_msg,
+            &self.prefixes(),
+        )?;
+        let change_addresses = self.rotate_change(&mut args).await?;
+        let tx = self
+            .build_shielded_transfer_args(args, wrapper_tx_msg)
+            .await?;
+
+        to_js_result((borsh::to_vec(&tx)?, change_addresses))
+    }
+
+    /// Builds an unshielding transfer, reusing a cached build on retries, see
+    /// build_shielded_transfer
+    pub async fn build_unshielding_transfer(
+        &self,
+        unshielding_transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+        retry: Option<bool>,
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_unshielding_transfer");
+        self.ensure_masp_params().await?;
+        let key = self
+            .build_key(&[unshielding_transfer_msg, wrapper_tx_msg])
+            .await?;
+        if let Some(tx) = self.cached_build(&key, retry).await? {
+            return Ok(to_js_bytes(&borsh::to_vec(&tx)?));
+        }
+
+        let mut args = args::unshielding_transfer_tx_args(
+            unshielding_transfer_msg,
+            wrapper_tx_msg,
+            &self.prefixes(),
+        )?;
+        if self.validate_amounts.get() {
+            self.validate_amounts(args.data.iter_mut().map(|d| (&d.token, &mut d.amount)))
+                .await?;
+        }
+        self.validate_unshielding(&args).await?;
+        let requested = self
+            .requested_amounts(&args::ShieldedTransferArgs {
+                shielded: None,
+                unshielding: vec![args.clone()],
+            })
+            .await?;
+        let (tx, signing_data) = match build_unshielding_transfer(&self.namada, &mut args)
+            .await
+            .context("build unshielding transfer")
+        {
+            Ok(tx) => tx,
+            Err(e) => return Err(self.diagnose_build_error(requested, e.into()).await),
+        };
+        self.audit_log
+            .record(AuditEntry::for_masp(&tx, &unshielding_sources(&args)));
+        let tx = tx::Tx::new(tx, wrapper_tx_msg, vec![signing_data])?;
+        let msg = BuildMsg::Unshielding(unshielding_transfer_msg.to_vec());
+        self.cache_build(key, msg, wrapper_tx_msg, &tx).await?;
+
+        Ok(to_js_bytes(&borsh::to_vec(&tx)?))
+    }
+
+    /// Computes the maximum amount of a token that can be unshielded from a spending key,
+    /// after converting all notes to the current MASP epoch and deducting the fees unshielded
+    /// from the same key. Returns a denominated amount.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_unshielding_transfer_msg` - Borsh serialized MaxUnshieldingTransferMsg
+    /// * `wrapper_tx_msg` - Borsh serialized WrapperTxMsg
+    pub async fn compute_max_unshielding_amount(
+        &self,
+        max_unshielding_transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<String, JsError> {
+        let _operation = crash_report::operation("compute_max_unshielding_amount");
+        self.ensure_masp_params().await?;
+        let args = args::max_unshielding_transfer_tx_args(
+            max_unshielding_transfer_msg,
+            wrapper_tx_msg,
+            &self.prefixes(),
+        )?;
+        let amount = self.max_unshielding_amount(&args).await?;
+
+        Ok(amount.to_string())
+    }
+
+    /// Builds an unshielding transfer of the maximum spendable amount of a token, see
+    /// compute_max_unshielding_amount
+    pub async fn build_max_unshielding_transfer(
+        &self,
+        max_unshielding_transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_max_unshielding_transfer");
+        self.ensure_masp_params().await?;
+        let mut args = args::max_unshielding_transfer_tx_args(
+            max_unshielding_transfer_msg,
+            wrapper_tx_msg,
+            &self.prefixes(),
+        )?;
+        let amount = self.max_unshielding_amount(&args).await?;
+        for data in args.data.iter_mut() {
+            data.amount = InputAmount::Validated(amount);
+        }
+
+        let (tx, signing_data) = build_unshielding_transfer(&self.namada, &mut args).await?;
+        self.audit_log
+            .record(AuditEntry::for_masp(&tx, &unshielding_sources(&args)));
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    /// Builds the next tx sweeping the shielded balance of a compromised spending key to a
+    /// safe target, with the fees unshielded from the swept key. Returns the serialized tx,
+    /// the swept tokens and amounts, and the number of tokens left for further sweeps, to be
+    /// built once the tx is applied and the key synced again. See sweep module for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `xsk` - Extended spending key to sweep
+    /// * `target` - Payment address or transparent address receiving the funds
+    /// * `wrapper_tx_msg` - Borsh serialized WrapperTxMsg
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the balance is empty or doesn't cover the fees, or the tx can't
+    /// be built
+    pub async fn build_emergency_sweep(
+        &self,
+        xsk: String,
+        target: String,
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_emergency_sweep");
+        self.ensure_masp_params().await?;
+        let prefixes = self.prefixes();
+        let args = args::sweep_tx_args(&xsk, &target, wrapper_tx_msg, &prefixes)?;
+        let xfvk =
+            ExtendedFullViewingKey::from(&zip32::ExtendedSpendingKey::from(args.source.clone()));
+        let balances = self.shielded_balances(&xfvk).await?;
+        let fee = self.raw_fee(&args.tx).await?;
+        let plan = sweep::plan_sweep(&balances, (args.tx.fee_token.clone(), fee))?;
+
+        let mut swept = vec![];
+        for (token, amount) in plan.swept {
+            let denom = rpc::query_denom(self.namada.client(), &token)
+                .await
+                .ok_or_else(|| JsError::new(&format!("Unknown token: {}", token)))?;
+            swept.push((token, DenominatedAmount::new(amount, denom)));
+        }
+        let tokens: Vec<(String, String)> = swept
+            .iter()
+            .map(|(token, amount)| (prefixes.configured(&token.to_string()), amount.to_string()))
+            .collect();
+        let tx = self
+            .build_shielded_transfer_args(args.transfer_args(swept), wrapper_tx_msg)
+            .await?;
+
+        to_js_result((borsh::to_vec(&tx)?, tokens, plan.remaining))
+    }
+
+    pub async fn build_shielding_transfer(
+        &self,
+        shielding_transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_shielding_transfer");
+        self.ensure_masp_params().await?;
+        let mut args = args::shielding_transfer_tx_args(
+            shielding_transfer_msg,
+            wrapper_tx_msg,
+            &self.prefixes(),
+        )?;
+        if self.validate_amounts.get() {
+            self.validate_amounts(args.data.iter_mut().map(|d| (&d.token, &mut d.amount)))
+                .await?;
+        }
+        let (tx, signing_data, _masp_epoch) = build_shielding_transfer(&self.namada, &mut args)
+            .await
+            .context("build shielding transfer")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    pub async fn build_ibc_transfer(
+        &self,
+        ibc_transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_ibc_transfer");
+        self.ensure_masp_params().await?;
+        let mut args =
+            args::ibc_transfer_tx_args(ibc_transfer_msg, wrapper_tx_msg, &self.prefixes())?;
+        let timeouts = self.ibc_preflight(&args).await?;
+        args.timeout_height = timeouts.timeout_height;
+        args.timeout_sec_offset = timeouts.timeout_sec_offset;
+
+        let (tx, signing_data, _) = build_ibc_transfer(&self.namada, &args)
+            .await
+            .context("build IBC transfer")?;
+        let tx = tx::Tx::new(tx, wrapper_tx_msg, vec![signing_data])?;
+
+        to_js_result((borsh::to_vec(&tx)?, timeouts))
+    }
+
+    pub async fn build_eth_bridge_transfer(
+        &self,
+        eth_bridge_transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_eth_bridge_transfer");
+        let args = args::eth_bridge_transfer_tx_args(
+            eth_bridge_transfer_msg,
+            wrapper_tx_msg,
+            &self.prefixes(),
+        )?;
+        if !args.tx.force {
+            eth_bridge::ensure_transferable(self.namada.client(), &args).await?;
+        }
+        let (tx, signing_data) = build_bridge_pool_tx(&self.namada, args.clone())
+            .await
+            .context("build Ethereum bridge transfer")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    /// Checks that an Ethereum bridge transfer would be accepted, i.e. the asset is whitelisted
+    /// and wrapped NAM transfers are within the cap, without building it
+    pub async fn validate_eth_bridge_transfer(
+        &self,
+        eth_bridge_transfer_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<(), JsError> {
+        let args = args::eth_bridge_transfer_tx_args(
+            eth_bridge_transfer_msg,
+            wrapper_tx_msg,
+            &self.prefixes(),
+        )?;
+        eth_bridge::ensure_transferable(self.namada.client(), &args).await
+    }
+
+    pub async fn build_vote_proposal(
+        &self,
+        vote_proposal_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_vote_proposal");
+        let args =
+            args::vote_proposal_tx_args(vote_proposal_msg, wrapper_tx_msg, &self.prefixes())?;
+        let epoch = query_epoch(self.namada.client()).await?;
+        // Forced votes skip the check, as namada_sdk does
+        if !args.tx.force {
+            self.ensure_proposal_votable(&args, epoch).await?;
+        }
+        let (tx, signing_data) = build_vote_proposal(&self.namada, &args, epoch)
+            .await
+            .context("build vote proposal")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    pub async fn build_claim_rewards(
+        &self,
+        claim_rewards_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_claim_rewards");
+        let args =
+            args::claim_rewards_tx_args(claim_rewards_msg, wrapper_tx_msg, &self.prefixes())?;
+        let (tx, signing_data) = build_claim_rewards(&self.namada, &args)
+            .await
+            .context("build claim rewards")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    /// Builds a tx creating an established account controlled by a set of public keys, see
+    /// args::init_account_tx_args
+    pub async fn build_init_account(
+        &self,
+        init_account_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_init_account");
+        let args = args::init_account_tx_args(init_account_msg, wrapper_tx_msg, &self.prefixes())?;
+        let (tx, signing_data) = build_init_account(&self.namada, &args)
+            .await
+            .context("build init account")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    /// Builds a tx replacing the public keys or the threshold of an established account, see
+    /// args::update_account_tx_args
+    pub async fn build_update_account(
+        &self,
+        update_account_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_update_account");
+        let args =
+            args::update_account_tx_args(update_account_msg, wrapper_tx_msg, &self.prefixes())?;
+        // A threshold-only update keeps the current keys, which bound the threshold
+        if let (Some(threshold), true) = (args.threshold, args.public_keys.is_empty()) {
+            let account = rpc::get_account_info(self.namada.client(), &args.addr)
+                .await?
+                .ok_or_else(|| {
+                    JsError::new(&format!(
+                        "Account {} does not exist",
+                        self.prefixes().configured(&args.addr.to_string())
+                    ))
+                })?;
+            args::check_current_threshold(threshold, account.public_keys_map.idx_to_pk.len())?;
+        }
+        let (tx, signing_data) = build_update_account(&self.namada, &args)
+            .await
+            .context("build update account")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    pub async fn build_bond(
+        &self,
+        bond_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_bond");
+        let args = args::bond_tx_args(bond_msg, wrapper_tx_msg, &self.prefixes())?;
+        let (tx, signing_data) = build_bond(&self.namada, &args)
+            .await
+            .context("build bond")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    pub async fn build_unbond(
+        &self,
+        unbond_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_unbond");
+        let args = args::unbond_tx_args(unbond_msg, wrapper_tx_msg, &self.prefixes())?;
+        let (tx, signing_data, _) = build_unbond(&self.namada, &args)
+            .await
+            .context("build unbond")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    pub async fn build_withdraw(
+        &self,
+        withdraw_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_withdraw");
+        let args = args::withdraw_tx_args(withdraw_msg, wrapper_tx_msg, &self.prefixes())?;
+        let (tx, signing_data) = build_withdraw(&self.namada, &args)
+            .await
+            .context("build withdraw")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    pub async fn build_redelegate(
+        &self,
+        redelegate_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_redelegate");
+        let args = args::redelegate_tx_args(redelegate_msg, wrapper_tx_msg, &self.prefixes())?;
+        let (tx, signing_data) = build_redelegation(&self.namada, &args)
+            .await
+            .context("build redelegation")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    /// Plans the redelegations, bonds and unbonds moving the bonds of owner to target weights,
+    /// e.g. `{ [validator]: 0.5 }`, see rebalance::plan_rebalance. The total bonded amount
+    /// is kept unless total is provided.
+    pub async fn plan_rebalance(
+        &self,
+        owner: String,
+        targets: JsValue,
+        total: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        let targets: BTreeMap<String, f64> = targets.into_serde()?;
+        let plan = rebalance::plan_rebalance(
+            self.namada.client(),
+            &owner,
+            &targets,
+            total.as_deref(),
+            &self.prefixes(),
+        )
+        .await?;
+        to_js_result(plan)
+    }
+
+    /// Builds a single batch tx of a rebalance plan, as returned by plan_rebalance. Unbonds
+    /// come first, then redelegations and bonds.
+    pub async fn build_rebalance(
+        &self,
+        plan: JsValue,
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_rebalance");
+        let plan: rebalance::RebalancePlan = plan.into_serde()?;
+        if plan.is_empty() {
+            return Err(JsError::new("Nothing to rebalance"));
+        }
+        let prefixes = self.prefixes();
+        let tx_args = args::tx_args_from_slice(wrapper_tx_msg, &prefixes)?;
+        let address = |address: &str| Address::from_str(&prefixes.native(address));
+        let owner = address(&plan.owner)?;
+        let amount = |amount: &str| Amount::from_str(amount, NATIVE_MAX_DECIMAL_PLACES);
+        let mut txs: Vec<(Tx, SigningTxData)> = vec![];
+
+        for unbond in &plan.unbonds {
+            let args = sdk_args::unbond(
+                tx_args.clone(),
+                owner.clone(),
+                address(&unbond.validator)?,
+                amount(&unbond.amount)?,
+            );
+            let (tx, signing_data, _) = build_unbond(&self.namada, &args)
+                .await
+                .context("build unbond")?;
+            txs.push((tx, signing_data));
+        }
+        for redelegation in &plan.redelegations {
+            let args = sdk_args::redelegate(
+                tx_args.clone(),
+                owner.clone(),
+                address(&redelegation.source)?,
+                address(&redelegation.destination)?,
+                amount(&redelegation.amount)?,
+            );
+            txs.push(
+                build_redelegation(&self.namada, &args)
+                    .await
+                    .context("build redelegation")?,
+            );
+        }
+        for bond in &plan.bonds {
+            let args = sdk_args::bond(
+                tx_args.clone(),
+                owner.clone(),
+                address(&bond.validator)?,
+                amount(&bond.amount)?,
+            );
+            txs.push(
+                build_bond(&self.namada, &args)
+                    .await
+                    .context("build bond")?,
+            );
+        }
+
+        let (tx, signing_data) = build_batch(txs)?;
+        let tx = tx::Tx::new(tx, wrapper_tx_msg, signing_data)?;
+        Ok(to_js_bytes(&borsh::to_vec(&tx)?))
+    }
+
+    /// Builds a single batch tx of validator maintenance changes, i.e. commission rate,
+    /// metadata and consensus key, so operators pay one wrapper fee and approve once. Changes
+    /// left out of the msg are not part of the batch. A new consensus key has to sign the tx
+    /// too, its public key is part of the signing data.
+    pub async fn build_validator_maintenance(
+        &self,
+        validator_maintenance_msg: &[u8],
+        wrapper_tx_msg: &[u8],
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_validator_maintenance");
+        let maintenance = args::validator_maintenance_tx_args(
+            validator_maintenance_msg,
+            wrapper_tx_msg,
+            &self.prefixes(),
+        )?;
+        let mut txs: Vec<(Tx, SigningTxData)> = vec![];
+
+        if let Some(args) = &maintenance.commission {
+            txs.push(
+                build_validator_commission_change(&self.namada, args)
+                    .await
+                    .context("build commission change")?,
+            );
+        }
+        if let Some(args) = &maintenance.metadata {
+            txs.push(
+                build_validator_metadata_change(&self.namada, args)
+                    .await
+                    .context("build metadata change")?,
+            );
+        }
+        if let Some(args) = &maintenance.consensus_key {
+            txs.push(
+                build_change_consensus_key(&self.namada, args)
+                    .await
+                    .context("build consensus key change")?,
+            );
+        }
+
+        let (tx, signing_data) = build_batch(txs)?;
+        let tx = tx::Tx::new(tx, wrapper_tx_msg, signing_data)?;
+        Ok(to_js_bytes(&borsh::to_vec(&tx)?))
+    }
+
+    pub async fn build_reveal_pk(&self, wrapper_tx_msg: &[u8]) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("build_reveal_pk");
+        let args = args::tx_args_from_slice(wrapper_tx_msg, &self.prefixes())?;
+        let public_key = args.signing_keys[0].clone();
+        let (tx, signing_data) = build_reveal_pk(&self.namada, &args.clone(), &public_key)
+            .await
+            .context("build reveal pk")?;
+        self.serialize_tx_result(tx, wrapper_tx_msg, signing_data)
+    }
+
+    // Sign arbitrary data with the provided signing key
+    pub fn sign_arbitrary(
+        &self,
+        signing_key: String,
+        data: String,
+        origin: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("sign_arbitrary");
+        let hash = Hash::sha256(data);
+        let secret = common::SecretKey::Ed25519(ed25519::SecretKey::from_str(&signing_key)?);
+        let signature = common::SigScheme::sign(&secret, hash);
+        let sig_bytes = signature.to_bytes();
+        self.audit_log.record(
+            AuditEntry::new(
+                SignatureKind::Arbitrary,
+                hash.to_string(),
+                vec![],
+                &[secret],
+            )
+            .with_origin(origin.as_deref()),
+        );
+
+        to_js_result((hash.to_string().to_lowercase(), hex::encode(sig_bytes)))
+    }
+
+    // Verify signed arbitrary data
+    pub fn verify_arbitrary(
+        &self,
+        public_key: String,
+        signed_hash: String,
+        signature: String,
+    ) -> Result<(), JsError> {
+        let public_key = common::PublicKey::from_str(&self.prefixes().native(&public_key))?;
+        let sig = common::Signature::try_from_slice(&hex::decode(signature)?)?;
+        let signed_hash = Hash::from_str(&signed_hash)?;
+
+        common::SigScheme::verify_signature(&public_key, &signed_hash, &sig).map_err(JsError::from)
+    }
+
+    pub async fn generate_ibc_shielding_memo(
+        &self,
+        target: &str,
+        token: String,
+        amount: &str,
+        channel_id: &str,
+    ) -> Result<JsValue, JsError> {
+        self.ensure_masp_params().await?;
+        let target = PaymentAddress::from_str(&self.prefixes().native(target))
+            .expect("target is a valid shielded address");
+        let amount =
+            InputAmount::Unvalidated(DenominatedAmount::from_str(amount).expect("amount is valid"));
+        let channel_id = ChannelId::from_str(channel_id).expect("channel ID is valid");
+
+        let memo = self
+            .gen_ibc_shielding_memo(target, token, amount, channel_id)
+            .await?;
+        to_js_result(memo)
+    }
+
+    /// Fills in the shielding memo of a MsgTransfer found in a Cosmos SignDoc, so a deposit
+    /// from a counterparty chain wallet(e.g. Keplr) lands in the shielded pool.
+    /// The receiver of the MsgTransfer is expected to be the target payment address, it is
+    /// replaced with the MASP address. Returns a tuple of updated SignDoc JSON and the memo.
+    ///
+    /// # Arguments
+    ///
+    /// * `sign_doc` - amino JSON or cosmjs SignDoc containing a MsgTransfer
+    /// * `channel_id` - Namada side channel id the tokens are received on
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the SignDoc does not contain a valid MsgTransfer
+    /// or the memo can't be generated.
+    pub async fn shield_ibc_sign_doc(
+        &self,
+        sign_doc: &str,
+        channel_id: &str,
+    ) -> Result<JsValue, JsError> {
+        let mut sign_doc: serde_json::Value = serde_json::from_str(sign_doc)?;
+        let msg_value = ibc::find_msg_transfer(&mut sign_doc)?;
+        let msg: ibc::CosmosMsgTransfer = serde_json::from_value(msg_value.clone())?;
+
+        let target = PaymentAddress::from_str(&self.prefixes().native(&msg.receiver))?;
+        let channel_id = ChannelId::from_str(channel_id)
+            .map_err(|e| JsError::new(&format!("Invalid channel id: {}", e)))?;
+        let token = ibc::received_denom(
+            &msg.source_port,
+            &msg.source_channel,
+            PortId::transfer().as_str(),
+            channel_id.as_str(),
+            &msg.token.denom,
+        );
+        // Counterparty amounts are always in the base denom
+        let amount = Amount::from_str(&msg.token.amount, 0u8)?;
+        let amount = InputAmount::Validated(amount.into());
+
+        let memo = self
+            .gen_ibc_shielding_memo(target, token, amount, channel_id)
+            .await?;
+
+        msg_value["receiver"] =
+            serde_json::Value::String(self.prefixes().configured(&MASP.to_string()));
+        msg_value["memo"] = serde_json::Value::String(memo.clone());
+
+        to_js_result((serde_json::to_string(&sign_doc)?, memo))
+    }
+
+    async fn gen_ibc_shielding_memo(
+        &self,
+        target: PaymentAddress,
+        token: String,
+        amount: InputAmount,
+        channel_id: ChannelId,
+    ) -> Result<String, JsError> {
+        let ledger_address = Url::from_str(&self.rpc_url).expect("RPC URL is a valid URL");
+        let target = TransferTarget::PaymentAddress(target);
+        let token = self.prefixes().native(&token).into_owned();
+
+        let args =
+            sdk_args::gen_ibc_shielding_transfer(ledger_address, target, token, amount, channel_id);
+
+        if let Some(masp_tx) = gen_ibc_shielding_transfer(&self.namada, args).await? {
+            Ok(convert_masp_tx_to_ibc_memo(&masp_tx))
+        } else {
+            Err(JsError::new(
+                "Generating ibc shielding transfer generated nothing",
+            ))
+        }
+    }
+
+    pub fn masp_address(&self) -> String {
+        self.prefixes().configured(&MASP.to_string())
+    }
+
+    /// Generates a proof that a spending key holds at least the given amount of a token,
+    /// without disclosing its notes or viewing key. The proof can't be broadcast. The shielded
+    /// context has to be synced first. See proof_of_funds module for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `spending_key` - Extended spending key
+    /// * `token` - Token address
+    /// * `amount` - Denominated amount to prove
+    /// * `challenge` - Challenge chosen by the verifier
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the spending key does not hold enough unspent notes
+    pub async fn generate_proof_of_funds(
+        &self,
+        spending_key: String,
+        token: String,
+        amount: String,
+        challenge: String,
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("generate_proof_of_funds");
+        self.ensure_masp_params().await?;
+        let prefixes = self.prefixes();
+        let xsk = ExtendedSpendingKey::from_str(&prefixes.native(&spending_key))
+            .secret_arg("proof of funds", "spending key")?;
+        let token =
+            Address::from_str(&prefixes.native(&token)).arg("proof of funds", "token", &token)?;
+        let amount =
+            DenominatedAmount::from_str(&amount).arg("proof of funds", "amount", &amount)?;
+
+        let amount = match self
+            .validated_amount(&token, InputAmount::Unvalidated(amount))
+            .await?
+        {
+            InputAmount::Validated(amount) | InputAmount::Unvalidated(amount) => amount.amount(),
+        };
+
+        let mut shielded = self.namada.shielded_mut().await;
+        shielded.load().await?;
+        let proof = proof_of_funds::proof_of_funds(
+            &shielded,
+            &zip32::ExtendedSpendingKey::from(xsk),
+            &token,
+            amount,
+            challenge,
+        )?;
+
+        Ok(to_js_bytes(&borsh::to_vec(&proof)?))
+    }
+
+    /// Verifies a proof of funds generated by generate_proof_of_funds. Requires MASP params
+    /// to be loaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - Borsh serialized ProofOfFunds
+    /// * `challenge` - Challenge the proof was requested with
+    ///
+    /// # Errors
+    ///
+    /// Returns JsError if the proof can't be deserialized or queries fail
+    pub async fn verify_proof_of_funds(
+        &self,
+        proof: &[u8],
+        challenge: String,
+    ) -> Result<bool, JsError> {
+        self.ensure_masp_params().await?;
+        let keys = VerifyingKeys::load(&self.namada.shielded().await.utils);
+        proof_of_funds::verify_proof_of_funds(self.namada.client(), &keys, proof, &challenge).await
+    }
+
+    /// Applies spend authorization signatures made from a signing plan, e.g. by a Ledger,
+    /// to the MASP transaction of a built tx. The signatures and the binding signature are
+    /// verified, and the binding signature is recomputed if it doesn't verify and the
+    /// binding signing key is given. Requires MASP params to be loaded. Returns the updated
+    /// tx bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Borsh serialized tx::Tx
+    /// * `masp_tx_id` - Borsh serialized MaspTxId, as in the signing plan
+    /// * `signatures` - [position of the spend, hex encoded signature][]
+    /// * `bsk` - Binding signing key of the bundle
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a signature is invalid or the signed transaction doesn't verify
+    pub async fn apply_spend_signatures(
+        &self,
+        tx: Vec<u8>,
+        masp_tx_id: Vec<u8>,
+        signatures: JsValue,
+        bsk: Option<Vec<u8>>,
+    ) -> Result<JsValue, JsError> {
+        let _operation = crash_report::operation("apply_spend_signatures");
+        self.ensure_masp_params().await?;
+        let signatures: Vec<(usize, String)> = signatures.into_serde()?;
+        let signatures = signatures
+            .into_iter()
+            .map(|(tx_index, signature)| {
+                let signature = hex::decode(signature)?;
+                Ok((tx_index, Signature::read(&signature[..])?))
+            })
+            .collect::<Result<Vec<_>, JsError>>()?;
+        let bsk = bsk
+            .map(|bsk| <[u8; 32]>::try_from(bsk.as_slice()))
+            .transpose()
+            .map_err(|_| JsError::new("Binding signing key must be 32 bytes"))?;
+        let masp_tx_id = MaspTxId::try_from_slice(&masp_tx_id)?;
+
+        let keys = VerifyingKeys::load(&self.namada.shielded().await.utils);
+
+        let built_tx: tx::Tx = borsh::from_slice(&tx)?;
+        let mut namada_tx: Tx = borsh::from_slice(built_tx.tx_bytes())?;
+        let (section_hash, masp_tx) = namada_tx
+            .sections
+            .iter()
+            .find_map(|section| match section {
+                Section::MaspTx(masp_tx) if MaspTxId::from(masp_tx.txid()) == masp_tx_id => {
+                    Some((section.get_hash(), masp_tx))
+                }
+                _ => None,
+            })
+            .ok_or_else(|| JsError::new("Tx has no such MASP transaction"))?;
+        let signed = signing_plan::apply_spend_signatures(masp_tx, &signatures, bsk, &keys)?;
+        sections::replace_section(&mut namada_tx, section_hash, Section::MaspTx(signed))?;
+
+        let tx = tx::Tx::new(
+            namada_tx,
+            &borsh::to_vec(&built_tx.args())?,
+            built_tx.signing_tx_data()?,
+        )?;
+        Ok(to_js_bytes(&borsh::to_vec(&tx)?))
+    }
+
+    async fn build_shielded_transfer_args(
+        &self,
+        args: args::ShieldedTransferArgs,
+        wrapper_tx_msg: &[u8],
+    ) -> Result<tx::Tx, JsError> {
+        let args::ShieldedTransferArgs {
+            mut shielded,
+            mut unshielding,
+        } = args;
+        if self.validate_amounts.get() {
+            if let Some(shielded) = shielded.as_mut() {
+                self.validate_amounts(shielded.data.iter_mut().map(|d| (&d.token, &mut d.amount)))
+                    .await?;
+            }
+            for args in unshielding.iter_mut() {
+                self.validate_amounts(args.data.iter_mut().map(|d| (&d.token, &mut d.amount)))
+                    .await?;
+            }
+        }
+
+        let requested = self
+            .requested_amounts(&args::ShieldedTransferArgs {
+                shielded: shielded.clone(),
+                unshielding: unshielding.clone(),
+            })
+            .await?;
+        let mut sources: Vec<ExtendedSpendingKey> =
+            unshielding.iter().flat_map(unshielding_sources).collect();
+        if let Some(shielded) = &shielded {
+            sources.extend(shielded.data.iter().map(|data| data.source.clone()));
+            sources.extend(shielded.gas_spending_key.clone());
+        }
+
+        let args = args::ShieldedTransferArgs {
+            shielded,
+            unshielding,
+        };
+        let (tx, signing_data) = match self.build_shielded_parts(args).await {
+            Ok(tx) => tx,
+            Err(e) => return Err(self.diagnose_build_error(requested, e).await),
+        };
+        self.audit_log.record(AuditEntry::for_masp(&tx, &sources));
+        tx::Tx::new(tx, wrapper_tx_msg, vec![signing_data])
+    }
+
+    /// Builds the parts of a shielded transfer as a single tx. A part of a single kind is
+    /// built by namada_sdk, mixed parts are assembled into one MASP transaction, see
+    /// mixed_transfer module.
+    async fn build_shielded_parts(
+        &self,
+        mut args: args::ShieldedTransferArgs,
+    ) -> Result<(Tx, SigningTxData), JsError> {
+        if mixed_transfer::is_mixed(&args) {
+            // namada_sdk validates the amounts of the parts it builds, mixed parts are
+            // validated here
+            if let Some(shielded) = args.shielded.as_mut() {
+                self.validate_amounts(shielded.data.iter_mut().map(|d| (&d.token, &mut d.amount)))
+                    .await?;
+            }
+            for args in args.unshielding.iter_mut() {
+                self.validate_amounts(args.data.iter_mut().map(|d| (&d.token, &mut d.amount)))
+                    .await?;
+            }
+            let (tx_args, tx_code_path, gas_spending_key) = mixed_transfer::common_args(&args)
+                .ok_or_else(|| JsError::new("At least one transfer is required"))?;
+            return mixed_transfer::build_mixed_transfer(
+                &self.namada,
+                mixed_transfer::transfer_data(&args),
+                tx_args,
+                tx_code_path,
+                gas_spending_key,
+            )
+            .await
+            .context("build mixed shielded transfer");
+        }
+
+        if let Some(mut args) = args.shielded {
+            return build_shielded_transfer(&self.namada, &mut args)
+                .await
+                .context("build shielded transfer");
+        }
+        let mut args = args
+            .unshielding
+            .pop()
+            .ok_or_else(|| JsError::new("At least one transfer is required"))?;
+        build_unshielding_transfer(&self.namada, &mut args)
+            .await
+            .context("build unshielding transfer")
+    }
+
+    /// Returns the cache key of a shielded build of the msgs at the anchor of the local
+    /// commitment tree
+    async fn build_key(&self, msgs: &[&[u8]]) -> Result<Hash, JsError> {
+        let mut shielded = self.namada.shielded_mut().await;
+        shielded.load().await?;
+        build_cache::build_key(msgs, &shielded.tree.root())
+    }
+
+    /// Returns the cached build of the key on a retry if the chain would still accept it,
+    /// dropping it otherwise
+    async fn cached_build(
+        &self,
+        key: &Hash,
+        retry: Option<bool>,
+    ) -> Result<Option<tx::Tx>, JsError> {
+        if !retry.unwrap_or_default() {
+            return Ok(None);
+        }
+        let build = match self.build_cache.get(key) {
+            Some(build) => build,
+            None => return Ok(None),
+        };
+        if build.is_valid(self.namada.client()).await? {
+            return Ok(Some(build.value.tx));
+        }
+        self.build_cache.remove(key);
+        Ok(None)
+    }
+
+    async fn cache_build(
+        &self,
+        key: Hash,
+        msg: BuildMsg,
+        wrapper_tx_msg: &[u8],
+        tx: &tx::Tx,
+    ) -> Result<(), JsError> {
+        let masp_epoch = query_masp_epoch(self.namada.client()).await?;
+        let built = Tx::try_from_slice(tx.tx_bytes())?;
+        let masp_txs: Vec<_> = built
+            .sections
+            .iter()
+            .filter_map(|section| match section {
+                Section::MaspTx(masp_tx) => Some(masp_tx.clone()),
+                _ => None,
+            })
+            .collect();
+        let build = ShieldedBuild {
+            msg,
+            wrapper_tx_msg: wrapper_tx_msg.to_vec(),
+            tx: tx.clone(),
+        };
+        let expiration = built.header.expiration;
+        self.build_cache.insert(
+            key,
+            CachedBuild::new(build, &masp_txs, masp_epoch, expiration),
+        );
+        Ok(())
+    }
+
+    /// Returns the raw amounts requested from every source per token, including the fees
+    /// unshielded by the gas spending key
+    async fn requested_amounts(
+        &self,
+        args: &args::ShieldedTransferArgs,
+    ) -> Result<Vec<(ExtendedSpendingKey, Address, Amount)>, JsError> {
+        let (tx_args, gas_spending_key) = match (&args.shielded, args.unshielding.first()) {
+            (Some(shielded), _) => (&shielded.tx, shielded.gas_spending_key.clone()),
+            (None, Some(unshielding)) => (
+                &unshielding.tx,
+                args.unshielding
+                    .iter()
+                    .find_map(|unshielding| unshielding.gas_spending_key.clone()),
+            ),
+            (None, None) => return Err(JsError::new("At least one transfer is required")),
+        };
+
+        // Requested raw amounts per source and token
+        let mut requested: Vec<(ExtendedSpendingKey, Address, Amount)> = vec![];
+        let shielded_data = args.shielded.iter().flat_map(|shielded| {
+            shielded
+                .data
+                .iter()
+                .map(|data| (data.source.clone(), data.token.clone(), data.amount))
+        });
+        let unshielding_data = args.unshielding.iter().flat_map(|unshielding| {
+            unshielding
+                .data
+                .iter()
+                .map(|data| (unshielding.source.clone(), data.token.clone(), data.amount))
+        });
+        for (source, token, amount) in shielded_data.chain(unshielding_data).collect::<Vec<_>>() {
+            let amount = match amount {
+                InputAmount::Unvalidated(amount) => amount,
+                InputAmount::Validated(amount) => amount,
+            };
+            let amount = self.raw_amount(&token, amount).await?;
+            match requested
+                .iter_mut()
+                .find(|(xsk, t, _)| *xsk == source && *t == token)
+            {
+                Some((_, _, total)) => {
+                    *total = total
+                        .checked_add(amount)
+                        .ok_or_else(|| JsError::new("Amount overflow"))?
+                }
+                None => requested.push((source, token, amount)),
+            }
+        }
+
+        // Fees unshielded by a source are spent from the same notes
+        if let Some(gas_spending_key) = gas_spending_key {
+            let fee = self.raw_fee(tx_args).await?;
+            if let Some((_, _, total)) = requested
+                .iter_mut()
+                .find(|(xsk, t, _)| *xsk == gas_spending_key && *t == tx_args.fee_token)
+            {
+                *total = total
+                    .checked_add(fee)
+                    .ok_or_else(|| JsError::new("Amount overflow"))?;
+            }
+        }
+
+        Ok(requested)
+    }
+
+    /// Replaces a failed MASP build error with the shortfalls of the sources, if any of
+    /// them is short of funds
+    async fn diagnose_build_error(
+        &self,
+        requested: Vec<(ExtendedSpendingKey, Address, Amount)>,
+        error: JsError,
+    ) -> JsError {
+        let mut shortfalls: Vec<masp_build_error::AssetShortfall> = vec![];
+
+        for (source, token, required) in requested {
+            let xfvk = ExtendedFullViewingKey::from(&zip32::ExtendedSpendingKey::from(source));
+            let available = match self.shielded_balance(&xfvk, &token).await {
+                Ok(available) => available,
+                Err(_) => return error,
+            };
+            let shortfall = match required.checked_sub(available) {
+                Some(shortfall) if !shortfall.is_zero() => shortfall,
+                _ => continue,
+            };
+            let unconverted = self
+                .unconverted_balances(&xfvk, &token)
+                .await
+                .unwrap_or_default();
+
+            shortfalls.push(masp_build_error::AssetShortfall {
+                token: self.prefixes().configured(&token.to_string()),
+                required: required.to_string(),
+                available: available.to_string(),
+                shortfall: shortfall.to_string(),
+                unconverted,
+            });
+        }
+
+        if shortfalls.is_empty() {
+            error
+        } else {
+            masp_build_error::MaspBuildError::InsufficientFunds { shortfalls }.into()
+        }
+    }
+
+    /// Returns raw balances of a token held in notes of older MASP epochs, per epoch
+    async fn unconverted_balances(
+        &self,
+        xfvk: &ExtendedFullViewingKey,
+        token: &Address,
+    ) -> Result<Vec<(String, String)>, JsError> {
+        let client = self.namada.client();
+        let epoch = query_masp_epoch(client).await?;
+
+        let mut shielded = self.namada.shielded_mut().await;
+        shielded.load().await?;
+        let balance = match shielded
+            .compute_shielded_balance(&xfvk.fvk.vk)
+            .await
+            .map_err(|e| JsError::new(&e.to_string()))?
+        {
+            Some(balance) => balance,
+            None => return Ok(vec![]),
+        };
+
+        let mut unconverted: BTreeMap<String, Amount> = BTreeMap::new();
+        let decoded = shielded.decode_sum(client, balance).await;
+        for ((_, asset_data), value) in decoded.components() {
+            let asset_epoch = match asset_data.epoch {
+                Some(asset_epoch) if &asset_data.token == token && asset_epoch != epoch => {
+                    asset_epoch
+                }
+                _ => continue,
+            };
+            let value = u64::try_from(*value).unwrap_or_default();
+            let amount = Amount::from_masp_denominated(value, asset_data.position);
+            let total = unconverted.entry(asset_epoch.to_string()).or_default();
+            *total = total
+                .checked_add(amount)
+                .ok_or_else(|| JsError::new("Amount overflow"))?;
+        }
+
+        Ok(unconverted
+            .into_iter()
+            .map(|(epoch, amount)| (epoch, amount.to_string()))
+            .collect())
+    }
+
+    /// Adds explicit change outputs to freshly derived payment addresses for every source
+    /// and token of the transfer. Returns the derived addresses. The change outputs are added
+    /// to the MASP transaction spending the notes, so all targets have to be payment
+    /// addresses, as unshielding parts are built as separate MASP transactions.
+    async fn rotate_change(
+        &self,
+        args: &mut args::ShieldedTransferArgs,
+    ) -> Result<Vec<String>, JsError> {
+        let client = self.namada.client();
+        if !args.unshielding.is_empty() {
+            return Err(JsError::new(
+                "Change rotation requires all targets to be payment addresses",
+            ));
+        }
+        let requested = self.requested_amounts(args).await?;
+        let shielded = args
+            .shielded
+            .as_mut()
+            .ok_or_else(|| JsError::new("At least one transfer is required"))?;
+
+        let mut change_data: Vec<namada_sdk::args::TxShieldedTransferData> = vec![];
+        let mut change_addresses: Vec<(ExtendedSpendingKey, PaymentAddress)> = vec![];
+        for (source, token, amount) in requested {
+            let xfvk =
+                ExtendedFullViewingKey::from(&zip32::ExtendedSpendingKey::from(source.clone()));
+            let balance = self.shielded_balance(&xfvk, &token).await?;
+            let change = balance.checked_sub(amount).ok_or_else(|| {
+                JsError::new(&format!("Insufficient shielded balance of {}", token))
+            })?;
+            if change.is_zero() {
+                continue;
+            }
+
+            let target = match change_addresses.iter().find(|(xsk, _)| *xsk == source) {
+                Some((_, target)) => *target,
+                None => {
+                    let mut index = [0u8; 11];
+                    rand::thread_rng().fill(&mut index);
+                    let (_, target) = xfvk
+                        .find_address(DiversifierIndex(index))
+                        .ok_or_else(|| JsError::new("Can't derive a change address"))?;
+                    let target = PaymentAddress::from(target);
+                    change_addresses.push((source.clone(), target));
+                    target
+                }
+            };
+            let denom = rpc::query_denom(client, &token)
+                .await
+                .ok_or_else(|| JsError::new(&format!("Unknown token: {}", token)))?;
+
+            change_data.push(sdk_args::shielded_transfer_data(
+                source,
+                target,
+                token,
+                InputAmount::Validated(DenominatedAmount::new(change, denom)),
+            ));
+        }
+
+        shielded.data.extend(change_data);
+
+        Ok(change_addresses
+            .into_iter()
+            .map(|(_, target)| target.to_string())
+            .collect())
+    }
+
+    async fn max_unshielding_amount(
+        &self,
+        args: &namada_sdk::args::TxUnshieldingTransfer,
+    ) -> Result<DenominatedAmount, JsError> {
+        let token = &args
+            .data
+            .first()
+            .ok_or_else(|| JsError::new("Unshielding target is required"))?
+            .token;
+        let xfvk =
+            ExtendedFullViewingKey::from(&zip32::ExtendedSpendingKey::from(args.source.clone()));
+        let mut amount = self.shielded_balance(&xfvk, token).await?;
+
+        // Fees are unshielded from the same notes, so they have to be left out
+        if args.gas_spending_key.as_ref() == Some(&args.source) && &args.tx.fee_token == token {
+            let fee = self.raw_fee(&args.tx).await?;
+            amount = amount
+                .checked_sub(fee)
+                .ok_or_else(|| JsError::new("Shielded balance does not cover the fees"))?;
+        }
+        if amount.is_zero() {
+            return Err(JsError::new(&format!("No spendable balance of {}", token)));
+        }
+
+        let denom = rpc::query_denom(self.namada.client(), token)
+            .await
+            .ok_or_else(|| JsError::new(&format!("Unknown token: {}", token)))?;
+
+        Ok(DenominatedAmount::new(amount, denom))
+    }
+
+    /// Checks the targets of an unshielding transfer and that the shielded balance of the
+    /// source covers the amounts of all targets, plus the fee if it's paid by the source
+    async fn validate_unshielding(
+        &self,
+        args: &namada_sdk::args::TxUnshieldingTransfer,
+    ) -> Result<(), JsError> {
+        let mut targets: Vec<unshielding::UnshieldingTarget> = vec![];
+        for data in args.data.iter() {
+            let amount = match data.amount {
+                InputAmount::Unvalidated(amount) => amount,
+                InputAmount::Validated(amount) => amount,
+            };
+            targets.push(unshielding::UnshieldingTarget {
+                target: data.target.clone(),
+                token: data.token.clone(),
+                amount: self.raw_amount(&data.token, amount).await?,
+            });
+        }
+        let fee = if args.gas_spending_key.as_ref() == Some(&args.source) {
+            let fee = self.raw_fee(&args.tx).await?;
+            Some((args.tx.fee_token.clone(), fee))
+        } else {
+            None
+        };
+
+        let required = unshielding::required_amounts(&targets, fee)?;
+        let xfvk =
+            ExtendedFullViewingKey::from(&zip32::ExtendedSpendingKey::from(args.source.clone()));
+        for (token, required) in required {
+            let available = self.shielded_balance(&xfvk, &token).await?;
+            unshielding::check_balance(&token, required, available)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the raw fee amount of the transaction, the fee per gas unit times the gas limit
+    async fn raw_fee(&self, tx_args: &namada_sdk::args::Tx) -> Result<Amount, JsError> {
+        let fee_amount = match tx_args.fee_amount {
+            Some(InputAmount::Unvalidated(amount)) => amount,
+            Some(InputAmount::Validated(amount)) => amount,
+            None => return Ok(Amount::zero()),
+        };
+
+        self.raw_amount(&tx_args.fee_token, fee_amount)
+            .await?
+            .checked_mul(Amount::from_u64(u64::from(tx_args.gas_limit)))
+            .ok_or_else(|| JsError::new("Fee amount overflow"))
+    }
+
+    /// Returns the raw shielded balance of a token, with all notes converted to the current
+    /// MASP epoch
+    async fn shielded_balance(
+        &self,
+        xfvk: &ExtendedFullViewingKey,
+        token: &Address,
+    ) -> Result<Amount, JsError> {
+        let balance = self
+            .shielded_balances(xfvk)
+            .await?
+            .into_iter()
+            .find(|(balance_token, _)| balance_token == token)
+            .map(|(_, amount)| amount)
+            .unwrap_or_default();
+
+        Ok(balance)
+    }
+
+    /// Returns the raw shielded balances of all tokens, with all notes converted to the
+    /// current MASP epoch
+    async fn shielded_balances(
+        &self,
+        xfvk: &ExtendedFullViewingKey,
+    ) -> Result<Vec<(Address, Amount)>, JsError> {
+        let client = self.namada.client();
+        let epoch = query_masp_epoch(client).await?;
+
+        let mut shielded = self.namada.shielded_mut().await;
+        shielded.load().await?;
+        let balance = shielded
+            .compute_exchanged_balance(client, &WebIo, &xfvk.fvk.vk, epoch)
+            .await
+            .map_err(|e| JsError::new(&format!("{:?}", e)))?;
+
+        let balances = match balance {
+            Some(balance) => {
+                let (decoded, _) = shielded
+                    .decode_combine_sum_to_epoch(client, balance, epoch)
+                    .await;
+                decoded
+                    .components()
+                    .map(|(token, change)| (token.clone(), Amount::from_change(*change)))
+                    .filter(|(_, amount)| !amount.is_zero())
+                    .collect()
+            }
+            None => vec![],
+        };
+
+        Ok(balances)
+    }
+
+    /// Validates an amount against the denomination of its token, see validate_amount
+    async fn validated_amount(
+        &self,
+        token: &Address,
+        amount: InputAmount,
+    ) -> Result<InputAmount, JsError> {
+        let amount = match amount {
+            InputAmount::Validated(_) => return Ok(amount),
+            InputAmount::Unvalidated(amount) => amount,
+        };
+        let denom = rpc::query_denom(self.namada.client(), token)
+            .await
+            .ok_or_else(|| JsError::new(&format!("Unknown token: {}", token)))?;
+        let validated = amount.increase_precision(denom).map_err(|_| {
+            JsError::new(&format!(
+                "Amount {} has more than the {} decimal places of {}",
+                amount, denom.0, token
+            ))
+        })?;
+
+        Ok(InputAmount::Validated(validated))
+    }
+
+    /// Replaces amounts with their validated amounts, see set_amount_validation
+    async fn validate_amounts<'a>(
+        &self,
+        amounts: impl Iterator<Item = (&'a Address, &'a mut InputAmount)>,
+    ) -> Result<(), JsError> {
+        for (token, amount) in amounts {
+            *amount = self.validated_amount(token, *amount).await?;
+        }
+        Ok(())
+    }
+
+    /// Converts a denominated amount to a raw amount of the token
+    async fn raw_amount(
+        &self,
+        token: &Address,
+        amount: DenominatedAmount,
+    ) -> Result<Amount, JsError> {
+        let denom = rpc::query_denom(self.namada.client(), token)
+            .await
+            .ok_or_else(|| JsError::new(&format!("Unknown token: {}", token)))?;
+
+        Ok(amount.increase_precision(denom)?.amount())
+    }
+
+    /// Returns the prefixes of the chain, cloned so they can be replaced while a call is awaited
+    fn prefixes(&self) -> ChainPrefixes {
+        self.prefixes.borrow().clone()
+    }
+
+    /// Returns a key or address with the configured prefix re-encoded with the Namada one
+    fn native(&self, s: &str) -> String {
+        self.prefixes.borrow().native(s).into_owned()
+    }
+
+    /// Screens the counterparties of a tx before signing it, see set_screening_list
+    async fn screen(&self, tx: &Tx) -> Result<(), JsError> {
+        // Cloned out, so the screening can be replaced while a callback is awaited
+        let screening = self.screening.borrow().clone();
+        match screening {
+            Some(screening) => {
+                let wasm_hashes = query::wasm_hashes(self.namada.client()).await;
+                screening.screen(tx, &wasm_hashes, &self.prefixes()).await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Applies a change of sections to the tx of a built tx, returning the updated tx bytes
+    /// along with the result of the change
+    fn update_tx_sections(
+        tx: &[u8],
+        update: impl FnOnce(&mut Tx) -> Result<String, JsError>,
+    ) -> Result<JsValue, JsError> {
+        let built_tx: tx::Tx = borsh::from_slice(tx)?;
+        let mut namada_tx: Tx = borsh::from_slice(built_tx.tx_bytes())?;
+        let result = update(&mut namada_tx)?;
+
+        let tx = tx::Tx::new(
+            namada_tx,
+            &borsh::to_vec(&built_tx.args())?,
+            built_tx.signing_tx_data()?,
+        )?;
+        to_js_result((borsh::to_vec(&tx)?, result))
+    }
+
+    fn serialize_tx_result(
+        &self,
+        tx: Tx,
+        wrapper_tx_msg: &[u8],
+        signing_data: SigningTxData,
+    ) -> Result<JsValue, JsError> {
+        let tx = tx::Tx::new(tx, wrapper_tx_msg, vec![signing_data])?;
+        Ok(to_js_bytes(&borsh::to_vec(&tx)?))
+    }
+
+    /// Checks that the channel of an IBC transfer can be relayed and returns its timeouts,
+    /// defaulting them based on the counterparty client when none are given
+    async fn ibc_preflight(
+        &self,
+        args: &namada_sdk::args::TxIbcTransfer,
+    ) -> Result<ibc::IbcTimeouts, JsError> {
+        let counterparty =
+            ibc::query_counterparty_client(self.namada.client(), &args.port_id, &args.channel_id)
+                .await?;
+        let now = (js_sys::Date::now() / 1000.0) as i64;
+        // Forced transfers skip the check, e.g. when a relayer is about to update the client
+        if !args.tx.force {
+            counterparty.ensure_usable(&args.port_id, &args.channel_id, now)?;
+        }
+
+        let timeouts = ibc::IbcTimeouts {
+            timeout_height: args.timeout_height,
+            timeout_sec_offset: args.timeout_sec_offset,
+        };
+        ibc::default_timeouts(timeouts, counterparty.expires_at(), now)
+    }
+
+    /// Checks that the proposal exists and that the voter can vote on it in the current
+    /// epoch, so votes that would fail on chain are rejected before paying fees
+    async fn ensure_proposal_votable(
+        &self,
+        args: &namada_sdk::args::VoteProposal,
+        epoch: Epoch,
+    ) -> Result<(), JsError> {
+        let client = self.namada.client();
+        let proposal = rpc::query_proposal_by_id(client, args.proposal_id)
+            .await
+            .context("vote proposal")?
+            .ok_or_else(|| {
+                JsError::new(&format!(
+                    "vote proposal: proposal {} does not exist",
+                    args.proposal_id
+                ))
+            })?;
+
+        if epoch < proposal.voting_start_epoch {
+            return Err(JsError::new(&format!(
+                "vote proposal: voting on proposal {} starts at epoch {}, current epoch is {}",
+                args.proposal_id, proposal.voting_start_epoch, epoch
+            )));
+        }
+        let role = voter_role(client, &args.voter_address, epoch).await?;
+        if !role.can_vote() {
+            return Err(JsError::new(&format!(
+                "vote proposal: {} has no stake to vote with{}",
+                args.voter_address,
+                if role.is_steward {
+                    ", PGF stewards vote as validators or delegators"
+                } else {
+                    ""
+                }
+            )));
+        }
+        let is_validator = role.is_validator;
+        if !proposal.can_be_voted(epoch, is_validator) {
+            return Err(JsError::new(&format!(
+                "vote proposal: voting on proposal {} {} ended, current epoch is {}",
+                args.proposal_id,
+                if is_validator {
+                    "for validators has"
+                } else {
+                    "has"
+                },
+                epoch
+            )));
+        }
+        Ok(())
+    }
+
+    /// Loads MASP params deferred with defer_masp_params, if any. The location is kept until
+    /// the params are loaded, and concurrent calls wait on the shielded context lock held
+    /// during the load, so no call proceeds without params.
+    async fn ensure_masp_params(&self) -> Result<(), JsError> {
+        if self.masp_params_location.borrow().is_none() {
+            return Ok(());
+        }
+
+        let mut shielded = self.namada.shielded_mut().await;
+        // None if a concurrent call loaded the params while this one waited for the lock
+        let location = self.masp_params_location.borrow().clone();
+        if let Some(path_or_db_name) = location {
+            *shielded = self
+                .masp_params_context(&path_or_db_name)
+                .await
+                .map_err(|e| JsError::new(&format!("Failed to load MASP params: {:?}", e)))?;
+            self.masp_params_location.borrow_mut().take();
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "nodejs"))]
+    async fn masp_params_context(
+        &self,
+        _db_name: &str,
+    ) -> Result<ShieldedContext<masp::JSShieldedUtils>, JsValue> {
+        // _dn_name is not used in the web version for a time being
+        let params = get_masp_params().await?;
+        let params_iter = js_sys::try_iter(&params)?.ok_or("Can't iterate over JsValue")?;
+        let mut params_bytes = params_iter.map(|p| to_bytes(p.unwrap()));
+
+        let spend = params_bytes.next().unwrap();
+        let output = params_bytes.next().unwrap();
+        let convert = params_bytes.next().unwrap();
+
+        // We are making sure that there are no more params left
+        assert_eq!(params_bytes.next(), None);
+
+        Ok(ShieldedContext::new(
+            masp::JSShieldedUtils::new(spend, output, convert).await?,
+        ))
+    }
+
+    #[cfg(feature = "nodejs")]
+    async fn masp_params_context(
+        &self,
+        context_dir: &str,
+    ) -> Result<ShieldedContext<masp::JSShieldedUtils>, JsValue> {
+        Ok(ShieldedContext::new(
+            masp::JSShieldedUtils::new(context_dir).await,
+        ))
+    }
+}
+
+/// Spending keys signing the MASP spends of an unshielding transfer
+fn unshielding_sources(args: &namada_sdk::args::TxUnshieldingTransfer) -> Vec<ExtendedSpendingKey> {
+    let mut sources = vec![args.source.clone()];
+    sources.extend(args.gas_spending_key.clone());
+    sources
+}
+
+#[wasm_bindgen(module = "/src/sdk/mod.js")]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = "getMaspParams")]
+    async fn get_masp_params() -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(catch, js_name = "hasMaspParams")]
+    async fn has_masp_params() -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(catch, js_name = "fetchAndStoreMaspParams")]
+    async fn fetch_and_store_masp_params(url: Option<String>) -> Result<JsValue, JsValue>;
+}
@@ -10,12 +10,15 @@ use namada_sdk::{
 use std::str::FromStr;
 use wasm_bindgen::prelude::*;
 
-/// Helper function to bech32 encode a public key from bytes
+use crate::sdk::prefixes::ChainPrefixes;
+
+/// Helper function to bech32 encode a public key from bytes, with the prefix of `prefixes`
+/// if any
 #[wasm_bindgen]
-pub fn public_key_to_bech32(bytes: Vec<u8>) -> Result<String, JsError> {
+pub fn public_key_to_bech32(bytes: Vec<u8>, prefixes: JsValue) -> Result<String, JsError> {
     let public_key = PublicKey::try_from_slice(&bytes)?;
 
-    Ok(public_key.to_string())
+    Ok(ChainPrefixes::from_js(prefixes)?.configured(&public_key.to_string()))
 }
 
 #[wasm_bindgen]
@@ -23,13 +26,14 @@ pub struct Address {
     implicit: address::Address,
     public: PublicKey,
     hash: PublicKeyHash,
+    prefixes: ChainPrefixes,
 }
 
 #[wasm_bindgen]
 impl Address {
-    /// Address helpers for wasm_bindgen
+    /// Address helpers for wasm_bindgen, encoding with the prefixes of `prefixes` if any
     #[wasm_bindgen(constructor)]
-    pub fn new(secret: String) -> Address {
+    pub fn new(secret: String, prefixes: JsValue) -> Result<Address, JsError> {
         let private = SecretKey::Ed25519(
             key::ed25519::SecretKey::from_str(&secret).expect("ed25519 encoding should not fail"),
         );
@@ -39,19 +43,20 @@ impl Address {
         let hash = PublicKeyHash::from(&public);
         let implicit = address::Address::Implicit(address::ImplicitAddress::from(&public));
 
-        Address {
+        Ok(Address {
             implicit,
             public,
             hash,
-        }
+            prefixes: ChainPrefixes::from_js(prefixes)?,
+        })
     }
 
     pub fn implicit(&self) -> String {
-        self.implicit.encode()
+        self.prefixes.configured(&self.implicit.encode())
     }
 
     pub fn public(&self) -> String {
-        self.public.to_string()
+        self.prefixes.configured(&self.public.to_string())
     }
 
     pub fn hash(&self) -> String {
@@ -68,7 +73,7 @@ mod tests {
     fn can_generate_implicit_address() {
         let secret =
             String::from("1498b5467a63dffa2dc9d9e069caf075d16fc33fdd4c3b01bfadae6433767d93");
-        let address = Address::new(secret);
+        let address = Address::new(secret, JsValue::UNDEFINED).unwrap();
         let implicit = address.implicit();
 
         assert_eq!(implicit, "tnam1qpgk927uh2aqjs92yhycsh08n5yggvltn5nk92zp");
@@ -79,7 +84,7 @@ mod tests {
     fn can_return_correct_public_key() {
         let secret =
             String::from("1498b5467a63dffa2dc9d9e069caf075d16fc33fdd4c3b01bfadae6433767d93");
-        let address = Address::new(secret);
+        let address = Address::new(secret, JsValue::UNDEFINED).unwrap();
         let public = address.public();
 
         assert_eq!(
@@ -93,7 +98,7 @@ mod tests {
     fn can_return_correct_public_key_hash() {
         let secret =
             String::from("1498b5467a63dffa2dc9d9e069caf075d16fc33fdd4c3b01bfadae6433767d93");
-        let address = Address::new(secret.clone());
+        let address = Address::new(secret.clone(), JsValue::UNDEFINED).unwrap();
         let hash = address.hash();
 
         assert_eq!("5162ABDCBABA0940AA25C9885DE79D088433EB9D", hash);
@@ -0,0 +1,225 @@
+//! Derivation self test
+//!
+//! Keys are derived in pieces: the TS packages turn the mnemonic into a seed and derive
+//! keys with @namada/crypto, then encode them and their addresses here. A mismatch between
+//! the pieces, e.g. a different coin type or hardening of the path, yields valid looking
+//! keys of other accounts. derivation_self_test derives the same accounts with namada_sdk
+//! and returns every intermediate value, so the extension can compare them with its own
+//! derivation at install time.
+use bip39::{Language, Mnemonic, Seed};
+use gloo_utils::format::JsValueSerdeExt;
+use namada_sdk::address::{Address, ImplicitAddress};
+use namada_sdk::borsh;
+use namada_sdk::key::{common, PublicKeyHash, RefTo, SchemeType};
+use namada_sdk::masp_primitives::zip32::{ChildIndex, ExtendedFullViewingKey, ExtendedSpendingKey};
+use namada_sdk::wallet::{derive_hd_secret_key, DerivationPath};
+use namada_sdk::{
+    ExtendedSpendingKey as NamadaExtendedSpendingKey,
+    ExtendedViewingKey as NamadaExtendedViewingKey, PaymentAddress as NamadaPaymentAddress,
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::sdk::prefixes::ChainPrefixes;
+use crate::utils::to_js_result;
+
+/// Path of the transparent keys, as Bip44Path of @namada/types
+#[derive(Debug, Clone, Deserialize)]
+pub struct Bip44Path {
+    pub account: u32,
+    #[serde(default)]
+    pub change: u32,
+    #[serde(default)]
+    pub index: u32,
+}
+
+/// Path of the shielded keys, as Zip32Path of @namada/types
+#[derive(Debug, Clone, Deserialize)]
+pub struct Zip32Path {
+    pub account: u32,
+    pub index: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SelfTestPath {
+    pub bip44: Bip44Path,
+    pub zip32: Zip32Path,
+}
+
+/// Keys and addresses of a path, with the values they're derived from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivedAccount {
+    pub bip44_path: String,
+    /// Hex encoded ed25519 secret key
+    pub private_key: String,
+    pub public_key: String,
+    pub public_key_hash: String,
+    pub address: String,
+    pub zip32_path: String,
+    pub spending_key: String,
+    pub viewing_key: String,
+    /// Default payment address of the viewing key
+    pub payment_address: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivationSelfTest {
+    pub coin_type: u32,
+    /// Hex encoded BIP-39 seed
+    pub seed: String,
+    pub accounts: Vec<DerivedAccount>,
+}
+
+pub fn bip44_path(coin_type: u32, path: &Bip44Path) -> String {
+    format!(
+        "m/44'/{}'/{}'/{}'/{}'",
+        coin_type, path.account, path.change, path.index
+    )
+}
+
+pub fn zip32_path(coin_type: u32, path: &Zip32Path) -> String {
+    let hardened = format!("m/32'/{}'/{}'", coin_type, path.account);
+    match path.index {
+        Some(index) => format!("{}/{}", hardened, index),
+        None => hardened,
+    }
+}
+
+/// Derives the keys of a path with the coin type of the prefixes, the shielded keys from the
+/// transparent secret key as in ShieldedHDWallet of @namada/crypto
+///
+/// # Errors
+///
+/// Returns JsError if the path is invalid
+pub fn derive_account(
+    seed: &[u8],
+    prefixes: &ChainPrefixes,
+    path: &SelfTestPath,
+) -> Result<DerivedAccount, JsError> {
+    let coin_type = prefixes.coin_type;
+    let bip44_path = bip44_path(coin_type, &path.bip44);
+    let derivation_path = DerivationPath::from_path_string(&bip44_path)
+        .map_err(|e| JsError::new(&format!("Invalid path {}: {}", bip44_path, e)))?;
+    let secret_key = match derive_hd_secret_key(SchemeType::Ed25519, seed, derivation_path) {
+        common::SecretKey::Ed25519(secret_key) => secret_key,
+        _ => return Err(JsError::new("Expected an ed25519 secret key")),
+    };
+    let secret_bytes: [u8; 32] = borsh::to_vec(&secret_key)?
+        .try_into()
+        .map_err(|_| JsError::new("Invalid ed25519 secret key size"))?;
+    let public_key = common::PublicKey::Ed25519(secret_key.ref_to());
+    let address = Address::Implicit(ImplicitAddress::from(&public_key));
+
+    let mut zip32_indices: Vec<ChildIndex> = [32, coin_type, path.zip32.account]
+        .into_iter()
+        .map(ChildIndex::Hardened)
+        .collect();
+    zip32_indices.extend(path.zip32.index.map(ChildIndex::NonHardened));
+    let master = ExtendedSpendingKey::master(&secret_bytes);
+    let xsk = ExtendedSpendingKey::from_path(&master, &zip32_indices);
+    let xfvk = ExtendedFullViewingKey::from(&xsk);
+    let payment_address = xfvk.default_address().1;
+
+    Ok(DerivedAccount {
+        bip44_path,
+        private_key: hex::encode(secret_bytes),
+        public_key: prefixes.configured(&public_key.to_string()),
+        public_key_hash: PublicKeyHash::from(&public_key).to_string(),
+        address: prefixes.configured(&address.encode()),
+        zip32_path: zip32_path(coin_type, &path.zip32),
+        spending_key: prefixes.configured(&NamadaExtendedSpendingKey::from(xsk).to_string()),
+        viewing_key: prefixes.configured(&NamadaExtendedViewingKey::from(xfvk).to_string()),
+        payment_address: prefixes
+            .configured(&NamadaPaymentAddress::from(payment_address).to_string()),
+    })
+}
+
+/// Derives the transparent and shielded keys of the paths from a mnemonic with the coin
+/// type of the prefixes, returning the seed and, for each path, the keys and addresses along with
+/// their paths. See DerivationSelfTest.
+///
+/// # Arguments
+///
+/// * `mnemonic` - BIP-39 phrase
+/// * `paths` - [{ bip44: Bip44Path, zip32: Zip32Path }]
+/// * `passphrase` - BIP-39 passphrase
+/// * `prefixes` - ChainPrefixes, see Sdk.chain_prefixes, Namada prefixes if undefined
+///
+/// # Errors
+///
+/// Returns JsError if the phrase, any of the paths or the prefixes are invalid
+#[wasm_bindgen]
+pub fn derivation_self_test(
+    mnemonic: String,
+    paths: JsValue,
+    passphrase: Option<String>,
+    prefixes: JsValue,
+) -> Result<JsValue, JsError> {
+    let paths: Vec<SelfTestPath> = paths.into_serde()?;
+    let prefixes = ChainPrefixes::from_js(prefixes)?;
+    let mnemonic = Mnemonic::from_phrase(&mnemonic, Language::English)
+        .map_err(|e| JsError::new(&format!("Invalid mnemonic: {}", e)))?;
+    let seed = Seed::new(&mnemonic, passphrase.as_deref().unwrap_or_default());
+    let accounts = paths
+        .iter()
+        .map(|path| derive_account(seed.as_bytes(), &prefixes, path))
+        .collect::<Result<Vec<_>, JsError>>()?;
+
+    to_js_result(DerivationSelfTest {
+        coin_type: prefixes.coin_type,
+        seed: hex::encode(seed.as_bytes()),
+        accounts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const PHRASE: &str = "caught pig embody hip goose like become worry face oval manual flame \
+                          pizza steel viable proud eternal speed chapter sunny boat because \
+                          view bullet";
+
+    fn seed() -> Vec<u8> {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        Seed::new(&mnemonic, "").as_bytes().to_vec()
+    }
+
+    #[wasm_bindgen_test]
+    fn derives_as_hd_wallet() {
+        let path = SelfTestPath {
+            bip44: Bip44Path {
+                account: 0,
+                change: 0,
+                index: 0,
+            },
+            zip32: Zip32Path {
+                account: 0,
+                index: None,
+            },
+        };
+        let account = derive_account(&seed(), &ChainPrefixes::default(), &path).unwrap();
+
+        // Same key as HDWallet of @namada/crypto for the phrase and path
+        assert_eq!(
+            account.private_key,
+            "e4680e1e3ac8ef748c9a97fba284b7bc6b002db624302e27711dfc492cf27d1e"
+        );
+        assert_eq!(account.bip44_path, "m/44'/877'/0'/0'/0'");
+        assert_eq!(account.zip32_path, "m/32'/877'/0'");
+        assert!(account.address.starts_with("tnam1"));
+        assert!(account.payment_address.starts_with("znam1"));
+    }
+
+    #[wasm_bindgen_test]
+    fn formats_paths() {
+        let zip32 = Zip32Path {
+            account: 1,
+            index: Some(2),
+        };
+        assert_eq!(zip32_path(1, &zip32), "m/32'/1'/1'/2");
+    }
+}
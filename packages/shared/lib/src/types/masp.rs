@@ -9,6 +9,8 @@ use namada_sdk::{
 use thiserror::Error;
 use wasm_bindgen::prelude::*;
 
+use crate::sdk::prefixes::ChainPrefixes;
+
 #[derive(Debug, Error)]
 pub enum MaspError {
     #[error("PaymentAddress from_str failed!")]
@@ -35,9 +37,9 @@ impl ExtendedViewingKey {
         Ok(ExtendedViewingKey(vk))
     }
 
-    /// Return ExtendedViewingKey as Bech32-encoded String
-    pub fn encode(&self) -> String {
-        self.0.to_string()
+    /// Return ExtendedViewingKey as Bech32-encoded String, with the prefix of `prefixes` if any
+    pub fn encode(&self, prefixes: JsValue) -> Result<String, JsError> {
+        Ok(ChainPrefixes::from_js(prefixes)?.configured(&self.0.to_string()))
     }
 }
 
@@ -59,9 +61,9 @@ impl ExtendedSpendingKey {
         Ok(ExtendedSpendingKey(xsk))
     }
 
-    /// Return ExtendedSpendingKey as Bech32-encoded String
-    pub fn encode(&self) -> String {
-        self.0.to_string()
+    /// Return ExtendedSpendingKey as Bech32-encoded String, with the prefix of `prefixes` if any
+    pub fn encode(&self, prefixes: JsValue) -> Result<String, JsError> {
+        Ok(ChainPrefixes::from_js(prefixes)?.configured(&self.0.to_string()))
     }
 }
 
@@ -86,9 +88,9 @@ impl PaymentAddress {
         self.0.hash()
     }
 
-    /// Return PaymentAddress as Bech32-encoded String
-    pub fn encode(&self) -> String {
-        self.0.to_string()
+    /// Return PaymentAddress as Bech32-encoded String, with the prefix of `prefixes` if any
+    pub fn encode(&self, prefixes: JsValue) -> Result<String, JsError> {
+        Ok(ChainPrefixes::from_js(prefixes)?.configured(&self.0.to_string()))
     }
 }
 
@@ -115,7 +117,7 @@ mod tests {
         let xsk = ExtendedSpendingKey::new(encoded_xsk)
             .expect("Instantiating ExtendedSpendingKey struct should not fail!");
 
-        let key = xsk.encode();
+        let key = xsk.encode(JsValue::UNDEFINED).unwrap();
         let expected_key =
             format!(
                 "{}{}{}",
@@ -145,7 +147,7 @@ mod tests {
         let xfvk = ExtendedViewingKey::new(encoded_xfvk)
             .expect("Instantiating ExtendedViewingKey struct should not fail!");
 
-        let key = xfvk.encode();
+        let key = xfvk.encode(JsValue::UNDEFINED).unwrap();
         let expected_key =
             format!(
                 "{}{}{}",
@@ -168,7 +170,7 @@ mod tests {
         let payment_address = PaymentAddress::new(encoded_payment_address)
             .expect("Instantiating PaymentAddress struct should not fail!");
 
-        let address = payment_address.encode();
+        let address = payment_address.encode(JsValue::UNDEFINED).unwrap();
         let hash = payment_address.hash();
 
         let expected_address =
@@ -1,3 +1,5 @@
 pub mod address;
+pub mod derivation;
 pub mod masp;
 pub mod query;
+pub mod storage;
@@ -1,8 +1,9 @@
-use namada_sdk::borsh::BorshSerialize;
+use namada_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
-#[derive(BorshSerialize)]
+#[derive(BorshSerialize, BorshDeserialize, Serialize)]
 #[borsh(crate = "namada_sdk::borsh")]
+#[serde(rename_all = "camelCase")]
 pub struct ProposalInfo {
     pub id: u64,
     pub content: String,
@@ -34,3 +35,89 @@ impl WasmHash {
         self.hash.clone()
     }
 }
+
+/// Wrapper tx waiting in the mempool
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTx {
+    pub hash: String,
+    pub inner_tx_hashes: Vec<String>,
+    pub fee_token: String,
+    pub fee_amount_per_gas_unit: String,
+    pub gas_limit: String,
+}
+
+/// Proof op of an ABCI query, with raw key and data
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbciProofOp {
+    pub field_type: String,
+    pub key: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+/// Raw response of an ABCI query
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbciQueryResponse {
+    pub value: Vec<u8>,
+    /// Proof ops, if requested
+    pub proof_ops: Option<Vec<AbciProofOp>>,
+    pub height: u64,
+    pub info: String,
+}
+
+/// Component of a MASP conversion, in raw units of the asset
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionComponent {
+    pub token: String,
+    pub digit_pos: u8,
+    pub epoch: String,
+    pub amount: String,
+}
+
+/// Allowed conversion of an asset of a past MASP epoch to the current one
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaspConversion {
+    pub token: String,
+    pub denom: u8,
+    pub digit_pos: u8,
+    pub epoch: String,
+    pub components: Vec<ConversionComponent>,
+}
+
+/// Reward parameters of a token in the MASP, with the inflation of the last epoch
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaspRewardRate {
+    pub name: String,
+    pub token: String,
+    pub max_reward_rate: String,
+    pub kp_gain: String,
+    pub kd_gain: String,
+    pub locked_amount_target: String,
+    pub last_inflation: Option<String>,
+    pub last_locked_amount: Option<String>,
+}
+
+/// Governance role of a voter, explaining where the weight of its vote comes from
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoterRole {
+    pub address: String,
+    pub is_validator: bool,
+    pub is_delegator: bool,
+    /// PGF stewards vote with their stake like any other address, being a steward only
+    /// changes the tally type of the proposals they author
+    pub is_steward: bool,
+    /// Stake of a validator or total delegations of a delegator, in raw native token units
+    pub voting_power: String,
+}
+
+impl VoterRole {
+    pub fn can_vote(&self) -> bool {
+        self.is_validator || self.is_delegator
+    }
+}
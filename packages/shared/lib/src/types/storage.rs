@@ -0,0 +1,101 @@
+//! Decoders of common storage value types, for values read with Query::query_storage_value
+use std::str::FromStr;
+
+use namada_sdk::borsh::BorshDeserialize;
+use namada_sdk::key::common::PublicKey;
+use namada_sdk::proof_of_stake::types::ValidatorState;
+use namada_sdk::proof_of_stake::Epoch;
+use namada_sdk::token::Amount;
+use wasm_bindgen::prelude::*;
+
+use crate::utils::to_js_result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StorageValueType {
+    /// Raw token amount, decoded to a string
+    Amount,
+    /// Epoch, decoded to a number
+    Epoch,
+    /// Public key, decoded to a bech32 string
+    PublicKey,
+    /// Validator state, decoded to its name in camelCase
+    ValidatorState,
+}
+
+impl FromStr for StorageValueType {
+    type Err = JsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "amount" => Ok(StorageValueType::Amount),
+            "epoch" => Ok(StorageValueType::Epoch),
+            "publicKey" => Ok(StorageValueType::PublicKey),
+            "validatorState" => Ok(StorageValueType::ValidatorState),
+            _ => Err(JsError::new(&format!("Unknown storage value type: {}", s))),
+        }
+    }
+}
+
+pub(crate) fn validator_state_name(state: ValidatorState) -> &'static str {
+    match state {
+        ValidatorState::Consensus => "consensus",
+        ValidatorState::BelowCapacity => "belowCapacity",
+        ValidatorState::BelowThreshold => "belowThreshold",
+        ValidatorState::Inactive => "inactive",
+        ValidatorState::Jailed => "jailed",
+    }
+}
+
+/// Decodes Borsh serialized storage value bytes of a type
+///
+/// # Arguments
+///
+/// * `bytes` - Storage value bytes
+/// * `value_type` - One of "amount", "epoch", "publicKey", "validatorState"
+///
+/// # Errors
+///
+/// Returns JsError if the type is unknown or the bytes don't decode to it
+#[wasm_bindgen]
+pub fn decode_storage_value(bytes: &[u8], value_type: &str) -> Result<JsValue, JsError> {
+    match StorageValueType::from_str(value_type)? {
+        StorageValueType::Amount => {
+            to_js_result(Amount::try_from_slice(bytes)?.raw_amount().to_string())
+        }
+        StorageValueType::Epoch => to_js_result(Epoch::try_from_slice(bytes)?.0),
+        StorageValueType::PublicKey => to_js_result(PublicKey::try_from_slice(bytes)?.to_string()),
+        StorageValueType::ValidatorState => {
+            to_js_result(validator_state_name(ValidatorState::try_from_slice(bytes)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use namada_sdk::borsh;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn parses_storage_value_types() {
+        assert_eq!(
+            StorageValueType::from_str("publicKey").unwrap(),
+            StorageValueType::PublicKey
+        );
+        assert!(StorageValueType::from_str("PublicKey").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn decodes_storage_values() {
+        let bytes = borsh::to_vec(&Epoch(42)).unwrap();
+        assert_eq!(decode_storage_value(&bytes, "epoch").unwrap(), 42);
+
+        let bytes = borsh::to_vec(&ValidatorState::Jailed).unwrap();
+        assert_eq!(
+            decode_storage_value(&bytes, "validatorState").unwrap(),
+            "jailed"
+        );
+
+        assert!(decode_storage_value(&[], "amount").is_err());
+    }
+}
@@ -4,6 +4,8 @@ use serde::Serialize;
 use std::fmt::Debug;
 use wasm_bindgen::prelude::*;
 
+use crate::sdk::prefixes::ChainPrefixes;
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
@@ -25,7 +27,14 @@ pub fn to_bytes(u_int_8_array: JsValue) -> Vec<u8> {
     array.to_vec()
 }
 
-/// Maps a result to a JsValue using Serde and Error into a JsError
+/// Copies bytes into a new Uint8Array. Unlike to_js_result, which converts bytes to an array
+/// of numbers, this is a single memcpy out of the wasm memory.
+pub fn to_js_bytes(bytes: &[u8]) -> JsValue {
+    Uint8Array::from(bytes).into()
+}
+
+/// Maps a result to a JsValue using Serde and Error into a JsError. Keys of maps are sorted,
+/// so the result doesn't depend on the iteration order of HashMaps.
 ///
 /// # Arguments
 ///
@@ -34,17 +43,35 @@ pub fn to_js_result<T>(result: T) -> Result<JsValue, JsError>
 where
     T: Serialize,
 {
-    match JsValue::from_serde(&result) {
+    let canonical = canonical_json(&result).map_err(|e| JsError::new(&e.to_string()))?;
+    match JsValue::from_serde(&canonical) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(JsError::new(&e.to_string())),
+    }
+}
+
+/// Serializes a value like to_js_result, with the addresses and keys re-encoded with the
+/// configured prefixes, see ChainPrefixes::configured
+pub fn to_js_configured<T>(result: T, prefixes: &ChainPrefixes) -> Result<JsValue, JsError>
+where
+    T: Serialize,
+{
+    let canonical = canonical_json(&result).map_err(|e| JsError::new(&e.to_string()))?;
+    match JsValue::from_serde(&prefixes.configured_json(canonical)) {
         Ok(v) => Ok(v),
         Err(e) => Err(JsError::new(&e.to_string())),
     }
 }
 
-#[cfg(feature = "dev")]
+/// Serializes a value to JSON with the keys of all maps and objects sorted
+pub fn canonical_json<T: Serialize>(value: &T) -> Result<serde_json::Value, serde_json::Error> {
+    // Objects of serde_json are BTreeMaps, as long as preserve_order is not enabled
+    serde_json::to_value(value)
+}
+
+/// Installs the panic hook logging panics and sending crash reports, see crash_report
 pub fn set_panic_hook() {
+    #[cfg(feature = "dev")]
     web_sys::console::log_1(&"Set panic hook".into());
-    console_error_panic_hook::set_once();
+    crate::crash_report::install();
 }
-
-#[cfg(not(feature = "dev"))]
-pub fn set_panic_hook() {}
@@ -0,0 +1,183 @@
+//! Versioning handshake between the wasm lib and the TS packages
+//!
+//! The wasm artifact and the TS packages are published separately, and a wallet may load a
+//! cached wasm of another release. Borsh layouts of Msgs that don't match panic or fail with
+//! opaque deserialization errors, so the TS side asks for the api_version and capabilities
+//! first, and disables what the loaded wasm doesn't support.
+//!
+//! API_VERSION is a semver of the JS facing API: bump the major when a Msg layout or an
+//! exported signature changes, the minor when Msgs or functions are added.
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::sdk::code_paths::TxCode;
+use crate::sdk::msg_schema::{self, MsgSchema};
+use crate::sdk::payment_request::PaymentRequestMsg;
+use crate::sdk::signature::SignatureMsg;
+use crate::sdk::templates::TransferTemplateMsg;
+use crate::sdk::wallet_connect::WcSessionRequestMsg;
+use crate::utils::to_js_result;
+
+pub const API_VERSION: &str = "2.4.0";
+
+/// Borsh Msgs accepted by the exported functions, nested Msgs excluded
+pub const MSG_TYPES: &[&str] = &[
+    "BondMsg",
+    "ClaimRewardsMsg",
+    "EthBridgeTransferMsg",
+    "IbcTransferMsg",
+    "InitAccountMsg",
+    "MaxUnshieldingTransferMsg",
+    "PaymentRequestMsg",
+    "RedelegateMsg",
+    "RevealPkMsg",
+    "ShieldedTransferMsg",
+    "ShieldingTransferMsg",
+    "SignatureMsg",
+    "TransferMsg",
+    "TransferTemplateMsg",
+    "TransparentTransferMsg",
+    "UnbondMsg",
+    "UnshieldingTransferMsg",
+    "UpdateAccountMsg",
+    "ValidatorMaintenanceMsg",
+    "VoteProposalMsg",
+    "WcSessionRequestMsg",
+    "WithdrawMsg",
+    "WrapperTxMsg",
+];
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub api_version: &'static str,
+    /// Version of the shared crate
+    pub crate_version: &'static str,
+    pub msg_types: &'static [&'static str],
+    pub tx_codes: Vec<TxCode>,
+    /// Cargo features compiled in, e.g. "multicore"
+    pub features: Vec<&'static str>,
+}
+
+pub fn capabilities() -> Capabilities {
+    let features = [
+        ("nodejs", cfg!(feature = "nodejs")),
+        ("multicore", cfg!(feature = "multicore")),
+        ("dev", cfg!(feature = "dev")),
+    ]
+    .into_iter()
+    .filter_map(|(feature, enabled)| enabled.then_some(feature))
+    .collect();
+
+    Capabilities {
+        api_version: API_VERSION,
+        crate_version: env!("CARGO_PKG_VERSION"),
+        msg_types: MSG_TYPES,
+        tx_codes: TxCode::ALL.to_vec(),
+        features,
+    }
+}
+
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version.trim().split('.').map(|part| part.parse().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+/// Returns true if a client built against `required` can use API_VERSION: same major, and at
+/// least the required minor. Before 1.0, minors are breaking too.
+pub fn is_compatible(required: &str, provided: &str) -> bool {
+    let (Some(required), Some(provided)) = (parse_version(required), parse_version(provided))
+    else {
+        return false;
+    };
+    match (required, provided) {
+        ((0, required_minor, _), (0, provided_minor, _)) => required_minor == provided_minor,
+        ((required_major, required_minor, _), (provided_major, provided_minor, _)) => {
+            required_major == provided_major && provided_minor >= required_minor
+        }
+    }
+}
+
+/// Returns the semver of the JS facing API, see API_VERSION
+#[wasm_bindgen]
+pub fn api_version() -> String {
+    API_VERSION.to_string()
+}
+
+/// Returns the Capabilities of this build
+///
+/// # Errors
+///
+/// Returns JsError if the capabilities can't be serialized
+#[wasm_bindgen(js_name = "capabilities")]
+pub fn capabilities_js() -> Result<JsValue, JsError> {
+    to_js_result(capabilities())
+}
+
+/// Returns true if a client requiring the `required` API version can use this build
+#[wasm_bindgen]
+pub fn is_api_compatible(required: &str) -> bool {
+    is_compatible(required, API_VERSION)
+}
+
+/// Returns the MsgSchema of every Msg accepted by the exported functions, see MSG_TYPES
+pub fn describe_messages() -> Vec<MsgSchema> {
+    let mut schemas = msg_schema::describe_messages();
+    schemas.extend([
+        msg_schema::describe_untagged::<PaymentRequestMsg>(),
+        msg_schema::describe_untagged::<SignatureMsg>(),
+        msg_schema::describe_untagged::<TransferTemplateMsg>(),
+        msg_schema::describe_untagged::<WcSessionRequestMsg>(),
+    ]);
+    schemas
+}
+
+/// Returns the Borsh layouts of the Msgs, see msg_schema.rs
+///
+/// # Errors
+///
+/// Returns JsError if the schemas can't be serialized
+#[wasm_bindgen(js_name = "describe_messages")]
+pub fn describe_messages_js() -> Result<JsValue, JsError> {
+    to_js_result(describe_messages())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn checks_compatibility() {
+        assert!(is_compatible("1.0.0", "1.2.3"));
+        assert!(is_compatible("1.2.0", "1.2.0"));
+        assert!(!is_compatible("1.3.0", "1.2.0"));
+        assert!(!is_compatible("1.0.0", "2.0.0"));
+        assert!(is_compatible("0.4.1", "0.4.0"));
+        assert!(!is_compatible("0.4.0", "0.5.0"));
+        assert!(!is_compatible("1.0", "1.0.0"));
+        assert!(is_api_compatible(API_VERSION));
+    }
+
+    #[wasm_bindgen_test]
+    fn lists_capabilities() {
+        let capabilities = capabilities();
+        assert_eq!(capabilities.api_version, API_VERSION);
+        assert_eq!(capabilities.tx_codes.len(), TxCode::ALL.len());
+        assert!(capabilities.msg_types.contains(&"WrapperTxMsg"));
+    }
+
+    #[wasm_bindgen_test]
+    fn describes_msg_types() {
+        let described: Vec<_> = describe_messages().into_iter().map(|s| s.name).collect();
+        for msg_type in MSG_TYPES {
+            assert!(
+                described.iter().any(|name| name == msg_type),
+                "{} is not described",
+                msg_type
+            );
+        }
+        assert!(described.iter().any(|name| name == "TransferKindMsg"));
+    }
+}
@@ -0,0 +1,135 @@
+// Regenerates lib/fixtures/golden_txs.json from txs dumped by namadac.
+//
+// Requires namadac of the namada_sdk version pinned in lib/Cargo.toml, and a wallet and
+// chain set up with the addresses below, e.g. a local devnet:
+//
+//   NAMADA_LEDGER_ADDRESS=http://127.0.0.1:26657 node ./scripts/golden-fixtures.js
+const { spawnSync } = require("child_process");
+const fs = require("fs");
+const os = require("os");
+const path = require("path");
+
+const ledgerAddress =
+  process.env.NAMADA_LEDGER_ADDRESS || "http://127.0.0.1:26657";
+const source = process.env.NAMADA_SOURCE || "albert";
+const target = process.env.NAMADA_TARGET || "bertha";
+const validator = process.env.NAMADA_VALIDATOR || "validator-0";
+
+const fixturesPath = path.join(__dirname, "../lib/fixtures/golden_txs.json");
+const cargoToml = fs.readFileSync(
+  path.join(__dirname, "../lib/Cargo.toml"),
+  "utf8"
+);
+const namadaVersion = cargoToml.match(/^namada_sdk = .*tag\s*=\s*"([^"]+)"/m)[1];
+
+// Fixed inputs, one tx per entry
+const txs = [
+  {
+    name: "transparent_transfer",
+    codeTags: ["tx_transfer.wasm"],
+    args: [
+      "transparent-transfer",
+      "--source",
+      source,
+      "--target",
+      target,
+      "--token",
+      "nam",
+      "--amount",
+      "1.5",
+    ],
+  },
+  {
+    name: "bond",
+    codeTags: ["tx_bond.wasm"],
+    args: [
+      "bond",
+      "--source",
+      source,
+      "--validator",
+      validator,
+      "--amount",
+      "10",
+    ],
+  },
+  {
+    name: "unbond",
+    codeTags: ["tx_unbond.wasm"],
+    args: [
+      "unbond",
+      "--source",
+      source,
+      "--validator",
+      validator,
+      "--amount",
+      "5",
+    ],
+  },
+  {
+    name: "withdraw",
+    codeTags: ["tx_withdraw.wasm"],
+    args: ["withdraw", "--source", source, "--validator", validator],
+  },
+  {
+    name: "claim_rewards",
+    codeTags: ["tx_claim_rewards.wasm"],
+    args: ["claim-rewards", "--source", source, "--validator", validator],
+  },
+  {
+    name: "vote_proposal",
+    codeTags: ["tx_vote_proposal.wasm"],
+    args: [
+      "vote-proposal",
+      "--address",
+      source,
+      "--proposal-id",
+      "0",
+      "--vote",
+      "yay",
+    ],
+  },
+];
+
+const outputDir = fs.mkdtempSync(path.join(os.tmpdir(), "golden-txs-"));
+
+const goldenTxs = txs.map(({ name, codeTags, args }) => {
+  const dir = path.join(outputDir, name);
+  fs.mkdirSync(dir);
+
+  const result = spawnSync(
+    "namadac",
+    [
+      ...args,
+      "--gas-limit",
+      "50000",
+      "--gas-price",
+      "0.000001",
+      "--dump-tx",
+      "--output-folder-path",
+      dir,
+      "--ledger-address",
+      ledgerAddress,
+    ],
+    { stdio: "inherit" }
+  );
+  if (result.status !== 0) {
+    throw new Error(`namadac failed to dump ${name}`);
+  }
+
+  // Dumped txs are named after their header hash
+  const [file] = fs.readdirSync(dir).filter((f) => f.endsWith(".tx"));
+  return {
+    name,
+    tx: JSON.parse(fs.readFileSync(path.join(dir, file), "utf8")),
+    headerHash: path.basename(file, ".tx"),
+    codeTags,
+  };
+});
+
+fs.writeFileSync(
+  fixturesPath,
+  JSON.stringify({ namadaVersion, txs: goldenTxs }, null, 2) + "\n"
+);
+console.log(
+  `Wrote ${goldenTxs.length} golden txs dumped with namada ${namadaVersion}`
+);
@@ -1,5 +1,62 @@
 import { Constructor, deserialize, serialize } from "@dao-xyz/borsh";
-import { Schema } from "../schema";
+import {
+  BondMsgValue,
+  ClaimRewardsMsgValue,
+  EthBridgeTransferMsgValue,
+  IbcTransferMsgValue,
+  InitAccountMsgValue,
+  MaxUnshieldingTransferMsgValue,
+  RedelegateMsgValue,
+  Schema,
+  ShieldedTransferMsgValue,
+  ShieldingTransferMsgValue,
+  TransferKindMsgValue,
+  TransparentTransferMsgValue,
+  UnbondMsgValue,
+  UnshieldingTransferMsgValue,
+  UpdateAccountMsgValue,
+  ValidatorMaintenanceMsgValue,
+  VoteProposalMsgValue,
+  WithdrawMsgValue,
+  WrapperTxMsgValue,
+} from "../schema";
+
+// Abstract to include TransferKindMsgValue, matched with instanceof
+type MsgClass = abstract new (...args: never[]) => Schema;
+
+/**
+ * First byte of the Msgs passed to the tx builders, so that the wasm lib
+ * rejects a wrapper tx msg passed in place of the specific msg, or the other
+ * way around. Keep in sync with MsgTag in packages/shared/lib/src/sdk/decode.rs
+ */
+export const MSG_TAGS: [MsgClass, number][] = [
+  [WrapperTxMsgValue, 0xa0],
+  [BondMsgValue, 0xa1],
+  [UnbondMsgValue, 0xa2],
+  [WithdrawMsgValue, 0xa3],
+  [RedelegateMsgValue, 0xa4],
+  [VoteProposalMsgValue, 0xa5],
+  [ClaimRewardsMsgValue, 0xa6],
+  [ValidatorMaintenanceMsgValue, 0xa7],
+  [TransparentTransferMsgValue, 0xa8],
+  [ShieldedTransferMsgValue, 0xa9],
+  [ShieldingTransferMsgValue, 0xaa],
+  [UnshieldingTransferMsgValue, 0xab],
+  [MaxUnshieldingTransferMsgValue, 0xac],
+  [IbcTransferMsgValue, 0xad],
+  [EthBridgeTransferMsgValue, 0xae],
+  [TransferKindMsgValue, 0xaf],
+  [InitAccountMsgValue, 0xb0],
+  [UpdateAccountMsgValue, 0xb1],
+];
+
+/**
+ * Returns the tag of a Msg, or undefined if it's not tagged
+ * @param value - Msg
+ * @returns tag byte
+ */
+export const msgTag = (value: Schema): number | undefined =>
+  MSG_TAGS.find(([msgClass]) => value instanceof msgClass)?.[1];
 
 export interface IMessage<T extends Schema> {
   encode(value: T): Uint8Array;
@@ -7,11 +64,21 @@ export interface IMessage<T extends Schema> {
 
 export class Message<T extends Schema> implements IMessage<T> {
   public encode(value: T): Uint8Array {
+    let bytes: Uint8Array;
     try {
-      return serialize(value);
+      bytes = serialize(value);
     } catch (e) {
       throw new Error(`Unable to serialize message: ${e}`);
     }
+
+    const tag = msgTag(value);
+    if (tag === undefined) {
+      return bytes;
+    }
+    const tagged = new Uint8Array(bytes.length + 1);
+    tagged[0] = tag;
+    tagged.set(bytes, 1);
+    return tagged;
   }
 
   public static decode<T extends Schema>(
@@ -3,15 +3,19 @@ export * from "./bond";
 export * from "./claimRewards";
 export * from "./ethBridgeTransfer";
 export * from "./ibcTransfer";
+export * from "./initAccount";
 export * from "./redelegate";
 export * from "./revealPk";
 export * from "./signature";
 export * from "./transfer";
+export * from "./transferTemplate";
 export * from "./tx";
 export * from "./txDetails";
 export * from "./txResponse";
 export * from "./unbond";
+export * from "./updateAccount";
 export * from "./utils";
+export * from "./validatorMaintenance";
 export * from "./voteProposal";
 export * from "./withdraw";
 export * from "./wrapperTx";
@@ -21,25 +25,34 @@ import { BondMsgValue } from "./bond";
 import { ClaimRewardsMsgValue } from "./claimRewards";
 import { EthBridgeTransferMsgValue } from "./ethBridgeTransfer";
 import { IbcTransferMsgValue } from "./ibcTransfer";
+import { InitAccountMsgValue } from "./initAccount";
 import { RedelegateMsgValue } from "./redelegate";
 import { RevealPkMsgValue } from "./revealPk";
 import { SignatureMsgValue } from "./signature";
 import {
+  MaxUnshieldingTransferMsgValue,
   ShieldedTransferDataMsgValue,
   ShieldedTransferMsgValue,
   ShieldingTransferDataMsgValue,
   ShieldingTransferMsgValue,
   TransferDataMsgValue,
+  TransferKindMsgValue,
   TransferMsgValue,
   TransparentTransferDataMsgValue,
   TransparentTransferMsgValue,
   UnshieldingTransferDataMsgValue,
   UnshieldingTransferMsgValue,
 } from "./transfer";
+import { TransferTemplateMsgValue } from "./transferTemplate";
 import { SigningDataMsgValue, TxMsgValue } from "./tx";
 import { CommitmentMsgValue, TxDetailsMsgValue } from "./txDetails";
-import { TxResponseMsgValue } from "./txResponse";
+import { FeeAccountingMsgValue, TxResponseMsgValue } from "./txResponse";
 import { UnbondMsgValue } from "./unbond";
+import { UpdateAccountMsgValue } from "./updateAccount";
+import {
+  ValidatorMaintenanceMsgValue,
+  ValidatorMetadataMsgValue,
+} from "./validatorMaintenance";
 import { VoteProposalMsgValue } from "./voteProposal";
 import { WithdrawMsgValue } from "./withdraw";
 import { WrapperTxMsgValue } from "./wrapperTx";
@@ -48,6 +61,8 @@ export type Schema =
   | BatchTxResultMsgValue
   | EthBridgeTransferMsgValue
   | IbcTransferMsgValue
+  | InitAccountMsgValue
+  | UpdateAccountMsgValue
   | SignatureMsgValue
   | BondMsgValue
   | UnbondMsgValue
@@ -61,14 +76,20 @@ export type Schema =
   | SigningDataMsgValue
   | TransferMsgValue
   | TransferDataMsgValue
+  | TransferKindMsgValue
+  | TransferTemplateMsgValue
   | TransparentTransferMsgValue
   | TransparentTransferDataMsgValue
   | TxMsgValue
   | TxResponseMsgValue
+  | FeeAccountingMsgValue
   | UnshieldingTransferDataMsgValue
   | UnshieldingTransferMsgValue
+  | MaxUnshieldingTransferMsgValue
   | WrapperTxMsgValue
   | RedelegateMsgValue
   | CommitmentMsgValue
   | TxDetailsMsgValue
-  | RevealPkMsgValue;
+  | RevealPkMsgValue
+  | ValidatorMaintenanceMsgValue
+  | ValidatorMetadataMsgValue;
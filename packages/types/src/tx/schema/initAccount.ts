@@ -0,0 +1,16 @@
+/* eslint-disable @typescript-eslint/no-unused-vars */
+import { field, option, vec } from "@dao-xyz/borsh";
+import { InitAccountProps } from "../types";
+
+export class InitAccountMsgValue {
+  @field({ type: vec("string") })
+  publicKeys!: string[];
+
+  // Number of signatures required, all keys if left out
+  @field({ type: option("u8") })
+  threshold?: number;
+
+  constructor(data: InitAccountProps) {
+    Object.assign(this, data);
+  }
+}
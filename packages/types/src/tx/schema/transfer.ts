@@ -1,11 +1,13 @@
 /* eslint-disable @typescript-eslint/no-unused-vars */
-import { field, option, vec } from "@dao-xyz/borsh";
+import { field, option, variant, vec } from "@dao-xyz/borsh";
 import BigNumber from "bignumber.js";
 import {
+  MaxUnshieldingTransferProps,
   ShieldedTransferDataProps,
   ShieldedTransferProps,
   ShieldingTransferDataProps,
   ShieldingTransferProps,
+  TransferKindProps,
   TransparentTransferDataProps,
   TransparentTransferProps,
   UnshieldingTransferDataProps,
@@ -54,6 +56,7 @@ export class ShieldedTransferDataMsgValue {
   @field({ type: "string" })
   source!: string;
 
+  // Payment address, or transparent address to unshield to
   @field({ type: "string" })
   target!: string;
 
@@ -147,8 +150,8 @@ export class UnshieldingTransferMsgValue {
   @field({ type: vec(UnshieldingTransferDataMsgValue) })
   data!: UnshieldingTransferDataMsgValue[];
 
-  @field({ type: option(vec("string")) })
-  gasSpendingKey?: string[];
+  @field({ type: option("string") })
+  gasSpendingKey?: string;
 
   constructor({ source, data, gasSpendingKey }: UnshieldingTransferProps) {
     Object.assign(this, {
@@ -162,6 +165,93 @@ export class UnshieldingTransferMsgValue {
   }
 }
 
+export class MaxUnshieldingTransferMsgValue {
+  @field({ type: "string" })
+  source!: string;
+
+  @field({ type: "string" })
+  target!: string;
+
+  @field({ type: "string" })
+  token!: string;
+
+  @field({ type: option("string") })
+  gasSpendingKey?: string;
+
+  constructor(data: MaxUnshieldingTransferProps) {
+    Object.assign(this, data);
+  }
+}
+
+/**
+ * Transfer of any kind, the variant index is the Borsh enum tag
+ */
+export abstract class TransferKindMsgValue {}
+
+@variant(0)
+export class TransparentTransferKindMsgValue extends TransferKindMsgValue {
+  @field({ type: TransparentTransferMsgValue })
+  value!: TransparentTransferMsgValue;
+
+  constructor(props: TransparentTransferProps) {
+    super();
+    this.value = new TransparentTransferMsgValue(props);
+  }
+}
+
+@variant(1)
+export class ShieldedTransferKindMsgValue extends TransferKindMsgValue {
+  @field({ type: ShieldedTransferMsgValue })
+  value!: ShieldedTransferMsgValue;
+
+  constructor(props: ShieldedTransferProps) {
+    super();
+    this.value = new ShieldedTransferMsgValue(props);
+  }
+}
+
+@variant(2)
+export class ShieldingTransferKindMsgValue extends TransferKindMsgValue {
+  @field({ type: ShieldingTransferMsgValue })
+  value!: ShieldingTransferMsgValue;
+
+  constructor(props: ShieldingTransferProps) {
+    super();
+    this.value = new ShieldingTransferMsgValue(props);
+  }
+}
+
+@variant(3)
+export class UnshieldingTransferKindMsgValue extends TransferKindMsgValue {
+  @field({ type: UnshieldingTransferMsgValue })
+  value!: UnshieldingTransferMsgValue;
+
+  constructor(props: UnshieldingTransferProps) {
+    super();
+    this.value = new UnshieldingTransferMsgValue(props);
+  }
+}
+
+/**
+ * Returns the message of a transfer of any kind
+ * @param transfer - kind and properties of the transfer
+ * @returns message variant of the kind
+ */
+export const transferKindMsgValue = (
+  transfer: TransferKindProps
+): TransferKindMsgValue => {
+  switch (transfer.kind) {
+    case "transparent":
+      return new TransparentTransferKindMsgValue(transfer.props);
+    case "shielded":
+      return new ShieldedTransferKindMsgValue(transfer.props);
+    case "shielding":
+      return new ShieldingTransferKindMsgValue(transfer.props);
+    case "unshielding":
+      return new UnshieldingTransferKindMsgValue(transfer.props);
+  }
+};
+
 /**
  * General Transfer schema used for displaying details
  */
@@ -0,0 +1,28 @@
+import { field, option } from "@dao-xyz/borsh";
+import { TransferTemplateProps } from "../types";
+
+export class TransferTemplateMsgValue {
+  @field({ type: "string" })
+  id!: string;
+
+  @field({ type: "string" })
+  source!: string;
+
+  @field({ type: "string" })
+  target!: string;
+
+  @field({ type: "string" })
+  token!: string;
+
+  // Amount formula: fixed amount ("12.5"), percentage of the balance ("25%"),
+  // whole balance ("max") or balance keeping a reserve ("max-1.5")
+  @field({ type: "string" })
+  amount!: string;
+
+  @field({ type: option("string") })
+  memo?: string;
+
+  constructor(data: TransferTemplateProps) {
+    Object.assign(this, data);
+  }
+}
@@ -17,6 +17,9 @@ export class CommitmentMsgValue {
 
   @field({ type: option("string") })
   memo?: string;
+
+  @field({ type: option("string") })
+  fiatValue?: string;
 }
 
 export class TxDetailsMsgValue {
@@ -25,4 +28,7 @@ export class TxDetailsMsgValue {
 
   @field({ type: vec(CommitmentMsgValue) })
   commitments!: CommitmentMsgValue[];
+
+  @field({ type: option("string") })
+  originHash?: string;
 }
@@ -1,8 +1,42 @@
 /* eslint-disable @typescript-eslint/no-unused-vars */
-import { field, vec } from "@dao-xyz/borsh";
-import { TxResponseProps } from "../types";
+import { field, option, vec } from "@dao-xyz/borsh";
+import { FeeAccountingProps, TxResponseProps } from "../types";
 import { BatchTxResultMsgValue } from "./batchTxResult";
 
+/**
+ * Gas and fee accounting of an applied wrapper tx, with denominated amounts.
+ * The fee is charged on the gas limit and paid in full to the block proposer
+ */
+export class FeeAccountingMsgValue {
+  @field({ type: "string" })
+  token!: string;
+
+  @field({ type: "string" })
+  gasLimit!: string;
+
+  @field({ type: "string" })
+  gasUsed!: string;
+
+  @field({ type: "string" })
+  feePerGasUnit!: string;
+
+  @field({ type: "string" })
+  charged!: string;
+
+  @field({ type: "string" })
+  refunded!: string;
+
+  @field({ type: "string" })
+  paidToProposer!: string;
+
+  @field({ type: "string" })
+  burned!: string;
+
+  constructor(data: FeeAccountingProps) {
+    Object.assign(this, data);
+  }
+}
+
 export class TxResponseMsgValue {
   @field({ type: "string" })
   code!: string;
@@ -25,6 +59,9 @@ export class TxResponseMsgValue {
   @field({ type: "string" })
   log!: string;
 
+  @field({ type: option(FeeAccountingMsgValue) })
+  fee?: FeeAccountingMsgValue;
+
   constructor(data: TxResponseProps) {
     Object.assign(this, data);
   }
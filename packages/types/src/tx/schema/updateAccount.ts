@@ -0,0 +1,21 @@
+/* eslint-disable @typescript-eslint/no-unused-vars */
+import { field, option, vec } from "@dao-xyz/borsh";
+import { UpdateAccountProps } from "../types";
+
+export class UpdateAccountMsgValue {
+  @field({ type: "string" })
+  address!: string;
+
+  // New public keys, the current keys are kept if empty
+  @field({ type: vec("string") })
+  publicKeys!: string[];
+
+  // New number of signatures required, unchanged if left out. Required with
+  // new public keys
+  @field({ type: option("u8") })
+  threshold?: number;
+
+  constructor(data: UpdateAccountProps) {
+    Object.assign(this, data);
+  }
+}
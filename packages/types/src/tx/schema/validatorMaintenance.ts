@@ -0,0 +1,48 @@
+/* eslint-disable @typescript-eslint/no-unused-vars */
+import { field, option } from "@dao-xyz/borsh";
+import { ValidatorMaintenanceProps, ValidatorMetadataProps } from "../types";
+
+export class ValidatorMetadataMsgValue {
+  @field({ type: option("string") })
+  email?: string;
+
+  @field({ type: option("string") })
+  description?: string;
+
+  @field({ type: option("string") })
+  website?: string;
+
+  @field({ type: option("string") })
+  discordHandle?: string;
+
+  @field({ type: option("string") })
+  avatar?: string;
+
+  @field({ type: option("string") })
+  name?: string;
+
+  constructor(data: ValidatorMetadataProps) {
+    Object.assign(this, data);
+  }
+}
+
+export class ValidatorMaintenanceMsgValue {
+  @field({ type: "string" })
+  validator!: string;
+
+  @field({ type: option("string") })
+  commissionRate?: string;
+
+  @field({ type: option(ValidatorMetadataMsgValue) })
+  metadata?: ValidatorMetadataMsgValue;
+
+  @field({ type: option("string") })
+  consensusKey?: string;
+
+  constructor({ metadata, ...data }: ValidatorMaintenanceProps) {
+    Object.assign(this, {
+      ...data,
+      metadata: metadata && new ValidatorMetadataMsgValue(metadata),
+    });
+  }
+}
@@ -1,5 +1,6 @@
 /* eslint-disable @typescript-eslint/no-unused-vars */
 import { field } from "@dao-xyz/borsh";
+import { VoteType } from "../../proposals";
 import { VoteProposalProps } from "../types";
 
 export class VoteProposalMsgValue {
@@ -9,8 +10,9 @@ export class VoteProposalMsgValue {
   @field({ type: "u64" })
   proposalId!: bigint;
 
+  // Validated case-insensitively in Rust, see ProposalVote
   @field({ type: "string" })
-  vote!: string;
+  vote!: VoteType;
 
   constructor(data: VoteProposalProps) {
     Object.assign(this, data);
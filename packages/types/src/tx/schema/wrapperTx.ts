@@ -1,5 +1,5 @@
 /* eslint-disable @typescript-eslint/no-unused-vars */
-import { field, option } from "@dao-xyz/borsh";
+import { field, option, vec } from "@dao-xyz/borsh";
 import BigNumber from "bignumber.js";
 import { WrapperTxProps } from "../types";
 import { BigNumberSerializer } from "./utils";
@@ -26,6 +26,22 @@ export class WrapperTxMsgValue {
   @field({ type: option("bool") })
   force?: boolean;
 
+  // RFC 3339 date, see Rpc.queryTxExpiration
+  @field({ type: option("string") })
+  expiration?: string;
+
+  // Public key paying the fee, defaults to the first signer
+  @field({ type: option("string") })
+  feePayer?: string;
+
+  // Public keys signing the inner txs, defaults to publicKey
+  @field({ type: option(vec("string")) })
+  signers?: string[];
+
+  // Hex encoded X25519 public key to encrypt the memo to, see Tx.encryptMemo
+  @field({ type: option("string") })
+  memoRecipient?: string;
+
   constructor(data: WrapperTxProps) {
     Object.assign(this, data);
   }
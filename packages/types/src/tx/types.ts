@@ -3,7 +3,10 @@ import {
   BondMsgValue,
   ClaimRewardsMsgValue,
   EthBridgeTransferMsgValue,
+  FeeAccountingMsgValue,
   IbcTransferMsgValue,
+  InitAccountMsgValue,
+  MaxUnshieldingTransferMsgValue,
   RedelegateMsgValue,
   ShieldedTransferDataMsgValue,
   ShieldedTransferMsgValue,
@@ -12,6 +15,7 @@ import {
   SignatureMsgValue,
   SigningDataMsgValue,
   TransferMsgValue,
+  TransferTemplateMsgValue,
   TransparentTransferDataMsgValue,
   TransparentTransferMsgValue,
   TxMsgValue,
@@ -19,6 +23,9 @@ import {
   UnbondMsgValue,
   UnshieldingTransferDataMsgValue,
   UnshieldingTransferMsgValue,
+  UpdateAccountMsgValue,
+  ValidatorMaintenanceMsgValue,
+  ValidatorMetadataMsgValue,
   VoteProposalMsgValue,
   WithdrawMsgValue,
   WrapperTxMsgValue,
@@ -28,7 +35,9 @@ import { RevealPkMsgValue } from "./schema/revealPk";
 export type BatchTxResultProps = BatchTxResultMsgValue;
 export type BondProps = BondMsgValue;
 export type EthBridgeTransferProps = EthBridgeTransferMsgValue;
+export type FeeAccountingProps = FeeAccountingMsgValue;
 export type IbcTransferProps = IbcTransferMsgValue;
+export type InitAccountProps = InitAccountMsgValue;
 export type RedelegateProps = RedelegateMsgValue;
 export type SignatureProps = SignatureMsgValue;
 export type ShieldedTransferProps = ShieldedTransferMsgValue;
@@ -37,18 +46,32 @@ export type ShieldingTransferProps = ShieldingTransferMsgValue;
 export type ShieldingTransferDataProps = ShieldingTransferDataMsgValue;
 export type UnshieldingTransferDataProps = UnshieldingTransferDataMsgValue;
 export type UnshieldingTransferProps = UnshieldingTransferMsgValue;
+export type MaxUnshieldingTransferProps = MaxUnshieldingTransferMsgValue;
 export type TransferProps = TransferMsgValue;
+export type TransferTemplateProps = TransferTemplateMsgValue;
 export type TransparentTransferProps = TransparentTransferMsgValue;
 export type TransparentTransferDataProps = TransparentTransferDataMsgValue;
 export type TxProps = TxMsgValue;
 export type TxResponseProps = TxResponseMsgValue;
 export type SigningDataProps = SigningDataMsgValue;
 export type UnbondProps = UnbondMsgValue;
+export type UpdateAccountProps = UpdateAccountMsgValue;
 export type VoteProposalProps = VoteProposalMsgValue;
 export type ClaimRewardsProps = ClaimRewardsMsgValue;
 export type WithdrawProps = WithdrawMsgValue;
 export type WrapperTxProps = WrapperTxMsgValue;
 export type RevealPkProps = RevealPkMsgValue;
+export type ValidatorMaintenanceProps = ValidatorMaintenanceMsgValue;
+export type ValidatorMetadataProps = ValidatorMetadataMsgValue;
+
+/**
+ * Transfer of any kind, see Tx.buildTransfer
+ */
+export type TransferKindProps =
+  | { kind: "transparent"; props: TransparentTransferProps }
+  | { kind: "shielded"; props: ShieldedTransferProps }
+  | { kind: "shielding"; props: ShieldingTransferProps }
+  | { kind: "unshielding"; props: UnshieldingTransferProps };
 
 export type SupportedTxProps =
   | BondProps
@@ -62,12 +85,35 @@ export type SupportedTxProps =
   | TransferProps
   | RevealPkProps;
 
+/**
+ * Memo parsed along known conventions, see
+ * packages/shared/lib/src/sdk/memo.rs
+ */
+export type ParsedMemo = (
+  | { kind: "text"; text: string }
+  | { kind: "encrypted" }
+  | { kind: "ibcShielding"; shieldedOutputs: number }
+  | {
+      kind: "packetForward";
+      hops: { receiver: string; port: string; channel: string }[];
+    }
+  | { kind: "depositTag"; tag: string }
+) & {
+  // Short description for history views
+  label: string;
+};
+
 export type CommitmentDetailProps = SupportedTxProps & {
   txType: unknown;
   hash: string;
   memo?: string;
+  // Parsed memo of the tx, or else of the IBC packet
+  parsedMemo?: ParsedMemo;
+  fiatValue?: string;
 };
 
 export type TxDetails = WrapperTxProps & {
   commitments: CommitmentDetailProps[];
+  // Hash of the origin of the dApp requesting the signature
+  originHash?: string;
 };